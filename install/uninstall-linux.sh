@@ -14,6 +14,7 @@ PURGE_PATHS=(
   "${HOME}/.config/insight-reader"
   "${HOME}/.cache/insight-reader"
   "${HOME}/.local/share/insight-reader"
+  "${HOME}/.local/share/insight-reader-data"
   "${HOME}/.config/com.gabriel.insight-reader-2"
   "${HOME}/.cache/com.gabriel.insight-reader-2"
   "${HOME}/.local/share/com.gabriel.insight-reader-2"
@@ -0,0 +1,46 @@
+#!/usr/bin/env bash
+# Downloads the onboarding voice bundled with the app, and a Piper binary to speak it with, into
+# src-tauri/resources/onboarding-voice.
+# Keep VOICE in sync with ONBOARDING_VOICE in src-tauri/src/tts/piper.rs.
+set -euo pipefail
+
+VOICE="en_US-danny-low"
+VOICE_PATH="en/en_US/danny/low"
+BASE_URL="https://huggingface.co/rhasspy/piper-voices/resolve/main"
+PIPER_RELEASE="https://github.com/rhasspy/piper/releases/download/2023.11.14-2"
+DEST="$(cd "$(dirname "$0")/.." && pwd)/src-tauri/resources/onboarding-voice"
+
+mkdir -p "$DEST"
+for file in "$VOICE.onnx" "$VOICE.onnx.json"; do
+  if [ -s "$DEST/$file" ]; then
+    echo "$file already present"
+    continue
+  fi
+  echo "Downloading $file"
+  curl -fL --retry 3 -o "$DEST/$file.part" "$BASE_URL/$VOICE_PATH/$file"
+  mv "$DEST/$file.part" "$DEST/$file"
+done
+
+# Piper for this platform (binary, its libraries and espeak-ng data), extracted to $DEST/piper.
+case "$(uname -s)-$(uname -m)" in
+  Linux-x86_64) ARCHIVE="piper_linux_x86_64.tar.gz" ;;
+  Linux-aarch64) ARCHIVE="piper_linux_aarch64.tar.gz" ;;
+  Darwin-x86_64) ARCHIVE="piper_macos_x64.tar.gz" ;;
+  Darwin-arm64) ARCHIVE="piper_macos_aarch64.tar.gz" ;;
+  MINGW*|MSYS*|CYGWIN*) ARCHIVE="piper_windows_amd64.zip" ;;
+  *)
+    echo "No Piper build for $(uname -s)-$(uname -m); the onboarding voice needs in-process inference"
+    exit 0
+    ;;
+esac
+if [ -e "$DEST/piper/piper" ] || [ -e "$DEST/piper/piper.exe" ]; then
+  echo "Piper already present"
+  exit 0
+fi
+echo "Downloading $ARCHIVE"
+curl -fL --retry 3 -o "$DEST/$ARCHIVE.part" "$PIPER_RELEASE/$ARCHIVE"
+case "$ARCHIVE" in
+  *.zip) unzip -q "$DEST/$ARCHIVE.part" -d "$DEST" ;;
+  *) tar -xzf "$DEST/$ARCHIVE.part" -C "$DEST" ;;
+esac
+rm "$DEST/$ARCHIVE.part"
@@ -0,0 +1,71 @@
+package com.gabriel.insight_reader_2
+
+// Android half of the `speech` plugin (see src/tts/mobile.rs): renders text to a WAV file in the
+// app cache with the platform TextToSpeech engine. Copy into gen/android/app/src/main/java/.
+
+import android.app.Activity
+import android.speech.tts.TextToSpeech
+import android.speech.tts.UtteranceProgressListener
+import app.tauri.annotation.Command
+import app.tauri.annotation.InvokeArg
+import app.tauri.annotation.TauriPlugin
+import app.tauri.plugin.Invoke
+import app.tauri.plugin.JSObject
+import app.tauri.plugin.Plugin
+import java.io.File
+import java.util.UUID
+import java.util.concurrent.ConcurrentHashMap
+
+@InvokeArg
+class SynthesizeArgs {
+    lateinit var text: String
+    var voice: String? = null
+}
+
+@TauriPlugin
+class SpeechPlugin(private val activity: Activity) : Plugin(activity) {
+    private val pending = ConcurrentHashMap<String, Pair<Invoke, File>>()
+
+    @Volatile
+    private var ready = false
+
+    private val tts = TextToSpeech(activity) { status -> ready = status == TextToSpeech.SUCCESS }
+
+    init {
+        tts.setOnUtteranceProgressListener(object : UtteranceProgressListener() {
+            override fun onStart(utteranceId: String) {}
+
+            override fun onDone(utteranceId: String) {
+                val (invoke, file) = pending.remove(utteranceId) ?: return
+                val result = JSObject()
+                result.put("path", file.absolutePath)
+                invoke.resolve(result)
+            }
+
+            @Deprecated("Deprecated in Java")
+            override fun onError(utteranceId: String) {
+                val (invoke, file) = pending.remove(utteranceId) ?: return
+                file.delete()
+                invoke.reject("Speech synthesis failed")
+            }
+        })
+    }
+
+    @Command
+    fun synthesize(invoke: Invoke) {
+        val args = invoke.parseArgs(SynthesizeArgs::class.java)
+        if (!ready) {
+            invoke.reject("The speech engine is not ready")
+            return
+        }
+        args.voice?.let { name -> tts.voices?.firstOrNull { it.name == name }?.let { tts.voice = it } }
+        val id = UUID.randomUUID().toString()
+        val file = File.createTempFile("speech-", ".wav", activity.cacheDir)
+        pending[id] = invoke to file
+        if (tts.synthesizeToFile(args.text, null, file, id) != TextToSpeech.SUCCESS) {
+            pending.remove(id)
+            file.delete()
+            invoke.reject("Speech synthesis failed")
+        }
+    }
+}
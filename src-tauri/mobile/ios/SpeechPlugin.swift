@@ -0,0 +1,57 @@
+// iOS half of the `speech` plugin (see src/tts/mobile.rs): renders text to a WAV file in the
+// temporary directory with AVSpeechSynthesizer. Add to the app target in gen/apple.
+
+import AVFoundation
+import Tauri
+
+class SynthesizeArgs: Decodable {
+  let text: String
+  let voice: String?
+}
+
+class SpeechPlugin: Plugin {
+  private let synthesizer = AVSpeechSynthesizer()
+
+  @objc public func synthesize(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(SynthesizeArgs.self)
+    let utterance = AVSpeechUtterance(string: args.text)
+    if let voice = args.voice {
+      utterance.voice = AVSpeechSynthesisVoice(identifier: voice)
+    }
+    let url = FileManager.default.temporaryDirectory
+      .appendingPathComponent("speech-\(UUID().uuidString).wav")
+    var file: AVAudioFile?
+    var failure: Error?
+    synthesizer.write(utterance) { buffer in
+      guard let pcm = buffer as? AVAudioPCMBuffer else { return }
+      // An empty buffer ends the utterance; dropping the file closes it.
+      if pcm.frameLength == 0 {
+        let written = file != nil
+        file = nil
+        if let failure = failure {
+          invoke.reject(failure.localizedDescription)
+        } else if !written {
+          invoke.reject("Speech synthesis produced no audio")
+        } else {
+          invoke.resolve(["path": url.path])
+        }
+        return
+      }
+      do {
+        if file == nil {
+          file = try AVAudioFile(
+            forWriting: url, settings: pcm.format.settings,
+            commonFormat: pcm.format.commonFormat, interleaved: pcm.format.isInterleaved)
+        }
+        try file?.write(from: pcm)
+      } catch {
+        failure = error
+      }
+    }
+  }
+}
+
+@_cdecl("init_plugin_speech")
+func initPlugin() -> Plugin {
+  return SpeechPlugin()
+}
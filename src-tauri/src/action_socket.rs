@@ -1,24 +1,55 @@
-//! Unix domain socket used for single-instance action dispatch.
+//! Unix domain socket (a loopback TCP bridge on Windows) used for single-instance action dispatch.
 //!
 //! When a second process is started (e.g. `insight-reader action read-selected`), it tries to
 //! connect to a running instance via this socket and send an action string instead of starting
 //! a new app. The path is chosen in order: `XDG_RUNTIME_DIR`, then `/run/user/{uid}`, then
-//! `/tmp/insight-reader-{uid}.sock`. On non-Unix platforms the socket is not used; `main.rs`
-//! still calls `send_action_to_running_instance` and falls back to setting
-//! `INSIGHT_READER_START_ACTION` for the next run.
+//! `/tmp/insight-reader-{uid}.sock`. On Windows the running instance listens on a loopback port
+//! instead; the port and a random token are written to `action-bridge.json` in the app data dir,
+//! and each connection must send the token as its first line. When no instance answers,
+//! `main.rs` falls back to setting `INSIGHT_READER_START_ACTION` for the next run.
 //!
-//! The listener runs in a background thread; each incoming connection sends a single action
-//! string (e.g. "read-selected") which is parsed and executed via the actions module.
+//! The listener runs in a background thread; each incoming connection sends one line: either a
+//! single action string (e.g. "read-selected"), which is parsed and executed via the actions
+//! module, or a JSON command answered with one line of JSON on the same connection. At most
+//! `MAX_CONNECTIONS` are handled at once (more are closed), and a connection that sends nothing
+//! for `READ_TIMEOUT` is dropped:
+//!
+//! - `{"cmd":"speak","text":"..."}` reads the text aloud; answered once playback has started.
+//! - `{"cmd":"status"}` answers with `playing`, `paused`, `position_ms` and `duration_ms`.
+//! - `{"cmd":"action","action":"pause"}` runs an action, like the plain form.
+//!
+//! Every answer has `ok`, and `error` when the command failed. For example:
+//! `echo '{"cmd":"status"}' | socat - UNIX-CONNECT:$XDG_RUNTIME_DIR/insight-reader.sock`.
 
-#[cfg(unix)]
-use std::io::{Read, Write};
+use std::io::{BufRead, BufReader, Write};
+#[cfg(windows)]
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
 #[cfg(unix)]
 use std::os::unix::net::{UnixListener, UnixStream};
-#[cfg(unix)]
 use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
 
+#[cfg(windows)]
+use nanoid::nanoid;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
 use tracing::warn;
 
+use crate::dispatch::Limiter;
+#[cfg(windows)]
+use crate::paths;
+#[cfg(windows)]
+use crate::util;
+use crate::{actions, history, tts};
+
+/// Connections handled at once, each on its own thread (speak waits for synthesis to start).
+const MAX_CONNECTIONS: usize = 8;
+/// How long a connection may take to send its request line.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+static CONNECTIONS: Limiter = Limiter::new("action socket", MAX_CONNECTIONS);
+
 // --- Path selection (Unix) ---
 
 /// Returns the path where the action socket is bound.
@@ -46,8 +77,8 @@ pub fn action_socket_path() -> PathBuf {
 }
 
 #[cfg(not(unix))]
-pub fn action_socket_path() -> std::path::PathBuf {
-    std::path::PathBuf::from("insight-reader.sock")
+pub fn action_socket_path() -> PathBuf {
+    PathBuf::from("insight-reader.sock")
 }
 
 // --- Sending action to running instance (used by main.rs) ---
@@ -84,16 +115,29 @@ pub fn send_action_to_running_instance(action: &str) -> Result<(), String> {
     Err("could not connect to a running instance action socket".to_string())
 }
 
-#[cfg(not(unix))]
+#[cfg(windows)]
+pub fn send_action_to_running_instance(action: &str) -> Result<(), String> {
+    let info =
+        read_bridge_info().ok_or_else(|| "no running instance action bridge found".to_string())?;
+    let mut stream = connect_bridge(&info)
+        .map_err(|e| format!("could not connect to a running instance action bridge: {e}"))?;
+    stream
+        .write_all(format!("{}\n{}\n", info.token, action.trim()).as_bytes())
+        .map_err(|e| format!("failed to send action to running instance: {e}"))
+}
+
+#[cfg(not(any(unix, windows)))]
 pub fn send_action_to_running_instance(_action: &str) -> Result<(), String> {
     Err("action bridge is not supported on this platform".to_string())
 }
 
-// --- Listener (Unix only): bound in setup, dispatches to actions ---
+// --- Listener: bound in setup, dispatches to actions ---
 
-/// Starts a background thread that binds the action socket and dispatches incoming actions.
-/// Called from lib's setup. On Unix only.
+/// Starts a background thread that binds the action socket (the loopback bridge on Windows) and
+/// dispatches incoming actions. Called from lib's setup.
 pub fn start_action_socket_listener<R: tauri::Runtime>(app: tauri::AppHandle<R>) {
+    #[cfg(windows)]
+    start_bridge_listener(app);
     #[cfg(unix)]
     {
         let path = action_socket_path();
@@ -126,26 +170,338 @@ pub fn start_action_socket_listener<R: tauri::Runtime>(app: tauri::AppHandle<R>)
             };
 
             for stream_result in listener.incoming() {
-                let mut stream = match stream_result {
+                let stream = match stream_result {
                     Ok(stream) => stream,
                     Err(e) => {
                         warn!(error = %e, "Action socket accept failed");
                         continue;
                     }
                 };
-
-                let mut payload = String::new();
-                if let Err(e) = stream.read_to_string(&mut payload) {
-                    warn!(error = %e, "Action socket read failed");
+                let Some(permit) = CONNECTIONS.try_acquire() else {
+                    continue;
+                };
+                if let Err(e) = stream.set_read_timeout(Some(READ_TIMEOUT)) {
+                    warn!(error = %e, "Action socket connection dropped");
                     continue;
                 }
+                // A speak request waits for playback; other connections need not.
+                let app = app.clone();
+                std::thread::spawn(move || {
+                    let _permit = permit;
+                    handle_connection(&app, &mut BufReader::new(&stream), &stream)
+                });
+            }
+        });
+    }
+}
+
+// --- Loopback bridge (Windows): port and token in the app data dir ---
 
-                let action_raw = payload.trim();
-                match crate::hotkeys::parse_app_action(action_raw) {
-                    Some(action) => crate::actions::execute_action(&app, action, "socket"),
-                    None => warn!(action = %action_raw, "Unknown action command"),
+#[cfg(windows)]
+const BRIDGE_FILE_NAME: &str = "action-bridge.json";
+#[cfg(windows)]
+const BRIDGE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Where the running instance listens, and the token a connection must send first. The file
+/// lives in the per-user app data dir, so other local users cannot read the token.
+#[cfg(windows)]
+#[derive(Debug, Serialize, Deserialize)]
+struct BridgeInfo {
+    port: u16,
+    token: String,
+}
+
+#[cfg(windows)]
+fn bridge_file() -> Result<PathBuf, String> {
+    Ok(paths::get_app_data_dir()?.join(BRIDGE_FILE_NAME))
+}
+
+#[cfg(windows)]
+fn read_bridge_info() -> Option<BridgeInfo> {
+    let data = std::fs::read_to_string(bridge_file().ok()?).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+#[cfg(windows)]
+fn write_bridge_info(info: &BridgeInfo) -> Result<(), String> {
+    let path = bridge_file()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let data = serde_json::to_string(info).map_err(|e| e.to_string())?;
+    std::fs::write(&path, data).map_err(|e| e.to_string())
+}
+
+#[cfg(windows)]
+fn connect_bridge(info: &BridgeInfo) -> std::io::Result<TcpStream> {
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, info.port));
+    TcpStream::connect_timeout(&addr, BRIDGE_TIMEOUT)
+}
+
+/// Whether an instance answers a status request on the bridge in the file. A stale file may name
+/// a port another program now uses; only a JSON answer counts.
+#[cfg(windows)]
+fn bridge_answers(info: &BridgeInfo) -> bool {
+    let Ok(stream) = connect_bridge(info) else {
+        return false;
+    };
+    let _ = stream.set_read_timeout(Some(BRIDGE_TIMEOUT));
+    if write!(&stream, "{}\n{{\"cmd\":\"status\"}}\n", info.token).is_err() {
+        return false;
+    }
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line).is_ok() && line.starts_with('{')
+}
+
+#[cfg(windows)]
+fn start_bridge_listener<R: tauri::Runtime>(app: tauri::AppHandle<R>) {
+    std::thread::spawn(move || {
+        if read_bridge_info().is_some_and(|info| bridge_answers(&info)) {
+            warn!("Action bridge already in use by another instance");
+            return;
+        }
+        let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, 0)) {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!(error = %e, "Failed to bind action bridge");
+                return;
+            }
+        };
+        let info = match listener.local_addr() {
+            Ok(addr) => BridgeInfo {
+                port: addr.port(),
+                token: nanoid!(32),
+            },
+            Err(e) => {
+                warn!(error = %e, "Failed to read action bridge address");
+                return;
+            }
+        };
+        if let Err(e) = write_bridge_info(&info) {
+            warn!(error = %e, "Failed to write action bridge file");
+            return;
+        }
+
+        for stream_result in listener.incoming() {
+            let stream = match stream_result {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!(error = %e, "Action bridge accept failed");
+                    continue;
                 }
+            };
+            let Some(permit) = CONNECTIONS.try_acquire() else {
+                continue;
+            };
+            if let Err(e) = stream.set_read_timeout(Some(READ_TIMEOUT)) {
+                warn!(error = %e, "Action bridge connection dropped");
+                continue;
             }
+            let app = app.clone();
+            let token = info.token.clone();
+            std::thread::spawn(move || {
+                let _permit = permit;
+                let mut reader = BufReader::new(&stream);
+                let mut sent = String::new();
+                if reader.read_line(&mut sent).is_err()
+                    || !util::constant_time_eq(sent.trim().as_bytes(), token.as_bytes())
+                {
+                    warn!("Action bridge connection rejected: wrong token");
+                    return;
+                }
+                handle_connection(&app, &mut reader, &stream);
+            });
+        }
+    });
+}
+
+// --- Requests: plain action words or JSON commands ---
+
+/// A request: the first line of a connection. The HTTP API (see `http_api`) maps its endpoints
+/// to these too.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "cmd", rename_all = "lowercase")]
+pub(crate) enum SocketRequest {
+    Speak { text: String },
+    Status,
+    Action { action: String },
+}
+
+/// The answer to a JSON request.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct SocketResponse {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    playing: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    paused: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    position_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_ms: Option<u64>,
+}
+
+impl SocketResponse {
+    pub(crate) fn from_result(result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => Self {
+                ok: true,
+                ..Self::default()
+            },
+            Err(e) => Self {
+                error: Some(e),
+                ..Self::default()
+            },
+        }
+    }
+}
+
+/// Parses a request line: a JSON command, or a plain action word.
+fn parse_request(line: &str) -> Result<SocketRequest, String> {
+    if line.starts_with('{') {
+        serde_json::from_str(line).map_err(|e| format!("Invalid request: {e}"))
+    } else {
+        Ok(SocketRequest::Action {
+            action: line.to_string(),
+        })
+    }
+}
+
+fn handle_connection<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    reader: &mut impl BufRead,
+    mut writer: impl Write,
+) {
+    let mut line = String::new();
+    if let Err(e) = reader.read_line(&mut line) {
+        warn!(error = %e, "Action socket read failed");
+        return;
+    }
+    let line = line.trim();
+    let response = match parse_request(line) {
+        Ok(request) => handle_request(app, request, "socket"),
+        Err(e) => {
+            warn!(error = %e, "Action socket request rejected");
+            SocketResponse::from_result(Err(e))
+        }
+    };
+    // Plain action words are one-way.
+    if !line.starts_with('{') {
+        return;
+    }
+    let written = serde_json::to_string(&response)
+        .map_err(|e| e.to_string())
+        .and_then(|payload| writeln!(writer, "{payload}").map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        warn!(error = %e, "Action socket write failed");
+    }
+}
+
+/// Runs `request`; `source` names the caller in logs and the reading history.
+pub(crate) fn handle_request<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    request: SocketRequest,
+    source: &'static str,
+) -> SocketResponse {
+    let tts_tx = || {
+        app.try_state::<tts::TtsState>()
+            .map(|state| state.inner().clone())
+            .ok_or_else(|| "TtsState not found".to_string())
+    };
+    match request {
+        SocketRequest::Action { action } => match actions::parse_app_action(&action) {
+            Some(parsed) => {
+                actions::execute_action(app, parsed, source);
+                SocketResponse::from_result(Ok(()))
+            }
+            None => {
+                warn!(action = %action, "Unknown action command");
+                SocketResponse::from_result(Err(format!("Unknown action: {action}")))
+            }
+        },
+        SocketRequest::Speak { text } => {
+            SocketResponse::from_result(tts_tx().and_then(|tx| speak(&tx, text, source)))
+        }
+        SocketRequest::Status => match tts_tx().and_then(|tx| playback_status(&tx)) {
+            Ok(((playing, paused), (position_ms, duration_ms))) => SocketResponse {
+                ok: true,
+                playing: Some(playing),
+                paused: Some(paused),
+                position_ms: Some(position_ms),
+                duration_ms: Some(duration_ms),
+                ..SocketResponse::default()
+            },
+            Err(e) => SocketResponse::from_result(Err(e)),
+        },
+    }
+}
+
+/// Sends Speak and waits until playback has started.
+fn speak(tts_tx: &tts::TtsState, text: String, source: &str) -> Result<(), String> {
+    history::begin(source, &text);
+    let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+    let result = tts_tx
+        .send(tts::TtsRequest::Speak(
+            text,
+            tts::InputKind::Text,
+            None,
+            resp_tx,
+        ))
+        .map_err(|e| format!("TTS channel: {e}"))
+        .and_then(|()| {
+            resp_rx
+                .recv()
+                .map_err(|_| "TTS worker disconnected".to_string())?
+                .map_err(|e| e.to_string())
         });
+    if result.is_err() {
+        history::abandon();
+    }
+    result
+}
+
+/// (playing, paused) and (position, duration) in milliseconds.
+fn playback_status(tts_tx: &tts::TtsState) -> Result<((bool, bool), (u64, u64)), String> {
+    let disconnected = || "TTS worker disconnected".to_string();
+    let status = tts::playback_status(tts_tx).ok_or_else(disconnected)?;
+    let (resp_tx, resp_rx) = mpsc::sync_channel(1);
+    tts_tx
+        .send(tts::TtsRequest::GetPosition(resp_tx))
+        .map_err(|e| format!("TTS channel: {e}"))?;
+    let position = resp_rx.recv().map_err(|_| disconnected())?;
+    Ok((status, position))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_json_commands_and_plain_action_words() {
+        assert_eq!(
+            parse_request(r#"{"cmd":"speak","text":"Hello"}"#),
+            Ok(SocketRequest::Speak {
+                text: "Hello".to_string()
+            })
+        );
+        assert_eq!(
+            parse_request(r#"{"cmd":"status"}"#),
+            Ok(SocketRequest::Status)
+        );
+        assert_eq!(
+            parse_request("read-selected"),
+            Ok(SocketRequest::Action {
+                action: "read-selected".to_string()
+            })
+        );
+        assert!(parse_request(r#"{"cmd":"speak"}"#).is_err());
+        assert!(parse_request(r#"{"cmd":"rewind"}"#).is_err());
+        let response = SocketResponse::from_result(Err("Unknown action: x".to_string()));
+        assert_eq!(
+            serde_json::to_string(&response).ok().as_deref(),
+            Some(r#"{"ok":false,"error":"Unknown action: x"}"#)
+        );
     }
 }
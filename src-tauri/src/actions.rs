@@ -1,28 +1,141 @@
-//! High-level execution of user-triggered actions: read selected text, toggle pause, stop.
+//! High-level execution of user-triggered actions: read selected text, read a screenshot, toggle
+//! pause, stop, summarize, explain or translate the selection, open the editor, toggle the skim
+//! speed.
 //!
 //! Invoked by the global hotkey handler, the tray menu, and the Unix action socket when the user
-//! requests "read", "pause", or "stop". Each action maps to TTS requests (speak, toggle pause, stop);
-//! "Read Selected" also pulls text from text_capture and sends it to the TTS worker. This module
-//! does not handle "Summarize Selected" or "Insight Editor" (those are tray-specific and use
-//! backend and windows from lib's setup).
+//! requests "read", "screenshot", "pause", "stop", "summarize", "editor", "skim", "explain" or
+//! "translate". Each playback action maps to TTS requests (speak, toggle pause, stop, toggle
+//! skim); "Read Selected" also pulls text from text_capture and sends it to the TTS worker, and
+//! "Read Screenshot" gets it from a captured screen region via `ocr`. "Summarize Selected" sends
+//! the selection to the backend on a background thread with a dedicated tokio runtime,
+//! registered as a cancellable background task; the summary (or the failure) opens in the
+//! editor; `summarize_text` does the same for text from elsewhere. "Explain Selected" does the
+//! same with the explain task for `explain_mode` (EXPLAIN1 or EXPLAIN2, see backend-api.md).
+//! "Translate Selected" reads the selection translated (see `translate`). "Insight Editor" opens
+//! the editor with the selection. The `summarize_and_read` command runs the summary pipeline for
+//! the frontend and reads the summary aloud instead.
+//!
+//! With the `playback_queue` feature flag, "Read Selected" while something plays queues the text
+//! instead of replacing the current read (see `tts::queue`). With `expand_urls` on, a selection
+//! that is just a URL reads the linked page's article instead (see `web_extract`).
+//!
+//! The reads, the translation, the summary and the explanation are single-flight: a trigger
+//! while the same action is still capturing or starting is ignored rather than starting a second
+//! thread (see `dispatch`).
 
 use std::sync::mpsc;
 
-use tauri::Manager;
-use tracing::{debug, warn};
+use tauri::{Manager, State};
+use tracing::{debug, error, warn};
 
-use crate::hotkeys;
+use crate::backend;
+use crate::commands_config::ConfigState;
+use crate::config;
+use crate::dispatch::Limiter;
+use crate::error::AppError;
+use crate::features;
+use crate::history;
+use crate::i18n::{self, SpokenText};
+use crate::latency;
+use crate::llm;
+use crate::ocr;
+use crate::tasks::{TaskKind, TaskManager};
 use crate::text_capture;
+use crate::translate;
 use crate::tts;
+use crate::web_extract;
+use crate::windows;
+
+/// Action that can be triggered by a hotkey, the tray or the action socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppAction {
+    ReadSelected,
+    TogglePause,
+    Stop,
+    ReadScreenshot,
+    Summarize,
+    OpenEditor,
+    /// Switches between the normal speed and `skim_speed`.
+    ToggleSkim,
+    ExplainSelected,
+    /// Translates the selection into `translate_language` and reads it (see `translate`).
+    TranslateSelected,
+}
+
+impl AppAction {
+    /// Every action, in the order they are listed in settings and hotkey status.
+    pub const ALL: [AppAction; 9] = [
+        AppAction::ReadSelected,
+        AppAction::TogglePause,
+        AppAction::Stop,
+        AppAction::ReadScreenshot,
+        AppAction::Summarize,
+        AppAction::OpenEditor,
+        AppAction::ToggleSkim,
+        AppAction::ExplainSelected,
+        AppAction::TranslateSelected,
+    ];
+
+    /// Canonical name, as accepted by `parse_app_action` and used as the `hotkeys` config key.
+    pub fn name(self) -> &'static str {
+        self.aliases()[0]
+    }
+
+    /// Names accepted by `parse_app_action` (socket, CLI, start action), canonical name first.
+    pub fn aliases(self) -> &'static [&'static str] {
+        match self {
+            AppAction::ReadSelected => &["read", "read-selected", "read_selected"],
+            AppAction::TogglePause => &["pause", "pause-toggle", "toggle-pause", "toggle_pause"],
+            AppAction::Stop => &["stop"],
+            AppAction::ReadScreenshot => &["screenshot", "read-screenshot", "read_screenshot"],
+            AppAction::Summarize => &["summarize", "summarize-selected", "summarize_selected"],
+            AppAction::OpenEditor => &["editor", "open-editor", "open_editor", "insight_editor"],
+            AppAction::ToggleSkim => &["skim", "toggle-skim", "toggle_skim"],
+            AppAction::ExplainSelected => &["explain", "explain-selected", "explain_selected"],
+            AppAction::TranslateSelected => {
+                &["translate", "translate-selected", "translate_selected"]
+            }
+        }
+    }
+
+    /// Display name, as in the tray menu.
+    pub fn label(self) -> &'static str {
+        match self {
+            AppAction::ReadSelected => "Read Selected",
+            AppAction::TogglePause => "Pause/Resume",
+            AppAction::Stop => "Stop",
+            AppAction::ReadScreenshot => "Read Screenshot",
+            AppAction::Summarize => "Summarize Selected",
+            AppAction::OpenEditor => "Insight Editor",
+            AppAction::ToggleSkim => "Skim Speed",
+            AppAction::ExplainSelected => "Explain Selected",
+            AppAction::TranslateSelected => "Translate Selected",
+        }
+    }
+}
+
+/// Parses an action string (e.g. from socket or INSIGHT_READER_START_ACTION) into AppAction.
+pub fn parse_app_action(raw: &str) -> Option<AppAction> {
+    let raw = raw.trim().to_lowercase();
+    AppAction::ALL
+        .into_iter()
+        .find(|action| action.aliases().contains(&raw.as_str()))
+}
+
+static READ_SELECTED: Limiter = Limiter::new("read-selected", 1);
+static READ_SCREENSHOT: Limiter = Limiter::new("read-screenshot", 1);
+static SUMMARIZE_SELECTED: Limiter = Limiter::new("summarize-selected", 1);
+static EXPLAIN_SELECTED: Limiter = Limiter::new("explain-selected", 1);
+static TRANSLATE_SELECTED: Limiter = Limiter::new("translate-selected", 1);
 
 /// Runs the given action using TtsState and text_capture. Called from hotkeys, tray, and action socket.
 pub fn execute_action<R: tauri::Runtime>(
     app: &tauri::AppHandle<R>,
-    action: hotkeys::AppAction,
+    action: AppAction,
     source: &'static str,
 ) {
     match action {
-        hotkeys::AppAction::ReadSelected => {
+        AppAction::ReadSelected => {
             let Some(tts_tx) = app
                 .try_state::<tts::TtsState>()
                 .map(|state| state.inner().clone())
@@ -30,33 +143,76 @@ pub fn execute_action<R: tauri::Runtime>(
                 warn!(source, "Read Selected: TtsState not found");
                 return;
             };
+            let expand_urls = app
+                .try_state::<ConfigState>()
+                .and_then(|state| state.lock().ok().and_then(|cfg| cfg.expand_urls))
+                .unwrap_or(false);
+            let Some(permit) = READ_SELECTED.try_acquire() else {
+                debug!(source, "Read Selected: already running, ignoring");
+                return;
+            };
 
             std::thread::spawn(move || {
+                let _permit = permit;
+                let read_id = latency::begin("selection");
+                let _span = latency::span(read_id).entered();
                 let text = text_capture::get_text_or_clipboard_impl();
                 if text.is_empty() {
                     warn!(source, "Read Selected: no text available");
                     return;
                 }
+                latency::captured();
                 text_capture::log_selected_text(&Some(text.clone()));
 
-                let (resp_tx, resp_rx) = mpsc::sync_channel(0);
-                if let Err(e) = tts_tx.send(tts::TtsRequest::Speak(text, resp_tx)) {
-                    warn!(source, error = %e, "Read Selected: failed to send speak request");
-                    return;
+                if let Some(url) = web_extract::as_url(&text).filter(|_| expand_urls) {
+                    match tauri::async_runtime::block_on(web_extract::fetch_public_article(&url)) {
+                        Ok(article) => {
+                            debug!(source, url = %url, "Read Selected: reading linked article");
+                            read_text(&tts_tx, article.spoken_text(), "web", source);
+                            return;
+                        }
+                        Err(e) => warn!(source, error = %e, "Read Selected: fetching URL failed"),
+                    }
                 }
+                read_text(&tts_tx, text, "selection", source);
+            });
+        }
+        AppAction::ReadScreenshot => {
+            if let Err(e) = ocr::check_available() {
+                warn!(source, error = %e, "Read Screenshot: not available");
+                return;
+            }
+            let Some(tts_tx) = app
+                .try_state::<tts::TtsState>()
+                .map(|state| state.inner().clone())
+            else {
+                warn!(source, "Read Screenshot: TtsState not found");
+                return;
+            };
+            let cfg = app
+                .try_state::<ConfigState>()
+                .and_then(|state| state.lock().ok().map(|cfg| cfg.clone()))
+                .unwrap_or_default();
+            let tasks = app
+                .try_state::<TaskManager>()
+                .map(|state| state.inner().clone())
+                .unwrap_or_default();
+            let Some(permit) = READ_SCREENSHOT.try_acquire() else {
+                debug!(source, "Read Screenshot: already running, ignoring");
+                return;
+            };
 
-                match resp_rx.recv() {
-                    Ok(Ok(())) => {}
-                    Ok(Err(e)) => {
-                        warn!(source, error = %e, "Read Selected: tts_speak failed");
-                    }
-                    Err(_) => {
-                        warn!(source, "Read Selected: TTS worker disconnected");
-                    }
+            let app = app.clone();
+            std::thread::spawn(move || {
+                let _permit = permit;
+                match ocr::read_screenshot_impl(&app, &tasks, &tts_tx, &cfg, None) {
+                    Ok(Some(_)) => {}
+                    Ok(None) => debug!(source, "Read Screenshot: selection cancelled"),
+                    Err(e) => warn!(source, error = %e, "Read Screenshot failed"),
                 }
             });
         }
-        hotkeys::AppAction::TogglePause => {
+        AppAction::TogglePause => {
             let Some(tts_tx) = app
                 .try_state::<tts::TtsState>()
                 .map(|state| state.inner().clone())
@@ -83,7 +239,7 @@ pub fn execute_action<R: tauri::Runtime>(
                 }
             }
         }
-        hotkeys::AppAction::Stop => {
+        AppAction::Stop => {
             if let Some(tts_tx) = app
                 .try_state::<tts::TtsState>()
                 .map(|state| state.inner().clone())
@@ -95,5 +251,483 @@ pub fn execute_action<R: tauri::Runtime>(
                 warn!(source, "Stop: TtsState not found");
             }
         }
+        AppAction::ToggleSkim => {
+            let Some(tts_tx) = app
+                .try_state::<tts::TtsState>()
+                .map(|state| state.inner().clone())
+            else {
+                warn!(source, "Toggle Skim: TtsState not found");
+                return;
+            };
+
+            let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+            if let Err(e) = tts_tx.send(tts::TtsRequest::ToggleSkim(resp_tx)) {
+                warn!(source, error = %e, "Toggle Skim: failed to send request");
+                return;
+            }
+
+            match resp_rx.recv() {
+                Ok(Ok(skimming)) => debug!(source, skimming, "Toggle Skim: updated speed"),
+                Ok(Err(e)) => warn!(source, error = %e, "Toggle Skim: request failed"),
+                Err(_) => warn!(source, "Toggle Skim: TTS worker disconnected"),
+            }
+        }
+        AppAction::Summarize => {
+            let Some(permit) = SUMMARIZE_SELECTED.try_acquire() else {
+                debug!(source, "Summarize Selected: already running, ignoring");
+                return;
+            };
+            let app = app.clone();
+            std::thread::spawn(move || {
+                let _permit = permit;
+                summarize_selected(&app);
+            });
+        }
+        AppAction::ExplainSelected => {
+            let Some(permit) = EXPLAIN_SELECTED.try_acquire() else {
+                debug!(source, "Explain Selected: already running, ignoring");
+                return;
+            };
+            let app = app.clone();
+            std::thread::spawn(move || {
+                let _permit = permit;
+                let text = text_capture::get_text_or_clipboard_impl();
+                if text.trim().is_empty() {
+                    warn!(source, "Explain Selected: no text available");
+                    return;
+                }
+                explain(&app, text, None);
+            });
+        }
+        AppAction::TranslateSelected => {
+            let Some(tts_tx) = app
+                .try_state::<tts::TtsState>()
+                .map(|state| state.inner().clone())
+            else {
+                warn!(source, "Translate Selected: TtsState not found");
+                return;
+            };
+            let Some(permit) = TRANSLATE_SELECTED.try_acquire() else {
+                debug!(source, "Translate Selected: already running, ignoring");
+                return;
+            };
+            let app = app.clone();
+            std::thread::spawn(move || {
+                let _permit = permit;
+                translate_selected(&app, &tts_tx, source);
+            });
+        }
+        AppAction::OpenEditor => {
+            let text = text_capture::get_text_or_clipboard_impl();
+            match app.try_state::<crate::EditorInitialText>() {
+                Some(state) => {
+                    if let Err(e) =
+                        windows::open_or_focus_editor_with_text(app, &state, text, false)
+                    {
+                        warn!(source, error = %e, "Insight Editor: open_editor_window failed");
+                    }
+                }
+                None => {
+                    warn!(source, "Insight Editor: EditorInitialText state not found");
+                }
+            }
+        }
+    }
+}
+
+/// Translates the selection into the configured language and reads the translation.
+fn translate_selected<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    tts_tx: &tts::TtsState,
+    source: &'static str,
+) {
+    let text = text_capture::get_text_or_clipboard_impl();
+    if text.trim().is_empty() {
+        warn!(source, "Translate Selected: no text available");
+        return;
+    }
+    let config = config::load_full_config().unwrap_or_default();
+    let target = translate::target_language(None, &config);
+    let rt = match tokio::runtime::Runtime::new() {
+        Ok(r) => r,
+        Err(e) => {
+            error!(error = %e, "Failed to create tokio runtime for translate");
+            return;
+        }
+    };
+    let result = rt.block_on(translate::translate(
+        text,
+        &target,
+        config.translate_url.as_deref(),
+    ));
+    match result {
+        Ok(translation) => {
+            if let Err(e) = translate::speak_translation(tts_tx, translation, &target) {
+                warn!(source, error = %e.message(), "Translate Selected: reading failed");
+            }
+        }
+        Err(e) => {
+            backend::notify_rate_limited(app, translate::TASK, &e);
+            warn!(source, error = %e, "Translate Selected: translation failed");
+        }
+    }
+}
+
+/// Reads `text` like "Read Selected": queued behind the current read with the `playback_queue`
+/// flag, spoken right away otherwise. `history_source` is the source in the reading history.
+/// Blocks until the TTS worker has taken the text.
+pub fn read_text(tts_tx: &tts::TtsState, text: String, history_source: &str, source: &'static str) {
+    if features::is_enabled(features::Flag::PlaybackQueue) {
+        let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+        let request = tts::TtsRequest::Enqueue(
+            text,
+            tts::InputKind::Text,
+            history_source.to_string(),
+            resp_tx,
+        );
+        if let Err(e) = tts_tx.send(request) {
+            warn!(source, error = %e, "Read: failed to send enqueue request");
+            return;
+        }
+        match resp_rx.recv() {
+            Ok(Ok(id)) => debug!(source, id, "Read: queued"),
+            Ok(Err(e)) => warn!(source, error = %e, "Read: enqueue failed"),
+            Err(_) => warn!(source, "Read: TTS worker disconnected"),
+        }
+        return;
+    }
+
+    history::begin(history_source, &text);
+    let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+    if let Err(e) = tts_tx.send(tts::TtsRequest::Speak(
+        text,
+        tts::InputKind::Text,
+        None,
+        resp_tx,
+    )) {
+        warn!(source, error = %e, "Read: failed to send speak request");
+        history::abandon();
+        return;
+    }
+
+    match resp_rx.recv() {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            warn!(source, error = %e, "Read: tts_speak failed");
+            history::abandon();
+        }
+        Err(_) => {
+            warn!(source, "Read: TTS worker disconnected");
+            history::abandon();
+        }
+    }
+}
+
+/// Summarizes `text` like "Summarize Selected", for text that did not come from the selection
+/// (e.g. a page sent over the HTTP API). Returns false when a summary is already running.
+pub fn summarize_text<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    text: String,
+    source: &'static str,
+) -> bool {
+    let Some(permit) = SUMMARIZE_SELECTED.try_acquire() else {
+        debug!(source, "Summarize: already running, ignoring");
+        return false;
+    };
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let _permit = permit;
+        summarize(&app, text);
+    });
+    true
+}
+
+fn summarize_selected<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let text = text_capture::get_text_or_clipboard_impl();
+    if text.trim().is_empty() {
+        warn!("Summarize Selected: no text available");
+        return;
+    }
+    summarize(app, text);
+}
+
+/// A backend task whose answer (or failure) opens in the editor: a summary or an explanation.
+struct EditorPrompt {
+    /// Backend task (see backend-api.md).
+    task: &'static str,
+    kind: TaskKind,
+    /// Background task label, also used in the logs.
+    label: &'static str,
+    failed: SpokenText,
+    start_failed: SpokenText,
+    /// The editor reads the answer aloud.
+    read_aloud: bool,
+}
+
+/// Sends `text` to the backend and opens the summary (or the failure) in the editor.
+fn summarize<R: tauri::Runtime>(app: &tauri::AppHandle<R>, text: String) {
+    let config = config::load_full_config().unwrap_or_default();
+    let summary_muted = config.summary_muted.unwrap_or(false);
+    let language = i18n::normalize_language(config.ui_language.as_deref());
+    let prompt = EditorPrompt {
+        task: if summary_muted {
+            "SUMMARIZE_PROMPT"
+        } else {
+            "SUMMARIZE_AND_READ_PROMPT"
+        },
+        kind: TaskKind::Summarize,
+        label: "Summarize Selected",
+        failed: SpokenText::SummaryFailed,
+        start_failed: SpokenText::SummaryStartFailed,
+        read_aloud: !summary_muted,
+    };
+    prompt_to_editor(app, &prompt, text, language);
+}
+
+/// Sends `text` to the explain task for `explain_mode` and opens the explanation (or the failure)
+/// in the editor, read aloud when `read_aloud` (default: unless `summary_muted`).
+fn explain<R: tauri::Runtime>(app: &tauri::AppHandle<R>, text: String, read_aloud: Option<bool>) {
+    let config = config::load_full_config().unwrap_or_default();
+    let language = i18n::normalize_language(config.ui_language.as_deref());
+    let prompt = EditorPrompt {
+        task: explain_task(config.explain_mode.as_deref()),
+        kind: TaskKind::Explain,
+        label: "Explain Selected",
+        failed: SpokenText::ExplanationFailed,
+        start_failed: SpokenText::ExplanationStartFailed,
+        read_aloud: read_aloud.unwrap_or(!config.summary_muted.unwrap_or(false)),
+    };
+    prompt_to_editor(app, &prompt, text, language);
+}
+
+/// Backend task for an `explain_mode` value: EXPLAIN2 (simpler) or EXPLAIN1, the default.
+fn explain_task(mode: Option<&str>) -> &'static str {
+    match mode.map(str::trim) {
+        Some(mode) if mode.eq_ignore_ascii_case("EXPLAIN2") => "EXPLAIN2",
+        _ => "EXPLAIN1",
+    }
+}
+
+/// Runs `prompt` on `text` as a cancellable background task and opens the answer (or the
+/// failure) in the editor.
+fn prompt_to_editor<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    prompt: &EditorPrompt,
+    text: String,
+    language: &str,
+) {
+    let label = prompt.label;
+    let task = prompt.task;
+    let rt = match tokio::runtime::Runtime::new() {
+        Ok(r) => r,
+        Err(e) => {
+            error!(error = %e, label, "Failed to create tokio runtime for the backend prompt");
+            if let Some(state) = app.try_state::<crate::EditorInitialText>() {
+                let msg = i18n::text(prompt.start_failed, language);
+                let _ =
+                    windows::open_or_focus_editor_with_text(app, &state, msg.to_string(), false);
+            }
+            return;
+        }
+    };
+
+    let tasks = app
+        .try_state::<TaskManager>()
+        .map(|state| state.inner().clone())
+        .unwrap_or_default();
+    let bg_task = tasks.start(app, prompt.kind, label);
+    let send = |text: String| {
+        let cancel = bg_task.token().clone();
+        async move {
+            tokio::select! {
+                result = llm::send_prompt(task.to_string(), text, None, None, None) => result,
+                _ = cancel.cancelled() => {
+                    Err(backend::BackendError::Other(format!("{label} cancelled")))
+                }
+            }
+        }
+    };
+
+    let mut result = rt.block_on(send(text.clone()));
+
+    // Rate limited: retry once after the server-provided delay (when it is short enough).
+    if let Err(err) = &result {
+        backend::notify_rate_limited(app, task, err);
+        if let Some(delay) = err.retry_delay().filter(|_| !bg_task.is_cancelled()) {
+            warn!(
+                delay_secs = delay.as_secs(),
+                label, "Backend prompt rate limited, retrying once"
+            );
+            std::thread::sleep(delay);
+            result = rt.block_on(send(text));
+        }
+    }
+
+    let cancelled = bg_task.is_cancelled();
+    bg_task.finish(&result);
+    let result = result.map_err(|e| e.to_string());
+    if cancelled {
+        return;
+    }
+
+    match result {
+        Ok(answer) => {
+            if let Some(state) = app.try_state::<crate::EditorInitialText>() {
+                if let Err(e) =
+                    windows::open_or_focus_editor_with_text(app, &state, answer, prompt.read_aloud)
+                {
+                    warn!(error = %e, label, "open_editor_window failed");
+                }
+            } else {
+                warn!(label, "EditorInitialText state not found");
+            }
+        }
+        Err(e) => {
+            if let Some(state) = app.try_state::<crate::EditorInitialText>() {
+                let _ = windows::open_or_focus_editor_with_text(
+                    app,
+                    &state,
+                    format!("{}: {}", i18n::text(prompt.failed, language), e),
+                    false,
+                );
+            } else {
+                warn!(error = %e, label, "backend_prompt failed");
+            }
+        }
+    }
+}
+
+/// Explains `text` (the selected or copied text when absent) like "Explain Selected": with the
+/// task for `explain_mode`, opened in the editor and read aloud when `read_aloud` (default: unless
+/// `summary_muted`). Returns false when an explanation is already running. Progress and failures
+/// are reported as a background task.
+#[tauri::command]
+pub fn explain_selected(
+    app: tauri::AppHandle,
+    text: Option<String>,
+    read_aloud: Option<bool>,
+) -> Result<bool, AppError> {
+    let Some(permit) = EXPLAIN_SELECTED.try_acquire() else {
+        debug!("Explain Selected: already running, ignoring");
+        return Ok(false);
+    };
+    let text = text
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(text_capture::get_text_or_clipboard_impl);
+    if text.trim().is_empty() {
+        return Err(AppError::NoText);
+    }
+    std::thread::spawn(move || {
+        let _permit = permit;
+        explain(&app, text, read_aloud);
+    });
+    Ok(true)
+}
+
+/// Summarizes `text` (the selected or copied text when absent) and reads the summary aloud unless
+/// `speak` is false (default: unless `summary_muted`). Returns the summary. Runs as a cancellable
+/// "Summarize" background task whose `task-updated` events report each step.
+#[tauri::command]
+pub async fn summarize_and_read(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    tts_state: State<'_, tts::TtsState>,
+    text: Option<String>,
+    speak: Option<bool>,
+) -> Result<String, AppError> {
+    let speak = speak.unwrap_or_else(|| {
+        !config::load_full_config()
+            .ok()
+            .and_then(|cfg| cfg.summary_muted)
+            .unwrap_or(false)
+    });
+    let mut bg_task = tasks.start(&app, TaskKind::Summarize, "Summarize and Read");
+    let result = summarize_and_read_steps(&app, &mut bg_task, &tts_state, text, speak).await;
+    bg_task.finish(&result);
+    result
+}
+
+async fn summarize_and_read_steps(
+    app: &tauri::AppHandle,
+    bg_task: &mut crate::tasks::TaskHandle,
+    tts_state: &tts::TtsState,
+    text: Option<String>,
+    speak: bool,
+) -> Result<String, AppError> {
+    let text = match text.filter(|t| !t.trim().is_empty()) {
+        Some(text) => text,
+        None => {
+            bg_task.set_progress(0.0, Some("Capturing text".to_string()));
+            tokio::task::spawn_blocking(text_capture::get_text_or_clipboard_impl)
+                .await
+                .map_err(|e| format!("spawn_blocking: {e}"))?
+        }
+    };
+    if text.trim().is_empty() {
+        return Err(AppError::NoText);
+    }
+
+    bg_task.set_progress(0.1, Some("Summarizing".to_string()));
+    let task = if speak {
+        "SUMMARIZE_AND_READ_PROMPT"
+    } else {
+        "SUMMARIZE_PROMPT"
+    };
+    let cancel = bg_task.token().clone();
+    let result = tokio::select! {
+        result = llm::send_prompt(task.to_string(), text, None, None, None) => result,
+        _ = cancel.cancelled() => {
+            Err(backend::BackendError::Other("Summary cancelled".to_string()))
+        }
+    };
+    if let Err(e) = &result {
+        backend::notify_rate_limited(app, task, e);
+    }
+    let summary = result?;
+    if !speak {
+        return Ok(summary);
+    }
+
+    bg_task.set_progress(0.9, Some("Reading the summary".to_string()));
+    let tts_tx = tts_state.clone();
+    let spoken = summary.clone();
+    tokio::task::spawn_blocking(move || {
+        history::begin("summary", &spoken);
+        let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+        let result = tts_tx
+            .send(tts::TtsRequest::Speak(
+                spoken,
+                tts::InputKind::Text,
+                None,
+                resp_tx,
+            ))
+            .map_err(|e| AppError::from(format!("TTS channel: {e}")))
+            .and_then(|()| {
+                resp_rx
+                    .recv()
+                    .map_err(|_| AppError::from("TTS worker disconnected"))?
+                    .map_err(AppError::from)
+            });
+        if result.is_err() {
+            history::abandon();
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking: {e}"))??;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_mode_picks_the_explain_task() {
+        assert_eq!(explain_task(Some("EXPLAIN2")), "EXPLAIN2");
+        assert_eq!(explain_task(Some(" explain2 ")), "EXPLAIN2");
+        assert_eq!(explain_task(Some("EXPLAIN1")), "EXPLAIN1");
+        assert_eq!(explain_task(Some("other")), "EXPLAIN1");
+        assert_eq!(explain_task(None), "EXPLAIN1");
     }
 }
@@ -0,0 +1,159 @@
+//! Version, build and environment details for the About dialog and bug reports.
+//!
+//! The git hash, build date and rustc version are recorded by `build.rs`. Features are the
+//! optional behaviours switched on in the current config, so a report shows what was active.
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::commands_config::ConfigState;
+use crate::config::FullConfig;
+use crate::error::AppError;
+use crate::paths;
+use crate::text::profanity::FilterMode;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppInfo {
+    pub version: &'static str,
+    pub git_hash: &'static str,
+    pub build_date: &'static str,
+    /// "debug" or "release".
+    pub build_profile: &'static str,
+    pub tauri_version: &'static str,
+    pub webview_version: Option<String>,
+    pub rustc_version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub os_version: Option<String>,
+    /// `XDG_SESSION_TYPE` (Linux).
+    pub session_type: Option<String>,
+    /// `XDG_CURRENT_DESKTOP` (Linux).
+    pub desktop: Option<String>,
+    pub portable: bool,
+    pub profile: Option<String>,
+    pub features: Vec<&'static str>,
+}
+
+fn active_features(cfg: &FullConfig) -> Vec<&'static str> {
+    let flags = [
+        ("playback-trace", cfg.playback_trace_enabled == Some(true)),
+        (
+            "profanity-filter",
+            FilterMode::from_config(cfg.profanity_filter.as_deref()).is_some(),
+        ),
+        ("ssml-generation", cfg.ssml_generation == Some(true)),
+        ("ocr-cleanup", cfg.ocr_cleanup != Some(false)),
+        (
+            "accelerated-inference",
+            cfg.inference_backend
+                .as_deref()
+                .is_some_and(|b| !b.is_empty() && b != "cpu"),
+        ),
+        (
+            "synthesis-priority",
+            cfg.synthesis_priority
+                .as_deref()
+                .is_some_and(|p| !p.is_empty()),
+        ),
+    ];
+    flags
+        .into_iter()
+        .filter_map(|(name, on)| on.then_some(name))
+        .collect()
+}
+
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+/// Distribution or OS release name, when the platform makes it cheap to get.
+fn os_version() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let release = std::fs::read_to_string("/etc/os-release").ok()?;
+        release.lines().find_map(|line| {
+            let value = line.strip_prefix("PRETTY_NAME=")?;
+            Some(value.trim_matches('"').to_string())
+        })
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("sw_vers")
+            .arg("-productVersion")
+            .output()
+            .ok()?;
+        let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (!version.is_empty()).then(|| format!("macOS {version}"))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        let output = std::process::Command::new("cmd")
+            .args(["/C", "ver"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()?;
+        let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (!version.is_empty()).then_some(version)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        None
+    }
+}
+
+pub fn app_info(cfg: &FullConfig) -> AppInfo {
+    let linux = cfg!(target_os = "linux");
+    AppInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        git_hash: env!("INSIGHT_READER_GIT_HASH"),
+        build_date: env!("INSIGHT_READER_BUILD_DATE"),
+        build_profile: if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        },
+        tauri_version: tauri::VERSION,
+        webview_version: tauri::webview_version().ok(),
+        rustc_version: env!("INSIGHT_READER_RUSTC_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        os_version: os_version(),
+        session_type: env_value("XDG_SESSION_TYPE").filter(|_| linux),
+        desktop: env_value("XDG_CURRENT_DESKTOP").filter(|_| linux),
+        portable: paths::is_portable(),
+        profile: paths::active_profile(),
+        features: active_features(cfg),
+    }
+}
+
+/// Returns version, build and environment details (About dialog, bug reports).
+#[tauri::command]
+pub fn get_app_info(state: State<'_, ConfigState>) -> Result<AppInfo, AppError> {
+    let cfg = state
+        .lock()
+        .map_err(|_| "Config lock poisoned".to_string())?
+        .clone();
+    Ok(app_info(&cfg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lists_features_enabled_in_config() {
+        let mut cfg = FullConfig::default();
+        assert_eq!(active_features(&cfg), ["ocr-cleanup"]);
+        cfg.ocr_cleanup = Some(false);
+        assert!(active_features(&cfg).is_empty());
+        cfg.profanity_filter = Some("mask".to_string());
+        cfg.inference_backend = Some("cpu".to_string());
+        cfg.ssml_generation = Some(true);
+        assert_eq!(
+            active_features(&cfg),
+            ["profanity-filter", "ssml-generation"]
+        );
+    }
+}
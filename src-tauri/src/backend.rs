@@ -1,16 +1,40 @@
 //! ReadingService HTTP API client.
 //!
-//! Calls the backend POST /api/prompt for tasks (SUMMARIZE, SUMMARIZE_PROMPT, SUMMARIZE_AND_READ_PROMPT, TTS, EXPLAIN1, EXPLAIN2, PROMPT).
-//! URL precedence: config.backend_url, then INSIGHT_READER_BACKEND_URL env, then default.
-//! See backend-api.md in the repo root for task semantics. Used by the frontend and by the
-//! tray "Summarize Selected" flow.
+//! Calls the backend POST /api/prompt for tasks (SUMMARIZE, SUMMARIZE_PROMPT,
+//! SUMMARIZE_AND_READ_PROMPT, TTS, EXPLAIN1, EXPLAIN2, TRANSLATE, PROMPT).
+//! URL precedence: config.backend_url, then INSIGHT_READER_BACKEND_URL env, then default
+//! (`text::cleanup` can send its task to `text_cleanup_url` instead).
+//! See backend-api.md in the repo root for task semantics. Used by the frontend and by the tray
+//! "Summarize Selected" flow. `backend_prompt` runs in the background: it returns a request id at
+//! once, ends with `backend-prompt-done`, and can be stopped with `backend_cancel` (closing the
+//! editor cancels the requests it started). `backend_prompt_stream` asks for the response as
+//! server-sent events and forwards it as it arrives (optionally reading each paragraph aloud). Also
+//! probes GET /health: the last result is cached for the status bar and a background monitor emits
+//! `backend-health-changed` when it changes. The commands run tasks on the provider chosen by
+//! `llm`, of which this backend is the default.
+//!
+//! Prompts that fail transiently (backend unreachable, HTTP 5xx) are retried with exponential
+//! backoff and jitter (`backend_retry_attempts`, `backend_retry_delay_ms`). After repeated failed
+//! calls a circuit breaker fails further calls to the configured backend at once for a while;
+//! health results report it as `circuit_open`. Responses are cached by `backend_cache`, which
+//! answers repeated prompts before any network call (so they also work offline).
 
-use std::sync::OnceLock;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use nanoid::nanoid;
+use tauri::Emitter;
+use tracing::{debug, warn};
 
+use crate::backend_cache;
 use crate::config;
+use crate::error::AppError;
+use crate::llm;
 use crate::machine_id;
+use crate::offline;
+use crate::tasks::{CancelToken, TaskStatus};
+use crate::tts;
+use crate::util::unix_millis_now;
 
 /// Default backend base URL when not set in config or env.
 const BACKEND_BASE_URL: &str = "https://api.insightreader.xyz";
@@ -30,6 +54,75 @@ fn backend_base_url() -> String {
         .to_string()
 }
 
+/// Interval between background health probes.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Timeout for a single health probe; kept short so the status bar reflects outages quickly.
+const HEALTH_CHECK_TIMEOUT_SECS: u64 = 5;
+
+/// Timeout for a prompt; LLM answers can take a while.
+const PROMPT_TIMEOUT_SECS: u64 = 120;
+
+/// Timeout for a streamed prompt, which stays open while the text arrives.
+const STREAM_TIMEOUT_SECS: u64 = 300;
+
+/// Retries after a transient failure when `backend_retry_attempts` is unset.
+const DEFAULT_RETRY_ATTEMPTS: u32 = 2;
+
+/// First backoff delay when `backend_retry_delay_ms` is unset; doubles with each retry.
+const DEFAULT_RETRY_DELAY_MS: u32 = 500;
+
+/// Upper bound on a single backoff delay.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Consecutive failed calls (after their retries) that open the circuit breaker.
+const CIRCUIT_FAILURE_THRESHOLD: u32 = 3;
+
+/// How long an open circuit fails calls at once before letting one through again.
+const CIRCUIT_OPEN_DURATION: Duration = Duration::from_secs(30);
+
+/// Wait used for an automatic retry when a 429 response has no usable Retry-After.
+const DEFAULT_RATE_LIMIT_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Longest Retry-After we honor automatically; longer waits are surfaced to the user instead.
+const MAX_AUTO_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Event emitted when the backend answers HTTP 429.
+pub const BACKEND_RATE_LIMITED_EVENT: &str = "backend-rate-limited";
+
+/// Events emitted by `backend_prompt_stream` (see `StreamChunk` and `StreamDone`).
+pub const BACKEND_STREAM_CHUNK_EVENT: &str = "backend-stream-chunk";
+pub const BACKEND_STREAM_DONE_EVENT: &str = "backend-stream-done";
+
+/// Event emitted when a `backend_prompt` request completes, fails or is cancelled.
+pub const BACKEND_PROMPT_DONE_EVENT: &str = "backend-prompt-done";
+
+/// Event emitted by the health monitor when reachability or status code changes.
+pub const BACKEND_HEALTH_CHANGED_EVENT: &str = "backend-health-changed";
+
+/// Result of a GET /health probe.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct BackendHealth {
+    pub reachable: bool,
+    pub status_code: Option<u16>,
+    pub latency_ms: Option<u64>,
+    /// Backend version, when the health response includes one.
+    pub version: Option<String>,
+    /// Unix timestamp (milliseconds) of the probe.
+    pub checked_at: u64,
+    /// Recent prompts kept failing, so calls fail at once until the backend recovers.
+    pub circuit_open: bool,
+}
+
+/// Last health probe result, shared by the command and the background monitor.
+static LAST_HEALTH: Mutex<Option<BackendHealth>> = Mutex::new(None);
+
+/// `backend_prompt` requests still running, so `backend_cancel` can stop them.
+static IN_FLIGHT: Mutex<Vec<InFlight>> = Mutex::new(Vec::new());
+
+/// Circuit breaker for prompts to the configured backend.
+static CIRCUIT: Mutex<CircuitBreaker> = Mutex::new(CircuitBreaker::new());
+
 /// Session ID: generated once per app launch, kept in memory only. Sent with backend requests.
 static SESSION_ID: OnceLock<String> = OnceLock::new();
 
@@ -54,7 +147,7 @@ fn user_agent() -> String {
     )
 }
 
-fn make_client(timeout_secs: u64) -> Result<reqwest::Client, String> {
+pub(crate) fn make_client(timeout_secs: u64) -> Result<reqwest::Client, String> {
     reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(timeout_secs))
         .user_agent(user_agent())
@@ -62,40 +155,311 @@ fn make_client(timeout_secs: u64) -> Result<reqwest::Client, String> {
         .map_err(|e| format!("HTTP client: {}", e))
 }
 
+/// Error from a backend prompt call. Rate limiting is kept distinct so callers can retry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackendError {
+    /// HTTP 429. `retry_after` comes from the Retry-After header when the server sends one.
+    RateLimited {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    /// Any other failure (unreachable, HTTP error, invalid response); already user-facing.
+    Other(String),
+}
+
+impl BackendError {
+    /// Delay before an automatic retry, or None when the server asked us to wait too long
+    /// (or the error is not a rate limit).
+    pub fn retry_delay(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after, .. } => {
+                let delay = retry_after.unwrap_or(DEFAULT_RATE_LIMIT_RETRY_DELAY);
+                (delay <= MAX_AUTO_RETRY_DELAY).then_some(delay)
+            }
+            Self::Other(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for BackendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RateLimited {
+                retry_after: Some(delay),
+                ..
+            } => write!(
+                f,
+                "The backend is receiving too many requests. Try again in {} seconds.",
+                delay.as_secs().max(1)
+            ),
+            Self::RateLimited {
+                retry_after: None, ..
+            } => write!(
+                f,
+                "The backend is receiving too many requests. Try again shortly."
+            ),
+            Self::Other(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+/// Payload of the `backend-rate-limited` event.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RateLimitInfo {
+    pub task: String,
+    pub retry_after_secs: Option<u64>,
+    pub message: String,
+}
+
+/// Parses a Retry-After header given in delta-seconds. HTTP-date values are treated as absent.
+pub(crate) fn parse_retry_after(value: Option<&reqwest::header::HeaderValue>) -> Option<Duration> {
+    value
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// Emits `backend-rate-limited` when `err` is a rate limit. No-op for other errors.
+pub fn notify_rate_limited<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    task: &str,
+    err: &BackendError,
+) {
+    if let BackendError::RateLimited {
+        message,
+        retry_after,
+    } = err
+    {
+        warn!(task, retry_after = ?retry_after, "Backend rate limited request");
+        let _ = app.emit(
+            BACKEND_RATE_LIMITED_EVENT,
+            RateLimitInfo {
+                task: task.to_string(),
+                retry_after_secs: retry_after.map(|d| d.as_secs()),
+                message: message.clone(),
+            },
+        );
+    }
+}
+
 /// Calls the ReadingService backend POST /api/prompt. Returns the response string on success.
-/// Async so the command does not block the app; long-running HTTP runs on the async runtime.
-#[tauri::command]
-pub async fn backend_prompt(
+pub async fn send_prompt(
     task: String,
     content: String,
     tone: Option<String>,
     format: Option<String>,
     instruction: Option<String>,
-) -> Result<String, String> {
-    let base = backend_base_url();
-    let url = format!("{}/api/prompt", base);
+) -> Result<String, BackendError> {
+    send_prompt_to(None, task, content, tone, format, instruction).await
+}
 
-    #[derive(serde::Serialize)]
-    struct Request {
-        task: String,
-        content: String,
-        #[serde(skip_serializing_if = "Option::is_none")]
-        tone: Option<String>,
-        #[serde(skip_serializing_if = "Option::is_none")]
-        format: Option<String>,
-        #[serde(skip_serializing_if = "Option::is_none")]
-        instruction: Option<String>,
+/// Like `send_prompt`, but to `base_url` when set (e.g. `text_cleanup_url`) instead of the
+/// configured backend.
+pub async fn send_prompt_to(
+    base_url: Option<&str>,
+    task: String,
+    content: String,
+    tone: Option<String>,
+    format: Option<String>,
+    instruction: Option<String>,
+) -> Result<String, BackendError> {
+    let request = PromptRequest {
+        task,
+        content,
+        tone,
+        format,
+        instruction,
+        stream: false,
+    };
+    let cache_key = request.cache_key();
+    if let Some(response) = backend_cache::lookup(&cache_key) {
+        debug!(task = %request.task, "Prompt answered from the cache");
+        return Ok(response);
     }
-    #[derive(serde::Deserialize)]
-    struct SuccessResponse {
-        response: String,
+    offline::ensure_online().map_err(BackendError::Other)?;
+    let resp = post_prompt(base_url, &request, PROMPT_TIMEOUT_SECS).await?;
+    let body = resp
+        .text()
+        .await
+        .map_err(|e| BackendError::Other(format!("Failed to read response: {}", e)))?;
+    let response = parse_prompt_response(&body)?;
+    backend_cache::store(&cache_key, &request.task, &request.content, &response);
+    Ok(response)
+}
+
+/// Like `send_prompt`, but asks the backend to stream the response and calls `on_delta` with
+/// each piece as it arrives. The stream is server-sent events whose data is `{"delta": "..."}`,
+/// ending with `[DONE]` (an `error` event carries `{"error": "..."}`); a backend that answers
+/// with plain JSON instead yields its whole response as one piece. Returns the full response.
+pub async fn stream_prompt(
+    task: String,
+    content: String,
+    tone: Option<String>,
+    format: Option<String>,
+    instruction: Option<String>,
+    mut on_delta: impl FnMut(&str),
+) -> Result<String, BackendError> {
+    let request = PromptRequest {
+        task,
+        content,
+        tone,
+        format,
+        instruction,
+        stream: true,
+    };
+    let cache_key = request.cache_key();
+    if let Some(response) = backend_cache::lookup(&cache_key) {
+        debug!(task = %request.task, "Prompt answered from the cache");
+        on_delta(&response);
+        return Ok(response);
     }
-    #[derive(serde::Deserialize)]
-    struct ErrorResponse {
-        error: Option<String>,
+    offline::ensure_online().map_err(BackendError::Other)?;
+    let response = read_prompt_stream(&request, &mut on_delta).await?;
+    backend_cache::store(&cache_key, &request.task, &request.content, &response);
+    Ok(response)
+}
+
+/// Sends the streaming `request` and reads its response (see `stream_prompt`).
+async fn read_prompt_stream(
+    request: &PromptRequest,
+    on_delta: &mut impl FnMut(&str),
+) -> Result<String, BackendError> {
+    let mut resp = post_prompt(None, request, STREAM_TIMEOUT_SECS).await?;
+    let is_event_stream = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    if !is_event_stream {
+        let body = resp
+            .text()
+            .await
+            .map_err(|e| BackendError::Other(format!("Failed to read response: {}", e)))?;
+        let response = parse_prompt_response(&body)?;
+        on_delta(&response);
+        return Ok(response);
+    }
+
+    let mut events = EventStream::default();
+    let mut response = String::new();
+    while let Some(bytes) = resp
+        .chunk()
+        .await
+        .map_err(|e| BackendError::Other(format!("Failed to read response: {}", e)))?
+    {
+        for event in events.push(&bytes) {
+            match event {
+                StreamEvent::Delta(delta) => {
+                    on_delta(&delta);
+                    response.push_str(&delta);
+                }
+                StreamEvent::Error(message) => return Err(BackendError::Other(message)),
+                StreamEvent::Done => return Ok(response),
+            }
+        }
+    }
+    debug!(len = response.len(), "Prompt stream ended without [DONE]");
+    Ok(response)
+}
+
+/// Body of POST /api/prompt.
+#[derive(serde::Serialize)]
+struct PromptRequest {
+    task: String,
+    content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instruction: Option<String>,
+    /// Asks for a `text/event-stream` response (see `stream_prompt`).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+impl PromptRequest {
+    fn cache_key(&self) -> String {
+        backend_cache::cache_key(
+            &self.task,
+            &self.content,
+            self.tone.as_deref(),
+            self.format.as_deref(),
+            self.instruction.as_deref(),
+        )
+    }
+}
+
+/// Sends `request` to `base_url` (else the configured backend) and returns the successful
+/// response. Transient failures are retried per `RetryPolicy`; calls to the configured backend
+/// also go through the circuit breaker.
+async fn post_prompt(
+    base_url: Option<&str>,
+    request: &PromptRequest,
+    timeout_secs: u64,
+) -> Result<reqwest::Response, BackendError> {
+    let custom = base_url
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty());
+    let guarded = custom.is_none();
+    let base = custom.unwrap_or_else(backend_base_url);
+    if guarded {
+        if let Err(remaining) = circuit_admit() {
+            return Err(BackendError::Other(format!(
+                "The backend is unavailable after repeated failures. Try again in {} seconds.",
+                remaining.as_secs().max(1)
+            )));
+        }
+    }
+
+    let policy = RetryPolicy::from_config();
+    let mut retry = 0;
+    loop {
+        match post_prompt_once(&base, request, timeout_secs).await {
+            Err(PostFailure::Transient(e)) if retry < policy.retries => {
+                retry += 1;
+                let delay = policy.delay(retry, jitter());
+                debug!(retry, delay_ms = delay.as_millis() as u64, error = %e, "Retrying prompt");
+                tokio::time::sleep(delay).await;
+            }
+            Err(PostFailure::Transient(e)) => {
+                if guarded {
+                    record_call(false);
+                }
+                return Err(e);
+            }
+            // The backend answered, so it is up even when it refused the request.
+            Err(PostFailure::Final(e)) => {
+                if guarded {
+                    record_call(true);
+                }
+                return Err(e);
+            }
+            Ok(resp) => {
+                if guarded {
+                    record_call(true);
+                }
+                return Ok(resp);
+            }
+        }
     }
+}
+
+/// A failed POST: transient failures (unreachable, HTTP 5xx) are worth retrying.
+enum PostFailure {
+    Transient(BackendError),
+    Final(BackendError),
+}
 
-    let client = make_client(120)?;
+/// One POST /api/prompt with the installation and session headers.
+async fn post_prompt_once(
+    base: &str,
+    request: &PromptRequest,
+    timeout_secs: u64,
+) -> Result<reqwest::Response, PostFailure> {
+    let url = format!("{}/api/prompt", base);
+    let client =
+        make_client(timeout_secs).map_err(|e| PostFailure::Final(BackendError::Other(e)))?;
 
     let install_id = config::get_or_create_installation_id().unwrap_or_default();
     let installation_header = installation_header_value(&install_id);
@@ -103,47 +467,665 @@ pub async fn backend_prompt(
         .post(&url)
         .header("X-Installation-ID", &installation_header)
         .header("X-Session-ID", get_session_id())
-        .json(&Request {
-            task,
-            content,
-            tone,
-            format,
-            instruction,
-        })
+        .json(request)
         .send()
         .await
         .map_err(|e| {
-            format!(
+            PostFailure::Transient(BackendError::Other(format!(
                 "Could not reach the backend at {}. Check Settings → General → Backend URL. \
                  Ensure the server is running and reachable. ({})",
                 base, e
-            )
+            )))
         })?;
-
     let status = resp.status();
-    let body = resp
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read response: {}", e))?;
-
     if status.is_success() {
-        let parsed: SuccessResponse =
-            serde_json::from_str(&body).map_err(|e| format!("Invalid response: {}", e))?;
-        Ok(parsed.response)
+        return Ok(resp);
+    }
+    let err = prompt_error(resp).await;
+    Err(if status.is_server_error() {
+        PostFailure::Transient(err)
+    } else {
+        PostFailure::Final(err)
+    })
+}
+
+/// How transient prompt failures are retried: `backend_retry_attempts` retries, the first after
+/// `backend_retry_delay_ms`, doubling each time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RetryPolicy {
+    retries: u32,
+    base_delay: Duration,
+}
+
+impl RetryPolicy {
+    fn from_config() -> Self {
+        let cfg = config::load_full_config().ok();
+        let cfg = cfg.as_ref();
+        Self {
+            retries: cfg
+                .and_then(|c| c.backend_retry_attempts)
+                .unwrap_or(DEFAULT_RETRY_ATTEMPTS),
+            base_delay: Duration::from_millis(u64::from(
+                cfg.and_then(|c| c.backend_retry_delay_ms)
+                    .unwrap_or(DEFAULT_RETRY_DELAY_MS),
+            )),
+        }
+    }
+
+    /// Delay before retry `retry` (1-based): the exponential delay, capped at `MAX_RETRY_DELAY`,
+    /// of which the second half is scaled by `jitter` (0..1) so clients do not retry in step.
+    fn delay(&self, retry: u32, jitter: f64) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(1 << retry.saturating_sub(1).min(16))
+            .min(MAX_RETRY_DELAY);
+        exponential.mul_f64(0.5 + 0.5 * jitter.clamp(0.0, 1.0))
+    }
+}
+
+/// A value in 0..1 from the clock's sub-second nanoseconds; random enough for backoff jitter.
+fn jitter() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    f64::from(nanos) / 1e9
+}
+
+/// Opens after `CIRCUIT_FAILURE_THRESHOLD` consecutive failed calls. Once
+/// `CIRCUIT_OPEN_DURATION` has passed it is half-open: one call goes through as a probe while the
+/// others keep failing. The probe's success closes the circuit, its failure reopens it; a probe
+/// that is never recorded (cancelled) lets another one through after `CIRCUIT_OPEN_DURATION`.
+#[derive(Debug)]
+struct CircuitBreaker {
+    failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    const fn new() -> Self {
+        Self {
+            failures: 0,
+            opened_at: None,
+        }
+    }
+
+    /// Time left before calls go through again; None when they go through now.
+    fn open_for(&self, now: Instant) -> Option<Duration> {
+        let opened_at = self.opened_at?;
+        CIRCUIT_OPEN_DURATION
+            .checked_sub(now.saturating_duration_since(opened_at))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Lets a call through, or returns the time left before one can go. Letting the probe of a
+    /// half-open circuit through holds the others back as if the circuit had just opened.
+    fn admit(&mut self, now: Instant) -> Result<(), Duration> {
+        if let Some(remaining) = self.open_for(now) {
+            return Err(remaining);
+        }
+        if self.opened_at.is_some() {
+            self.opened_at = Some(now);
+        }
+        Ok(())
+    }
+
+    fn record(&mut self, success: bool, now: Instant) {
+        if success {
+            *self = Self::new();
+            return;
+        }
+        self.failures += 1;
+        if self.failures >= CIRCUIT_FAILURE_THRESHOLD {
+            self.opened_at = Some(now);
+        }
+    }
+}
+
+fn circuit_open_for() -> Option<Duration> {
+    CIRCUIT
+        .lock()
+        .ok()
+        .and_then(|circuit| circuit.open_for(Instant::now()))
+}
+
+/// See `CircuitBreaker::admit`.
+fn circuit_admit() -> Result<(), Duration> {
+    let Ok(mut circuit) = CIRCUIT.lock() else {
+        return Ok(());
+    };
+    let half_open = circuit.opened_at.is_some();
+    circuit.admit(Instant::now())?;
+    if half_open {
+        debug!("Backend circuit half-open, letting one call through");
+    }
+    Ok(())
+}
+
+fn record_call(success: bool) {
+    if let Ok(mut circuit) = CIRCUIT.lock() {
+        let was_open = circuit.opened_at.is_some();
+        circuit.record(success, Instant::now());
+        match (was_open, circuit.opened_at.is_some()) {
+            (false, true) => warn!(
+                failures = circuit.failures,
+                "Backend circuit opened after repeated failures"
+            ),
+            (true, false) => debug!("Backend circuit closed"),
+            _ => {}
+        }
+    }
+}
+
+/// The `response` of a successful prompt.
+fn parse_prompt_response(body: &str) -> Result<String, BackendError> {
+    #[derive(serde::Deserialize)]
+    struct SuccessResponse {
+        response: String,
+    }
+    serde_json::from_str::<SuccessResponse>(body)
+        .map(|parsed| parsed.response)
+        .map_err(|e| BackendError::Other(format!("Invalid response: {}", e)))
+}
+
+/// The error for a prompt answered with a non-success status.
+async fn prompt_error(resp: reqwest::Response) -> BackendError {
+    #[derive(serde::Deserialize)]
+    struct ErrorResponse {
+        error: Option<String>,
+    }
+    let status = resp.status();
+    let retry_after = parse_retry_after(resp.headers().get(reqwest::header::RETRY_AFTER));
+    let body = match resp.text().await {
+        Ok(body) => body,
+        Err(e) => return BackendError::Other(format!("Failed to read response: {}", e)),
+    };
+    let err_msg = serde_json::from_str::<ErrorResponse>(&body)
+        .ok()
+        .and_then(|r| r.error)
+        .unwrap_or_else(|| format!("HTTP {}: {}", status, body));
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        BackendError::RateLimited {
+            message: err_msg,
+            retry_after,
+        }
     } else {
-        let err_msg = serde_json::from_str::<ErrorResponse>(&body)
-            .ok()
-            .and_then(|r| r.error)
-            .unwrap_or_else(|| format!("HTTP {}: {}", status, body));
-        Err(err_msg)
+        BackendError::Other(err_msg)
+    }
+}
+
+/// One event of a streamed prompt response.
+#[derive(Debug, PartialEq, Eq)]
+enum StreamEvent {
+    Delta(String),
+    Error(String),
+    Done,
+}
+
+/// Splits a `text/event-stream` body into `StreamEvent`s as its bytes arrive (an event may span
+/// several chunks, and a chunk may end inside a UTF-8 character).
+#[derive(Debug, Default)]
+struct EventStream {
+    buffer: Vec<u8>,
+}
+
+impl EventStream {
+    fn push(&mut self, bytes: &[u8]) -> Vec<StreamEvent> {
+        self.buffer.extend(bytes.iter().filter(|b| **b != b'\r'));
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let block: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            events.extend(parse_stream_event(&String::from_utf8_lossy(&block)));
+        }
+        events
+    }
+}
+
+/// An event block: `event:` and `data:` lines (comments and other fields are ignored).
+fn parse_stream_event(block: &str) -> Option<StreamEvent> {
+    #[derive(serde::Deserialize)]
+    struct Payload {
+        delta: Option<String>,
+        error: Option<String>,
+    }
+    let mut name = None;
+    let mut data = Vec::new();
+    for line in block.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            name = Some(value.trim());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+    let data = data.join("\n");
+    let payload = serde_json::from_str::<Payload>(&data).ok();
+    match (name, payload) {
+        (Some("done"), _) => Some(StreamEvent::Done),
+        _ if data == "[DONE]" => Some(StreamEvent::Done),
+        (Some("error"), payload) => Some(StreamEvent::Error(
+            payload.and_then(|p| p.error).unwrap_or(data),
+        )),
+        (_, Some(Payload { error: Some(e), .. })) => Some(StreamEvent::Error(e)),
+        (_, Some(Payload { delta, .. })) => delta.map(StreamEvent::Delta),
+        (_, None) if data.is_empty() => None,
+        // Plain text data.
+        (_, None) => Some(StreamEvent::Delta(data)),
+    }
+}
+
+/// A running `backend_prompt` request and the window that started it.
+struct InFlight {
+    id: String,
+    window: String,
+    token: CancelToken,
+}
+
+/// Payload of `backend-prompt-done`: the response, the error, or neither when cancelled.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PromptDone {
+    pub request_id: String,
+    pub status: TaskStatus,
+    pub response: Option<String>,
+    pub error: Option<AppError>,
+}
+
+/// Runs the task in the background on the configured LLM provider (the ReadingService backend
+/// unless `llm_provider` says otherwise, see `llm`) and returns the request id
+/// (`request_id`, generated when absent). `backend-prompt-done` carries the response or error;
+/// `backend_cancel` stops the request. On HTTP 429 also emits `backend-rate-limited` with the
+/// server's Retry-After.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn backend_prompt(
+    app: tauri::AppHandle,
+    window: tauri::Window,
+    task: String,
+    content: String,
+    tone: Option<String>,
+    format: Option<String>,
+    instruction: Option<String>,
+    request_id: Option<String>,
+) -> Result<String, AppError> {
+    let request_id = request_id.unwrap_or_else(|| nanoid!(8));
+    let token = CancelToken::default();
+    {
+        let mut in_flight = IN_FLIGHT
+            .lock()
+            .map_err(|_| AppError::from("Prompt registry lock poisoned"))?;
+        if in_flight.iter().any(|p| p.id == request_id) {
+            return Err(AppError::InvalidInput(format!(
+                "Request {request_id} is already running"
+            )));
+        }
+        in_flight.push(InFlight {
+            id: request_id.clone(),
+            window: window.label().to_string(),
+            token: token.clone(),
+        });
+    }
+
+    let id = request_id.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = tokio::select! {
+            result = llm::send_prompt(task.clone(), content, tone, format, instruction) => {
+                Some(result)
+            }
+            () = token.cancelled() => None,
+        };
+        if let Ok(mut in_flight) = IN_FLIGHT.lock() {
+            in_flight.retain(|p| p.id != id);
+        }
+        let (status, response, error) = match result {
+            Some(Ok(response)) => (TaskStatus::Completed, Some(response), None),
+            Some(Err(e)) => {
+                notify_rate_limited(&app, &task, &e);
+                (TaskStatus::Failed, None, Some(AppError::from(e)))
+            }
+            None => {
+                debug!(request_id = %id, task = %task, "Prompt cancelled");
+                (TaskStatus::Cancelled, None, None)
+            }
+        };
+        let _ = app.emit(
+            BACKEND_PROMPT_DONE_EVENT,
+            PromptDone {
+                request_id: id,
+                status,
+                response,
+                error,
+            },
+        );
+    });
+    Ok(request_id)
+}
+
+/// Cancels the `backend_prompt` request `request_id`. Returns false when it is not running.
+#[tauri::command]
+pub fn backend_cancel(request_id: String) -> Result<bool, AppError> {
+    Ok(cancel_prompts(|p| p.id == request_id) > 0)
+}
+
+/// Cancels the `backend_prompt` requests started by the window `label`; called when the editor
+/// is closed.
+pub fn cancel_window_prompts(label: &str) {
+    let cancelled = cancel_prompts(|p| p.window == label);
+    if cancelled > 0 {
+        debug!(window = label, cancelled, "Cancelled the window's prompts");
+    }
+}
+
+/// Signals the running requests matching `filter`; each ends with a cancelled
+/// `backend-prompt-done`. Returns how many were signalled.
+fn cancel_prompts(filter: impl Fn(&InFlight) -> bool) -> usize {
+    let Ok(in_flight) = IN_FLIGHT.lock() else {
+        return 0;
+    };
+    let mut cancelled = 0;
+    for request in in_flight.iter().filter(|p| filter(p)) {
+        request.token.cancel();
+        cancelled += 1;
+    }
+    cancelled
+}
+
+/// Payload of `backend-stream-chunk`: the next piece of the response to stream `stream_id`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StreamChunk {
+    pub stream_id: String,
+    pub delta: String,
+}
+
+/// Payload of `backend-stream-done`: the full response, or the error that ended the stream.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StreamDone {
+    pub stream_id: String,
+    pub response: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Like `backend_prompt`, but streams the response: each piece is emitted as
+/// `backend-stream-chunk` as it arrives and `backend-stream-done` ends the stream. `stream_id`
+/// (generated when absent) tags the events. With `read_aloud`, each paragraph is queued for
+/// reading as soon as it is complete, so speech starts while the rest streams in. Returns the
+/// full response.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn backend_prompt_stream(
+    app: tauri::AppHandle,
+    tts_state: tauri::State<'_, tts::TtsState>,
+    task: String,
+    content: String,
+    tone: Option<String>,
+    format: Option<String>,
+    instruction: Option<String>,
+    stream_id: Option<String>,
+    read_aloud: Option<bool>,
+) -> Result<String, AppError> {
+    let stream_id = stream_id.unwrap_or_else(|| nanoid!(8));
+    let tts_tx = read_aloud
+        .unwrap_or(false)
+        .then(|| tts_state.inner().clone());
+    let mut paragraphs = Paragraphs::default();
+    let read = |paragraph: String| {
+        if let Some(tx) = &tts_tx {
+            // The worker answers with the queue id; nobody waits for it.
+            let (resp_tx, _) = std::sync::mpsc::sync_channel(1);
+            let request = tts::TtsRequest::Enqueue(
+                paragraph,
+                tts::InputKind::Text,
+                "summary".to_string(),
+                resp_tx,
+            );
+            if let Err(e) = tx.send(request) {
+                warn!(error = %e, "Streamed prompt: could not queue a paragraph");
+            }
+        }
+    };
+
+    let result = llm::stream_prompt(task.clone(), content, tone, format, instruction, |delta| {
+        let _ = app.emit(
+            BACKEND_STREAM_CHUNK_EVENT,
+            StreamChunk {
+                stream_id: stream_id.clone(),
+                delta: delta.to_string(),
+            },
+        );
+        paragraphs.push(delta).into_iter().for_each(read);
+    })
+    .await;
+    if result.is_ok() {
+        paragraphs.finish().into_iter().for_each(read);
+    }
+    if let Err(e) = &result {
+        notify_rate_limited(&app, &task, e);
+    }
+    let _ = app.emit(
+        BACKEND_STREAM_DONE_EVENT,
+        StreamDone {
+            stream_id,
+            response: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        },
+    );
+    result.map_err(AppError::from)
+}
+
+/// Collects streamed text and hands out each paragraph once it is complete.
+#[derive(Debug, Default)]
+struct Paragraphs {
+    pending: String,
+}
+
+impl Paragraphs {
+    /// Adds `delta`; returns the paragraphs it completed.
+    fn push(&mut self, delta: &str) -> Vec<String> {
+        self.pending.push_str(delta);
+        let mut complete = Vec::new();
+        while let Some(end) = self.pending.find("\n\n") {
+            let paragraph: String = self.pending.drain(..end + 2).collect();
+            let paragraph = paragraph.trim();
+            if !paragraph.is_empty() {
+                complete.push(paragraph.to_string());
+            }
+        }
+        complete
+    }
+
+    /// The last paragraph, once the stream has ended.
+    fn finish(self) -> Option<String> {
+        let rest = self.pending.trim();
+        (!rest.is_empty()).then(|| rest.to_string())
+    }
+}
+
+/// Probes GET /health and caches the result. Never fails: an unreachable backend is a result.
+pub async fn probe_backend_health() -> BackendHealth {
+    #[derive(serde::Deserialize)]
+    struct HealthResponse {
+        version: Option<String>,
+    }
+
+    let url = format!("{}/health", backend_base_url());
+    let started = Instant::now();
+
+    let response =
+        match offline::ensure_online().and_then(|()| make_client(HEALTH_CHECK_TIMEOUT_SECS)) {
+            Ok(client) => client.get(&url).send().await.map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        };
+
+    let health = match response {
+        Ok(resp) => {
+            let latency_ms = started.elapsed().as_millis() as u64;
+            let status = resp.status();
+            let version = resp
+                .json::<HealthResponse>()
+                .await
+                .ok()
+                .and_then(|r| r.version)
+                .filter(|v| !v.trim().is_empty());
+            // A healthy backend closes the circuit without waiting for the next prompt.
+            if status.is_success() {
+                record_call(true);
+            }
+            BackendHealth {
+                reachable: status.is_success(),
+                status_code: Some(status.as_u16()),
+                latency_ms: Some(latency_ms),
+                version,
+                checked_at: unix_millis_now(),
+                circuit_open: circuit_open_for().is_some(),
+            }
+        }
+        Err(e) => {
+            debug!(error = %e, url = %url, "Backend health probe failed");
+            BackendHealth {
+                reachable: false,
+                status_code: None,
+                latency_ms: None,
+                version: None,
+                checked_at: unix_millis_now(),
+                circuit_open: circuit_open_for().is_some(),
+            }
+        }
+    };
+
+    if let Ok(mut guard) = LAST_HEALTH.lock() {
+        *guard = Some(health.clone());
     }
+    health
+}
+
+/// Starts a background thread that probes backend health periodically and emits
+/// `backend-health-changed` when reachability, the status code or the circuit state changes.
+/// Called from lib's setup.
+pub fn start_health_monitor<R: tauri::Runtime>(app: tauri::AppHandle<R>) {
+    std::thread::spawn(move || {
+        let mut previous: Option<(bool, Option<u16>, bool)> = None;
+        loop {
+            let health = tauri::async_runtime::block_on(probe_backend_health());
+            let current = (health.reachable, health.status_code, health.circuit_open);
+            if previous != Some(current) {
+                debug!(
+                    reachable = health.reachable,
+                    status_code = ?health.status_code,
+                    circuit_open = health.circuit_open,
+                    "Backend health changed"
+                );
+                let _ = app.emit(BACKEND_HEALTH_CHANGED_EVENT, &health);
+                previous = Some(current);
+            }
+            std::thread::sleep(HEALTH_CHECK_INTERVAL);
+        }
+    });
+}
+
+/// Probes GET /health now and returns reachability, status code, latency, version, and whether
+/// the circuit breaker is failing prompts at once.
+#[tauri::command]
+pub async fn backend_health_check() -> Result<BackendHealth, AppError> {
+    Ok(probe_backend_health().await)
+}
+
+/// Returns the last cached health result (from the monitor or a manual check), if any.
+#[tauri::command]
+pub fn get_backend_health() -> Option<BackendHealth> {
+    LAST_HEALTH.lock().ok().and_then(|guard| guard.clone())
 }
 
-/// Returns true if Polly credentials are configured and valid. Used by settings UI.
+/// Whether Polly credentials are configured, with the profile, region and engine in use. Used by
+/// the settings UI.
 #[tauri::command]
-pub fn check_polly_credentials() -> Result<bool, String> {
-    match crate::tts::check_polly_credentials() {
-        Ok(()) => Ok(true),
-        Err(_) => Ok(false),
+pub fn check_polly_credentials() -> Result<crate::tts::PollyDiagnostics, AppError> {
+    Ok(crate::tts::polly_diagnostics())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_stream_splits_events_across_chunks() {
+        let mut events = EventStream::default();
+        assert_eq!(events.push(b"data: {\"delta\": \"Hel"), vec![]);
+        assert_eq!(
+            events.push(b"lo\"}\r\n\r\n: keep-alive\n\ndata: world\n\n"),
+            vec![
+                StreamEvent::Delta("Hello".to_string()),
+                StreamEvent::Delta("world".to_string()),
+            ]
+        );
+        assert_eq!(
+            events.push(b"event: error\ndata: {\"error\": \"quota\"}\n\ndata: [DONE]\n\n"),
+            vec![StreamEvent::Error("quota".to_string()), StreamEvent::Done,]
+        );
+
+        let mut paragraphs = Paragraphs::default();
+        assert!(paragraphs.push("First para").is_empty());
+        assert_eq!(paragraphs.push("graph.\n\nSec"), vec!["First paragraph."]);
+        assert_eq!(paragraphs.finish().as_deref(), Some("Sec"));
+    }
+
+    #[test]
+    fn test_retries_back_off_up_to_the_limit() {
+        let policy = RetryPolicy {
+            retries: 3,
+            base_delay: Duration::from_millis(500),
+        };
+        assert_eq!(policy.delay(1, 0.0), Duration::from_millis(250));
+        assert_eq!(policy.delay(2, 1.0), Duration::from_millis(1000));
+        assert_eq!(policy.delay(30, 1.0), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn test_circuit_opens_after_repeated_failures_and_lets_one_probe_through() {
+        let start = Instant::now();
+        let mut circuit = CircuitBreaker::new();
+        for _ in 1..CIRCUIT_FAILURE_THRESHOLD {
+            circuit.record(false, start);
+        }
+        assert_eq!(circuit.admit(start), Ok(()));
+        circuit.record(false, start);
+        assert_eq!(circuit.admit(start), Err(CIRCUIT_OPEN_DURATION));
+
+        let half_open = start + CIRCUIT_OPEN_DURATION;
+        assert_eq!(circuit.admit(half_open), Ok(()));
+        assert_eq!(
+            circuit.admit(half_open),
+            Err(CIRCUIT_OPEN_DURATION),
+            "one probe at a time"
+        );
+        circuit.record(false, half_open);
+        assert_eq!(circuit.open_for(half_open), Some(CIRCUIT_OPEN_DURATION));
+
+        let half_open = half_open + CIRCUIT_OPEN_DURATION;
+        assert_eq!(circuit.admit(half_open), Ok(()));
+        circuit.record(true, half_open);
+        assert_eq!(circuit.admit(half_open), Ok(()));
+        assert_eq!(circuit.admit(half_open), Ok(()), "closed again");
+    }
+
+    #[test]
+    fn test_cancel_window_prompts_signals_only_that_windows_requests() {
+        let editor = CancelToken::default();
+        let main = CancelToken::default();
+        if let Ok(mut in_flight) = IN_FLIGHT.lock() {
+            in_flight.push(InFlight {
+                id: "test-editor".to_string(),
+                window: "test-editor-window".to_string(),
+                token: editor.clone(),
+            });
+            in_flight.push(InFlight {
+                id: "test-main".to_string(),
+                window: "test-main-window".to_string(),
+                token: main.clone(),
+            });
+        }
+        cancel_window_prompts("test-editor-window");
+        assert!(editor.is_cancelled());
+        assert!(!main.is_cancelled());
+        assert_eq!(backend_cancel("test-main".to_string()), Ok(true));
+        assert!(main.is_cancelled());
+        assert_eq!(backend_cancel("unknown".to_string()), Ok(false));
     }
 }
@@ -0,0 +1,233 @@
+//! Local cache of backend prompt responses, so asking for the same summary of the same text again
+//! is instant and works offline.
+//!
+//! Entries are keyed by the task, its options and a hash of the content, live in
+//! `responses.json` in the backend cache dir (see `paths`), newest first and capped at
+//! `MAX_ENTRIES`, and expire after `backend_cache_ttl_hours` (default a week). Setting
+//! `backend_cache_enabled` to false turns the cache off. `backend_history_list` lists the cached
+//! prompts and `clear_backend_cache` empties the cache.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::config;
+use crate::error::AppError;
+use crate::paths;
+use crate::util::unix_millis_now;
+
+const CACHE_FILE_NAME: &str = "responses.json";
+const MAX_ENTRIES: usize = 200;
+const PREVIEW_CHARS: usize = 120;
+pub const DEFAULT_TTL_HOURS: u32 = 24 * 7;
+const MS_PER_HOUR: u64 = 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    key: String,
+    task: String,
+    /// Start of the content, for `backend_history_list`; the content itself is not kept.
+    preview: String,
+    response: String,
+    /// Unix ms when the response was cached.
+    created_at: u64,
+}
+
+/// A cached prompt, returned by `backend_history_list`.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendHistoryEntry {
+    pub task: String,
+    pub preview: String,
+    pub response: String,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+/// Serializes reading and rewriting the cache file.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// How long entries live (ms), or None when the cache is off.
+fn settings() -> Option<u64> {
+    let cfg = config::load_full_config().unwrap_or_default();
+    cfg.backend_cache_enabled
+        .unwrap_or(true)
+        .then(|| u64::from(cfg.backend_cache_ttl_hours.unwrap_or(DEFAULT_TTL_HOURS)) * MS_PER_HOUR)
+}
+
+fn cache_path() -> Result<PathBuf, String> {
+    Ok(paths::get_backend_cache_dir()?.join(CACHE_FILE_NAME))
+}
+
+fn load_entries(path: &PathBuf) -> Vec<CacheEntry> {
+    let Ok(data) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&data).unwrap_or_else(|e| {
+        warn!(error = %e, path = %path.display(), "Failed to parse backend cache, ignoring it");
+        Vec::new()
+    })
+}
+
+fn save_entries(path: &PathBuf, entries: &[CacheEntry]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create backend cache directory: {e}"))?;
+    }
+    let data = serde_json::to_string(entries)
+        .map_err(|e| format!("Failed to serialize backend cache: {e}"))?;
+    fs::write(path, data).map_err(|e| format!("Failed to write backend cache: {e}"))
+}
+
+/// 64-bit FNV-1a. Keys are written to disk, so the hash must not change between builds the way
+/// `DefaultHasher` may.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The cache key of a prompt: a hash of every field that shapes the response.
+pub fn cache_key(
+    task: &str,
+    content: &str,
+    tone: Option<&str>,
+    format: Option<&str>,
+    instruction: Option<&str>,
+) -> String {
+    let mut bytes = Vec::with_capacity(task.len() + content.len() + 8);
+    for field in [
+        task,
+        content,
+        tone.unwrap_or(""),
+        format.unwrap_or(""),
+        instruction.unwrap_or(""),
+    ] {
+        bytes.extend_from_slice(field.as_bytes());
+        // Keeps ("ab", "c") and ("a", "bc") apart.
+        bytes.push(0);
+    }
+    format!("{:016x}-{}", fnv1a(&bytes), content.len())
+}
+
+fn is_fresh(entry: &CacheEntry, now: u64, ttl_ms: u64) -> bool {
+    now.saturating_sub(entry.created_at) < ttl_ms
+}
+
+/// The cached response for `key`, unless the cache is off or the entry has expired.
+pub fn lookup(key: &str) -> Option<String> {
+    let ttl_ms = settings()?;
+    let _guard = LOCK.lock().ok()?;
+    let now = unix_millis_now();
+    load_entries(&cache_path().ok()?)
+        .into_iter()
+        .find(|e| e.key == key && is_fresh(e, now, ttl_ms))
+        .map(|e| e.response)
+}
+
+/// Caches `response` under `key` and drops expired entries. No-op when the cache is off.
+pub fn store(key: &str, task: &str, content: &str, response: &str) {
+    let Some(ttl_ms) = settings() else {
+        return;
+    };
+    let Ok(_guard) = LOCK.lock() else {
+        return;
+    };
+    let Ok(path) = cache_path() else {
+        return;
+    };
+    let now = unix_millis_now();
+    let mut entries = load_entries(&path);
+    entries.retain(|e| e.key != key && is_fresh(e, now, ttl_ms));
+    let content = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    let preview = match content.char_indices().nth(PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", &content[..end]),
+        None => content,
+    };
+    entries.insert(
+        0,
+        CacheEntry {
+            key: key.to_string(),
+            task: task.to_string(),
+            preview,
+            response: response.to_string(),
+            created_at: now,
+        },
+    );
+    entries.truncate(MAX_ENTRIES);
+    match save_entries(&path, &entries) {
+        Ok(()) => debug!(task, entries = entries.len(), "Cached backend response"),
+        Err(e) => warn!(error = %e, "Could not cache backend response"),
+    }
+}
+
+// --- Commands ---
+
+/// Lists the cached prompts that have not expired, newest first.
+#[tauri::command]
+pub fn backend_history_list() -> Result<Vec<BackendHistoryEntry>, AppError> {
+    let ttl_ms = u64::from(
+        config::load_full_config()
+            .unwrap_or_default()
+            .backend_cache_ttl_hours
+            .unwrap_or(DEFAULT_TTL_HOURS),
+    ) * MS_PER_HOUR;
+    let _guard = LOCK
+        .lock()
+        .map_err(|_| AppError::from("Backend cache lock poisoned"))?;
+    let now = unix_millis_now();
+    Ok(load_entries(&cache_path()?)
+        .into_iter()
+        .filter(|e| is_fresh(e, now, ttl_ms))
+        .map(|e| BackendHistoryEntry {
+            expires_at: e.created_at + ttl_ms,
+            task: e.task,
+            preview: e.preview,
+            response: e.response,
+            created_at: e.created_at,
+        })
+        .collect())
+}
+
+/// Removes every cached response. Returns how many were removed.
+#[tauri::command]
+pub fn clear_backend_cache() -> Result<usize, AppError> {
+    let _guard = LOCK
+        .lock()
+        .map_err(|_| AppError::from("Backend cache lock poisoned"))?;
+    let path = cache_path()?;
+    let removed = load_entries(&path).len();
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to clear backend cache: {e}"))?;
+    }
+    debug!(removed, "Cleared backend cache");
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_covers_every_prompt_field() {
+        let key = cache_key("SUMMARIZE", "Some article.", None, None, None);
+        assert_eq!(
+            key,
+            cache_key("SUMMARIZE", "Some article.", None, None, None)
+        );
+        assert_ne!(
+            key,
+            cache_key("EXPLAIN1", "Some article.", None, None, None)
+        );
+        assert_ne!(
+            key,
+            cache_key("SUMMARIZE", "Some article.", Some("casual"), None, None)
+        );
+        assert_ne!(
+            cache_key("TRANSLATE", "ab", None, None, Some("c")),
+            cache_key("TRANSLATE", "a", None, None, Some("bc"))
+        );
+    }
+}
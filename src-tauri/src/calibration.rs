@@ -0,0 +1,333 @@
+//! Per-voice reading-speed calibration.
+//!
+//! The settings UI plays a short passage at several speeds (`calibration_play_sample`), the user
+//! confirms the most comfortable one (`calibration_save_speed`), and the speed is stored in
+//! `FullConfig::calibrated_speeds` keyed by `"<provider>:<voice>"`. The TTS worker applies the
+//! calibrated speed automatically whenever it switches to that voice. Samples play at their own
+//! speed; the speed from before the first sample is put back once the last one stops playing.
+
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+
+use tauri::{Emitter, State};
+
+use crate::commands_config::ConfigState;
+use crate::config::{self, FullConfig};
+use crate::error::AppError;
+use crate::history;
+use crate::i18n::{self, SpokenText};
+use crate::tts;
+
+/// Speeds offered by the calibration flow, slowest first.
+const SAMPLE_SPEEDS: [f32; 6] = [0.8, 1.0, 1.2, 1.4, 1.6, 1.8];
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 4.0;
+/// How often a sample's playback is checked for its end.
+const SAMPLE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The speed to put back after calibration samples, and the latest sample: only its watcher
+/// restores, so samples played in a row keep the speed from before the first one.
+struct SampleRestore {
+    speed: Option<Option<f32>>,
+    sample: u64,
+}
+
+static SAMPLE_RESTORE: Mutex<SampleRestore> = Mutex::new(SampleRestore {
+    speed: None,
+    sample: 0,
+});
+
+/// Calibration key for the voice currently selected in `cfg` (`"piper:en_US-lessac-medium"`).
+/// Providers without an explicit voice use `"<provider>:default"`.
+pub fn voice_key(cfg: &FullConfig) -> String {
+    let provider = cfg
+        .voice_provider
+        .as_deref()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or(crate::tts::TtsProvider::default().name());
+    let voice = match provider {
+        "piper" => cfg.selected_voice.as_deref(),
+        "polly" => cfg.selected_polly_voice.as_deref(),
+        "system" => cfg.selected_system_voice.as_deref(),
+        "mobile" => cfg.selected_mobile_voice.as_deref(),
+        "custom" => cfg.custom_tts_speaker.as_deref(),
+        _ => cfg.selected_microsoft_voice.as_deref(),
+    }
+    .filter(|v| !v.trim().is_empty())
+    .unwrap_or("default");
+    format!("{provider}:{voice}")
+}
+
+/// Calibrated speed for the voice currently selected in `cfg`, if any.
+pub fn calibrated_speed(cfg: &FullConfig) -> Option<f32> {
+    cfg.calibrated_speeds
+        .as_ref()?
+        .get(&voice_key(cfg))
+        .copied()
+        .filter(|s| s.is_finite())
+        .map(|s| s.clamp(MIN_SPEED, MAX_SPEED))
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SpeedCalibration {
+    pub voice_key: String,
+    pub calibrated_speed: Option<f32>,
+    pub sample_speeds: Vec<f32>,
+}
+
+fn validate_speed(speed: f64) -> Result<f32, AppError> {
+    let speed = speed as f32;
+    if !speed.is_finite() || !(MIN_SPEED..=MAX_SPEED).contains(&speed) {
+        return Err(AppError::InvalidInput(format!(
+            "Speed must be between {MIN_SPEED} and {MAX_SPEED}"
+        )));
+    }
+    Ok(speed)
+}
+
+/// Notes the worker's speed (unless an earlier sample already did) and starts a new sample.
+fn begin_sample(tx: &tts::TtsState) -> Result<u64, String> {
+    let mut restore = SAMPLE_RESTORE
+        .lock()
+        .map_err(|_| "Calibration state lock poisoned".to_string())?;
+    if restore.speed.is_none() {
+        let (resp_tx, resp_rx) = mpsc::sync_channel(1);
+        tx.send(tts::TtsRequest::GetSpeed(resp_tx))
+            .map_err(|e| format!("TTS channel: {e}"))?;
+        let speed = resp_rx
+            .recv()
+            .map_err(|_| "TTS worker disconnected".to_string())?;
+        restore.speed = Some(speed);
+    }
+    restore.sample += 1;
+    Ok(restore.sample)
+}
+
+/// Puts back the speed from before the samples when `sample` is still the latest one.
+fn end_sample(tx: &tts::TtsState, sample: u64) {
+    let Ok(mut restore) = SAMPLE_RESTORE.lock() else {
+        return;
+    };
+    if sample != restore.sample {
+        return;
+    }
+    if let Some(speed) = restore.speed.take() {
+        let _ = tx.send(tts::TtsRequest::RestoreSpeed(speed));
+    }
+}
+
+/// Waits until `sample` stopped playing, then restores the speed (see `end_sample`).
+fn watch_sample(tx: tts::TtsState, sample: u64) {
+    loop {
+        std::thread::sleep(SAMPLE_POLL_INTERVAL);
+        let (resp_tx, resp_rx) = mpsc::sync_channel(1);
+        if tx.send(tts::TtsRequest::GetStatus(resp_tx)).is_err() {
+            return;
+        }
+        let playing = resp_rx.recv().is_ok_and(|(playing, _)| playing);
+        let current = SAMPLE_RESTORE
+            .lock()
+            .is_ok_and(|restore| restore.sample == sample);
+        if !current {
+            return;
+        }
+        if !playing {
+            end_sample(&tx, sample);
+            return;
+        }
+    }
+}
+
+/// Sends SetSpeed (and optionally Speak) to the worker, waiting for each response.
+fn send_speed_and_text(tx: tts::TtsState, speed: f32, text: Option<String>) -> Result<(), String> {
+    let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+    tx.send(tts::TtsRequest::SetSpeed(speed, resp_tx))
+        .map_err(|e| format!("TTS channel: {e}"))?;
+    resp_rx
+        .recv()
+        .map_err(|_| "TTS worker disconnected".to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let Some(text) = text else {
+        return Ok(());
+    };
+    history::detach();
+    let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+    tx.send(tts::TtsRequest::Speak(
+        text,
+        tts::InputKind::Text,
+        None,
+        resp_tx,
+    ))
+    .map_err(|e| format!("TTS channel: {e}"))?;
+    resp_rx
+        .recv()
+        .map_err(|_| "TTS worker disconnected".to_string())?
+        .map_err(|e| e.to_string())
+}
+
+// --- Commands ---
+
+/// Returns the current voice's calibration key, its calibrated speed, and the sample speeds.
+#[tauri::command]
+pub fn get_speed_calibration(state: State<'_, ConfigState>) -> Result<SpeedCalibration, AppError> {
+    let cfg = state
+        .lock()
+        .map_err(|_| "Config lock poisoned".to_string())?;
+    Ok(SpeedCalibration {
+        voice_key: voice_key(&cfg),
+        calibrated_speed: calibrated_speed(&cfg),
+        sample_speeds: SAMPLE_SPEEDS.to_vec(),
+    })
+}
+
+/// Plays the calibration passage with the current voice at `speed`. The previous speed comes
+/// back once the sample has finished (or failed to start).
+#[tauri::command]
+pub async fn calibration_play_sample(
+    tts_state: State<'_, tts::TtsState>,
+    speed: f64,
+) -> Result<(), AppError> {
+    let speed = validate_speed(speed)?;
+    let passage = i18n::text(SpokenText::CalibrationPassage, i18n::configured_language());
+    let tx = tts_state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let _ = tx.send(tts::TtsRequest::Stop);
+        let sample = begin_sample(&tx)?;
+        if let Err(e) = send_speed_and_text(tx.clone(), speed, Some(passage.to_string())) {
+            end_sample(&tx, sample);
+            return Err(e);
+        }
+        std::thread::spawn(move || watch_sample(tx, sample));
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking: {e}"))??;
+    Ok(())
+}
+
+/// Stores `speed` as the calibrated speed for the current voice and applies it immediately.
+#[tauri::command]
+pub async fn calibration_save_speed(
+    app: tauri::AppHandle,
+    state: State<'_, ConfigState>,
+    tts_state: State<'_, tts::TtsState>,
+    speed: f64,
+) -> Result<(), AppError> {
+    let speed = validate_speed(speed)?;
+    let new_cfg = {
+        let mut cfg = state
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?;
+        let key = voice_key(&cfg);
+        cfg.calibrated_speeds
+            .get_or_insert_with(Default::default)
+            .insert(key, speed);
+        cfg.clone()
+    };
+    config::save_full_config(new_cfg)?;
+    let _ = app.emit("config-changed", ());
+
+    let tx = tts_state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        // The saved speed replaces the one from before the samples.
+        if let Ok(mut restore) = SAMPLE_RESTORE.lock() {
+            restore.speed = None;
+            restore.sample += 1;
+        }
+        send_speed_and_text(tx, speed, None)
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking: {e}"))??;
+    Ok(())
+}
+
+/// Removes the calibrated speed for `voice_key` (default: the current voice).
+#[tauri::command]
+pub fn calibration_clear(
+    app: tauri::AppHandle,
+    state: State<'_, ConfigState>,
+    voice_key: Option<String>,
+) -> Result<(), AppError> {
+    let new_cfg = {
+        let mut cfg = state
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?;
+        let key = voice_key.unwrap_or_else(|| self::voice_key(&cfg));
+        if let Some(speeds) = cfg.calibrated_speeds.as_mut() {
+            speeds.remove(&key);
+        }
+        cfg.clone()
+    };
+    config::save_full_config(new_cfg)?;
+    let _ = app.emit("config-changed", ());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config(provider: &str, voice: &str) -> FullConfig {
+        FullConfig {
+            voice_provider: Some(provider.to_string()),
+            selected_voice: Some(voice.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_voice_key_uses_the_selected_voice_of_the_provider() {
+        assert_eq!(
+            voice_key(&config("piper", "en_US-lessac-medium")),
+            "piper:en_US-lessac-medium"
+        );
+        // The Piper voice is not the Polly one.
+        assert_eq!(
+            voice_key(&config("polly", "en_US-lessac-medium")),
+            "polly:default"
+        );
+        assert_eq!(voice_key(&config("piper", "  ")), "piper:default");
+
+        let default_provider = tts::TtsProvider::default().name();
+        let cfg = FullConfig {
+            voice_provider: Some(String::new()),
+            ..Default::default()
+        };
+        assert_eq!(voice_key(&cfg), format!("{default_provider}:default"));
+    }
+
+    #[test]
+    fn test_calibrated_speed_is_clamped_and_ignores_invalid_values() {
+        let mut cfg = config("piper", "voice");
+        assert_eq!(calibrated_speed(&cfg), None);
+
+        for (stored, expected) in [
+            (1.3, Some(1.3)),
+            (9.0, Some(MAX_SPEED)),
+            (0.0, Some(MIN_SPEED)),
+            (f32::NAN, None),
+            (f32::INFINITY, None),
+        ] {
+            cfg.calibrated_speeds = Some(HashMap::from([("piper:voice".to_string(), stored)]));
+            assert_eq!(calibrated_speed(&cfg), expected, "stored {stored}");
+        }
+
+        cfg.selected_voice = Some("other".to_string());
+        assert_eq!(calibrated_speed(&cfg), None);
+    }
+
+    #[test]
+    fn test_validate_speed_accepts_only_the_supported_range() {
+        assert_eq!(validate_speed(1.5).unwrap(), 1.5);
+        assert_eq!(validate_speed(f64::from(MIN_SPEED)).unwrap(), MIN_SPEED);
+        assert_eq!(validate_speed(f64::from(MAX_SPEED)).unwrap(), MAX_SPEED);
+        for speed in [0.1, 4.5, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                validate_speed(speed),
+                Err(AppError::InvalidInput(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,560 @@
+//! Command line interface: `insight-reader <command>`. Without a command the app starts as usual.
+//!
+//! - `action <word>` hands an action to the running instance (see `action_socket`), or starts the
+//!   app with it when none is running.
+//! - `speak` reads text aloud and exits when playback ends.
+//! - `export --out FILE` saves the audio as WAV or MP3 (`--format`, or the file's extension).
+//! - `voices list` prints the voices of a provider, one per line, tab-separated.
+//!
+//! Text comes from the argument, `--file`, or stdin. `speak`, `export` and `voices` run headless:
+//! they use the config and the TTS worker directly and never build the Tauri app. `--provider`
+//! and `--voice` only apply to that run (see `tts::VoiceOverride`). Logs go to stderr, at `warn`
+//! unless `RUST_LOG` says otherwise. Windows release builds have no console, so there the output
+//! only shows when redirected (e.g. `insight-reader voices list > voices.txt`).
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use crate::config::FullConfig;
+use crate::tasks::{TaskInfo, TaskKind, TaskManager, TaskNotifier, TaskStatus};
+use crate::text::pipeline::Pipeline;
+use crate::{action_socket, tts, voices};
+
+/// How often `speak` checks whether playback has finished.
+const PLAYBACK_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// How long to wait for the TTS worker to stop before exiting.
+const WORKER_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+const USAGE: &str = "\
+Reads text aloud. Without a command, starts the app.
+
+Usage: insight-reader <COMMAND>
+
+Commands:
+  action <ACTION>              Sends an action to the running instance, or starts the app
+                               with it: read-selected, read-screenshot, pause, stop,
+                               summarize, explain, translate, skim or editor
+  speak [TEXT]                 Reads text aloud and exits when playback ends
+  export [TEXT] --out <FILE>   Saves text as a WAV or MP3 file
+  voices list                  Prints the voices of a provider (the configured one by
+                               default; --provider)
+
+Options for speak and export:
+  [TEXT]                 Text to read; \"-\" or none reads stdin
+  -f, --file <FILE>      Reads the text from a file
+      --provider <NAME>  Provider for this run: piper, microsoft, polly, system or custom
+      --voice <VOICE>    Voice for this run, as listed by `voices list`
+  -o, --out <FILE>       File to write (export)
+      --format <FORMAT>  wav or mp3 (export); by default from the file's extension, WAV
+                         unless it is .mp3
+";
+
+#[derive(Debug)]
+enum Command {
+    Action(String),
+    Speak(TextInput, VoiceArgs),
+    Export {
+        input: TextInput,
+        voice: VoiceArgs,
+        out: PathBuf,
+        format: Option<Format>,
+    },
+    VoicesList(Option<tts::TtsProvider>),
+    Help,
+    Version,
+}
+
+#[derive(Debug)]
+struct TextInput {
+    text: Option<String>,
+    file: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+struct VoiceArgs {
+    provider: Option<tts::TtsProvider>,
+    voice: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Wav,
+    Mp3,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ParseError {
+    /// The first argument is not a command: the arguments are meant for the app (e.g. passed by
+    /// the OS or a launcher); those started the app before the CLI existed, so they still do.
+    Foreign,
+    Invalid(String),
+}
+
+impl From<String> for ParseError {
+    fn from(message: String) -> Self {
+        Self::Invalid(message)
+    }
+}
+
+/// Short forms of the options that have one.
+const SHORT_OPTIONS: [(&str, &str); 2] = [("f", "file"), ("o", "out")];
+
+/// The options (all of which take a value) and positional arguments after a command.
+struct Parsed {
+    options: Vec<(&'static str, String)>,
+    positionals: Vec<String>,
+}
+
+impl Parsed {
+    /// Splits `args` into the `allowed` options (`--name value`, `--name=value` or a short form)
+    /// and positional arguments. Everything after `--` is positional.
+    fn new(args: &[String], allowed: &[&'static str]) -> Result<Self, String> {
+        let mut parsed = Self {
+            options: Vec::new(),
+            positionals: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                parsed.positionals.extend(args.by_ref().cloned());
+                break;
+            }
+            if arg == "-" || !arg.starts_with('-') {
+                parsed.positionals.push(arg.clone());
+                continue;
+            }
+            let (name, inline) = match arg.strip_prefix("--") {
+                Some(long) => match long.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_string())),
+                    None => (long, None),
+                },
+                None => {
+                    let short = &arg[1..];
+                    let long = SHORT_OPTIONS.iter().find(|(s, _)| *s == short);
+                    (long.map_or(short, |(_, long)| *long), None)
+                }
+            };
+            let Some(&name) = allowed.iter().find(|allowed| **allowed == name) else {
+                return Err(format!("unexpected argument '{arg}'"));
+            };
+            let value = match inline {
+                Some(value) => value,
+                None => args
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| format!("'{arg}' needs a value"))?,
+            };
+            if parsed.options.iter().any(|(n, _)| *n == name) {
+                return Err(format!("'--{name}' given more than once"));
+            }
+            parsed.options.push((name, value));
+        }
+        Ok(parsed)
+    }
+
+    fn take(&mut self, name: &str) -> Option<String> {
+        let index = self.options.iter().position(|(n, _)| *n == name)?;
+        Some(self.options.remove(index).1)
+    }
+
+    /// The positional arguments, which must be at most `max`.
+    fn positionals(self, max: usize) -> Result<Vec<String>, String> {
+        match self.positionals.get(max) {
+            Some(extra) => Err(format!("unexpected argument '{extra}'")),
+            None => Ok(self.positionals),
+        }
+    }
+
+    fn voice(&mut self) -> Result<VoiceArgs, String> {
+        Ok(VoiceArgs {
+            provider: self
+                .take("provider")
+                .map(|p| parse_provider(&p))
+                .transpose()?,
+            voice: self.take("voice"),
+        })
+    }
+
+    /// The text argument or `--file`, which conflict.
+    fn text_input(mut self) -> Result<TextInput, String> {
+        let file = self.take("file").map(PathBuf::from);
+        let text = self.positionals(1)?.pop();
+        if text.is_some() && file.is_some() {
+            return Err("the text and '--file' cannot be used together".to_string());
+        }
+        Ok(TextInput { text, file })
+    }
+}
+
+/// Parses the arguments after the program name.
+fn parse(args: &[String]) -> Result<Command, ParseError> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(ParseError::Foreign);
+    };
+    let command = match command.as_str() {
+        "-h" | "--help" | "help" => Command::Help,
+        "-V" | "--version" => Command::Version,
+        "action" => {
+            let action = Parsed::new(rest, &[])?.positionals(1)?.pop();
+            Command::Action(action.ok_or_else(|| "'action' needs an action".to_string())?)
+        }
+        "speak" => {
+            let mut parsed = Parsed::new(rest, &["file", "provider", "voice"])?;
+            let voice = parsed.voice()?;
+            Command::Speak(parsed.text_input()?, voice)
+        }
+        "export" => {
+            let mut parsed = Parsed::new(rest, &["file", "provider", "voice", "out", "format"])?;
+            let voice = parsed.voice()?;
+            let out = parsed
+                .take("out")
+                .ok_or_else(|| "'export' needs '--out <FILE>'".to_string())?;
+            let format = parsed
+                .take("format")
+                .map(|f| parse_format(&f))
+                .transpose()?;
+            Command::Export {
+                input: parsed.text_input()?,
+                voice,
+                out: PathBuf::from(out),
+                format,
+            }
+        }
+        "voices" => {
+            let mut parsed = Parsed::new(rest, &["provider"])?;
+            let provider = parsed
+                .take("provider")
+                .map(|p| parse_provider(&p))
+                .transpose()?;
+            if parsed.positionals(1)? != ["list"] {
+                return Err(ParseError::Invalid("usage: voices list".to_string()));
+            }
+            Command::VoicesList(provider)
+        }
+        _ => return Err(ParseError::Foreign),
+    };
+    Ok(command)
+}
+
+fn parse_provider(name: &str) -> Result<tts::TtsProvider, String> {
+    tts::TtsProvider::from_name(name).ok_or_else(|| format!("unknown provider \"{name}\""))
+}
+
+fn parse_format(name: &str) -> Result<Format, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "wav" => Ok(Format::Wav),
+        "mp3" => Ok(Format::Mp3),
+        _ => Err(format!("unknown format \"{name}\" (wav or mp3)")),
+    }
+}
+
+/// Runs the command on the command line. Returns `None` when the app should start instead: no
+/// command, arguments that are not ours, or an action with no running instance (which is then
+/// passed on in `INSIGHT_READER_START_ACTION`).
+pub fn run_cli() -> Option<ExitCode> {
+    // `--portable` is handled by paths.rs; it may appear anywhere on the command line.
+    let args: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| arg != "--portable")
+        .collect();
+    let command = match parse(&args) {
+        Ok(command) => command,
+        Err(ParseError::Foreign) => return None,
+        Err(ParseError::Invalid(e)) => {
+            eprintln!("insight-reader: {e}\n\n{USAGE}");
+            return Some(ExitCode::from(2));
+        }
+    };
+
+    let result = match command {
+        Command::Help => {
+            print!("{USAGE}");
+            Ok(())
+        }
+        Command::Version => {
+            println!("insight-reader {}", env!("CARGO_PKG_VERSION"));
+            Ok(())
+        }
+        Command::Action(action) => {
+            if action_socket::send_action_to_running_instance(&action).is_ok() {
+                return Some(ExitCode::SUCCESS);
+            }
+            std::env::set_var("INSIGHT_READER_START_ACTION", action);
+            return None;
+        }
+        Command::Speak(input, voice) => read_text(&input).and_then(|text| speak(voice, text)),
+        Command::Export {
+            input,
+            voice,
+            out,
+            format,
+        } => read_text(&input).and_then(|text| export(voice, text, &out, format)),
+        Command::VoicesList(provider) => list_voices(provider),
+    };
+    match result {
+        Ok(()) => Some(ExitCode::SUCCESS),
+        Err(e) => {
+            eprintln!("insight-reader: {e}");
+            Some(ExitCode::FAILURE)
+        }
+    }
+}
+
+fn read_text(input: &TextInput) -> Result<String, String> {
+    let text = match (&input.text, &input.file) {
+        (_, Some(path)) => std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?,
+        (Some(text), None) if text != "-" => text.clone(),
+        _ => {
+            let mut text = String::new();
+            std::io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|e| format!("Failed to read stdin: {e}"))?;
+            text
+        }
+    };
+    if text.trim().is_empty() {
+        return Err("No text to read".to_string());
+    }
+    Ok(text)
+}
+
+/// Sets up logging and config for a headless command, applies `voice` and returns the config.
+fn prepare(voice: Option<VoiceArgs>) -> FullConfig {
+    crate::logging::init("warn", true);
+    let config = crate::prepare_runtime(crate::app_context().package_info());
+    if let Some(voice) = voice {
+        tts::set_voice_override(tts::VoiceOverride {
+            provider: voice.provider,
+            voice: voice.voice,
+        });
+    }
+    config
+}
+
+fn speak(voice: VoiceArgs, text: String) -> Result<(), String> {
+    prepare(Some(voice));
+    let tts_tx = tts::create_tts_state();
+    let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+    tts_tx
+        .send(tts::TtsRequest::Speak(
+            text,
+            tts::InputKind::Text,
+            None,
+            resp_tx,
+        ))
+        .map_err(|e| format!("TTS channel: {e}"))?;
+    resp_rx
+        .recv()
+        .map_err(|_| "TTS worker disconnected".to_string())?
+        .map_err(|e| e.to_string())?;
+
+    loop {
+        std::thread::sleep(PLAYBACK_POLL_INTERVAL);
+        let (playing, paused) =
+            tts::playback_status(&tts_tx).ok_or_else(|| "TTS worker disconnected".to_string())?;
+        if !playing && !paused {
+            break;
+        }
+    }
+    tts::shutdown_worker(&tts_tx, WORKER_SHUTDOWN_TIMEOUT);
+    Ok(())
+}
+
+fn export_format(out: &Path, format: Option<Format>) -> tts::ExportFormat {
+    let format = format.unwrap_or_else(|| {
+        let is_mp3 = out
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("mp3"));
+        if is_mp3 {
+            Format::Mp3
+        } else {
+            Format::Wav
+        }
+    });
+    match format {
+        Format::Wav => tts::ExportFormat::Wav,
+        Format::Mp3 => tts::ExportFormat::Mp3,
+    }
+}
+
+fn export(
+    voice: VoiceArgs,
+    text: String,
+    out: &Path,
+    format: Option<Format>,
+) -> Result<(), String> {
+    let config = prepare(Some(voice));
+    let tts_tx = tts::create_tts_state();
+    let pipeline = Pipeline::for_config(&config);
+    let format = export_format(out, format);
+
+    // Progress goes to stderr on one line, like the task list in the app.
+    let notify: TaskNotifier = Arc::new(|info: &TaskInfo| match info.status {
+        TaskStatus::Running => {
+            if let Some(message) = &info.message {
+                eprint!("\r{}: {message}", info.label);
+            }
+        }
+        _ => eprintln!(),
+    });
+    let mut task = TaskManager::default().start_with_notifier(
+        TaskKind::AudioExport,
+        format!("Exporting {}", out.display()),
+        notify,
+    );
+    let result = tts::export_to_file(&tts_tx, &pipeline, &text, out, format, &mut task);
+    task.finish(&result);
+    tts::shutdown_worker(&tts_tx, WORKER_SHUTDOWN_TIMEOUT);
+
+    let exported = result?;
+    println!(
+        "{}\t{:.1}s\t{} bytes",
+        exported.path,
+        exported.duration_ms as f64 / 1000.0,
+        exported.bytes
+    );
+    Ok(())
+}
+
+fn list_voices(provider: Option<tts::TtsProvider>) -> Result<(), String> {
+    let config = prepare(None);
+    let provider = provider
+        .or_else(|| {
+            config
+                .voice_provider
+                .as_deref()
+                .and_then(tts::TtsProvider::from_name)
+        })
+        .unwrap_or_default();
+    let lines: Vec<String> = match provider {
+        tts::TtsProvider::Piper => voices::download::list_downloaded_voices()?
+            .into_iter()
+            .map(|voice| format!("{}\t{}", voice.key, voice.language))
+            .collect(),
+        tts::TtsProvider::Polly => {
+            tauri::async_runtime::block_on(voices::fetch_polly_voices(false))?
+                .into_iter()
+                .map(|voice| format!("{}\t{}\t{}", voice.id, voice.language_code, voice.gender))
+                .collect()
+        }
+        tts::TtsProvider::Microsoft => {
+            tauri::async_runtime::block_on(voices::fetch_microsoft_voices(false))?
+                .into_iter()
+                .map(|voice| {
+                    format!(
+                        "{}\t{}\t{}",
+                        voice.short_name, voice.language_code, voice.gender
+                    )
+                })
+                .collect()
+        }
+        tts::TtsProvider::Custom => {
+            let url = config
+                .custom_tts_url
+                .clone()
+                .filter(|url| !url.trim().is_empty())
+                .unwrap_or_else(|| tts::DEFAULT_CUSTOM_SERVER_URL.to_string());
+            tauri::async_runtime::block_on(voices::fetch_custom_server_voices(&url))?
+                .into_iter()
+                .map(|voice| voice.name)
+                .collect()
+        }
+        tts::TtsProvider::System | tts::TtsProvider::Mobile => {
+            return Err(format!(
+                "{} voices cannot be listed from the command line",
+                provider.name()
+            ));
+        }
+    };
+    for line in lines {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Command, ParseError> {
+        parse(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_parses_export_options_in_any_form() {
+        let command = parse_args(&[
+            "export",
+            "--voice",
+            "en_US-amy-medium",
+            "-o",
+            "out.mp3",
+            "hello",
+        ]);
+        let Ok(Command::Export {
+            input,
+            voice,
+            out,
+            format,
+        }) = command
+        else {
+            panic!("expected export, got {command:?}");
+        };
+        assert_eq!(input.text.as_deref(), Some("hello"));
+        assert_eq!(voice.voice.as_deref(), Some("en_US-amy-medium"));
+        assert_eq!(format, None);
+        assert_eq!(export_format(&out, None), tts::ExportFormat::Mp3);
+        assert_eq!(
+            export_format(Path::new("out.MP3"), Some(Format::Wav)),
+            tts::ExportFormat::Wav
+        );
+
+        let command = parse_args(&["export", "--out=a.bin", "--format=MP3", "-f", "in.txt"]);
+        let Ok(Command::Export { input, format, .. }) = command else {
+            panic!("expected export, got {command:?}");
+        };
+        assert_eq!(input.file, Some(PathBuf::from("in.txt")));
+        assert_eq!(format, Some(Format::Mp3));
+    }
+
+    #[test]
+    fn test_rejects_invalid_arguments() {
+        for args in [
+            &["speak", "--provider", "nope"][..],
+            &["speak", "-f", "a.txt", "text"],
+            &["speak", "one", "two"],
+            &["speak", "--voic", "x"],
+            &["speak", "--voice"],
+            &["export", "hello"],
+            &["export", "--out", "a.wav", "--format", "ogg"],
+            &["action"],
+            &["voices"],
+        ] {
+            assert!(
+                matches!(parse_args(args), Err(ParseError::Invalid(_))),
+                "{args:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_leaves_foreign_arguments_to_the_app() {
+        assert_eq!(parse_args(&[]).err(), Some(ParseError::Foreign));
+        assert_eq!(
+            parse_args(&["-psn_0_12345"]).err(),
+            Some(ParseError::Foreign)
+        );
+        assert_eq!(parse_args(&["notes.txt"]).err(), Some(ParseError::Foreign));
+        assert!(matches!(
+            parse_args(&["action", "read-selected"]),
+            Ok(Command::Action(action)) if action == "read-selected"
+        ));
+        assert!(matches!(
+            parse_args(&["speak", "--", "-"]),
+            Ok(Command::Speak(TextInput { text: Some(text), file: None }, _)) if text == "-"
+        ));
+    }
+}
@@ -0,0 +1,172 @@
+//! Clipboard watch: reads newly copied text aloud, for hands-free reading.
+//!
+//! Off by default (`clipboard_watch`); toggled from the tray ("Read Copied Text") or with
+//! `set_clipboard_watch`. While on, a background thread polls the clipboard every `POLL_INTERVAL`
+//! (change notifications are not available everywhere, e.g. on Wayland) and reads text that
+//! differs from the last seen, like "Read Selected" (see `actions::read_text`). Text under
+//! `clipboard_watch_min_chars` characters (default `DEFAULT_MIN_CHARS`) is skipped, and so is text
+//! copied while an app named in `clipboard_watch_ignore_apps` (e.g. a password manager) is in
+//! front, where `system::foreground_app` can tell.
+//!
+//! Whatever is on the clipboard when the watch starts is not read. "Read Selected" copies through
+//! the clipboard on Windows and macOS (simulated Ctrl+C / Cmd+C), so changes seen while a capture
+//! is in flight are not read either.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{Emitter, Manager};
+use tracing::{debug, info};
+
+use crate::actions;
+use crate::commands_config::{self, ConfigState};
+use crate::config::{self, FullConfig};
+use crate::dispatch;
+use crate::system;
+use crate::text_capture;
+use crate::tray;
+use crate::tts;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+pub const DEFAULT_MIN_CHARS: usize = 20;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static MIN_CHARS: AtomicUsize = AtomicUsize::new(DEFAULT_MIN_CHARS);
+/// Lowercased app names.
+static IGNORE_APPS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Applies the `clipboard_watch*` settings (see `commands_config::apply_runtime_settings`).
+pub fn configure(cfg: &FullConfig) {
+    ENABLED.store(cfg.clipboard_watch.unwrap_or(false), Ordering::Relaxed);
+    MIN_CHARS.store(
+        cfg.clipboard_watch_min_chars
+            .map_or(DEFAULT_MIN_CHARS, |n| n as usize),
+        Ordering::Relaxed,
+    );
+    if let Ok(mut apps) = IGNORE_APPS.lock() {
+        *apps = cfg
+            .clipboard_watch_ignore_apps
+            .iter()
+            .flatten()
+            .map(|app| app.trim().to_lowercase())
+            .filter(|app| !app.is_empty())
+            .collect();
+    }
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Clipboard samples since the watch (re)started.
+#[derive(Default)]
+struct Watch {
+    /// Set by the first sample, which only records what is already on the clipboard.
+    primed: bool,
+    last: Option<String>,
+}
+
+impl Watch {
+    /// Takes one clipboard sample; returns newly copied text long enough to read.
+    fn on_sample(&mut self, text: Option<String>, min_chars: usize) -> Option<String> {
+        if !std::mem::replace(&mut self.primed, true) {
+            self.last = text;
+            return None;
+        }
+        let text = text?;
+        if self.last.as_deref() == Some(text.as_str()) {
+            return None;
+        }
+        self.last = Some(text.clone());
+        (text.trim().chars().count() >= min_chars).then_some(text)
+    }
+}
+
+fn is_ignored(app_name: &str) -> bool {
+    let app_name = app_name.to_lowercase();
+    IGNORE_APPS
+        .lock()
+        .map(|apps| apps.contains(&app_name))
+        .unwrap_or(false)
+}
+
+/// Polls the clipboard while the watch is on. Called from lib's setup.
+pub fn start(tts_tx: tts::TtsState) {
+    std::thread::spawn(move || {
+        let mut watch = Watch::default();
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            // Start over after a pause or our own capture, so neither reads stale text.
+            if !is_enabled() || dispatch::CAPTURES.is_busy() {
+                watch = Watch::default();
+                continue;
+            }
+            let sample = text_capture::get_clipboard_text_impl();
+            let Some(text) = watch.on_sample(sample, MIN_CHARS.load(Ordering::Relaxed)) else {
+                continue;
+            };
+            if let Some(front) = system::foreground_app().filter(|name| is_ignored(name)) {
+                debug!(app = %front, "Clipboard watch: ignoring copy from ignored app");
+                continue;
+            }
+            debug!(len = text.len(), "Clipboard watch: reading copied text");
+            actions::read_text(&tts_tx, text, "clipboard", "clipboard-watch");
+        }
+    });
+}
+
+/// Turns the watch on or off and saves it to config. Shared by the command and the tray.
+pub fn set_enabled_impl<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    enabled: bool,
+) -> Result<(), String> {
+    let state = app
+        .try_state::<ConfigState>()
+        .ok_or_else(|| "ConfigState not found".to_string())?;
+    let new_cfg = {
+        let mut cfg = state
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?;
+        cfg.clipboard_watch = Some(enabled);
+        cfg.clone()
+    };
+    commands_config::apply_runtime_settings(&new_cfg);
+    config::save_full_config(new_cfg)?;
+    info!(enabled, "Clipboard watch toggled");
+    tray::refresh_tray_menu(app);
+    let _ = app.emit("config-changed", ());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_clipboard_watch(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    set_enabled_impl(&app, enabled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_only_new_copies_long_enough() {
+        let mut watch = Watch::default();
+        let copy = |s: &str| Some(s.to_string());
+        assert_eq!(watch.on_sample(copy("already on the clipboard"), 5), None);
+        assert_eq!(watch.on_sample(copy("already on the clipboard"), 5), None);
+        assert_eq!(
+            watch.on_sample(copy("a newly copied sentence"), 5),
+            copy("a newly copied sentence")
+        );
+        assert_eq!(watch.on_sample(None, 5), None);
+        assert_eq!(watch.on_sample(copy("a newly copied sentence"), 5), None);
+        assert_eq!(watch.on_sample(copy("  ok  "), 5), None);
+
+        let mut empty_at_start = Watch::default();
+        assert_eq!(empty_at_start.on_sample(None, 5), None);
+        assert_eq!(
+            empty_at_start.on_sample(copy("first copy"), 5),
+            copy("first copy")
+        );
+    }
+}
@@ -2,14 +2,59 @@
 
 use std::sync::{Arc, Mutex};
 
-use tauri::{Emitter, Manager, State};
+#[cfg(desktop)]
+use tauri::Manager;
+use tauri::{Emitter, State};
 
+#[cfg(desktop)]
+use crate::clipboard_watch;
 use crate::config;
+use crate::diagnostics;
+use crate::dispatch;
+use crate::ducking;
+use crate::features;
+use crate::history;
+#[cfg(desktop)]
 use crate::hotkeys;
+#[cfg(desktop)]
+use crate::http_api;
+use crate::logging;
+use crate::mic_pause;
+use crate::offline;
+use crate::paths;
+use crate::suspend;
+#[cfg(desktop)]
+use crate::tray;
+use crate::tts;
+use crate::usage;
+use crate::voices;
 
 /// Shared config state type used by these commands and by lib's composition root.
 pub type ConfigState = Arc<Mutex<config::FullConfig>>;
 
+/// Pushes config values that are read outside `ConfigState` (TTS worker globals) into place.
+pub fn apply_runtime_settings(cfg: &config::FullConfig) {
+    logging::configure(cfg);
+    diagnostics::configure(cfg);
+    offline::configure(cfg);
+    tts::set_playback_trace_enabled(cfg.playback_trace_enabled.unwrap_or(false));
+    tts::set_synthesis_priority(cfg.synthesis_priority.as_deref());
+    tts::set_inference_backend(cfg.inference_backend.as_deref());
+    tts::set_read_announcement(cfg.announce_reads.unwrap_or(false));
+    dispatch::set_capture_concurrency(cfg.capture_concurrency);
+    features::apply(cfg.experimental.as_ref());
+    history::configure(cfg);
+    mic_pause::configure(cfg);
+    suspend::configure(cfg);
+    ducking::configure(cfg);
+    voices::configure(cfg);
+    usage::configure(cfg);
+    #[cfg(desktop)]
+    http_api::configure(cfg);
+    #[cfg(desktop)]
+    clipboard_watch::configure(cfg);
+}
+
 /// Returns the current platform (e.g., "macos", "windows", "linux").
 #[tauri::command]
 pub fn get_platform() -> &'static str {
@@ -19,10 +64,26 @@ pub fn get_platform() -> &'static str {
     return "windows";
     #[cfg(target_os = "linux")]
     return "linux";
-    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    #[cfg(target_os = "android")]
+    return "android";
+    #[cfg(target_os = "ios")]
+    return "ios";
+    #[cfg(not(any(
+        target_os = "macos",
+        target_os = "windows",
+        target_os = "linux",
+        target_os = "android",
+        target_os = "ios"
+    )))]
     return "unknown";
 }
 
+/// Returns the resolved config/cache/data directories (diagnostics).
+#[tauri::command]
+pub fn get_app_paths() -> paths::AppPaths {
+    paths::app_paths()
+}
+
 #[tauri::command]
 pub fn get_config(state: State<'_, ConfigState>) -> Result<config::FullConfig, String> {
     let cfg = state
@@ -39,7 +100,10 @@ pub fn save_config(
 ) -> Result<(), String> {
     let mut cfg: config::FullConfig = serde_json::from_str(&config_json)
         .map_err(|e| format!("Failed to parse config JSON: {}", e))?;
+    #[cfg(desktop)]
+    hotkeys::validate_config(&cfg)?;
     cfg.installation_id = Some(config::get_or_create_installation_id()?);
+    apply_runtime_settings(&cfg);
     {
         let mut shared = state
             .lock()
@@ -47,13 +111,23 @@ pub fn save_config(
         *shared = cfg.clone();
     }
     config::save_full_config(cfg).map_err(|e| e.to_string())?;
+    notify_config_changed(&app);
+    Ok(())
+}
 
+/// Propagates a new `ConfigState` (already applied with `apply_runtime_settings`): refreshes
+/// hotkeys and the tray menu, has the TTS worker re-read the config and emits `config-changed`.
+pub fn notify_config_changed<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    #[cfg(desktop)]
     if let Some(state) = app.try_state::<hotkeys::GlobalHotkeyState>() {
-        hotkeys::refresh_global_hotkeys(&app, &state.inner().clone());
+        hotkeys::refresh_global_hotkeys(app, &state.inner().clone());
+    }
+    #[cfg(desktop)]
+    tray::refresh_tray_menu(app);
+    if let Some(tts) = app.try_state::<tts::TtsState>() {
+        let _ = tts.send(tts::TtsRequest::ReloadConfig);
     }
-
     let _ = app.emit("config-changed", ());
-    Ok(())
 }
 
 /// Sets the explain mode preference in a single, serialized read-modify-write.
@@ -73,6 +147,7 @@ pub fn set_explain_mode(
 
     config::save_full_config(new_cfg).map_err(|e| e.to_string())?;
 
+    #[cfg(desktop)]
     if let Some(state) = app.try_state::<hotkeys::GlobalHotkeyState>() {
         hotkeys::refresh_global_hotkeys(&app, &state.inner().clone());
     }
@@ -1,30 +1,256 @@
-//! Tauri commands for TTS: speak, stop, pause, seek, volume, speed, provider.
+//! Tauri commands for TTS: speak, queue, proofread, export, stop, pause, seek, volume, speed,
+//! skim speed, sleep timer, provider, word timeline, audio outputs.
 
-use tauri::State;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::time::Duration;
 
+use tauri::{AppHandle, Emitter, State};
+use tracing::warn;
+
+use crate::commands_config::ConfigState;
+use crate::config::{self, FullConfig};
+use crate::editor_pages;
+use crate::error::AppError;
+use crate::history;
+use crate::i18n;
+use crate::tasks::{TaskKind, TaskManager};
+use crate::text::language::LanguageSpan;
+use crate::text::pipeline::Pipeline;
+use crate::text::readability::{self, ProofreadReport, Thresholds};
+use crate::text::reading_mode::ReadingMode;
+use crate::text::ssml;
 use crate::tts;
 
+/// Emitted while audio plays, when the spoken word changes.
+const TTS_PROGRESS_EVENT: &str = "tts-progress";
+/// Emitted when the playback queue moves to its next text (see `tts::QueueAdvance`).
+const TTS_QUEUE_ADVANCED_EVENT: &str = "tts-queue-advanced";
+/// Emitted when a failing provider is replaced by the next fallback (see `tts::ProviderFallback`).
+const TTS_PROVIDER_FALLBACK_EVENT: &str = "tts-provider-fallback";
+/// Emitted by `tts_proofread` with the sentences over the thresholds.
+const PROOFREAD_FLAGS_EVENT: &str = "proofread-flags";
+/// Waveform resolution for `tts_get_waveform` when none is given, and the most it returns.
+const DEFAULT_WAVEFORM_BUCKETS: usize = 200;
+const MAX_WAVEFORM_BUCKETS: usize = 4096;
+/// How long volume, speed and pitch must stay unchanged before they are saved, so dragging a
+/// slider writes the config once rather than on every step.
+const PLAYBACK_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Bumped by every volume, speed or pitch change; a delayed save only runs for the latest one.
+static PLAYBACK_SAVE_GENERATION: AtomicU64 = AtomicU64::new(0);
+/// Whether a volume, speed or pitch change has not been saved yet.
+static PLAYBACK_SAVE_PENDING: AtomicBool = AtomicBool::new(false);
+
 /// Speaks the given text (Piper, Microsoft, or Polly). Fails if TTS is unavailable or text is empty.
-/// Runs send+recv in spawn_blocking so the command thread does not block while synthesis runs.
+/// `input_kind` "ssml" sends `text` as SSML to the cloud voices (see `text::ssml`); default "text".
+/// The read is recorded in the reading history under `source` (default "app"). `languages` marks
+/// stretches of `text` in another language (byte ranges), spoken with that language's
+/// pronunciation by the voices that support it; without them, they are detected when
+/// `ssml_language_tags` is on. `reading_mode` "markdown", "code" or "auto" reads Markdown or
+/// source code as prose (see `text::reading_mode`); the converted text is what gets recorded, and
+/// `languages` no longer apply to it.
 #[tauri::command]
-pub async fn tts_speak(state: State<'_, tts::TtsState>, text: String) -> Result<(), String> {
-    let tx = state.inner().clone();
+pub async fn tts_speak(
+    state: State<'_, tts::TtsState>,
+    text: String,
+    input_kind: Option<tts::InputKind>,
+    source: Option<String>,
+    languages: Option<Vec<LanguageSpan>>,
+    reading_mode: Option<ReadingMode>,
+) -> Result<(), AppError> {
+    let input_kind = input_kind.unwrap_or_default();
+    let (text, languages) = match reading_mode
+        .filter(|_| input_kind == tts::InputKind::Text)
+        .and_then(|mode| mode.apply(&text))
+    {
+        Some(converted) => (converted, None),
+        None => (text, languages),
+    };
+    record_read(source.as_deref().unwrap_or("app"), &text, input_kind);
+    let result = speak(state, text, input_kind, languages).await;
+    if result.is_err() {
+        history::abandon();
+    }
+    result
+}
+
+/// Records a read in the history (the plain text of SSML input).
+fn record_read(source: &str, text: &str, input_kind: tts::InputKind) {
+    match input_kind {
+        tts::InputKind::Text => history::begin(source, text),
+        tts::InputKind::Ssml => history::begin(source, &ssml::Passthrough::new(text).plain),
+    }
+}
+
+/// Sends the request `make_request` builds around a reply channel to the TTS worker and waits for
+/// the reply. Runs in spawn_blocking so the command thread does not block while the worker is busy
+/// (e.g. synthesizing the start of a read).
+async fn request<T: Send + 'static>(
+    tx: &tts::TtsState,
+    make_request: impl FnOnce(mpsc::SyncSender<T>) -> tts::TtsRequest + Send + 'static,
+) -> Result<T, AppError> {
+    let tx = tx.clone();
     tokio::task::spawn_blocking(move || {
-        let (resp_tx, resp_rx) = std::sync::mpsc::sync_channel(0);
-        tx.send(tts::TtsRequest::Speak(text, resp_tx))
+        let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+        tx.send(make_request(resp_tx))
             .map_err(|e| format!("TTS channel: {e}"))?;
         resp_rx
             .recv()
-            .map_err(|_| "TTS worker disconnected".to_string())?
-            .map_err(|e| e.to_string())
+            .map_err(|_| AppError::from("TTS worker disconnected"))
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking: {e}"))?
+}
+
+/// Sends Speak and waits until synthesis has started.
+async fn speak(
+    state: State<'_, tts::TtsState>,
+    text: String,
+    input_kind: tts::InputKind,
+    languages: Option<Vec<LanguageSpan>>,
+) -> Result<(), AppError> {
+    request(&state, move |resp_tx| {
+        tts::TtsRequest::Speak(text, input_kind, languages, resp_tx)
+    })
+    .await?
+    .map_err(AppError::from)
+}
+
+/// Reads `text` after the texts already queued, or right away when nothing is playing. Returns
+/// its queue id; `tts-queue-advanced` reports when it starts. `input_kind` and `source` are as
+/// for `tts_speak`.
+#[tauri::command]
+pub async fn tts_enqueue(
+    state: State<'_, tts::TtsState>,
+    text: String,
+    input_kind: Option<tts::InputKind>,
+    source: Option<String>,
+) -> Result<u64, AppError> {
+    if text.trim().is_empty() {
+        return Err(AppError::NoText);
+    }
+    request(&state, move |resp_tx| {
+        tts::TtsRequest::Enqueue(
+            text,
+            input_kind.unwrap_or_default(),
+            source.unwrap_or_else(|| "app".to_string()),
+            resp_tx,
+        )
+    })
+    .await?
+    .map_err(AppError::from)
+}
+
+/// Lists the texts waiting in the playback queue, next first (not the one playing).
+#[tauri::command]
+pub async fn tts_queue_list(
+    state: State<'_, tts::TtsState>,
+) -> Result<Vec<tts::QueueItem>, AppError> {
+    request(&state, tts::TtsRequest::QueueList).await
+}
+
+/// Stops the current text and starts the next queued one. Returns false when the queue was empty
+/// (playback just stops).
+#[tauri::command]
+pub async fn tts_queue_skip(state: State<'_, tts::TtsState>) -> Result<bool, AppError> {
+    request(&state, tts::TtsRequest::QueueSkip).await
+}
+
+/// Drops the waiting texts; the current one keeps playing. Returns how many were dropped.
+#[tauri::command]
+pub async fn tts_queue_clear(state: State<'_, tts::TtsState>) -> Result<usize, AppError> {
+    request(&state, tts::TtsRequest::QueueClear).await
+}
+
+/// Proofread-by-ear: reads `text` clause by clause with pauses and flags sentences over the
+/// configured length (`proofread_max_words`) or reading grade (`proofread_max_grade`). The flags
+/// are emitted as `proofread-flags` before playback starts and returned once it has started.
+#[tauri::command]
+pub async fn tts_proofread(
+    app: AppHandle,
+    state: State<'_, tts::TtsState>,
+    config: State<'_, ConfigState>,
+    text: String,
+) -> Result<ProofreadReport, AppError> {
+    let (pipeline, thresholds) = {
+        let cfg = config
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?;
+        (Pipeline::for_config(&cfg), Thresholds::from_config(&cfg))
+    };
+    let prepared = crate::text::prepare(&text, &pipeline);
+    history::detach();
+    let report = readability::review(&text, &prepared.segments, thresholds);
+    let _ = app.emit(PROOFREAD_FLAGS_EVENT, &report);
+
+    request(&state, move |resp_tx| {
+        tts::TtsRequest::Proofread(text, resp_tx)
+    })
+    .await?
+    .map_err(AppError::from)?;
+    Ok(report)
+}
+
+/// Synthesizes `text` with the current voice and saves it to `path` as `format` ("wav" or "mp3")
+/// instead of playing it. Runs as a background task (progress via `task-updated`, cancellable
+/// with `cancel_task`).
+#[tauri::command]
+pub async fn tts_export_to_file(
+    app: AppHandle,
+    tasks: State<'_, TaskManager>,
+    state: State<'_, tts::TtsState>,
+    config: State<'_, ConfigState>,
+    text: String,
+    path: String,
+    format: tts::ExportFormat,
+) -> Result<tts::ExportResult, AppError> {
+    let pipeline = {
+        let cfg = config
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?;
+        Pipeline::for_config(&cfg)
+    };
+    let file_name = Path::new(&path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.clone());
+    let mut task = tasks.start(
+        &app,
+        TaskKind::AudioExport,
+        format!("Exporting {file_name}"),
+    );
+    let tx = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        let result =
+            tts::export_to_file(&tx, &pipeline, &text, Path::new(&path), format, &mut task);
+        task.finish(&result);
+        result.map_err(AppError::from)
     })
     .await
     .map_err(|e| format!("spawn_blocking: {e}"))?
 }
 
+/// Speaks a short sample sentence to preview the current voice. `language` is the voice's
+/// language (e.g. "pt_BR"); defaults to the configured `ui_language`.
+#[tauri::command]
+pub async fn tts_preview_voice(
+    state: State<'_, tts::TtsState>,
+    language: Option<String>,
+) -> Result<(), AppError> {
+    let language = match language.as_deref() {
+        Some(tag) => i18n::normalize_language(Some(tag)),
+        None => i18n::configured_language(),
+    };
+    let text = i18n::text(i18n::SpokenText::VoicePreviewSample, language).to_string();
+    history::detach();
+    speak(state, text, tts::InputKind::Text, None).await
+}
+
 /// Stops any ongoing TTS playback. No-op if TTS is unavailable.
 #[tauri::command]
-pub fn tts_stop(state: State<tts::TtsState>) -> Result<(), String> {
+pub fn tts_stop(state: State<tts::TtsState>) -> Result<(), AppError> {
     state
         .inner()
         .send(tts::TtsRequest::Stop)
@@ -34,145 +260,383 @@ pub fn tts_stop(state: State<tts::TtsState>) -> Result<(), String> {
 
 /// Toggles pause state of TTS playback. Returns true if paused, false if playing.
 #[tauri::command]
-pub async fn tts_toggle_pause(state: State<'_, tts::TtsState>) -> Result<bool, String> {
-    let tx = state.inner().clone();
-    tokio::task::spawn_blocking(move || {
-        let (resp_tx, resp_rx) = std::sync::mpsc::sync_channel(0);
-        tx.send(tts::TtsRequest::TogglePause(resp_tx))
-            .map_err(|e| format!("TTS channel: {e}"))?;
-        resp_rx
-            .recv()
-            .map_err(|_| "TTS worker disconnected".to_string())?
-            .map_err(|e| e.to_string())
-    })
-    .await
-    .map_err(|e| format!("spawn_blocking: {e}"))?
+pub async fn tts_toggle_pause(state: State<'_, tts::TtsState>) -> Result<bool, AppError> {
+    request(&state, tts::TtsRequest::TogglePause)
+        .await?
+        .map_err(AppError::from)
 }
 
 /// Gets the current TTS playback status. Returns (is_playing, is_paused).
 #[tauri::command]
-pub async fn tts_get_status(state: State<'_, tts::TtsState>) -> Result<(bool, bool), String> {
-    let tx = state.inner().clone();
-    tokio::task::spawn_blocking(move || {
-        let (resp_tx, resp_rx) = std::sync::mpsc::sync_channel(0);
-        tx.send(tts::TtsRequest::GetStatus(resp_tx))
-            .map_err(|e| format!("TTS channel: {e}"))?;
-        resp_rx
-            .recv()
-            .map_err(|_| "TTS worker disconnected".to_string())
+pub async fn tts_get_status(state: State<'_, tts::TtsState>) -> Result<(bool, bool), AppError> {
+    request(&state, tts::TtsRequest::GetStatus).await
+}
+
+/// Fades out and stops playback (clearing the queue) after `minutes`, replacing a running sleep
+/// timer. Returns the timer as `tts_get_sleep_timer` reports it.
+#[tauri::command]
+pub async fn tts_set_sleep_timer(
+    state: State<'_, tts::TtsState>,
+    minutes: u32,
+) -> Result<tts::SleepTimerStatus, AppError> {
+    if !(1..=tts::MAX_SLEEP_MINUTES).contains(&minutes) {
+        return Err(AppError::InvalidInput(format!(
+            "Sleep timer must be between 1 and {} minutes, got {minutes}",
+            tts::MAX_SLEEP_MINUTES
+        )));
+    }
+    request(&state, move |resp_tx| {
+        tts::TtsRequest::SetSleepTimer(minutes, resp_tx)
     })
-    .await
-    .map_err(|e| format!("spawn_blocking: {e}"))?
+    .await?
+    .map_err(AppError::from)
+}
+
+/// Cancels the sleep timer. Returns false when none was running.
+#[tauri::command]
+pub async fn tts_cancel_sleep_timer(state: State<'_, tts::TtsState>) -> Result<bool, AppError> {
+    request(&state, tts::TtsRequest::CancelSleepTimer).await
 }
 
-/// Seeks TTS playback by the given offset in milliseconds.
-/// Returns (success, at_start, at_end). Fails if paused or seeking is not supported.
+/// The running sleep timer and the time left before playback stops, or null.
+#[tauri::command]
+pub async fn tts_get_sleep_timer(
+    state: State<'_, tts::TtsState>,
+) -> Result<Option<tts::SleepTimerStatus>, AppError> {
+    request(&state, tts::TtsRequest::GetSleepTimer).await
+}
+
+/// Seeks TTS playback by the given offset in milliseconds. Works while paused; playback stays
+/// paused at the new position. Returns where playback landed. Fails if seeking is not supported.
 #[tauri::command]
 pub async fn tts_seek(
     state: State<'_, tts::TtsState>,
     offset_ms: i64,
-) -> Result<(bool, bool, bool), String> {
-    let tx = state.inner().clone();
-    tokio::task::spawn_blocking(move || {
-        let (resp_tx, resp_rx) = std::sync::mpsc::sync_channel(0);
-        tx.send(tts::TtsRequest::Seek(offset_ms, resp_tx))
-            .map_err(|e| format!("TTS channel: {e}"))?;
-        resp_rx
-            .recv()
-            .map_err(|_| "TTS worker disconnected".to_string())?
-            .map_err(|e| e.to_string())
+) -> Result<tts::SeekResult, AppError> {
+    request(&state, move |resp_tx| {
+        tts::TtsRequest::Seek(offset_ms, resp_tx)
     })
-    .await
-    .map_err(|e| format!("spawn_blocking: {e}"))?
+    .await?
+    .map_err(AppError::from)
+}
+
+/// Seeks TTS playback to `position_ms` of content time, clamped to the audio queued so far. Works
+/// while paused. Returns the new position like `tts_seek`.
+#[tauri::command]
+pub async fn tts_seek_to(
+    state: State<'_, tts::TtsState>,
+    position_ms: u64,
+) -> Result<tts::SeekResult, AppError> {
+    request(&state, move |resp_tx| {
+        tts::TtsRequest::SeekTo(position_ms, resp_tx)
+    })
+    .await?
+    .map_err(AppError::from)
+}
+
+/// Seeks TTS playback to `percent` (0-100) of the audio queued so far, for progress-bar scrubbing.
+/// While a long read is still synthesizing, the percentage is of what has been synthesized.
+#[tauri::command]
+pub async fn tts_seek_percent(
+    state: State<'_, tts::TtsState>,
+    percent: f64,
+) -> Result<tts::SeekResult, AppError> {
+    if !(0.0..=100.0).contains(&percent) {
+        return Err(AppError::InvalidInput(format!(
+            "Seek percentage must be between 0 and 100, got {percent}"
+        )));
+    }
+    request(&state, move |resp_tx| {
+        tts::TtsRequest::SeekPercent(percent, resp_tx)
+    })
+    .await?
+    .map_err(AppError::from)
+}
+
+/// Parses the `unit` argument of the segment jumps; sentences when omitted.
+fn segment_unit(unit: Option<String>) -> Result<tts::SegmentUnit, AppError> {
+    match unit {
+        None => Ok(tts::SegmentUnit::default()),
+        Some(unit) => tts::SegmentUnit::from_name(&unit.to_lowercase()).ok_or_else(|| {
+            AppError::InvalidInput(format!(
+                "Unknown unit: {unit}. Use 'sentence' or 'paragraph'."
+            ))
+        }),
+    }
+}
+
+/// Jumps playback to the next sentence (or paragraph, with `unit: "paragraph"`) of the current
+/// read. Returns the new position like `tts_seek`; fails when there is no next one yet.
+#[tauri::command]
+pub async fn tts_next_segment(
+    state: State<'_, tts::TtsState>,
+    unit: Option<String>,
+) -> Result<tts::SeekResult, AppError> {
+    let unit = segment_unit(unit)?;
+    request(&state, move |resp_tx| {
+        tts::TtsRequest::NextSegment(unit, resp_tx)
+    })
+    .await?
+    .map_err(AppError::from)
+}
+
+/// Jumps playback back to the start of the current sentence (or paragraph), or to the previous
+/// one when playback is near its start. Returns the new position like `tts_seek`.
+#[tauri::command]
+pub async fn tts_prev_segment(
+    state: State<'_, tts::TtsState>,
+    unit: Option<String>,
+) -> Result<tts::SeekResult, AppError> {
+    let unit = segment_unit(unit)?;
+    request(&state, move |resp_tx| {
+        tts::TtsRequest::PrevSegment(unit, resp_tx)
+    })
+    .await?
+    .map_err(AppError::from)
 }
 
 /// Gets the current playback position and total duration in milliseconds.
 /// Returns (current_ms, total_ms).
 #[tauri::command]
-pub async fn tts_get_position(state: State<'_, tts::TtsState>) -> Result<(u64, u64), String> {
-    let tx = state.inner().clone();
-    tokio::task::spawn_blocking(move || {
-        let (resp_tx, resp_rx) = std::sync::mpsc::sync_channel(0);
-        tx.send(tts::TtsRequest::GetPosition(resp_tx))
-            .map_err(|e| format!("TTS channel: {e}"))?;
-        resp_rx
-            .recv()
-            .map_err(|_| "TTS worker disconnected".to_string())
+pub async fn tts_get_position(state: State<'_, tts::TtsState>) -> Result<(u64, u64), AppError> {
+    request(&state, tts::TtsRequest::GetPosition).await
+}
+
+/// Gets the word timeline of the current read: for each word synthesized so far, its start time
+/// in ms and its UTF-16 range in the text passed to `tts_speak`.
+#[tauri::command]
+pub async fn tts_get_timeline(
+    state: State<'_, tts::TtsState>,
+) -> Result<Vec<tts::TimelineWord>, AppError> {
+    request(&state, tts::TtsRequest::GetTimeline).await
+}
+
+/// Gets the amplitude envelope of the current read in `buckets` slices (default 200), for a
+/// waveform progress bar without sending PCM to the webview.
+#[tauri::command]
+pub async fn tts_get_waveform(
+    state: State<'_, tts::TtsState>,
+    buckets: Option<usize>,
+) -> Result<tts::TtsWaveform, AppError> {
+    let buckets = buckets
+        .unwrap_or(DEFAULT_WAVEFORM_BUCKETS)
+        .clamp(1, MAX_WAVEFORM_BUCKETS);
+    request(&state, move |resp_tx| {
+        tts::TtsRequest::GetWaveform(buckets, resp_tx)
     })
     .await
-    .map_err(|e| format!("spawn_blocking: {e}"))?
 }
 
-/// Sets TTS playback volume as percentage from 0 to 100.
+/// Makes the TTS worker emit `tts-progress` (see `tts::TtsProgress`) when the spoken word changes,
+/// and keeps the reading history's position up to date.
+pub fn start_progress_events(app: &AppHandle, state: &tts::TtsState) {
+    let app = app.clone();
+    let notifier: tts::ProgressNotifier = Box::new(move |progress| {
+        history::note_progress(progress);
+        let _ = app.emit(TTS_PROGRESS_EVENT, progress);
+    });
+    if state
+        .send(tts::TtsRequest::SetProgressNotifier(notifier))
+        .is_err()
+    {
+        warn!("TTS worker not running, progress events disabled");
+    }
+}
+
+/// Makes the TTS worker emit `tts-queue-advanced` when the queue moves on, records each queued
+/// text in the reading history when it starts, and keeps a paged editor read one page ahead.
+pub fn start_queue_events(app: &AppHandle, state: &tts::TtsState) {
+    let app = app.clone();
+    let tx = state.clone();
+    let notifier: tts::QueueNotifier = Box::new(move |advance| {
+        editor_pages::on_queue_advance(&app, &tx, advance);
+        if let Some(item) = advance.current.as_ref().filter(|item| !item.recorded) {
+            record_read(&item.source, &item.text, item.input_kind);
+        }
+        let _ = app.emit(TTS_QUEUE_ADVANCED_EVENT, advance);
+    });
+    if state
+        .send(tts::TtsRequest::SetQueueNotifier(notifier))
+        .is_err()
+    {
+        warn!("TTS worker not running, queue events disabled");
+    }
+}
+
+/// Makes the TTS worker emit `tts-provider-fallback` when it switches to a fallback provider.
+pub fn start_fallback_events(app: &AppHandle, state: &tts::TtsState) {
+    let app = app.clone();
+    let notifier: tts::FallbackNotifier = Box::new(move |fallback| {
+        let _ = app.emit(TTS_PROVIDER_FALLBACK_EVENT, fallback);
+    });
+    if state
+        .send(tts::TtsRequest::SetFallbackNotifier(notifier))
+        .is_err()
+    {
+        warn!("TTS worker not running, provider fallback events disabled");
+    }
+}
+
+/// Sets TTS playback volume as percentage from 0 to 100. Saved as `playback_volume`.
 #[tauri::command]
 pub async fn tts_set_volume(
     state: State<'_, tts::TtsState>,
+    config: State<'_, ConfigState>,
     volume_percent: u8,
-) -> Result<(), String> {
-    let tx = state.inner().clone();
-    tokio::task::spawn_blocking(move || {
-        let (resp_tx, resp_rx) = std::sync::mpsc::sync_channel(0);
-        tx.send(tts::TtsRequest::SetVolume(volume_percent, resp_tx))
-            .map_err(|e| format!("TTS channel: {e}"))?;
-        resp_rx
-            .recv()
-            .map_err(|_| "TTS worker disconnected".to_string())?
-            .map_err(|e| e.to_string())
+) -> Result<(), AppError> {
+    let volume_percent = volume_percent.min(100);
+    request(&state, move |resp_tx| {
+        tts::TtsRequest::SetVolume(volume_percent, resp_tx)
     })
-    .await
-    .map_err(|e| format!("spawn_blocking: {e}"))?
+    .await??;
+    save_playback_setting(&config, |cfg| cfg.playback_volume = Some(volume_percent))
 }
 
 /// Sets TTS playback speed (1.0 = normal). Takes effect immediately. Clamped to 0.25..=4.0.
+/// Saved as `playback_speed`, which then replaces the voice's calibrated speed.
 #[tauri::command]
-pub async fn tts_set_speed(state: State<'_, tts::TtsState>, speed: f64) -> Result<(), String> {
+pub async fn tts_set_speed(
+    state: State<'_, tts::TtsState>,
+    config: State<'_, ConfigState>,
+    speed: f64,
+) -> Result<(), AppError> {
     let raw = speed as f32;
     let speed_f32 = if raw.is_finite() {
-        raw.clamp(0.25, 4.0)
+        raw.clamp(tts::MIN_SPEED, tts::MAX_SPEED)
     } else {
         1.0
     };
-    let tx = state.inner().clone();
-    tokio::task::spawn_blocking(move || {
-        let (resp_tx, resp_rx) = std::sync::mpsc::sync_channel(0);
-        tx.send(tts::TtsRequest::SetSpeed(speed_f32, resp_tx))
-            .map_err(|e| format!("TTS channel: {e}"))?;
-        resp_rx
-            .recv()
-            .map_err(|_| "TTS worker disconnected".to_string())?
-            .map_err(|e| e.to_string())
+    request(&state, move |resp_tx| {
+        tts::TtsRequest::SetSpeed(speed_f32, resp_tx)
     })
-    .await
-    .map_err(|e| format!("spawn_blocking: {e}"))?
+    .await??;
+    save_playback_setting(&config, |cfg| cfg.playback_speed = Some(speed_f32))
+}
+
+/// Switches between the normal speed and the skim speed (`skim_speed`, default 2.0), keeping the
+/// pitch. Returns true when skimming. Not saved: a restart or `tts_set_speed` leaves skim mode.
+#[tauri::command]
+pub async fn tts_toggle_skim(state: State<'_, tts::TtsState>) -> Result<bool, AppError> {
+    request(&state, tts::TtsRequest::ToggleSkim)
+        .await?
+        .map_err(AppError::from)
+}
+
+/// Sets the voice pitch in percent (0 = the voice's own, clamped to -50..=50). Local voices change
+/// immediately; the cloud voices get it as SSML prosody from the next text on (Polly's neural
+/// voices have no pitch). Saved as `playback_pitch`.
+#[tauri::command]
+pub async fn tts_set_pitch(
+    state: State<'_, tts::TtsState>,
+    config: State<'_, ConfigState>,
+    pitch: i32,
+) -> Result<(), AppError> {
+    let pitch = pitch.clamp(tts::MIN_PITCH, tts::MAX_PITCH);
+    request(&state, move |resp_tx| {
+        tts::TtsRequest::SetPitch(pitch, resp_tx)
+    })
+    .await??;
+    save_playback_setting(&config, |cfg| cfg.playback_pitch = Some(pitch))
+}
+
+/// Updates a playback setting in `ConfigState` and saves it, so the next start uses it, once no
+/// further change has come for `PLAYBACK_SAVE_DELAY`.
+fn save_playback_setting(
+    config: &State<'_, ConfigState>,
+    update: impl FnOnce(&mut FullConfig),
+) -> Result<(), AppError> {
+    {
+        let mut cfg = config
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?;
+        update(&mut cfg);
+    }
+    PLAYBACK_SAVE_PENDING.store(true, Ordering::SeqCst);
+    let generation = PLAYBACK_SAVE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let config = config.inner().clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(PLAYBACK_SAVE_DELAY).await;
+        if PLAYBACK_SAVE_GENERATION.load(Ordering::SeqCst) == generation {
+            flush_playback_settings(&config);
+        }
+    });
+    Ok(())
+}
+
+/// Saves volume, speed and pitch changes still waiting for `PLAYBACK_SAVE_DELAY`. Called by the
+/// shutdown sequence.
+pub fn flush_playback_settings(config: &ConfigState) {
+    if !PLAYBACK_SAVE_PENDING.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let cfg = match config.lock() {
+        Ok(cfg) => cfg.clone(),
+        Err(_) => {
+            warn!("Config lock poisoned, playback settings not saved");
+            return;
+        }
+    };
+    if let Err(e) = config::save_full_config(cfg) {
+        warn!(error = %e, "Failed to save playback settings");
+    }
 }
 
-/// Switches the TTS provider. provider should be "piper", "microsoft", or "polly".
+/// Switches the TTS provider. provider should be "piper", "microsoft", "polly", "system",
+/// "mobile", or "custom".
 #[tauri::command]
 pub async fn tts_switch_provider(
     state: State<'_, tts::TtsState>,
     provider: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let provider = match provider.to_lowercase().as_str() {
         "piper" => tts::TtsProvider::Piper,
         "microsoft" => tts::TtsProvider::Microsoft,
         "polly" => tts::TtsProvider::Polly,
+        "system" => tts::TtsProvider::System,
+        "mobile" => tts::TtsProvider::Mobile,
+        "custom" => tts::TtsProvider::Custom,
         _ => {
-            return Err(format!(
-                "Unknown provider: {}. Use 'piper', 'microsoft', or 'polly'.",
+            return Err(AppError::InvalidInput(format!(
+                "Unknown provider: {}. Use 'piper', 'microsoft', 'polly', 'system', 'mobile', or \
+                 'custom'.",
                 provider
-            ))
+            )))
         }
     };
-    let tx = state.inner().clone();
-    tokio::task::spawn_blocking(move || {
-        let (resp_tx, resp_rx) = std::sync::mpsc::sync_channel(0);
-        tx.send(tts::TtsRequest::SwitchProvider(provider, resp_tx))
-            .map_err(|e| format!("TTS channel: {e}"))?;
-        resp_rx
-            .recv()
-            .map_err(|_| "TTS worker disconnected".to_string())?
-            .map_err(|e| e.to_string())
+    request(&state, move |resp_tx| {
+        tts::TtsRequest::SwitchProvider(provider, resp_tx)
     })
-    .await
-    .map_err(|e| format!("spawn_blocking: {e}"))?
+    .await?
+    .map_err(AppError::from)
+}
+
+/// Returns the buffered playback trace (empty unless `playback_trace_enabled` is set in config).
+#[tauri::command]
+pub fn dump_playback_trace() -> Vec<tts::PlaybackTraceEntry> {
+    tts::playback_trace()
+}
+
+/// Reports inference backends (CPU, CUDA, DirectML, Core ML) available on this machine and the
+/// one local synthesis uses after fallback.
+#[tauri::command]
+pub async fn get_inference_backends() -> Result<tts::InferenceBackends, AppError> {
+    tokio::task::spawn_blocking(tts::inference_backends)
+        .await
+        .map_err(|e| AppError::Internal(format!("spawn_blocking: {e}")))
+}
+
+/// Availability of each provider (Piper binary and model, Polly credentials, Edge TTS and the
+/// custom server reachable), the provider and voice in use, and the last synthesis error.
+#[tauri::command]
+pub async fn tts_get_provider_status() -> Result<tts::TtsProviderStatus, AppError> {
+    Ok(tts::provider_status().await)
+}
+
+/// Audio output devices, marking the system default and the one saved as `selected_audio_output`.
+/// Saving another `selected_audio_output` moves playback to it at the same position.
+#[tauri::command]
+pub async fn list_audio_outputs() -> Result<Vec<tts::AudioOutput>, AppError> {
+    tokio::task::spawn_blocking(tts::audio_outputs)
+        .await
+        .map_err(|e| AppError::from(format!("spawn_blocking: {e}")))
 }
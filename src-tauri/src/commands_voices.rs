@@ -1,60 +1,260 @@
-//! Tauri commands for voice listing and Piper voice download.
+//! Tauri commands for voice listing and Piper voice download and management.
 
+use serde::Serialize;
+use tauri::State;
+use tracing::info;
+
+use crate::commands_config::ConfigState;
+use crate::error::AppError;
+use crate::offline;
+use crate::tasks::{TaskHandle, TaskKind, TaskManager};
+use crate::tts;
 use crate::voices;
 use crate::voices::download::{
-    get_current_progress, list_downloaded_voices as list_local_downloaded_voices, DownloadProgress,
-    DownloadedVoice,
+    active_downloads, download_task_id, find_downloaded_voice,
+    list_downloaded_voices as list_local_downloaded_voices, DownloadProgress, DownloadedVoice,
 };
 
 #[tauri::command]
-pub async fn list_piper_voices() -> Result<Vec<voices::VoiceInfo>, String> {
-    let voices = voices::fetch_piper_voices(false).await?;
+pub async fn list_piper_voices() -> Result<Vec<voices::VoiceInfo>, AppError> {
+    let voices = voices::fetch_piper_voices(false)
+        .await
+        .map_err(AppError::network)?;
     Ok(voices.into_values().collect())
 }
 
 #[tauri::command]
-pub async fn refresh_piper_voices() -> Result<Vec<voices::VoiceInfo>, String> {
-    let voices = voices::fetch_piper_voices(true).await?;
+pub async fn refresh_piper_voices() -> Result<Vec<voices::VoiceInfo>, AppError> {
+    let voices = voices::fetch_piper_voices(true)
+        .await
+        .map_err(AppError::network)?;
     Ok(voices.into_values().collect())
 }
 
+/// Lists the Polly voices from the cache; `refresh` fetches them again.
 #[tauri::command]
-pub async fn list_polly_voices() -> Result<Vec<voices::PollyVoiceInfo>, String> {
-    voices::fetch_polly_voices().await
+pub async fn list_polly_voices(
+    refresh: Option<bool>,
+) -> Result<Vec<voices::PollyVoiceInfo>, AppError> {
+    voices::fetch_polly_voices(refresh.unwrap_or(false))
+        .await
+        .map_err(AppError::network)
 }
 
+/// Lists the Microsoft voices from the cache; `refresh` fetches them again.
 #[tauri::command]
-pub async fn list_microsoft_voices() -> Result<Vec<voices::MicrosoftVoiceInfo>, String> {
-    voices::fetch_microsoft_voices().await
+pub async fn list_microsoft_voices(
+    refresh: Option<bool>,
+) -> Result<Vec<voices::MicrosoftVoiceInfo>, AppError> {
+    voices::fetch_microsoft_voices(refresh.unwrap_or(false))
+        .await
+        .map_err(AppError::network)
 }
 
+/// Lists the speakers of the custom voice server (`url` defaults to the configured server).
 #[tauri::command]
-pub async fn download_voice(voice_key: String) -> Result<String, String> {
-    let voices = voices::fetch_piper_voices(false).await?;
+pub async fn list_custom_server_voices(
+    state: State<'_, ConfigState>,
+    url: Option<String>,
+) -> Result<Vec<voices::CustomServerVoice>, AppError> {
+    let url = match url.filter(|u| !u.trim().is_empty()) {
+        Some(url) => url,
+        None => state
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?
+            .custom_tts_url
+            .clone()
+            .filter(|u| !u.trim().is_empty())
+            .unwrap_or_else(|| tts::DEFAULT_CUSTOM_SERVER_URL.to_string()),
+    };
+    voices::fetch_custom_server_voices(&url)
+        .await
+        .map_err(AppError::network)
+}
+
+/// Downloads a Piper voice as a background task (listed by `list_background_tasks`, cancellable
+/// with `cancel_task` or `cancel_download`). A download that failed earlier resumes. Returns the voice directory on success.
+#[tauri::command]
+pub async fn download_voice(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    voice_key: String,
+) -> Result<String, AppError> {
+    offline::ensure_online()?;
+    let mut task = tasks.start(
+        &app,
+        TaskKind::VoiceDownload,
+        format!("Downloading voice {voice_key}"),
+    );
+    let result = download_voice_task(&voice_key, &mut task).await;
+    task.finish(&result);
+    result
+}
+
+async fn download_voice_task(voice_key: &str, task: &mut TaskHandle) -> Result<String, AppError> {
+    let voices = voices::fetch_piper_voices(false)
+        .await
+        .map_err(AppError::network)?;
     let voice_info = voices
-        .get(&voice_key)
-        .ok_or_else(|| format!("Voice not found: {}", voice_key))?;
+        .get(voice_key)
+        .ok_or_else(|| voice_not_found(voice_key))?;
 
     // If files are empty, force refresh to get the full data with files
     if voice_info.files.is_empty() {
-        let voices = voices::fetch_piper_voices(true).await?;
+        let voices = voices::fetch_piper_voices(true)
+            .await
+            .map_err(AppError::network)?;
         let voice_info = voices
-            .get(&voice_key)
-            .ok_or_else(|| format!("Voice not found: {}", voice_key))?;
-        let path = voices::download::download_voice(&voice_key, voice_info).await?;
+            .get(voice_key)
+            .ok_or_else(|| voice_not_found(voice_key))?;
+        let path = voices::download::download_voice(voice_key, voice_info, task)
+            .await
+            .map_err(AppError::network)?;
         return Ok(path.to_string_lossy().to_string());
     }
 
-    let path = voices::download::download_voice(&voice_key, voice_info).await?;
+    let path = voices::download::download_voice(voice_key, voice_info, task)
+        .await
+        .map_err(AppError::network)?;
     Ok(path.to_string_lossy().to_string())
 }
 
+fn voice_not_found(voice_key: &str) -> AppError {
+    AppError::NotFound(format!("Voice not found: {voice_key}"))
+}
+
+/// Progress of the running voice downloads, one entry per voice.
+#[tauri::command]
+pub fn get_download_progress() -> Vec<DownloadProgress> {
+    active_downloads()
+}
+
+/// Cancels the running download of `voice_key` and removes its partial files.
+#[tauri::command]
+pub fn cancel_download(tasks: State<'_, TaskManager>, voice_key: String) -> Result<(), AppError> {
+    let task_id = download_task_id(&voice_key)
+        .ok_or_else(|| AppError::NotFound(format!("Voice {voice_key} is not downloading")))?;
+    Ok(tasks.cancel(&task_id)?)
+}
+
+#[tauri::command]
+pub fn list_downloaded_voices() -> Result<Vec<DownloadedVoice>, AppError> {
+    Ok(list_local_downloaded_voices()?)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VoicesDiskUsage {
+    pub voices: Vec<DownloadedVoice>,
+    pub total_bytes: u64,
+}
+
+/// Downloaded voices with their sizes, largest first, and the total.
+#[tauri::command]
+pub fn get_voices_disk_usage() -> Result<VoicesDiskUsage, AppError> {
+    let mut voices = list_local_downloaded_voices()?;
+    voices.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+    let total_bytes = voices.iter().map(|v| v.size_bytes).sum();
+    Ok(VoicesDiskUsage {
+        voices,
+        total_bytes,
+    })
+}
+
+/// Deletes a downloaded voice. The selected voice can't be deleted. Returns the bytes freed.
+#[tauri::command]
+pub fn delete_downloaded_voice(
+    state: State<'_, ConfigState>,
+    voice_key: String,
+) -> Result<u64, AppError> {
+    let selected = state
+        .lock()
+        .map_err(|_| "Config lock poisoned".to_string())?
+        .selected_voice
+        .clone();
+    if selected.as_deref() == Some(voice_key.as_str()) {
+        return Err(AppError::InvalidInput(format!(
+            "Voice {voice_key} is the selected voice. Choose another voice before deleting it."
+        )));
+    }
+    Ok(voices::download::delete_downloaded_voice(&voice_key)?)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VoiceIntegrity {
+    pub voice_key: String,
+    pub ok: bool,
+    pub problems: Vec<String>,
+}
+
+/// Checks a downloaded voice's files against the catalog: sizes always, MD5 checksums when
+/// `checksum` is set (slower; reads the whole model).
 #[tauri::command]
-pub fn get_download_progress() -> Option<DownloadProgress> {
-    get_current_progress()
+pub async fn verify_voice(
+    voice_key: String,
+    checksum: Option<bool>,
+) -> Result<VoiceIntegrity, AppError> {
+    let voice = find_downloaded_voice(&voice_key)?
+        .ok_or_else(|| AppError::NotFound(format!("Voice {voice_key} is not downloaded")))?;
+    let voices = voices::fetch_piper_voices(false)
+        .await
+        .map_err(AppError::network)?;
+    let mut voice_info = voices
+        .get(&voice_key)
+        .cloned()
+        .ok_or_else(|| voice_not_found(&voice_key))?;
+    if voice_info.files.is_empty() {
+        voice_info = voices::fetch_piper_voices(true)
+            .await
+            .map_err(AppError::network)?
+            .remove(&voice_key)
+            .ok_or_else(|| voice_not_found(&voice_key))?;
+    }
+    let checksum = checksum.unwrap_or(false);
+    let key = voice_key.clone();
+    let problems = tokio::task::spawn_blocking(move || {
+        voices::validate::check_integrity(&key, &voice.path, &voice_info, checksum)
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking: {e}"))?;
+    info!(voice_key = %voice_key, checksum, problems = problems.len(), "Checked voice integrity");
+    Ok(VoiceIntegrity {
+        voice_key,
+        ok: problems.is_empty(),
+        problems,
+    })
 }
 
+/// Repairs a voice by deleting its files and downloading it again, as a background task like
+/// `download_voice`. Returns the voice directory on success.
 #[tauri::command]
-pub fn list_downloaded_voices() -> Result<Vec<DownloadedVoice>, String> {
-    list_local_downloaded_voices()
+pub async fn repair_voice(
+    app: tauri::AppHandle,
+    tasks: State<'_, TaskManager>,
+    voice_key: String,
+) -> Result<String, AppError> {
+    // Repairing deletes the files before downloading them again.
+    offline::ensure_online()?;
+    let mut task = tasks.start(
+        &app,
+        TaskKind::VoiceDownload,
+        format!("Repairing voice {voice_key}"),
+    );
+    let result = repair_voice_task(&voice_key, &mut task).await;
+    task.finish(&result);
+    result
+}
+
+async fn repair_voice_task(voice_key: &str, task: &mut TaskHandle) -> Result<String, AppError> {
+    // Don't delete a voice that can't be downloaded again.
+    if !voices::fetch_piper_voices(false)
+        .await
+        .map_err(AppError::network)?
+        .contains_key(voice_key)
+    {
+        return Err(voice_not_found(voice_key));
+    }
+    if find_downloaded_voice(voice_key)?.is_some() {
+        voices::download::delete_downloaded_voice(voice_key)?;
+    }
+    download_voice_task(voice_key, task).await
 }
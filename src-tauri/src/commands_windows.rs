@@ -5,6 +5,7 @@ use tauri::window::{Effect, EffectsBuilder};
 use tauri::{LogicalSize, Manager, WebviewWindowBuilder};
 use tracing::warn;
 
+use crate::error::AppError;
 use crate::tray;
 use crate::windows;
 
@@ -69,7 +70,7 @@ pub fn show_main_window_impl<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
 }
 
 #[tauri::command]
-pub fn open_settings_window(app: tauri::AppHandle) -> Result<(), String> {
+pub fn open_settings_window(app: tauri::AppHandle) -> Result<(), AppError> {
     if let Some(win) = app.get_webview_window("settings") {
         win.show().map_err(|e| e.to_string())?;
         win.set_focus().map_err(|e| e.to_string())?;
@@ -106,6 +107,6 @@ pub fn open_settings_window(app: tauri::AppHandle) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn hide_main_window(app: tauri::AppHandle, to_tray: Option<bool>) -> Result<(), String> {
-    hide_main_window_impl(&app, to_tray.unwrap_or(false))
+pub fn hide_main_window(app: tauri::AppHandle, to_tray: Option<bool>) -> Result<(), AppError> {
+    Ok(hide_main_window_impl(&app, to_tray.unwrap_or(false))?)
 }
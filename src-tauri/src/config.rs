@@ -1,61 +1,47 @@
 //! Persistent configuration handling for Insight Reader.
 //!
 //! Persists configuration in a JSON file:
-//! `~/.config/insight-reader/config.json`.
+//! `~/.config/insight-reader/config.json` (see `paths::get_config_dir`).
+//!
+//! In memory (and towards the frontend) the config is the flat `FullConfig`. On disk it is
+//! grouped into sections (`tts`, `hotkeys`, `backend`, ...) under a `config_version`. Files from
+//! before versioning are read as they are and rewritten once at startup (`migrate_config_file`),
+//! keeping a `config.v1.json.bak` copy: older builds only read the flat format, so going back to
+//! one means restoring that copy. A file from a newer version is backed up before this version
+//! overwrites it. Writes go through a temp file (`util::write_atomic`), so the config watcher
+//! never sees a half-written file.
 
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use dirs::config_dir;
 use nanoid::nanoid;
 use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::paths;
+use crate::util;
+
+pub(crate) const CONFIG_FILE_NAME: &str = "config.json";
 
-const APP_CONFIG_DIR_NAME: &str = "insight-reader";
-const CONFIG_FILE_NAME: &str = "config.json";
+/// Format version written to `config_version`. Version 1 was a flat object without the field.
+pub const CONFIG_VERSION: u32 = 2;
 
-fn config_path() -> Option<PathBuf> {
-    let path = config_dir()?
-        .join(APP_CONFIG_DIR_NAME)
-        .join(CONFIG_FILE_NAME);
-    Some(path)
+fn config_path() -> Result<PathBuf, String> {
+    Ok(paths::get_config_dir()?.join(CONFIG_FILE_NAME))
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
-struct RawConfig {
-    #[serde(default)]
-    backend_url: Option<String>,
-    #[serde(default)]
-    voice_provider: Option<String>,
-    #[serde(default)]
-    log_level: Option<String>,
-    #[serde(default)]
-    selected_voice: Option<String>,
-    #[serde(default)]
-    selected_polly_voice: Option<String>,
-    #[serde(default)]
-    selected_microsoft_voice: Option<String>,
+/// One action's global shortcut in `hotkeys`, keyed by action name (see `hotkeys`). Without a
+/// key the action keeps its default shortcut; missing modifiers fall back to `hotkey_modifiers`;
+/// `enabled: false` or an empty key unassigns it.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct HotkeyBinding {
     #[serde(default)]
-    hotkey_enabled: Option<bool>,
-    #[serde(default)]
-    hotkey_modifiers: Option<String>,
-    #[serde(default)]
-    hotkey_key: Option<String>,
-    #[serde(default)]
-    ui_volume: Option<u8>,
-    #[serde(default)]
-    ui_muted: Option<bool>,
-    #[serde(default)]
-    ui_theme: Option<String>,
-    #[serde(default)]
-    ui_playback_speed: Option<f64>,
-    #[serde(default)]
-    summary_muted: Option<bool>,
-    #[serde(default)]
-    explain_mode: Option<String>,
+    pub modifiers: Option<String>,
     #[serde(default)]
-    editor_dark_mode: Option<bool>,
+    pub key: Option<String>,
     #[serde(default)]
-    installation_id: Option<String>,
+    pub enabled: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -77,52 +63,457 @@ pub struct FullConfig {
     pub explain_mode: Option<String>,
     pub editor_dark_mode: Option<bool>,
     pub installation_id: Option<String>,
+    pub audio_cache_max_mb: Option<u32>,
+    pub ocr_cache_max_mb: Option<u32>,
+    pub log_retention_days: Option<u32>,
+    pub playback_trace_enabled: Option<bool>,
+    pub ui_language: Option<String>,
+    pub calibrated_speeds: Option<HashMap<String, f32>>,
+    pub ocr_preprocess: Option<bool>,
+    pub ocr_upscale: Option<bool>,
+    pub synthesis_priority: Option<String>,
+    pub inference_backend: Option<String>,
+    pub preprocessing_stages: Option<Vec<String>>,
+    pub proofread_max_words: Option<u32>,
+    pub proofread_max_grade: Option<f32>,
+    pub profanity_filter: Option<String>,
+    pub ocr_cleanup: Option<bool>,
+    pub stale_file_max_age_hours: Option<u32>,
+    pub capture_concurrency: Option<u32>,
+    pub ssml_generation: Option<bool>,
+    pub ssml_rate: Option<i32>,
+    pub ssml_pitch: Option<i32>,
+    pub ssml_paragraph_break_ms: Option<u32>,
+    pub ssml_say_as: Option<bool>,
+    pub experimental: Option<HashMap<String, bool>>,
+    pub selected_system_voice: Option<String>,
+    pub mic_auto_pause: Option<bool>,
+    pub mic_auto_resume: Option<bool>,
+    pub custom_tts_url: Option<String>,
+    pub custom_tts_speaker: Option<String>,
+    pub custom_tts_language: Option<String>,
+    pub provider_fallbacks: Option<Vec<String>>,
+    pub voice_map: Option<HashMap<String, String>>,
+    pub announce_reads: Option<bool>,
+    pub selected_mobile_voice: Option<String>,
+    pub hotkeys: Option<HashMap<String, HotkeyBinding>>,
+    pub ssml_language_tags: Option<bool>,
+    pub http_api_enabled: Option<bool>,
+    pub http_api_port: Option<u16>,
+    pub clipboard_watch: Option<bool>,
+    pub clipboard_watch_min_chars: Option<u32>,
+    pub clipboard_watch_ignore_apps: Option<Vec<String>>,
+    pub text_cleanup_mode: Option<String>,
+    pub text_cleanup_url: Option<String>,
+    pub expand_urls: Option<bool>,
+    pub playback_speed: Option<f32>,
+    pub playback_pitch: Option<i32>,
+    pub playback_volume: Option<u8>,
+    pub voice_mirrors: Option<Vec<String>>,
+    pub polly_region: Option<String>,
+    pub polly_profile: Option<String>,
+    pub polly_engine: Option<String>,
+    pub usage_monthly_char_budget: Option<u64>,
+    pub usage_budget_action: Option<String>,
+    pub offline_mode: Option<bool>,
+    pub selected_audio_output: Option<String>,
+    pub duck_background_audio: Option<bool>,
+    pub duck_level: Option<u8>,
+    pub sentence_pause_ms: Option<u32>,
+    pub skim_speed: Option<f32>,
+    pub translate_language: Option<String>,
+    pub translate_url: Option<String>,
+    pub backend_retry_attempts: Option<u32>,
+    pub backend_retry_delay_ms: Option<u32>,
+    pub backend_cache_enabled: Option<bool>,
+    pub backend_cache_ttl_hours: Option<u32>,
+    pub llm_provider: Option<String>,
+    pub llm_model: Option<String>,
+    pub llm_url: Option<String>,
+    pub resume_after_wake: Option<bool>,
+    pub history_enabled: Option<bool>,
+}
+
+/// On-disk config file (format version 2): the `FullConfig` fields grouped into sections.
+/// Missing sections and fields default to unset.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct ConfigFile {
+    config_version: u32,
+    general: GeneralSection,
+    backend: BackendSection,
+    tts: TtsSection,
+    ssml: SsmlSection,
+    text: TextSection,
+    ocr: OcrSection,
+    hotkeys: HotkeysSection,
+    ui: UiSection,
+    storage: StorageSection,
+    integrations: IntegrationsSection,
+}
+
+/// App-wide settings.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct GeneralSection {
+    log_level: Option<String>,
+    installation_id: Option<String>,
+    capture_concurrency: Option<u32>,
+    experimental: Option<HashMap<String, bool>>,
+    offline_mode: Option<bool>,
+}
+
+/// The summary/explain backend and the tasks sent to it.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct BackendSection {
+    url: Option<String>,
+    explain_mode: Option<String>,
+    cleanup_mode: Option<String>,
+    cleanup_url: Option<String>,
+    proofread_max_words: Option<u32>,
+    proofread_max_grade: Option<f32>,
+    translate_language: Option<String>,
+    translate_url: Option<String>,
+    retry_attempts: Option<u32>,
+    retry_delay_ms: Option<u32>,
+    cache_enabled: Option<bool>,
+    cache_ttl_hours: Option<u32>,
+    llm_provider: Option<String>,
+    llm_model: Option<String>,
+    llm_url: Option<String>,
+}
+
+/// Providers, voices and playback.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct TtsSection {
+    provider: Option<String>,
+    piper_voice: Option<String>,
+    polly_voice: Option<String>,
+    microsoft_voice: Option<String>,
+    system_voice: Option<String>,
+    mobile_voice: Option<String>,
+    custom_url: Option<String>,
+    custom_speaker: Option<String>,
+    custom_language: Option<String>,
+    provider_fallbacks: Option<Vec<String>>,
+    voice_map: Option<HashMap<String, String>>,
+    voice_mirrors: Option<Vec<String>>,
+    calibrated_speeds: Option<HashMap<String, f32>>,
+    synthesis_priority: Option<String>,
+    inference_backend: Option<String>,
+    announce_reads: Option<bool>,
+    playback_speed: Option<f32>,
+    playback_pitch: Option<i32>,
+    playback_volume: Option<u8>,
+    playback_trace_enabled: Option<bool>,
+    polly_region: Option<String>,
+    polly_profile: Option<String>,
+    polly_engine: Option<String>,
+    usage_monthly_char_budget: Option<u64>,
+    usage_budget_action: Option<String>,
+    selected_audio_output: Option<String>,
+    duck_background_audio: Option<bool>,
+    duck_level: Option<u8>,
+    sentence_pause_ms: Option<u32>,
+    skim_speed: Option<f32>,
+}
+
+/// SSML generation for the cloud providers.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct SsmlSection {
+    generation: Option<bool>,
+    rate: Option<i32>,
+    pitch: Option<i32>,
+    paragraph_break_ms: Option<u32>,
+    say_as: Option<bool>,
+    language_tags: Option<bool>,
+}
+
+/// Text preprocessing before reading.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct TextSection {
+    preprocessing_stages: Option<Vec<String>>,
+    profanity_filter: Option<String>,
+    expand_urls: Option<bool>,
+}
+
+/// Screen capture OCR.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct OcrSection {
+    preprocess: Option<bool>,
+    upscale: Option<bool>,
+    cleanup: Option<bool>,
 }
 
-impl From<RawConfig> for FullConfig {
-    fn from(raw: RawConfig) -> Self {
+/// Global shortcuts.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct HotkeysSection {
+    enabled: Option<bool>,
+    modifiers: Option<String>,
+    key: Option<String>,
+    bindings: Option<HashMap<String, HotkeyBinding>>,
+}
+
+/// Window and player UI state.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct UiSection {
+    volume: Option<u8>,
+    muted: Option<bool>,
+    theme: Option<String>,
+    playback_speed: Option<f64>,
+    language: Option<String>,
+    summary_muted: Option<bool>,
+    editor_dark_mode: Option<bool>,
+}
+
+/// Cache size caps and retention.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct StorageSection {
+    audio_cache_max_mb: Option<u32>,
+    ocr_cache_max_mb: Option<u32>,
+    log_retention_days: Option<u32>,
+    stale_file_max_age_hours: Option<u32>,
+    history_enabled: Option<bool>,
+}
+
+/// Clipboard watch, the local HTTP API and microphone auto-pause.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+struct IntegrationsSection {
+    clipboard_watch: Option<bool>,
+    clipboard_watch_min_chars: Option<u32>,
+    clipboard_watch_ignore_apps: Option<Vec<String>>,
+    http_api_enabled: Option<bool>,
+    http_api_port: Option<u16>,
+    mic_auto_pause: Option<bool>,
+    mic_auto_resume: Option<bool>,
+    resume_after_wake: Option<bool>,
+}
+
+impl From<ConfigFile> for FullConfig {
+    fn from(file: ConfigFile) -> Self {
+        let ConfigFile {
+            config_version: _,
+            general,
+            backend,
+            tts,
+            ssml,
+            text,
+            ocr,
+            hotkeys,
+            ui,
+            storage,
+            integrations,
+        } = file;
         Self {
-            backend_url: raw.backend_url,
-            voice_provider: raw.voice_provider,
-            log_level: raw.log_level,
-            selected_voice: raw.selected_voice,
-            selected_polly_voice: raw.selected_polly_voice,
-            selected_microsoft_voice: raw.selected_microsoft_voice,
-            hotkey_enabled: raw.hotkey_enabled,
-            hotkey_modifiers: raw.hotkey_modifiers,
-            hotkey_key: raw.hotkey_key,
-            ui_volume: raw.ui_volume,
-            ui_muted: raw.ui_muted,
-            ui_theme: raw.ui_theme,
-            ui_playback_speed: raw.ui_playback_speed,
-            summary_muted: raw.summary_muted,
-            explain_mode: raw.explain_mode,
-            editor_dark_mode: raw.editor_dark_mode,
-            installation_id: raw.installation_id,
+            backend_url: backend.url,
+            voice_provider: tts.provider,
+            log_level: general.log_level,
+            selected_voice: tts.piper_voice,
+            selected_polly_voice: tts.polly_voice,
+            selected_microsoft_voice: tts.microsoft_voice,
+            hotkey_enabled: hotkeys.enabled,
+            hotkey_modifiers: hotkeys.modifiers,
+            hotkey_key: hotkeys.key,
+            ui_volume: ui.volume,
+            ui_muted: ui.muted,
+            ui_theme: ui.theme,
+            ui_playback_speed: ui.playback_speed,
+            summary_muted: ui.summary_muted,
+            explain_mode: backend.explain_mode,
+            editor_dark_mode: ui.editor_dark_mode,
+            installation_id: general.installation_id,
+            audio_cache_max_mb: storage.audio_cache_max_mb,
+            ocr_cache_max_mb: storage.ocr_cache_max_mb,
+            log_retention_days: storage.log_retention_days,
+            playback_trace_enabled: tts.playback_trace_enabled,
+            ui_language: ui.language,
+            calibrated_speeds: tts.calibrated_speeds,
+            ocr_preprocess: ocr.preprocess,
+            ocr_upscale: ocr.upscale,
+            synthesis_priority: tts.synthesis_priority,
+            inference_backend: tts.inference_backend,
+            preprocessing_stages: text.preprocessing_stages,
+            proofread_max_words: backend.proofread_max_words,
+            proofread_max_grade: backend.proofread_max_grade,
+            profanity_filter: text.profanity_filter,
+            ocr_cleanup: ocr.cleanup,
+            stale_file_max_age_hours: storage.stale_file_max_age_hours,
+            capture_concurrency: general.capture_concurrency,
+            ssml_generation: ssml.generation,
+            ssml_rate: ssml.rate,
+            ssml_pitch: ssml.pitch,
+            ssml_paragraph_break_ms: ssml.paragraph_break_ms,
+            ssml_say_as: ssml.say_as,
+            experimental: general.experimental,
+            selected_system_voice: tts.system_voice,
+            mic_auto_pause: integrations.mic_auto_pause,
+            mic_auto_resume: integrations.mic_auto_resume,
+            custom_tts_url: tts.custom_url,
+            custom_tts_speaker: tts.custom_speaker,
+            custom_tts_language: tts.custom_language,
+            provider_fallbacks: tts.provider_fallbacks,
+            voice_map: tts.voice_map,
+            announce_reads: tts.announce_reads,
+            selected_mobile_voice: tts.mobile_voice,
+            hotkeys: hotkeys.bindings,
+            ssml_language_tags: ssml.language_tags,
+            http_api_enabled: integrations.http_api_enabled,
+            http_api_port: integrations.http_api_port,
+            clipboard_watch: integrations.clipboard_watch,
+            clipboard_watch_min_chars: integrations.clipboard_watch_min_chars,
+            clipboard_watch_ignore_apps: integrations.clipboard_watch_ignore_apps,
+            text_cleanup_mode: backend.cleanup_mode,
+            text_cleanup_url: backend.cleanup_url,
+            expand_urls: text.expand_urls,
+            playback_speed: tts.playback_speed,
+            playback_pitch: tts.playback_pitch,
+            playback_volume: tts.playback_volume,
+            voice_mirrors: tts.voice_mirrors,
+            polly_region: tts.polly_region,
+            polly_profile: tts.polly_profile,
+            polly_engine: tts.polly_engine,
+            usage_monthly_char_budget: tts.usage_monthly_char_budget,
+            usage_budget_action: tts.usage_budget_action,
+            offline_mode: general.offline_mode,
+            selected_audio_output: tts.selected_audio_output,
+            duck_background_audio: tts.duck_background_audio,
+            duck_level: tts.duck_level,
+            sentence_pause_ms: tts.sentence_pause_ms,
+            skim_speed: tts.skim_speed,
+            translate_language: backend.translate_language,
+            translate_url: backend.translate_url,
+            backend_retry_attempts: backend.retry_attempts,
+            backend_retry_delay_ms: backend.retry_delay_ms,
+            backend_cache_enabled: backend.cache_enabled,
+            backend_cache_ttl_hours: backend.cache_ttl_hours,
+            llm_provider: backend.llm_provider,
+            llm_model: backend.llm_model,
+            llm_url: backend.llm_url,
+            resume_after_wake: integrations.resume_after_wake,
+            history_enabled: storage.history_enabled,
         }
     }
 }
 
-impl From<FullConfig> for RawConfig {
-    fn from(json: FullConfig) -> Self {
+impl From<FullConfig> for ConfigFile {
+    fn from(config: FullConfig) -> Self {
         Self {
-            backend_url: json.backend_url,
-            voice_provider: json.voice_provider,
-            log_level: json.log_level,
-            selected_voice: json.selected_voice,
-            selected_polly_voice: json.selected_polly_voice,
-            selected_microsoft_voice: json.selected_microsoft_voice,
-            hotkey_enabled: json.hotkey_enabled,
-            hotkey_modifiers: json.hotkey_modifiers,
-            hotkey_key: json.hotkey_key,
-            ui_volume: json.ui_volume,
-            ui_muted: json.ui_muted,
-            ui_theme: json.ui_theme,
-            ui_playback_speed: json.ui_playback_speed,
-            summary_muted: json.summary_muted,
-            explain_mode: json.explain_mode,
-            editor_dark_mode: json.editor_dark_mode,
-            installation_id: json.installation_id,
+            config_version: CONFIG_VERSION,
+            general: GeneralSection {
+                log_level: config.log_level,
+                installation_id: config.installation_id,
+                capture_concurrency: config.capture_concurrency,
+                experimental: config.experimental,
+                offline_mode: config.offline_mode,
+            },
+            backend: BackendSection {
+                url: config.backend_url,
+                explain_mode: config.explain_mode,
+                cleanup_mode: config.text_cleanup_mode,
+                cleanup_url: config.text_cleanup_url,
+                proofread_max_words: config.proofread_max_words,
+                proofread_max_grade: config.proofread_max_grade,
+                translate_language: config.translate_language,
+                translate_url: config.translate_url,
+                retry_attempts: config.backend_retry_attempts,
+                retry_delay_ms: config.backend_retry_delay_ms,
+                cache_enabled: config.backend_cache_enabled,
+                cache_ttl_hours: config.backend_cache_ttl_hours,
+                llm_provider: config.llm_provider,
+                llm_model: config.llm_model,
+                llm_url: config.llm_url,
+            },
+            tts: TtsSection {
+                provider: config.voice_provider,
+                piper_voice: config.selected_voice,
+                polly_voice: config.selected_polly_voice,
+                microsoft_voice: config.selected_microsoft_voice,
+                system_voice: config.selected_system_voice,
+                mobile_voice: config.selected_mobile_voice,
+                custom_url: config.custom_tts_url,
+                custom_speaker: config.custom_tts_speaker,
+                custom_language: config.custom_tts_language,
+                provider_fallbacks: config.provider_fallbacks,
+                voice_map: config.voice_map,
+                voice_mirrors: config.voice_mirrors,
+                calibrated_speeds: config.calibrated_speeds,
+                synthesis_priority: config.synthesis_priority,
+                inference_backend: config.inference_backend,
+                announce_reads: config.announce_reads,
+                playback_speed: config.playback_speed,
+                playback_pitch: config.playback_pitch,
+                playback_volume: config.playback_volume,
+                playback_trace_enabled: config.playback_trace_enabled,
+                polly_region: config.polly_region,
+                polly_profile: config.polly_profile,
+                polly_engine: config.polly_engine,
+                usage_monthly_char_budget: config.usage_monthly_char_budget,
+                usage_budget_action: config.usage_budget_action,
+                selected_audio_output: config.selected_audio_output,
+                duck_background_audio: config.duck_background_audio,
+                duck_level: config.duck_level,
+                sentence_pause_ms: config.sentence_pause_ms,
+                skim_speed: config.skim_speed,
+            },
+            ssml: SsmlSection {
+                generation: config.ssml_generation,
+                rate: config.ssml_rate,
+                pitch: config.ssml_pitch,
+                paragraph_break_ms: config.ssml_paragraph_break_ms,
+                say_as: config.ssml_say_as,
+                language_tags: config.ssml_language_tags,
+            },
+            text: TextSection {
+                preprocessing_stages: config.preprocessing_stages,
+                profanity_filter: config.profanity_filter,
+                expand_urls: config.expand_urls,
+            },
+            ocr: OcrSection {
+                preprocess: config.ocr_preprocess,
+                upscale: config.ocr_upscale,
+                cleanup: config.ocr_cleanup,
+            },
+            hotkeys: HotkeysSection {
+                enabled: config.hotkey_enabled,
+                modifiers: config.hotkey_modifiers,
+                key: config.hotkey_key,
+                bindings: config.hotkeys,
+            },
+            ui: UiSection {
+                volume: config.ui_volume,
+                muted: config.ui_muted,
+                theme: config.ui_theme,
+                playback_speed: config.ui_playback_speed,
+                language: config.ui_language,
+                summary_muted: config.summary_muted,
+                editor_dark_mode: config.editor_dark_mode,
+            },
+            storage: StorageSection {
+                audio_cache_max_mb: config.audio_cache_max_mb,
+                ocr_cache_max_mb: config.ocr_cache_max_mb,
+                log_retention_days: config.log_retention_days,
+                stale_file_max_age_hours: config.stale_file_max_age_hours,
+                history_enabled: config.history_enabled,
+            },
+            integrations: IntegrationsSection {
+                clipboard_watch: config.clipboard_watch,
+                clipboard_watch_min_chars: config.clipboard_watch_min_chars,
+                clipboard_watch_ignore_apps: config.clipboard_watch_ignore_apps,
+                http_api_enabled: config.http_api_enabled,
+                http_api_port: config.http_api_port,
+                mic_auto_pause: config.mic_auto_pause,
+                mic_auto_resume: config.mic_auto_resume,
+                resume_after_wake: config.resume_after_wake,
+            },
         }
     }
 }
@@ -143,25 +534,132 @@ pub fn get_or_create_installation_id() -> Result<String, String> {
 }
 
 pub fn load_full_config() -> Result<FullConfig, String> {
-    let path = config_path().ok_or("No config directory available")?;
+    let path = config_path()?;
     if !path.exists() {
         return Ok(FullConfig::default());
     }
     let data = fs::read_to_string(&path).map_err(|e| format!("Failed to read config: {}", e))?;
-    let raw: RawConfig =
-        serde_json::from_str(&data).map_err(|e| format!("Failed to parse config: {}", e))?;
-    Ok(raw.into())
+    let (config, version) = parse_config(&data)?;
+    if version > CONFIG_VERSION {
+        warn!(
+            version,
+            supported = CONFIG_VERSION,
+            "Config was written by a newer version; unknown settings are ignored"
+        );
+    }
+    Ok(config)
 }
 
 pub fn save_full_config(config: FullConfig) -> Result<(), String> {
-    let path = config_path().ok_or("No config directory available")?;
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    let path = config_path()?;
+    // Keep a copy of a file from a newer version before dropping the settings it has and this
+    // version doesn't know.
+    let existing_version = fs::read_to_string(&path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok())
+        .map(|value| file_version(&value))
+        .unwrap_or(CONFIG_VERSION);
+    if existing_version > CONFIG_VERSION {
+        backup_file(&path, existing_version);
     }
-    let raw: RawConfig = config.into();
-    let data = serde_json::to_string_pretty(&raw)
+    write_config(&path, config)
+}
+
+fn write_config(path: &Path, config: FullConfig) -> Result<(), String> {
+    let file: ConfigFile = config.into();
+    let data = serde_json::to_string_pretty(&file)
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    fs::write(&path, data).map_err(|e| format!("Failed to write config: {}", e))?;
-    Ok(())
+    util::write_atomic(path, data).map_err(|e| format!("Failed to write config: {}", e))
+}
+
+/// Parses a config file of any version. Returns the config and the file's format version.
+fn parse_config(data: &str) -> Result<(FullConfig, u32), String> {
+    let value: serde_json::Value =
+        serde_json::from_str(data).map_err(|e| format!("Failed to parse config: {}", e))?;
+    let version = file_version(&value);
+    let config = if version < 2 {
+        // Version 1: flat, with the `FullConfig` field names.
+        serde_json::from_value(value)
+    } else {
+        serde_json::from_value::<ConfigFile>(value).map(FullConfig::from)
+    }
+    .map_err(|e| format!("Failed to parse config: {}", e))?;
+    Ok((config, version))
+}
+
+/// `config_version` of a parsed file; files from before versioning are version 1.
+fn file_version(value: &serde_json::Value) -> u32 {
+    value
+        .get("config_version")
+        .and_then(serde_json::Value::as_u64)
+        .map_or(1, |v| u32::try_from(v).unwrap_or(u32::MAX))
+}
+
+/// Rewrites the active profile's config file in the current format if it is older, keeping the
+/// original as a backup. Runs at startup and on profile switch rather than in `load_full_config`,
+/// which the config watcher and most commands call.
+pub fn migrate_config_file() {
+    let Ok(path) = config_path() else {
+        return;
+    };
+    let Ok(data) = fs::read_to_string(&path) else {
+        return;
+    };
+    let (config, version) = match parse_config(&data) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            warn!(error = %e, "Not migrating unreadable config file");
+            return;
+        }
+    };
+    if version >= CONFIG_VERSION {
+        return;
+    }
+    backup_file(&path, version);
+    match write_config(&path, config) {
+        Ok(()) => info!(from = version, to = CONFIG_VERSION, "Migrated config file"),
+        Err(e) => warn!(error = %e, "Failed to migrate config file"),
+    }
+}
+
+/// Copies the config file to `config.v<version>.json.bak`, unless that backup already exists.
+fn backup_file(path: &Path, version: u32) {
+    let backup = path.with_file_name(format!("config.v{version}.json.bak"));
+    if backup.exists() {
+        return;
+    }
+    if let Err(e) = fs::copy(path, &backup) {
+        warn!(error = %e, backup = %backup.display(), "Failed to back up config file");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flat_config_migrates_to_sections() {
+        let v1 = r#"{"backend_url":"http://localhost:8080","selected_voice":"en_US-amy-low",
+            "hotkey_key":"R","ssml_rate":10,"hotkeys":{"summarize":{"key":"S"}},"unknown":1}"#;
+        let (config, version) = parse_config(v1).unwrap();
+        assert_eq!(version, 1);
+        assert_eq!(config.selected_voice.as_deref(), Some("en_US-amy-low"));
+
+        let file = serde_json::to_value(ConfigFile::from(config.clone())).unwrap();
+        assert_eq!(file["config_version"], CONFIG_VERSION);
+        assert_eq!(file["backend"]["url"], "http://localhost:8080");
+        assert_eq!(file["tts"]["piper_voice"], "en_US-amy-low");
+        assert_eq!(file["hotkeys"]["key"], "R");
+        assert_eq!(file["hotkeys"]["bindings"]["summarize"]["key"], "S");
+        assert_eq!(file["ssml"]["rate"], 10);
+
+        let (reloaded, version) = parse_config(&file.to_string()).unwrap();
+        assert_eq!(version, CONFIG_VERSION);
+        assert_eq!(
+            serde_json::to_value(reloaded).unwrap(),
+            serde_json::to_value(config).unwrap()
+        );
+        let (partial, _) = parse_config(r#"{"config_version":2,"ui":{"theme":"dark"}}"#).unwrap();
+        assert_eq!(partial.ui_theme.as_deref(), Some("dark"));
+    }
 }
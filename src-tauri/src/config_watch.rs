@@ -0,0 +1,200 @@
+//! Hot-reload of `config.json` edited outside the app (by hand, a sync tool, another instance).
+//!
+//! A background thread watches the config directory and, once writes have settled for
+//! `SETTLE_DELAY`, loads the file. When it differs from `ConfigState` the new config replaces it
+//! and is propagated like a save from the settings window (`commands_config::notify_config_changed`):
+//! runtime settings, hotkeys, tray, the TTS worker and a `config-changed` event. The app's own
+//! saves match the state already and are skipped; a file that does not parse, or whose hotkeys
+//! are invalid, is logged and ignored. A profile switch calls `retarget`, which moves the watch to
+//! the new profile's config directory.
+
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use tauri::Manager;
+use tracing::{debug, info, warn};
+
+use crate::commands_config::{self, ConfigState};
+use crate::config::{self, FullConfig, CONFIG_FILE_NAME};
+use crate::hotkeys;
+use crate::paths;
+
+/// Quiet time after the last change before the file is read (editors write in several steps).
+const SETTLE_DELAY: Duration = Duration::from_millis(300);
+
+enum Message {
+    Changed(notify::Event),
+    /// The active profile changed; watch its config directory instead.
+    Retarget,
+}
+
+/// Sender to the watch thread, once started.
+static WATCH: Mutex<Option<mpsc::Sender<Message>>> = Mutex::new(None);
+
+/// Watches `dir`, creating it if needed.
+fn watch_dir(watcher: &mut impl Watcher, dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Cannot create config directory {}: {e}", dir.display()))?;
+    // The directory, not the file: editors often save by replacing the file.
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Cannot watch {}: {e}", dir.display()))
+}
+
+/// Starts watching the config file. Does nothing (beyond a warning) when watching is unavailable.
+pub fn start<R: tauri::Runtime>(app: tauri::AppHandle<R>) {
+    let mut dir = match paths::get_config_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            warn!(error = %e, "Config watch not started: no config directory");
+            return;
+        }
+    };
+    let (tx, rx) = mpsc::channel();
+    let events = tx.clone();
+    let mut watcher =
+        match notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            if let Ok(event) = res {
+                let _ = events.send(Message::Changed(event));
+            }
+        }) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                warn!(error = %e, "Config watch not started");
+                return;
+            }
+        };
+    if let Err(e) = watch_dir(&mut watcher, &dir) {
+        warn!(error = %e, "Config watch not started");
+        return;
+    }
+    debug!(dir = %dir.display(), "Watching config file");
+    if let Ok(mut watch) = WATCH.lock() {
+        *watch = Some(tx);
+    }
+
+    std::thread::spawn(move || {
+        while let Ok(message) = rx.recv() {
+            match message {
+                Message::Changed(event) if is_config_change(&event) => {
+                    // Let the writes settle.
+                    let mut switched = false;
+                    while let Ok(message) = rx.recv_timeout(SETTLE_DELAY) {
+                        switched |= matches!(message, Message::Retarget);
+                    }
+                    if switched {
+                        retarget_watcher(&mut watcher, &mut dir);
+                    }
+                    reload(&app);
+                }
+                Message::Changed(_) => {}
+                Message::Retarget => retarget_watcher(&mut watcher, &mut dir),
+            }
+        }
+    });
+}
+
+/// Moves the watch to the active profile's config directory (see `retarget`).
+fn retarget_watcher(watcher: &mut impl Watcher, dir: &mut PathBuf) {
+    let new_dir = match paths::get_config_dir() {
+        Ok(new_dir) if new_dir != *dir => new_dir,
+        Ok(_) => return,
+        Err(e) => {
+            warn!(error = %e, "Config watch not moved: no config directory");
+            return;
+        }
+    };
+    if let Err(e) = watcher.unwatch(dir) {
+        debug!(error = %e, dir = %dir.display(), "Failed to stop watching config directory");
+    }
+    match watch_dir(watcher, &new_dir) {
+        Ok(()) => debug!(dir = %new_dir.display(), "Watching config file"),
+        Err(e) => warn!(error = %e, "Config watch stopped"),
+    }
+    *dir = new_dir;
+}
+
+/// Has the watcher follow the active profile's config file; call after a profile switch.
+pub fn retarget() {
+    if let Ok(watch) = WATCH.lock() {
+        if let Some(tx) = watch.as_ref() {
+            let _ = tx.send(Message::Retarget);
+        }
+    }
+}
+
+fn is_config_change(event: &notify::Event) -> bool {
+    !matches!(event.kind, EventKind::Access(_))
+        && event
+            .paths
+            .iter()
+            .any(|p| p.file_name() == Some(Path::new(CONFIG_FILE_NAME).as_os_str()))
+}
+
+fn reload<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let cfg = match config::load_full_config() {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            warn!(error = %e, "Config file changed but could not be loaded, keeping the current config");
+            return;
+        }
+    };
+    if let Err(e) = hotkeys::validate_config(&cfg) {
+        warn!(error = %e, "Config file changed but has invalid hotkeys, keeping the current config");
+        return;
+    }
+    let Some(state) = app.try_state::<ConfigState>() else {
+        return;
+    };
+    {
+        let Ok(mut shared) = state.lock() else {
+            return;
+        };
+        if same_config(&shared, &cfg) {
+            return;
+        }
+        *shared = cfg.clone();
+    }
+    info!("Config file changed on disk, reloading");
+    commands_config::apply_runtime_settings(&cfg);
+    commands_config::notify_config_changed(app);
+}
+
+/// `FullConfig` has no `PartialEq`; its JSON form is compared instead.
+fn same_config(a: &FullConfig, b: &FullConfig) -> bool {
+    match (serde_json::to_value(a), serde_json::to_value(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_config_file_changes_count() {
+        let event = |kind, path: &str| notify::Event::new(kind).add_path(path.into());
+        let modify = EventKind::Modify(notify::event::ModifyKind::Any);
+        assert!(is_config_change(&event(
+            modify,
+            "/cfg/insight-reader/config.json"
+        )));
+        assert!(!is_config_change(&event(
+            modify,
+            "/cfg/insight-reader/config.v1.json.bak"
+        )));
+        assert!(!is_config_change(&event(
+            EventKind::Access(notify::event::AccessKind::Any),
+            "/cfg/insight-reader/config.json"
+        )));
+
+        let mut a = FullConfig::default();
+        let b = a.clone();
+        assert!(same_config(&a, &b));
+        a.ui_theme = Some("dark".to_string());
+        assert!(!same_config(&a, &b));
+    }
+}
@@ -0,0 +1,156 @@
+//! D-Bus service for scripting on Linux, next to the action socket: the session bus name
+//! `org.insightreader.Reader` serves the `org.insightreader.Reader1` interface at
+//! `/org/insightreader/Reader`.
+//!
+//! Methods: `Read` (the selected text, like the read hotkey), `Pause` (pauses, or resumes when
+//! paused), `Stop` and `Speak(s text)`. The read-only properties `Playing` and `Paused` follow
+//! playback: a background thread polls the TTS worker every `POLL_INTERVAL` and emits
+//! `PropertiesChanged` when they change. For example:
+//!
+//! ```text
+//! busctl --user call org.insightreader.Reader /org/insightreader/Reader \
+//!     org.insightreader.Reader1 Speak s "Hello"
+//! ```
+//!
+//! Only the first instance gets the name; later ones hand their action to it over the socket.
+
+use std::sync::mpsc;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+use tracing::{debug, warn};
+use zbus::blocking::connection;
+use zbus::{fdo, interface};
+
+use crate::actions::{self, AppAction};
+use crate::history;
+use crate::tts;
+
+const BUS_NAME: &str = "org.insightreader.Reader";
+const OBJECT_PATH: &str = "/org/insightreader/Reader";
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+struct Reader {
+    app: AppHandle,
+    playing: bool,
+    paused: bool,
+}
+
+#[interface(name = "org.insightreader.Reader1")]
+impl Reader {
+    /// Reads the selected text (or the clipboard).
+    fn read(&self) {
+        actions::execute_action(&self.app, AppAction::ReadSelected, "dbus");
+    }
+
+    /// Pauses playback, or resumes it when paused.
+    fn pause(&self) {
+        actions::execute_action(&self.app, AppAction::TogglePause, "dbus");
+    }
+
+    fn stop(&self) {
+        actions::execute_action(&self.app, AppAction::Stop, "dbus");
+    }
+
+    /// Reads `text` aloud; returns once playback has started.
+    async fn speak(&self, text: String) -> fdo::Result<()> {
+        let tts_tx = self
+            .app
+            .try_state::<tts::TtsState>()
+            .map(|state| state.inner().clone())
+            .ok_or_else(|| fdo::Error::Failed("TTS is not available".to_string()))?;
+        history::begin("dbus", &text);
+        let result = tauri::async_runtime::spawn_blocking(move || {
+            let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+            tts_tx
+                .send(tts::TtsRequest::Speak(
+                    text,
+                    tts::InputKind::Text,
+                    None,
+                    resp_tx,
+                ))
+                .map_err(|e| format!("TTS channel: {e}"))?;
+            resp_rx
+                .recv()
+                .map_err(|_| "TTS worker disconnected".to_string())?
+                .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| format!("spawn_blocking: {e}"))
+        .and_then(|result| result);
+        if result.is_err() {
+            history::abandon();
+        }
+        result.map_err(fdo::Error::Failed)
+    }
+
+    #[zbus(property)]
+    fn playing(&self) -> bool {
+        self.playing
+    }
+
+    #[zbus(property)]
+    fn paused(&self) -> bool {
+        self.paused
+    }
+}
+
+/// Claims the bus name, serves the interface and follows playback state on a background thread.
+/// Called from lib's setup.
+pub fn start(app: AppHandle, tts_tx: tts::TtsState) {
+    std::thread::spawn(move || {
+        let reader = Reader {
+            app,
+            playing: false,
+            paused: false,
+        };
+        let connection = match connection::Builder::session()
+            .and_then(|builder| builder.name(BUS_NAME))
+            .and_then(|builder| builder.serve_at(OBJECT_PATH, reader))
+            .and_then(|builder| builder.build())
+        {
+            Ok(connection) => connection,
+            Err(e) => {
+                warn!(error = %e, name = BUS_NAME, "Failed to start the D-Bus service");
+                return;
+            }
+        };
+        let reader = match connection
+            .object_server()
+            .interface::<_, Reader>(OBJECT_PATH)
+        {
+            Ok(reader) => reader,
+            Err(e) => {
+                warn!(error = %e, "D-Bus service interface unavailable");
+                return;
+            }
+        };
+        debug!(name = BUS_NAME, "D-Bus service started");
+
+        let mut last = (false, false);
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let Some((playing, paused)) = tts::playback_status(&tts_tx) else {
+                break;
+            };
+            if (playing, paused) == last {
+                continue;
+            }
+            let mut state = reader.get_mut();
+            let emitter = reader.signal_emitter();
+            if state.playing != playing {
+                state.playing = playing;
+                if let Err(e) = tauri::async_runtime::block_on(state.playing_changed(emitter)) {
+                    debug!(error = %e, "Failed to emit Playing change");
+                }
+            }
+            if state.paused != paused {
+                state.paused = paused;
+                if let Err(e) = tauri::async_runtime::block_on(state.paused_changed(emitter)) {
+                    debug!(error = %e, "Failed to emit Paused change");
+                }
+            }
+            last = (playing, paused);
+        }
+    });
+}
@@ -0,0 +1,232 @@
+//! Crash reporting and the diagnostics report for bug reports.
+//!
+//! `init` installs a panic hook that writes `crash.json` (message, location, backtrace and a
+//! summary of the app state) to the app data dir before the default hook runs. At the next
+//! startup the marker is moved to `last-crash.json` and the crash is logged, so the previous run's
+//! crash stays available until another one replaces it. `get_diagnostics_report` bundles the
+//! environment, provider availability, hotkey status, recent errors from the log files and the
+//! last crash into one JSON blob.
+
+use std::backtrace::Backtrace;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+#[cfg(desktop)]
+use tauri::Manager;
+use tauri::State;
+use tracing::{info, warn};
+
+use crate::app_info::{self, AppInfo};
+use crate::backend::{self, BackendHealth};
+use crate::commands_config::ConfigState;
+use crate::config;
+#[cfg(desktop)]
+use crate::hotkeys::{self, HotkeyStatus};
+use crate::logging;
+use crate::paths;
+use crate::tts::{self, PollyDiagnostics, TtsProviderStatus};
+use crate::util::unix_millis_now;
+
+const CRASH_FILE_NAME: &str = "crash.json";
+const LAST_CRASH_FILE_NAME: &str = "last-crash.json";
+
+/// Log lines scanned for errors and warnings, and how many of those are kept.
+const ERROR_SCAN_LINES: usize = 2000;
+const MAX_RECENT_ERRORS: usize = 50;
+
+/// What the app was doing, kept current by `configure` so the panic hook need not take locks
+/// that the panicking thread may hold.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StateSummary {
+    pub voice_provider: Option<String>,
+    pub offline_mode: bool,
+    pub profile: Option<String>,
+}
+
+static STATE: RwLock<Option<StateSummary>> = RwLock::new(None);
+static PREVIOUS_RUN_CRASHED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// Unix time in milliseconds.
+    pub time: u64,
+    pub version: String,
+    pub thread: Option<String>,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub state: Option<StateSummary>,
+}
+
+fn crash_file(name: &str) -> Result<PathBuf, String> {
+    Ok(paths::get_app_data_dir()?.join(name))
+}
+
+/// Updates the state summary written with a crash.
+pub fn configure(cfg: &config::FullConfig) {
+    let summary = StateSummary {
+        voice_provider: cfg.voice_provider.clone(),
+        offline_mode: cfg.offline_mode.unwrap_or(false),
+        profile: paths::active_profile(),
+    };
+    if let Ok(mut state) = STATE.write() {
+        *state = Some(summary);
+    }
+}
+
+/// Moves a crash marker left by the previous run to `last-crash.json` and installs the panic hook.
+pub fn init() {
+    match (
+        crash_file(CRASH_FILE_NAME),
+        crash_file(LAST_CRASH_FILE_NAME),
+    ) {
+        (Ok(marker), Ok(last)) if marker.is_file() => {
+            PREVIOUS_RUN_CRASHED.store(true, Ordering::Relaxed);
+            match fs::rename(&marker, &last) {
+                Ok(()) => warn!(report = %last.display(), "The previous run crashed"),
+                Err(e) => warn!(error = %e, "The previous run crashed; could not keep its report"),
+            }
+        }
+        _ => {}
+    }
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic| {
+        let payload = panic.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Box<dyn Any>".to_string());
+        let report = CrashReport {
+            time: unix_millis_now(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            thread: std::thread::current().name().map(str::to_string),
+            message,
+            location: panic
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            backtrace: Backtrace::force_capture().to_string(),
+            state: STATE.try_read().ok().and_then(|state| state.clone()),
+        };
+        write_crash_marker(&report);
+        default_hook(panic);
+    }));
+}
+
+fn write_crash_marker(report: &CrashReport) {
+    let Ok(path) = crash_file(CRASH_FILE_NAME) else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Ok(json) = serde_json::to_string_pretty(report) {
+        let _ = fs::write(&path, json);
+    }
+}
+
+fn last_crash() -> Option<CrashReport> {
+    let path = crash_file(LAST_CRASH_FILE_NAME).ok()?;
+    let json = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// The last `MAX_RECENT_ERRORS` error and warning lines, oldest first.
+fn recent_errors(lines: &[String]) -> Vec<String> {
+    let mut errors: Vec<String> = lines
+        .iter()
+        .rev()
+        .filter(|line| matches!(line.split_whitespace().nth(1), Some("ERROR" | "WARN")))
+        .take(MAX_RECENT_ERRORS)
+        .cloned()
+        .collect();
+    errors.reverse();
+    errors
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsReport {
+    /// Unix time in milliseconds.
+    pub generated_at: u64,
+    pub app: AppInfo,
+    pub voice_provider: Option<String>,
+    pub providers: TtsProviderStatus,
+    pub polly: PollyDiagnostics,
+    #[cfg(desktop)]
+    pub hotkeys: Option<HotkeyStatus>,
+    pub backend: Option<BackendHealth>,
+    pub recent_errors: Vec<String>,
+    pub previous_run_crashed: bool,
+    pub last_crash: Option<CrashReport>,
+}
+
+/// Environment, provider status, hotkeys, recent errors and the last crash, for bug reports.
+/// Probes the cloud and custom voice services (see `tts::provider_status`), so it can take a few
+/// seconds.
+#[tauri::command]
+pub async fn get_diagnostics_report(
+    app: tauri::AppHandle,
+    state: State<'_, ConfigState>,
+) -> Result<DiagnosticsReport, String> {
+    let cfg = state
+        .lock()
+        .map_err(|_| "Config lock poisoned".to_string())?
+        .clone();
+    let recent_errors = match logging::recent_lines(ERROR_SCAN_LINES) {
+        Ok(lines) => recent_errors(&lines),
+        Err(e) => {
+            warn!(error = %e, "Could not read the log files");
+            Vec::new()
+        }
+    };
+    let providers = tts::provider_status().await;
+    #[cfg(not(desktop))]
+    let _ = &app;
+    let report = DiagnosticsReport {
+        generated_at: unix_millis_now(),
+        app: app_info::app_info(&cfg),
+        voice_provider: cfg.voice_provider.clone(),
+        providers,
+        polly: tts::polly_diagnostics(),
+        #[cfg(desktop)]
+        hotkeys: app
+            .try_state::<hotkeys::GlobalHotkeyState>()
+            .map(hotkeys::get_hotkey_status),
+        backend: backend::get_backend_health(),
+        recent_errors,
+        previous_run_crashed: PREVIOUS_RUN_CRASHED.load(Ordering::Relaxed),
+        last_crash: last_crash(),
+    };
+    info!(
+        errors = report.recent_errors.len(),
+        crashed = report.previous_run_crashed,
+        "Built diagnostics report"
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_error_and_warning_lines_in_order() {
+        let lines: Vec<String> = [
+            "2026-01-01T00:00:00Z  INFO insight_reader: started",
+            "2026-01-01T00:00:01Z  WARN insight_reader: slow",
+            "2026-01-01T00:00:02Z ERROR insight_reader: failed",
+            "2026-01-01T00:00:03Z DEBUG insight_reader: ERROR in a message",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        assert_eq!(
+            recent_errors(&lines),
+            vec![lines[1].clone(), lines[2].clone()]
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Bounded concurrency for user-triggered background work.
+//!
+//! Hotkeys, the tray and the action socket each start a thread per trigger, and a selection read
+//! that times out keeps its thread blocked until the system answers. On a slow X11 session rapid
+//! presses would pile these up. A `Limiter` caps how many run at once; callers that cannot get a
+//! permit drop the request instead of queueing it.
+//!
+//! Text capture allows `capture_concurrency` reads in flight (default 2); each action type is
+//! single-flight (a limit of 1).
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tracing::debug;
+
+pub const DEFAULT_CAPTURE_CONCURRENCY: usize = 2;
+
+pub struct Limiter {
+    name: &'static str,
+    running: AtomicUsize,
+    limit: AtomicUsize,
+}
+
+/// A slot in a `Limiter`, released when dropped.
+pub struct Permit<'a> {
+    limiter: &'a Limiter,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.limiter.running.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl Limiter {
+    pub const fn new(name: &'static str, limit: usize) -> Self {
+        Self {
+            name,
+            running: AtomicUsize::new(0),
+            limit: AtomicUsize::new(limit),
+        }
+    }
+
+    /// Changes the limit (at least 1). Work already running keeps its permits.
+    pub fn set_limit(&self, limit: usize) {
+        self.limit.store(limit.max(1), Ordering::Release);
+    }
+
+    /// Whether any work holds a permit right now.
+    pub fn is_busy(&self) -> bool {
+        self.running.load(Ordering::Acquire) > 0
+    }
+
+    /// Takes a slot, or returns `None` when `limit` are already running.
+    pub fn try_acquire(&self) -> Option<Permit<'_>> {
+        let limit = self.limit.load(Ordering::Acquire);
+        let acquired = self
+            .running
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |running| {
+                (running < limit).then_some(running + 1)
+            });
+        match acquired {
+            Ok(_) => Some(Permit { limiter: self }),
+            Err(running) => {
+                debug!(
+                    limiter = self.name,
+                    running, limit, "Limiter full, dropping request"
+                );
+                None
+            }
+        }
+    }
+}
+
+/// In-flight selection/clipboard reads (see `text_capture`).
+pub static CAPTURES: Limiter = Limiter::new("capture", DEFAULT_CAPTURE_CONCURRENCY);
+
+/// Applies the `capture_concurrency` config value.
+pub fn set_capture_concurrency(value: Option<u32>) {
+    CAPTURES.set_limit(value.map_or(DEFAULT_CAPTURE_CONCURRENCY, |v| v as usize));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limiter_caps_and_releases_permits() {
+        let limiter = Limiter::new("test", 2);
+        let first = limiter.try_acquire();
+        let second = limiter.try_acquire();
+        assert!(first.is_some() && second.is_some());
+        assert!(limiter.try_acquire().is_none());
+        drop(first);
+        let third = limiter.try_acquire();
+        assert!(third.is_some());
+
+        limiter.set_limit(0);
+        drop((second, third));
+        assert!(limiter.try_acquire().is_some());
+    }
+}
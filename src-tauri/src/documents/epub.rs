@@ -0,0 +1,387 @@
+//! EPUB parsing: container.xml -> OPF package -> spine documents (XHTML) -> plain text.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use tracing::debug;
+
+use super::{Document, Section};
+
+/// Largest archive entry read into memory (guards against zip bombs).
+const MAX_ENTRY_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Elements whose text is never read aloud.
+const SKIPPED_ELEMENTS: [&[u8]; 4] = [b"script", b"style", b"svg", b"head"];
+
+/// Elements that end a paragraph.
+const BLOCK_ELEMENTS: [&[u8]; 18] = [
+    b"p",
+    b"div",
+    b"br",
+    b"li",
+    b"tr",
+    b"section",
+    b"article",
+    b"blockquote",
+    b"pre",
+    b"h1",
+    b"h2",
+    b"h3",
+    b"h4",
+    b"h5",
+    b"h6",
+    b"dt",
+    b"dd",
+    b"hr",
+];
+
+const HEADING_ELEMENTS: [&[u8]; 3] = [b"h1", b"h2", b"h3"];
+
+pub(super) fn load(path: &Path) -> Result<Document, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Not a valid EPUB archive: {e}"))?;
+
+    let container = read_entry(&mut archive, "META-INF/container.xml")?;
+    let opf_path = rootfile_path(&container)
+        .ok_or_else(|| "EPUB container.xml has no rootfile".to_string())?;
+    let opf = read_entry(&mut archive, &opf_path)?;
+    let package = parse_package(&opf)?;
+    let base_dir = opf_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+
+    let mut sections = Vec::new();
+    for href in &package.spine {
+        let entry = resolve_href(base_dir, href);
+        let xhtml = match read_entry(&mut archive, &entry) {
+            Ok(xhtml) => xhtml,
+            Err(e) => {
+                debug!(entry = %entry, error = %e, "Skipping unreadable EPUB spine item");
+                continue;
+            }
+        };
+        let (heading, text) = xhtml_to_text(&xhtml);
+        if text.trim().is_empty() {
+            continue;
+        }
+        let title = heading.unwrap_or_else(|| format!("Chapter {}", sections.len() + 1));
+        sections.push(Section { title, text });
+    }
+
+    Ok(Document {
+        title: package.title,
+        sections,
+    })
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<File>, name: &str) -> Result<String, String> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|e| format!("EPUB entry {name}: {e}"))?;
+    if entry.size() > MAX_ENTRY_BYTES {
+        return Err(format!("EPUB entry {name} is too large"));
+    }
+    let mut bytes = Vec::with_capacity(entry.size() as usize);
+    entry
+        .take(MAX_ENTRY_BYTES)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read EPUB entry {name}: {e}"))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// `full-path` of the first `<rootfile>` in META-INF/container.xml.
+fn rootfile_path(container: &str) -> Option<String> {
+    let mut reader = Reader::from_str(container);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.local_name().as_ref() == b"rootfile" => {
+                return attribute(&e, b"full-path");
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Package {
+    title: Option<String>,
+    /// Spine item hrefs (relative to the OPF), in reading order.
+    spine: Vec<String>,
+}
+
+/// Reads the title, manifest, and spine from an OPF package document.
+fn parse_package(opf: &str) -> Result<Package, String> {
+    let mut reader = Reader::from_str(opf);
+    let mut manifest: HashMap<String, String> = HashMap::new();
+    let mut spine_ids: Vec<String> = Vec::new();
+    let mut title: Option<String> = None;
+    let mut in_title = false;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"title" && title.is_none() => {
+                in_title = true;
+            }
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"title" => in_title = false,
+            Ok(Event::Text(t)) if in_title => {
+                let text = t.decode().map_err(|e| format!("Invalid OPF title: {e}"))?;
+                let text = collapse_whitespace(&text);
+                if !text.is_empty() {
+                    title = Some(text);
+                }
+            }
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => match e.local_name().as_ref() {
+                b"item" => {
+                    if let (Some(id), Some(href)) = (attribute(&e, b"id"), attribute(&e, b"href")) {
+                        manifest.insert(id, href);
+                    }
+                }
+                b"itemref" => {
+                    let linear = attribute(&e, b"linear");
+                    if linear.as_deref() != Some("no") {
+                        if let Some(idref) = attribute(&e, b"idref") {
+                            spine_ids.push(idref);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Invalid EPUB package document: {e}")),
+            _ => {}
+        }
+    }
+
+    let spine = spine_ids
+        .iter()
+        .filter_map(|id| manifest.get(id).cloned())
+        .collect::<Vec<_>>();
+    if spine.is_empty() {
+        return Err("EPUB has no readable chapters".to_string());
+    }
+    Ok(Package { title, spine })
+}
+
+fn attribute(e: &quick_xml::events::BytesStart<'_>, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == name)
+        .and_then(|a| a.unescape_value().ok())
+        .map(|v| v.into_owned())
+}
+
+/// Resolves a manifest href against the OPF directory: drops the fragment, decodes `%XX`, and
+/// normalizes `.`/`..` segments (zip entry names never start with `/`).
+fn resolve_href(base_dir: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let href = percent_decode(href);
+    let mut parts: Vec<&str> = if base_dir.is_empty() {
+        Vec::new()
+    } else {
+        base_dir.split('/').collect()
+    };
+    for segment in href.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(segment),
+        }
+    }
+    parts.join("/")
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Converts an XHTML chapter to plain text (paragraphs separated by blank lines). Also returns the
+/// first h1–h3 heading, used as the chapter title. Malformed markup ends the text early.
+fn xhtml_to_text(xhtml: &str) -> (Option<String>, String) {
+    let mut reader = Reader::from_str(xhtml);
+    reader.config_mut().check_end_names = false;
+
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut heading: Option<String> = None;
+    let mut heading_buf: Option<String> = None;
+    let mut skip_depth = 0usize;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = e.local_name();
+                let name = name.as_ref();
+                if skip_depth > 0 || SKIPPED_ELEMENTS.contains(&name) {
+                    skip_depth += 1;
+                    continue;
+                }
+                if BLOCK_ELEMENTS.contains(&name) {
+                    end_paragraph(&mut current, &mut paragraphs);
+                }
+                if heading.is_none() && HEADING_ELEMENTS.contains(&name) {
+                    heading_buf = Some(String::new());
+                }
+            }
+            Ok(Event::End(e)) => {
+                if skip_depth > 0 {
+                    skip_depth -= 1;
+                    continue;
+                }
+                let name = e.local_name();
+                let name = name.as_ref();
+                if BLOCK_ELEMENTS.contains(&name) {
+                    end_paragraph(&mut current, &mut paragraphs);
+                }
+                if HEADING_ELEMENTS.contains(&name) {
+                    if let Some(buf) = heading_buf.take() {
+                        let text = collapse_whitespace(&buf);
+                        if !text.is_empty() {
+                            heading = Some(text);
+                        }
+                    }
+                }
+            }
+            Ok(Event::Empty(e))
+                if skip_depth == 0 && BLOCK_ELEMENTS.contains(&e.local_name().as_ref()) =>
+            {
+                end_paragraph(&mut current, &mut paragraphs);
+            }
+            Ok(Event::Text(t)) if skip_depth == 0 => {
+                if let Ok(text) = t.decode() {
+                    push_text(&text, &mut current, &mut heading_buf);
+                }
+            }
+            Ok(Event::CData(t)) if skip_depth == 0 => {
+                if let Ok(text) = t.decode() {
+                    push_text(&text, &mut current, &mut heading_buf);
+                }
+            }
+            Ok(Event::GeneralRef(r)) if skip_depth == 0 => {
+                let resolved = match r.resolve_char_ref() {
+                    Ok(Some(c)) => Some(c),
+                    _ => named_entity(r.as_ref()),
+                };
+                if let Some(c) = resolved {
+                    push_text(c.encode_utf8(&mut [0; 4]), &mut current, &mut heading_buf);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                debug!(error = %e, "Malformed XHTML in EPUB chapter, keeping text read so far");
+                break;
+            }
+            _ => {}
+        }
+    }
+    end_paragraph(&mut current, &mut paragraphs);
+    (heading, paragraphs.join("\n\n"))
+}
+
+fn push_text(text: &str, current: &mut String, heading_buf: &mut Option<String>) {
+    current.push_str(text);
+    if let Some(buf) = heading_buf.as_mut() {
+        buf.push_str(text);
+    }
+}
+
+fn end_paragraph(current: &mut String, paragraphs: &mut Vec<String>) {
+    let text = collapse_whitespace(current);
+    if !text.is_empty() {
+        paragraphs.push(text);
+    }
+    current.clear();
+}
+
+/// Entities that commonly appear in EPUB XHTML without a DTD.
+fn named_entity(name: &[u8]) -> Option<char> {
+    match name {
+        b"amp" => Some('&'),
+        b"lt" => Some('<'),
+        b"gt" => Some('>'),
+        b"quot" => Some('"'),
+        b"apos" => Some('\''),
+        b"nbsp" => Some(' '),
+        b"mdash" => Some('—'),
+        b"ndash" => Some('–'),
+        b"hellip" => Some('…'),
+        b"lsquo" => Some('‘'),
+        b"rsquo" => Some('’'),
+        b"ldquo" => Some('“'),
+        b"rdquo" => Some('”'),
+        _ => None,
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xhtml_to_text_keeps_paragraphs_and_heading() {
+        let xhtml = r#"<?xml version="1.0" encoding="utf-8"?>
+            <html xmlns="http://www.w3.org/1999/xhtml">
+            <head><title>ignored</title><style>p { color: red; }</style></head>
+            <body>
+              <h1>Chapter <em>One</em></h1>
+              <p>It was a  bright
+                 cold day&nbsp;in April &amp; the clocks&#8230;</p>
+              <p>Second<br/>line</p>
+            </body></html>"#;
+        let (heading, text) = xhtml_to_text(xhtml);
+        assert_eq!(heading.as_deref(), Some("Chapter One"));
+        assert_eq!(
+            text,
+            "Chapter One\n\nIt was a bright cold day in April & the clocks…\n\nSecond\n\nline"
+        );
+    }
+
+    #[test]
+    fn test_package_spine_resolves_in_reading_order() {
+        let opf = r#"<package xmlns="http://www.idpf.org/2007/opf">
+            <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Book</dc:title></metadata>
+            <manifest>
+              <item id="c2" href="text/ch%202.xhtml" media-type="application/xhtml+xml"/>
+              <item id="c1" href="text/ch1.xhtml#top" media-type="application/xhtml+xml"/>
+              <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml"/>
+            </manifest>
+            <spine><itemref idref="nav" linear="no"/><itemref idref="c1"/><itemref idref="c2"/></spine>
+            </package>"#;
+        let package = parse_package(opf).unwrap();
+        assert_eq!(package.title.as_deref(), Some("Book"));
+        let entries: Vec<String> = package
+            .spine
+            .iter()
+            .map(|href| resolve_href("OEBPS", href))
+            .collect();
+        assert_eq!(entries, ["OEBPS/text/ch1.xhtml", "OEBPS/text/ch 2.xhtml"]);
+        assert_eq!(
+            resolve_href("OEBPS/text", "../img/a.png"),
+            "OEBPS/img/a.png"
+        );
+    }
+}
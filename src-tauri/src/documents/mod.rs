@@ -0,0 +1,397 @@
+//! Document reading mode: open an EPUB or PDF, then listen to it one chapter (EPUB spine item) or
+//! page (PDF) at a time.
+//!
+//! The open document lives in `DocumentState`; each navigation command sends the section text to
+//! the TTS worker and, once synthesis has started, emits `document-position`. The last section per
+//! file is remembered in `<user data dir>/document-positions.json`, so reopening a book resumes
+//! where it was left.
+//! Scanned PDFs are recognized page by page with OCR while opening (see `document-ocr-progress`).
+
+mod epub;
+mod pdf;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tauri::{Emitter, State};
+use tracing::{debug, info, warn};
+
+use crate::commands_config::ConfigState;
+use crate::config::FullConfig;
+use crate::history;
+use crate::paths;
+use crate::tts;
+use crate::util;
+
+/// Event emitted after the current section changes.
+pub const DOCUMENT_POSITION_EVENT: &str = "document-position";
+
+/// Event emitted before each page of a scanned PDF is recognized.
+pub const DOCUMENT_OCR_PROGRESS_EVENT: &str = "document-ocr-progress";
+
+const POSITIONS_FILE_NAME: &str = "document-positions.json";
+
+/// One readable unit: an EPUB chapter or a PDF page.
+#[derive(Debug, Clone)]
+struct Section {
+    title: String,
+    text: String,
+}
+
+/// Parsed document text.
+#[derive(Debug, Clone)]
+struct Document {
+    title: Option<String>,
+    sections: Vec<Section>,
+}
+
+#[derive(Debug)]
+pub struct OpenDocument {
+    path: PathBuf,
+    title: String,
+    sections: Vec<Section>,
+    current: usize,
+}
+
+/// The document being read, if any.
+pub type DocumentState = Mutex<Option<OpenDocument>>;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DocumentInfo {
+    pub path: String,
+    pub title: String,
+    pub section_titles: Vec<String>,
+    pub position: DocumentPosition,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DocumentPosition {
+    /// Zero-based index of the current section.
+    pub index: usize,
+    pub count: usize,
+    pub title: String,
+}
+
+/// Payload of `document-ocr-progress`: `page` (1-based) of `total` is being recognized.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OcrProgress {
+    pub page: usize,
+    pub total: usize,
+}
+
+impl OpenDocument {
+    fn position(&self) -> DocumentPosition {
+        DocumentPosition {
+            index: self.current,
+            count: self.sections.len(),
+            title: self.sections[self.current].title.clone(),
+        }
+    }
+
+    fn info(&self) -> DocumentInfo {
+        DocumentInfo {
+            path: self.path.display().to_string(),
+            title: self.title.clone(),
+            section_titles: self.sections.iter().map(|s| s.title.clone()).collect(),
+            position: self.position(),
+        }
+    }
+}
+
+/// Parses the file by extension (`.epub` or `.pdf`). `on_ocr_page` reports OCR progress of a
+/// scanned PDF.
+fn load_document(
+    path: &Path,
+    cfg: &FullConfig,
+    on_ocr_page: &dyn Fn(usize, usize),
+) -> Result<Document, String> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("epub") => epub::load(path),
+        Some("pdf") => pdf::load(path, cfg, on_ocr_page),
+        _ => Err("Unsupported document type (expected .epub or .pdf)".to_string()),
+    }
+}
+
+// --- Saved positions ---
+
+fn positions_path() -> Result<PathBuf, String> {
+    Ok(paths::get_user_data_dir()?.join(POSITIONS_FILE_NAME))
+}
+
+fn load_positions() -> HashMap<String, usize> {
+    positions_path()
+        .map(|path| read_positions(&path))
+        .unwrap_or_default()
+}
+
+fn read_positions(path: &Path) -> HashMap<String, usize> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+/// Records `index` as the current section of `document` in the positions file at `path`.
+fn write_position(path: &Path, document: &Path, index: usize) -> Result<(), String> {
+    let mut positions = read_positions(path);
+    positions.insert(document.display().to_string(), index);
+    let json = serde_json::to_string_pretty(&positions)
+        .map_err(|e| format!("Failed to serialize document positions: {e}"))?;
+    util::write_atomic(path, json)
+}
+
+fn save_position(doc: &OpenDocument) {
+    let result = positions_path().and_then(|path| write_position(&path, &doc.path, doc.current));
+    if let Err(e) = result {
+        warn!(error = %e, "Failed to save document position");
+    }
+}
+
+/// Section to resume the document at `path` from: the saved one if it still exists, else the
+/// first.
+fn resume_index(positions: &HashMap<String, usize>, path: &Path, count: usize) -> usize {
+    positions
+        .get(&path.display().to_string())
+        .copied()
+        .filter(|i| *i < count)
+        .unwrap_or(0)
+}
+
+// --- Navigation ---
+
+/// Resolves `target` to a section index (if in range) and returns it with the section text to
+/// speak. The position is not changed until the section is actually spoken (see `move_to`).
+fn section_at(
+    state: &DocumentState,
+    target: impl FnOnce(&OpenDocument) -> Option<usize>,
+) -> Result<(PathBuf, usize, String), String> {
+    let guard = state
+        .lock()
+        .map_err(|_| "Document lock poisoned".to_string())?;
+    let doc = guard
+        .as_ref()
+        .ok_or_else(|| "No document is open".to_string())?;
+    let index = target(doc)
+        .filter(|i| *i < doc.sections.len())
+        .ok_or_else(|| "No more sections in this direction".to_string())?;
+    Ok((doc.path.clone(), index, doc.sections[index].text.clone()))
+}
+
+/// Moves the current section of the document at `path` to `index`, saves and emits the position.
+fn move_to(
+    app: &tauri::AppHandle,
+    state: &DocumentState,
+    path: &Path,
+    index: usize,
+) -> Result<DocumentPosition, String> {
+    let mut guard = state
+        .lock()
+        .map_err(|_| "Document lock poisoned".to_string())?;
+    let doc = guard
+        .as_mut()
+        .filter(|doc| doc.path == path && index < doc.sections.len())
+        .ok_or_else(|| "The document was closed".to_string())?;
+    doc.current = index;
+    save_position(doc);
+    let position = doc.position();
+    let _ = app.emit(DOCUMENT_POSITION_EVENT, &position);
+    Ok(position)
+}
+
+/// Stops current playback and speaks `text` (waits until synthesis has started).
+async fn speak_section(tx: tts::TtsState, text: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let _ = tx.send(tts::TtsRequest::Stop);
+        history::begin("document", &text);
+        let (resp_tx, resp_rx) = std::sync::mpsc::sync_channel(0);
+        tx.send(tts::TtsRequest::Speak(
+            text,
+            tts::InputKind::Text,
+            None,
+            resp_tx,
+        ))
+        .map_err(|e| format!("TTS channel: {e}"))?;
+        resp_rx
+            .recv()
+            .map_err(|_| "TTS worker disconnected".to_string())?
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking: {e}"))?
+    .inspect_err(|_| history::abandon())
+}
+
+async fn move_and_speak(
+    app: tauri::AppHandle,
+    state: State<'_, DocumentState>,
+    tts_state: State<'_, tts::TtsState>,
+    target: impl FnOnce(&OpenDocument) -> Option<usize>,
+) -> Result<DocumentPosition, String> {
+    let (path, index, text) = section_at(&state, target)?;
+    speak_section(tts_state.inner().clone(), text).await?;
+    move_to(&app, &state, &path, index)
+}
+
+// --- Commands ---
+
+/// Opens an EPUB or PDF and restores the last read section. Does not start reading. A scanned PDF
+/// is recognized with OCR first, which can take a while (see `document-ocr-progress`).
+#[tauri::command]
+pub async fn open_document(
+    app: tauri::AppHandle,
+    state: State<'_, DocumentState>,
+    config: State<'_, ConfigState>,
+    path: String,
+) -> Result<DocumentInfo, String> {
+    let path = PathBuf::from(path);
+    let cfg = config
+        .lock()
+        .map_err(|_| "Config lock poisoned".to_string())?
+        .clone();
+    let progress_app = app.clone();
+    let (path, document) = tokio::task::spawn_blocking(move || {
+        let path = std::fs::canonicalize(&path)
+            .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+        let on_ocr_page = |page, total| {
+            let _ = progress_app.emit(DOCUMENT_OCR_PROGRESS_EVENT, OcrProgress { page, total });
+        };
+        let document = load_document(&path, &cfg, &on_ocr_page)?;
+        Ok::<_, String>((path, document))
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking: {e}"))??;
+    if document.sections.is_empty() {
+        return Err("Document has no readable text".to_string());
+    }
+
+    let saved = resume_index(&load_positions(), &path, document.sections.len());
+    let title = document.title.unwrap_or_else(|| {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+    let doc = OpenDocument {
+        path,
+        title,
+        sections: document.sections,
+        current: saved,
+    };
+    info!(
+        title = %doc.title,
+        sections = doc.sections.len(),
+        resume_at = saved,
+        "Opened document"
+    );
+    let info = doc.info();
+    *state
+        .lock()
+        .map_err(|_| "Document lock poisoned".to_string())? = Some(doc);
+    let _ = app.emit(DOCUMENT_POSITION_EVENT, &info.position);
+    Ok(info)
+}
+
+/// Reads the section at `index` (default: the current one) from the start.
+#[tauri::command]
+pub async fn document_read_section(
+    app: tauri::AppHandle,
+    state: State<'_, DocumentState>,
+    tts_state: State<'_, tts::TtsState>,
+    index: Option<usize>,
+) -> Result<DocumentPosition, String> {
+    move_and_speak(app, state, tts_state, |doc| {
+        Some(index.unwrap_or(doc.current))
+    })
+    .await
+}
+
+/// Advances to the next chapter/page and reads it.
+#[tauri::command]
+pub async fn document_next_chapter(
+    app: tauri::AppHandle,
+    state: State<'_, DocumentState>,
+    tts_state: State<'_, tts::TtsState>,
+) -> Result<DocumentPosition, String> {
+    move_and_speak(app, state, tts_state, |doc| doc.current.checked_add(1)).await
+}
+
+/// Goes back to the previous chapter/page and reads it.
+#[tauri::command]
+pub async fn document_previous_chapter(
+    app: tauri::AppHandle,
+    state: State<'_, DocumentState>,
+    tts_state: State<'_, tts::TtsState>,
+) -> Result<DocumentPosition, String> {
+    move_and_speak(app, state, tts_state, |doc| doc.current.checked_sub(1)).await
+}
+
+/// Returns the open document and current position, or `None` when no document is open.
+#[tauri::command]
+pub fn get_document_position(
+    state: State<'_, DocumentState>,
+) -> Result<Option<DocumentInfo>, String> {
+    let guard = state
+        .lock()
+        .map_err(|_| "Document lock poisoned".to_string())?;
+    Ok(guard.as_ref().map(OpenDocument::info))
+}
+
+/// Closes the open document (its position stays saved). Does not stop playback.
+#[tauri::command]
+pub fn close_document(state: State<'_, DocumentState>) -> Result<(), String> {
+    let closed = state
+        .lock()
+        .map_err(|_| "Document lock poisoned".to_string())?
+        .take();
+    if let Some(doc) = closed {
+        debug!(title = %doc.title, "Closed document");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_positions_are_restored_per_document() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data").join(POSITIONS_FILE_NAME);
+        let book = Path::new("/books/novel.epub");
+        let other = Path::new("/books/paper.pdf");
+        assert!(read_positions(&path).is_empty());
+
+        write_position(&path, book, 3).unwrap();
+        write_position(&path, other, 1).unwrap();
+        write_position(&path, book, 5).unwrap();
+        let positions = read_positions(&path);
+        assert_eq!(resume_index(&positions, book, 10), 5);
+        assert_eq!(resume_index(&positions, other, 10), 1);
+        assert_eq!(
+            resume_index(&positions, Path::new("/books/new.epub"), 10),
+            0
+        );
+    }
+
+    #[test]
+    fn test_saved_position_past_the_end_resumes_at_the_start() {
+        let book = Path::new("/books/novel.epub");
+        let positions = HashMap::from([(book.display().to_string(), 7)]);
+        assert_eq!(resume_index(&positions, book, 7), 0);
+        assert_eq!(resume_index(&positions, book, 8), 7);
+    }
+
+    #[test]
+    fn test_unreadable_positions_file_is_treated_as_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(POSITIONS_FILE_NAME);
+        std::fs::write(&path, "not json").unwrap();
+        assert!(read_positions(&path).is_empty());
+        write_position(&path, Path::new("/books/novel.epub"), 2).unwrap();
+        assert_eq!(read_positions(&path).len(), 1);
+    }
+}
@@ -0,0 +1,168 @@
+//! PDF text extraction via poppler's `pdftotext` (one section per page).
+//!
+//! Scanned PDFs have no text layer; then each page is rendered with `pdftoppm` and run through
+//! OCR, reporting progress per page since this takes a few seconds each.
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use tracing::{debug, info, warn};
+
+use super::{Document, Section};
+use crate::config::FullConfig;
+use crate::ocr::{self, cleanup, preprocess::PreprocessOptions};
+
+/// Rendering resolution for OCR; enough for body text without huge images.
+const OCR_DPI: &str = "200";
+
+/// Runs a poppler tool and returns its stdout.
+fn poppler(program: &str, configure: impl FnOnce(&mut Command)) -> Result<Vec<u8>, String> {
+    let mut cmd = Command::new(program);
+    configure(&mut cmd);
+    cmd.stdin(Stdio::null());
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = cmd.output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            format!("PDF reading needs {program} (poppler-utils) installed")
+        } else {
+            format!("Failed to run {program}: {e}")
+        }
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{program} failed: {}", stderr.trim()));
+    }
+    Ok(output.stdout)
+}
+
+/// Loads the pages' text, falling back to OCR when the PDF has no text layer. `on_ocr_page` is
+/// called with (page, page count) before each page is recognized.
+pub(super) fn load(
+    path: &Path,
+    cfg: &FullConfig,
+    on_ocr_page: &dyn Fn(usize, usize),
+) -> Result<Document, String> {
+    let output = poppler("pdftotext", |cmd| {
+        cmd.args(["-enc", "UTF-8"]).arg(path).arg("-");
+    })?;
+    let text = String::from_utf8_lossy(&output);
+    let mut sections = split_pages(&text);
+    if sections.is_empty() {
+        // pdftotext ends every page with a form feed, even a page without text.
+        let pages = text.matches('\u{c}').count();
+        info!(pages, "PDF has no text layer, recognizing pages with OCR");
+        sections = ocr_pages(path, pages, cfg, on_ocr_page)?;
+    }
+    if sections.is_empty() {
+        return Err("PDF has no readable text".to_string());
+    }
+    Ok(Document {
+        title: None,
+        sections,
+    })
+}
+
+/// Renders each page and recognizes its text. Pages that fail are skipped (logged), so one bad
+/// page does not lose the rest of the book; all pages failing is an error.
+fn ocr_pages(
+    path: &Path,
+    pages: usize,
+    cfg: &FullConfig,
+    on_page: &dyn Fn(usize, usize),
+) -> Result<Vec<Section>, String> {
+    let options = PreprocessOptions::from_config(cfg);
+    let cleanup = cfg.ocr_cleanup.unwrap_or(true);
+    let mut sections = Vec::new();
+    let mut last_error = None;
+    for page in 1..=pages {
+        on_page(page, pages);
+        match ocr_page(path, page, options) {
+            Ok(text) => {
+                let text = if cleanup {
+                    cleanup::cleanup_text(&text)
+                } else {
+                    text
+                };
+                let text = text.trim();
+                debug!(page, len = text.len(), "PDF page recognized");
+                if !text.is_empty() {
+                    sections.push(Section {
+                        title: format!("Page {page}"),
+                        text: text.to_string(),
+                    });
+                }
+            }
+            Err(e) => {
+                warn!(page, error = %e, "Failed to recognize PDF page");
+                last_error = Some(e);
+            }
+        }
+    }
+    match last_error {
+        Some(e) if sections.is_empty() => Err(e),
+        _ => Ok(sections),
+    }
+}
+
+/// Renders one page (1-based) to PNG and recognizes it.
+fn ocr_page(path: &Path, page: usize, options: PreprocessOptions) -> Result<String, String> {
+    let page = page.to_string();
+    // Without an output root, pdftoppm writes the image to stdout.
+    let png = poppler("pdftoppm", |cmd| {
+        cmd.args(["-png", "-gray", "-singlefile", "-r", OCR_DPI])
+            .args(["-f", &page, "-l", &page])
+            .arg(path);
+    })?;
+    let image = image::load_from_memory(&png)
+        .map_err(|e| format!("Failed to decode rendered page: {e}"))?;
+    Ok(ocr::extract_text_with_positions(&image, options)?.text)
+}
+
+/// Splits `pdftotext` output on form feeds. Blank pages are dropped but keep their page number.
+fn split_pages(text: &str) -> Vec<Section> {
+    text.split('\u{c}')
+        .enumerate()
+        .filter_map(|(i, page)| {
+            let text = page
+                .split("\n\n")
+                .map(|para| para.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|para| !para.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            (!text.is_empty()).then(|| Section {
+                title: format!("Page {}", i + 1),
+                text,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_pages_numbers_pages_and_skips_blank_ones() {
+        let sections = split_pages("First page\n\u{c}\n  \n\u{c}Third page\n\u{c}");
+        let titles: Vec<&str> = sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, ["Page 1", "Page 3"]);
+        assert_eq!(sections[1].text, "Third page");
+    }
+
+    #[test]
+    fn test_split_pages_joins_wrapped_lines_and_keeps_paragraphs() {
+        let sections = split_pages("A line\nwrapped  here.\n\n\n\nNext\tparagraph.\n\u{c}");
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].text, "A line wrapped here.\n\nNext paragraph.");
+    }
+
+    #[test]
+    fn test_split_pages_of_a_scanned_pdf_is_empty() {
+        assert!(split_pages("\u{c}\u{c}\n\u{c}").is_empty());
+    }
+}
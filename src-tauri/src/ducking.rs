@@ -0,0 +1,78 @@
+//! Ducking: other apps' audio is lowered while a read plays and restored when playback pauses or
+//! stops, so TTS stays intelligible over music.
+//!
+//! Off by default (`duck_background_audio`); `duck_level` is the percentage of their volume the
+//! other apps keep (default 30). A background thread polls the player every `POLL_INTERVAL`
+//! while enabled. Only streams playing when a read starts are lowered, and a volume the user
+//! changes meanwhile is overwritten on restore. The shutdown sequence restores before exiting.
+
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tracing::info;
+
+use crate::config::FullConfig;
+use crate::system::{self, DuckedAudio};
+use crate::tts;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const DEFAULT_LEVEL: u8 = 30;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static LEVEL: AtomicU8 = AtomicU8::new(DEFAULT_LEVEL);
+/// What is lowered now; `Some` while ducked.
+static DUCKED: Mutex<Option<DuckedAudio>> = Mutex::new(None);
+
+/// Applies `duck_background_audio` / `duck_level` (see `commands_config::apply_runtime_settings`).
+pub fn configure(cfg: &FullConfig) {
+    ENABLED.store(
+        cfg.duck_background_audio.unwrap_or(false),
+        Ordering::Relaxed,
+    );
+    LEVEL.store(
+        cfg.duck_level.unwrap_or(DEFAULT_LEVEL).min(100),
+        Ordering::Relaxed,
+    );
+}
+
+/// Ducks or restores, when that changes the current state.
+fn set_ducked(duck: bool) {
+    let Ok(mut ducked) = DUCKED.lock() else {
+        return;
+    };
+    match (duck, ducked.take()) {
+        (true, None) => {
+            let level = LEVEL.load(Ordering::Relaxed);
+            let audio = system::duck_other_audio(f32::from(level) / 100.0);
+            info!(streams = audio.count(), level, "Ducked other audio");
+            *ducked = Some(audio);
+        }
+        (false, Some(audio)) => {
+            info!(streams = audio.count(), "Restoring other audio");
+            system::restore_other_audio(audio);
+        }
+        (_, current) => *ducked = current,
+    }
+}
+
+/// Restores other apps' volumes if they are lowered. Called by the shutdown sequence.
+pub fn restore() {
+    set_ducked(false);
+}
+
+/// Starts the polling thread. Called from lib's setup.
+pub fn start(tts_tx: tts::TtsState) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        if !ENABLED.load(Ordering::Relaxed) {
+            restore();
+            continue;
+        }
+        let Some((playing, paused)) = tts::playback_status(&tts_tx) else {
+            restore();
+            break;
+        };
+        set_ducked(playing && !paused);
+    });
+}
@@ -0,0 +1,262 @@
+//! Paged editor text: very large texts opened in the editor are kept here and handed to the
+//! webview one page at a time.
+//!
+//! Texts over `PAGING_THRESHOLD_BYTES` are split into pages of at most `PAGE_MAX_BYTES`, ending at
+//! a paragraph break where possible (else a line break, sentence end or space). The editor gets
+//! the first page and the page count (see `EditorInitialStateInner`) and loads others with
+//! `get_text_page`. `read_from_page` reads a page and keeps exactly one page ahead in the playback
+//! queue: when a queued page starts (`on_queue_advance`), the one after it is enqueued, so the TTS
+//! worker never holds more than two pages. Each page that starts is reported with `editor-page`.
+
+use std::ops::Range;
+use std::sync::mpsc;
+use std::sync::Mutex;
+
+use tauri::{Emitter, Manager, State};
+use tracing::{debug, warn};
+
+use crate::history;
+use crate::tts;
+
+/// Event emitted when the read moves to another page (see `PagePosition`).
+pub const EDITOR_PAGE_EVENT: &str = "editor-page";
+
+/// Texts up to this size go to the editor whole.
+pub const PAGING_THRESHOLD_BYTES: usize = 200_000;
+const PAGE_MAX_BYTES: usize = 20_000;
+/// Queue source of the pages after the first, as recorded in the reading history.
+const PAGE_SOURCE: &str = "editor";
+
+/// Splits `text` into page ranges of at most `max_bytes`, preferring paragraph breaks, then line
+/// breaks, sentence ends and spaces. Ranges cover the whole text.
+fn page_ranges(text: &str, max_bytes: usize) -> Vec<Range<usize>> {
+    let mut pages = Vec::new();
+    let mut start = 0;
+    while text.len() - start > max_bytes {
+        let mut limit = start + max_bytes;
+        while !text.is_char_boundary(limit) {
+            limit -= 1;
+        }
+        let window = &text[start..limit];
+        let cut = ["\n\n", "\n", ". ", " "]
+            .iter()
+            .find_map(|sep| window.rfind(sep).map(|i| i + sep.len()))
+            .filter(|cut| *cut > 0)
+            .unwrap_or(window.len());
+        pages.push(start..start + cut);
+        start += cut;
+    }
+    if start < text.len() || pages.is_empty() {
+        pages.push(start..text.len());
+    }
+    pages
+}
+
+/// A large text and its pages, and which page the read will queue next.
+pub struct PagedText {
+    text: String,
+    pages: Vec<Range<usize>>,
+    /// Queue id and page index of the page waiting in the playback queue.
+    queued: Option<(u64, usize)>,
+}
+
+impl PagedText {
+    pub fn new(text: String) -> Self {
+        let pages = page_ranges(&text, PAGE_MAX_BYTES);
+        Self {
+            text,
+            pages,
+            queued: None,
+        }
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn page(&self, index: usize) -> Option<&str> {
+        self.pages.get(index).map(|range| &self.text[range.clone()])
+    }
+}
+
+/// The paged text of the editor, if its text is paged.
+pub type EditorPagesState = Mutex<Option<PagedText>>;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TextPage {
+    /// Zero-based page index.
+    pub index: usize,
+    pub count: usize,
+    pub text: String,
+}
+
+/// Payload of `editor-page`: the page now being read.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PagePosition {
+    pub index: usize,
+    pub count: usize,
+}
+
+/// Enqueues page `index` and remembers it as the page waiting in the queue.
+fn enqueue_page(app: &tauri::AppHandle, tx: &tts::TtsState, index: usize) -> Result<(), String> {
+    let Some(state) = app.try_state::<EditorPagesState>() else {
+        return Ok(());
+    };
+    let Some(text) = state
+        .lock()
+        .map_err(|_| "Editor pages lock poisoned".to_string())?
+        .as_ref()
+        .and_then(|paged| paged.page(index).map(str::to_string))
+    else {
+        return Ok(());
+    };
+    let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+    tx.send(tts::TtsRequest::Enqueue(
+        text,
+        tts::InputKind::Text,
+        PAGE_SOURCE.to_string(),
+        resp_tx,
+    ))
+    .map_err(|e| format!("TTS channel: {e}"))?;
+    let id = resp_rx
+        .recv()
+        .map_err(|_| "TTS worker disconnected".to_string())?
+        .map_err(|e| e.to_string())?;
+    if let Some(paged) = state
+        .lock()
+        .map_err(|_| "Editor pages lock poisoned".to_string())?
+        .as_mut()
+    {
+        paged.queued = Some((id, index));
+    }
+    Ok(())
+}
+
+/// Called on every queue advance (on the TTS worker thread): when the queued page starts, reports
+/// it and enqueues the page after it.
+pub fn on_queue_advance(app: &tauri::AppHandle, tx: &tts::TtsState, advance: &tts::QueueAdvance) {
+    let Some(current) = advance.current.as_ref() else {
+        return;
+    };
+    let Some(state) = app.try_state::<EditorPagesState>() else {
+        return;
+    };
+    let Ok(mut guard) = state.lock() else {
+        return;
+    };
+    let Some(paged) = guard.as_mut() else {
+        return;
+    };
+    let Some((_, index)) = paged.queued.filter(|(id, _)| *id == current.id) else {
+        return;
+    };
+    paged.queued = None;
+    let count = paged.page_count();
+    drop(guard);
+    debug!(page = index, count, "Reading next editor page");
+    let _ = app.emit(EDITOR_PAGE_EVENT, PagePosition { index, count });
+    if index + 1 < count {
+        // Enqueue waits for the worker, which is the thread calling this.
+        let app = app.clone();
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = enqueue_page(&app, &tx, index + 1) {
+                warn!(page = index + 1, error = %e, "Failed to queue the next editor page");
+            }
+        });
+    }
+}
+
+// --- Commands ---
+
+/// Returns page `index` of the editor's paged text.
+#[tauri::command]
+pub fn get_text_page(state: State<'_, EditorPagesState>, index: usize) -> Result<TextPage, String> {
+    let guard = state
+        .lock()
+        .map_err(|_| "Editor pages lock poisoned".to_string())?;
+    let paged = guard
+        .as_ref()
+        .ok_or_else(|| "The editor text is not paged".to_string())?;
+    let text = paged
+        .page(index)
+        .ok_or_else(|| format!("No page {index} (the text has {})", paged.page_count()))?;
+    Ok(TextPage {
+        index,
+        count: paged.page_count(),
+        text: text.to_string(),
+    })
+}
+
+/// Reads the paged text from page `index` to the end, synthesizing one page at a time. Waits
+/// until speech of the page has started.
+#[tauri::command]
+pub async fn read_from_page(
+    app: tauri::AppHandle,
+    state: State<'_, EditorPagesState>,
+    tts_state: State<'_, tts::TtsState>,
+    index: usize,
+) -> Result<PagePosition, String> {
+    let (text, count) = {
+        let mut guard = state
+            .lock()
+            .map_err(|_| "Editor pages lock poisoned".to_string())?;
+        let paged = guard
+            .as_mut()
+            .ok_or_else(|| "The editor text is not paged".to_string())?;
+        let text = paged
+            .page(index)
+            .ok_or_else(|| format!("No page {index} (the text has {})", paged.page_count()))?
+            .to_string();
+        paged.queued = None;
+        (text, paged.page_count())
+    };
+    let tx = tts_state.inner().clone();
+    let position = PagePosition { index, count };
+    let _ = app.emit(EDITOR_PAGE_EVENT, &position);
+    tokio::task::spawn_blocking(move || {
+        // Pages of an earlier read may still wait in the queue.
+        let _ = tx.send(tts::TtsRequest::Stop);
+        history::begin(PAGE_SOURCE, &text);
+        let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+        tx.send(tts::TtsRequest::Speak(
+            text,
+            tts::InputKind::Text,
+            None,
+            resp_tx,
+        ))
+        .map_err(|e| format!("TTS channel: {e}"))?;
+        resp_rx
+            .recv()
+            .map_err(|_| "TTS worker disconnected".to_string())?
+            .map_err(|e| e.to_string())
+            .inspect_err(|_| history::abandon())?;
+        if index + 1 < count {
+            enqueue_page(&app, &tx, index + 1)?;
+        }
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking: {e}"))??;
+    Ok(position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pages_end_at_paragraph_breaks_and_cover_the_text() {
+        let text = "First paragraph here.\n\nSecond one is longer. It has two sentences.\n\nEnd";
+        let pages = page_ranges(text, 40);
+        let texts: Vec<&str> = pages.iter().map(|r| &text[r.clone()]).collect();
+        assert_eq!(texts[0], "First paragraph here.\n\n");
+        assert_eq!(texts.concat(), text);
+        assert!(pages.iter().all(|r| r.len() <= 40));
+
+        let unbroken = "é".repeat(30);
+        let pages = page_ranges(&unbroken, 25);
+        assert_eq!(pages.len(), 3);
+        assert!(pages.iter().all(|r| unbroken.is_char_boundary(r.start)));
+    }
+}
@@ -0,0 +1,208 @@
+//! Structured errors for Tauri commands.
+//!
+//! The TTS, backend, voice and window commands return `AppError`, serialized as
+//! `{ code, message, hint }` (plus `retry_after_secs` for rate limits), so the UI can tell "no
+//! text" from "provider not configured" from "network down", show an actionable hint and decide
+//! whether to retry. Helpers below the commands keep returning `String`; `?` turns those into
+//! `AppError::Internal` (or `Offline` for `offline::OFFLINE_ERROR`).
+
+use serde::{Serialize, Serializer};
+
+use crate::backend::BackendError;
+use crate::offline;
+use crate::tts::TTSError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppError {
+    /// Nothing to read: no selection, empty clipboard or empty input.
+    NoText,
+    /// The voice provider lacks credentials or settings.
+    ProviderNotConfigured(String),
+    /// A request to a remote service failed (unreachable, timeout, HTTP error).
+    Network(String),
+    /// Offline mode is on (see `offline`).
+    Offline,
+    /// The service asked to slow down; `retry_after_secs` when it said for how long.
+    RateLimited {
+        message: String,
+        retry_after_secs: Option<u64>,
+    },
+    /// The monthly character budget would be exceeded (see `usage`).
+    BudgetExceeded(String),
+    /// A voice, task or file that does not exist.
+    NotFound(String),
+    /// An argument the command cannot use.
+    InvalidInput(String),
+    Internal(String),
+}
+
+impl AppError {
+    /// A failed request to a remote service; the offline-mode error stays `Offline`.
+    pub fn network(message: String) -> Self {
+        if message == offline::OFFLINE_ERROR {
+            Self::Offline
+        } else {
+            Self::Network(message)
+        }
+    }
+
+    /// Stable identifier for the frontend.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NoText => "no_text",
+            Self::ProviderNotConfigured(_) => "provider_not_configured",
+            Self::Network(_) => "network",
+            Self::Offline => "offline",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::BudgetExceeded(_) => "budget_exceeded",
+            Self::NotFound(_) => "not_found",
+            Self::InvalidInput(_) => "invalid_input",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    /// What the user can do about it, when there is something.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::NoText => Some("Select or copy some text first."),
+            Self::ProviderNotConfigured(_) => {
+                Some("Check the voice provider in Settings, or choose another one.")
+            }
+            Self::Network(_) => Some("Check your internet connection and try again."),
+            Self::Offline => Some("Turn off offline mode in Settings."),
+            Self::RateLimited { .. } => Some("Wait a moment and try again."),
+            Self::BudgetExceeded(_) => {
+                Some("Raise the monthly budget or allow reads over budget in Settings.")
+            }
+            Self::NotFound(_) | Self::InvalidInput(_) | Self::Internal(_) => None,
+        }
+    }
+
+    /// Whether trying again later can succeed.
+    pub fn retryable(&self) -> bool {
+        matches!(self, Self::Network(_) | Self::RateLimited { .. })
+    }
+
+    /// The message shown to the user (also the `message` field).
+    pub fn message(&self) -> String {
+        match self {
+            Self::NoText => "There is no text to read".to_string(),
+            Self::Offline => offline::OFFLINE_ERROR.to_string(),
+            Self::ProviderNotConfigured(message)
+            | Self::Network(message)
+            | Self::RateLimited { message, .. }
+            | Self::BudgetExceeded(message)
+            | Self::NotFound(message)
+            | Self::InvalidInput(message)
+            | Self::Internal(message) => message.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Payload {
+            code: &'static str,
+            message: String,
+            hint: Option<&'static str>,
+            retryable: bool,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            retry_after_secs: Option<u64>,
+        }
+        let retry_after_secs = match self {
+            Self::RateLimited {
+                retry_after_secs, ..
+            } => *retry_after_secs,
+            _ => None,
+        };
+        Payload {
+            code: self.code(),
+            message: self.message(),
+            hint: self.hint(),
+            retryable: self.retryable(),
+            retry_after_secs,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        if message == offline::OFFLINE_ERROR {
+            Self::Offline
+        } else {
+            Self::Internal(message)
+        }
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl From<TTSError> for AppError {
+    fn from(e: TTSError) -> Self {
+        match e {
+            TTSError::EmptyText => Self::NoText,
+            TTSError::NotConfigured(message) => Self::ProviderNotConfigured(message),
+            TTSError::Offline => Self::Offline,
+            TTSError::BudgetExceeded(message) => Self::BudgetExceeded(message),
+            e @ (TTSError::ProcessError(_) | TTSError::AudioError(_)) => {
+                Self::Internal(e.to_string())
+            }
+        }
+    }
+}
+
+impl From<BackendError> for AppError {
+    fn from(e: BackendError) -> Self {
+        match &e {
+            BackendError::RateLimited { retry_after, .. } => Self::RateLimited {
+                message: e.to_string(),
+                retry_after_secs: retry_after.map(|d| d.as_secs()),
+            },
+            BackendError::Other(message) if message == offline::OFFLINE_ERROR => Self::Offline,
+            BackendError::Other(message) => Self::Network(message.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_code_message_and_hint() {
+        let json = serde_json::to_value(AppError::from(TTSError::EmptyText)).unwrap();
+        assert_eq!(json["code"], "no_text");
+        assert_eq!(json["hint"], "Select or copy some text first.");
+        assert_eq!(json["retryable"], false);
+        assert!(json.get("retry_after_secs").is_none());
+
+        let limited = AppError::from(BackendError::RateLimited {
+            message: "slow down".into(),
+            retry_after: Some(std::time::Duration::from_secs(30)),
+        });
+        let json = serde_json::to_value(&limited).unwrap();
+        assert_eq!(json["code"], "rate_limited");
+        assert_eq!(json["retry_after_secs"], 30);
+        assert_eq!(json["retryable"], true);
+
+        assert_eq!(
+            AppError::from(offline::OFFLINE_ERROR.to_string()),
+            AppError::Offline
+        );
+        assert_eq!(AppError::from("boom").code(), "internal");
+    }
+}
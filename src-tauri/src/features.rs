@@ -0,0 +1,164 @@
+//! Feature flags for experimental subsystems.
+//!
+//! Each flag is registered in `FLAGS` with its default, so experimental code paths can ship dark
+//! and be switched on per user. Overrides are stored in the `experimental` config map (flag name
+//! to bool) and applied at startup and on every config save (see
+//! `commands_config::apply_runtime_settings`); code checks them with `is_enabled`, which is
+//! lock-free. Unknown names in the config are kept but ignored.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+use tauri::State;
+use tracing::{info, warn};
+
+use crate::commands_config::{self, ConfigState};
+use crate::config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    /// Reuse the audio of unchanged chunks when the same text is read again (see `tts::stream`).
+    AudioReuse,
+    /// The read hotkey queues the text while something plays instead of replacing it.
+    PlaybackQueue,
+    /// Run Piper models in process with onnxruntime instead of the Piper CLI (see `tts::onnx`).
+    InProcessInference,
+}
+
+struct FlagSpec {
+    flag: Flag,
+    name: &'static str,
+    description: &'static str,
+    default: bool,
+}
+
+/// All flags, in `Flag` order.
+const FLAGS: &[FlagSpec] = &[
+    FlagSpec {
+        flag: Flag::AudioReuse,
+        name: "audio_reuse",
+        description: "Reuse synthesized audio of unchanged sentences when text is read again",
+        default: true,
+    },
+    FlagSpec {
+        flag: Flag::PlaybackQueue,
+        name: "playback_queue",
+        description: "Reading a selection while something plays adds it to the queue",
+        default: true,
+    },
+    FlagSpec {
+        flag: Flag::InProcessInference,
+        name: "in_process_inference",
+        description: "Run local voices in process instead of starting Piper for each sentence",
+        default: false,
+    },
+];
+
+/// Current state per flag, indexed by `Flag`; set by `apply`.
+static ENABLED: [AtomicBool; FLAGS.len()] = [const { AtomicBool::new(false) }; FLAGS.len()];
+
+/// A flag as shown in settings.
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureFlag {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default: bool,
+    pub enabled: bool,
+}
+
+fn spec(name: &str) -> Option<&'static FlagSpec> {
+    FLAGS.iter().find(|spec| spec.name == name)
+}
+
+fn resolve(spec: &FlagSpec, overrides: Option<&HashMap<String, bool>>) -> bool {
+    overrides
+        .and_then(|map| map.get(spec.name).copied())
+        .unwrap_or(spec.default)
+}
+
+pub fn is_enabled(flag: Flag) -> bool {
+    ENABLED[flag as usize].load(Ordering::Relaxed)
+}
+
+/// Applies the `experimental` config map.
+pub fn apply(overrides: Option<&HashMap<String, bool>>) {
+    for spec in FLAGS {
+        ENABLED[spec.flag as usize].store(resolve(spec, overrides), Ordering::Relaxed);
+    }
+    for name in overrides.into_iter().flat_map(|map| map.keys()) {
+        if spec(name).is_none() {
+            warn!(flag = %name, "Unknown feature flag in config, ignoring it");
+        }
+    }
+}
+
+// --- Commands ---
+
+/// Lists the registered feature flags with their current state.
+#[tauri::command]
+pub fn list_feature_flags(state: State<'_, ConfigState>) -> Result<Vec<FeatureFlag>, String> {
+    let cfg = state
+        .lock()
+        .map_err(|_| "Config lock poisoned".to_string())?;
+    Ok(FLAGS
+        .iter()
+        .map(|spec| FeatureFlag {
+            name: spec.name,
+            description: spec.description,
+            default: spec.default,
+            enabled: resolve(spec, cfg.experimental.as_ref()),
+        })
+        .collect())
+}
+
+/// Turns a feature flag on or off; `enabled` omitted resets it to its default.
+#[tauri::command]
+pub fn set_feature_flag(
+    app: tauri::AppHandle,
+    state: State<'_, ConfigState>,
+    name: String,
+    enabled: Option<bool>,
+) -> Result<(), String> {
+    if spec(&name).is_none() {
+        return Err(format!("Unknown feature flag: {name}"));
+    }
+    let new_cfg = {
+        let mut cfg = state
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?;
+        let overrides = cfg.experimental.get_or_insert_with(HashMap::new);
+        match enabled {
+            Some(enabled) => overrides.insert(name.clone(), enabled),
+            None => overrides.remove(&name),
+        };
+        if overrides.is_empty() {
+            cfg.experimental = None;
+        }
+        cfg.clone()
+    };
+    commands_config::apply_runtime_settings(&new_cfg);
+    config::save_full_config(new_cfg)?;
+    info!(flag = %name, ?enabled, "Feature flag changed");
+    commands_config::notify_config_changed(&app);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_matches_flag_order() {
+        for (index, spec) in FLAGS.iter().enumerate() {
+            assert_eq!(spec.flag as usize, index, "{}", spec.name);
+        }
+    }
+
+    #[test]
+    fn test_overrides_replace_the_default() {
+        let overrides = HashMap::from([("audio_reuse".to_string(), false)]);
+        assert!(!resolve(&FLAGS[0], Some(&overrides)));
+        assert_eq!(resolve(&FLAGS[0], None), FLAGS[0].default);
+    }
+}
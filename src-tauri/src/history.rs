@@ -0,0 +1,405 @@
+//! Reading history: every text read aloud, with where the listener stopped, so a read can be
+//! resumed after a restart.
+//!
+//! Texts are stored as read (selections and clipboard contents included), so nothing is recorded
+//! unless `history_enabled` is on (off by default; applied with `configure`).
+//! Entries live in `history.json` in the history dir (per profile, see `paths`), newest first and
+//! capped at `MAX_ENTRIES`. They are kept in a `store::JsonStore` and written at most every
+//! `SAVE_INTERVAL`; shutdown calls `flush`. Callers that start a read call `begin` before sending
+//! Speak and `abandon` when it fails to start; speech that is not a read (previews, calibration)
+//! calls `detach`. The last spoken word comes from the worker's progress reports (`note_progress`).
+//! Positions are UTF-16 offsets into the entry text, like the word timeline.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use nanoid::nanoid;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use tracing::{debug, warn};
+
+use crate::config::FullConfig;
+use crate::paths;
+use crate::store::JsonStore;
+use crate::tts;
+use crate::util::unix_millis_now;
+
+const HISTORY_FILE_NAME: &str = "history.json";
+const MAX_ENTRIES: usize = 200;
+const PREVIEW_CHARS: usize = 120;
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HistoryEntry {
+    id: String,
+    /// What started the read: "selection", "screenshot", "document", "app", ...
+    source: String,
+    text: String,
+    provider: String,
+    voice: Option<String>,
+    /// Unix ms of the first read and of the latest read or resume.
+    started_at: u64,
+    last_read_at: u64,
+    /// Audio listened to, in ms, across resumes.
+    duration_ms: u64,
+    /// Start of the last spoken word.
+    position: usize,
+    finished: bool,
+}
+
+/// A history entry without its text, returned by `history_list`.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistorySummary {
+    pub id: String,
+    pub source: String,
+    pub preview: String,
+    pub provider: String,
+    pub voice: Option<String>,
+    pub started_at: u64,
+    pub last_read_at: u64,
+    pub duration_ms: u64,
+    pub position: usize,
+    /// UTF-16 length of the text.
+    pub length: usize,
+    pub finished: bool,
+}
+
+impl From<&HistoryEntry> for HistorySummary {
+    fn from(entry: &HistoryEntry) -> Self {
+        let text = entry.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let preview = match text.char_indices().nth(PREVIEW_CHARS) {
+            Some((end, _)) => format!("{}…", &text[..end]),
+            None => text,
+        };
+        Self {
+            id: entry.id.clone(),
+            source: entry.source.clone(),
+            preview,
+            provider: entry.provider.clone(),
+            voice: entry.voice.clone(),
+            started_at: entry.started_at,
+            last_read_at: entry.last_read_at,
+            duration_ms: entry.duration_ms,
+            position: entry.position,
+            length: utf16_len(&entry.text),
+            finished: entry.finished,
+        }
+    }
+}
+
+/// The read in progress: the entry it belongs to.
+struct Current {
+    id: String,
+    /// Whether `begin` created the entry (`abandon` removes it again).
+    created: bool,
+    /// Offset and listened time of the resumed part; progress is relative to it.
+    base_position: usize,
+    base_duration_ms: u64,
+    length: usize,
+}
+
+static CURRENT: Mutex<Option<Current>> = Mutex::new(None);
+
+static HISTORY: JsonStore<Vec<HistoryEntry>> =
+    JsonStore::new("reading history", history_path, SAVE_INTERVAL);
+
+/// Provider and voice new entries are recorded with; `None` while `history_enabled` is off.
+struct Recording {
+    provider: String,
+    voice: Option<String>,
+}
+
+static RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+
+/// Applies `history_enabled` and the selected voice (see
+/// `commands_config::apply_runtime_settings`).
+pub fn configure(cfg: &FullConfig) {
+    let recording = cfg.history_enabled.unwrap_or(false).then(|| Recording {
+        provider: cfg
+            .voice_provider
+            .clone()
+            .unwrap_or_else(|| "microsoft".to_string()),
+        voice: selected_voice(cfg),
+    });
+    if let Ok(mut guard) = RECORDING.lock() {
+        *guard = recording;
+    }
+}
+
+fn history_path() -> Result<PathBuf, String> {
+    Ok(paths::get_history_dir()?.join(HISTORY_FILE_NAME))
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// Byte index of UTF-16 offset `offset` in `text` (rounded down to a char boundary).
+fn byte_index(text: &str, offset: usize) -> usize {
+    let mut units = 0;
+    for (index, c) in text.char_indices() {
+        if units + c.len_utf16() > offset {
+            return index;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+/// Where a resume starts: the last spoken word, or the beginning once the text was finished.
+fn resume_offset(entry: &HistoryEntry) -> usize {
+    if entry.finished {
+        0
+    } else {
+        entry.position
+    }
+}
+
+/// Puts `entry` first and drops the oldest entries over the cap.
+fn push_front(entries: &mut Vec<HistoryEntry>, entry: HistoryEntry) {
+    entries.retain(|e| e.id != entry.id);
+    entries.insert(0, entry);
+    entries.truncate(MAX_ENTRIES);
+}
+
+fn selected_voice(cfg: &FullConfig) -> Option<String> {
+    match cfg.voice_provider.as_deref() {
+        Some("piper") => cfg.selected_voice.clone(),
+        Some("polly") => cfg.selected_polly_voice.clone(),
+        Some("system") => cfg.selected_system_voice.clone(),
+        Some("mobile") => cfg.selected_mobile_voice.clone(),
+        Some("custom") => cfg.custom_tts_speaker.clone(),
+        _ => cfg.selected_microsoft_voice.clone(),
+    }
+}
+
+/// Makes `current` the read in progress.
+fn set_current(current: Option<Current>) {
+    if let Ok(mut guard) = CURRENT.lock() {
+        *guard = current;
+    }
+}
+
+/// Records a new read of `text` (call before sending Speak). The Speak that follows is announced
+/// when start-of-read announcements are on.
+pub fn begin(source: &str, text: &str) {
+    tts::note_read_source(Some(source));
+    let entry = {
+        let Ok(recording) = RECORDING.lock() else {
+            return;
+        };
+        let Some(recording) = recording.as_ref() else {
+            set_current(None);
+            return;
+        };
+        let now = unix_millis_now();
+        HistoryEntry {
+            id: nanoid!(10),
+            source: source.to_string(),
+            text: text.to_string(),
+            provider: recording.provider.clone(),
+            voice: recording.voice.clone(),
+            started_at: now,
+            last_read_at: now,
+            duration_ms: 0,
+            position: 0,
+            finished: false,
+        }
+    };
+    let current = Current {
+        id: entry.id.clone(),
+        created: true,
+        base_position: 0,
+        base_duration_ms: 0,
+        length: utf16_len(text.trim_end()),
+    };
+    match HISTORY.update(|entries| push_front(entries, entry)) {
+        Ok(()) => set_current(Some(current)),
+        Err(e) => {
+            warn!(source, error = %e, "Failed to record reading history");
+            set_current(None);
+        }
+    }
+}
+
+/// Ends progress tracking for the current read; call before speech that is not recorded
+/// (voice previews, calibration, proofreading) so its progress is not mistaken for the read's.
+pub fn detach() {
+    tts::note_read_source(None);
+    set_current(None);
+}
+
+/// Forgets the read started by `begin` (or `history_resume`) when speech did not start.
+pub fn abandon() {
+    let Ok(mut guard) = CURRENT.lock() else {
+        return;
+    };
+    let Some(current) = guard.take() else {
+        return;
+    };
+    if !current.created {
+        return;
+    }
+    if let Err(e) = HISTORY.update(|entries| entries.retain(|e| e.id != current.id)) {
+        warn!(error = %e, "Failed to remove abandoned history entry");
+    }
+}
+
+/// Updates the current read from a worker progress report (see `tts::TtsProgress`).
+pub fn note_progress(progress: &tts::TtsProgress) {
+    let Ok(guard) = CURRENT.lock() else {
+        return;
+    };
+    let Some(current) = guard.as_ref() else {
+        return;
+    };
+    let result = HISTORY.update(|entries| {
+        if let Some(entry) = entries.iter_mut().find(|e| e.id == current.id) {
+            entry.position = current.base_position + progress.char_start;
+            entry.duration_ms = current.base_duration_ms + progress.position_ms;
+            entry.finished = current.base_position + progress.char_end >= current.length;
+        }
+    });
+    if let Err(e) = result {
+        warn!(error = %e, "Failed to save reading position");
+    }
+}
+
+/// Writes history changes not saved yet (shutdown).
+pub fn flush() {
+    HISTORY.flush();
+}
+
+// --- Commands ---
+
+/// Lists the reading history, most recently read first.
+#[tauri::command]
+pub fn history_list() -> Result<Vec<HistorySummary>, String> {
+    HISTORY.read(|entries| entries.iter().map(HistorySummary::from).collect())
+}
+
+/// Speaks a history entry from where it was left (from the start when it was finished).
+#[tauri::command]
+pub async fn history_resume(
+    state: State<'_, tts::TtsState>,
+    id: String,
+) -> Result<HistorySummary, String> {
+    let (text, offset, current, summary) = HISTORY.update(|entries| {
+        let mut entry = entries
+            .iter()
+            .find(|e| e.id == id)
+            .cloned()
+            .ok_or_else(|| "History entry not found".to_string())?;
+        let offset = resume_offset(&entry);
+        let text = entry.text[byte_index(&entry.text, offset)..].to_string();
+        if entry.finished {
+            entry.duration_ms = 0;
+            entry.finished = false;
+        }
+        entry.position = offset;
+        entry.last_read_at = unix_millis_now();
+        let current = Current {
+            id: entry.id.clone(),
+            created: false,
+            base_position: offset,
+            base_duration_ms: entry.duration_ms,
+            length: utf16_len(entry.text.trim_end()),
+        };
+        let summary = HistorySummary::from(&entry);
+        push_front(entries, entry);
+        Ok::<_, String>((text, offset, current, summary))
+    })??;
+    set_current(Some(current));
+    debug!(id = %id, offset, "Resuming read from history");
+
+    let tx = state.inner().clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let (resp_tx, resp_rx) = std::sync::mpsc::sync_channel(0);
+        tx.send(tts::TtsRequest::Speak(
+            text,
+            tts::InputKind::Text,
+            None,
+            resp_tx,
+        ))
+        .map_err(|e| format!("TTS channel: {e}"))?;
+        resp_rx
+            .recv()
+            .map_err(|_| "TTS worker disconnected".to_string())?
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking: {e}"))?;
+    if let Err(e) = result {
+        abandon();
+        return Err(e);
+    }
+    Ok(summary)
+}
+
+/// Deletes one history entry, or the whole history when `id` is omitted.
+#[tauri::command]
+pub fn history_delete(id: Option<String>) -> Result<(), String> {
+    if let Ok(mut guard) = CURRENT.lock() {
+        if guard
+            .as_ref()
+            .is_some_and(|c| id.as_ref().is_none_or(|id| *id == c.id))
+        {
+            *guard = None;
+        }
+    }
+    HISTORY.update_now(|entries| match id {
+        Some(id) => entries.retain(|e| e.id != id),
+        None => entries.clear(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resumes_from_last_spoken_word() {
+        let text = "Café 😀 resumes here";
+        let mut entry = HistoryEntry {
+            id: "a".into(),
+            source: "app".into(),
+            text: text.into(),
+            provider: "piper".into(),
+            voice: None,
+            started_at: 0,
+            last_read_at: 0,
+            duration_ms: 0,
+            // "Café 😀 " is 8 UTF-16 units.
+            position: 8,
+            finished: false,
+        };
+        assert_eq!(
+            &text[byte_index(text, resume_offset(&entry))..],
+            "resumes here"
+        );
+        entry.finished = true;
+        assert_eq!(resume_offset(&entry), 0);
+
+        let mut entries = Vec::new();
+        for i in 0..MAX_ENTRIES + 1 {
+            push_front(
+                &mut entries,
+                HistoryEntry {
+                    id: i.to_string(),
+                    ..entry.clone()
+                },
+            );
+        }
+        push_front(
+            &mut entries,
+            HistoryEntry {
+                id: "5".into(),
+                ..entry.clone()
+            },
+        );
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert_eq!(entries[0].id, "5");
+        assert_eq!(entries.iter().filter(|e| e.id == "5").count(), 1);
+    }
+}
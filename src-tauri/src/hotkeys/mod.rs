@@ -0,0 +1,949 @@
+//! Global keyboard shortcut registration and handling.
+//!
+//! Each action (read, pause, stop, read screenshot, summarize, open editor, skim speed,
+//! explain, translate; see `AppAction::ALL`) can have its own shortcut. By default they derive
+//! from one modifier+key (`hotkey_modifiers`, `hotkey_key`; Cmd+R / Ctrl+R for read, with shift
+//! for pause, with alt for read screenshot, with shift+alt for stop) and the other actions have
+//! none; the `hotkeys` config map overrides any of them per action. Two actions on the same
+//! shortcut are a conflict: the one listed first keeps it and the other is reported and left
+//! unregistered.
+//! Shortcuts are registered with the Tauri global shortcut plugin, and a failure for one action
+//! does not prevent the others.
+//!
+//! On Wayland, where applications cannot grab keys themselves, the shortcuts are bound through
+//! the XDG desktop portal instead (see `portal`; mode "wayland-portal"). The desktop may ask the
+//! user and assign other keys; the status shows the triggers it assigned. Without the portal
+//! (mode "wayland-compositor") we only report status; the frontend can use compositor-specific
+//! or in-app shortcuts. State (HotkeyRuntime) is managed in lib and
+//! passed to refresh_global_hotkeys and handle_global_shortcut_event. Called from lib's setup
+//! and from save_config when the user changes settings.
+//!
+//! get_shortcut_reference lists every action with its current hotkey, tray entry and CLI/socket
+//! names, for the frontend's cheat sheet.
+
+#[cfg(target_os = "linux")]
+mod portal;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
+use tracing::warn;
+
+use crate::actions::AppAction;
+use crate::config::{self, FullConfig, HotkeyBinding};
+use crate::tray;
+
+// --- State and config types ---
+
+/// Runtime state for global hotkeys: mode, registered shortcuts, per-action status, last error.
+/// Stored in Tauri state as `GlobalHotkeyState`.
+#[derive(Debug, Clone)]
+pub struct HotkeyRuntime {
+    pub mode: String,
+    pub session_type: String,
+    pub enabled: bool,
+    /// Some shortcut is registered, natively or through the portal (see `mode`).
+    pub native_active: bool,
+    pub shortcuts: Vec<(Shortcut, AppAction)>,
+    pub actions: Vec<ActionHotkeyStatus>,
+    pub last_error: Option<String>,
+}
+
+/// Serializable status returned by the get_hotkey_status command. `read_shortcut`,
+/// `pause_shortcut` and `screenshot_shortcut` repeat the labels from `actions` (empty when
+/// unassigned).
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HotkeyStatus {
+    pub mode: String,
+    pub session_type: String,
+    pub enabled: bool,
+    pub native_active: bool,
+    pub read_shortcut: String,
+    pub pause_shortcut: String,
+    pub screenshot_shortcut: String,
+    pub actions: Vec<ActionHotkeyStatus>,
+    pub last_error: Option<String>,
+}
+
+/// One action's shortcut in `HotkeyStatus`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ActionHotkeyStatus {
+    /// Action name (`AppAction::name`).
+    pub action: String,
+    /// Label such as "Ctrl+Shift+R"; `None` when the action has no shortcut.
+    pub shortcut: Option<String>,
+    pub registered: bool,
+    /// Why the shortcut is not registered: invalid, conflicting, or refused by the system.
+    pub error: Option<String>,
+}
+
+/// Everything that triggers an action, returned by the get_shortcut_reference command.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ShortcutReference {
+    pub mode: String,
+    pub hotkeys_enabled: bool,
+    pub hotkeys_active: bool,
+    pub entries: Vec<ShortcutReferenceEntry>,
+}
+
+/// One action (or tray-only entry) in `ShortcutReference`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ShortcutReferenceEntry {
+    /// Action name (`AppAction::name`), or the tray menu id of a tray-only entry.
+    pub id: String,
+    pub label: String,
+    /// Current global shortcut label; `None` when unassigned.
+    pub hotkey: Option<String>,
+    pub hotkey_registered: bool,
+    pub hotkey_error: Option<String>,
+    /// Tray menu id, when the tray menu has the entry.
+    pub tray_item: Option<String>,
+    /// Names accepted by `insight-reader action <name>` and the action socket.
+    pub action_names: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct EffectiveHotkeyConfig {
+    enabled: bool,
+    modifiers: String,
+    key: String,
+    bindings: HashMap<String, HotkeyBinding>,
+}
+
+/// An action's shortcut as resolved from config, before registration.
+#[derive(Debug)]
+struct PlannedHotkey {
+    action: AppAction,
+    label: Option<String>,
+    shortcut: Result<Option<Shortcut>, String>,
+}
+
+pub type GlobalHotkeyState = Arc<Mutex<HotkeyRuntime>>;
+
+// --- Platform defaults and session detection ---
+
+fn default_modifier_key() -> &'static str {
+    #[cfg(target_os = "macos")]
+    {
+        "command"
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        "control"
+    }
+}
+
+fn current_session_type() -> String {
+    std::env::var("XDG_SESSION_TYPE")
+        .unwrap_or_else(|_| "unknown".to_string())
+        .to_lowercase()
+}
+
+fn is_wayland_session() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        current_session_type() == "wayland"
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        false
+    }
+}
+
+fn supports_native_hotkeys() -> bool {
+    !is_wayland_session()
+}
+
+impl Default for HotkeyRuntime {
+    fn default() -> Self {
+        let effective = EffectiveHotkeyConfig::from_config(&FullConfig::default());
+        Self {
+            mode: "native".to_string(),
+            session_type: "unknown".to_string(),
+            enabled: true,
+            native_active: false,
+            shortcuts: Vec::new(),
+            actions: unregistered_statuses(&plan_hotkeys(&effective)),
+            last_error: None,
+        }
+    }
+}
+
+// --- Parsing and building shortcuts ---
+
+fn parse_modifier_token(token: &str) -> Option<Modifiers> {
+    match token {
+        "control" | "ctrl" => Some(Modifiers::CONTROL),
+        "shift" => Some(Modifiers::SHIFT),
+        "alt" | "option" => Some(Modifiers::ALT),
+        "command" | "cmd" | "super" | "meta" => Some(Modifiers::SUPER),
+        _ => None,
+    }
+}
+
+fn parse_modifiers(raw: &str) -> Result<Option<Modifiers>, String> {
+    let mut modifiers = Modifiers::empty();
+    for token in raw
+        .split(|c: char| c == '+' || c == ',' || c.is_whitespace())
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+    {
+        let parsed = parse_modifier_token(&token)
+            .ok_or_else(|| format!("Unsupported modifier token: {token}"))?;
+        modifiers |= parsed;
+    }
+
+    if modifiers.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(modifiers))
+    }
+}
+
+/// Parses a key: a letter or digit, F1–F24, an arrow ("Up", "ArrowUp"), Home/End/PageUp/PageDown/
+/// Insert/Delete, a numpad key ("Num1", "NumpadAdd", "Num+") or punctuation (";" or "Semicolon").
+fn parse_key_code(raw: &str) -> Result<Code, String> {
+    let code = match raw.trim().to_uppercase().as_str() {
+        "A" => Code::KeyA,
+        "B" => Code::KeyB,
+        "C" => Code::KeyC,
+        "D" => Code::KeyD,
+        "E" => Code::KeyE,
+        "F" => Code::KeyF,
+        "G" => Code::KeyG,
+        "H" => Code::KeyH,
+        "I" => Code::KeyI,
+        "J" => Code::KeyJ,
+        "K" => Code::KeyK,
+        "L" => Code::KeyL,
+        "M" => Code::KeyM,
+        "N" => Code::KeyN,
+        "O" => Code::KeyO,
+        "P" => Code::KeyP,
+        "Q" => Code::KeyQ,
+        "R" => Code::KeyR,
+        "S" => Code::KeyS,
+        "T" => Code::KeyT,
+        "U" => Code::KeyU,
+        "V" => Code::KeyV,
+        "W" => Code::KeyW,
+        "X" => Code::KeyX,
+        "Y" => Code::KeyY,
+        "Z" => Code::KeyZ,
+        "0" => Code::Digit0,
+        "1" => Code::Digit1,
+        "2" => Code::Digit2,
+        "3" => Code::Digit3,
+        "4" => Code::Digit4,
+        "5" => Code::Digit5,
+        "6" => Code::Digit6,
+        "7" => Code::Digit7,
+        "8" => Code::Digit8,
+        "9" => Code::Digit9,
+        "UP" | "ARROWUP" => Code::ArrowUp,
+        "DOWN" | "ARROWDOWN" => Code::ArrowDown,
+        "LEFT" | "ARROWLEFT" => Code::ArrowLeft,
+        "RIGHT" | "ARROWRIGHT" => Code::ArrowRight,
+        "HOME" => Code::Home,
+        "END" => Code::End,
+        "PAGEUP" | "PGUP" => Code::PageUp,
+        "PAGEDOWN" | "PGDN" => Code::PageDown,
+        "INSERT" | "INS" => Code::Insert,
+        "DELETE" | "DEL" => Code::Delete,
+        "NUM0" | "NUMPAD0" => Code::Numpad0,
+        "NUM1" | "NUMPAD1" => Code::Numpad1,
+        "NUM2" | "NUMPAD2" => Code::Numpad2,
+        "NUM3" | "NUMPAD3" => Code::Numpad3,
+        "NUM4" | "NUMPAD4" => Code::Numpad4,
+        "NUM5" | "NUMPAD5" => Code::Numpad5,
+        "NUM6" | "NUMPAD6" => Code::Numpad6,
+        "NUM7" | "NUMPAD7" => Code::Numpad7,
+        "NUM8" | "NUMPAD8" => Code::Numpad8,
+        "NUM9" | "NUMPAD9" => Code::Numpad9,
+        "NUM+" | "NUMADD" | "NUMPADADD" => Code::NumpadAdd,
+        "NUM-" | "NUMSUBTRACT" | "NUMPADSUBTRACT" => Code::NumpadSubtract,
+        "NUM*" | "NUMMULTIPLY" | "NUMPADMULTIPLY" => Code::NumpadMultiply,
+        "NUM/" | "NUMDIVIDE" | "NUMPADDIVIDE" => Code::NumpadDivide,
+        "NUM." | "NUMDECIMAL" | "NUMPADDECIMAL" => Code::NumpadDecimal,
+        "NUMENTER" | "NUMPADENTER" => Code::NumpadEnter,
+        ";" | "SEMICOLON" => Code::Semicolon,
+        "=" | "EQUAL" => Code::Equal,
+        "," | "COMMA" => Code::Comma,
+        "-" | "MINUS" => Code::Minus,
+        "." | "PERIOD" => Code::Period,
+        "/" | "SLASH" => Code::Slash,
+        "\\" | "BACKSLASH" => Code::Backslash,
+        "`" | "BACKQUOTE" => Code::Backquote,
+        "'" | "QUOTE" => Code::Quote,
+        "[" | "BRACKETLEFT" => Code::BracketLeft,
+        "]" | "BRACKETRIGHT" => Code::BracketRight,
+        other => {
+            return function_key(other)
+                .ok_or_else(|| format!("Unsupported hotkey key: {}", raw.trim()))
+        }
+    };
+    Ok(code)
+}
+
+/// F1–F24 (`key` uppercased).
+fn function_key(key: &str) -> Option<Code> {
+    let number: u8 = key.strip_prefix('F')?.parse().ok()?;
+    if !(1..=24).contains(&number) {
+        return None;
+    }
+    key.parse().ok()
+}
+
+/// Display form of a key: "R", "5", "F9", "Up", "PageUp", "Num1", ";".
+fn key_label(key: &str) -> String {
+    let key = key.trim();
+    let Ok(code) = parse_key_code(key) else {
+        return key.to_uppercase();
+    };
+    let name = code.to_string();
+    if let Some(rest) = ["Key", "Digit", "Arrow"]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+    {
+        rest.to_string()
+    } else if let Some(rest) = name.strip_prefix("Numpad") {
+        format!("Num{rest}")
+    } else if key.chars().count() == 1 {
+        key.to_string()
+    } else {
+        name
+    }
+}
+
+fn format_modifier_label(raw: &str) -> String {
+    raw.split(|c: char| c == '+' || c == ',' || c.is_whitespace())
+        .filter_map(|token| {
+            let normalized = token.trim().to_lowercase();
+            if normalized.is_empty() {
+                return None;
+            }
+            let label = match normalized.as_str() {
+                "control" | "ctrl" => "Ctrl",
+                "shift" => "Shift",
+                "alt" | "option" => "Alt",
+                "command" | "cmd" => "Cmd",
+                "super" | "meta" => "Super",
+                _ => token.trim(),
+            };
+            Some(label.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+fn build_shortcut(modifiers: &str, key: &str) -> Result<Shortcut, String> {
+    let mods = parse_modifiers(modifiers)?;
+    let code = parse_key_code(key)?;
+    Ok(Shortcut::new(mods, code))
+}
+
+fn shortcut_label(modifiers: &str, key: &str) -> String {
+    let mod_label = format_modifier_label(modifiers);
+    let key_label = key_label(key);
+    if mod_label.is_empty() {
+        key_label
+    } else {
+        format!("{mod_label}+{key_label}")
+    }
+}
+
+impl EffectiveHotkeyConfig {
+    fn from_config(config: &FullConfig) -> Self {
+        Self {
+            enabled: config.hotkey_enabled.unwrap_or(true),
+            modifiers: config
+                .hotkey_modifiers
+                .clone()
+                .unwrap_or_else(|| default_modifier_key().to_string()),
+            key: config.hotkey_key.clone().unwrap_or_else(|| "r".to_string()),
+            bindings: config.hotkeys.clone().unwrap_or_default(),
+        }
+    }
+}
+
+fn load_effective_hotkey_config() -> EffectiveHotkeyConfig {
+    EffectiveHotkeyConfig::from_config(&config::load_full_config().unwrap_or_default())
+}
+
+/// `modifiers` plus `extra[0]`, unless it already contains it (or one of its aliases).
+fn with_modifier(modifiers: &str, extra: &[&str]) -> String {
+    let lower = modifiers.to_lowercase();
+    if extra.iter().any(|alias| lower.contains(alias)) {
+        modifiers.to_string()
+    } else {
+        format!("{}+{}", modifiers, extra[0])
+    }
+}
+
+/// The shortcut an action gets from `hotkey_modifiers` and `hotkey_key` alone, if any.
+fn default_shortcut_parts(
+    config: &EffectiveHotkeyConfig,
+    action: AppAction,
+) -> Option<(String, String)> {
+    let modifiers = match action {
+        AppAction::ReadSelected => config.modifiers.clone(),
+        AppAction::TogglePause => with_modifier(&config.modifiers, &["shift"]),
+        AppAction::ReadScreenshot => with_modifier(&config.modifiers, &["alt", "option"]),
+        AppAction::Stop => with_modifier(
+            &with_modifier(&config.modifiers, &["shift"]),
+            &["alt", "option"],
+        ),
+        AppAction::Summarize
+        | AppAction::OpenEditor
+        | AppAction::ToggleSkim
+        | AppAction::ExplainSelected
+        | AppAction::TranslateSelected => return None,
+    };
+    Some((modifiers, config.key.clone()))
+}
+
+/// The action's shortcut: its `hotkeys` entry when it names a key, otherwise the default.
+fn action_shortcut_parts(
+    config: &EffectiveHotkeyConfig,
+    action: AppAction,
+) -> Option<(String, String)> {
+    let Some(binding) = config.bindings.get(action.name()) else {
+        return default_shortcut_parts(config, action);
+    };
+    if binding.enabled == Some(false) {
+        return None;
+    }
+    match binding.key.as_deref().map(str::trim) {
+        Some("") => None,
+        Some(key) => Some((
+            binding
+                .modifiers
+                .clone()
+                .unwrap_or_else(|| config.modifiers.clone()),
+            key.to_string(),
+        )),
+        None => default_shortcut_parts(config, action),
+    }
+}
+
+/// Checks the hotkey settings before they are saved: known action names in `hotkeys`, and
+/// modifiers and keys the parser accepts. Conflicts are allowed; the status reports them.
+pub fn validate_config(config: &FullConfig) -> Result<(), String> {
+    let effective = EffectiveHotkeyConfig::from_config(config);
+    if let Some(name) = effective
+        .bindings
+        .keys()
+        .find(|name| !AppAction::ALL.iter().any(|action| action.name() == *name))
+    {
+        return Err(format!("Unknown hotkey action: {name}"));
+    }
+    for action in AppAction::ALL {
+        if let Some((modifiers, key)) = action_shortcut_parts(&effective, action) {
+            build_shortcut(&modifiers, &key)
+                .map_err(|e| format!("Invalid hotkey for {}: {e}", action.name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Resolves every action's shortcut and marks conflicts: an action whose shortcut is already
+/// taken by an earlier action in `AppAction::ALL` gets an error instead.
+fn plan_hotkeys(config: &EffectiveHotkeyConfig) -> Vec<PlannedHotkey> {
+    let mut planned: Vec<PlannedHotkey> = Vec::new();
+    for action in AppAction::ALL {
+        let parts = action_shortcut_parts(config, action);
+        let label = parts
+            .as_ref()
+            .map(|(modifiers, key)| shortcut_label(modifiers, key));
+        let mut shortcut = parts
+            .map(|(modifiers, key)| build_shortcut(&modifiers, &key))
+            .transpose();
+        if let Ok(Some(candidate)) = &shortcut {
+            let taken_by = planned
+                .iter()
+                .find(|other| matches!(&other.shortcut, Ok(Some(s)) if s == candidate));
+            if let Some(other) = taken_by {
+                shortcut = Err(format!(
+                    "{} is already used by {}",
+                    label.as_deref().unwrap_or_default(),
+                    other.action.name()
+                ));
+            }
+        }
+        planned.push(PlannedHotkey {
+            action,
+            label,
+            shortcut,
+        });
+    }
+    planned
+}
+
+/// Status of each planned shortcut before (or without) registration.
+fn unregistered_statuses(planned: &[PlannedHotkey]) -> Vec<ActionHotkeyStatus> {
+    planned
+        .iter()
+        .map(|plan| ActionHotkeyStatus {
+            action: plan.action.name().to_string(),
+            shortcut: plan.label.clone(),
+            registered: false,
+            error: plan.shortcut.as_ref().err().cloned(),
+        })
+        .collect()
+}
+
+// --- Registration and event handling ---
+
+/// Re-reads config and registers or unregisters global shortcuts. Called from setup and save_config.
+pub fn refresh_global_hotkeys<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    state: &GlobalHotkeyState,
+) {
+    let effective = load_effective_hotkey_config();
+    let session_type = current_session_type();
+    let mode = if supports_native_hotkeys() {
+        "native"
+    } else if cfg!(target_os = "linux") && effective.enabled {
+        "wayland-portal"
+    } else {
+        "wayland-compositor"
+    };
+    let planned = plan_hotkeys(&effective);
+    let mut statuses = unregistered_statuses(&planned);
+
+    if let Ok(mut runtime) = state.lock() {
+        runtime.mode = mode.to_string();
+        runtime.session_type = session_type;
+        runtime.enabled = effective.enabled;
+        runtime.actions = statuses.clone();
+        runtime.last_error = None;
+        runtime.native_active = false;
+        runtime.shortcuts.clear();
+    }
+
+    if let Err(e) = app.global_shortcut().unregister_all() {
+        let message = format!("Failed to clear old global shortcuts: {e}");
+        if let Ok(mut runtime) = state.lock() {
+            runtime.last_error = Some(message);
+        }
+        warn!(error = %e, "Failed to clear old global shortcuts");
+        return;
+    }
+
+    if !supports_native_hotkeys() {
+        #[cfg(target_os = "linux")]
+        refresh_portal_hotkeys(app, state, &planned, effective.enabled);
+        return;
+    }
+    if !effective.enabled {
+        return;
+    }
+
+    let mut shortcuts = Vec::new();
+    for (plan, status) in planned.iter().zip(statuses.iter_mut()) {
+        let shortcut = match &plan.shortcut {
+            Ok(Some(shortcut)) => *shortcut,
+            Ok(None) => continue,
+            Err(e) => {
+                warn!(action = plan.action.name(), error = %e, "Shortcut unavailable");
+                continue;
+            }
+        };
+        match app.global_shortcut().register(shortcut) {
+            Ok(()) => {
+                status.registered = true;
+                shortcuts.push((shortcut, plan.action));
+            }
+            Err(e) => {
+                let label = plan.label.as_deref().unwrap_or_default();
+                warn!(error = %e, shortcut = %label, action = plan.action.name(),
+                    "Failed to register shortcut");
+                status.error = Some(format!("Failed to register {label}: {e}"));
+            }
+        }
+    }
+
+    if let Ok(mut runtime) = state.lock() {
+        runtime.native_active = !shortcuts.is_empty();
+        runtime.shortcuts = shortcuts;
+        runtime.last_error = statuses.iter().find_map(|status| status.error.clone());
+        runtime.actions = statuses;
+    }
+}
+
+/// Binds the planned shortcuts through the desktop portal, on a thread since the desktop may ask
+/// the user first, and records the triggers it assigned. Falls back to "wayland-compositor" when
+/// the portal is unavailable.
+#[cfg(target_os = "linux")]
+fn refresh_portal_hotkeys<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    state: &GlobalHotkeyState,
+    planned: &[PlannedHotkey],
+    enabled: bool,
+) {
+    let generation = portal::request();
+    let shortcuts: Vec<(AppAction, Option<String>)> = planned
+        .iter()
+        .filter_map(|plan| match &plan.shortcut {
+            Ok(Some(shortcut)) => Some((plan.action, portal::preferred_trigger(shortcut))),
+            _ => None,
+        })
+        .collect();
+    if !enabled || shortcuts.is_empty() {
+        std::thread::spawn(move || portal::unbind(generation));
+        return;
+    }
+
+    let handler: portal::PortalHandler = {
+        let app = app.clone();
+        let state = state.clone();
+        Arc::new(move |event: portal::PortalEvent| match event {
+            portal::PortalEvent::Activated(action) => {
+                crate::actions::execute_action(&app, action, "global-hotkey")
+            }
+            portal::PortalEvent::Changed(bound) => {
+                if let Ok(mut runtime) = state.lock() {
+                    apply_portal_bindings(&mut runtime, &bound);
+                }
+            }
+        })
+    };
+    let state = state.clone();
+    let spawned = std::thread::Builder::new()
+        .name("hotkey-portal-bind".into())
+        .spawn(
+            move || match portal::bind(generation, &shortcuts, handler) {
+                Ok(Some(bound)) => {
+                    if let Ok(mut runtime) = state.lock() {
+                        apply_portal_bindings(&mut runtime, &bound);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    warn!(error = %e, "Failed to bind shortcuts through the desktop portal");
+                    if let Ok(mut runtime) = state.lock() {
+                        runtime.mode = "wayland-compositor".to_string();
+                        runtime.last_error = Some(e);
+                    }
+                }
+            },
+        );
+    if let Err(e) = spawned {
+        warn!(error = %e, "Failed to start portal binding");
+    }
+}
+
+/// Marks the actions the portal bound as registered, showing the trigger it assigned; requested
+/// actions it left out get an error.
+#[cfg(target_os = "linux")]
+fn apply_portal_bindings(runtime: &mut HotkeyRuntime, bound: &[(AppAction, String)]) {
+    for status in &mut runtime.actions {
+        match bound
+            .iter()
+            .find(|(action, _)| action.name() == status.action)
+        {
+            Some((_, trigger)) => {
+                status.registered = true;
+                status.error = None;
+                if !trigger.is_empty() {
+                    status.shortcut = Some(trigger.clone());
+                }
+            }
+            None if status.shortcut.is_some() && status.error.is_none() => {
+                status.registered = false;
+                status.error = Some("Not bound by the desktop portal".to_string());
+            }
+            None => {}
+        }
+    }
+    runtime.native_active = !bound.is_empty();
+    runtime.last_error = runtime
+        .actions
+        .iter()
+        .find_map(|status| status.error.clone());
+}
+
+/// Called by the global shortcut plugin when a key is pressed. Determines the action and invokes dispatch.
+pub fn handle_global_shortcut_event<R, F>(
+    app: &tauri::AppHandle<R>,
+    shortcut: &Shortcut,
+    event_state: ShortcutState,
+    hotkey_state: &GlobalHotkeyState,
+    dispatch: F,
+) where
+    R: tauri::Runtime,
+    F: Fn(&tauri::AppHandle<R>, AppAction),
+{
+    if event_state != ShortcutState::Pressed {
+        return;
+    }
+
+    let action = {
+        let Ok(runtime) = hotkey_state.lock() else {
+            return;
+        };
+
+        if !runtime.native_active {
+            return;
+        }
+
+        runtime
+            .shortcuts
+            .iter()
+            .find(|(registered, _)| registered == shortcut)
+            .map(|(_, action)| *action)
+    };
+
+    if let Some(action) = action {
+        dispatch(app, action);
+    }
+}
+
+// --- Command ---
+
+fn action_label(actions: &[ActionHotkeyStatus], action: AppAction) -> String {
+    actions
+        .iter()
+        .find(|status| status.action == action.name())
+        .and_then(|status| status.shortcut.clone())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn get_hotkey_status(state: tauri::State<GlobalHotkeyState>) -> HotkeyStatus {
+    let runtime = match state.inner().lock() {
+        Ok(runtime) => runtime.clone(),
+        Err(_) => HotkeyRuntime {
+            mode: "unknown".to_string(),
+            enabled: false,
+            last_error: Some("Hotkey state unavailable".to_string()),
+            ..HotkeyRuntime::default()
+        },
+    };
+    HotkeyStatus {
+        read_shortcut: action_label(&runtime.actions, AppAction::ReadSelected),
+        pause_shortcut: action_label(&runtime.actions, AppAction::TogglePause),
+        screenshot_shortcut: action_label(&runtime.actions, AppAction::ReadScreenshot),
+        mode: runtime.mode,
+        session_type: runtime.session_type,
+        enabled: runtime.enabled,
+        native_active: runtime.native_active,
+        actions: runtime.actions,
+        last_error: runtime.last_error,
+    }
+}
+
+/// The reference for the current hotkey state: the actions in `AppAction::ALL` order, then the
+/// tray-only entries.
+fn shortcut_reference(runtime: &HotkeyRuntime) -> ShortcutReference {
+    let actions = AppAction::ALL.into_iter().map(|action| {
+        let status = runtime
+            .actions
+            .iter()
+            .find(|status| status.action == action.name());
+        ShortcutReferenceEntry {
+            id: action.name().to_string(),
+            label: action.label().to_string(),
+            hotkey: status.and_then(|status| status.shortcut.clone()),
+            hotkey_registered: status.is_some_and(|status| status.registered),
+            hotkey_error: status.and_then(|status| status.error.clone()),
+            tray_item: tray::ACTION_ITEMS
+                .iter()
+                .find(|(_, item_action)| *item_action == action)
+                .map(|(id, _)| id.to_string()),
+            action_names: action
+                .aliases()
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        }
+    });
+    let window_items = tray::WINDOW_ITEMS
+        .iter()
+        .map(|(id, label)| ShortcutReferenceEntry {
+            id: id.to_string(),
+            label: label.to_string(),
+            hotkey: None,
+            hotkey_registered: false,
+            hotkey_error: None,
+            tray_item: Some(id.to_string()),
+            action_names: Vec::new(),
+        });
+    ShortcutReference {
+        mode: runtime.mode.clone(),
+        hotkeys_enabled: runtime.enabled,
+        hotkeys_active: runtime.native_active,
+        entries: actions.chain(window_items).collect(),
+    }
+}
+
+/// Every action with its current hotkey, tray entry and CLI/socket names, for a cheat sheet.
+#[tauri::command]
+pub fn get_shortcut_reference(state: tauri::State<GlobalHotkeyState>) -> ShortcutReference {
+    match state.inner().lock() {
+        Ok(runtime) => shortcut_reference(&runtime),
+        Err(_) => shortcut_reference(&HotkeyRuntime {
+            mode: "unknown".to_string(),
+            enabled: false,
+            ..HotkeyRuntime::default()
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn effective(bindings: &[(&str, HotkeyBinding)]) -> EffectiveHotkeyConfig {
+        EffectiveHotkeyConfig {
+            enabled: true,
+            modifiers: "control".to_string(),
+            key: "r".to_string(),
+            bindings: bindings
+                .iter()
+                .map(|(name, binding)| (name.to_string(), binding.clone()))
+                .collect(),
+        }
+    }
+
+    fn binding(modifiers: Option<&str>, key: &str) -> HotkeyBinding {
+        HotkeyBinding {
+            modifiers: modifiers.map(str::to_string),
+            key: Some(key.to_string()),
+            enabled: None,
+        }
+    }
+
+    fn labels(planned: &[PlannedHotkey]) -> Vec<Option<&str>> {
+        planned.iter().map(|plan| plan.label.as_deref()).collect()
+    }
+
+    #[test]
+    fn test_defaults_derive_from_the_read_shortcut() {
+        let planned = plan_hotkeys(&effective(&[]));
+        assert_eq!(
+            labels(&planned),
+            vec![
+                Some("Ctrl+R"),
+                Some("Ctrl+Shift+R"),
+                Some("Ctrl+Shift+Alt+R"),
+                Some("Ctrl+Alt+R"),
+                None,
+                None,
+                None,
+                None,
+                None,
+            ]
+        );
+        assert!(planned.iter().all(|plan| plan.shortcut.is_ok()));
+    }
+
+    #[test]
+    fn test_bindings_override_per_action_and_conflicts_are_reported() {
+        let planned = plan_hotkeys(&effective(&[
+            ("summarize", binding(Some("ctrl+shift"), "s")),
+            ("editor", binding(None, "e")),
+            ("stop", binding(Some("ctrl"), "r")),
+            (
+                "screenshot",
+                HotkeyBinding {
+                    enabled: Some(false),
+                    ..HotkeyBinding::default()
+                },
+            ),
+        ]));
+        let by_action = |action: AppAction| {
+            planned
+                .iter()
+                .find(|plan| plan.action == action)
+                .map(|plan| (plan.label.as_deref(), plan.shortcut.is_ok()))
+        };
+
+        assert_eq!(
+            by_action(AppAction::Summarize),
+            Some((Some("Ctrl+Shift+S"), true))
+        );
+        assert_eq!(
+            by_action(AppAction::OpenEditor),
+            Some((Some("Ctrl+E"), true))
+        );
+        assert_eq!(by_action(AppAction::ReadScreenshot), Some((None, true)));
+        let stop = planned
+            .iter()
+            .find(|plan| plan.action == AppAction::Stop)
+            .map(|plan| plan.shortcut.clone());
+        assert_eq!(
+            stop,
+            Some(Err("Ctrl+R is already used by read".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_reference_lists_actions_then_tray_entries() {
+        let reference = shortcut_reference(&HotkeyRuntime::default());
+        let ids: Vec<&str> = reference.entries.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "read",
+                "pause",
+                "stop",
+                "screenshot",
+                "summarize",
+                "editor",
+                "skim",
+                "explain",
+                "translate",
+                "hide_window",
+                "show_window",
+                "quit",
+            ]
+        );
+        let read = &reference.entries[0];
+        assert_eq!(read.tray_item.as_deref(), Some("read_selected"));
+        assert!(read.action_names.contains(&"read-selected".to_string()));
+        assert!(read.hotkey.is_some() && !read.hotkey_registered);
+        assert_eq!(reference.entries[1].tray_item, None);
+        assert_eq!(reference.entries[4].hotkey, None);
+    }
+
+    #[test]
+    fn test_parses_function_navigation_numpad_and_punctuation_keys() {
+        for (key, code, label) in [
+            ("f9", Code::F9, "F9"),
+            ("F24", Code::F24, "F24"),
+            ("ArrowUp", Code::ArrowUp, "Up"),
+            ("pgdn", Code::PageDown, "PageDown"),
+            ("num7", Code::Numpad7, "Num7"),
+            ("Num+", Code::NumpadAdd, "NumAdd"),
+            (";", Code::Semicolon, ";"),
+            ("bracketleft", Code::BracketLeft, "BracketLeft"),
+            ("r", Code::KeyR, "R"),
+        ] {
+            assert_eq!(parse_key_code(key), Ok(code), "{key}");
+            assert_eq!(key_label(key), label, "{key}");
+        }
+        assert!(parse_key_code("F25").is_err());
+        assert!(parse_key_code("F0").is_err());
+
+        let mut config = FullConfig {
+            hotkey_key: Some("F9".to_string()),
+            ..FullConfig::default()
+        };
+        assert_eq!(validate_config(&config), Ok(()));
+        config.hotkeys = Some(HashMap::from([(
+            "stop".to_string(),
+            binding(Some("ctrl"), "Esc"),
+        )]));
+        assert_eq!(
+            validate_config(&config),
+            Err("Invalid hotkey for stop: Unsupported hotkey key: Esc".to_string())
+        );
+        config.hotkeys = Some(HashMap::from([("jump".to_string(), binding(None, "j"))]));
+        assert_eq!(
+            validate_config(&config),
+            Err("Unknown hotkey action: jump".to_string())
+        );
+    }
+}
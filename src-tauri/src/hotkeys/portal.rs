@@ -0,0 +1,404 @@
+//! Global shortcuts on Wayland through the XDG desktop portal
+//! (`org.freedesktop.portal.GlobalShortcuts`), supported by KDE and GNOME.
+//!
+//! Shortcuts belong to a portal session. `bind` creates a session, asks for the actions'
+//! shortcuts with their configured keys as preferred triggers (the desktop may ask the user and
+//! assign others) and then closes the previous session, so a new configuration replaces the old
+//! one. A listener thread, started with the first session, forwards the current session's
+//! `Activated` and `ShortcutsChanged` signals to the handler given to `bind`.
+//!
+//! Binding can wait for the user, so callers run it off the main thread; `request` orders
+//! concurrent binds so that only the latest one takes effect.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
+use tracing::{debug, warn};
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+
+use crate::actions::AppAction;
+
+const DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PATH: &str = "/org/freedesktop/portal/desktop";
+const INTERFACE: &str = "org.freedesktop.portal.GlobalShortcuts";
+
+/// A signal of the current session.
+pub enum PortalEvent {
+    Activated(AppAction),
+    /// The user changed the shortcuts in the desktop settings: the new trigger of each action.
+    Changed(Vec<(AppAction, String)>),
+}
+
+pub type PortalHandler = Arc<dyn Fn(PortalEvent) + Send + Sync>;
+
+struct Portal {
+    connection: Connection,
+    session: Option<OwnedObjectPath>,
+    handler: Option<PortalHandler>,
+}
+
+static PORTAL: Mutex<Option<Portal>> = Mutex::new(None);
+/// Held while binding, so sessions are created one at a time.
+static BINDING: Mutex<()> = Mutex::new(());
+static REQUESTED: AtomicU64 = AtomicU64::new(0);
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(0);
+
+/// Starts a new configuration; binds and unbinds of earlier ones are dropped. Returns its id
+/// for `bind` and `unbind`.
+pub fn request() -> u64 {
+    REQUESTED.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+fn superseded(generation: u64) -> bool {
+    REQUESTED.load(Ordering::SeqCst) != generation
+}
+
+/// Binds `shortcuts` (each action with its preferred trigger, if any) in a new session and
+/// closes the previous one. Returns each bound action with the trigger the desktop assigned
+/// (empty when it has none yet), or `None` when a later `request` superseded this one.
+pub fn bind(
+    generation: u64,
+    shortcuts: &[(AppAction, Option<String>)],
+    handler: PortalHandler,
+) -> Result<Option<Vec<(AppAction, String)>>, String> {
+    let _binding = BINDING
+        .lock()
+        .map_err(|_| "Portal state unavailable".to_string())?;
+    if superseded(generation) {
+        return Ok(None);
+    }
+    let connection = connection()?;
+    let session = create_session(&connection)?;
+    let bound = match bind_shortcuts(&connection, &session, shortcuts) {
+        Ok(bound) => bound,
+        Err(e) => {
+            close_session(&connection, &session);
+            return Err(e);
+        }
+    };
+    if superseded(generation) {
+        close_session(&connection, &session);
+        return Ok(None);
+    }
+    let previous = {
+        let mut portal = PORTAL
+            .lock()
+            .map_err(|_| "Portal state unavailable".to_string())?;
+        portal.as_mut().and_then(|portal| {
+            portal.handler = Some(handler);
+            portal.session.replace(session)
+        })
+    };
+    if let Some(previous) = previous {
+        close_session(&connection, &previous);
+    }
+    Ok(Some(bound))
+}
+
+/// Closes the current session (hotkeys disabled), unless a later `request` superseded this one.
+pub fn unbind(generation: u64) {
+    let Ok(_binding) = BINDING.lock() else {
+        return;
+    };
+    if superseded(generation) {
+        return;
+    }
+    let current = PORTAL.lock().ok().and_then(|mut portal| {
+        let portal = portal.as_mut()?;
+        portal.handler = None;
+        Some((portal.connection.clone(), portal.session.take()?))
+    });
+    if let Some((connection, session)) = current {
+        close_session(&connection, &session);
+    }
+}
+
+/// The session bus connection, opened (and the listener started) on first use.
+fn connection() -> Result<Connection, String> {
+    let mut portal = PORTAL
+        .lock()
+        .map_err(|_| "Portal state unavailable".to_string())?;
+    if let Some(portal) = portal.as_ref() {
+        return Ok(portal.connection.clone());
+    }
+    let connection =
+        Connection::session().map_err(|e| format!("Failed to connect to the session bus: {e}"))?;
+    let listener = connection.clone();
+    std::thread::Builder::new()
+        .name("hotkey-portal".into())
+        .spawn(move || {
+            if let Err(e) = listen(&listener) {
+                warn!(error = %e, "Global shortcuts portal listener stopped");
+            }
+        })
+        .map_err(|e| format!("Failed to start the portal listener: {e}"))?;
+    *portal = Some(Portal {
+        connection: connection.clone(),
+        session: None,
+        handler: None,
+    });
+    Ok(connection)
+}
+
+fn next_token() -> String {
+    format!(
+        "insight_reader_{}",
+        NEXT_TOKEN.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Calls `method`, whose options carry `handle_token = token`, and waits for the portal's
+/// response. Returns its results, or why the request failed.
+fn portal_request<B>(
+    connection: &Connection,
+    token: &str,
+    method: &str,
+    body: &B,
+) -> Result<HashMap<String, OwnedValue>, String>
+where
+    B: serde::Serialize + zbus::zvariant::DynamicType,
+{
+    // The request object's path is known up front, so the response cannot be missed.
+    let sender = connection
+        .unique_name()
+        .ok_or_else(|| "Not connected to the session bus".to_string())?
+        .trim_start_matches(':')
+        .replace('.', "_");
+    let request_path = format!("{PATH}/request/{sender}/{token}");
+    let request = Proxy::new(
+        connection,
+        DESTINATION,
+        request_path.as_str(),
+        "org.freedesktop.portal.Request",
+    )
+    .map_err(|e| format!("{method} failed: {e}"))?;
+    let mut responses = request
+        .receive_signal("Response")
+        .map_err(|e| format!("{method} failed: {e}"))?;
+    Proxy::new(connection, DESTINATION, PATH, INTERFACE)
+        .and_then(|portal| portal.call_method(method, body))
+        .map_err(|e| format!("Global shortcuts portal unavailable ({method}): {e}"))?;
+    let response = responses
+        .next()
+        .ok_or_else(|| format!("{method}: the portal closed the request"))?;
+    let (code, results): (u32, HashMap<String, OwnedValue>) = response
+        .body()
+        .deserialize()
+        .map_err(|e| format!("{method}: invalid response: {e}"))?;
+    match code {
+        0 => Ok(results),
+        1 => Err(format!("{method} was cancelled")),
+        _ => Err(format!("{method} failed")),
+    }
+}
+
+fn create_session(connection: &Connection) -> Result<OwnedObjectPath, String> {
+    let token = next_token();
+    let session_token = next_token();
+    let options = HashMap::from([
+        ("handle_token", Value::from(token.as_str())),
+        ("session_handle_token", Value::from(session_token.as_str())),
+    ]);
+    let results = portal_request(connection, &token, "CreateSession", &(options,))?;
+    // A string in older portal versions, an object path in newer ones.
+    let session = match results.get("session_handle").map(|handle| &**handle) {
+        Some(Value::Str(handle)) => ObjectPath::try_from(handle.as_str())
+            .map(OwnedObjectPath::from)
+            .ok(),
+        Some(Value::ObjectPath(handle)) => Some(OwnedObjectPath::from(handle.clone())),
+        _ => None,
+    };
+    let session = session.ok_or_else(|| "CreateSession: no session handle".to_string())?;
+    debug!(session = %session.as_str(), "Global shortcuts portal session created");
+    Ok(session)
+}
+
+fn bind_shortcuts(
+    connection: &Connection,
+    session: &OwnedObjectPath,
+    shortcuts: &[(AppAction, Option<String>)],
+) -> Result<Vec<(AppAction, String)>, String> {
+    let token = next_token();
+    let requested: Vec<(&str, HashMap<&str, Value>)> = shortcuts
+        .iter()
+        .map(|(action, trigger)| {
+            let mut properties = HashMap::from([("description", Value::from(action.label()))]);
+            if let Some(trigger) = trigger {
+                properties.insert("preferred_trigger", Value::from(trigger.as_str()));
+            }
+            (action.name(), properties)
+        })
+        .collect();
+    let options = HashMap::from([("handle_token", Value::from(token.as_str()))]);
+    let body = (session.as_ref(), requested, "", options);
+    let results = portal_request(connection, &token, "BindShortcuts", &body)?;
+    let bound: Vec<(String, HashMap<String, OwnedValue>)> = results
+        .get("shortcuts")
+        .and_then(|shortcuts| shortcuts.try_clone().ok())
+        .and_then(|shortcuts| shortcuts.try_into().ok())
+        .unwrap_or_default();
+    Ok(triggers(bound))
+}
+
+fn close_session(connection: &Connection, session: &OwnedObjectPath) {
+    let closed = Proxy::new(
+        connection,
+        DESTINATION,
+        session.as_str(),
+        "org.freedesktop.portal.Session",
+    )
+    .and_then(|proxy| proxy.call_method("Close", &()));
+    if let Err(e) = closed {
+        debug!(error = %e, "Failed to close global shortcuts portal session");
+    }
+}
+
+/// Each known action in a portal shortcut list with its trigger description.
+fn triggers(shortcuts: Vec<(String, HashMap<String, OwnedValue>)>) -> Vec<(AppAction, String)> {
+    shortcuts
+        .into_iter()
+        .filter_map(|(id, properties)| {
+            let action = AppAction::ALL
+                .into_iter()
+                .find(|action| action.name() == id)?;
+            let trigger = match properties.get("trigger_description").map(|value| &**value) {
+                Some(Value::Str(trigger)) => trigger.to_string(),
+                _ => String::new(),
+            };
+            Some((action, trigger))
+        })
+        .collect()
+}
+
+/// The handler of `session`, if it is the current one.
+fn current_handler(session: &str) -> Option<PortalHandler> {
+    let portal = PORTAL.lock().ok()?;
+    let portal = portal.as_ref()?;
+    if portal.session.as_ref()?.as_str() != session {
+        return None;
+    }
+    portal.handler.clone()
+}
+
+fn listen(connection: &Connection) -> zbus::Result<()> {
+    let portal = Proxy::new(connection, DESTINATION, PATH, INTERFACE)?;
+    for message in portal.receive_all_signals()? {
+        let header = message.header();
+        let event = match header.member().map(|member| member.as_str()) {
+            Some("Activated") => message
+                .body()
+                .deserialize::<(OwnedObjectPath, String, u64, HashMap<String, OwnedValue>)>()
+                .ok()
+                .and_then(|(session, id, _, _)| {
+                    let action = AppAction::ALL
+                        .into_iter()
+                        .find(|action| action.name() == id)?;
+                    Some((session, PortalEvent::Activated(action)))
+                }),
+            Some("ShortcutsChanged") => message
+                .body()
+                .deserialize::<(OwnedObjectPath, Vec<(String, HashMap<String, OwnedValue>)>)>()
+                .ok()
+                .map(|(session, shortcuts)| (session, PortalEvent::Changed(triggers(shortcuts)))),
+            _ => None,
+        };
+        if let Some((session, event)) = event {
+            if let Some(handler) = current_handler(session.as_str()) {
+                handler(event);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `shortcut` as a portal trigger ("CTRL+SHIFT+r"): modifiers, then the XKB key name.
+pub fn preferred_trigger(shortcut: &Shortcut) -> Option<String> {
+    let mut parts: Vec<String> = [
+        (Modifiers::CONTROL, "CTRL"),
+        (Modifiers::ALT, "ALT"),
+        (Modifiers::SHIFT, "SHIFT"),
+        (Modifiers::SUPER, "LOGO"),
+    ]
+    .into_iter()
+    .filter(|(modifier, _)| shortcut.mods.contains(*modifier))
+    .map(|(_, name)| name.to_string())
+    .collect();
+    parts.push(key_name(shortcut.key)?);
+    Some(parts.join("+"))
+}
+
+/// The XKB keysym name of a key `parse_key_code` accepts.
+fn key_name(code: Code) -> Option<String> {
+    let name = match code {
+        Code::ArrowUp => "Up",
+        Code::ArrowDown => "Down",
+        Code::ArrowLeft => "Left",
+        Code::ArrowRight => "Right",
+        Code::Home => "Home",
+        Code::End => "End",
+        Code::PageUp => "Page_Up",
+        Code::PageDown => "Page_Down",
+        Code::Insert => "Insert",
+        Code::Delete => "Delete",
+        Code::NumpadAdd => "KP_Add",
+        Code::NumpadSubtract => "KP_Subtract",
+        Code::NumpadMultiply => "KP_Multiply",
+        Code::NumpadDivide => "KP_Divide",
+        Code::NumpadDecimal => "KP_Decimal",
+        Code::NumpadEnter => "KP_Enter",
+        Code::Semicolon => "semicolon",
+        Code::Equal => "equal",
+        Code::Comma => "comma",
+        Code::Minus => "minus",
+        Code::Period => "period",
+        Code::Slash => "slash",
+        Code::Backslash => "backslash",
+        Code::Backquote => "grave",
+        Code::Quote => "apostrophe",
+        Code::BracketLeft => "bracketleft",
+        Code::BracketRight => "bracketright",
+        other => {
+            // KeyA → a, Digit1 → 1, Numpad1 → KP_1, F5 → F5.
+            let name = other.to_string();
+            return if let Some(letter) = name.strip_prefix("Key") {
+                Some(letter.to_lowercase())
+            } else if let Some(digit) = name.strip_prefix("Digit") {
+                Some(digit.to_string())
+            } else if let Some(digit) = name.strip_prefix("Numpad") {
+                Some(format!("KP_{digit}"))
+            } else if name.starts_with('F') && name[1..].parse::<u8>().is_ok() {
+                Some(name)
+            } else {
+                None
+            };
+        }
+    };
+    Some(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_converts_shortcuts_to_portal_triggers() {
+        let trigger = |mods, key| preferred_trigger(&Shortcut::new(mods, key));
+        assert_eq!(
+            trigger(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::KeyR).as_deref(),
+            Some("CTRL+SHIFT+r")
+        );
+        assert_eq!(
+            trigger(Some(Modifiers::SUPER | Modifiers::ALT), Code::F9).as_deref(),
+            Some("ALT+LOGO+F9")
+        );
+        assert_eq!(trigger(None, Code::Numpad5).as_deref(), Some("KP_5"));
+        assert_eq!(
+            trigger(Some(Modifiers::CONTROL), Code::PageDown).as_deref(),
+            Some("CTRL+Page_Down")
+        );
+        assert_eq!(trigger(None, Code::CapsLock), None);
+    }
+}
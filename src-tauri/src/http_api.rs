@@ -0,0 +1,439 @@
+//! Local HTTP API for the companion browser extension, which sends page text to the app.
+//!
+//! Off by default (`http_api_enabled`). When on, the server listens on 127.0.0.1 at
+//! `http_api_port` (`DEFAULT_PORT` when unset), and every request must carry the API token as
+//! `Authorization: Bearer <token>`. The token is created on first start in `TOKEN_FILE_NAME` in the
+//! app data dir, readable only by the user; `get_http_api_status` shows it for pairing and `regenerate_http_api_token`
+//! replaces it. Endpoints, with JSON bodies:
+//!
+//! - `POST /speak` `{"text":"..."}` reads the text; answered once playback has started.
+//! - `POST /stop` stops playback.
+//! - `GET /status` answers with `playing`, `paused`, `position_ms` and `duration_ms`.
+//! - `POST /summarize` `{"text":"..."}` summarizes the text like "Summarize Selected" (the summary
+//!   opens in the editor); answered right away, with 409 while another summary runs.
+//!
+//! Speak, stop and status go through the action socket's request handling, so the answers have
+//! its shape (`ok`, `error`, ...). Browser preflight (`OPTIONS`) is answered without the token so
+//! extension pages can call the API; the token, not the origin, authorizes requests. Saving the
+//! settings starts, stops or moves the server (see `configure`). At most `MAX_IN_FLIGHT` requests
+//! are handled at once; more are answered with 503.
+
+use std::io::Read;
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use nanoid::nanoid;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tiny_http::{Header, Method, Request, Response, Server};
+use tracing::{debug, info, warn};
+
+use crate::action_socket::{self, SocketRequest, SocketResponse};
+use crate::actions;
+use crate::config::FullConfig;
+use crate::janitor;
+use crate::paths;
+use crate::util;
+
+pub const DEFAULT_PORT: u16 = 47615;
+
+const TOKEN_FILE_NAME: &str = "http-api-token";
+/// Larger bodies are rejected; a long article is well under this.
+const MAX_BODY_BYTES: u64 = 4 * 1024 * 1024;
+/// Requests handled at once, each on its own thread (speak waits for synthesis to start).
+const MAX_IN_FLIGHT: usize = 8;
+
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts a request in `IN_FLIGHT` until its handler thread is done.
+struct InFlight;
+
+impl InFlight {
+    fn acquire() -> Option<Self> {
+        let taken = IN_FLIGHT
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < MAX_IN_FLIGHT).then_some(n + 1)
+            })
+            .is_ok();
+        taken.then_some(Self)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        IN_FLIGHT.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+struct Running {
+    port: u16,
+    server: Arc<Server>,
+}
+
+struct ServerState {
+    app: Option<AppHandle>,
+    /// Port to serve on; `None` when the API is off.
+    wanted_port: Option<u16>,
+    running: Option<Running>,
+    token: Option<String>,
+    last_error: Option<String>,
+}
+
+static STATE: Mutex<ServerState> = Mutex::new(ServerState {
+    app: None,
+    wanted_port: None,
+    running: None,
+    token: None,
+    last_error: None,
+});
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpApiStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    /// `None` until the server has started once.
+    pub token: Option<String>,
+    /// Why the server is not running, e.g. the port is taken.
+    pub last_error: Option<String>,
+}
+
+/// Applies `http_api_enabled` / `http_api_port` (see `commands_config::apply_runtime_settings`).
+pub fn configure(cfg: &FullConfig) {
+    let wanted_port = cfg
+        .http_api_enabled
+        .unwrap_or(false)
+        .then(|| cfg.http_api_port.unwrap_or(DEFAULT_PORT));
+    if let Ok(mut state) = STATE.lock() {
+        state.wanted_port = wanted_port;
+    }
+    apply();
+}
+
+/// Serves the API when enabled; later config changes restart it. Called from lib's setup.
+pub fn start(app: AppHandle) {
+    if let Ok(mut state) = STATE.lock() {
+        state.app = Some(app);
+    }
+    apply();
+}
+
+/// Brings the server in line with the wanted port: stops it, (re)binds it, or leaves it alone.
+fn apply() {
+    let Ok(mut state) = STATE.lock() else {
+        return;
+    };
+    let Some(app) = state.app.clone() else {
+        return;
+    };
+    if state.running.as_ref().map(|running| running.port) == state.wanted_port {
+        return;
+    }
+    if let Some(running) = state.running.take() {
+        running.server.unblock();
+        info!(port = running.port, "HTTP API stopped");
+    }
+    let Some(port) = state.wanted_port else {
+        return;
+    };
+    if state.token.is_none() {
+        match load_or_create_token() {
+            Ok(token) => state.token = Some(token),
+            Err(e) => {
+                warn!(error = %e, "HTTP API token unavailable");
+                state.last_error = Some(e);
+                return;
+            }
+        }
+    }
+    match serve(app, port) {
+        Ok(server) => {
+            info!(port, "HTTP API listening on 127.0.0.1");
+            state.running = Some(Running { port, server });
+            state.last_error = None;
+        }
+        Err(e) => {
+            warn!(error = %e, port, "Failed to start the HTTP API");
+            state.last_error = Some(e);
+        }
+    }
+}
+
+fn token_file() -> Result<PathBuf, String> {
+    Ok(paths::get_app_data_dir()?.join(TOKEN_FILE_NAME))
+}
+
+fn load_or_create_token() -> Result<String, String> {
+    let path = token_file()?;
+    if let Ok(token) = std::fs::read_to_string(&path) {
+        let token = token.trim();
+        if !token.is_empty() && is_private(&path) {
+            return Ok(token.to_string());
+        }
+    }
+    write_new_token(&path)
+}
+
+/// Whether only the user can read `path`; tokens written by earlier versions were not, so they
+/// are replaced.
+fn is_private(path: &std::path::Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::metadata(path).is_ok_and(|meta| meta.permissions().mode() & 0o077 == 0)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        true
+    }
+}
+
+/// Writes a new token to `path`, created owner-only (0600 on Unix) and moved into place.
+fn write_new_token(path: &std::path::Path) -> Result<String, String> {
+    use std::io::Write;
+
+    let token = nanoid!(32);
+    let dir = path
+        .parent()
+        .ok_or_else(|| "API token path has no directory".to_string())?;
+    let mut file = janitor::private_file_in(dir, ".http-api-token-", "")?;
+    file.write_all(token.as_bytes())
+        .map_err(|e| format!("Failed to write API token: {e}"))?;
+    file.persist(path)
+        .map_err(|e| format!("Failed to write API token: {e}"))?;
+    Ok(token)
+}
+
+fn serve(app: AppHandle, port: u16) -> Result<Arc<Server>, String> {
+    let server = Server::http((Ipv4Addr::LOCALHOST, port))
+        .map(Arc::new)
+        .map_err(|e| format!("Failed to bind 127.0.0.1:{port}: {e}"))?;
+    let incoming = server.clone();
+    std::thread::Builder::new()
+        .name("http-api".to_string())
+        .spawn(move || {
+            for request in incoming.incoming_requests() {
+                let Some(in_flight) = InFlight::acquire() else {
+                    respond_error(request, 503, "Too many requests in progress".to_string());
+                    continue;
+                };
+                let app = app.clone();
+                let spawned = std::thread::Builder::new()
+                    .name("http-api-request".to_string())
+                    .spawn(move || {
+                        let _in_flight = in_flight;
+                        handle(&app, request);
+                    });
+                if let Err(e) = spawned {
+                    warn!(error = %e, "Failed to start an HTTP API request thread");
+                }
+            }
+            debug!(port, "HTTP API accept loop ended");
+        })
+        .map_err(|e| format!("Failed to start HTTP API thread: {e}"))?;
+    Ok(server)
+}
+
+// --- Requests ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Route {
+    Preflight,
+    Speak,
+    Stop,
+    Status,
+    Summarize,
+}
+
+/// Maps a request line to a route, or to the HTTP status for an unknown path (404) or method (405).
+fn route(method: &Method, url: &str) -> Result<Route, u16> {
+    if *method == Method::Options {
+        return Ok(Route::Preflight);
+    }
+    let path = url.split('?').next().unwrap_or_default();
+    let (route, expected) = match path.trim_end_matches('/') {
+        "/speak" => (Route::Speak, Method::Post),
+        "/stop" => (Route::Stop, Method::Post),
+        "/status" => (Route::Status, Method::Get),
+        "/summarize" => (Route::Summarize, Method::Post),
+        _ => return Err(404),
+    };
+    if *method == expected {
+        Ok(route)
+    } else {
+        Err(405)
+    }
+}
+
+/// Whether an `Authorization` header value carries `token`. Compares in constant time.
+fn authorized(header: Option<&str>, token: &str) -> bool {
+    let Some(sent) = header.and_then(|value| value.trim().strip_prefix("Bearer ")) else {
+        return false;
+    };
+    util::constant_time_eq(sent.trim().as_bytes(), token.as_bytes())
+}
+
+#[derive(Deserialize)]
+struct TextBody {
+    text: String,
+}
+
+fn read_text(request: &mut Request) -> Result<String, (u16, String)> {
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| (400, format!("Failed to read body: {e}")))?;
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return Err((413, "Body too large".to_string()));
+    }
+    let parsed: TextBody =
+        serde_json::from_str(&body).map_err(|e| (400, format!("Invalid body: {e}")))?;
+    if parsed.text.trim().is_empty() {
+        return Err((400, "No text".to_string()));
+    }
+    Ok(parsed.text)
+}
+
+fn header(name: &str, value: &str) -> Option<Header> {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).ok()
+}
+
+fn respond(request: Request, status: u16, body: Option<&SocketResponse>) {
+    let json = body
+        .and_then(|body| serde_json::to_string(body).ok())
+        .unwrap_or_default();
+    let mut response = Response::from_string(json).with_status_code(status);
+    for (name, value) in [
+        ("Content-Type", "application/json"),
+        ("Access-Control-Allow-Origin", "*"),
+        (
+            "Access-Control-Allow-Headers",
+            "Authorization, Content-Type",
+        ),
+        ("Access-Control-Allow-Methods", "GET, POST, OPTIONS"),
+    ] {
+        if let Some(header) = header(name, value) {
+            response = response.with_header(header);
+        }
+    }
+    if let Err(e) = request.respond(response) {
+        debug!(error = %e, "HTTP API response failed");
+    }
+}
+
+fn respond_error(request: Request, status: u16, error: String) {
+    respond(
+        request,
+        status,
+        Some(&SocketResponse::from_result(Err(error))),
+    );
+}
+
+fn handle(app: &AppHandle, mut request: Request) {
+    let route = match route(request.method(), request.url()) {
+        Ok(route) => route,
+        Err(status) => {
+            let error = format!("No {} {}", request.method(), request.url());
+            return respond_error(request, status, error);
+        }
+    };
+    if route == Route::Preflight {
+        return respond(request, 204, None);
+    }
+    let token = STATE.lock().ok().and_then(|state| state.token.clone());
+    let sent = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .map(|header| header.value.as_str());
+    if !token.is_some_and(|token| authorized(sent, &token)) {
+        warn!(url = %request.url(), "HTTP API request rejected: bad token");
+        return respond_error(request, 401, "Missing or wrong API token".to_string());
+    }
+
+    let socket_request = match route {
+        Route::Speak => match read_text(&mut request) {
+            Ok(text) => SocketRequest::Speak { text },
+            Err((status, error)) => return respond_error(request, status, error),
+        },
+        Route::Stop => SocketRequest::Action {
+            action: "stop".to_string(),
+        },
+        Route::Status => SocketRequest::Status,
+        Route::Summarize => {
+            return match read_text(&mut request) {
+                Ok(text) if actions::summarize_text(app, text, "http") => {
+                    respond(request, 202, Some(&SocketResponse::from_result(Ok(()))))
+                }
+                Ok(_) => respond_error(request, 409, "A summary is already running".to_string()),
+                Err((status, error)) => respond_error(request, status, error),
+            };
+        }
+        Route::Preflight => return respond(request, 204, None),
+    };
+    let response = action_socket::handle_request(app, socket_request, "http");
+    let status = if response.ok { 200 } else { 500 };
+    respond(request, status, Some(&response));
+}
+
+// --- Commands ---
+
+/// Whether the API is on and running, its port, and the token the extension must send.
+#[tauri::command]
+pub fn get_http_api_status() -> Result<HttpApiStatus, String> {
+    let state = STATE
+        .lock()
+        .map_err(|_| "HTTP API state lock poisoned".to_string())?;
+    Ok(HttpApiStatus {
+        enabled: state.wanted_port.is_some(),
+        running: state.running.is_some(),
+        port: state
+            .running
+            .as_ref()
+            .map(|running| running.port)
+            .or(state.wanted_port)
+            .unwrap_or(DEFAULT_PORT),
+        token: state.token.clone(),
+        last_error: state.last_error.clone(),
+    })
+}
+
+/// Replaces the API token; the extension must be paired again. Returns the new token.
+#[tauri::command]
+pub fn regenerate_http_api_token() -> Result<String, String> {
+    let token = write_new_token(&token_file()?)?;
+    let mut state = STATE
+        .lock()
+        .map_err(|_| "HTTP API state lock poisoned".to_string())?;
+    state.token = Some(token.clone());
+    info!("HTTP API token regenerated");
+    Ok(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_checks_method_and_path() {
+        assert_eq!(route(&Method::Post, "/speak"), Ok(Route::Speak));
+        assert_eq!(route(&Method::Get, "/status/?v=1"), Ok(Route::Status));
+        assert_eq!(route(&Method::Options, "/speak"), Ok(Route::Preflight));
+        assert_eq!(route(&Method::Get, "/speak"), Err(405));
+        assert_eq!(route(&Method::Post, "/read"), Err(404));
+    }
+
+    #[test]
+    fn test_authorized_needs_the_exact_bearer_token() {
+        assert!(authorized(Some("Bearer abc123"), "abc123"));
+        assert!(!authorized(Some("Bearer abc124"), "abc123"));
+        assert!(!authorized(Some("Bearer abc"), "abc123"));
+        assert!(!authorized(Some("abc123"), "abc123"));
+        assert!(!authorized(None, "abc123"));
+    }
+}
@@ -0,0 +1,205 @@
+//! Localized strings that get synthesized or announced (not the webview UI, which has its own).
+//!
+//! Keyed by the `ui_language` config value (BCP 47 or POSIX style: "pt-BR", "pt_BR", "pt").
+//! Only the primary language subtag is used; unknown languages fall back to English.
+
+/// Languages with a translation table. The first entry is the fallback.
+const SUPPORTED_LANGUAGES: [&str; 6] = ["en", "es", "fr", "de", "pt", "it"];
+
+/// Strings that may be spoken or shown as the text to read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpokenText {
+    /// Prefix for a failed summary, followed by the error.
+    SummaryFailed,
+    /// Summary could not start (no async runtime).
+    SummaryStartFailed,
+    /// Prefix for a failed explanation ("Explain Selected"), followed by the error.
+    ExplanationFailed,
+    /// Explanation could not start (no async runtime).
+    ExplanationStartFailed,
+    /// Sample sentence used by voice preview.
+    VoicePreviewSample,
+    /// Passage played at several speeds by the reading-speed calibration flow.
+    CalibrationPassage,
+    /// Spoken when the reading timer ends (see `reading_timer`).
+    BreakAnnouncement,
+}
+
+/// Normalizes a language tag ("pt-BR", "pt_BR", "PT") to a supported primary subtag.
+pub fn normalize_language(tag: Option<&str>) -> &'static str {
+    let primary = tag
+        .unwrap_or_default()
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    SUPPORTED_LANGUAGES
+        .iter()
+        .find(|lang| **lang == primary)
+        .copied()
+        .unwrap_or(SUPPORTED_LANGUAGES[0])
+}
+
+/// The `ui_language` from the saved config (normalized).
+pub fn configured_language() -> &'static str {
+    let cfg = crate::config::load_full_config().unwrap_or_default();
+    normalize_language(cfg.ui_language.as_deref())
+}
+
+/// Returns the string for `key` in `language` (any tag accepted by `normalize_language`).
+pub fn text(key: SpokenText, language: &str) -> &'static str {
+    use SpokenText::*;
+    match (key, normalize_language(Some(language))) {
+        (SummaryFailed, "es") => "El resumen falló",
+        (SummaryFailed, "fr") => "Le résumé a échoué",
+        (SummaryFailed, "de") => "Zusammenfassung fehlgeschlagen",
+        (SummaryFailed, "pt") => "O resumo falhou",
+        (SummaryFailed, "it") => "Riassunto non riuscito",
+        (SummaryFailed, _) => "Summary failed",
+
+        (SummaryStartFailed, "es") => {
+            "El resumen falló: no se pudo iniciar la tarea en segundo plano."
+        }
+        (SummaryStartFailed, "fr") => {
+            "Le résumé a échoué : impossible de démarrer la tâche en arrière-plan."
+        }
+        (SummaryStartFailed, "de") => {
+            "Zusammenfassung fehlgeschlagen: Hintergrundaufgabe konnte nicht gestartet werden."
+        }
+        (SummaryStartFailed, "pt") => {
+            "O resumo falhou: não foi possível iniciar a tarefa em segundo plano."
+        }
+        (SummaryStartFailed, "it") => {
+            "Riassunto non riuscito: impossibile avviare l'attività in background."
+        }
+        (SummaryStartFailed, _) => "Summary failed: could not start background task.",
+
+        (ExplanationFailed, "es") => "La explicación falló",
+        (ExplanationFailed, "fr") => "L'explication a échoué",
+        (ExplanationFailed, "de") => "Erklärung fehlgeschlagen",
+        (ExplanationFailed, "pt") => "A explicação falhou",
+        (ExplanationFailed, "it") => "Spiegazione non riuscita",
+        (ExplanationFailed, _) => "Explanation failed",
+
+        (ExplanationStartFailed, "es") => {
+            "La explicación falló: no se pudo iniciar la tarea en segundo plano."
+        }
+        (ExplanationStartFailed, "fr") => {
+            "L'explication a échoué : impossible de démarrer la tâche en arrière-plan."
+        }
+        (ExplanationStartFailed, "de") => {
+            "Erklärung fehlgeschlagen: Hintergrundaufgabe konnte nicht gestartet werden."
+        }
+        (ExplanationStartFailed, "pt") => {
+            "A explicação falhou: não foi possível iniciar a tarefa em segundo plano."
+        }
+        (ExplanationStartFailed, "it") => {
+            "Spiegazione non riuscita: impossibile avviare l'attività in background."
+        }
+        (ExplanationStartFailed, _) => "Explanation failed: could not start background task.",
+
+        (VoicePreviewSample, "es") => {
+            "Hola, esta es una muestra de mi voz. Así sonará tu texto cuando lo lea en voz alta."
+        }
+        (VoicePreviewSample, "fr") => {
+            "Bonjour, ceci est un aperçu de ma voix. Voici comment votre texte sonnera à la lecture."
+        }
+        (VoicePreviewSample, "de") => {
+            "Hallo, dies ist eine Hörprobe meiner Stimme. So klingt Ihr Text, wenn ich ihn vorlese."
+        }
+        (VoicePreviewSample, "pt") => {
+            "Olá, esta é uma amostra da minha voz. É assim que o seu texto vai soar quando eu o ler."
+        }
+        (VoicePreviewSample, "it") => {
+            "Ciao, questo è un esempio della mia voce. Ecco come suonerà il tuo testo letto ad alta voce."
+        }
+        (VoicePreviewSample, _) => {
+            "Hello, this is a preview of my voice. This is how your text will sound when I read it aloud."
+        }
+
+        (CalibrationPassage, "es") => {
+            "Vamos a encontrar tu velocidad de lectura ideal. Escucha este párrafo y elige la \
+             velocidad a la que puedas seguir cada palabra con comodidad, sin perder la atención."
+        }
+        (CalibrationPassage, "fr") => {
+            "Trouvons votre vitesse de lecture idéale. Écoutez ce passage et choisissez la vitesse \
+             à laquelle vous suivez chaque mot confortablement, sans perdre le fil."
+        }
+        (CalibrationPassage, "de") => {
+            "Lassen Sie uns Ihre ideale Lesegeschwindigkeit finden. Hören Sie diesen Abschnitt und \
+             wählen Sie das Tempo, bei dem Sie jedem Wort bequem folgen können."
+        }
+        (CalibrationPassage, "pt") => {
+            "Vamos encontrar a sua velocidade de leitura ideal. Ouça este trecho e escolha a \
+             velocidade em que consegue acompanhar cada palavra com conforto, sem perder o foco."
+        }
+        (CalibrationPassage, "it") => {
+            "Troviamo la tua velocità di lettura ideale. Ascolta questo brano e scegli la velocità \
+             con cui riesci a seguire ogni parola comodamente, senza perdere la concentrazione."
+        }
+        (CalibrationPassage, _) => {
+            "Let's find your ideal reading speed. Listen to this passage and pick the speed at \
+             which you can follow every word comfortably without losing focus."
+        }
+
+        (BreakAnnouncement, "es") => "Es hora de un breve descanso.",
+        (BreakAnnouncement, "fr") => "C'est l'heure d'une petite pause.",
+        (BreakAnnouncement, "de") => "Zeit für eine kurze Pause.",
+        (BreakAnnouncement, "pt") => "Hora de uma pequena pausa.",
+        (BreakAnnouncement, "it") => "È ora di una breve pausa.",
+        (BreakAnnouncement, _) => "Time for a short break.",
+    }
+}
+
+/// Spoken before a read when `announce_reads` is on: what is read ("selection", "screenshot",
+/// "document", anything else is plain text) and about how many minutes it takes.
+pub fn read_announcement(source: &str, minutes: u32, language: &str) -> String {
+    let language = normalize_language(Some(language));
+    let what = match (source, language) {
+        ("selection", "es") => "Leyendo la selección",
+        ("screenshot", "es") => "Leyendo la captura de pantalla",
+        ("document", "es") => "Leyendo el documento",
+        (_, "es") => "Leyendo el texto",
+        ("selection", "fr") => "Lecture de la sélection",
+        ("screenshot", "fr") => "Lecture de la capture d'écran",
+        ("document", "fr") => "Lecture du document",
+        (_, "fr") => "Lecture du texte",
+        ("selection", "de") => "Vorlesen der Auswahl",
+        ("screenshot", "de") => "Vorlesen des Bildschirmfotos",
+        ("document", "de") => "Vorlesen des Dokuments",
+        (_, "de") => "Vorlesen des Textes",
+        ("selection", "pt") => "Lendo a seleção",
+        ("screenshot", "pt") => "Lendo a captura de tela",
+        ("document", "pt") => "Lendo o documento",
+        (_, "pt") => "Lendo o texto",
+        ("selection", "it") => "Lettura della selezione",
+        ("screenshot", "it") => "Lettura dello screenshot",
+        ("document", "it") => "Lettura del documento",
+        (_, "it") => "Lettura del testo",
+        ("selection", _) => "Reading selection",
+        ("screenshot", _) => "Reading screenshot",
+        ("document", _) => "Reading document",
+        _ => "Reading text",
+    };
+    let duration = match (minutes, language) {
+        (0, "es") => "menos de un minuto".to_string(),
+        (1, "es") => "1 minuto".to_string(),
+        (n, "es") => format!("{n} minutos"),
+        (0, "fr") => "moins d'une minute".to_string(),
+        (n, "fr") => format!("{n} minute{}", if n == 1 { "" } else { "s" }),
+        (0, "de") => "unter einer Minute".to_string(),
+        (1, "de") => "1 Minute".to_string(),
+        (n, "de") => format!("{n} Minuten"),
+        (0, "pt") => "menos de um minuto".to_string(),
+        (1, "pt") => "1 minuto".to_string(),
+        (n, "pt") => format!("{n} minutos"),
+        (0, "it") => "meno di un minuto".to_string(),
+        (1, "it") => "1 minuto".to_string(),
+        (n, "it") => format!("{n} minuti"),
+        (0, _) => "under a minute".to_string(),
+        (1, _) => "1 minute".to_string(),
+        (n, _) => format!("{n} minutes"),
+    };
+    format!("{what}, {duration}.")
+}
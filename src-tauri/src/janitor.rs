@@ -0,0 +1,271 @@
+//! Cleanup after crashes: orphaned child processes and stale temporary files.
+//!
+//! Running Piper processes are listed in a pidfile in the cache dir while they run. If the app
+//! dies mid-synthesis the file survives, and the next startup kills the listed processes that are
+//! still alive and still Piper (the owner line is checked first, so a running instance is left
+//! alone), before the TTS worker starts and replaces the file. Startup also removes files older
+//! than `stale_file_max_age_hours` from the app temp dir, and `insight-reader*` files left in the
+//! system temp dir by earlier versions.
+//!
+//! Temporary files are created here too (`temp_file`, `private_file_in`): random names, created
+//! exclusively with owner-only permissions, in a directory only the user can list, and removed
+//! when dropped so error paths cannot leak them.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use tempfile::NamedTempFile;
+use tracing::{debug, info};
+
+use crate::config::FullConfig;
+use crate::paths;
+
+pub const DEFAULT_STALE_FILE_MAX_AGE_HOURS: u32 = 24;
+
+const PIDFILE_NAME: &str = "child-processes.pid";
+/// Prefix of temp files created in the system temp dir by earlier versions.
+const LEGACY_TEMP_FILE_PREFIX: &str = "insight-reader";
+
+/// Children of this process that are currently running: (pid, program name).
+static TRACKED: Mutex<Vec<(u32, &'static str)>> = Mutex::new(Vec::new());
+
+/// Keeps a child listed in the pidfile until dropped (after the child has exited).
+pub struct TrackedChild {
+    pid: u32,
+}
+
+impl Drop for TrackedChild {
+    fn drop(&mut self) {
+        if let Ok(mut tracked) = TRACKED.lock() {
+            tracked.retain(|(pid, _)| *pid != self.pid);
+            write_pidfile(&tracked);
+        }
+    }
+}
+
+/// Lists a spawned child (e.g. Piper, `name` = "piper") in the pidfile.
+pub fn track_child(pid: u32, name: &'static str) -> TrackedChild {
+    if let Ok(mut tracked) = TRACKED.lock() {
+        tracked.push((pid, name));
+        write_pidfile(&tracked);
+    }
+    TrackedChild { pid }
+}
+
+/// Creates `dir` if needed and restricts it to the user (0700 on Unix).
+pub fn create_private_dir(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create directory {}: {e}", dir.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Failed to restrict directory {}: {e}", dir.display()))?;
+    }
+    Ok(())
+}
+
+/// Creates a new empty file named `<prefix><random><suffix>` in `dir` (0600 on Unix). The file is
+/// deleted when the handle is dropped unless it is kept with `NamedTempFile::keep`.
+pub fn private_file_in(dir: &Path, prefix: &str, suffix: &str) -> Result<NamedTempFile, String> {
+    create_private_dir(dir)?;
+    tempfile::Builder::new()
+        .prefix(prefix)
+        .suffix(suffix)
+        .tempfile_in(dir)
+        .map_err(|e| format!("Failed to create file in {}: {e}", dir.display()))
+}
+
+/// Creates a temporary file in the app temp dir (`paths::get_temp_dir`), deleted on drop.
+pub fn temp_file(prefix: &str, suffix: &str) -> Result<NamedTempFile, String> {
+    private_file_in(&paths::get_temp_dir()?, prefix, suffix)
+}
+
+fn pidfile_path() -> Result<PathBuf, String> {
+    Ok(paths::get_cache_dir()?.join(PIDFILE_NAME))
+}
+
+fn write_pidfile(tracked: &[(u32, &'static str)]) {
+    let Ok(path) = pidfile_path() else {
+        return;
+    };
+    if tracked.is_empty() {
+        let _ = fs::remove_file(&path);
+        return;
+    }
+    let mut data = format!("owner {}\n", std::process::id());
+    for (pid, name) in tracked {
+        data.push_str(&format!("{name} {pid}\n"));
+    }
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Err(e) = fs::write(&path, data) {
+        debug!(error = %e, "Failed to write child pidfile");
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Pidfile {
+    owner: Option<u32>,
+    children: Vec<(String, u32)>,
+}
+
+fn parse_pidfile(data: &str) -> Pidfile {
+    let mut pidfile = Pidfile {
+        owner: None,
+        children: Vec::new(),
+    };
+    for line in data.lines() {
+        let Some((name, pid)) = line.trim().split_once(' ') else {
+            continue;
+        };
+        let Ok(pid) = pid.trim().parse::<u32>() else {
+            continue;
+        };
+        if name == "owner" {
+            pidfile.owner = Some(pid);
+        } else if !name.is_empty() {
+            pidfile.children.push((name.to_string(), pid));
+        }
+    }
+    pidfile
+}
+
+/// Command line (Unix) or image name (Windows) of a running process.
+fn process_command(pid: u32) -> Option<String> {
+    #[cfg(not(target_os = "windows"))]
+    let output = Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "command="])
+        .output()
+        .ok()?;
+    #[cfg(target_os = "windows")]
+    let output = {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {pid}"), "/FO", "CSV", "/NH"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()?
+    };
+    let command = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    // tasklist prints an "INFO: No tasks ..." line instead of failing.
+    (output.status.success() && !command.is_empty() && !command.starts_with("INFO:"))
+        .then_some(command)
+}
+
+fn kill_process(pid: u32) -> bool {
+    #[cfg(not(target_os = "windows"))]
+    let status = Command::new("kill")
+        .args(["-TERM", &pid.to_string()])
+        .status();
+    #[cfg(target_os = "windows")]
+    let status = {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .status()
+    };
+    status.is_ok_and(|s| s.success())
+}
+
+/// True when `pid` is a running Insight Reader, this one included (pids are reused, so the name
+/// is checked).
+fn is_running_instance(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    let own_name = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.file_stem().map(|s| s.to_string_lossy().to_lowercase()));
+    let Some(own_name) = own_name else {
+        return false;
+    };
+    process_command(pid).is_some_and(|command| command.to_lowercase().contains(&own_name))
+}
+
+/// Kills children listed by a previous run that crashed. Returns how many were killed. Call before
+/// the TTS worker starts: its first Piper process replaces the pidfile.
+pub fn kill_orphans() -> usize {
+    let Ok(path) = pidfile_path() else {
+        return 0;
+    };
+    let Ok(data) = fs::read_to_string(&path) else {
+        return 0;
+    };
+    let pidfile = parse_pidfile(&data);
+    if pidfile.owner.is_some_and(is_running_instance) {
+        debug!("Child pidfile belongs to a running instance, leaving it");
+        return 0;
+    }
+    let mut killed = 0;
+    for (name, pid) in &pidfile.children {
+        let still_ours = process_command(*pid)
+            .is_some_and(|command| command.to_lowercase().contains(name.as_str()));
+        if still_ours && kill_process(*pid) {
+            info!(pid, name = %name, "Killed orphaned child process");
+            killed += 1;
+        }
+    }
+    let _ = fs::remove_file(&path);
+    killed
+}
+
+/// Removes files older than `max_age` in `dir` whose names start with `prefix` (all files when
+/// `prefix` is empty). Returns the number removed.
+fn remove_stale_files(dir: &Path, prefix: &str, max_age: Duration) -> usize {
+    let Some(cutoff) = SystemTime::now().checked_sub(max_age) else {
+        return 0;
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(prefix))
+        .filter(|entry| {
+            entry
+                .metadata()
+                .is_ok_and(|m| m.is_file() && m.modified().is_ok_and(|t| t < cutoff))
+        })
+        .filter(|entry| fs::remove_file(entry.path()).is_ok())
+        .count()
+}
+
+/// Startup janitor: removes stale temp files. Runs in the background with the other startup
+/// maintenance (see `storage::prune_caches`); orphaned children are killed earlier, by
+/// `kill_orphans`.
+pub fn run_startup_cleanup(config: &FullConfig) {
+    let hours = config
+        .stale_file_max_age_hours
+        .unwrap_or(DEFAULT_STALE_FILE_MAX_AGE_HOURS);
+    let max_age = Duration::from_secs(u64::from(hours) * 60 * 60);
+    let mut removed = remove_stale_files(&std::env::temp_dir(), LEGACY_TEMP_FILE_PREFIX, max_age);
+    if let Ok(dir) = paths::get_temp_dir() {
+        removed += remove_stale_files(&dir, "", max_age);
+    }
+    if removed > 0 {
+        info!(removed, "Startup cleanup: removed stale files");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_pidfile_and_skips_bad_lines() {
+        let pidfile = parse_pidfile("owner 100\npiper 200\ngarbage\npiper x\n piper 300 \n");
+        assert_eq!(pidfile.owner, Some(100));
+        assert_eq!(
+            pidfile.children,
+            [("piper".to_string(), 200), ("piper".to_string(), 300)]
+        );
+    }
+}
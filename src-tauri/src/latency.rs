@@ -0,0 +1,231 @@
+//! Per-read latency: how long each stage of a read took, from the trigger to the first audio.
+//!
+//! Each read gets an id. Reads triggered by the user (hotkey, tray, socket) start with `begin`
+//! before the text is captured and call `captured` once it is; reads sent straight to the TTS
+//! worker (the app, documents, history) start when the worker picks them up. The worker records
+//! preprocessing, the synthesis of the first chunk (near zero when its audio is reused), and
+//! the time to first audio, which completes the read: it is logged and becomes what
+//! `get_last_read_timings` returns.
+//! A read that never plays is replaced by the next one.
+//!
+//! The stages run on different threads; each runs inside `span(read_id)`, so their log lines
+//! share the `read_id` field.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tracing::info;
+
+use crate::util::unix_millis_now;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static TRACKER: Mutex<Tracker> = Mutex::new(Tracker {
+    current: None,
+    last: None,
+});
+
+/// Stage durations of a read, in milliseconds. Stages the read did not go through are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReadTimings {
+    pub read_id: u64,
+    /// What triggered the read ("selection", "screenshot"); `None` for reads sent to the worker.
+    pub source: Option<String>,
+    /// Unix timestamp in milliseconds.
+    pub started_at_ms: u64,
+    /// Getting the text: selection or clipboard, or screenshot and OCR.
+    pub capture_ms: Option<u64>,
+    /// Text preparation (pipeline and segmentation) in the worker.
+    pub preprocess_ms: Option<u64>,
+    /// Synthesis of the first chunk.
+    pub synthesis_ms: Option<u64>,
+    /// From the start of the read until its first audio was queued for playback.
+    pub time_to_first_audio_ms: Option<u64>,
+}
+
+struct InFlight {
+    timings: ReadTimings,
+    started: Instant,
+    /// The worker has picked the read up.
+    spoken: bool,
+    synthesis_started: Option<Instant>,
+}
+
+struct Tracker {
+    current: Option<InFlight>,
+    last: Option<ReadTimings>,
+}
+
+impl Tracker {
+    fn begin(&mut self, source: Option<&str>, now: Instant) -> u64 {
+        let read_id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let started_at_ms = unix_millis_now();
+        self.current = Some(InFlight {
+            timings: ReadTimings {
+                read_id,
+                source: source.map(str::to_string),
+                started_at_ms,
+                ..ReadTimings::default()
+            },
+            started: now,
+            spoken: false,
+            synthesis_started: None,
+        });
+        read_id
+    }
+
+    fn current(&mut self, read_id: u64) -> Option<&mut InFlight> {
+        self.current
+            .as_mut()
+            .filter(|read| read.timings.read_id == read_id)
+    }
+
+    fn captured(&mut self, now: Instant) {
+        if let Some(read) = self.current.as_mut().filter(|read| !read.spoken) {
+            read.timings.capture_ms = Some(millis(now - read.started));
+        }
+    }
+
+    fn speak_received(&mut self, now: Instant) -> u64 {
+        match self.current.as_mut().filter(|read| !read.spoken) {
+            Some(read) => {
+                read.spoken = true;
+                read.timings.read_id
+            }
+            None => {
+                let read_id = self.begin(None, now);
+                if let Some(read) = self.current.as_mut() {
+                    read.spoken = true;
+                }
+                read_id
+            }
+        }
+    }
+
+    fn preprocessed(&mut self, read_id: u64, took: Duration) {
+        if let Some(read) = self.current(read_id) {
+            read.timings.preprocess_ms = Some(millis(took));
+        }
+    }
+
+    fn synthesis_started(&mut self, read_id: u64, now: Instant) {
+        if let Some(read) = self.current(read_id) {
+            read.synthesis_started = Some(now);
+        }
+    }
+
+    fn first_audio(&mut self, read_id: u64, now: Instant) -> Option<ReadTimings> {
+        self.current(read_id)?;
+        let read = self.current.take()?;
+        let mut timings = read.timings;
+        timings.synthesis_ms = read.synthesis_started.map(|start| millis(now - start));
+        timings.time_to_first_audio_ms = Some(millis(now - read.started));
+        self.last = Some(timings.clone());
+        Some(timings)
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+fn with_tracker<T>(f: impl FnOnce(&mut Tracker) -> T) -> Option<T> {
+    TRACKER.lock().ok().map(|mut tracker| f(&mut tracker))
+}
+
+/// The span each stage of a read runs in.
+pub fn span(read_id: u64) -> tracing::Span {
+    tracing::info_span!("read", read_id)
+}
+
+/// Starts a read triggered by the user, before its text is captured. Returns its id.
+pub fn begin(source: &str) -> u64 {
+    with_tracker(|tracker| tracker.begin(Some(source), Instant::now())).unwrap_or(0)
+}
+
+/// The text of the read started with `begin` has been captured.
+pub fn captured() {
+    with_tracker(|tracker| tracker.captured(Instant::now()));
+}
+
+/// The worker picked up a Speak: the read started with `begin`, or a new one. Returns its id.
+pub fn speak_received() -> u64 {
+    with_tracker(|tracker| tracker.speak_received(Instant::now())).unwrap_or(0)
+}
+
+pub fn preprocessed(read_id: u64, took: Duration) {
+    with_tracker(|tracker| tracker.preprocessed(read_id, took));
+}
+
+pub fn synthesis_started(read_id: u64) {
+    with_tracker(|tracker| tracker.synthesis_started(read_id, Instant::now()));
+}
+
+/// The first audio of the read was queued for playback; completes the read.
+pub fn first_audio(read_id: u64) {
+    let Some(Some(timings)) = with_tracker(|tracker| tracker.first_audio(read_id, Instant::now()))
+    else {
+        return;
+    };
+    info!(
+        read_id,
+        capture_ms = ?timings.capture_ms,
+        preprocess_ms = ?timings.preprocess_ms,
+        synthesis_ms = ?timings.synthesis_ms,
+        time_to_first_audio_ms = ?timings.time_to_first_audio_ms,
+        "Read timings"
+    );
+}
+
+/// Stage timings of the last read that reached playback.
+#[tauri::command]
+pub fn get_last_read_timings() -> Option<ReadTimings> {
+    with_tracker(|tracker| tracker.last.clone()).flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_stages_of_the_current_read_only() {
+        let mut tracker = Tracker {
+            current: None,
+            last: None,
+        };
+        let t0 = Instant::now();
+        let ms = Duration::from_millis;
+
+        let stale = tracker.begin(Some("selection"), t0);
+        let read_id = tracker.begin(Some("selection"), t0);
+        tracker.captured(t0 + ms(40));
+        assert_eq!(tracker.speak_received(t0 + ms(45)), read_id);
+        tracker.preprocessed(read_id, ms(5));
+        tracker.synthesis_started(read_id, t0 + ms(50));
+        assert_eq!(tracker.first_audio(stale, t0 + ms(60)), None);
+        let timings = tracker.first_audio(read_id, t0 + ms(250));
+        assert_eq!(
+            timings.map(|t| (
+                t.capture_ms,
+                t.preprocess_ms,
+                t.synthesis_ms,
+                t.time_to_first_audio_ms
+            )),
+            Some((Some(40), Some(5), Some(200), Some(250)))
+        );
+        assert_eq!(
+            tracker.last.as_ref().map(|t| t.source.as_deref()),
+            Some(Some("selection"))
+        );
+
+        // A Speak nobody announced with `begin` starts its own read.
+        let sent = tracker.speak_received(t0 + ms(300));
+        assert_ne!(sent, read_id);
+        tracker.captured(t0 + ms(310));
+        assert_eq!(
+            tracker.current(sent).map(|r| r.timings.capture_ms),
+            Some(None)
+        );
+    }
+}
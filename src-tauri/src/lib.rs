@@ -5,44 +5,128 @@
 //! this file is bootstrap only.
 //!
 //! **Modules:** `action_socket` — single-instance action bridge; `actions` — read/pause/stop;
-//! `backend` — ReadingService HTTP API; `commands_*` — Tauri commands by domain; `config` / `paths` —
-//! config and paths; `hotkeys` — global shortcuts; `system` / `text_capture` — clipboard/selection;
+//! `app_info` — version, build and environment details; `backend` — ReadingService HTTP API;
+//! `backend_cache` — cache of backend responses with TTL, listed as prompt history; `calibration` —
+//! per-voice reading-speed calibration; `cli` — command line: actions, headless speak/export and
+//! voice listing; `clipboard_watch` — opt-in reading of newly copied text (tray toggle, min length,
+//! ignored apps); `commands_*` — Tauri commands by domain; `config` / `paths` — config and paths;
+//! `config_watch` — hot-reload of config.json edited outside the app; `dbus_service` — D-Bus
+//! interface for actions and playback state on Linux; `diagnostics` — crash reports from a panic
+//! hook and the diagnostics report for bug reports; `dispatch` — bounded concurrency for captures
+//! and actions; `documents` — EPUB/PDF reading mode with chapter navigation; `ducking` — lowers
+//! other apps' audio while a read plays; `editor_pages` — paging of very large editor texts;
+//! `error` — structured command errors (code, message, hint) for the TTS, backend and voice
+//! commands; `features` — feature flags for experimental subsystems; `history` — reading history
+//! with resume; `hotkeys` — global shortcuts; `http_api` — local HTTP API for the browser
+//! extension; `i18n` — spoken strings; `janitor` — private temp files, and cleanup of orphaned
+//! processes and stale temp files after crashes; `latency` — per-read stage timings from capture to
+//! first audio; `llm` — LLM providers for the backend tasks: the ReadingService backend, OpenAI,
+//! Anthropic or Ollama; `logging` — log level, daily log files and recent logs for bug reports;
+//! `media_session` — media keys and the system media session on Windows and macOS; `mic_pause` —
+//! auto-pause playback while the microphone is in use; `ocr` — OCR preprocessing and text
+//! recognition; `offline` — offline mode that turns off all network calls; `profiles` — named user
+//! profiles; `reading_stats` — words read aloud, listening time and sessions per day and provider;
+//! `reading_timer` — reading timer that pauses playback for breaks; `secrets` — cloud credentials
+//! in the OS keychain; `storage` — disk usage and cache pruning; `store` — JSON files kept in
+//! memory and written back atomically (stats, usage, history); `suspend` — pauses playback before
+//! system sleep and optionally resumes it on wake; `system` / `text_capture` — clipboard/selection;
+//! `tasks` / `shutdown` — background tasks and orchestrated quit; `text` — preprocessing pipeline,
+//! pronunciation lexicon, SSML, profanity filter, sentence segmentation, readability metrics, and
+//! the prepared-text cache; `translate` — translate-and-read through the backend `TRANSLATE` task;
 //! `tts` / `voices` — TTS and voice listing; `tray` / `tray_actions` — tray menu and handlers;
-//! `windows` — webview URL and editor window.
+//! `usage` — characters synthesized per provider and month, Polly cost estimate and budget; `util`
+//! — small shared helpers (Unix timestamps and dates, atomic file writes); `web_extract` — fetches
+//! a linked page and extracts its article for reading (`read_url`); `windows` — webview URL and
+//! editor window.
+//!
+//! The action socket, tray, global hotkeys and window management are desktop-only
+//! (`cfg(desktop)`); on Android and iOS the app runs in a single webview and speaks with the
+//! platform speech engine (`tts::mobile_speech_plugin`).
 
 #[cfg(target_os = "macos")]
 #[macro_use]
 extern crate objc;
 
+#[cfg(desktop)]
 mod action_socket;
 mod actions;
+mod app_info;
 mod backend;
+mod backend_cache;
+mod calibration;
+#[cfg(desktop)]
+mod cli;
+#[cfg(desktop)]
+mod clipboard_watch;
 mod commands_config;
 mod commands_tts;
 mod commands_voices;
+#[cfg(desktop)]
 mod commands_windows;
 mod config;
+#[cfg(desktop)]
+mod config_watch;
+#[cfg(target_os = "linux")]
+mod dbus_service;
+mod diagnostics;
+mod dispatch;
+mod documents;
+mod ducking;
+mod editor_pages;
+mod error;
+mod features;
+mod history;
+#[cfg(desktop)]
 mod hotkeys;
+#[cfg(desktop)]
+mod http_api;
+mod i18n;
+mod janitor;
+mod latency;
+mod llm;
+mod logging;
 mod machine_id;
 #[cfg(target_os = "macos")]
 mod macos_dock_icon;
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+mod media_session;
+mod mic_pause;
+mod ocr;
+mod offline;
 mod paths;
+mod profiles;
+mod reading_stats;
+mod reading_timer;
+#[cfg(desktop)]
+mod secrets;
+mod shutdown;
+mod storage;
+mod store;
+mod suspend;
 mod system;
+mod tasks;
+mod text;
 mod text_capture;
+mod translate;
+#[cfg(desktop)]
 mod tray;
+#[cfg(desktop)]
 mod tray_actions;
 mod tts;
+mod usage;
+mod util;
 mod voices;
+mod web_extract;
 mod windows;
 
+#[cfg(desktop)]
 pub use action_socket::send_action_to_running_instance;
+#[cfg(desktop)]
+pub use cli::run_cli;
 
 use std::sync::{Arc, Mutex};
-#[cfg(target_os = "macos")]
-use tauri::RunEvent;
-use tauri::{Manager, WindowEvent};
+use tauri::{Manager, RunEvent};
 use tracing::error;
-use tracing_subscriber::EnvFilter;
 
 // --- State types (shared with windows and tray) ---
 
@@ -51,6 +135,9 @@ use tracing_subscriber::EnvFilter;
 pub struct EditorInitialStateInner {
     pub text: Option<String>,
     pub trigger_read: bool,
+    /// Number of pages when the text is too large to pass whole and `text` is its first page
+    /// (see `editor_pages`); 0 otherwise.
+    pub page_count: usize,
 }
 
 /// Managed state for initial text and trigger-read flag passed to the editor window.
@@ -59,106 +146,194 @@ pub type EditorInitialState = Arc<Mutex<EditorInitialStateInner>>;
 /// Legacy type alias for code that still refers to EditorInitialText (e.g. try_state).
 pub type EditorInitialText = EditorInitialState;
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    let mut env_filter =
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-
-    for directive in [
-        "aws_config::profile::credentials=warn",
-        "aws_credential_types=warn",
-    ] {
-        if let Ok(parsed) = directive.parse() {
-            env_filter = env_filter.add_directive(parsed);
-        }
+/// Startup shared by the app and the headless CLI: data paths, profile, runtime settings and the
+/// resource dir. Returns the loaded config.
+fn prepare_runtime(package_info: &tauri::PackageInfo) -> config::FullConfig {
+    paths::migrate_legacy_paths();
+    profiles::init();
+    if let Some(root) = paths::portable_root() {
+        tracing::info!(root = %root.display(), "Portable mode: storing all data beside the executable");
+    }
+
+    config::migrate_config_file();
+    let config = config::load_full_config().unwrap_or_default();
+    commands_config::apply_runtime_settings(&config);
+    // The TTS worker may need the bundled onboarding voice before the app is built.
+    match tauri::utils::platform::resource_dir(package_info, &tauri::Env::default()) {
+        Ok(dir) => paths::set_resource_dir(dir),
+        Err(e) => tracing::warn!(error = %e, "Could not resolve the resource directory"),
     }
+    config
+}
+
+/// The app's context (config and embedded assets). `run` and the CLI share this one expansion so
+/// the assets are embedded in the binary once.
+fn app_context() -> tauri::Context<tauri::Wry> {
+    tauri::generate_context!()
+}
 
-    tracing_subscriber::fmt().with_env_filter(env_filter).init();
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    logging::init("info", false);
+    diagnostics::init();
 
+    let context = app_context();
+    let initial_config = prepare_runtime(context.package_info());
     let editor_initial: EditorInitialState =
         Arc::new(Mutex::new(EditorInitialStateInner::default()));
-    let initial_config = config::load_full_config().unwrap_or_default();
     let config_state: commands_config::ConfigState = Arc::new(Mutex::new(initial_config));
+    // Reads the previous run's pidfile before the worker's first Piper process replaces it.
+    janitor::kill_orphans();
     let tts_state = tts::create_tts_state();
-    let hotkey_state: hotkeys::GlobalHotkeyState =
-        Arc::new(Mutex::new(hotkeys::HotkeyRuntime::default()));
-
-    #[cfg(target_os = "linux")]
-    let window_state_plugin = tauri_plugin_window_state::Builder::default()
-        .with_state_flags(
-            tauri_plugin_window_state::StateFlags::all()
-                .difference(tauri_plugin_window_state::StateFlags::SIZE),
-        )
-        .build();
-    #[cfg(not(target_os = "linux"))]
-    let window_state_plugin = tauri_plugin_window_state::Builder::default().build();
 
-    let hotkey_state_for_handler = hotkey_state.clone();
+    let builder = tauri::Builder::default().plugin(tauri_plugin_opener::init());
+    #[cfg(desktop)]
+    let builder = with_desktop_plugins(builder);
+    #[cfg(mobile)]
+    let builder = builder.plugin(tts::mobile_speech_plugin());
 
-    let app = match tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .plugin(window_state_plugin)
-        .plugin(
-            tauri_plugin_global_shortcut::Builder::new()
-                .with_handler(move |app, shortcut, event| {
-                    hotkeys::handle_global_shortcut_event(
-                        app,
-                        shortcut,
-                        event.state(),
-                        &hotkey_state_for_handler,
-                        |app, action| actions::execute_action(app, action, "global-hotkey"),
-                    );
-                })
-                .build(),
-        )
+    let app = match builder
         .manage(editor_initial)
         .manage(config_state)
         .manage(tts_state)
-        .manage(hotkey_state.clone())
+        .manage(tasks::TaskManager::default())
+        .manage(documents::DocumentState::default())
+        .manage(editor_pages::EditorPagesState::default())
         .invoke_handler(tauri::generate_handler![
             backend::backend_prompt,
+            backend::backend_prompt_stream,
+            backend::backend_cancel,
+            backend_cache::backend_history_list,
+            backend_cache::clear_backend_cache,
+            llm::ollama::ollama_status,
             backend::check_polly_credentials,
+            backend::backend_health_check,
+            backend::get_backend_health,
             text_capture::get_selected_text,
             text_capture::get_clipboard_text,
             text_capture::get_text_or_clipboard,
             windows::open_editor_window,
             windows::get_editor_initial_text,
             commands_tts::tts_speak,
+            commands_tts::tts_enqueue,
+            commands_tts::tts_queue_list,
+            commands_tts::tts_queue_skip,
+            commands_tts::tts_queue_clear,
+            commands_tts::tts_proofread,
+            commands_tts::tts_export_to_file,
+            commands_tts::tts_preview_voice,
             commands_tts::tts_stop,
             commands_tts::tts_toggle_pause,
             commands_tts::tts_get_status,
+            commands_tts::tts_set_sleep_timer,
+            commands_tts::tts_cancel_sleep_timer,
+            commands_tts::tts_get_sleep_timer,
             commands_tts::tts_seek,
+            commands_tts::tts_seek_to,
+            commands_tts::tts_seek_percent,
+            commands_tts::tts_next_segment,
+            commands_tts::tts_prev_segment,
             commands_tts::tts_get_position,
+            commands_tts::tts_get_timeline,
+            commands_tts::tts_get_waveform,
             commands_tts::tts_set_volume,
             commands_tts::tts_set_speed,
+            commands_tts::tts_toggle_skim,
+            commands_tts::tts_set_pitch,
             commands_tts::tts_switch_provider,
+            commands_tts::dump_playback_trace,
+            commands_tts::get_inference_backends,
+            commands_tts::tts_get_provider_status,
+            commands_tts::list_audio_outputs,
+            calibration::get_speed_calibration,
+            calibration::calibration_play_sample,
+            calibration::calibration_save_speed,
+            calibration::calibration_clear,
             commands_config::get_platform,
+            commands_config::get_app_paths,
+            app_info::get_app_info,
+            diagnostics::get_diagnostics_report,
+            logging::get_recent_logs,
+            logging::open_log_folder,
+            features::list_feature_flags,
+            features::set_feature_flag,
+            offline::get_offline_mode,
+            offline::set_offline_mode,
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::switch_profile,
+            #[cfg(desktop)]
+            secrets::set_secret,
+            #[cfg(desktop)]
+            secrets::has_secret,
+            #[cfg(desktop)]
+            secrets::delete_secret,
             commands_config::get_config,
             commands_config::save_config,
             commands_config::set_explain_mode,
+            #[cfg(desktop)]
             hotkeys::get_hotkey_status,
+            #[cfg(desktop)]
+            hotkeys::get_shortcut_reference,
             commands_voices::list_piper_voices,
             commands_voices::refresh_piper_voices,
             commands_voices::list_polly_voices,
             commands_voices::list_microsoft_voices,
+            commands_voices::list_custom_server_voices,
             commands_voices::download_voice,
             commands_voices::get_download_progress,
+            commands_voices::cancel_download,
             commands_voices::list_downloaded_voices,
+            commands_voices::get_voices_disk_usage,
+            commands_voices::delete_downloaded_voice,
+            commands_voices::verify_voice,
+            commands_voices::repair_voice,
+            #[cfg(desktop)]
             commands_windows::open_settings_window,
+            #[cfg(desktop)]
             commands_windows::hide_main_window,
+            tasks::list_background_tasks,
+            tasks::cancel_task,
+            storage::get_storage_report,
+            storage::clear_cache,
+            usage::get_tts_usage,
+            usage::allow_usage_over_budget,
+            reading_stats::get_reading_stats,
+            reading_timer::start_reading_timer,
+            reading_timer::cancel_reading_timer,
+            reading_timer::get_reading_timer,
+            ocr::ocr_preprocess_image,
+            ocr::ocr_extract_text,
+            ocr::read_screenshot,
+            ocr::ocr_annotate_image,
+            documents::open_document,
+            documents::document_read_section,
+            documents::document_next_chapter,
+            documents::document_previous_chapter,
+            documents::get_document_position,
+            documents::close_document,
+            editor_pages::get_text_page,
+            editor_pages::read_from_page,
+            history::history_list,
+            history::history_resume,
+            history::history_delete,
+            latency::get_last_read_timings,
+            #[cfg(desktop)]
+            http_api::get_http_api_status,
+            #[cfg(desktop)]
+            http_api::regenerate_http_api_token,
+            #[cfg(desktop)]
+            clipboard_watch::set_clipboard_watch,
+            text::preview_preprocessing,
+            text::cleanup::clean_text,
+            web_extract::read_url,
+            actions::summarize_and_read,
+            actions::explain_selected,
+            translate::translate_and_read,
+            text::lexicon::lexicon_list,
+            text::lexicon::lexicon_add,
+            text::lexicon::lexicon_remove,
         ])
-        .on_window_event(|window, event| {
-            if let WindowEvent::CloseRequested { api, .. } = event {
-                let label = window.label();
-                if label == "editor" {
-                    let _ = window.hide();
-                    api.prevent_close();
-                } else if label == "main" {
-                    let _ = commands_windows::hide_main_window_impl(window.app_handle(), false);
-                    api.prevent_close();
-                }
-            }
-        })
         .setup(|app| {
             // Ensure main window decorations stay off on macOS (config can be inconsistent)
             #[cfg(target_os = "macos")]
@@ -166,6 +341,7 @@ pub fn run() {
                 let _ = win.set_decorations(false);
             }
 
+            #[cfg(desktop)]
             if let Some(tray) = app.tray_by_id("main") {
                 let is_visible = match app.get_webview_window("main") {
                     Some(win) => win.is_visible().unwrap_or_else(|e| {
@@ -189,14 +365,41 @@ pub fn run() {
 
             let app_handle = app.handle().clone();
 
+            #[cfg(desktop)]
             if let Some(state) = app.try_state::<hotkeys::GlobalHotkeyState>() {
                 hotkeys::refresh_global_hotkeys(&app_handle, &state.inner().clone());
             }
 
+            if let Some(state) = app.try_state::<tts::TtsState>() {
+                commands_tts::start_progress_events(&app_handle, state.inner());
+                commands_tts::start_queue_events(&app_handle, state.inner());
+                commands_tts::start_fallback_events(&app_handle, state.inner());
+                mic_pause::start(app_handle.clone(), state.inner().clone());
+                suspend::start(app_handle.clone(), state.inner().clone());
+                ducking::start(state.inner().clone());
+                #[cfg(target_os = "linux")]
+                dbus_service::start(app_handle.clone(), state.inner().clone());
+                #[cfg(any(target_os = "windows", target_os = "macos"))]
+                media_session::start(app_handle.clone(), state.inner().clone());
+                #[cfg(desktop)]
+                clipboard_watch::start(state.inner().clone());
+            }
+            #[cfg(desktop)]
             action_socket::start_action_socket_listener(app_handle.clone());
+            #[cfg(desktop)]
+            http_api::start(app_handle.clone());
+            #[cfg(desktop)]
+            config_watch::start(app_handle.clone());
+            backend::start_health_monitor(app_handle.clone());
+            tts::probe_inference_backends();
+            std::thread::spawn(|| {
+                let config = config::load_full_config().unwrap_or_default();
+                janitor::run_startup_cleanup(&config);
+                storage::prune_caches(&config);
+            });
 
             if let Ok(start_action) = std::env::var("INSIGHT_READER_START_ACTION") {
-                if let Some(action) = hotkeys::parse_app_action(&start_action) {
+                if let Some(action) = actions::parse_app_action(&start_action) {
                     actions::execute_action(&app_handle, action, "startup-action");
                 }
                 std::env::remove_var("INSIGHT_READER_START_ACTION");
@@ -204,7 +407,7 @@ pub fn run() {
 
             Ok(())
         })
-        .build(tauri::generate_context!())
+        .build(context)
     {
         Ok(app) => app,
         Err(e) => {
@@ -213,14 +416,71 @@ pub fn run() {
         }
     };
 
-    app.run(|_app_handle, _event| {
+    app.run(|app_handle, event| {
+        // Route every exit (OS quit, last window closed) through the shutdown sequence.
+        if let RunEvent::ExitRequested { api, .. } = &event {
+            if shutdown::intercept_exit(app_handle) {
+                api.prevent_exit();
+            }
+        }
+
         #[cfg(target_os = "macos")]
         if let RunEvent::Reopen {
             has_visible_windows: false,
             ..
-        } = _event
+        } = event
         {
-            commands_windows::show_main_window_impl(_app_handle);
+            commands_windows::show_main_window_impl(app_handle);
         }
     });
 }
+
+/// Adds the desktop-only plugins, state and window handling: window state, global shortcuts, and
+/// hiding (rather than closing) the main and editor windows.
+#[cfg(desktop)]
+fn with_desktop_plugins(builder: tauri::Builder<tauri::Wry>) -> tauri::Builder<tauri::Wry> {
+    let hotkey_state: hotkeys::GlobalHotkeyState =
+        Arc::new(Mutex::new(hotkeys::HotkeyRuntime::default()));
+
+    #[cfg(target_os = "linux")]
+    let window_state_plugin = tauri_plugin_window_state::Builder::default()
+        .with_state_flags(
+            tauri_plugin_window_state::StateFlags::all()
+                .difference(tauri_plugin_window_state::StateFlags::SIZE),
+        )
+        .build();
+    #[cfg(not(target_os = "linux"))]
+    let window_state_plugin = tauri_plugin_window_state::Builder::default().build();
+
+    let hotkey_state_for_handler = hotkey_state.clone();
+
+    builder
+        .plugin(window_state_plugin)
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(move |app, shortcut, event| {
+                    hotkeys::handle_global_shortcut_event(
+                        app,
+                        shortcut,
+                        event.state(),
+                        &hotkey_state_for_handler,
+                        |app, action| actions::execute_action(app, action, "global-hotkey"),
+                    );
+                })
+                .build(),
+        )
+        .manage(hotkey_state)
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                let label = window.label();
+                if label == "editor" {
+                    backend::cancel_window_prompts(label);
+                    let _ = window.hide();
+                    api.prevent_close();
+                } else if label == "main" {
+                    let _ = commands_windows::hide_main_window_impl(window.app_handle(), false);
+                    api.prevent_close();
+                }
+            }
+        })
+}
@@ -0,0 +1,76 @@
+//! Anthropic Messages API (`/v1/messages`).
+
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{custom_url, missing_key, model, post_json, prompts, LlmProvider, LlmRequest};
+use crate::backend::BackendError;
+use crate::config::FullConfig;
+use crate::offline;
+
+const DEFAULT_URL: &str = "https://api.anthropic.com/v1";
+const DEFAULT_MODEL: &str = "claude-3-5-haiku-latest";
+const API_VERSION: &str = "2023-06-01";
+/// Reply length cap; the API requires one.
+const MAX_TOKENS: u32 = 4096;
+
+pub(super) struct Anthropic {
+    url: String,
+    model: String,
+    key: String,
+}
+
+impl Anthropic {
+    pub(super) fn from_config(cfg: &FullConfig) -> Result<Self, BackendError> {
+        // Keys live in the keychain, which only desktop builds have.
+        #[cfg(desktop)]
+        let key = crate::secrets::get_or_none(crate::secrets::ANTHROPIC_API_KEY);
+        #[cfg(not(desktop))]
+        let key = None;
+        let key = key.ok_or_else(|| missing_key("Anthropic"))?;
+        Ok(Self {
+            url: custom_url(cfg).unwrap_or_else(|| DEFAULT_URL.to_string()),
+            model: model(cfg, DEFAULT_MODEL),
+            key,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    content: Vec<ContentBlock>,
+}
+
+#[derive(Deserialize)]
+struct ContentBlock {
+    text: Option<String>,
+}
+
+impl LlmProvider for Anthropic {
+    fn id(&self) -> String {
+        format!("anthropic:{}", self.model)
+    }
+
+    async fn complete(&self, request: &LlmRequest) -> Result<String, BackendError> {
+        offline::ensure_online().map_err(BackendError::Other)?;
+        let mut body = json!({
+            "model": self.model,
+            "max_tokens": MAX_TOKENS,
+            "messages": [{ "role": "user", "content": request.content }],
+        });
+        if let Some(system) = prompts::system_prompt(request)? {
+            body["system"] = json!(system);
+        }
+        let headers = [
+            ("x-api-key", self.key.as_str()),
+            ("anthropic-version", API_VERSION),
+        ];
+        let url = format!("{}/messages", self.url);
+        let response: MessagesResponse = post_json("Anthropic", &url, &headers, &body).await?;
+        Ok(response
+            .content
+            .into_iter()
+            .filter_map(|block| block.text)
+            .collect())
+    }
+}
@@ -0,0 +1,280 @@
+//! LLM providers for the backend tasks (SUMMARIZE, EXPLAIN1, ...): the ReadingService backend, or
+//! OpenAI, Anthropic or Ollama called directly with the user's own key or local endpoint.
+//!
+//! `llm_provider` picks the provider (`backend` by default); `llm_model` and `llm_url` override
+//! its default model and endpoint (`llm_url` also points `openai` at any OpenAI-compatible
+//! server). The OpenAI and Anthropic keys are keychain secrets (see `secrets`). Direct providers
+//! get each task's instructions from `prompts`, since the backend applies its own, and their
+//! replies are cached like the backend's (see `backend_cache`). `send_prompt` and
+//! `stream_prompt` are used by the Summarize/Explain flows and the `backend_prompt` commands.
+
+mod anthropic;
+pub mod ollama;
+mod openai;
+mod prompts;
+
+use std::future::Future;
+
+use serde::de::DeserializeOwned;
+use tracing::{debug, warn};
+
+use crate::backend::{self, BackendError};
+use crate::backend_cache;
+use crate::config::{self, FullConfig};
+
+/// Timeout for a direct provider call; long inputs can take a while to summarize.
+const REQUEST_TIMEOUT_SECS: u64 = 120;
+
+/// A task for an LLM, with the fields of a backend prompt (see backend-api.md).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LlmRequest {
+    pub task: String,
+    pub content: String,
+    pub tone: Option<String>,
+    pub format: Option<String>,
+    pub instruction: Option<String>,
+}
+
+/// Runs backend tasks on some LLM.
+pub trait LlmProvider {
+    /// Provider and model (e.g. `openai:gpt-4o-mini`); keys the cached replies.
+    fn id(&self) -> String;
+
+    /// Runs `request` and returns the reply text.
+    fn complete(
+        &self,
+        request: &LlmRequest,
+    ) -> impl Future<Output = Result<String, BackendError>> + Send;
+}
+
+/// The ReadingService backend: tasks go to POST /api/prompt as they are (see `backend`).
+pub struct ReadingService;
+
+impl LlmProvider for ReadingService {
+    fn id(&self) -> String {
+        "backend".to_string()
+    }
+
+    async fn complete(&self, request: &LlmRequest) -> Result<String, BackendError> {
+        backend::send_prompt(
+            request.task.clone(),
+            request.content.clone(),
+            request.tone.clone(),
+            request.format.clone(),
+            request.instruction.clone(),
+        )
+        .await
+    }
+}
+
+/// The `llm_provider` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderKind {
+    ReadingService,
+    OpenAi,
+    Anthropic,
+    Ollama,
+}
+
+impl ProviderKind {
+    pub fn from_config(cfg: &FullConfig) -> Self {
+        let name = cfg
+            .llm_provider
+            .as_deref()
+            .map(|p| p.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+        match name.as_str() {
+            "openai" => Self::OpenAi,
+            "anthropic" => Self::Anthropic,
+            "ollama" => Self::Ollama,
+            "" | "backend" => Self::ReadingService,
+            other => {
+                warn!(provider = other, "Unknown llm_provider, using the backend");
+                Self::ReadingService
+            }
+        }
+    }
+}
+
+/// Runs the task on the configured provider and returns the reply.
+pub async fn send_prompt(
+    task: String,
+    content: String,
+    tone: Option<String>,
+    format: Option<String>,
+    instruction: Option<String>,
+) -> Result<String, BackendError> {
+    let request = LlmRequest {
+        task,
+        content,
+        tone,
+        format,
+        instruction,
+    };
+    let cfg = config::load_full_config().unwrap_or_default();
+    match ProviderKind::from_config(&cfg) {
+        // The backend caches its replies itself.
+        ProviderKind::ReadingService => ReadingService.complete(&request).await,
+        ProviderKind::OpenAi => {
+            complete_cached(&openai::OpenAi::from_config(&cfg)?, &request).await
+        }
+        ProviderKind::Anthropic => {
+            complete_cached(&anthropic::Anthropic::from_config(&cfg)?, &request).await
+        }
+        ProviderKind::Ollama => {
+            complete_cached(&ollama::Ollama::resolve(&cfg).await, &request).await
+        }
+    }
+}
+
+/// Like `send_prompt`, calling `on_delta` with the reply as it arrives. Only the backend streams;
+/// other providers hand over the whole reply at once.
+pub async fn stream_prompt(
+    task: String,
+    content: String,
+    tone: Option<String>,
+    format: Option<String>,
+    instruction: Option<String>,
+    mut on_delta: impl FnMut(&str),
+) -> Result<String, BackendError> {
+    let cfg = config::load_full_config().unwrap_or_default();
+    if ProviderKind::from_config(&cfg) == ProviderKind::ReadingService {
+        return backend::stream_prompt(task, content, tone, format, instruction, on_delta).await;
+    }
+    let response = send_prompt(task, content, tone, format, instruction).await?;
+    on_delta(&response);
+    Ok(response)
+}
+
+/// Runs `request` on `provider`, answering from the response cache when it can.
+async fn complete_cached<P: LlmProvider>(
+    provider: &P,
+    request: &LlmRequest,
+) -> Result<String, BackendError> {
+    let key = backend_cache::cache_key(
+        &format!("{}/{}", provider.id(), request.task),
+        &request.content,
+        request.tone.as_deref(),
+        request.format.as_deref(),
+        request.instruction.as_deref(),
+    );
+    if let Some(response) = backend_cache::lookup(&key) {
+        debug!(provider = %provider.id(), task = %request.task, "Prompt answered from the cache");
+        return Ok(response);
+    }
+    debug!(provider = %provider.id(), task = %request.task, "Sending prompt");
+    let response = provider.complete(request).await?;
+    if response.trim().is_empty() {
+        return Err(BackendError::Other(format!(
+            "{} returned an empty response",
+            provider.id()
+        )));
+    }
+    backend_cache::store(&key, &request.task, &request.content, &response);
+    Ok(response)
+}
+
+/// `llm_model`, else the provider's default.
+fn model(cfg: &FullConfig, default: &str) -> String {
+    cfg.llm_model
+        .as_deref()
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .unwrap_or(default)
+        .to_string()
+}
+
+/// `llm_url` without a trailing slash, when set.
+fn custom_url(cfg: &FullConfig) -> Option<String> {
+    cfg.llm_url
+        .as_deref()
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+}
+
+/// Posts `body` to `url` and parses the JSON reply. HTTP 429 becomes `RateLimited`; other errors
+/// carry the provider's message.
+async fn post_json<T: DeserializeOwned>(
+    provider: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: &serde_json::Value,
+) -> Result<T, BackendError> {
+    let client = backend::make_client(REQUEST_TIMEOUT_SECS).map_err(BackendError::Other)?;
+    let mut builder = client.post(url).json(body);
+    for (name, value) in headers {
+        builder = builder.header(*name, *value);
+    }
+    let resp = builder
+        .send()
+        .await
+        .map_err(|e| BackendError::Other(format!("Could not reach {provider} at {url}. ({e})")))?;
+    let status = resp.status();
+    if status.is_success() {
+        return resp
+            .json::<T>()
+            .await
+            .map_err(|e| BackendError::Other(format!("Invalid {provider} response: {e}")));
+    }
+    let retry_after = backend::parse_retry_after(resp.headers().get(reqwest::header::RETRY_AFTER));
+    let body = resp.text().await.unwrap_or_default();
+    let message =
+        error_message(&body).unwrap_or_else(|| format!("{provider} HTTP {status}: {body}"));
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        Err(BackendError::RateLimited {
+            message,
+            retry_after,
+        })
+    } else {
+        Err(BackendError::Other(format!("{provider}: {message}")))
+    }
+}
+
+/// The message of an error body: `error.message` (OpenAI, Anthropic) or `error` (Ollama).
+fn error_message(body: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let error = value.get("error")?;
+    error
+        .get("message")
+        .and_then(|m| m.as_str())
+        .or_else(|| error.as_str())
+        .map(str::to_string)
+}
+
+/// The error for a provider whose key has not been stored.
+fn missing_key(provider: &str) -> BackendError {
+    BackendError::Other(format!(
+        "No {provider} API key. Add it in Settings → General → LLM Provider."
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_defaults_to_the_backend() {
+        let mut cfg = FullConfig::default();
+        assert_eq!(
+            ProviderKind::from_config(&cfg),
+            ProviderKind::ReadingService
+        );
+        cfg.llm_provider = Some(" Anthropic ".to_string());
+        assert_eq!(ProviderKind::from_config(&cfg), ProviderKind::Anthropic);
+        cfg.llm_provider = Some("gemini".to_string());
+        assert_eq!(
+            ProviderKind::from_config(&cfg),
+            ProviderKind::ReadingService
+        );
+
+        assert_eq!(
+            error_message(r#"{"error": {"message": "Invalid API key", "type": "auth"}}"#),
+            Some("Invalid API key".to_string())
+        );
+        assert_eq!(
+            error_message(r#"{"error": "model not found"}"#),
+            Some("model not found".to_string())
+        );
+        assert_eq!(error_message("Bad Gateway"), None);
+    }
+}
@@ -0,0 +1,218 @@
+//! Ollama's chat API (`/api/chat`) on a local or remote Ollama server, so the AI features work
+//! without any cloud service.
+//!
+//! The server is found at `llm_url`, else on localhost. Its installed models come from
+//! `/api/tags`: `ollama_status` reports them to the settings UI, and without an `llm_model` the
+//! prompts run on `DEFAULT_MODEL` when it is installed, else on the first installed model.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::debug;
+
+use super::{custom_url, model, post_json, prompts, LlmProvider, LlmRequest};
+use crate::backend::{self, BackendError};
+use crate::config::{self, FullConfig};
+use crate::error::AppError;
+use crate::offline;
+
+const DEFAULT_URL: &str = "http://localhost:11434";
+const DEFAULT_MODEL: &str = "llama3.2";
+
+/// Timeout for listing the installed models; a local server answers at once when it runs.
+const TAGS_TIMEOUT_SECS: u64 = 3;
+
+pub(super) struct Ollama {
+    url: String,
+    model: String,
+}
+
+impl Ollama {
+    pub(super) fn from_config(cfg: &FullConfig) -> Self {
+        Self {
+            url: custom_url(cfg).unwrap_or_else(|| DEFAULT_URL.to_string()),
+            model: model(cfg, DEFAULT_MODEL),
+        }
+    }
+
+    /// Like `from_config`, but without an `llm_model` picks an installed model (see
+    /// `pick_model`). Falls back to `DEFAULT_MODEL` when the server cannot be asked.
+    pub(super) async fn resolve(cfg: &FullConfig) -> Self {
+        let mut ollama = Self::from_config(cfg);
+        let explicit = cfg
+            .llm_model
+            .as_deref()
+            .is_some_and(|m| !m.trim().is_empty());
+        if explicit || !ollama.may_connect() {
+            return ollama;
+        }
+        match installed_models(&ollama.url).await {
+            Ok(models) => {
+                if let Some(name) = pick_model(&models) {
+                    ollama.model = name.to_string();
+                }
+            }
+            Err(e) => debug!(error = %e, "Could not list Ollama models"),
+        }
+        ollama
+    }
+
+    /// Whether the server runs on this machine, which offline mode allows.
+    fn is_local(&self) -> bool {
+        reqwest::Url::parse(&self.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .is_some_and(|host| matches!(host.as_str(), "localhost" | "127.0.0.1" | "[::1]"))
+    }
+
+    /// Local servers are always allowed; remote ones only outside offline mode.
+    fn may_connect(&self) -> bool {
+        self.is_local() || offline::ensure_online().is_ok()
+    }
+}
+
+/// A model installed on the Ollama server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OllamaModel {
+    /// Name to use as `llm_model`, e.g. `llama3.2:latest`.
+    pub name: String,
+    /// Download size in bytes.
+    pub size: u64,
+    /// e.g. `3.2B`, when the server reports it.
+    pub parameter_size: Option<String>,
+}
+
+/// Result of `ollama_status`.
+#[derive(Debug, Clone, Serialize)]
+pub struct OllamaStatus {
+    pub url: String,
+    pub running: bool,
+    pub models: Vec<OllamaModel>,
+}
+
+#[derive(Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<TagsModel>,
+}
+
+#[derive(Deserialize)]
+struct TagsModel {
+    name: String,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    details: TagsDetails,
+}
+
+#[derive(Deserialize, Default)]
+struct TagsDetails {
+    parameter_size: Option<String>,
+}
+
+/// The models installed on the server at `url`; an error when it does not answer.
+async fn installed_models(url: &str) -> Result<Vec<OllamaModel>, String> {
+    let client = backend::make_client(TAGS_TIMEOUT_SECS)?;
+    let resp = client
+        .get(format!("{url}/api/tags"))
+        .send()
+        .await
+        .map_err(|e| format!("Ollama is not running at {url} ({e})"))?;
+    if !resp.status().is_success() {
+        return Err(format!("Ollama at {url} answered HTTP {}", resp.status()));
+    }
+    let tags: TagsResponse = resp
+        .json()
+        .await
+        .map_err(|e| format!("Invalid Ollama response: {e}"))?;
+    Ok(tags
+        .models
+        .into_iter()
+        .map(|m| OllamaModel {
+            name: m.name,
+            size: m.size,
+            parameter_size: m.details.parameter_size,
+        })
+        .collect())
+}
+
+/// `DEFAULT_MODEL` when installed (under any tag), else the first installed model.
+fn pick_model(installed: &[OllamaModel]) -> Option<&str> {
+    installed
+        .iter()
+        .find(|m| m.name.split(':').next() == Some(DEFAULT_MODEL))
+        .or_else(|| installed.first())
+        .map(|m| m.name.as_str())
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    message: Message,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    content: String,
+}
+
+impl LlmProvider for Ollama {
+    fn id(&self) -> String {
+        format!("ollama:{}", self.model)
+    }
+
+    async fn complete(&self, request: &LlmRequest) -> Result<String, BackendError> {
+        if !self.is_local() {
+            offline::ensure_online().map_err(BackendError::Other)?;
+        }
+        let mut messages = Vec::new();
+        if let Some(system) = prompts::system_prompt(request)? {
+            messages.push(json!({ "role": "system", "content": system }));
+        }
+        messages.push(json!({ "role": "user", "content": request.content }));
+        let body = json!({ "model": self.model, "messages": messages, "stream": false });
+        let url = format!("{}/api/chat", self.url);
+        let response: ChatResponse = post_json("Ollama", &url, &[], &body).await?;
+        Ok(response.message.content)
+    }
+}
+
+/// Whether an Ollama server answers at `llm_url` (else on localhost), and its installed models.
+#[tauri::command]
+pub async fn ollama_status() -> Result<OllamaStatus, AppError> {
+    let cfg = config::load_full_config().unwrap_or_default();
+    let ollama = Ollama::from_config(&cfg);
+    if !ollama.is_local() {
+        offline::ensure_online()?;
+    }
+    let models = installed_models(&ollama.url).await;
+    if let Err(e) = &models {
+        debug!(error = %e, "Ollama not detected");
+    }
+    Ok(OllamaStatus {
+        running: models.is_ok(),
+        models: models.unwrap_or_default(),
+        url: ollama.url,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_model_prefers_the_default_model() {
+        let model = |name: &str| OllamaModel {
+            name: name.to_string(),
+            size: 0,
+            parameter_size: None,
+        };
+        assert_eq!(pick_model(&[]), None);
+        assert_eq!(
+            pick_model(&[model("mistral:7b"), model("llama3.2:latest")]),
+            Some("llama3.2:latest")
+        );
+        assert_eq!(
+            pick_model(&[model("mistral:7b"), model("llama3.1:8b")]),
+            Some("mistral:7b")
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! OpenAI chat completions (`/chat/completions`), or any OpenAI-compatible server at `llm_url`.
+
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{custom_url, missing_key, model, post_json, prompts, LlmProvider, LlmRequest};
+use crate::backend::BackendError;
+use crate::config::FullConfig;
+use crate::offline;
+
+const DEFAULT_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+pub(super) struct OpenAi {
+    url: String,
+    model: String,
+    /// Optional for a custom `llm_url` (local servers usually take none).
+    key: Option<String>,
+}
+
+impl OpenAi {
+    pub(super) fn from_config(cfg: &FullConfig) -> Result<Self, BackendError> {
+        let url = custom_url(cfg);
+        // Keys live in the keychain, which only desktop builds have.
+        #[cfg(desktop)]
+        let key = crate::secrets::get_or_none(crate::secrets::OPENAI_API_KEY);
+        #[cfg(not(desktop))]
+        let key = None;
+        if key.is_none() && url.is_none() {
+            return Err(missing_key("OpenAI"));
+        }
+        Ok(Self {
+            url: url.unwrap_or_else(|| DEFAULT_URL.to_string()),
+            model: model(cfg, DEFAULT_MODEL),
+            key,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: Message,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    content: Option<String>,
+}
+
+impl LlmProvider for OpenAi {
+    fn id(&self) -> String {
+        format!("openai:{}", self.model)
+    }
+
+    async fn complete(&self, request: &LlmRequest) -> Result<String, BackendError> {
+        offline::ensure_online().map_err(BackendError::Other)?;
+        let mut messages = Vec::new();
+        if let Some(system) = prompts::system_prompt(request)? {
+            messages.push(json!({ "role": "system", "content": system }));
+        }
+        messages.push(json!({ "role": "user", "content": request.content }));
+        let body = json!({ "model": self.model, "messages": messages });
+        let authorization = self.key.as_ref().map(|key| format!("Bearer {key}"));
+        let headers: Vec<(&str, &str)> = authorization
+            .as_deref()
+            .map(|value| ("Authorization", value))
+            .into_iter()
+            .collect();
+        let url = format!("{}/chat/completions", self.url);
+        let response: ChatResponse = post_json("OpenAI", &url, &headers, &body).await?;
+        Ok(response
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .unwrap_or_default())
+    }
+}
@@ -0,0 +1,114 @@
+//! Instructions for the backend tasks when they run on a provider directly, following the task
+//! semantics in backend-api.md.
+
+use super::LlmRequest;
+use crate::backend::BackendError;
+
+const SUMMARIZE: &str = "Summarize the user's text into a concise, high-signal summary. Ignore \
+    UI chrome and metadata (timestamps, vote counts, permalinks, share/reply buttons, flair, \
+    pagination, signatures, tracking parameters). Keep the substance: arguments, conclusions, \
+    decisions, important caveats, numbers and named entities. Preserve nuance and call out \
+    disagreements or key alternatives. Do not invent anything that is not in the text. Answer \
+    in plain text or Markdown: a single short paragraph for short inputs; for longer ones a \
+    one or two sentence overview, then 3 to 8 bullet points, and 'Action items:' or 'Open \
+    questions:' when the text implies them.";
+
+const READ_ALOUD: &str = " The summary will be read aloud: use short sentences that sound \
+    natural when spoken and avoid tables or symbols.";
+
+const TTS: &str = "Clean the user's text (copied from a social or media page) for reading \
+    aloud: remove UI clutter, format it for narration and turn URLs and emojis into their \
+    spoken form. Keep the content itself. Answer with Markdown only.";
+
+const EXPLAIN1: &str = "Explain the substance of the user's text for a capable professional who \
+    missed the point: clearer wording and brief clarifications, with the same rigor. Do not \
+    oversimplify.";
+
+const EXPLAIN2: &str = "Explain the user's text in plain language: short sentences, concrete \
+    examples and as little jargon as possible, while staying professional and respectful.";
+
+const REWRITE: &str = "Rewrite the user's text, keeping its core meaning while adjusting its \
+    style. Answer with the rewritten text only.";
+
+const QUICK_EDIT: &str = "Apply a small, focused edit to the user's text and answer with the \
+    edited text only.";
+
+/// Quick edit applied when the request names none.
+const DEFAULT_QUICK_EDIT: &str = "Make it clearer and more concise.";
+
+/// The system prompt for `request`; `None` for `PROMPT`, whose content is the whole prompt.
+pub(super) fn system_prompt(request: &LlmRequest) -> Result<Option<String>, BackendError> {
+    let hint = |label: &str, value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| format!(" {label}: {v}."))
+            .unwrap_or_default()
+    };
+    let prompt = match request.task.as_str() {
+        "PROMPT" => return Ok(None),
+        "TTS" => TTS.to_string(),
+        "SUMMARIZE" | "SUMMARIZE_PROMPT" => SUMMARIZE.to_string(),
+        "SUMMARIZE_AND_READ_PROMPT" => format!("{SUMMARIZE}{READ_ALOUD}"),
+        "EXPLAIN1" => EXPLAIN1.to_string(),
+        "EXPLAIN2" => EXPLAIN2.to_string(),
+        "REWRITE" => format!(
+            "{REWRITE}{}{}",
+            hint("Tone", &request.tone),
+            hint("Format", &request.format)
+        ),
+        "QUICK_EDIT" => {
+            let instruction = request
+                .instruction
+                .as_deref()
+                .map(str::trim)
+                .filter(|i| !i.is_empty())
+                .unwrap_or(DEFAULT_QUICK_EDIT);
+            format!("{QUICK_EDIT} Edit: {instruction}")
+        }
+        "TRANSLATE" => {
+            let target = request
+                .instruction
+                .as_deref()
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .ok_or_else(|| {
+                    BackendError::Other("TRANSLATE needs a target language".to_string())
+                })?;
+            format!(
+                "Translate the user's text into the language with the BCP-47 tag {target}. \
+                 Keep the paragraphs and answer with the translation only."
+            )
+        }
+        other => return Err(BackendError::Other(format!("Unknown task: {other}"))),
+    };
+    Ok(Some(prompt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_options_go_into_the_system_prompt() {
+        let mut request = LlmRequest {
+            task: "REWRITE".to_string(),
+            content: "hey, can we talk tomorrow".to_string(),
+            tone: Some("professional".to_string()),
+            format: None,
+            instruction: None,
+        };
+        let prompt = system_prompt(&request).ok().flatten().unwrap_or_default();
+        assert!(prompt.ends_with(" Tone: professional."));
+
+        request.task = "TRANSLATE".to_string();
+        assert!(system_prompt(&request).is_err());
+        request.instruction = Some("pt-BR".to_string());
+        let prompt = system_prompt(&request).ok().flatten().unwrap_or_default();
+        assert!(prompt.contains("pt-BR"));
+
+        request.task = "PROMPT".to_string();
+        assert_eq!(system_prompt(&request), Ok(None));
+    }
+}
@@ -0,0 +1,220 @@
+//! Logging: stderr/stdout plus daily log files in `paths::get_logs_dir()`.
+//!
+//! The level comes from `log_level` in the config and is applied again on every config change
+//! (see `commands_config::apply_runtime_settings`); `RUST_LOG`, when set, overrides it for the
+//! whole session. Files rotate daily and the newest `MAX_LOG_FILES` are kept (the storage janitor
+//! also prunes by `log_retention_days`). `get_recent_logs` and `open_log_folder` are for support
+//! and bug reports.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tauri_plugin_opener::OpenerExt;
+use tracing::{info, warn};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+use crate::config;
+use crate::paths;
+
+/// Log file names: `insight-reader.<date>.log`.
+const LOG_FILE_PREFIX: &str = "insight-reader";
+const LOG_FILE_SUFFIX: &str = "log";
+/// Daily log files kept by the appender.
+const MAX_LOG_FILES: usize = 7;
+
+const DEFAULT_RECENT_LINES: usize = 200;
+const MAX_RECENT_LINES: usize = 5000;
+
+/// Noisy dependencies kept at `warn` whatever the level.
+const QUIET_DIRECTIVES: [&str; 2] = [
+    "aws_config::profile::credentials=warn",
+    "aws_credential_types=warn",
+];
+
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+static DEFAULT_LEVEL: OnceLock<String> = OnceLock::new();
+
+fn filter_for(level: &str) -> Result<EnvFilter, String> {
+    let mut filter = EnvFilter::try_new(level).map_err(|e| format!("Invalid log level: {e}"))?;
+    for directive in QUIET_DIRECTIVES {
+        if let Ok(parsed) = directive.parse() {
+            filter = filter.add_directive(parsed);
+        }
+    }
+    Ok(filter)
+}
+
+fn file_appender() -> Result<RollingFileAppender, String> {
+    let dir = paths::get_logs_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)
+        .map_err(|e| format!("Failed to open log file in {}: {e}", dir.display()))
+}
+
+/// Sets up logging; `RUST_LOG` overrides `default_level`. The headless CLI logs to stderr so its
+/// output on stdout stays clean.
+pub fn init(default_level: &str, to_stderr: bool) {
+    let _ = DEFAULT_LEVEL.set(default_level.to_string());
+    let filter = EnvFilter::try_from_default_env()
+        .ok()
+        .or_else(|| filter_for(default_level).ok())
+        .unwrap_or_else(|| EnvFilter::new("info"));
+    let (filter, handle) = reload::Layer::new(filter);
+    let _ = FILTER.set(handle);
+
+    let console_writer = if to_stderr {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+    let (file, file_error) = match file_appender() {
+        Ok(appender) => (
+            Some(fmt::layer().with_ansi(false).with_writer(appender)),
+            None,
+        ),
+        Err(e) => (None, Some(e)),
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(console_writer))
+        .with(file)
+        .init();
+    if let Some(e) = file_error {
+        warn!(error = %e, "Logging to stderr only");
+    }
+}
+
+/// Applies `log_level` from the config, unless `RUST_LOG` is set.
+pub fn configure(cfg: &config::FullConfig) {
+    if std::env::var_os("RUST_LOG").is_some() {
+        return;
+    }
+    let Some(handle) = FILTER.get() else {
+        return;
+    };
+    let level = cfg
+        .log_level
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .or(DEFAULT_LEVEL.get().map(String::as_str))
+        .unwrap_or("info");
+    match filter_for(level) {
+        Ok(filter) => {
+            if let Err(e) = handle.reload(filter) {
+                warn!(error = %e, "Failed to apply log level");
+            }
+        }
+        Err(e) => warn!(level = %level, error = %e, "Ignoring log_level"),
+    }
+}
+
+/// Log files in `dir`, newest first.
+fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .flatten()
+        .filter(|e| {
+            e.file_name()
+                .to_str()
+                .is_some_and(|n| n.starts_with(LOG_FILE_PREFIX))
+        })
+        .filter_map(|e| {
+            let modified = e.metadata().ok()?.modified().ok()?;
+            Some((modified, e.path()))
+        })
+        .collect();
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    files.into_iter().map(|(_, path)| path).collect()
+}
+
+/// The last `count` lines of `contents` (newest file first), oldest line first.
+fn tail_lines(contents: &[String], count: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for content in contents {
+        let needed = count - lines.len();
+        let mut older: Vec<String> = content
+            .lines()
+            .rev()
+            .take(needed)
+            .map(str::to_string)
+            .collect();
+        older.reverse();
+        older.append(&mut lines);
+        lines = older;
+        if lines.len() >= count {
+            break;
+        }
+    }
+    lines
+}
+
+/// The last `count` log lines, oldest first, across log files.
+pub fn recent_lines(count: usize) -> Result<Vec<String>, String> {
+    let mut contents = Vec::new();
+    let mut total = 0;
+    for path in log_files(&paths::get_logs_dir()?) {
+        if total >= count {
+            break;
+        }
+        match fs::read(&path) {
+            Ok(bytes) => {
+                let content = String::from_utf8_lossy(&bytes).into_owned();
+                total += content.lines().count();
+                contents.push(content);
+            }
+            Err(e) => warn!(path = %path.display(), error = %e, "Failed to read log file"),
+        }
+    }
+    Ok(tail_lines(&contents, count))
+}
+
+/// The last `lines` log lines (default 200, at most 5000), oldest first, across log files.
+#[tauri::command]
+pub fn get_recent_logs(lines: Option<usize>) -> Result<Vec<String>, String> {
+    recent_lines(
+        lines
+            .unwrap_or(DEFAULT_RECENT_LINES)
+            .clamp(1, MAX_RECENT_LINES),
+    )
+}
+
+/// Opens the log folder in the file manager.
+#[tauri::command]
+pub fn open_log_folder(app: tauri::AppHandle) -> Result<(), String> {
+    let dir = paths::get_logs_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {e}", dir.display()))?;
+    info!(dir = %dir.display(), "Opened log folder");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_spans_newest_files_first() {
+        let contents = vec!["c1\nc2\n".to_string(), "b1\nb2\nb3\n".to_string()];
+        assert_eq!(tail_lines(&contents, 1), vec!["c2"]);
+        assert_eq!(tail_lines(&contents, 4), vec!["b2", "b3", "c1", "c2"]);
+        assert_eq!(tail_lines(&contents, 10).len(), 5);
+        assert!(filter_for("debug").is_ok());
+        assert!(filter_for("insight_reader=nonsense=").is_err());
+    }
+}
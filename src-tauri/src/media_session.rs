@@ -0,0 +1,144 @@
+//! System media session on Windows and macOS: media keys, headphone buttons and the OS media
+//! overlay (SMTC on Windows, Now Playing on macOS) control playback, and the session shows
+//! whether the app is reading. On Linux, `dbus_service` covers scripting instead.
+//!
+//! Play/Pause/Toggle map to `AppAction::TogglePause` (Play only while paused, Pause only while
+//! playing), Stop to `AppAction::Stop`, and Next/Previous to the next/previous sentence. The
+//! controls are created on the main thread and stay there (they are not `Send` on macOS); a
+//! background thread polls the TTS worker every `POLL_INTERVAL` and updates the session when
+//! playback changes.
+
+use std::cell::RefCell;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use souvlaki::{MediaControlEvent, MediaControls, MediaMetadata, MediaPlayback, PlatformConfig};
+use tauri::AppHandle;
+use tracing::{debug, warn};
+
+use crate::actions::{self, AppAction};
+use crate::tts;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+thread_local! {
+    static CONTROLS: RefCell<Option<MediaControls>> = const { RefCell::new(None) };
+}
+
+/// Seeks to the next or previous sentence.
+fn skip_sentence(tts_tx: &tts::TtsState, next: bool) {
+    let (resp_tx, _resp_rx) = mpsc::sync_channel(1);
+    let unit = tts::SegmentUnit::Sentence;
+    let request = if next {
+        tts::TtsRequest::NextSegment(unit, resp_tx)
+    } else {
+        tts::TtsRequest::PrevSegment(unit, resp_tx)
+    };
+    let _ = tts_tx.send(request);
+}
+
+fn handle_event(app: &AppHandle, tts_tx: &tts::TtsState, event: MediaControlEvent) {
+    let (playing, paused) = tts::playback_status(tts_tx).unwrap_or_default();
+    match event {
+        MediaControlEvent::Toggle => {
+            actions::execute_action(app, AppAction::TogglePause, "media-key")
+        }
+        MediaControlEvent::Play if paused => {
+            actions::execute_action(app, AppAction::TogglePause, "media-key")
+        }
+        MediaControlEvent::Pause if playing && !paused => {
+            actions::execute_action(app, AppAction::TogglePause, "media-key")
+        }
+        MediaControlEvent::Stop => actions::execute_action(app, AppAction::Stop, "media-key"),
+        MediaControlEvent::Next => skip_sentence(tts_tx, true),
+        MediaControlEvent::Previous => skip_sentence(tts_tx, false),
+        other => debug!(event = ?other, "Ignoring media control event"),
+    }
+}
+
+fn playback((playing, paused): (bool, bool)) -> MediaPlayback {
+    if paused {
+        MediaPlayback::Paused { progress: None }
+    } else if playing {
+        MediaPlayback::Playing { progress: None }
+    } else {
+        MediaPlayback::Stopped
+    }
+}
+
+/// Creates the controls on the main thread.
+fn create(app: AppHandle, tts_tx: tts::TtsState) {
+    #[cfg(target_os = "windows")]
+    let hwnd = {
+        use tauri::Manager;
+        app.get_webview_window("main")
+            .and_then(|window| window.hwnd().ok())
+            .map(|hwnd| hwnd.0)
+    };
+    #[cfg(not(target_os = "windows"))]
+    let hwnd = None;
+
+    let config = PlatformConfig {
+        dbus_name: "insight_reader",
+        display_name: "Insight Reader",
+        hwnd,
+    };
+    let mut controls = match MediaControls::new(config) {
+        Ok(controls) => controls,
+        Err(e) => {
+            warn!(error = ?e, "Failed to create the system media session");
+            return;
+        }
+    };
+    if let Err(e) = controls.attach(move |event| handle_event(&app, &tts_tx, event)) {
+        warn!(error = ?e, "Failed to attach media control events");
+        return;
+    }
+    let metadata = MediaMetadata {
+        title: Some("Insight Reader"),
+        ..MediaMetadata::default()
+    };
+    if let Err(e) = controls.set_metadata(metadata) {
+        debug!(error = ?e, "Failed to set media session metadata");
+    }
+    if let Err(e) = controls.set_playback(MediaPlayback::Stopped) {
+        debug!(error = ?e, "Failed to set media session state");
+    }
+    CONTROLS.with(|cell| *cell.borrow_mut() = Some(controls));
+}
+
+/// Registers the media session and keeps it in sync with playback. Called from lib's setup.
+pub fn start(app: AppHandle, tts_tx: tts::TtsState) {
+    let main_app = app.clone();
+    let events_tx = tts_tx.clone();
+    if let Err(e) = app.run_on_main_thread(move || create(main_app, events_tx)) {
+        warn!(error = %e, "Failed to create the system media session");
+        return;
+    }
+
+    std::thread::spawn(move || {
+        let mut last = (false, false);
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let Some(state) = tts::playback_status(&tts_tx) else {
+                break;
+            };
+            if state == last {
+                continue;
+            }
+            last = state;
+            let updated = app.run_on_main_thread(move || {
+                CONTROLS.with(|cell| {
+                    if let Some(controls) = cell.borrow_mut().as_mut() {
+                        if let Err(e) = controls.set_playback(playback(state)) {
+                            debug!(error = ?e, "Failed to update media session state");
+                        }
+                    }
+                })
+            });
+            if updated.is_err() {
+                break;
+            }
+        }
+    });
+}
@@ -0,0 +1,137 @@
+//! Auto-pause during microphone use: when a meeting or call app starts recording, playback is
+//! paused, and (optionally) resumed once the microphone is released.
+//!
+//! Off by default (`mic_auto_pause`); `mic_auto_resume` (on by default) controls resuming. A
+//! background thread polls `system::is_microphone_in_use` every `POLL_INTERVAL` while enabled and
+//! emits `mic-auto-pause` when it pauses or resumes. Only playback we paused is resumed: if the
+//! user resumed, stopped or started something else in between, it is left alone.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use tauri::Emitter;
+use tracing::{debug, info};
+
+use crate::config::FullConfig;
+use crate::system;
+use crate::tts;
+
+/// Event emitted when playback is paused or resumed because of the microphone.
+pub const MIC_AUTO_PAUSE_EVENT: &str = "mic-auto-pause";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static RESUME: AtomicBool = AtomicBool::new(true);
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MicAutoPause {
+    /// True when playback was paused, false when it was resumed.
+    pub paused: bool,
+}
+
+/// Applies `mic_auto_pause` / `mic_auto_resume` (see `commands_config::apply_runtime_settings`).
+pub fn configure(cfg: &FullConfig) {
+    ENABLED.store(cfg.mic_auto_pause.unwrap_or(false), Ordering::Relaxed);
+    RESUME.store(cfg.mic_auto_resume.unwrap_or(true), Ordering::Relaxed);
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    Pause,
+    Resume,
+}
+
+/// Microphone transitions and whether the current pause is ours.
+#[derive(Default)]
+struct Monitor {
+    mic_in_use: bool,
+    paused_by_us: bool,
+}
+
+impl Monitor {
+    /// Takes one sample; `status` is the player's (playing, paused).
+    fn on_sample(
+        &mut self,
+        mic_in_use: bool,
+        status: (bool, bool),
+        resume: bool,
+    ) -> Option<Action> {
+        let (playing, paused) = status;
+        let was_in_use = std::mem::replace(&mut self.mic_in_use, mic_in_use);
+        if mic_in_use && !was_in_use {
+            self.paused_by_us = playing && !paused;
+            return self.paused_by_us.then_some(Action::Pause);
+        }
+        if !mic_in_use && was_in_use {
+            let ours = std::mem::take(&mut self.paused_by_us);
+            return (ours && resume && playing && paused).then_some(Action::Resume);
+        }
+        None
+    }
+}
+
+/// Starts the polling thread. Called from lib's setup.
+pub fn start<R: tauri::Runtime>(app: tauri::AppHandle<R>, tts_tx: tts::TtsState) {
+    std::thread::spawn(move || {
+        let mut monitor = Monitor::default();
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            if !ENABLED.load(Ordering::Relaxed) {
+                monitor = Monitor::default();
+                continue;
+            }
+            let Some(mic_in_use) = system::is_microphone_in_use() else {
+                continue;
+            };
+            let Some(status) = tts::playback_status(&tts_tx) else {
+                break;
+            };
+            let Some(action) =
+                monitor.on_sample(mic_in_use, status, RESUME.load(Ordering::Relaxed))
+            else {
+                continue;
+            };
+            info!(?action, "Microphone in use changed, toggling playback");
+            let (resp_tx, resp_rx) = mpsc::sync_channel(1);
+            if tts_tx.send(tts::TtsRequest::TogglePause(resp_tx)).is_err() {
+                break;
+            }
+            match resp_rx.recv() {
+                Ok(Ok(paused)) => {
+                    let _ = app.emit(MIC_AUTO_PAUSE_EVENT, MicAutoPause { paused });
+                }
+                Ok(Err(e)) => debug!(error = %e, "Auto-pause toggle failed"),
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resumes_only_playback_it_paused() {
+        let mut monitor = Monitor::default();
+        assert_eq!(
+            monitor.on_sample(true, (true, false), true),
+            Some(Action::Pause)
+        );
+        assert_eq!(monitor.on_sample(true, (true, true), true), None);
+        assert_eq!(
+            monitor.on_sample(false, (true, true), true),
+            Some(Action::Resume)
+        );
+
+        // Nothing playing when the call started: nothing to resume.
+        assert_eq!(monitor.on_sample(true, (false, false), true), None);
+        assert_eq!(monitor.on_sample(false, (true, true), true), None);
+
+        // The user stopped playback during the call.
+        monitor.on_sample(true, (true, false), true);
+        assert_eq!(monitor.on_sample(false, (false, false), true), None);
+    }
+}
@@ -0,0 +1,58 @@
+//! Debug rendering of OCR results onto the captured image.
+//!
+//! Each recognized word gets a box outline colored by confidence (green / orange / red) and a bar
+//! above it whose length is proportional to the confidence. Used by `ocr_annotate_image` so users
+//! can attach the picture to bug reports about missed text.
+
+use image::{Rgba, RgbaImage};
+
+use super::OcrWord;
+
+const BOX_THICKNESS: u32 = 2;
+const BAR_HEIGHT: u32 = 3;
+const HIGH_CONFIDENCE: f32 = 80.0;
+const MEDIUM_CONFIDENCE: f32 = 50.0;
+
+const GREEN: Rgba<u8> = Rgba([0, 180, 0, 255]);
+const ORANGE: Rgba<u8> = Rgba([255, 140, 0, 255]);
+const RED: Rgba<u8> = Rgba([220, 0, 0, 255]);
+
+fn confidence_color(confidence: f32) -> Rgba<u8> {
+    if confidence >= HIGH_CONFIDENCE {
+        GREEN
+    } else if confidence >= MEDIUM_CONFIDENCE {
+        ORANGE
+    } else {
+        RED
+    }
+}
+
+/// Draws boxes and confidence bars for `words` onto `image`.
+pub fn annotate(image: &mut RgbaImage, words: &[OcrWord]) {
+    for word in words {
+        let color = confidence_color(word.confidence);
+        draw_rect_outline(image, word.x, word.y, word.width, word.height, color);
+
+        let bar_width = (word.width as f32 * word.confidence.clamp(0.0, 100.0) / 100.0) as u32;
+        let bar_y = word.y.saturating_sub(BAR_HEIGHT + 1);
+        fill_rect(image, word.x, bar_y, bar_width.max(1), BAR_HEIGHT, color);
+    }
+}
+
+fn draw_rect_outline(image: &mut RgbaImage, x: u32, y: u32, w: u32, h: u32, color: Rgba<u8>) {
+    let t = BOX_THICKNESS;
+    fill_rect(image, x, y, w, t, color);
+    fill_rect(image, x, (y + h).saturating_sub(t), w, t, color);
+    fill_rect(image, x, y, t, h, color);
+    fill_rect(image, (x + w).saturating_sub(t), y, t, h, color);
+}
+
+/// Fills a rectangle, clipped to the image bounds.
+fn fill_rect(image: &mut RgbaImage, x: u32, y: u32, w: u32, h: u32, color: Rgba<u8>) {
+    let (width, height) = image.dimensions();
+    for py in y..(y + h).min(height) {
+        for px in x..(x + w).min(width) {
+            image.put_pixel(px, py, color);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Interactive screen-region capture via the platform screenshot tool.
+//!
+//! macOS uses `screencapture -i`; Linux tries the usual region tools for the session (grim +
+//! slurp on wlroots compositors, gnome-screenshot, spectacle, maim, scrot). The tool writes into a
+//! private temp file; cancelling the selection leaves it empty and returns `None`. Windows has no
+//! CLI region picker, so capture is not available there yet.
+
+use std::fs;
+use std::path::Path;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::process::Command;
+
+use image::DynamicImage;
+use tracing::debug;
+
+use crate::janitor;
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+const UNSUPPORTED: &str = "Screenshot capture is not available on this platform yet";
+
+/// Whether this platform has a region capture tool (Linux tools are found when capturing).
+pub fn check_supported() -> Result<(), String> {
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        Ok(())
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        Err(UNSUPPORTED.to_string())
+    }
+}
+
+/// Lets the user select a screen region and returns it, or `None` when the selection was
+/// cancelled.
+pub fn capture_screenshot() -> Result<Option<DynamicImage>, String> {
+    // The tool writes over the empty file; it is removed when `path` is dropped.
+    let path = janitor::temp_file("capture-", ".png")?.into_temp_path();
+
+    run_capture_tool(&path)?;
+    if !fs::metadata(&path).is_ok_and(|m| m.len() > 0) {
+        debug!("Screenshot: selection cancelled");
+        return Ok(None);
+    }
+    image::open(&path)
+        .map(Some)
+        .map_err(|e| format!("Failed to open screenshot: {e}"))
+}
+
+#[cfg(target_os = "macos")]
+fn run_capture_tool(out: &Path) -> Result<(), String> {
+    // -i: interactive selection, -x: no shutter sound. Exits non-zero when cancelled.
+    Command::new("screencapture")
+        .arg("-i")
+        .arg("-x")
+        .arg(out)
+        .status()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to run screencapture: {e}"))
+}
+
+#[cfg(target_os = "linux")]
+fn run_capture_tool(out: &Path) -> Result<(), String> {
+    use std::io::ErrorKind;
+
+    let wayland =
+        std::env::var("XDG_SESSION_TYPE").is_ok_and(|s| s.eq_ignore_ascii_case("wayland"));
+    if wayland {
+        match Command::new("slurp").output() {
+            Ok(selection) => {
+                let geometry = String::from_utf8_lossy(&selection.stdout)
+                    .trim()
+                    .to_string();
+                if !selection.status.success() || geometry.is_empty() {
+                    return Ok(());
+                }
+                return Command::new("grim")
+                    .arg("-g")
+                    .arg(&geometry)
+                    .arg(out)
+                    .status()
+                    .map(|_| ())
+                    .map_err(|e| format!("Failed to run grim: {e}"));
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to run slurp: {e}")),
+        }
+    }
+
+    let out = out.as_os_str();
+    let tools: [(&str, &[&str]); 4] = [
+        ("gnome-screenshot", &["-a", "-f"]),
+        ("spectacle", &["-b", "-n", "-r", "-o"]),
+        ("maim", &["-s"]),
+        // -o: write into the existing file instead of picking a new name next to it.
+        ("scrot", &["-s", "-o"]),
+    ];
+    for (tool, args) in tools {
+        match Command::new(tool).args(args).arg(out).status() {
+            Ok(status) => {
+                debug!(tool, ?status, "Screenshot: capture tool finished");
+                return Ok(());
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to run {tool}: {e}")),
+        }
+    }
+    Err(
+        "Screenshot capture needs one of: grim + slurp, gnome-screenshot, spectacle, maim, scrot"
+            .to_string(),
+    )
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn run_capture_tool(_out: &Path) -> Result<(), String> {
+    Err(UNSUPPORTED.to_string())
+}
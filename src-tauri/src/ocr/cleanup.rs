@@ -0,0 +1,53 @@
+//! Cleanup of recognized screen text before it is spoken.
+//!
+//! Screenshots pick up UI borders (read as `|`), icons (read as stray symbols), and short labels
+//! on their own lines. The speech segmenter joins every line of a paragraph, so a label followed
+//! by a capitalized line is split into its own paragraph instead of running into the next one.
+
+/// Characters that come from UI borders and separators rather than text.
+const BORDER_CHARS: &[char] = &['|', '¦', '_', '—', '─', '│', '[', ']'];
+
+/// Lines with fewer letters/digits than this are treated as icon or border noise.
+const MIN_ALPHANUMERIC: usize = 2;
+
+pub fn cleanup_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut previous: Option<&str> = None;
+    let mut blank_since_previous = false;
+    for line in text.lines() {
+        let line = line.trim_matches(|c: char| c.is_whitespace() || BORDER_CHARS.contains(&c));
+        if line.chars().filter(|c| c.is_alphanumeric()).count() < MIN_ALPHANUMERIC {
+            blank_since_previous |= line.is_empty();
+            continue;
+        }
+        if let Some(prev) = previous {
+            let ends_clause = prev.ends_with(['.', '!', '?', ':', ';', ',', '-']);
+            let starts_upper = line.chars().next().is_some_and(char::is_uppercase);
+            if blank_since_previous || (!ends_clause && starts_upper) {
+                out.push_str("\n\n");
+            } else {
+                out.push('\n');
+            }
+        }
+        out.push_str(line);
+        previous = Some(line);
+        blank_since_previous = false;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drops_noise_and_separates_labels() {
+        let text = "| File  Edit View |\n©\nThe quick brown fox jumps over\nthe lazy dog.\n\
+                    Next sentence here.\n\n\n\n— Footer —";
+        assert_eq!(
+            cleanup_text(text),
+            "File  Edit View\n\nThe quick brown fox jumps over\nthe lazy dog.\n\
+             Next sentence here.\n\nFooter"
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! OCR support: image preprocessing and text recognition for screen captures.
+//!
+//! Recognition is behind `extract_text_with_positions`; desktop platforms use the `tesseract` CLI.
+//! `read_screenshot` chains capture, recognition, cleanup, and speech for the hotkey, tray,
+//! and frontend.
+
+mod annotate;
+pub mod capture;
+pub mod cleanup;
+pub mod preprocess;
+#[cfg(desktop)]
+mod tesseract;
+
+use std::sync::mpsc;
+
+use tauri::State;
+use tracing::debug;
+
+use crate::commands_config::ConfigState;
+use crate::config::FullConfig;
+use crate::history;
+use crate::janitor;
+use crate::latency;
+use crate::paths;
+use crate::tasks::{TaskHandle, TaskKind, TaskManager};
+use crate::tts;
+use preprocess::PreprocessOptions;
+
+/// One recognized word with its bounding box (pixels, in the source image) and confidence (0-100).
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OcrWord {
+    pub text: String,
+    pub confidence: f32,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Recognized text (reading order, lines and paragraphs kept) and the words it is made of.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OcrText {
+    pub text: String,
+    pub words: Vec<OcrWord>,
+}
+
+/// Recognizes text in an (already preprocessed) image.
+fn recognize(image: &image::GrayImage) -> Result<OcrText, String> {
+    #[cfg(desktop)]
+    {
+        tesseract::recognize(image)
+    }
+    #[cfg(not(desktop))]
+    {
+        let _ = image;
+        Err("OCR is not available on this platform".to_string())
+    }
+}
+
+/// Checks that screenshots can be read here (a capture tool and tesseract), so the user is not
+/// asked to select a region that cannot be read.
+pub fn check_available() -> Result<(), String> {
+    capture::check_supported()?;
+    #[cfg(desktop)]
+    {
+        tesseract::binary().map(|_| ())
+    }
+    #[cfg(not(desktop))]
+    {
+        Err("OCR is not available on this platform".to_string())
+    }
+}
+
+/// Whether `read_screenshot` can work here (see `check_available`).
+pub fn is_available() -> bool {
+    check_available().is_ok()
+}
+
+/// Preprocesses `image`, recognizes its text, and maps word boxes back to `image` coordinates.
+pub fn extract_text_with_positions(
+    image: &image::DynamicImage,
+    options: PreprocessOptions,
+) -> Result<OcrText, String> {
+    let processed = preprocess::preprocess_for_ocr(image, options);
+    let factor = processed.width() as f32 / image.width().max(1) as f32;
+    let OcrText { text, words } = recognize(&processed)?;
+    let words = words.into_iter().map(|w| scale_word(w, factor)).collect();
+    Ok(OcrText { text, words })
+}
+
+/// Maps a box from the preprocessed image back to source-image coordinates.
+fn scale_word(word: OcrWord, factor: f32) -> OcrWord {
+    let scale = |v: u32| (v as f32 / factor).round() as u32;
+    OcrWord {
+        x: scale(word.x),
+        y: scale(word.y),
+        width: scale(word.width),
+        height: scale(word.height),
+        ..word
+    }
+}
+
+/// Captures a screen region, recognizes its text, optionally cleans it up (`ocr_cleanup`, on by
+/// default), and speaks it. Blocks until the selection is made and speech has started. Once a
+/// region is selected the work runs as a cancellable background task (`tasks`). Returns the
+/// spoken text, or `None` when the selection was cancelled.
+pub fn read_screenshot_impl<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    tasks: &TaskManager,
+    tts_tx: &tts::TtsState,
+    cfg: &FullConfig,
+    cleanup: Option<bool>,
+) -> Result<Option<String>, String> {
+    check_available()?;
+    let Some(image) = capture::capture_screenshot()? else {
+        return Ok(None);
+    };
+    let mut task = tasks.start(app, TaskKind::OcrCapture, "Reading screenshot");
+    let result = read_capture(&mut task, &image, tts_tx, cfg, cleanup);
+    task.finish(&result);
+    result.map(Some)
+}
+
+/// Recognizes and speaks a captured region; see `read_screenshot_impl`.
+fn read_capture(
+    task: &mut TaskHandle,
+    image: &image::DynamicImage,
+    tts_tx: &tts::TtsState,
+    cfg: &FullConfig,
+    cleanup: Option<bool>,
+) -> Result<String, String> {
+    // The read starts once the region is selected: capture is the OCR, not the user's selection.
+    let read_id = latency::begin("screenshot");
+    let _span = latency::span(read_id).entered();
+    task.set_progress(0.0, Some("Recognizing text".to_string()));
+    let recognized = extract_text_with_positions(image, PreprocessOptions::from_config(cfg))?;
+    let text = if cleanup.unwrap_or(cfg.ocr_cleanup.unwrap_or(true)) {
+        cleanup::cleanup_text(&recognized.text)
+    } else {
+        recognized.text
+    };
+    if text.trim().is_empty() {
+        return Err("No text found in the screenshot".to_string());
+    }
+    debug!(
+        words = recognized.words.len(),
+        len = text.len(),
+        "Read screenshot: text recognized"
+    );
+    if task.is_cancelled() {
+        return Err("Screenshot reading cancelled".to_string());
+    }
+
+    task.set_progress(0.8, Some("Starting speech".to_string()));
+    latency::captured();
+    history::begin("screenshot", &text);
+    let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+    let spoken = tts_tx
+        .send(tts::TtsRequest::Speak(
+            text.clone(),
+            tts::InputKind::Text,
+            None,
+            resp_tx,
+        ))
+        .map_err(|e| format!("Failed to send speak request: {e}"))
+        .and_then(|()| {
+            resp_rx
+                .recv()
+                .map_err(|_| "TTS worker disconnected".to_string())?
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = spoken {
+        history::abandon();
+        return Err(e);
+    }
+    Ok(text)
+}
+
+/// Lets the user select a screen region and reads its text aloud. Returns the spoken text, or
+/// `null` when the selection was cancelled. `cleanup` overrides the `ocr_cleanup` setting.
+#[tauri::command]
+pub async fn read_screenshot(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    tasks: State<'_, TaskManager>,
+    tts_state: State<'_, tts::TtsState>,
+    cleanup: Option<bool>,
+) -> Result<Option<String>, String> {
+    let cfg = config
+        .lock()
+        .map_err(|_| "Config lock poisoned".to_string())?
+        .clone();
+    let tasks = tasks.inner().clone();
+    let tts_tx = tts_state.inner().clone();
+    tokio::task::spawn_blocking(move || read_screenshot_impl(&app, &tasks, &tts_tx, &cfg, cleanup))
+        .await
+        .map_err(|e| format!("spawn_blocking: {e}"))?
+}
+
+/// Runs the OCR preprocessing pipeline on an image file and writes the result as PNG to the OCR
+/// cache dir. Returns the output path (lets users check what the recognizer will see).
+#[tauri::command]
+pub async fn ocr_preprocess_image(
+    state: State<'_, ConfigState>,
+    path: String,
+) -> Result<String, String> {
+    let options = {
+        let cfg = state
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?;
+        PreprocessOptions::from_config(&cfg)
+    };
+    tokio::task::spawn_blocking(move || {
+        let image = image::open(&path).map_err(|e| format!("Failed to open image {path}: {e}"))?;
+        let processed = preprocess::preprocess_for_ocr(&image, options);
+
+        let out_dir = paths::get_ocr_cache_dir()?;
+        let out_file = janitor::private_file_in(&out_dir, "preprocessed-", ".png")?;
+        processed
+            .save(out_file.path())
+            .map_err(|e| format!("Failed to save preprocessed image: {e}"))?;
+        let (_, out_path) = out_file
+            .keep()
+            .map_err(|e| format!("Failed to save preprocessed image: {e}"))?;
+        debug!(path = %out_path.display(), "Saved preprocessed OCR image");
+        Ok(out_path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking: {e}"))?
+}
+
+/// Recognizes the text in an image file (e.g. a captured screen region) so it can be read aloud.
+#[tauri::command]
+pub async fn ocr_extract_text(
+    state: State<'_, ConfigState>,
+    path: String,
+) -> Result<OcrText, String> {
+    let options = {
+        let cfg = state
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?;
+        PreprocessOptions::from_config(&cfg)
+    };
+    tokio::task::spawn_blocking(move || {
+        let image = image::open(&path).map_err(|e| format!("Failed to open image {path}: {e}"))?;
+        let result = extract_text_with_positions(&image, options)?;
+        debug!(words = result.words.len(), "OCR text extracted");
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking: {e}"))?
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OcrAnnotation {
+    /// Annotated PNG in the OCR cache dir.
+    pub image_path: String,
+    /// JSON file with the recognized words, next to the image.
+    pub words_path: String,
+    pub word_count: usize,
+}
+
+/// Debug: runs OCR on an image file, draws word boxes and confidences onto it, and saves the
+/// result (plus the words as JSON) to the OCR cache dir for bug reports.
+#[tauri::command]
+pub async fn ocr_annotate_image(
+    state: State<'_, ConfigState>,
+    path: String,
+) -> Result<OcrAnnotation, String> {
+    let options = {
+        let cfg = state
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?;
+        PreprocessOptions::from_config(&cfg)
+    };
+    tokio::task::spawn_blocking(move || {
+        let image = image::open(&path).map_err(|e| format!("Failed to open image {path}: {e}"))?;
+        let words = extract_text_with_positions(&image, options)?.words;
+
+        let mut annotated = image.to_rgba8();
+        annotate::annotate(&mut annotated, &words);
+
+        let out_dir = paths::get_ocr_cache_dir()?;
+        let image_file = janitor::private_file_in(&out_dir, "annotated-", ".png")?;
+        // Same random stem as the image, created just as exclusively.
+        let stem = image_file
+            .path()
+            .file_stem()
+            .map(|s| s.to_os_string())
+            .unwrap_or_default();
+        let words_file = tempfile::Builder::new()
+            .prefix(&stem)
+            .suffix(".json")
+            .rand_bytes(0)
+            .tempfile_in(&out_dir)
+            .map_err(|e| format!("Failed to create OCR words file: {e}"))?;
+        annotated
+            .save(image_file.path())
+            .map_err(|e| format!("Failed to save annotated image: {e}"))?;
+        let words_json = serde_json::to_string_pretty(&words)
+            .map_err(|e| format!("Failed to serialize OCR words: {e}"))?;
+        std::fs::write(words_file.path(), words_json)
+            .map_err(|e| format!("Failed to write OCR words: {e}"))?;
+        let (_, image_path) = image_file
+            .keep()
+            .map_err(|e| format!("Failed to save annotated image: {e}"))?;
+        let (_, words_path) = words_file
+            .keep()
+            .map_err(|e| format!("Failed to write OCR words: {e}"))?;
+        debug!(path = %image_path.display(), words = words.len(), "Saved OCR annotation");
+
+        Ok(OcrAnnotation {
+            image_path: image_path.to_string_lossy().to_string(),
+            words_path: words_path.to_string_lossy().to_string(),
+            word_count: words.len(),
+        })
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking: {e}"))?
+}
@@ -0,0 +1,132 @@
+//! Image preprocessing before OCR.
+//!
+//! Screenshots of low-contrast or dark-mode UIs recognize poorly as-is. The pipeline converts to
+//! grayscale, inverts light-on-dark images so text is dark on a light background, stretches the
+//! contrast to the full range (ignoring outlier pixels), and upscales small captures so glyphs
+//! are large enough for the recognizer.
+
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GrayImage};
+
+use crate::config::FullConfig;
+
+/// Captures whose shorter side is below this are upscaled (when enabled).
+const UPSCALE_BELOW_PX: u32 = 1000;
+const UPSCALE_FACTOR: u32 = 2;
+/// Fraction of darkest/brightest pixels ignored when finding the contrast range.
+const STRETCH_CLIP_FRACTION: f64 = 0.01;
+/// Mean luminance below which the image is treated as dark mode and inverted.
+const DARK_MODE_MEAN_LUMA: f64 = 110.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PreprocessOptions {
+    pub enabled: bool,
+    pub upscale: bool,
+}
+
+impl PreprocessOptions {
+    pub fn from_config(cfg: &FullConfig) -> Self {
+        Self {
+            enabled: cfg.ocr_preprocess.unwrap_or(true),
+            upscale: cfg.ocr_upscale.unwrap_or(true),
+        }
+    }
+}
+
+/// Runs the preprocessing pipeline. Returns the image unchanged (as grayscale) when disabled.
+pub fn preprocess_for_ocr(image: &DynamicImage, options: PreprocessOptions) -> GrayImage {
+    let mut gray = image.to_luma8();
+    if !options.enabled {
+        return gray;
+    }
+
+    if mean_luma(&gray) < DARK_MODE_MEAN_LUMA {
+        imageops::invert(&mut gray);
+    }
+    stretch_contrast(&mut gray);
+
+    let (width, height) = gray.dimensions();
+    if options.upscale && width.min(height) < UPSCALE_BELOW_PX {
+        gray = imageops::resize(
+            &gray,
+            width * UPSCALE_FACTOR,
+            height * UPSCALE_FACTOR,
+            FilterType::CatmullRom,
+        );
+    }
+    gray
+}
+
+fn mean_luma(image: &GrayImage) -> f64 {
+    let count = image.pixels().len();
+    if count == 0 {
+        return 255.0;
+    }
+    image.pixels().map(|p| f64::from(p.0[0])).sum::<f64>() / count as f64
+}
+
+/// Linearly maps the [low, high] percentile range to [0, 255].
+fn stretch_contrast(image: &mut GrayImage) {
+    let mut histogram = [0usize; 256];
+    for pixel in image.pixels() {
+        histogram[usize::from(pixel.0[0])] += 1;
+    }
+    let total = image.pixels().len();
+    if total == 0 {
+        return;
+    }
+    let clip = (total as f64 * STRETCH_CLIP_FRACTION) as usize;
+    let low = percentile_bound(histogram.iter().enumerate(), clip);
+    let high = percentile_bound(histogram.iter().enumerate().rev(), clip);
+    if high <= low {
+        return;
+    }
+
+    let range = f64::from(high - low);
+    for pixel in image.pixels_mut() {
+        let value = pixel.0[0].clamp(low, high);
+        pixel.0[0] = (f64::from(value - low) * 255.0 / range).round() as u8;
+    }
+}
+
+/// First luma value (in iteration order) after skipping `clip` pixels.
+fn percentile_bound<'a>(bins: impl Iterator<Item = (usize, &'a usize)>, clip: usize) -> u8 {
+    let mut seen = 0;
+    let mut last = 0;
+    for (value, count) in bins {
+        last = value;
+        seen += count;
+        if seen > clip {
+            break;
+        }
+    }
+    last as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    #[test]
+    fn test_stretch_contrast_expands_range() {
+        let mut image = GrayImage::from_fn(10, 10, |x, _| Luma([100 + (x as u8) * 5]));
+        stretch_contrast(&mut image);
+        let values: Vec<u8> = image.pixels().map(|p| p.0[0]).collect();
+        assert_eq!(*values.iter().min().unwrap(), 0);
+        assert_eq!(*values.iter().max().unwrap(), 255);
+    }
+
+    #[test]
+    fn test_preprocess_inverts_dark_mode() {
+        // Light text (200) on a dark background (30).
+        let image = GrayImage::from_fn(20, 20, |x, _| Luma([if x < 4 { 200 } else { 30 }]));
+        let options = PreprocessOptions {
+            enabled: true,
+            upscale: false,
+        };
+        let out = preprocess_for_ocr(&DynamicImage::ImageLuma8(image), options);
+        assert_eq!(out.get_pixel(10, 10).0[0], 255, "background becomes white");
+        assert_eq!(out.get_pixel(0, 0).0[0], 0, "text becomes black");
+    }
+}
@@ -0,0 +1,171 @@
+//! Text recognition via the `tesseract` CLI (TSV output gives per-word boxes), on every desktop
+//! platform. The binary is looked up on PATH and where the usual installers put it (Homebrew on
+//! macOS, whose paths GUI apps do not inherit; the UB Mannheim installer on Windows).
+
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use image::{GrayImage, ImageFormat};
+use tracing::debug;
+
+use super::{OcrText, OcrWord};
+
+/// TSV row level of a single word (1 = page, 2 = block, 3 = paragraph, 4 = line).
+const WORD_LEVEL: &str = "5";
+
+#[cfg(target_os = "linux")]
+const INSTALL_HINT: &str = "OCR needs tesseract installed (e.g. `sudo apt install tesseract-ocr`)";
+#[cfg(target_os = "macos")]
+const INSTALL_HINT: &str = "OCR needs tesseract installed (`brew install tesseract`)";
+#[cfg(target_os = "windows")]
+const INSTALL_HINT: &str =
+    "OCR needs Tesseract installed (https://github.com/UB-Mannheim/tesseract/wiki)";
+
+#[cfg(target_os = "windows")]
+const BINARY_NAME: &str = "tesseract.exe";
+#[cfg(not(target_os = "windows"))]
+const BINARY_NAME: &str = "tesseract";
+
+/// Install locations checked after PATH.
+fn known_locations() -> Vec<PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        vec![
+            PathBuf::from("/opt/homebrew/bin").join(BINARY_NAME),
+            PathBuf::from("/usr/local/bin").join(BINARY_NAME),
+        ]
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let program_files = ["ProgramFiles", "ProgramFiles(x86)"]
+            .into_iter()
+            .filter_map(std::env::var_os)
+            .map(PathBuf::from);
+        let user_programs =
+            std::env::var_os("LOCALAPPDATA").map(|dir| PathBuf::from(dir).join("Programs"));
+        program_files
+            .chain(user_programs)
+            .map(|dir| dir.join("Tesseract-OCR").join(BINARY_NAME))
+            .collect()
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        Vec::new()
+    }
+}
+
+/// The tesseract binary, or how to install it.
+pub(super) fn binary() -> Result<PathBuf, String> {
+    let on_path = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|dir| dir.join(BINARY_NAME));
+    on_path
+        .chain(known_locations())
+        .find(|p| p.is_file())
+        .ok_or_else(|| INSTALL_HINT.to_string())
+}
+
+pub(super) fn recognize(image: &GrayImage) -> Result<OcrText, String> {
+    let binary = binary()?;
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode image for OCR: {e}"))?;
+
+    let mut command = Command::new(&binary);
+    command
+        .args(["stdin", "stdout", "tsv"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to run tesseract: {e}"))?;
+    if let Some(ref mut stdin) = child.stdin {
+        stdin
+            .write_all(&png)
+            .map_err(|e| format!("Failed to write to tesseract: {e}"))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("tesseract failed: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("tesseract failed: {}", stderr.trim()));
+    }
+
+    let result = parse_tsv(&String::from_utf8_lossy(&output.stdout));
+    debug!(words = result.words.len(), "Tesseract: text recognized");
+    Ok(result)
+}
+
+/// Parses `tesseract ... tsv` output. Words on the same line are joined with spaces, lines with
+/// newlines, and paragraphs with a blank line.
+fn parse_tsv(tsv: &str) -> OcrText {
+    let mut words = Vec::new();
+    let mut text = String::new();
+    let mut last_line: Option<(&str, &str, &str)> = None;
+    for row in tsv.lines().skip(1) {
+        let cols: Vec<&str> = row.split('\t').collect();
+        if cols.len() < 12 || cols[0] != WORD_LEVEL {
+            continue;
+        }
+        let word = cols[11].trim();
+        if word.is_empty() {
+            continue;
+        }
+        let number = |i: usize| cols[i].parse::<u32>().unwrap_or(0);
+        let line = (cols[2], cols[3], cols[4]);
+        match last_line {
+            Some(last) if last == line => text.push(' '),
+            Some(last) if (last.0, last.1) == (line.0, line.1) => text.push('\n'),
+            Some(_) => text.push_str("\n\n"),
+            None => {}
+        }
+        last_line = Some(line);
+        text.push_str(word);
+        words.push(OcrWord {
+            text: word.to_string(),
+            confidence: cols[10].parse::<f32>().unwrap_or(0.0).max(0.0),
+            x: number(6),
+            y: number(7),
+            width: number(8),
+            height: number(9),
+        });
+    }
+    OcrText { text, words }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_words_and_layout_from_tsv() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+                   1\t1\t0\t0\t0\t0\t0\t0\t800\t600\t-1\t\n\
+                   5\t1\t1\t1\t1\t1\t10\t20\t50\t12\t96.5\tHello\n\
+                   5\t1\t1\t1\t1\t2\t65\t20\t60\t12\t91\tworld.\n\
+                   5\t1\t1\t1\t2\t1\t10\t40\t40\t12\t88\tNext\n\
+                   5\t1\t2\t1\t1\t1\t10\t90\t70\t12\t45\tFooter\n\
+                   5\t1\t2\t1\t1\t2\t90\t90\t5\t12\t-1\t \n";
+        let result = parse_tsv(tsv);
+        assert_eq!(result.text, "Hello world.\nNext\n\nFooter");
+        assert_eq!(result.words.len(), 4);
+        let hello = &result.words[0];
+        assert_eq!(
+            (hello.x, hello.y, hello.width, hello.height),
+            (10, 20, 50, 12)
+        );
+        assert_eq!(hello.confidence, 96.5);
+    }
+}
@@ -0,0 +1,82 @@
+//! Offline mode (`offline_mode`): no network calls, for metered or locked-down machines.
+//!
+//! While it is on, code that would reach the network checks `ensure_online` and fails right away
+//! with `OFFLINE_ERROR` instead of timing out: backend prompts and health probes, voice list
+//! refreshes (cached lists are still served, see `voices::cache`), voice downloads and `read_url`.
+//! Text cleanup runs locally only, and reads use a local provider instead of Microsoft or Polly
+//! (see `tts`); the custom voice server is local and stays available. Applied at startup and on
+//! every config save (see `commands_config::apply_runtime_settings`).
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::State;
+use tracing::info;
+
+use crate::commands_config::{self, ConfigState};
+use crate::config;
+
+/// Error returned by anything that needs the network while offline mode is on.
+pub const OFFLINE_ERROR: &str = "Offline mode is on. Turn it off in Settings to use the network.";
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Applies `offline_mode` from the config.
+pub fn configure(cfg: &config::FullConfig) {
+    OFFLINE.store(cfg.offline_mode.unwrap_or(false), Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Errs with `OFFLINE_ERROR` when offline mode is on. Call before any network request.
+pub fn ensure_online() -> Result<(), String> {
+    if is_offline() {
+        Err(OFFLINE_ERROR.to_string())
+    } else {
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub fn get_offline_mode() -> bool {
+    is_offline()
+}
+
+/// Turns offline mode on or off and saves it.
+#[tauri::command]
+pub fn set_offline_mode(
+    app: tauri::AppHandle,
+    state: State<'_, ConfigState>,
+    enabled: bool,
+) -> Result<(), String> {
+    let new_cfg = {
+        let mut cfg = state
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?;
+        cfg.offline_mode = Some(enabled);
+        cfg.clone()
+    };
+    commands_config::apply_runtime_settings(&new_cfg);
+    config::save_full_config(new_cfg)?;
+    info!(enabled, "Offline mode changed");
+    commands_config::notify_config_changed(&app);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_mode_blocks_network() {
+        let offline = config::FullConfig {
+            offline_mode: Some(true),
+            ..Default::default()
+        };
+        configure(&offline);
+        assert_eq!(ensure_online(), Err(OFFLINE_ERROR.to_string()));
+        configure(&config::FullConfig::default());
+        assert!(ensure_online().is_ok());
+    }
+}
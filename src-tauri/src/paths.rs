@@ -1,7 +1,76 @@
 //! Path utilities for cross-platform home directory resolution.
+//!
+//! All app-owned directories (config, cache, voices, app data) are resolved here, following the
+//! XDG base directories on Linux and the platform equivalents elsewhere (via `dirs`). In portable
+//! mode (a `portable` flag file next to the executable, or `--portable` on the command line)
+//! they all live under `insight-reader-data/` beside the executable instead of `$HOME`.
+//! `migrate_legacy_paths` moves data from the old hard-coded locations once at startup.
+//!
+//! When a named profile is active (see `profiles`), config and per-user data (history, reading
+//! positions) live in `<app data dir>/profiles/<name>` instead; voices, venv, and caches stay
+//! shared.
 
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+use tracing::{info, warn};
+
+/// Flag file placed next to the executable to enable portable mode.
+const PORTABLE_FLAG_FILE: &str = "portable";
+/// Command-line switch that enables portable mode.
+const PORTABLE_ARG: &str = "--portable";
+/// Data directory created next to the executable in portable mode.
+const PORTABLE_DATA_DIR_NAME: &str = "insight-reader-data";
+/// Directory name used under the platform config/cache/data directories.
+const APP_DIR_NAME: &str = "insight-reader";
+/// Directory name under the Linux data dir. `$XDG_DATA_HOME/insight-reader` is the install root
+/// of `install-linux.sh` (the AppImage), so app data stays out of it.
+#[cfg(target_os = "linux")]
+const DATA_DIR_NAME: &str = "insight-reader-data";
+#[cfg(not(target_os = "linux"))]
+const DATA_DIR_NAME: &str = APP_DIR_NAME;
+
+/// Bundle resource directory holding the onboarding voice (see `tauri.conf.json`).
+const ONBOARDING_VOICE_DIR_NAME: &str = "onboarding-voice";
+
+/// Directory under the app data dir that holds named profiles.
+const PROFILES_DIR_NAME: &str = "profiles";
+
+static PORTABLE_ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+static ACTIVE_PROFILE: RwLock<Option<String>> = RwLock::new(None);
+static RESOURCE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Portable data root (`<exe dir>/insight-reader-data`) when portable mode is enabled.
+/// Resolved once per process.
+pub fn portable_root() -> Option<&'static PathBuf> {
+    PORTABLE_ROOT.get_or_init(resolve_portable_root).as_ref()
+}
+
+/// Whether the app runs in portable mode.
+pub fn is_portable() -> bool {
+    portable_root().is_some()
+}
+
+fn resolve_portable_root() -> Option<PathBuf> {
+    let exe_dir = env::current_exe().ok()?.parent()?.to_path_buf();
+    let requested = env::args().skip(1).any(|arg| arg == PORTABLE_ARG)
+        || exe_dir.join(PORTABLE_FLAG_FILE).exists();
+    requested.then(|| exe_dir.join(PORTABLE_DATA_DIR_NAME))
+}
+
+/// Name of the active profile, or `None` for the default (unscoped) directories.
+pub fn active_profile() -> Option<String> {
+    ACTIVE_PROFILE.read().ok().and_then(|p| p.clone())
+}
+
+/// Sets the active profile; path lookups after this resolve into its directory.
+pub fn set_active_profile(name: Option<String>) {
+    if let Ok(mut active) = ACTIVE_PROFILE.write() {
+        *active = name;
+    }
+}
 
 /// Gets the user's home directory.
 ///
@@ -27,12 +96,244 @@ pub fn get_home_dir() -> Result<PathBuf, String> {
     Err("Could not determine home directory: HOME and USERPROFILE are not set".to_string())
 }
 
-/// Gets the base application data directory: `${HOME}/.insight-reader-2`
+/// Gets the base application data directory: `$XDG_DATA_HOME/insight-reader-data`,
+/// `~/Library/Application Support/insight-reader`, `%APPDATA%\insight-reader` (portable: the
+/// portable data root).
 pub fn get_app_data_dir() -> Result<PathBuf, String> {
-    Ok(get_home_dir()?.join(".insight-reader-2"))
+    if let Some(root) = portable_root() {
+        return Ok(root.clone());
+    }
+    dirs::data_dir()
+        .map(|dir| dir.join(DATA_DIR_NAME))
+        .ok_or_else(|| "No data directory available".to_string())
+}
+
+/// Gets the profiles directory: `<app data dir>/profiles`.
+pub fn get_profiles_dir() -> Result<PathBuf, String> {
+    Ok(get_app_data_dir()?.join(PROFILES_DIR_NAME))
+}
+
+/// Gets the directory for per-user data: the active profile's directory, or the app data dir.
+pub fn get_user_data_dir() -> Result<PathBuf, String> {
+    match active_profile() {
+        Some(name) => Ok(get_profiles_dir()?.join(name)),
+        None => get_app_data_dir(),
+    }
+}
+
+/// Gets the config directory: platform config dir + `insight-reader`
+/// (`$XDG_CONFIG_HOME/insight-reader` on Linux; portable: `<root>/config`; with a profile: the
+/// profile directory).
+pub fn get_config_dir() -> Result<PathBuf, String> {
+    if let Some(name) = active_profile() {
+        return Ok(get_profiles_dir()?.join(name));
+    }
+    if let Some(root) = portable_root() {
+        return Ok(root.join("config"));
+    }
+    dirs::config_dir()
+        .map(|dir| dir.join(APP_DIR_NAME))
+        .ok_or_else(|| "No config directory available".to_string())
+}
+
+/// Gets the cache directory: platform cache dir + `insight-reader`
+/// (`$XDG_CACHE_HOME/insight-reader` on Linux; portable: `<root>/cache`).
+pub fn get_cache_dir() -> Result<PathBuf, String> {
+    if let Some(root) = portable_root() {
+        return Ok(root.join("cache"));
+    }
+    dirs::cache_dir()
+        .map(|dir| dir.join(APP_DIR_NAME))
+        .ok_or_else(|| "No cache directory available".to_string())
+}
+
+/// Gets the Piper voices directory: `<app data dir>/voices`.
+pub fn get_voices_dir() -> Result<PathBuf, String> {
+    Ok(get_app_data_dir()?.join("voices"))
+}
+
+/// Gets the OCR result cache directory: `<cache dir>/ocr`.
+pub fn get_ocr_cache_dir() -> Result<PathBuf, String> {
+    Ok(get_cache_dir()?.join("ocr"))
+}
+
+/// Gets the app's private temp directory: `<cache dir>/tmp`. Used instead of the shared system
+/// temp dir (see `janitor::temp_file`).
+pub fn get_temp_dir() -> Result<PathBuf, String> {
+    Ok(get_cache_dir()?.join("tmp"))
+}
+
+/// Gets the synthesized audio cache directory: `<cache dir>/audio`.
+pub fn get_audio_cache_dir() -> Result<PathBuf, String> {
+    Ok(get_cache_dir()?.join("audio"))
+}
+
+/// Gets the log file directory: `<app data dir>/logs`.
+pub fn get_logs_dir() -> Result<PathBuf, String> {
+    Ok(get_app_data_dir()?.join("logs"))
+}
+
+/// Gets the reading history directory: `<user data dir>/history`.
+pub fn get_history_dir() -> Result<PathBuf, String> {
+    Ok(get_user_data_dir()?.join("history"))
 }
 
-/// Gets the Piper venv directory: `${HOME}/.insight-reader-2/venv`
+/// Gets the backend response cache directory: `<app data dir>/backend-cache`.
+pub fn get_backend_cache_dir() -> Result<PathBuf, String> {
+    Ok(get_app_data_dir()?.join("backend-cache"))
+}
+
+/// Records the app's bundle resource directory. Called once at startup, before the TTS worker
+/// starts.
+pub fn set_resource_dir(dir: PathBuf) {
+    let _ = RESOURCE_DIR.set(dir);
+}
+
+/// Gets the directory of the onboarding voice bundled with the app: `<resources>/onboarding-voice`.
+pub fn get_onboarding_voice_dir() -> Result<PathBuf, String> {
+    RESOURCE_DIR
+        .get()
+        .map(|dir| dir.join(ONBOARDING_VOICE_DIR_NAME))
+        .ok_or_else(|| "Resource directory not set".to_string())
+}
+
+/// Gets the Piper venv directory: `<app data dir>/venv`, or the legacy `${HOME}/.insight-reader-2/venv`
+/// when only that one exists (venvs are not relocatable, so they are never migrated).
 pub fn get_venv_dir() -> Result<PathBuf, String> {
-    Ok(get_app_data_dir()?.join("venv"))
+    let venv = get_app_data_dir()?.join("venv");
+    if !venv.exists() && portable_root().is_none() {
+        if let Ok(legacy) = legacy_app_data_dir().map(|dir| dir.join("venv")) {
+            if legacy.exists() {
+                return Ok(legacy);
+            }
+        }
+    }
+    Ok(venv)
+}
+
+/// Resolved app directories, returned by the `get_app_paths` diagnostic command.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AppPaths {
+    pub portable: bool,
+    pub profile: Option<String>,
+    pub config_dir: Option<String>,
+    pub cache_dir: Option<String>,
+    pub data_dir: Option<String>,
+    pub voices_dir: Option<String>,
+    pub venv_dir: Option<String>,
+    pub logs_dir: Option<String>,
+    pub history_dir: Option<String>,
+}
+
+pub fn app_paths() -> AppPaths {
+    let display = |path: Result<PathBuf, String>| path.ok().map(|p| p.display().to_string());
+    AppPaths {
+        portable: is_portable(),
+        profile: active_profile(),
+        config_dir: display(get_config_dir()),
+        cache_dir: display(get_cache_dir()),
+        data_dir: display(get_app_data_dir()),
+        voices_dir: display(get_voices_dir()),
+        venv_dir: display(get_venv_dir()),
+        logs_dir: display(get_logs_dir()),
+        history_dir: display(get_history_dir()),
+    }
+}
+
+// --- One-time migration from the pre-XDG layout ---
+
+/// Marker written to the data dir once legacy locations have been migrated.
+const MIGRATION_MARKER_FILE: &str = ".paths-migrated";
+
+/// `${HOME}/.insight-reader-2` (pre-XDG app data dir; still holds user-created venvs).
+fn legacy_app_data_dir() -> Result<PathBuf, String> {
+    Ok(get_home_dir()?.join(".insight-reader-2"))
+}
+
+/// Moves data from the legacy hard-coded locations (`~/.cache/insight-reader`,
+/// `~/.local/share/insight-reader/voices`, `~/.insight-reader-2`) into the unified directories.
+/// Runs once (marker file); skipped in portable mode. Targets that already exist are left alone.
+pub fn migrate_legacy_paths() {
+    if is_portable() {
+        return;
+    }
+    let Ok(data_dir) = get_app_data_dir() else {
+        return;
+    };
+    let marker = data_dir.join(MIGRATION_MARKER_FILE);
+    if marker.exists() {
+        return;
+    }
+    let Ok(home) = get_home_dir() else {
+        return;
+    };
+
+    let mut moves: Vec<(PathBuf, PathBuf)> = Vec::new();
+    if let Ok(cache_dir) = get_cache_dir() {
+        moves.push((home.join(".cache").join(APP_DIR_NAME), cache_dir));
+    }
+    if let Ok(voices_dir) = get_voices_dir() {
+        moves.push((
+            home.join(".local")
+                .join("share")
+                .join(APP_DIR_NAME)
+                .join("voices"),
+            voices_dir,
+        ));
+    }
+    if let Ok(legacy) = legacy_app_data_dir() {
+        if let Ok(entries) = fs::read_dir(&legacy) {
+            moves.extend(
+                entries
+                    .flatten()
+                    .filter(|entry| entry.file_name() != "venv")
+                    .map(|entry| (entry.path(), data_dir.join(entry.file_name()))),
+            );
+        }
+    }
+
+    for (from, to) in moves {
+        if from == to || !from.exists() || to.exists() {
+            continue;
+        }
+        match move_path(&from, &to) {
+            Ok(()) => info!(from = %from.display(), to = %to.display(), "Migrated legacy data"),
+            Err(e) => {
+                warn!(error = %e, from = %from.display(), to = %to.display(), "Failed to migrate legacy data")
+            }
+        }
+    }
+
+    if let Err(e) = fs::create_dir_all(&data_dir).and_then(|()| fs::write(&marker, "1")) {
+        warn!(error = %e, "Failed to write path migration marker");
+    }
+}
+
+/// Renames `from` to `to`, falling back to copy + delete across filesystems.
+fn move_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_recursive(from, to)?;
+    if from.is_dir() {
+        fs::remove_dir_all(from)
+    } else {
+        fs::remove_file(from)
+    }
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
 }
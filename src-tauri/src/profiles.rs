@@ -0,0 +1,263 @@
+//! Named user profiles for shared machines.
+//!
+//! Each profile has its own config, reading history, and reading positions in
+//! `<app data dir>/profiles/<name>`; voices and caches stay shared. Without a profile the app
+//! uses the regular directories, so single-user installs are unaffected. The profile is chosen at
+//! startup (`--profile <name>`, `INSIGHT_READER_PROFILE`, or the last one switched to) and can be
+//! changed at runtime from settings or the tray.
+
+use std::fs;
+
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+use tracing::{info, warn};
+
+use crate::commands_config::{self, ConfigState};
+use crate::config;
+#[cfg(desktop)]
+use crate::config_watch;
+use crate::paths;
+
+/// Event emitted after the active profile changes (payload: the profile name or `null`).
+pub const PROFILE_CHANGED_EVENT: &str = "profile-changed";
+
+/// Remembers the last selected profile, in the profiles directory.
+const ACTIVE_PROFILE_FILE: &str = "active-profile";
+const MAX_NAME_CHARS: usize = 32;
+/// Device names Windows reserves in every directory, also with an extension ("nul.txt").
+const WINDOWS_RESERVED_NAMES: [&str; 4] = ["CON", "PRN", "AUX", "NUL"];
+
+/// Whether Windows refuses `name` as a file or directory name, ignoring case and extension.
+fn is_windows_reserved(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+    {
+        return true;
+    }
+    let stem = stem.to_ascii_uppercase();
+    ["COM", "LPT"].iter().any(|device| {
+        stem.strip_prefix(device)
+            .is_some_and(|n| matches!(n.as_bytes(), [b'1'..=b'9']))
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileList {
+    /// `None` when the default (unscoped) directories are in use.
+    pub active: Option<String>,
+    pub profiles: Vec<String>,
+}
+
+/// Checks that `name` is usable as a directory name on every platform and returns it trimmed.
+fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name is empty".to_string());
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(format!(
+            "Profile name is longer than {MAX_NAME_CHARS} characters"
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '_' | '-'))
+    {
+        return Err(
+            "Profile names may only contain letters, digits, spaces, '_' and '-'".to_string(),
+        );
+    }
+    if is_windows_reserved(name) {
+        return Err(format!("\"{name}\" is reserved by Windows"));
+    }
+    Ok(name.to_string())
+}
+
+/// Profile name from `--profile <name>` / `--profile=<name>` in `args`.
+fn profile_from_args(args: impl IntoIterator<Item = String>) -> Option<String> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next();
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+/// Existing profile names, sorted.
+pub fn list() -> Vec<String> {
+    let Ok(entries) = paths::get_profiles_dir().and_then(|dir| {
+        fs::read_dir(dir).map_err(|e| format!("Failed to read profiles directory: {e}"))
+    }) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| validate_name(name).as_deref() == Ok(name.as_str()))
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names
+}
+
+fn create(name: &str) -> Result<String, String> {
+    let name = validate_name(name)?;
+    let dir = paths::get_profiles_dir()?.join(&name);
+    if dir.exists() {
+        return Err(format!("Profile \"{name}\" already exists"));
+    }
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create profile: {e}"))?;
+    info!(profile = %name, "Profile created");
+    Ok(name)
+}
+
+fn persist_active(name: Option<&str>) -> Result<(), String> {
+    let path = paths::get_profiles_dir()?.join(ACTIVE_PROFILE_FILE);
+    match name {
+        Some(name) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create profiles directory: {e}"))?;
+            }
+            fs::write(&path, name).map_err(|e| format!("Failed to save active profile: {e}"))
+        }
+        None if path.exists() => {
+            fs::remove_file(&path).map_err(|e| format!("Failed to save active profile: {e}"))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Selects the startup profile. Call before the config is loaded.
+///
+/// A profile named on the command line or in the environment is created if missing; a remembered
+/// one that no longer exists falls back to the default directories.
+pub fn init() {
+    let requested = profile_from_args(std::env::args().skip(1))
+        .or_else(|| std::env::var("INSIGHT_READER_PROFILE").ok())
+        .filter(|name| !name.trim().is_empty());
+    let name = match requested {
+        Some(name) => match validate_name(&name) {
+            Ok(name) => {
+                if !list().contains(&name) {
+                    if let Err(e) = create(&name) {
+                        warn!(error = %e, "Startup profile could not be created");
+                        return;
+                    }
+                }
+                Some(name)
+            }
+            Err(e) => {
+                warn!(error = %e, profile = %name, "Ignoring invalid startup profile");
+                return;
+            }
+        },
+        None => paths::get_profiles_dir()
+            .ok()
+            .and_then(|dir| fs::read_to_string(dir.join(ACTIVE_PROFILE_FILE)).ok())
+            .map(|name| name.trim().to_string())
+            .filter(|name| list().contains(name)),
+    };
+    if let Some(name) = &name {
+        info!(profile = %name, "Using profile");
+    }
+    paths::set_active_profile(name);
+}
+
+/// Activates `name` (`None` = default directories), persists the choice, and reloads everything
+/// that was read from the previous profile's config. Shared by the command and the tray.
+pub fn switch_profile_impl<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    name: Option<String>,
+) -> Result<(), String> {
+    let name = name.map(|name| validate_name(&name)).transpose()?;
+    if let Some(name) = &name {
+        if !list().contains(name) {
+            return Err(format!("Profile \"{name}\" does not exist"));
+        }
+    }
+    if name == paths::active_profile() {
+        return Ok(());
+    }
+    paths::set_active_profile(name.clone());
+    persist_active(name.as_deref())?;
+
+    config::migrate_config_file();
+    let cfg = config::load_full_config().unwrap_or_default();
+    commands_config::apply_runtime_settings(&cfg);
+    if let Some(state) = app.try_state::<ConfigState>() {
+        let mut shared = state
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?;
+        *shared = cfg;
+    }
+    commands_config::notify_config_changed(app);
+    #[cfg(desktop)]
+    config_watch::retarget();
+
+    info!(profile = ?name, "Switched profile");
+    let _ = app.emit(PROFILE_CHANGED_EVENT, &name);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_profiles() -> ProfileList {
+    ProfileList {
+        active: paths::active_profile(),
+        profiles: list(),
+    }
+}
+
+/// Creates an empty profile (it starts with default settings). Returns the stored name.
+#[tauri::command]
+pub fn create_profile(name: String) -> Result<String, String> {
+    create(&name)
+}
+
+/// Switches to the profile `name`, or back to the default directories when `name` is null.
+#[tauri::command]
+pub fn switch_profile(app: tauri::AppHandle, name: Option<String>) -> Result<(), String> {
+    switch_profile_impl(&app, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validates_names_and_reads_the_profile_arg() {
+        assert_eq!(validate_name("  Ana Maria "), Ok("Ana Maria".to_string()));
+        assert_eq!(validate_name("kid_2-b"), Ok("kid_2-b".to_string()));
+        assert!(validate_name("").is_err());
+        assert!(validate_name("../config").is_err());
+        assert!(validate_name(&"x".repeat(MAX_NAME_CHARS + 1)).is_err());
+
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            profile_from_args(args(&["--minimized", "--profile", "Sam"])),
+            Some("Sam".to_string())
+        );
+        assert_eq!(
+            profile_from_args(args(&["--profile=Sam"])),
+            Some("Sam".to_string())
+        );
+        assert_eq!(profile_from_args(args(&["--profile"])), None);
+    }
+
+    #[test]
+    fn test_rejects_windows_reserved_names() {
+        for name in ["CON", "nul", "Aux", "com1", "LPT9", "nul.txt", "Con .log"] {
+            assert!(is_windows_reserved(name), "{name}");
+        }
+        for name in ["Connie", "com", "COM10", "lpt0", "nullable"] {
+            assert!(!is_windows_reserved(name), "{name}");
+        }
+        assert!(validate_name("Nul").is_err());
+    }
+}
@@ -0,0 +1,251 @@
+//! Reading statistics for a stats page: words read aloud, listening time, reading sessions per
+//! day and the same per provider.
+//!
+//! The TTS worker records each read when it ends, whether it finished, was stopped or was replaced
+//! by the next one (`record`, from `tts::timeline`), with the words spoken and how far playback
+//! got. Totals live in `reading_stats.json` in the user data dir (per profile, see `paths`), keyed
+//! by UTC day. They are kept in a `store::JsonStore`, which follows profile switches, and written
+//! at most every `SAVE_INTERVAL`; shutdown calls `flush`. Reads less
+//! than `SESSION_GAP_MS` apart belong to one session. `get_reading_stats` sums them over a range.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::paths;
+use crate::store::JsonStore;
+use crate::util::{civil_date, unix_millis_now};
+
+const STATS_FILE_NAME: &str = "reading_stats.json";
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+/// A read starting this long after the previous one ended starts a new session.
+const SESSION_GAP_MS: u64 = 15 * 60 * 1000;
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Totals of one provider.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProviderStats {
+    pub reads: u64,
+    pub words: u64,
+    pub listening_ms: u64,
+}
+
+impl ProviderStats {
+    fn add(&mut self, other: &ProviderStats) {
+        self.reads += other.reads;
+        self.words += other.words;
+        self.listening_ms += other.listening_ms;
+    }
+}
+
+/// Totals of one day, or of a range in `ReadingStats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DayStats {
+    pub reads: u64,
+    pub sessions: u64,
+    pub words: u64,
+    pub listening_ms: u64,
+    /// By provider name (`piper`, `polly`, ...).
+    pub providers: BTreeMap<String, ProviderStats>,
+}
+
+impl DayStats {
+    fn add(&mut self, other: &DayStats) {
+        self.reads += other.reads;
+        self.sessions += other.sessions;
+        self.words += other.words;
+        self.listening_ms += other.listening_ms;
+        for (provider, stats) in &other.providers {
+            self.providers
+                .entry(provider.clone())
+                .or_default()
+                .add(stats);
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct StatsFile {
+    /// By day (`YYYY-MM-DD`, UTC).
+    days: BTreeMap<String, DayStats>,
+    /// Unix ms when the last recorded read ended.
+    last_read_at: Option<u64>,
+}
+
+impl StatsFile {
+    fn add_read(&mut self, day: String, provider: &str, words: u64, listening_ms: u64, now: u64) {
+        let new_session = !self.days.contains_key(&day)
+            || self
+                .last_read_at
+                .is_none_or(|last| now.saturating_sub(last) >= SESSION_GAP_MS);
+        self.last_read_at = Some(now);
+        let read = ProviderStats {
+            reads: 1,
+            words,
+            listening_ms,
+        };
+        self.days.entry(day).or_default().add(&DayStats {
+            reads: 1,
+            sessions: u64::from(new_session),
+            words,
+            listening_ms,
+            providers: BTreeMap::from([(provider.to_string(), read)]),
+        });
+    }
+}
+
+static STATS: JsonStore<StatsFile> = JsonStore::new("reading stats", stats_path, SAVE_INTERVAL);
+
+fn stats_path() -> Result<PathBuf, String> {
+    Ok(paths::get_user_data_dir()?.join(STATS_FILE_NAME))
+}
+
+/// Writes stats not saved yet.
+pub fn flush() {
+    STATS.flush();
+}
+
+/// `YYYY-MM-DD` (UTC) of a Unix time.
+fn day_of(unix_secs: u64) -> String {
+    let (year, month, day) = civil_date(unix_secs);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Records a read that just ended: `words` spoken with `provider`, playback `listening_ms` long.
+pub fn record(provider: &str, words: usize, listening_ms: u64) {
+    if words == 0 {
+        return;
+    }
+    let now_ms = unix_millis_now();
+    let result = STATS.update(|stats| {
+        stats.add_read(
+            day_of(now_ms / 1000),
+            provider,
+            words as u64,
+            listening_ms,
+            now_ms,
+        );
+    });
+    if let Err(e) = result {
+        warn!(error = %e, "Failed to record read in the reading stats");
+        return;
+    }
+    debug!(
+        provider,
+        words, listening_ms, "Recorded read in the reading stats"
+    );
+}
+
+/// Period `get_reading_stats` sums over, ending today.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsRange {
+    Today,
+    #[default]
+    Week,
+    Month,
+    Year,
+    All,
+}
+
+impl StatsRange {
+    /// Days covered, today included; `None` for all of them.
+    fn days(self) -> Option<u64> {
+        match self {
+            Self::Today => Some(1),
+            Self::Week => Some(7),
+            Self::Month => Some(30),
+            Self::Year => Some(365),
+            Self::All => None,
+        }
+    }
+}
+
+/// A day of `ReadingStats::days`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DayEntry {
+    /// `YYYY-MM-DD` (UTC).
+    pub day: String,
+    #[serde(flatten)]
+    pub stats: DayStats,
+}
+
+/// Returned by `get_reading_stats`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadingStats {
+    /// First day of the range; `None` for `all`.
+    pub from: Option<String>,
+    pub to: String,
+    /// Totals over the range, with the per-provider breakdown.
+    pub total: DayStats,
+    /// Days with reads, oldest first, for charts.
+    pub days: Vec<DayEntry>,
+}
+
+fn aggregate(file: &StatsFile, range: StatsRange, now_secs: u64) -> ReadingStats {
+    let from = range
+        .days()
+        .map(|days| day_of(now_secs.saturating_sub((days - 1) * SECS_PER_DAY)));
+    let days: Vec<DayEntry> = file
+        .days
+        .iter()
+        .filter(|(day, _)| from.as_ref().is_none_or(|from| *day >= from))
+        .map(|(day, stats)| DayEntry {
+            day: day.clone(),
+            stats: stats.clone(),
+        })
+        .collect();
+    let mut total = DayStats::default();
+    days.iter().for_each(|entry| total.add(&entry.stats));
+    ReadingStats {
+        from,
+        to: day_of(now_secs),
+        total,
+        days,
+    }
+}
+
+// --- Commands ---
+
+/// Words read aloud, listening time, sessions and reads over `range` (default: the last week),
+/// in total, per provider and per day.
+#[tauri::command]
+pub fn get_reading_stats(range: Option<StatsRange>) -> Result<ReadingStats, String> {
+    let now = unix_millis_now() / 1000;
+    STATS.read(|stats| aggregate(stats, range.unwrap_or_default(), now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_are_grouped_into_sessions_and_summed_over_the_range() {
+        let day = 1_709_251_200; // 2024-03-01
+        let mut file = StatsFile::default();
+        file.add_read(day_of(day), "piper", 100, 30_000, day * 1000);
+        file.add_read(day_of(day), "polly", 50, 15_000, (day + 60) * 1000);
+        file.add_read(day_of(day), "piper", 10, 3_000, (day + 3600) * 1000);
+        let yesterday = day - SECS_PER_DAY;
+        file.add_read(day_of(yesterday), "piper", 20, 6_000, yesterday * 1000);
+
+        let today = aggregate(&file, StatsRange::Today, day + 7200);
+        assert_eq!(today.from.as_deref(), Some("2024-03-01"));
+        assert_eq!(today.total.reads, 3);
+        assert_eq!(today.total.sessions, 2);
+        assert_eq!(today.total.words, 160);
+        assert_eq!(today.total.providers["piper"].words, 110);
+        assert_eq!(today.total.providers["polly"].listening_ms, 15_000);
+
+        let week = aggregate(&file, StatsRange::Week, day + 7200);
+        assert_eq!(week.days.len(), 2);
+        assert_eq!(week.days[0].day, "2024-02-29");
+        assert_eq!(week.total.sessions, 3);
+    }
+}
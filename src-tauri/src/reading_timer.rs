@@ -0,0 +1,239 @@
+//! Reading timer for enforced breaks (Pomodoro style): after the chosen number of minutes,
+//! playback is paused and `reading-break` is emitted.
+//!
+//! `start_reading_timer` replaces any running timer; each timer has a thread that sleeps until it
+//! is due and gives up once it is cancelled or replaced. With `announce`, a short break phrase in
+//! the UI language is spoken too. Speaking it ends the paused read, which continues from the last
+//! spoken word through the reading history (`history_resume`, with `history_enabled` on).
+
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter, State};
+use tracing::{debug, info};
+
+use crate::history;
+use crate::i18n::{self, SpokenText};
+use crate::tts;
+
+/// Event emitted when a reading timer ends.
+pub const READING_BREAK_EVENT: &str = "reading-break";
+
+const MAX_MINUTES: u32 = 240;
+/// Longest sleep of a timer thread between checks for cancellation.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReadingBreak {
+    /// Length of the timer that ended.
+    pub minutes: u32,
+    /// True when playback was paused (false when nothing was playing).
+    pub paused: bool,
+    /// True when the break announcement was spoken.
+    pub announced: bool,
+}
+
+/// A running timer, as returned by `start_reading_timer` and `get_reading_timer`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReadingTimer {
+    pub minutes: u32,
+    pub remaining_secs: u64,
+    pub announce: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ActiveTimer {
+    id: u64,
+    minutes: u32,
+    announce: bool,
+    due: Instant,
+}
+
+impl ActiveTimer {
+    fn status(&self, now: Instant) -> ReadingTimer {
+        ReadingTimer {
+            minutes: self.minutes,
+            remaining_secs: self.due.saturating_duration_since(now).as_secs(),
+            announce: self.announce,
+        }
+    }
+}
+
+/// The running timer; a new one supersedes it, so threads of older timers find it gone.
+#[derive(Default)]
+struct Timers {
+    current: Option<ActiveTimer>,
+    next_id: u64,
+}
+
+impl Timers {
+    fn start(&mut self, minutes: u32, announce: bool, now: Instant) -> ActiveTimer {
+        self.next_id += 1;
+        let timer = ActiveTimer {
+            id: self.next_id,
+            minutes,
+            announce,
+            due: now + Duration::from_secs(u64::from(minutes) * 60),
+        };
+        self.current = Some(timer);
+        timer
+    }
+
+    /// Whether timer `id` is still the running one.
+    fn is_current(&self, id: u64) -> bool {
+        self.current.is_some_and(|timer| timer.id == id)
+    }
+
+    /// Ends timer `id` when it is still running and due; true when it should fire.
+    fn take_due(&mut self, id: u64, now: Instant) -> bool {
+        let due = self
+            .current
+            .is_some_and(|timer| timer.id == id && timer.due <= now);
+        if due {
+            self.current = None;
+        }
+        due
+    }
+}
+
+static TIMERS: Mutex<Timers> = Mutex::new(Timers {
+    current: None,
+    next_id: 0,
+});
+
+/// Pauses playback if it is playing; true when it was paused.
+fn pause(tts_tx: &tts::TtsState) -> bool {
+    if tts::playback_status(tts_tx) != Some((true, false)) {
+        return false;
+    }
+    let (resp_tx, resp_rx) = mpsc::sync_channel(1);
+    if tts_tx.send(tts::TtsRequest::TogglePause(resp_tx)).is_err() {
+        return false;
+    }
+    match resp_rx.recv() {
+        Ok(Ok(paused)) => paused,
+        Ok(Err(e)) => {
+            debug!(error = %e, "Reading timer could not pause playback");
+            false
+        }
+        Err(_) => false,
+    }
+}
+
+/// Speaks the break phrase; true when speech started.
+fn announce_break(tts_tx: &tts::TtsState) -> bool {
+    let text = i18n::text(SpokenText::BreakAnnouncement, i18n::configured_language());
+    history::detach();
+    let (resp_tx, resp_rx) = mpsc::sync_channel(1);
+    let request = tts::TtsRequest::Speak(text.to_string(), tts::InputKind::Text, None, resp_tx);
+    if tts_tx.send(request).is_err() {
+        return false;
+    }
+    match resp_rx.recv() {
+        Ok(Ok(())) => true,
+        Ok(Err(e)) => {
+            debug!(error = %e, "Break announcement failed");
+            false
+        }
+        Err(_) => false,
+    }
+}
+
+/// Sleeps until `timer` is due, then pauses playback (and announces the break) unless the timer
+/// was cancelled or replaced in the meantime.
+fn run(app: AppHandle, tts_tx: tts::TtsState, timer: ActiveTimer) {
+    loop {
+        let now = Instant::now();
+        let Ok(mut timers) = TIMERS.lock() else {
+            return;
+        };
+        if timers.take_due(timer.id, now) {
+            break;
+        }
+        if !timers.is_current(timer.id) {
+            return;
+        }
+        drop(timers);
+        std::thread::sleep(timer.due.saturating_duration_since(now).min(CHECK_INTERVAL));
+    }
+    let paused = pause(&tts_tx);
+    let announced = timer.announce && announce_break(&tts_tx);
+    info!(
+        minutes = timer.minutes,
+        paused, announced, "Reading timer ended"
+    );
+    let _ = app.emit(
+        READING_BREAK_EVENT,
+        ReadingBreak {
+            minutes: timer.minutes,
+            paused,
+            announced,
+        },
+    );
+}
+
+// --- Commands ---
+
+/// Starts a reading timer of `minutes` (1 to 240), replacing a running one. When it ends,
+/// playback is paused, `reading-break` is emitted and, with `announce`, a break phrase is spoken.
+#[tauri::command]
+pub fn start_reading_timer(
+    app: AppHandle,
+    state: State<tts::TtsState>,
+    minutes: u32,
+    announce: Option<bool>,
+) -> Result<ReadingTimer, String> {
+    if !(1..=MAX_MINUTES).contains(&minutes) {
+        return Err(format!("Timer must be between 1 and {MAX_MINUTES} minutes"));
+    }
+    let now = Instant::now();
+    let timer = TIMERS
+        .lock()
+        .map_err(|_| "Reading timer unavailable".to_string())?
+        .start(minutes, announce.unwrap_or(false), now);
+    let tts_tx = state.inner().clone();
+    std::thread::spawn(move || run(app, tts_tx, timer));
+    info!(minutes, "Reading timer started");
+    Ok(timer.status(now))
+}
+
+/// Cancels the running reading timer; false when none was running.
+#[tauri::command]
+pub fn cancel_reading_timer() -> Result<bool, String> {
+    let mut timers = TIMERS
+        .lock()
+        .map_err(|_| "Reading timer unavailable".to_string())?;
+    Ok(timers.current.take().is_some())
+}
+
+/// The running reading timer and the time left, if any.
+#[tauri::command]
+pub fn get_reading_timer() -> Result<Option<ReadingTimer>, String> {
+    let timers = TIMERS
+        .lock()
+        .map_err(|_| "Reading timer unavailable".to_string())?;
+    Ok(timers.current.map(|timer| timer.status(Instant::now())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_the_latest_timer_fires() {
+        let mut timers = Timers::default();
+        let now = Instant::now();
+        let first = timers.start(25, false, now);
+        let second = timers.start(5, true, now);
+        let later = now + Duration::from_secs(30 * 60);
+        assert!(!timers.take_due(first.id, later));
+        assert!(!timers.take_due(second.id, now));
+        assert_eq!(
+            timers.current.map(|t| t.status(now).remaining_secs),
+            Some(300)
+        );
+        assert!(timers.take_due(second.id, later));
+        assert!(timers.current.is_none());
+    }
+}
@@ -0,0 +1,130 @@
+//! Cloud credentials and API keys in the OS keychain (macOS Keychain, Windows Credential Manager,
+//! Secret Service on Linux), so they need not sit in environment variables or plain-text files.
+//!
+//! Only the names in `SECRET_NAMES` can be stored, under the `insight-reader` service. The
+//! commands let the settings UI store, delete and check for a secret; a stored value is never
+//! sent back to the frontend. Providers read them when they are created (`tts::polly`, `llm`).
+
+use tracing::{debug, info, warn};
+
+/// Keychain service the secrets are stored under.
+const SERVICE: &str = "insight-reader";
+
+pub const AWS_ACCESS_KEY_ID: &str = "aws_access_key_id";
+pub const AWS_SECRET_ACCESS_KEY: &str = "aws_secret_access_key";
+pub const AWS_SESSION_TOKEN: &str = "aws_session_token";
+pub const OPENAI_API_KEY: &str = "openai_api_key";
+pub const ANTHROPIC_API_KEY: &str = "anthropic_api_key";
+
+/// Secrets that can be stored.
+const SECRET_NAMES: [&str; 5] = [
+    AWS_ACCESS_KEY_ID,
+    AWS_SECRET_ACCESS_KEY,
+    AWS_SESSION_TOKEN,
+    OPENAI_API_KEY,
+    ANTHROPIC_API_KEY,
+];
+
+fn validate_name(name: &str) -> Result<(), String> {
+    if SECRET_NAMES.contains(&name) {
+        Ok(())
+    } else {
+        Err(format!("Unknown secret: {name}"))
+    }
+}
+
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    validate_name(name)?;
+    keyring::Entry::new(SERVICE, name).map_err(|e| format!("Keychain unavailable: {e}"))
+}
+
+/// The stored secret `name`, or `None` when there is none.
+pub fn get(name: &str) -> Result<Option<String>, String> {
+    match entry(name)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read {name} from the keychain: {e}")),
+    }
+}
+
+/// Like `get`, but a keychain error is logged and treated as no secret.
+pub fn get_or_none(name: &str) -> Option<String> {
+    get(name).unwrap_or_else(|e| {
+        warn!(error = %e, "Keychain lookup failed");
+        None
+    })
+}
+
+/// AWS credentials from the keychain, when both the access key id and the secret key are stored.
+pub fn aws_credentials() -> Option<aws_sdk_polly::config::Credentials> {
+    let access_key_id = get_or_none(AWS_ACCESS_KEY_ID)?;
+    let secret_access_key = get_or_none(AWS_SECRET_ACCESS_KEY)?;
+    Some(aws_sdk_polly::config::Credentials::new(
+        access_key_id,
+        secret_access_key,
+        get_or_none(AWS_SESSION_TOKEN),
+        None,
+        "keychain",
+    ))
+}
+
+fn set(name: &str, value: &str) -> Result<(), String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(format!("{name} must not be empty"));
+    }
+    entry(name)?
+        .set_password(value)
+        .map_err(|e| format!("Failed to store {name} in the keychain: {e}"))?;
+    info!(name = %name, "Stored secret in the keychain");
+    Ok(())
+}
+
+fn delete(name: &str) -> Result<(), String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) => {
+            info!(name = %name, "Deleted secret from the keychain");
+            Ok(())
+        }
+        Err(keyring::Error::NoEntry) => {
+            debug!(name = %name, "No secret to delete");
+            Ok(())
+        }
+        Err(e) => Err(format!("Failed to delete {name} from the keychain: {e}")),
+    }
+}
+
+/// Stores `value` as the secret `name`, replacing any previous value.
+#[tauri::command]
+pub fn set_secret(name: String, value: String) -> Result<(), String> {
+    set(&name, &value)
+}
+
+/// Whether the secret `name` is stored. The value itself is never returned.
+#[tauri::command]
+pub fn has_secret(name: String) -> Result<bool, String> {
+    Ok(get(&name)?.is_some())
+}
+
+/// Removes the secret `name`; succeeds when it was not stored.
+#[tauri::command]
+pub fn delete_secret(name: String) -> Result<(), String> {
+    delete(&name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_known_secrets_are_accepted() {
+        assert!(validate_name(AWS_ACCESS_KEY_ID).is_ok());
+        assert!(validate_name(AWS_SESSION_TOKEN).is_ok());
+        assert_eq!(
+            validate_name("github_token"),
+            Err("Unknown secret: github_token".to_string())
+        );
+        assert!(set_secret("github_token".into(), "x".into()).is_err());
+        assert!(set_secret(AWS_ACCESS_KEY_ID.into(), "  ".into()).is_err());
+    }
+}
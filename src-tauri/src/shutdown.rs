@@ -0,0 +1,116 @@
+//! Orchestrated application shutdown.
+//!
+//! Quitting (tray "Quit", or an exit request from the OS / last window) goes through
+//! `request_shutdown`, which runs on a background thread so the UI stays responsive:
+//! 1. cancel registered background tasks and wait (bounded) for them to clean up partial files;
+//! 2. stop the TTS worker and wait (bounded) until it has recorded the read that was playing;
+//! 3. save the position of the current read (see `history`), the TTS usage counts (`usage`) and
+//!    playback settings not saved yet (`commands_tts::flush_playback_settings`), and restore other
+//!    apps' volumes if they are lowered (`ducking`);
+//! 4. exit the app.
+//!
+//! Exit requests that arrive before the sequence has finished are intercepted with
+//! `intercept_exit` so they cannot skip the cleanup.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::{Duration, Instant};
+
+use tauri::Manager;
+use tracing::{info, warn};
+
+use crate::commands_config::ConfigState;
+use crate::commands_tts;
+use crate::ducking;
+use crate::history;
+use crate::reading_stats;
+use crate::tasks::TaskManager;
+use crate::tts;
+use crate::usage;
+
+/// How long to wait for cancelled tasks to finish their cleanup before exiting anyway.
+const TASK_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+const TASK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// How long to wait for the TTS worker to stop playback and record the current read.
+const TTS_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+const STATE_RUNNING: u8 = 0;
+const STATE_SHUTTING_DOWN: u8 = 1;
+const STATE_DONE: u8 = 2;
+
+static SHUTDOWN_STATE: AtomicU8 = AtomicU8::new(STATE_RUNNING);
+
+/// Starts the shutdown sequence (no-op if it is already running). Exits the app when done.
+pub fn request_shutdown<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    if SHUTDOWN_STATE
+        .compare_exchange(
+            STATE_RUNNING,
+            STATE_SHUTTING_DOWN,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        )
+        .is_err()
+    {
+        return;
+    }
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        info!("Shutdown requested");
+        wait_for_tasks(&app);
+
+        // The worker records the read that was playing when it stops; wait for that before
+        // flushing.
+        if let Some(state) = app.try_state::<tts::TtsState>() {
+            if !tts::shutdown_worker(state.inner(), TTS_SHUTDOWN_TIMEOUT) {
+                warn!("TTS worker did not stop before shutdown timeout");
+            }
+        }
+        history::flush();
+        usage::flush();
+        reading_stats::flush();
+        if let Some(config) = app.try_state::<ConfigState>() {
+            commands_tts::flush_playback_settings(config.inner());
+        }
+        ducking::restore();
+
+        SHUTDOWN_STATE.store(STATE_DONE, Ordering::SeqCst);
+        info!("Shutdown complete, exiting");
+        app.exit(0);
+    });
+}
+
+/// Call from `RunEvent::ExitRequested`. Returns true when the exit must be prevented because
+/// the shutdown sequence has not finished yet (it is started if needed).
+pub fn intercept_exit<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> bool {
+    if SHUTDOWN_STATE.load(Ordering::SeqCst) == STATE_DONE {
+        return false;
+    }
+    request_shutdown(app);
+    true
+}
+
+/// Cancels all background tasks and waits until they have removed themselves (or timeout).
+fn wait_for_tasks<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(tasks) = app.try_state::<TaskManager>() else {
+        return;
+    };
+    let cancelled = tasks.cancel_all();
+    if cancelled == 0 {
+        return;
+    }
+    info!(count = cancelled, "Cancelling background tasks before exit");
+
+    let deadline = Instant::now() + TASK_SHUTDOWN_TIMEOUT;
+    while Instant::now() < deadline {
+        if tasks.list().is_empty() {
+            return;
+        }
+        std::thread::sleep(TASK_POLL_INTERVAL);
+    }
+
+    let remaining: Vec<String> = tasks.list().into_iter().map(|t| t.label).collect();
+    warn!(
+        ?remaining,
+        "Background tasks did not finish before shutdown timeout"
+    );
+}
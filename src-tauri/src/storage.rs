@@ -0,0 +1,205 @@
+//! Disk usage reporting and cache management.
+//!
+//! `get_storage_report` sums the bytes used by each app-owned directory (see `paths`);
+//! `clear_cache` empties one cache kind. `prune_caches` applies the size caps and log retention
+//! configured in `FullConfig` and runs once in the background at startup.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use tracing::{debug, info, warn};
+
+use crate::config::FullConfig;
+use crate::paths;
+use crate::voices;
+
+pub const DEFAULT_AUDIO_CACHE_MAX_MB: u32 = 200;
+pub const DEFAULT_OCR_CACHE_MAX_MB: u32 = 50;
+pub const DEFAULT_LOG_RETENTION_DAYS: u32 = 14;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Bytes used per storage area. Missing directories count as zero.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct StorageReport {
+    pub voices_bytes: u64,
+    pub ocr_cache_bytes: u64,
+    pub audio_cache_bytes: u64,
+    pub logs_bytes: u64,
+    pub history_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// What `clear_cache` removes. Voices and history are user data and are not caches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheKind {
+    Ocr,
+    Audio,
+    Logs,
+    VoiceList,
+    All,
+}
+
+pub fn storage_report() -> StorageReport {
+    let size_of = |dir: Result<PathBuf, String>| dir.map(|d| dir_size(&d)).unwrap_or(0);
+    let mut report = StorageReport {
+        voices_bytes: size_of(paths::get_voices_dir()),
+        ocr_cache_bytes: size_of(paths::get_ocr_cache_dir()),
+        audio_cache_bytes: size_of(paths::get_audio_cache_dir()),
+        logs_bytes: size_of(paths::get_logs_dir()),
+        history_bytes: size_of(paths::get_history_dir()),
+        total_bytes: 0,
+    };
+    report.total_bytes = report.voices_bytes
+        + report.ocr_cache_bytes
+        + report.audio_cache_bytes
+        + report.logs_bytes
+        + report.history_bytes;
+    report
+}
+
+/// Removes the contents of the given cache. Returns the number of bytes freed.
+pub fn clear(kind: CacheKind) -> Result<u64, String> {
+    match kind {
+        CacheKind::Ocr => clear_dir(&paths::get_ocr_cache_dir()?),
+        CacheKind::Audio => clear_dir(&paths::get_audio_cache_dir()?),
+        CacheKind::Logs => clear_dir(&paths::get_logs_dir()?),
+        CacheKind::VoiceList => voices::cache::clear(),
+        CacheKind::All => [
+            CacheKind::Ocr,
+            CacheKind::Audio,
+            CacheKind::Logs,
+            CacheKind::VoiceList,
+        ]
+        .into_iter()
+        .try_fold(0, |freed, kind| Ok(freed + clear(kind)?)),
+    }
+}
+
+/// Applies the configured size caps (oldest files first) and log retention.
+pub fn prune_caches(config: &FullConfig) {
+    let audio_cap = u64::from(
+        config
+            .audio_cache_max_mb
+            .unwrap_or(DEFAULT_AUDIO_CACHE_MAX_MB),
+    );
+    let ocr_cap = u64::from(config.ocr_cache_max_mb.unwrap_or(DEFAULT_OCR_CACHE_MAX_MB));
+    let retention_days = config
+        .log_retention_days
+        .unwrap_or(DEFAULT_LOG_RETENTION_DAYS);
+
+    let mut freed = 0;
+    if let Ok(dir) = paths::get_audio_cache_dir() {
+        freed += prune_to_size(&dir, audio_cap * BYTES_PER_MB);
+    }
+    if let Ok(dir) = paths::get_ocr_cache_dir() {
+        freed += prune_to_size(&dir, ocr_cap * BYTES_PER_MB);
+    }
+    if let Ok(dir) = paths::get_logs_dir() {
+        let max_age = Duration::from_secs(u64::from(retention_days) * SECS_PER_DAY);
+        freed += prune_older_than(&dir, max_age);
+    }
+    if freed > 0 {
+        info!(freed_bytes = freed, "Pruned caches");
+    }
+}
+
+pub(crate) fn dir_size(dir: &Path) -> u64 {
+    collect_files(dir).iter().map(|f| f.size).sum()
+}
+
+struct FileEntry {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+fn collect_files(dir: &Path) -> Vec<FileEntry> {
+    let mut files = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if meta.is_dir() {
+            files.extend(collect_files(&entry.path()));
+        } else {
+            files.push(FileEntry {
+                path: entry.path(),
+                size: meta.len(),
+                modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            });
+        }
+    }
+    files
+}
+
+fn clear_dir(dir: &Path) -> Result<u64, String> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let size = dir_size(dir);
+    fs::remove_dir_all(dir).map_err(|e| format!("Failed to clear {}: {e}", dir.display()))?;
+    debug!(dir = %dir.display(), freed_bytes = size, "Cleared cache directory");
+    Ok(size)
+}
+
+/// Deletes the oldest files until the directory is at most `max_bytes`. Returns bytes freed.
+fn prune_to_size(dir: &Path, max_bytes: u64) -> u64 {
+    let mut files = collect_files(dir);
+    let mut total: u64 = files.iter().map(|f| f.size).sum();
+    if total <= max_bytes {
+        return 0;
+    }
+    files.sort_by_key(|f| f.modified);
+    let mut freed = 0;
+    for file in files {
+        if total <= max_bytes {
+            break;
+        }
+        match fs::remove_file(&file.path) {
+            Ok(()) => {
+                total -= file.size;
+                freed += file.size;
+            }
+            Err(e) => warn!(error = %e, path = %file.path.display(), "Failed to prune cache file"),
+        }
+    }
+    freed
+}
+
+/// Deletes files last modified more than `max_age` ago. Returns bytes freed.
+fn prune_older_than(dir: &Path, max_age: Duration) -> u64 {
+    let Some(cutoff) = SystemTime::now().checked_sub(max_age) else {
+        return 0;
+    };
+    collect_files(dir)
+        .into_iter()
+        .filter(|f| f.modified < cutoff)
+        .filter(|f| fs::remove_file(&f.path).is_ok())
+        .map(|f| f.size)
+        .sum()
+}
+
+// --- Commands ---
+
+/// Returns bytes used by voices, OCR cache, audio cache, logs, and history.
+#[tauri::command]
+pub async fn get_storage_report() -> Result<StorageReport, String> {
+    tokio::task::spawn_blocking(storage_report)
+        .await
+        .map_err(|e| format!("spawn_blocking: {e}"))
+}
+
+/// Clears one cache kind (`ocr`, `audio`, `logs`, `voice_list`, `all`). Returns bytes freed.
+#[tauri::command]
+pub async fn clear_cache(kind: CacheKind) -> Result<u64, String> {
+    tokio::task::spawn_blocking(move || clear(kind))
+        .await
+        .map_err(|e| format!("spawn_blocking: {e}"))?
+}
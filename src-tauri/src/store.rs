@@ -0,0 +1,182 @@
+//! JSON files kept in memory: reading stats, TTS usage and reading history.
+//!
+//! A `JsonStore` loads its file on first use. Changes are made in memory and written back
+//! atomically (`util::write_atomic`) at most every save interval, right away with `update_now`,
+//! and by `flush` at shutdown. The path is resolved on every access: after a profile switch the
+//! previous profile's changes are written to its own file and the new profile's file is loaded.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::util;
+
+struct Loaded<T> {
+    path: PathBuf,
+    value: T,
+    dirty: bool,
+    saved_at: Instant,
+}
+
+impl<T: Serialize> Loaded<T> {
+    fn save(&mut self, name: &str) -> Result<(), String> {
+        if !self.dirty {
+            return Ok(());
+        }
+        self.saved_at = Instant::now();
+        let json = serde_json::to_string(&self.value)
+            .map_err(|e| format!("Failed to serialize {name}: {e}"))?;
+        util::write_atomic(&self.path, json)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn save_or_warn(&mut self, name: &str) {
+        if let Err(e) = self.save(name) {
+            warn!(error = %e, "Failed to save {name}");
+        }
+    }
+}
+
+/// A JSON file loaded on first use and written back when changed (see the module docs).
+pub struct JsonStore<T> {
+    /// What the file holds, for messages ("reading stats").
+    name: &'static str,
+    path: fn() -> Result<PathBuf, String>,
+    save_interval: Duration,
+    loaded: Mutex<Option<Loaded<T>>>,
+}
+
+impl<T: Default + Serialize + DeserializeOwned> JsonStore<T> {
+    pub const fn new(
+        name: &'static str,
+        path: fn() -> Result<PathBuf, String>,
+        save_interval: Duration,
+    ) -> Self {
+        Self {
+            name,
+            path,
+            save_interval,
+            loaded: Mutex::new(None),
+        }
+    }
+
+    fn load(&self, path: PathBuf) -> Loaded<T> {
+        let value = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!(path = %path.display(), error = %e, "Ignoring unreadable {} file", self.name);
+                T::default()
+            }),
+            Err(_) => T::default(),
+        };
+        Loaded {
+            path,
+            value,
+            dirty: false,
+            saved_at: Instant::now(),
+        }
+    }
+
+    /// Runs `f` on the file at the current path, saving and replacing the loaded one when the
+    /// path changed.
+    fn with_loaded<R>(&self, f: impl FnOnce(&mut Loaded<T>) -> R) -> Result<R, String> {
+        let path = (self.path)()?;
+        let mut guard = self
+            .loaded
+            .lock()
+            .map_err(|_| format!("{} lock poisoned", self.name))?;
+        if guard.as_ref().is_some_and(|loaded| loaded.path != path) {
+            if let Some(mut previous) = guard.take() {
+                previous.save_or_warn(self.name);
+            }
+        }
+        let loaded = guard.get_or_insert_with(|| self.load(path));
+        Ok(f(loaded))
+    }
+
+    /// Reads the contents.
+    pub fn read<R>(&self, f: impl FnOnce(&T) -> R) -> Result<R, String> {
+        self.with_loaded(|loaded| f(&loaded.value))
+    }
+
+    /// Changes the contents. They are written once the save interval has passed since the last
+    /// write, or by `flush`.
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, String> {
+        self.with_loaded(|loaded| {
+            let result = f(&mut loaded.value);
+            loaded.dirty = true;
+            if loaded.saved_at.elapsed() >= self.save_interval {
+                loaded.save_or_warn(self.name);
+            }
+            result
+        })
+    }
+
+    /// Changes the contents and writes them right away (user-visible edits such as deletions).
+    pub fn update_now<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, String> {
+        self.with_loaded(|loaded| {
+            let result = f(&mut loaded.value);
+            loaded.dirty = true;
+            loaded.save(self.name).map(|()| result)
+        })?
+    }
+
+    /// Writes changes not saved yet.
+    pub fn flush(&self) {
+        if let Ok(mut guard) = self.loaded.lock() {
+            if let Some(loaded) = guard.as_mut() {
+                loaded.save_or_warn(self.name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TEST_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+    fn test_path() -> Result<PathBuf, String> {
+        TEST_PATH
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| "no path".to_string())
+    }
+
+    fn set_test_path(path: PathBuf) {
+        *TEST_PATH.lock().unwrap() = Some(path);
+    }
+
+    #[test]
+    fn test_changes_follow_the_path_they_were_made_under() {
+        let store: JsonStore<Vec<u32>> =
+            JsonStore::new("test store", test_path, Duration::from_secs(3600));
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("a").join("store.json");
+        let second = dir.path().join("b").join("store.json");
+        fs::create_dir_all(second.parent().unwrap()).unwrap();
+        fs::write(&second, "[7]").unwrap();
+
+        set_test_path(first.clone());
+        store.update(|v| v.push(1)).unwrap();
+        assert!(!first.exists(), "written before the save interval");
+
+        set_test_path(second.clone());
+        assert_eq!(store.read(|v| v.clone()).unwrap(), [7]);
+        assert_eq!(fs::read_to_string(&first).unwrap(), "[1]");
+
+        store.update(|v| v.push(8)).unwrap();
+        store.flush();
+        assert_eq!(fs::read_to_string(&second).unwrap(), "[7,8]");
+
+        store.update_now(|v| v.clear()).unwrap();
+        assert_eq!(fs::read_to_string(&second).unwrap(), "[]");
+    }
+}
@@ -0,0 +1,147 @@
+//! Playback around system sleep: playback is paused before the system suspends and, with
+//! `resume_after_wake` (off by default), resumed at the same position once it wakes up.
+//!
+//! Sleep and wake come from `system::watch_power`. On wake the worker reopens the audio output
+//! first, since the stream opened before sleep may be dead even on the same device. Only playback
+//! we paused is resumed, as in `mic_pause`. `system-sleep` is emitted on both events.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+
+use tauri::Emitter;
+use tracing::{debug, info};
+
+use crate::config::FullConfig;
+use crate::system::{self, PowerEvent};
+use crate::tts;
+
+/// Event emitted when the system is about to sleep and when it woke up.
+pub const SYSTEM_SLEEP_EVENT: &str = "system-sleep";
+
+static RESUME: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SystemSleep {
+    /// True before the system sleeps, false once it woke up.
+    pub sleeping: bool,
+    /// Whether playback was paused (before sleep) or resumed (after waking).
+    pub playback_changed: bool,
+}
+
+/// Applies `resume_after_wake` (see `commands_config::apply_runtime_settings`).
+pub fn configure(cfg: &FullConfig) {
+    RESUME.store(cfg.resume_after_wake.unwrap_or(false), Ordering::Relaxed);
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    Pause,
+    Resume,
+}
+
+/// Whether the current pause is ours.
+#[derive(Default)]
+struct Monitor {
+    paused_by_us: bool,
+}
+
+impl Monitor {
+    /// Handles one event; `status` is the player's (playing, paused).
+    fn on_event(
+        &mut self,
+        event: PowerEvent,
+        status: (bool, bool),
+        resume: bool,
+    ) -> Option<Action> {
+        match event {
+            PowerEvent::Suspending => {
+                self.paused_by_us = status == (true, false);
+                self.paused_by_us.then_some(Action::Pause)
+            }
+            PowerEvent::Resumed => {
+                let ours = std::mem::take(&mut self.paused_by_us);
+                (ours && resume && status == (true, true)).then_some(Action::Resume)
+            }
+        }
+    }
+}
+
+fn toggle_pause(tts_tx: &tts::TtsState) -> bool {
+    let (resp_tx, resp_rx) = mpsc::sync_channel(1);
+    if tts_tx.send(tts::TtsRequest::TogglePause(resp_tx)).is_err() {
+        return false;
+    }
+    match resp_rx.recv() {
+        Ok(Ok(_)) => true,
+        Ok(Err(e)) => {
+            debug!(error = %e, "Sleep pause toggle failed");
+            false
+        }
+        Err(_) => false,
+    }
+}
+
+/// Starts listening for sleep and wake. Called from lib's setup.
+pub fn start<R: tauri::Runtime>(app: tauri::AppHandle<R>, tts_tx: tts::TtsState) {
+    let monitor = Mutex::new(Monitor::default());
+    let watching = system::watch_power(Box::new(move |event| {
+        if event == PowerEvent::Resumed {
+            let _ = tts_tx.send(tts::TtsRequest::ReopenOutput);
+        }
+        let Some(status) = tts::playback_status(&tts_tx) else {
+            return;
+        };
+        let action = monitor.lock().ok().and_then(|mut monitor| {
+            monitor.on_event(event, status, RESUME.load(Ordering::Relaxed))
+        });
+        if let Some(action) = &action {
+            info!(?event, ?action, "System sleep, toggling playback");
+        }
+        let playback_changed = action.is_some() && toggle_pause(&tts_tx);
+        let _ = app.emit(
+            SYSTEM_SLEEP_EVENT,
+            SystemSleep {
+                sleeping: event == PowerEvent::Suspending,
+                playback_changed,
+            },
+        );
+    }));
+    if watching {
+        debug!("Watching for system sleep");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resumes_after_wake_only_what_it_paused() {
+        let mut monitor = Monitor::default();
+        assert_eq!(
+            monitor.on_event(PowerEvent::Suspending, (true, false), true),
+            Some(Action::Pause)
+        );
+        assert_eq!(
+            monitor.on_event(PowerEvent::Resumed, (true, true), true),
+            Some(Action::Resume)
+        );
+
+        // Already paused by the user before sleep.
+        assert_eq!(
+            monitor.on_event(PowerEvent::Suspending, (true, true), true),
+            None
+        );
+        assert_eq!(
+            monitor.on_event(PowerEvent::Resumed, (true, true), true),
+            None
+        );
+
+        // Resuming is off.
+        monitor.on_event(PowerEvent::Suspending, (true, false), false);
+        assert_eq!(
+            monitor.on_event(PowerEvent::Resumed, (true, true), false),
+            None
+        );
+    }
+}
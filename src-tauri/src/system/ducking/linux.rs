@@ -0,0 +1,152 @@
+//! Linux: sink input (stream) volumes from PulseAudio (or PipeWire's PulseAudio server) via
+//! `pactl`.
+
+use std::process::{Command, Stdio};
+
+use tracing::{debug, warn};
+
+#[derive(Debug)]
+pub(super) struct Ducked {
+    index: u32,
+    /// Raw per-channel volumes before ducking, in channel map order.
+    volumes: Vec<u32>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct SinkInput {
+    index: u32,
+    volumes: Vec<u32>,
+    corked: bool,
+    process_id: Option<u32>,
+}
+
+fn pactl(args: &[&str]) -> Option<String> {
+    let output = Command::new("pactl")
+        .args(args)
+        // The listing is parsed: keep it in English.
+        .env("LC_ALL", "C")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| debug!(error = %e, "pactl not available"))
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Sink inputs in `pactl list sink-inputs` output.
+fn sink_inputs(listing: &str) -> Vec<SinkInput> {
+    let mut inputs = Vec::new();
+    let mut current: Option<SinkInput> = None;
+    for line in listing.lines() {
+        if let Some(index) = line.strip_prefix("Sink Input #") {
+            inputs.extend(current.take());
+            current = index.trim().parse().ok().map(|index| SinkInput {
+                index,
+                ..SinkInput::default()
+            });
+            continue;
+        }
+        let Some(input) = current.as_mut() else {
+            continue;
+        };
+        let line = line.trim();
+        if let Some(volume) = line.strip_prefix("Volume:") {
+            // "front-left: 65536 / 100% / 0.00 dB,   front-right: 65536 / 100% / 0.00 dB"
+            input.volumes = volume
+                .split(',')
+                .filter_map(|channel| channel.split(':').nth(1)?.split('/').next())
+                .filter_map(|raw| raw.trim().parse().ok())
+                .collect();
+        } else if let Some(corked) = line.strip_prefix("Corked:") {
+            input.corked = corked.trim() == "yes";
+        } else if let Some(pid) = line.strip_prefix("application.process.id = ") {
+            input.process_id = pid.trim_matches('"').parse().ok();
+        }
+    }
+    inputs.extend(current);
+    inputs
+}
+
+fn set_volumes(index: u32, volumes: &[u32]) -> bool {
+    let index = index.to_string();
+    let volumes: Vec<String> = volumes.iter().map(u32::to_string).collect();
+    let mut args = vec!["set-sink-input-volume", index.as_str()];
+    args.extend(volumes.iter().map(String::as_str));
+    pactl(&args).is_some()
+}
+
+pub(super) fn duck(level: f32) -> Vec<Ducked> {
+    let Some(listing) = pactl(&["list", "sink-inputs"]) else {
+        warn!("Cannot duck other audio: pactl failed");
+        return Vec::new();
+    };
+    let own = std::process::id();
+    sink_inputs(&listing)
+        .into_iter()
+        .filter(|input| !input.corked && !input.volumes.is_empty())
+        .filter(|input| input.process_id != Some(own))
+        .filter_map(|input| {
+            let ducked: Vec<u32> = input
+                .volumes
+                .iter()
+                .map(|v| (*v as f32 * level) as u32)
+                .collect();
+            set_volumes(input.index, &ducked).then_some(Ducked {
+                index: input.index,
+                volumes: input.volumes,
+            })
+        })
+        .collect()
+}
+
+pub(super) fn restore(ducked: &[Ducked]) {
+    for stream in ducked {
+        if !set_volumes(stream.index, &stream.volumes) {
+            debug!(
+                index = stream.index,
+                "Stream ended before its volume was restored"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_sink_inputs() {
+        let listing = "Sink Input #42\n\
+            \tDriver: PipeWire\n\
+            \tCorked: no\n\
+            \tVolume: front-left: 65536 / 100% / 0.00 dB,   front-right: 32768 / 50% / -18.06 dB\n\
+            \t        balance 0.00\n\
+            \tProperties:\n\
+            \t\tapplication.name = \"Firefox\"\n\
+            \t\tapplication.process.id = \"1234\"\n\
+            \n\
+            Sink Input #43\n\
+            \tCorked: yes\n\
+            \tVolume: mono: 40000 /  61% / -12.9 dB\n";
+        assert_eq!(
+            sink_inputs(listing),
+            vec![
+                SinkInput {
+                    index: 42,
+                    volumes: vec![65536, 32768],
+                    corked: false,
+                    process_id: Some(1234),
+                },
+                SinkInput {
+                    index: 43,
+                    volumes: vec![40000],
+                    corked: true,
+                    process_id: None,
+                },
+            ]
+        );
+    }
+}
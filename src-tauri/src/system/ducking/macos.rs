@@ -0,0 +1,75 @@
+//! macOS: CoreAudio has no per-app volume, so the players that expose one to AppleScript (Music
+//! and Spotify) are lowered through their `sound volume`. Running it needs the Automation
+//! permission for those apps; without it, they are left alone.
+
+use std::process::{Command, Stdio};
+
+use tracing::debug;
+
+/// Apps with a scriptable `sound volume` (0-100).
+const PLAYERS: [&str; 2] = ["Music", "Spotify"];
+
+#[derive(Debug)]
+pub(super) struct Ducked {
+    app: &'static str,
+    volume: u32,
+}
+
+fn osascript(script: &str) -> Option<String> {
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| debug!(error = %e, "osascript failed"))
+        .ok()?;
+    if !output.status.success() {
+        debug!(
+            stderr = %String::from_utf8_lossy(&output.stderr).trim(),
+            "AppleScript failed"
+        );
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The app's volume while it is playing; `None` when it is not running or paused (checking
+/// `is running` first keeps AppleScript from launching it).
+fn playing_volume(app: &str) -> Option<u32> {
+    let script = format!(
+        r#"if application "{app}" is running then
+            tell application "{app}"
+                if player state is playing then return sound volume
+            end tell
+        end if"#
+    );
+    osascript(&script)?.parse().ok()
+}
+
+fn set_volume(app: &str, volume: u32) -> bool {
+    let script = format!(
+        r#"if application "{app}" is running then
+            tell application "{app}" to set sound volume to {volume}
+        end if"#
+    );
+    osascript(&script).is_some()
+}
+
+pub(super) fn duck(level: f32) -> Vec<Ducked> {
+    PLAYERS
+        .into_iter()
+        .filter_map(|app| {
+            let volume = playing_volume(app)?;
+            let ducked = (volume as f32 * level).round() as u32;
+            set_volume(app, ducked).then_some(Ducked { app, volume })
+        })
+        .collect()
+}
+
+pub(super) fn restore(ducked: &[Ducked]) {
+    for player in ducked {
+        if !set_volume(player.app, player.volume) {
+            debug!(app = player.app, "Could not restore the player's volume");
+        }
+    }
+}
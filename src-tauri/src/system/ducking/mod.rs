@@ -0,0 +1,50 @@
+//! Lowering other apps' audio while we play ("ducking") and restoring it afterwards.
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[cfg(target_os = "linux")]
+use linux as platform;
+#[cfg(target_os = "macos")]
+use macos as platform;
+#[cfg(target_os = "windows")]
+use windows as platform;
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+mod platform {
+    #[derive(Debug)]
+    pub(super) struct Ducked;
+
+    pub(super) fn duck(_level: f32) -> Vec<Ducked> {
+        Vec::new()
+    }
+
+    pub(super) fn restore(_ducked: &[Ducked]) {}
+}
+
+/// Volumes lowered by `duck_other_audio`, to hand back to `restore_other_audio`.
+#[derive(Debug)]
+pub struct DuckedAudio(Vec<platform::Ducked>);
+
+impl DuckedAudio {
+    /// Number of streams (or apps) that were lowered.
+    pub fn count(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Lowers the audio other apps are playing to `level` (0.0-1.0) of its volume: their PulseAudio
+/// or PipeWire streams on Linux, their WASAPI sessions on Windows, and Music and Spotify on macOS
+/// (which has no per-app volume). Empty when nothing was playing or it is not supported.
+pub fn duck_other_audio(level: f32) -> DuckedAudio {
+    DuckedAudio(platform::duck(level.clamp(0.0, 1.0)))
+}
+
+/// Puts back the volumes `duck_other_audio` lowered. Streams that ended meanwhile are skipped.
+pub fn restore_other_audio(ducked: DuckedAudio) {
+    platform::restore(&ducked.0);
+}
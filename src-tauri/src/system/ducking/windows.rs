@@ -0,0 +1,304 @@
+//! Windows: the volume of each audio session (per app) on the default output device, through the
+//! WASAPI session interfaces (`IAudioSessionManager2`, `ISimpleAudioVolume`), declared here.
+
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::ptr;
+
+use tracing::debug;
+
+#[repr(C)]
+struct Guid {
+    data1: u32,
+    data2: u16,
+    data3: u16,
+    data4: [u8; 8],
+}
+
+const CLSID_MM_DEVICE_ENUMERATOR: Guid = Guid {
+    data1: 0xBCDE0395,
+    data2: 0xE52F,
+    data3: 0x467C,
+    data4: [0x8E, 0x3D, 0xC4, 0x57, 0x92, 0x91, 0x69, 0x2E],
+};
+const IID_IMM_DEVICE_ENUMERATOR: Guid = Guid {
+    data1: 0xA95664D2,
+    data2: 0x9614,
+    data3: 0x4F35,
+    data4: [0xA7, 0x46, 0xDE, 0x8D, 0xB6, 0x36, 0x17, 0xE6],
+};
+const IID_IAUDIO_SESSION_MANAGER2: Guid = Guid {
+    data1: 0x77AA99A0,
+    data2: 0x1BD6,
+    data3: 0x484F,
+    data4: [0x8B, 0xC7, 0x2C, 0x65, 0x4C, 0x9A, 0x9B, 0x6F],
+};
+const IID_IAUDIO_SESSION_CONTROL2: Guid = Guid {
+    data1: 0xBFB7FF88,
+    data2: 0x7239,
+    data3: 0x4FC9,
+    data4: [0x8F, 0xA2, 0x07, 0xC9, 0x50, 0xBE, 0x9C, 0x6D],
+};
+const IID_ISIMPLE_AUDIO_VOLUME: Guid = Guid {
+    data1: 0x87CE5498,
+    data2: 0x68D6,
+    data3: 0x44E5,
+    data4: [0x92, 0x15, 0x6D, 0xA4, 0x7E, 0xF8, 0x83, 0xD8],
+};
+
+const CLSCTX_ALL: u32 = 0x17;
+const COINIT_MULTITHREADED: u32 = 0;
+const E_RENDER: i32 = 0;
+const E_CONSOLE: i32 = 0;
+const AUDIO_SESSION_STATE_ACTIVE: i32 = 1;
+const S_OK: i32 = 0;
+
+type HResult = i32;
+/// A vtable slot we do not call.
+type Unused = usize;
+
+#[repr(C)]
+struct UnknownVtbl {
+    query_interface:
+        unsafe extern "system" fn(*mut c_void, *const Guid, *mut *mut c_void) -> HResult,
+    add_ref: Unused,
+    release: unsafe extern "system" fn(*mut c_void) -> u32,
+}
+
+#[repr(C)]
+struct DeviceEnumeratorVtbl {
+    unknown: UnknownVtbl,
+    enum_audio_endpoints: Unused,
+    get_default_audio_endpoint:
+        unsafe extern "system" fn(*mut c_void, i32, i32, *mut *mut c_void) -> HResult,
+}
+
+#[repr(C)]
+struct DeviceVtbl {
+    unknown: UnknownVtbl,
+    activate: unsafe extern "system" fn(
+        *mut c_void,
+        *const Guid,
+        u32,
+        *mut c_void,
+        *mut *mut c_void,
+    ) -> HResult,
+}
+
+#[repr(C)]
+struct SessionManager2Vtbl {
+    unknown: UnknownVtbl,
+    get_audio_session_control: Unused,
+    get_simple_audio_volume: Unused,
+    get_session_enumerator: unsafe extern "system" fn(*mut c_void, *mut *mut c_void) -> HResult,
+}
+
+#[repr(C)]
+struct SessionEnumeratorVtbl {
+    unknown: UnknownVtbl,
+    get_count: unsafe extern "system" fn(*mut c_void, *mut i32) -> HResult,
+    get_session: unsafe extern "system" fn(*mut c_void, i32, *mut *mut c_void) -> HResult,
+}
+
+#[repr(C)]
+struct SessionControl2Vtbl {
+    unknown: UnknownVtbl,
+    get_state: unsafe extern "system" fn(*mut c_void, *mut i32) -> HResult,
+    /// The rest of `IAudioSessionControl`, then `GetSessionIdentifier` and
+    /// `GetSessionInstanceIdentifier` of `IAudioSessionControl2`.
+    unused: [Unused; 10],
+    get_process_id: unsafe extern "system" fn(*mut c_void, *mut u32) -> HResult,
+    is_system_sounds_session: unsafe extern "system" fn(*mut c_void) -> HResult,
+}
+
+#[repr(C)]
+struct SimpleAudioVolumeVtbl {
+    unknown: UnknownVtbl,
+    set_master_volume: unsafe extern "system" fn(*mut c_void, f32, *const Guid) -> HResult,
+    get_master_volume: unsafe extern "system" fn(*mut c_void, *mut f32) -> HResult,
+}
+
+#[link(name = "ole32")]
+extern "system" {
+    fn CoInitializeEx(reserved: *mut c_void, coinit: u32) -> HResult;
+    fn CoCreateInstance(
+        clsid: *const Guid,
+        outer: *mut c_void,
+        context: u32,
+        iid: *const Guid,
+        object: *mut *mut c_void,
+    ) -> HResult;
+}
+
+/// An owned COM interface pointer whose vtable starts like `V`; released on drop.
+struct Com<V> {
+    ptr: *mut c_void,
+    _vtbl: PhantomData<V>,
+}
+
+impl<V> Com<V> {
+    /// Wraps the pointer a successful COM call wrote to `ptr`.
+    ///
+    /// # Safety
+    /// `ptr` must be null or an interface pointer whose vtable starts like `V` (which starts with
+    /// `UnknownVtbl`).
+    unsafe fn new(status: HResult, ptr: *mut c_void) -> Option<Self> {
+        (status == S_OK && !ptr.is_null()).then_some(Self {
+            ptr,
+            _vtbl: PhantomData,
+        })
+    }
+
+    fn vtbl(&self) -> &V {
+        // SAFETY: a COM object starts with its vtable pointer; `new` checked the vtable type.
+        unsafe { &**(self.ptr as *const *const V) }
+    }
+
+    fn unknown(&self) -> &UnknownVtbl {
+        // SAFETY: every vtable starts with IUnknown's.
+        unsafe { &**(self.ptr as *const *const UnknownVtbl) }
+    }
+
+    /// The same object as another interface (`QueryInterface`).
+    ///
+    /// # Safety
+    /// `iid` must be the interface `W` describes.
+    unsafe fn cast<W>(&self, iid: &Guid) -> Option<Com<W>> {
+        let mut out = ptr::null_mut();
+        let status = (self.unknown().query_interface)(self.ptr, iid, &mut out);
+        Com::new(status, out)
+    }
+}
+
+impl<V> Drop for Com<V> {
+    fn drop(&mut self) {
+        // SAFETY: `ptr` holds the reference this wrapper owns.
+        unsafe {
+            (self.unknown().release)(self.ptr);
+        }
+    }
+}
+
+/// Sessions of other processes on the default output device (only those playing with
+/// `active_only`), with their process id.
+fn sessions(active_only: bool) -> Vec<(u32, Com<SimpleAudioVolumeVtbl>)> {
+    let own = std::process::id();
+    // SAFETY: each call gets valid out-pointers and its result is only wrapped (by `Com::new`)
+    // when it succeeded, as the interface the IID names.
+    unsafe {
+        // Already initialized (maybe in another mode) is fine: the objects are used on this
+        // thread only.
+        CoInitializeEx(ptr::null_mut(), COINIT_MULTITHREADED);
+        let mut out = ptr::null_mut();
+        let status = CoCreateInstance(
+            &CLSID_MM_DEVICE_ENUMERATOR,
+            ptr::null_mut(),
+            CLSCTX_ALL,
+            &IID_IMM_DEVICE_ENUMERATOR,
+            &mut out,
+        );
+        let Some(enumerator) = Com::<DeviceEnumeratorVtbl>::new(status, out) else {
+            debug!(status, "No audio device enumerator");
+            return Vec::new();
+        };
+        let mut out = ptr::null_mut();
+        let status = (enumerator.vtbl().get_default_audio_endpoint)(
+            enumerator.ptr,
+            E_RENDER,
+            E_CONSOLE,
+            &mut out,
+        );
+        let Some(device) = Com::<DeviceVtbl>::new(status, out) else {
+            return Vec::new();
+        };
+        let mut out = ptr::null_mut();
+        let status = (device.vtbl().activate)(
+            device.ptr,
+            &IID_IAUDIO_SESSION_MANAGER2,
+            CLSCTX_ALL,
+            ptr::null_mut(),
+            &mut out,
+        );
+        let Some(manager) = Com::<SessionManager2Vtbl>::new(status, out) else {
+            return Vec::new();
+        };
+        let mut out = ptr::null_mut();
+        let status = (manager.vtbl().get_session_enumerator)(manager.ptr, &mut out);
+        let Some(list) = Com::<SessionEnumeratorVtbl>::new(status, out) else {
+            return Vec::new();
+        };
+        let mut count = 0;
+        if (list.vtbl().get_count)(list.ptr, &mut count) != S_OK {
+            return Vec::new();
+        }
+
+        let mut sessions = Vec::new();
+        for i in 0..count {
+            let mut out = ptr::null_mut();
+            let status = (list.vtbl().get_session)(list.ptr, i, &mut out);
+            // IAudioSessionControl; only its IUnknown part is used before the casts.
+            let Some(control) = Com::<UnknownVtbl>::new(status, out) else {
+                continue;
+            };
+            let Some(control2) = control.cast::<SessionControl2Vtbl>(&IID_IAUDIO_SESSION_CONTROL2)
+            else {
+                continue;
+            };
+            let mut state = 0;
+            let mut pid = 0;
+            let vtbl = control2.vtbl();
+            if (vtbl.get_state)(control2.ptr, &mut state) != S_OK
+                || (active_only && state != AUDIO_SESSION_STATE_ACTIVE)
+                || (vtbl.is_system_sounds_session)(control2.ptr) == S_OK
+                || (vtbl.get_process_id)(control2.ptr, &mut pid) != S_OK
+                || pid == own
+            {
+                continue;
+            }
+            if let Some(volume) = control.cast::<SimpleAudioVolumeVtbl>(&IID_ISIMPLE_AUDIO_VOLUME) {
+                sessions.push((pid, volume));
+            }
+        }
+        sessions
+    }
+}
+
+fn master_volume(volume: &Com<SimpleAudioVolumeVtbl>) -> Option<f32> {
+    let mut level = 0.0;
+    // SAFETY: `volume` is a live ISimpleAudioVolume and `level` a valid out-pointer.
+    let status = unsafe { (volume.vtbl().get_master_volume)(volume.ptr, &mut level) };
+    (status == S_OK).then_some(level)
+}
+
+fn set_master_volume(volume: &Com<SimpleAudioVolumeVtbl>, level: f32) -> bool {
+    // SAFETY: `volume` is a live ISimpleAudioVolume; a null event context is allowed.
+    unsafe { (volume.vtbl().set_master_volume)(volume.ptr, level, ptr::null()) == S_OK }
+}
+
+#[derive(Debug)]
+pub(super) struct Ducked {
+    process_id: u32,
+    level: f32,
+}
+
+pub(super) fn duck(level: f32) -> Vec<Ducked> {
+    sessions(true)
+        .into_iter()
+        .filter_map(|(process_id, volume)| {
+            let before = master_volume(&volume)?;
+            set_master_volume(&volume, before * level).then_some(Ducked {
+                process_id,
+                level: before,
+            })
+        })
+        .collect()
+}
+
+pub(super) fn restore(ducked: &[Ducked]) {
+    // Also sessions paused since, so they do not stay lowered.
+    for (process_id, volume) in sessions(false) {
+        if let Some(session) = ducked.iter().find(|d| d.process_id == process_id) {
+            set_master_volume(&volume, session.level);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Linux (X11): the active window's `WM_CLASS` via `xprop`. Wayland does not expose the focused
+//! window to other clients.
+
+use std::process::{Command, Stdio};
+
+use tracing::debug;
+
+fn xprop(args: &[&str]) -> Option<String> {
+    let output = Command::new("xprop")
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| debug!(error = %e, "xprop not available"))
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub(super) fn foreground_app() -> Option<String> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() || std::env::var_os("DISPLAY").is_none() {
+        return None;
+    }
+    let window = active_window_id(&xprop(&["-root", "_NET_ACTIVE_WINDOW"])?)?;
+    window_class(&xprop(&["-id", &window, "WM_CLASS"])?)
+}
+
+/// The window id in `_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007`; `None` for 0x0 (no
+/// focused window).
+fn active_window_id(output: &str) -> Option<String> {
+    let (_, ids) = output.split_once('#')?;
+    let id = ids.split(',').next()?.trim();
+    (id.starts_with("0x") && id != "0x0").then(|| id.to_string())
+}
+
+/// The class (second string) in `WM_CLASS(STRING) = "keepassxc", "KeePassXC"`.
+fn window_class(output: &str) -> Option<String> {
+    let (_, values) = output.split_once('=')?;
+    let mut parts = values.split(',').map(|part| part.trim().trim_matches('"'));
+    let instance = parts.next()?;
+    let class = parts.next().unwrap_or(instance);
+    (!class.is_empty()).then(|| class.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_xprop_output() {
+        assert_eq!(
+            active_window_id("_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007\n").as_deref(),
+            Some("0x3a00007")
+        );
+        assert_eq!(
+            active_window_id("_NET_ACTIVE_WINDOW(WINDOW): window id # 0x0\n"),
+            None
+        );
+        assert_eq!(
+            window_class("WM_CLASS(STRING) = \"keepassxc\", \"KeePassXC\"\n").as_deref(),
+            Some("KeePassXC")
+        );
+        assert_eq!(window_class("WM_CLASS:  not found.\n"), None);
+    }
+}
@@ -0,0 +1,31 @@
+//! macOS: the frontmost application's localized name (NSWorkspace).
+
+use std::ffi::{c_char, CStr};
+
+use objc::msg_send;
+use objc::runtime::{Class, Object};
+
+pub(super) fn foreground_app() -> Option<String> {
+    let workspace_class = Class::get("NSWorkspace")?;
+    // SAFETY: plain AppKit getters; every returned object is checked for nil before use, and the
+    // UTF-8 buffer stays valid while `name` is alive (autoreleased, not released here).
+    unsafe {
+        let workspace: *mut Object = msg_send![workspace_class, sharedWorkspace];
+        if workspace.is_null() {
+            return None;
+        }
+        let app: *mut Object = msg_send![workspace, frontmostApplication];
+        if app.is_null() {
+            return None;
+        }
+        let name: *mut Object = msg_send![app, localizedName];
+        if name.is_null() {
+            return None;
+        }
+        let utf8: *const c_char = msg_send![name, UTF8String];
+        if utf8.is_null() {
+            return None;
+        }
+        Some(CStr::from_ptr(utf8).to_string_lossy().into_owned())
+    }
+}
@@ -0,0 +1,30 @@
+//! Which app is in front (has keyboard focus), by name.
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+/// Name of the foreground app: the X11 window class on Linux, the app name on macOS, the
+/// executable name without `.exe` on Windows. `None` when it cannot be determined (Wayland, tool
+/// missing, unsupported platform).
+pub fn foreground_app() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::foreground_app()
+    }
+    #[cfg(target_os = "macos")]
+    {
+        macos::foreground_app()
+    }
+    #[cfg(target_os = "windows")]
+    {
+        windows::foreground_app()
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        None
+    }
+}
@@ -0,0 +1,56 @@
+//! Windows: the executable of the process that owns the foreground window.
+
+use std::ffi::c_void;
+use std::path::Path;
+
+#[link(name = "user32")]
+extern "system" {
+    fn GetForegroundWindow() -> *mut c_void;
+    fn GetWindowThreadProcessId(window: *mut c_void, process_id: *mut u32) -> u32;
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> *mut c_void;
+    fn QueryFullProcessImageNameW(
+        process: *mut c_void,
+        flags: u32,
+        name: *mut u16,
+        size: *mut u32,
+    ) -> i32;
+    fn CloseHandle(handle: *mut c_void) -> i32;
+}
+
+const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+const MAX_PATH_CHARS: usize = 1024;
+
+pub(super) fn foreground_app() -> Option<String> {
+    // SAFETY: the window and process handles are checked before use and the process handle is
+    // closed; the name buffer and its size describe the same allocation.
+    let path = unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return None;
+        }
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(window, &mut process_id);
+        if process_id == 0 {
+            return None;
+        }
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, process_id);
+        if process.is_null() {
+            return None;
+        }
+        let mut name = vec![0u16; MAX_PATH_CHARS];
+        let mut size = name.len() as u32;
+        let ok = QueryFullProcessImageNameW(process, 0, name.as_mut_ptr(), &mut size);
+        CloseHandle(process);
+        if ok == 0 {
+            return None;
+        }
+        String::from_utf16_lossy(&name[..size as usize])
+    };
+    Path::new(&path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+}
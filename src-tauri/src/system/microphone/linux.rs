@@ -0,0 +1,46 @@
+//! Linux: source state from PulseAudio (or PipeWire's PulseAudio server) via `pactl`.
+
+use std::process::{Command, Stdio};
+
+use tracing::debug;
+
+pub(super) fn is_microphone_in_use() -> Option<bool> {
+    let output = Command::new("pactl")
+        .args(["list", "sources", "short"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| debug!(error = %e, "pactl not available"))
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(any_input_running(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Whether a source in `pactl list sources short` output is RUNNING (being recorded from).
+/// Monitor sources (loopback of an output) do not count: screen recorders use them.
+fn any_input_running(sources: &str) -> bool {
+    sources.lines().any(|line| {
+        let columns: Vec<&str> = line.split('\t').collect();
+        match (columns.get(1), columns.last()) {
+            (Some(name), Some(state)) => !name.ends_with(".monitor") && *state == "RUNNING",
+            _ => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignores_monitors_and_idle_sources() {
+        let idle =
+            "55\talsa_output.pci.analog-stereo.monitor\tPipeWire\ts32le 2ch 48000Hz\tRUNNING\n\
+                    56\talsa_input.pci.analog-stereo\tPipeWire\ts32le 2ch 48000Hz\tSUSPENDED\n";
+        assert!(!any_input_running(idle));
+        let recording = idle.replace("SUSPENDED", "RUNNING");
+        assert!(any_input_running(&recording));
+    }
+}
@@ -0,0 +1,59 @@
+//! macOS: whether the default input device is running in any process (CoreAudio).
+
+use std::ffi::c_void;
+
+#[repr(C)]
+struct AudioObjectPropertyAddress {
+    selector: u32,
+    scope: u32,
+    element: u32,
+}
+
+#[link(name = "CoreAudio", kind = "framework")]
+extern "C" {
+    fn AudioObjectGetPropertyData(
+        object_id: u32,
+        address: *const AudioObjectPropertyAddress,
+        qualifier_size: u32,
+        qualifier: *const c_void,
+        data_size: *mut u32,
+        data: *mut c_void,
+    ) -> i32;
+}
+
+const SYSTEM_OBJECT: u32 = 1;
+/// 'dIn '
+const DEFAULT_INPUT_DEVICE: u32 = u32::from_be_bytes(*b"dIn ");
+/// 'gone'
+const DEVICE_IS_RUNNING_SOMEWHERE: u32 = u32::from_be_bytes(*b"gone");
+/// 'glob'
+const SCOPE_GLOBAL: u32 = u32::from_be_bytes(*b"glob");
+const ELEMENT_MAIN: u32 = 0;
+
+/// Reads a u32 property of a CoreAudio object.
+fn property_u32(object_id: u32, selector: u32) -> Option<u32> {
+    let address = AudioObjectPropertyAddress {
+        selector,
+        scope: SCOPE_GLOBAL,
+        element: ELEMENT_MAIN,
+    };
+    let mut value: u32 = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    // SAFETY: `address` and the output buffer are valid for the call and `size` matches `value`.
+    let status = unsafe {
+        AudioObjectGetPropertyData(
+            object_id,
+            &address,
+            0,
+            std::ptr::null(),
+            &mut size,
+            (&mut value as *mut u32).cast(),
+        )
+    };
+    (status == 0).then_some(value)
+}
+
+pub(super) fn is_microphone_in_use() -> Option<bool> {
+    let device = property_u32(SYSTEM_OBJECT, DEFAULT_INPUT_DEVICE).filter(|id| *id != 0)?;
+    Some(property_u32(device, DEVICE_IS_RUNNING_SOMEWHERE)? != 0)
+}
@@ -0,0 +1,29 @@
+//! Microphone capture detection (is any app recording from an input device right now).
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+/// Whether an app is capturing from a microphone. `None` when it cannot be determined (tool
+/// missing, unsupported platform).
+pub fn is_microphone_in_use() -> Option<bool> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::is_microphone_in_use()
+    }
+    #[cfg(target_os = "macos")]
+    {
+        macos::is_microphone_in_use()
+    }
+    #[cfg(target_os = "windows")]
+    {
+        windows::is_microphone_in_use()
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        None
+    }
+}
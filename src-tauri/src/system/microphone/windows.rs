@@ -0,0 +1,31 @@
+//! Windows: the privacy consent store, where Windows records per app when it last started and
+//! stopped using the microphone (a stop time of 0 means it is still recording).
+
+use winreg::enums::HKEY_CURRENT_USER;
+use winreg::RegKey;
+
+const CONSENT_STORE: &str =
+    r"Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone";
+
+pub(super) fn is_microphone_in_use() -> Option<bool> {
+    let store = RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey(CONSENT_STORE)
+        .ok()?;
+    // Packaged apps are direct subkeys; desktop apps are under "NonPackaged".
+    let non_packaged = store.open_subkey("NonPackaged").ok();
+    let in_use = [Some(&store), non_packaged.as_ref()]
+        .into_iter()
+        .flatten()
+        .any(|parent| {
+            parent
+                .enum_keys()
+                .flatten()
+                .filter_map(|name| parent.open_subkey(name).ok())
+                .any(|app| {
+                    let start = app.get_value::<u64, _>("LastUsedTimeStart").unwrap_or(0);
+                    let stop = app.get_value::<u64, _>("LastUsedTimeStop").unwrap_or(1);
+                    start != 0 && stop == 0
+                })
+        });
+    Some(in_use)
+}
@@ -1,5 +1,13 @@
-//! System interactions (clipboard, etc.)
+//! System interactions (clipboard, microphone, other apps' audio, sleep and wake, etc.)
 
 mod clipboard;
+mod ducking;
+mod foreground;
+mod microphone;
+mod power;
 
 pub use clipboard::{get_clipboard_text, get_selected_text};
+pub use ducking::{duck_other_audio, restore_other_audio, DuckedAudio};
+pub use foreground::foreground_app;
+pub use microphone::is_microphone_in_use;
+pub use power::{watch_power, PowerEvent, PowerHandler};
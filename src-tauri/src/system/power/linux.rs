@@ -0,0 +1,65 @@
+//! Linux: logind's `PrepareForSleep` signal on the system bus. A delay inhibitor makes logind wait
+//! (up to its `InhibitDelayMaxSec`) until the handler has run before the system sleeps.
+
+use tracing::{debug, warn};
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::OwnedFd;
+
+use super::{PowerEvent, PowerHandler};
+
+const DESTINATION: &str = "org.freedesktop.login1";
+const PATH: &str = "/org/freedesktop/login1";
+const INTERFACE: &str = "org.freedesktop.login1.Manager";
+
+/// Takes a delay inhibitor; sleep waits until the returned descriptor is closed.
+fn inhibit(manager: &Proxy) -> Option<OwnedFd> {
+    manager
+        .call(
+            "Inhibit",
+            &(
+                "sleep",
+                "Insight Reader",
+                "Pause reading before sleep",
+                "delay",
+            ),
+        )
+        .map_err(|e| debug!(error = %e, "Failed to take a sleep delay inhibitor"))
+        .ok()
+}
+
+fn listen(connection: &Connection, handler: &PowerHandler) -> zbus::Result<()> {
+    let manager = Proxy::new(connection, DESTINATION, PATH, INTERFACE)?;
+    let signals = manager.receive_signal("PrepareForSleep")?;
+    let mut inhibitor = inhibit(&manager);
+    for message in signals {
+        let Ok(sleeping) = message.body().deserialize::<bool>() else {
+            continue;
+        };
+        if sleeping {
+            handler(PowerEvent::Suspending);
+            drop(inhibitor.take());
+        } else {
+            inhibitor = inhibit(&manager);
+            handler(PowerEvent::Resumed);
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn watch_power(handler: PowerHandler) -> bool {
+    let connection = match Connection::system() {
+        Ok(connection) => connection,
+        Err(e) => {
+            warn!(error = %e, "Failed to connect to the system bus, sleep is not detected");
+            return false;
+        }
+    };
+    std::thread::Builder::new()
+        .name("power-watch".into())
+        .spawn(move || {
+            if let Err(e) = listen(&connection, &handler) {
+                warn!(error = %e, "Sleep notification listener stopped");
+            }
+        })
+        .is_ok()
+}
@@ -0,0 +1,113 @@
+//! macOS: IOKit system power notifications (`IORegisterForSystemPower`), delivered on a run loop
+//! of our own thread. Sleep waits until the handler has run and the change is allowed.
+
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use tracing::warn;
+
+use super::{PowerEvent, PowerHandler};
+
+type IoServiceInterestCallback =
+    extern "C" fn(refcon: *mut c_void, service: u32, message_type: u32, argument: *mut c_void);
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IORegisterForSystemPower(
+        refcon: *mut c_void,
+        notify_port: *mut *mut c_void,
+        callback: IoServiceInterestCallback,
+        notifier: *mut u32,
+    ) -> u32;
+    fn IONotificationPortGetRunLoopSource(notify_port: *mut c_void) -> *mut c_void;
+    fn IOAllowPowerChange(kernel_port: u32, notification_id: isize) -> i32;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    static kCFRunLoopDefaultMode: *const c_void;
+    fn CFRunLoopGetCurrent() -> *mut c_void;
+    fn CFRunLoopAddSource(run_loop: *mut c_void, source: *mut c_void, mode: *const c_void);
+    fn CFRunLoopRun();
+}
+
+/// `kIOMessageCanSystemSleep`: idle sleep may be vetoed; we never do.
+const CAN_SYSTEM_SLEEP: u32 = 0xE000_0270;
+/// `kIOMessageSystemWillSleep`: sleep waits for `IOAllowPowerChange`.
+const SYSTEM_WILL_SLEEP: u32 = 0xE000_0280;
+/// `kIOMessageSystemHasPoweredOn`.
+const SYSTEM_HAS_POWERED_ON: u32 = 0xE000_0300;
+
+struct Watch {
+    /// Root power domain connection returned by `IORegisterForSystemPower`.
+    root_port: AtomicU32,
+    handler: PowerHandler,
+}
+
+extern "C" fn on_power(
+    refcon: *mut c_void,
+    _service: u32,
+    message_type: u32,
+    argument: *mut c_void,
+) {
+    // SAFETY: `refcon` is the `Watch` leaked in `watch_power`, which lives for the whole process.
+    let watch = unsafe { &*refcon.cast::<Watch>() };
+    let root_port = watch.root_port.load(Ordering::Acquire);
+    match message_type {
+        CAN_SYSTEM_SLEEP => {
+            // SAFETY: the notification id comes from this callback's argument.
+            unsafe { IOAllowPowerChange(root_port, argument as isize) };
+        }
+        SYSTEM_WILL_SLEEP => {
+            (watch.handler)(PowerEvent::Suspending);
+            // SAFETY: as above.
+            unsafe { IOAllowPowerChange(root_port, argument as isize) };
+        }
+        SYSTEM_HAS_POWERED_ON => (watch.handler)(PowerEvent::Resumed),
+        _ => {}
+    }
+}
+
+pub(super) fn watch_power(handler: PowerHandler) -> bool {
+    let (registered_tx, registered_rx) = std::sync::mpsc::sync_channel(1);
+    let spawned = std::thread::Builder::new()
+        .name("power-watch".into())
+        .spawn(move || {
+            let watch: &'static Watch = Box::leak(Box::new(Watch {
+                root_port: AtomicU32::new(0),
+                handler,
+            }));
+            let mut notify_port: *mut c_void = std::ptr::null_mut();
+            let mut notifier = 0u32;
+            // SAFETY: `watch` outlives the registration (leaked); the out pointers are valid, and
+            // the run loop source belongs to the port registered here, on this thread's run loop.
+            unsafe {
+                let root_port = IORegisterForSystemPower(
+                    (watch as *const Watch).cast_mut().cast(),
+                    &mut notify_port,
+                    on_power,
+                    &mut notifier,
+                );
+                if root_port == 0 || notify_port.is_null() {
+                    let _ = registered_tx.send(false);
+                    return;
+                }
+                watch.root_port.store(root_port, Ordering::Release);
+                CFRunLoopAddSource(
+                    CFRunLoopGetCurrent(),
+                    IONotificationPortGetRunLoopSource(notify_port),
+                    kCFRunLoopDefaultMode,
+                );
+                let _ = registered_tx.send(true);
+                CFRunLoopRun();
+            }
+        });
+    if spawned.is_err() {
+        return false;
+    }
+    let registered = registered_rx.recv().unwrap_or(false);
+    if !registered {
+        warn!("Failed to register for system power notifications, sleep is not detected");
+    }
+    registered
+}
@@ -0,0 +1,42 @@
+//! System sleep and wake notifications: logind's `PrepareForSleep` on Linux, IOKit system power
+//! notifications on macOS and the `PBT_*` power broadcasts on Windows.
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "windows")]
+mod windows;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerEvent {
+    /// The system is about to sleep; it waits for the handler to return (within a few seconds).
+    Suspending,
+    /// The system woke up.
+    Resumed,
+}
+
+/// Receives sleep and wake, on a thread of the platform listener.
+pub type PowerHandler = Box<dyn Fn(PowerEvent) + Send + Sync>;
+
+/// Starts listening for sleep and wake. False when not supported or the listener could not be
+/// registered.
+pub fn watch_power(handler: PowerHandler) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        linux::watch_power(handler)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        macos::watch_power(handler)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        windows::watch_power(handler)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    {
+        let _ = handler;
+        false
+    }
+}
@@ -0,0 +1,71 @@
+//! Windows: the power broadcasts that `WM_POWERBROADCAST` carries, through
+//! `PowerRegisterSuspendResumeNotification` so no window is needed. The system waits for the
+//! callback of `PBT_APMSUSPEND` (up to about two seconds) before it sleeps.
+
+use std::ffi::c_void;
+
+use tracing::warn;
+
+use super::{PowerEvent, PowerHandler};
+
+type DeviceNotifyCallback =
+    unsafe extern "system" fn(context: *mut c_void, kind: u32, setting: *mut c_void) -> u32;
+
+#[repr(C)]
+struct DeviceNotifySubscribeParameters {
+    callback: DeviceNotifyCallback,
+    context: *mut c_void,
+}
+
+#[link(name = "powrprof")]
+extern "system" {
+    fn PowerRegisterSuspendResumeNotification(
+        flags: u32,
+        recipient: *mut c_void,
+        registration: *mut *mut c_void,
+    ) -> u32;
+}
+
+const DEVICE_NOTIFY_CALLBACK: u32 = 2;
+const ERROR_SUCCESS: u32 = 0;
+const PBT_APMSUSPEND: u32 = 0x4;
+/// Sent on every wake; `PBT_APMRESUMESUSPEND` follows only when a user is present.
+const PBT_APMRESUMEAUTOMATIC: u32 = 0x12;
+
+unsafe extern "system" fn on_power(context: *mut c_void, kind: u32, _setting: *mut c_void) -> u32 {
+    // SAFETY: `context` is the handler leaked in `watch_power`, alive for the whole process.
+    let handler = unsafe { &*context.cast::<PowerHandler>() };
+    match kind {
+        PBT_APMSUSPEND => handler(PowerEvent::Suspending),
+        PBT_APMRESUMEAUTOMATIC => handler(PowerEvent::Resumed),
+        _ => {}
+    }
+    ERROR_SUCCESS
+}
+
+pub(super) fn watch_power(handler: PowerHandler) -> bool {
+    let handler: &'static PowerHandler = Box::leak(Box::new(handler));
+    let parameters: &'static mut DeviceNotifySubscribeParameters =
+        Box::leak(Box::new(DeviceNotifySubscribeParameters {
+            callback: on_power,
+            context: (handler as *const PowerHandler).cast_mut().cast(),
+        }));
+    let mut registration: *mut c_void = std::ptr::null_mut();
+    // SAFETY: the parameters and the handler they point to are leaked, so they outlive the
+    // registration, which is kept for the whole process.
+    let status = unsafe {
+        PowerRegisterSuspendResumeNotification(
+            DEVICE_NOTIFY_CALLBACK,
+            (parameters as *mut DeviceNotifySubscribeParameters).cast(),
+            &mut registration,
+        )
+    };
+    if status != ERROR_SUCCESS {
+        warn!(
+            status,
+            "Failed to register for suspend notifications, sleep is not detected"
+        );
+        return false;
+    }
+    true
+}
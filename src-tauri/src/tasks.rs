@@ -0,0 +1,411 @@
+//! Central registry for long-running background work (summaries, explanations, voice downloads,
+//! audio exports, screenshot reading).
+//!
+//! Each operation registers itself with the managed `TaskManager`, gets a `TaskHandle` with an ID,
+//! a cancellation token, and progress reporting, and is removed from the registry when it
+//! finishes. Every state change is emitted as a `task-updated` event so the UI has one consistent
+//! way to show and cancel long operations (`list_background_tasks` / `cancel_task`). A failed task
+//! keeps its structured `AppError` (code and, for rate limits, `retry_after_secs`) next to the
+//! message.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use nanoid::nanoid;
+use tauri::{Emitter, State};
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+
+use crate::error::AppError;
+use crate::util::unix_millis_now;
+
+/// Event emitted whenever a task starts, reports progress, or finishes.
+pub const TASK_UPDATED_EVENT: &str = "task-updated";
+
+/// Minimum progress change (0.0..=1.0) between two emitted progress events.
+const PROGRESS_EMIT_STEP: f32 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    Summarize,
+    Explain,
+    VoiceDownload,
+    AudioExport,
+    OcrCapture,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Snapshot of a task, returned by `list_background_tasks` and sent with `task-updated`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TaskInfo {
+    pub id: String,
+    pub kind: TaskKind,
+    pub label: String,
+    pub status: TaskStatus,
+    /// Fraction done (0.0..=1.0) when the task can measure it.
+    pub progress: Option<f32>,
+    pub message: Option<String>,
+    /// Why the task failed; `message` holds the same text.
+    pub error: Option<AppError>,
+    /// Unix timestamp (milliseconds) when the task started.
+    pub started_at: u64,
+}
+
+/// Cooperative cancellation flag that async code can also await.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    flag: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once `cancel` has been called. Use with `tokio::select!`.
+    pub async fn cancelled(&self) {
+        loop {
+            // Register before checking the flag so a concurrent cancel cannot be missed.
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+struct TaskEntry {
+    info: TaskInfo,
+    token: CancelToken,
+}
+
+/// Managed registry of running tasks. Cheap to clone (shared inner map).
+#[derive(Clone, Default)]
+pub struct TaskManager {
+    tasks: Arc<Mutex<HashMap<String, TaskEntry>>>,
+}
+
+pub type TaskNotifier = Arc<dyn Fn(&TaskInfo) + Send + Sync>;
+
+impl TaskManager {
+    /// Registers a new running task and emits its first `task-updated` event.
+    pub fn start<R: tauri::Runtime>(
+        &self,
+        app: &tauri::AppHandle<R>,
+        kind: TaskKind,
+        label: impl Into<String>,
+    ) -> TaskHandle {
+        let app = app.clone();
+        let notify: TaskNotifier = Arc::new(move |info: &TaskInfo| {
+            let _ = app.emit(TASK_UPDATED_EVENT, info);
+        });
+        self.start_with_notifier(kind, label, notify)
+    }
+
+    /// Like `start`, but state changes go to `notify` instead of `task-updated` events (the
+    /// headless CLI has no window to emit to).
+    pub fn start_with_notifier(
+        &self,
+        kind: TaskKind,
+        label: impl Into<String>,
+        notify: TaskNotifier,
+    ) -> TaskHandle {
+        let info = TaskInfo {
+            id: nanoid!(8),
+            kind,
+            label: label.into(),
+            status: TaskStatus::Running,
+            progress: None,
+            message: None,
+            error: None,
+            started_at: unix_millis_now(),
+        };
+        let token = CancelToken::default();
+
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.insert(
+                info.id.clone(),
+                TaskEntry {
+                    info: info.clone(),
+                    token: token.clone(),
+                },
+            );
+        }
+
+        debug!(id = %info.id, kind = ?info.kind, label = %info.label, "Task started");
+        notify(&info);
+
+        TaskHandle {
+            id: info.id,
+            token,
+            manager: self.clone(),
+            notify,
+            last_emitted_progress: None,
+            finished: false,
+        }
+    }
+
+    /// Snapshots of all running tasks, oldest first.
+    pub fn list(&self) -> Vec<TaskInfo> {
+        let mut list: Vec<TaskInfo> = self
+            .tasks
+            .lock()
+            .map(|tasks| tasks.values().map(|e| e.info.clone()).collect())
+            .unwrap_or_default();
+        list.sort_by_key(|t| t.started_at);
+        list
+    }
+
+    /// Requests cancellation of a running task. The task stops at its next cancellation point.
+    pub fn cancel(&self, id: &str) -> Result<(), String> {
+        let tasks = self
+            .tasks
+            .lock()
+            .map_err(|_| "Task registry lock poisoned".to_string())?;
+        let entry = tasks
+            .get(id)
+            .ok_or_else(|| format!("No running task with id {id}"))?;
+        entry.token.cancel();
+        debug!(id, "Task cancellation requested");
+        Ok(())
+    }
+
+    /// Requests cancellation of every running task. Returns how many were signalled.
+    pub fn cancel_all(&self) -> usize {
+        self.tasks
+            .lock()
+            .map(|tasks| {
+                tasks.values().for_each(|e| e.token.cancel());
+                tasks.len()
+            })
+            .unwrap_or(0)
+    }
+
+    fn update(&self, id: &str, apply: impl FnOnce(&mut TaskInfo)) -> Option<TaskInfo> {
+        let mut tasks = self.tasks.lock().ok()?;
+        let entry = tasks.get_mut(id)?;
+        apply(&mut entry.info);
+        Some(entry.info.clone())
+    }
+
+    fn remove(&self, id: &str) -> Option<TaskInfo> {
+        self.tasks.lock().ok()?.remove(id).map(|e| e.info)
+    }
+}
+
+/// Handle owned by the code running a task. Dropping it without `finish` marks the task failed
+/// (or cancelled, if cancellation was requested).
+pub struct TaskHandle {
+    id: String,
+    token: CancelToken,
+    manager: TaskManager,
+    notify: TaskNotifier,
+    last_emitted_progress: Option<f32>,
+    finished: bool,
+}
+
+impl TaskHandle {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn token(&self) -> &CancelToken {
+        &self.token
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Reports progress (clamped to 0.0..=1.0). Events are throttled to 1% steps.
+    pub fn set_progress(&mut self, progress: f32, message: Option<String>) {
+        let progress = progress.clamp(0.0, 1.0);
+        let should_emit = self
+            .last_emitted_progress
+            .is_none_or(|last| (progress - last).abs() >= PROGRESS_EMIT_STEP || progress >= 1.0);
+        let Some(info) = self.manager.update(&self.id, |info| {
+            info.progress = Some(progress);
+            if message.is_some() {
+                info.message = message;
+            }
+        }) else {
+            return;
+        };
+        if should_emit {
+            self.last_emitted_progress = Some(progress);
+            (self.notify)(&info);
+        }
+    }
+
+    /// Removes the task from the registry and emits its final state.
+    pub fn finish<T, E>(mut self, result: &Result<T, E>)
+    where
+        E: Clone + Into<AppError>,
+    {
+        let status = match result {
+            Ok(_) => TaskStatus::Completed,
+            Err(_) if self.is_cancelled() => TaskStatus::Cancelled,
+            Err(_) => TaskStatus::Failed,
+        };
+        let error = result.as_ref().err().map(|e| e.clone().into());
+        self.complete_with(status, error);
+    }
+
+    fn complete_with(&mut self, status: TaskStatus, error: Option<AppError>) {
+        self.finished = true;
+        let Some(mut info) = self.manager.remove(&self.id) else {
+            return;
+        };
+        info.status = status;
+        if let Some(error) = error {
+            info.message = Some(error.message());
+            info.error = Some(error);
+        }
+        if status == TaskStatus::Completed {
+            info.progress = Some(1.0);
+        }
+        debug!(id = %info.id, status = ?status, "Task finished");
+        (self.notify)(&info);
+    }
+}
+
+impl Drop for TaskHandle {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        if self.is_cancelled() {
+            self.complete_with(TaskStatus::Cancelled, None);
+        } else {
+            warn!(id = %self.id, "Task handle dropped without finishing");
+            self.complete_with(
+                TaskStatus::Failed,
+                Some(AppError::Internal("Task ended unexpectedly".to_string())),
+            );
+        }
+    }
+}
+
+// --- Commands ---
+
+/// Lists running background tasks (summaries, voice downloads, ...).
+#[tauri::command]
+pub fn list_background_tasks(state: State<'_, TaskManager>) -> Vec<TaskInfo> {
+    state.list()
+}
+
+/// Requests cancellation of a running background task.
+#[tauri::command]
+pub fn cancel_task(state: State<'_, TaskManager>, id: String) -> Result<(), String> {
+    state.cancel(&id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Notifier that records every state it is sent.
+    fn recorder() -> (TaskNotifier, Arc<Mutex<Vec<TaskInfo>>>) {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sink = sent.clone();
+        let notify: TaskNotifier = Arc::new(move |info: &TaskInfo| {
+            sink.lock().unwrap().push(info.clone());
+        });
+        (notify, sent)
+    }
+
+    fn last_status(sent: &Mutex<Vec<TaskInfo>>) -> Option<TaskStatus> {
+        sent.lock().unwrap().last().map(|info| info.status)
+    }
+
+    #[test]
+    fn test_dropped_handle_marks_the_task_failed() {
+        let manager = TaskManager::default();
+        let (notify, sent) = recorder();
+        drop(manager.start_with_notifier(TaskKind::Summarize, "summary", notify));
+
+        assert!(manager.list().is_empty());
+        assert_eq!(last_status(&sent), Some(TaskStatus::Failed));
+        let sent = sent.lock().unwrap();
+        let error = sent.last().and_then(|info| info.error.clone());
+        assert!(matches!(error, Some(AppError::Internal(_))));
+    }
+
+    #[test]
+    fn test_dropped_handle_after_cancel_marks_the_task_cancelled() {
+        let manager = TaskManager::default();
+        let (notify, sent) = recorder();
+        let handle = manager.start_with_notifier(TaskKind::VoiceDownload, "voice", notify);
+        manager.cancel(handle.id()).unwrap();
+        assert!(handle.is_cancelled());
+        drop(handle);
+
+        assert!(manager.list().is_empty());
+        assert_eq!(last_status(&sent), Some(TaskStatus::Cancelled));
+        assert!(sent.lock().unwrap().last().unwrap().error.is_none());
+    }
+
+    #[test]
+    fn test_finish_reports_once_and_drop_adds_nothing() {
+        let manager = TaskManager::default();
+        let (notify, sent) = recorder();
+        let handle = manager.start_with_notifier(TaskKind::AudioExport, "export", notify);
+        handle.finish::<(), AppError>(&Ok(()));
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 2, "start and finish only");
+        assert_eq!(sent[1].status, TaskStatus::Completed);
+        assert_eq!(sent[1].progress, Some(1.0));
+    }
+
+    #[test]
+    fn test_cancel_rejects_unknown_and_finished_tasks() {
+        let manager = TaskManager::default();
+        let (notify, _sent) = recorder();
+        assert!(manager.cancel("missing").is_err());
+
+        let handle = manager.start_with_notifier(TaskKind::Explain, "explain", notify);
+        let id = handle.id().to_string();
+        handle.finish::<(), AppError>(&Ok(()));
+        assert!(manager.cancel(&id).is_err());
+    }
+
+    #[test]
+    fn test_list_returns_oldest_first() {
+        let manager = TaskManager::default();
+        let (notify, _sent) = recorder();
+        let handles: Vec<TaskHandle> = [30, 10, 20]
+            .into_iter()
+            .map(|started_at| {
+                let handle = manager.start_with_notifier(
+                    TaskKind::OcrCapture,
+                    started_at.to_string(),
+                    notify.clone(),
+                );
+                manager.update(handle.id(), |info| info.started_at = started_at);
+                handle
+            })
+            .collect();
+
+        let labels: Vec<String> = manager.list().into_iter().map(|t| t.label).collect();
+        assert_eq!(labels, ["10", "20", "30"]);
+        assert_eq!(manager.cancel_all(), handles.len());
+    }
+}
@@ -0,0 +1,99 @@
+//! Maps words of the prepared (spoken) text back onto the original text.
+//!
+//! Preprocessing strips markup and normalizes punctuation, so spoken words are searched for in
+//! order, by their alphanumeric core, a bounded distance ahead of the previous match. Offsets are
+//! UTF-16 code units (JS string indices), which is what the webview needs to highlight.
+
+/// Bytes of source text searched for the next word before it is given up as removed by
+/// preprocessing (e.g. a link target).
+const WINDOW_BYTES: usize = 400;
+
+#[derive(Debug, Default)]
+pub struct Aligner {
+    source: String,
+    /// Byte offset after the last match, and the same offset in UTF-16 units.
+    cursor: usize,
+    cursor_utf16: usize,
+}
+
+impl Aligner {
+    pub fn new(source: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            cursor: 0,
+            cursor_utf16: 0,
+        }
+    }
+
+    /// Finds `word` (without surrounding punctuation) after the previous match. Returns its UTF-16
+    /// range in the source.
+    pub fn find(&mut self, word: &str) -> Option<(usize, usize)> {
+        let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+        if core.is_empty() {
+            return None;
+        }
+        let mut window_end = (self.cursor + WINDOW_BYTES).min(self.source.len());
+        while !self.source.is_char_boundary(window_end) {
+            window_end -= 1;
+        }
+        let found = self.source[self.cursor..window_end].find(core)?;
+        let start = self.cursor + found;
+        let char_start = self.cursor_utf16 + utf16_len(&self.source[self.cursor..start]);
+        let char_end = char_start + utf16_len(core);
+        self.cursor = start + core.len();
+        self.cursor_utf16 = char_end;
+        Some((char_start, char_end))
+    }
+
+    /// UTF-16 range spanning the words of `text` (e.g. a sentence), advancing past all of them.
+    pub fn find_span(&mut self, text: &str) -> Option<(usize, usize)> {
+        text.split_whitespace()
+            .filter_map(|word| self.find(word))
+            .fold(None, |span, (start, end)| match span {
+                None => Some((start, end)),
+                Some((first, _)) => Some((first, end)),
+            })
+    }
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.chars().map(char::len_utf16).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offsets_count_utf16_units_for_non_ascii_and_astral_text() {
+        let mut aligner = Aligner::new("café 😀 naïve «test»");
+        assert_eq!(aligner.find("café"), Some((0, 4)));
+        // The emoji takes two UTF-16 units.
+        assert_eq!(aligner.find("naïve,"), Some((8, 13)));
+        assert_eq!(aligner.find("\"test\""), Some((15, 19)));
+    }
+
+    #[test]
+    fn test_words_removed_by_preprocessing_are_skipped_without_moving_the_cursor() {
+        let mut aligner = Aligner::new("See [the docs](https://example.com/docs) for more.");
+        assert_eq!(aligner.find("See"), Some((0, 3)));
+        assert_eq!(aligner.find("link"), None);
+        assert_eq!(aligner.find("—"), None);
+        assert_eq!(aligner.find("the"), Some((5, 8)));
+        assert_eq!(aligner.find_span("docs for more."), Some((9, 49)));
+    }
+
+    #[test]
+    fn test_words_beyond_the_window_are_not_found() {
+        let far = format!("start {} end", "x ".repeat(WINDOW_BYTES));
+        let mut aligner = Aligner::new(&far);
+        assert_eq!(aligner.find("start"), Some((0, 5)));
+        assert_eq!(aligner.find("end"), None);
+
+        // A window ending inside a multi-byte character is cut back to a boundary.
+        let wide = format!("a{}", "é".repeat(WINDOW_BYTES));
+        let mut aligner = Aligner::new(&wide);
+        assert_eq!(aligner.find("b"), None);
+        assert_eq!(aligner.find("a"), Some((0, 1)));
+    }
+}
@@ -0,0 +1,312 @@
+//! Cleanup of pasted web pages and documents for reading: the editor's "Clear" action.
+//!
+//! The local pass is rule based and works offline: it drops code fences, URLs, citation markers
+//! like `[12]` or `[citation needed]`, boilerplate lines (cookie banners, "Skip to content", page
+//! numbers, running headers and footers repeated through the text), and joins words hyphenated
+//! across line breaks. It is also available as the `declutter` preprocessing stage. The cloud pass
+//! is the backend `TTS` task (see backend-api.md), which also rewrites text for narration.
+//!
+//! `text_cleanup_mode` picks `cloud` (default), `local`, or `local_then_cloud`; in the last mode a
+//! failed cloud call falls back to the local result. `text_cleanup_url` sends cloud cleanup to a
+//! different server than `backend_url`.
+
+use std::collections::HashMap;
+
+use tauri::State;
+use tracing::{debug, warn};
+
+use super::pipeline;
+use crate::backend;
+use crate::commands_config::ConfigState;
+use crate::offline;
+
+/// Backend task that cleans text for narration.
+const CLOUD_TASK: &str = "TTS";
+
+/// Lines no longer than this that appear `REPEATED_LINE_MIN` times are treated as running
+/// headers/footers.
+const REPEATED_LINE_MAX_CHARS: usize = 80;
+const REPEATED_LINE_MIN: usize = 3;
+
+/// Whole-line boilerplate, compared lowercased.
+const BOILERPLATE_LINES: &[&str] = &[
+    "advertisement",
+    "skip to content",
+    "skip to main content",
+    "share this article",
+    "share this",
+    "related articles",
+    "read more",
+    "back to top",
+    "accept all cookies",
+    "accept cookies",
+];
+
+/// Boilerplate line starts, compared lowercased.
+const BOILERPLATE_PREFIXES: &[&str] = &[
+    "we use cookies",
+    "this website uses cookies",
+    "sign up for our newsletter",
+    "subscribe to our newsletter",
+    "all rights reserved",
+    "copyright ©",
+    "©",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CleanupMode {
+    /// Only the local rules.
+    Local,
+    /// Only the backend (the behavior before local cleanup existed).
+    #[default]
+    Cloud,
+    /// Local rules first, so less is sent, then the backend.
+    LocalThenCloud,
+}
+
+impl CleanupMode {
+    /// Parses the `text_cleanup_mode` config value; unset or unknown values mean `Cloud`.
+    pub fn from_config(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Some("local") => Self::Local,
+            Some("local_then_cloud") => Self::LocalThenCloud,
+            Some("cloud") | Some("") | None => Self::Cloud,
+            Some(other) => {
+                warn!(value = %other, "Unknown text_cleanup_mode value, using cloud");
+                Self::Cloud
+            }
+        }
+    }
+
+    fn runs_local(self) -> bool {
+        matches!(self, Self::Local | Self::LocalThenCloud)
+    }
+}
+
+/// The local cleanup rules.
+pub fn declutter(text: &str) -> String {
+    let text = pipeline::cleanup(text);
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for line in text.lines().map(str::trim) {
+        if !line.is_empty() && line.chars().count() <= REPEATED_LINE_MAX_CHARS {
+            *counts.entry(line).or_default() += 1;
+        }
+    }
+
+    let mut out: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence
+            || is_boilerplate(trimmed)
+            || counts.get(trimmed).is_some_and(|n| *n >= REPEATED_LINE_MIN)
+        {
+            continue;
+        }
+        let cleaned = strip_citations(&strip_urls(line));
+        let cleaned = if cleaned == line {
+            cleaned
+        } else {
+            tidy_spaces(&cleaned)
+        };
+        if cleaned.trim().is_empty() {
+            // Keep paragraph breaks, but not the lines that only held a URL or citation.
+            if trimmed.is_empty() && out.last().is_some_and(|l| !l.is_empty()) {
+                out.push(String::new());
+            }
+            continue;
+        }
+        out.push(cleaned);
+    }
+    out.join("\n").trim().to_string()
+}
+
+fn is_boilerplate(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    BOILERPLATE_LINES.contains(&lower.as_str())
+        || BOILERPLATE_PREFIXES.iter().any(|p| lower.starts_with(p))
+        || is_page_number(&lower)
+}
+
+/// "12", "Page 3", "Page 3 of 10", "3 / 10", "- 4 -".
+fn is_page_number(line: &str) -> bool {
+    let line = line.trim_matches(|c: char| c == '-' || c.is_whitespace());
+    let line = line.strip_prefix("page").unwrap_or(line).trim();
+    let mut parts = line
+        .split(|c: char| c == '/' || c.is_whitespace())
+        .filter(|p| !p.is_empty() && *p != "of");
+    let is_number = |p: &str| p.len() <= 4 && p.chars().all(|c| c.is_ascii_digit());
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(page), None, None) => is_number(page),
+        (Some(page), Some(total), None) => is_number(page) && is_number(total),
+        _ => false,
+    }
+}
+
+/// Drops `http://`, `https://` and `www.` links, keeping punctuation that ends the sentence. A
+/// link that is alone in parentheses loses the parentheses too.
+fn strip_urls(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = find_url(rest) {
+        let end = start
+            + rest[start..]
+                .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | ')'))
+                .unwrap_or(rest.len() - start);
+        let url = rest[start..end].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        let after = &rest[start + url.len()..];
+        let mut before = &rest[..start];
+        let mut after_url = after;
+        if before.ends_with('(') && after.starts_with(')') {
+            before = &before[..before.len() - 1];
+            after_url = &after[1..];
+        }
+        out.push_str(before);
+        rest = after_url;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Byte offset of the next link that starts a word.
+fn find_url(text: &str) -> Option<usize> {
+    text.char_indices()
+        .filter(|(i, _)| {
+            text[..*i]
+                .chars()
+                .next_back()
+                .is_none_or(|c| c.is_whitespace() || matches!(c, '(' | '<' | '"'))
+        })
+        .map(|(i, _)| i)
+        .find(|i| {
+            let word = &text[*i..];
+            ["http://", "https://", "www."]
+                .iter()
+                .any(|p| word.len() > p.len() && word[..p.len()].eq_ignore_ascii_case(p))
+        })
+}
+
+/// Drops `[12]`, `[3, 4]`, `[5–7]` and `[citation needed]`.
+fn strip_citations(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(open) = rest.find('[') {
+        let Some(close) = rest[open..].find(']').map(|c| open + c) else {
+            break;
+        };
+        let inner = &rest[open + 1..close];
+        let is_citation = inner.eq_ignore_ascii_case("citation needed")
+            || (inner.chars().any(|c| c.is_ascii_digit())
+                && inner
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || matches!(c, ',' | ' ' | '-' | '–')));
+        out.push_str(&rest[..open]);
+        if !is_citation {
+            out.push_str(&rest[open..=close]);
+        }
+        rest = &rest[close + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Collapses the spaces left where something was removed, and drops spaces before punctuation.
+fn tidy_spaces(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    for c in line.trim_end().chars() {
+        if c == ' ' && out.ends_with(' ') {
+            continue;
+        }
+        if matches!(c, '.' | ',' | ';' | ':' | '!' | '?') && out.ends_with(' ') {
+            out.pop();
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Cleans `text` for reading with the configured `text_cleanup_mode`.
+#[tauri::command]
+pub async fn clean_text(
+    app: tauri::AppHandle,
+    state: State<'_, ConfigState>,
+    text: String,
+) -> Result<String, String> {
+    let (mode, url) = {
+        let cfg = state
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?;
+        (
+            CleanupMode::from_config(cfg.text_cleanup_mode.as_deref()),
+            cfg.text_cleanup_url.clone(),
+        )
+    };
+    // Offline mode: the cloud service is off, local cleanup still runs.
+    let mode = if offline::is_offline() {
+        CleanupMode::Local
+    } else {
+        mode
+    };
+    let text = if mode.runs_local() {
+        declutter(&text)
+    } else {
+        text
+    };
+    if mode == CleanupMode::Local || text.is_empty() {
+        debug!(len = text.len(), "Text cleanup: local only");
+        return Ok(text);
+    }
+    let result = backend::send_prompt_to(
+        url.as_deref(),
+        CLOUD_TASK.to_string(),
+        text.clone(),
+        None,
+        None,
+        None,
+    )
+    .await;
+    match result {
+        Ok(cleaned) => Ok(cleaned),
+        Err(e) => {
+            backend::notify_rate_limited(&app, CLOUD_TASK, &e);
+            if mode == CleanupMode::LocalThenCloud {
+                warn!(error = %e, "Cloud text cleanup failed, using the local result");
+                return Ok(text);
+            }
+            Err(e.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_declutter_drops_web_and_document_noise() {
+        let page = "Skip to content\nMy Blog\nThe ele-\nphant is large [12] and grey \
+                    (https://example.com/elephants).\nSee www.example.org, or [citation needed].\
+                    \n\n```\nlet x = 1;\n```\nMy Blog\nKeep [this] and the 3 items.\nPage 2 of 9\
+                    \nMy Blog\n\n\n© 2024 Example Inc.\nhttps://example.com\nThe end.";
+        assert_eq!(
+            declutter(page),
+            "The elephant is large and grey.\nSee, or.\n\nKeep [this] and the 3 items.\n\nThe end."
+        );
+    }
+
+    #[test]
+    fn test_cleanup_mode_from_config_falls_back_to_cloud() {
+        assert_eq!(
+            CleanupMode::from_config(Some(" Local_Then_Cloud ")),
+            CleanupMode::LocalThenCloud
+        );
+        assert_eq!(CleanupMode::from_config(Some("local")), CleanupMode::Local);
+        assert_eq!(CleanupMode::from_config(None), CleanupMode::Cloud);
+        assert_eq!(CleanupMode::from_config(Some("")), CleanupMode::Cloud);
+        assert_eq!(CleanupMode::from_config(Some("deep")), CleanupMode::Cloud);
+    }
+}
@@ -0,0 +1,373 @@
+//! Language detection and per-language voices.
+//!
+//! With a `voice_map` in config (`{"pt": "pt_BR-cadu-medium", "en": "en-US-AriaNeural"}`), the
+//! language of each read is detected (or taken from a read sent with one span covering it all, as
+//! translations are) and the mapped voice speaks it instead of the selected one.
+//! Keys are ISO 639-1 codes ("pt") or the ISO 639-3 codes the detector reports ("por"). The
+//! provider follows from the voice name: Piper voices look like `pt_BR-cadu-medium`, Microsoft
+//! voices end in `Neural`; other voices can name their provider (`polly:Camila`) and otherwise
+//! belong to the selected provider. Text too short or too mixed to detect keeps the selected voice.
+//!
+//! Within a read, stretches in another language than the voice's can be tagged for the voices
+//! that take SSML `<lang>` (see `ssml::LanguageTags`): spans sent with the read, or, with
+//! `ssml_language_tags` on, sentences and quotes detected in another language.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use tracing::debug;
+
+use super::segment::Segment;
+use super::ssml::LanguageTags;
+use crate::config::FullConfig;
+
+/// Shortest text (in characters) worth detecting; shorter text keeps the selected voice.
+const MIN_DETECT_CHARS: usize = 20;
+
+/// Language of `text` as an ISO 639-3 code ("eng", "por"), when the detection is reliable.
+pub fn detect(text: &str) -> Option<&'static str> {
+    if text.chars().filter(|c| c.is_alphabetic()).count() < MIN_DETECT_CHARS {
+        return None;
+    }
+    let info = whatlang::detect(text)?;
+    info.is_reliable().then(|| info.lang().code())
+}
+
+/// ISO 639-1 code for an ISO 639-3 code reported by the detector.
+fn iso_639_1(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "afr" => "af",
+        "aka" => "ak",
+        "amh" => "am",
+        "ara" => "ar",
+        "aze" => "az",
+        "bel" => "be",
+        "ben" => "bn",
+        "bul" => "bg",
+        "cat" => "ca",
+        "ces" => "cs",
+        "cmn" => "zh",
+        "dan" => "da",
+        "deu" => "de",
+        "ell" => "el",
+        "eng" => "en",
+        "epo" => "eo",
+        "est" => "et",
+        "fin" => "fi",
+        "fra" => "fr",
+        "guj" => "gu",
+        "heb" => "he",
+        "hin" => "hi",
+        "hrv" => "hr",
+        "hun" => "hu",
+        "hye" => "hy",
+        "ind" => "id",
+        "ita" => "it",
+        "jav" => "jv",
+        "jpn" => "ja",
+        "kan" => "kn",
+        "kat" => "ka",
+        "khm" => "km",
+        "kor" => "ko",
+        "lat" => "la",
+        "lav" => "lv",
+        "lit" => "lt",
+        "mal" => "ml",
+        "mar" => "mr",
+        "mkd" => "mk",
+        "mya" => "my",
+        "nep" => "ne",
+        "nld" => "nl",
+        "nob" => "nb",
+        "ori" => "or",
+        "pan" => "pa",
+        "pes" => "fa",
+        "pol" => "pl",
+        "por" => "pt",
+        "ron" => "ro",
+        "rus" => "ru",
+        "sin" => "si",
+        "slk" => "sk",
+        "slv" => "sl",
+        "sna" => "sn",
+        "spa" => "es",
+        "srp" => "sr",
+        "swe" => "sv",
+        "tam" => "ta",
+        "tel" => "te",
+        "tgl" => "tl",
+        "tha" => "th",
+        "tuk" => "tk",
+        "tur" => "tr",
+        "ukr" => "uk",
+        "urd" => "ur",
+        "uzb" => "uz",
+        "vie" => "vi",
+        "yid" => "yi",
+        "zul" => "zu",
+        _ => return None,
+    })
+}
+
+/// Locale for a bare language code: the one Polly and Microsoft voices most commonly use.
+fn default_locale(short: &str) -> Option<&'static str> {
+    Some(match short {
+        "ar" => "ar-SA",
+        "ca" => "ca-ES",
+        "cs" => "cs-CZ",
+        "da" => "da-DK",
+        "de" => "de-DE",
+        "el" => "el-GR",
+        "en" => "en-US",
+        "es" => "es-ES",
+        "fi" => "fi-FI",
+        "fr" => "fr-FR",
+        "he" => "he-IL",
+        "hi" => "hi-IN",
+        "hu" => "hu-HU",
+        "id" => "id-ID",
+        "it" => "it-IT",
+        "ja" => "ja-JP",
+        "ko" => "ko-KR",
+        "nb" => "nb-NO",
+        "nl" => "nl-NL",
+        "pl" => "pl-PL",
+        "pt" => "pt-BR",
+        "ro" => "ro-RO",
+        "ru" => "ru-RU",
+        "sv" => "sv-SE",
+        "th" => "th-TH",
+        "tr" => "tr-TR",
+        "uk" => "uk-UA",
+        "vi" => "vi-VN",
+        "zh" => "zh-CN",
+        _ => return None,
+    })
+}
+
+/// BCP-47 tag for a language code: "pt_BR" and "pt-BR" are kept, bare ISO 639-1 ("pt") and
+/// 639-3 ("por") codes get their default locale.
+pub fn language_tag(code: &str) -> Option<String> {
+    let code = code.trim().replace('_', "-");
+    if code.contains('-') {
+        return Some(code);
+    }
+    let lower = code.to_lowercase();
+    let short = match lower.len() {
+        2 => lower.as_str(),
+        3 => iso_639_1(&lower)?,
+        _ => return None,
+    };
+    Some(default_locale(short).unwrap_or(short).to_string())
+}
+
+/// Primary language subtag ("pt" for "pt_BR", "pt-BR" or "por").
+fn primary_language(code: &str) -> Option<String> {
+    let primary = code.split(['-', '_']).next()?.trim().to_lowercase();
+    match primary.len() {
+        2 => Some(primary),
+        3 => iso_639_1(&primary).map(str::to_string),
+        _ => None,
+    }
+}
+
+/// A stretch of a read in a given language, sent with the read: a byte range into its text and
+/// a language code ("pt", "pt-BR").
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct LanguageSpan {
+    pub start: usize,
+    pub end: usize,
+    pub language: String,
+}
+
+/// Tags for the spans sent with `text`. Spans that are out of range or name no language are
+/// dropped.
+pub fn tags_from_spans(text: &str, spans: &[LanguageSpan]) -> LanguageTags {
+    LanguageTags::new(
+        spans
+            .iter()
+            .filter_map(|span| {
+                let stretch = text.get(span.start..span.end)?;
+                // Chunks are whitespace-normalized (see `segment`).
+                let stretch = stretch.split_whitespace().collect::<Vec<_>>().join(" ");
+                Some((stretch, language_tag(&span.language)?))
+            })
+            .collect(),
+    )
+}
+
+/// Tags for the sentences, and the quotes inside the other sentences, detected in another
+/// language than the voice's (`voice_language`; when unknown, the language of the whole text).
+pub fn detect_tags(segments: &[Segment], voice_language: Option<&str>) -> LanguageTags {
+    let base = match voice_language.and_then(primary_language) {
+        Some(base) => base,
+        None => {
+            let text = segments
+                .iter()
+                .map(|segment| segment.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let Some(base) = detect(&text).and_then(iso_639_1) else {
+                return LanguageTags::default();
+            };
+            base.to_string()
+        }
+    };
+    let foreign = |text: &str| {
+        let code = detect(text)?;
+        (iso_639_1(code)? != base).then(|| language_tag(code))?
+    };
+    let mut tags = Vec::new();
+    for segment in segments {
+        if let Some(tag) = foreign(&segment.text) {
+            tags.push((segment.text.clone(), tag));
+            continue;
+        }
+        for quote in quotes(&segment.text) {
+            if let Some(tag) = foreign(quote) {
+                tags.push((quote.to_string(), tag));
+            }
+        }
+    }
+    if !tags.is_empty() {
+        debug!(
+            stretches = tags.len(),
+            "Tagging stretches in another language"
+        );
+    }
+    LanguageTags::new(tags)
+}
+
+/// Quoted stretches of `text`, without the quote marks.
+fn quotes(text: &str) -> Vec<&str> {
+    const PAIRS: [(char, char); 4] = [('"', '"'), ('“', '”'), ('«', '»'), ('„', '“')];
+    let mut out = Vec::new();
+    for (open, close) in PAIRS {
+        let mut rest = text;
+        while let Some(start) = rest.find(open) {
+            let inner = &rest[start + open.len_utf8()..];
+            let Some(len) = inner.find(close) else {
+                break;
+            };
+            out.push(inner[..len].trim());
+            rest = &inner[len + close.len_utf8()..];
+        }
+    }
+    out
+}
+
+/// Language of a read sent with one span covering all of `text` (e.g. a translation), which
+/// then picks the voice instead of detection.
+pub fn whole_text_language<'a>(text: &str, spans: &'a [LanguageSpan]) -> Option<&'a str> {
+    match spans {
+        [span] if span.start == 0 && span.end >= text.trim_end().len() => Some(&span.language),
+        _ => None,
+    }
+}
+
+/// Mapped voice for a language (ISO 639-3 or 639-1), by its 639-1 or 639-3 key.
+fn mapped_voice<'a>(map: &'a HashMap<String, String>, code: &str) -> Option<&'a str> {
+    iso_639_1(code)
+        .and_then(|short| map.get(short))
+        .or_else(|| map.get(code))
+        .map(|voice| voice.trim())
+        .filter(|voice| !voice.is_empty())
+}
+
+/// Provider and voice for a `voice_map` value; `None` as provider means the selected one.
+fn voice_target(value: &str) -> (Option<&str>, &str) {
+    if let Some((provider, voice)) = value.split_once(':') {
+        if ["piper", "microsoft", "polly", "system", "mobile", "custom"].contains(&provider) {
+            return (Some(provider), voice);
+        }
+    }
+    let region = value.split('-').next().unwrap_or("");
+    if region.contains('_') {
+        (Some("piper"), value)
+    } else if value.ends_with("Neural") {
+        (Some("microsoft"), value)
+    } else {
+        (None, value)
+    }
+}
+
+/// Switches `cfg` to the voice mapped to `language` ("pt", "pt-BR"), or without it to the
+/// detected language of `text`, if any. Returns the language when a mapped voice was applied.
+pub fn apply_voice_map(cfg: &mut FullConfig, text: &str, language: Option<&str>) -> Option<String> {
+    let map = cfg.voice_map.as_ref().filter(|map| !map.is_empty())?;
+    let code = match language {
+        Some(language) => primary_language(language)?,
+        None => detect(text)?.to_string(),
+    };
+    let value = mapped_voice(map, &code)?.to_string();
+    let (provider, voice) = voice_target(&value);
+    if let Some(provider) = provider {
+        cfg.voice_provider = Some(provider.to_string());
+    }
+    let voice = Some(voice.to_string());
+    match cfg.voice_provider.as_deref() {
+        Some("piper") => cfg.selected_voice = voice,
+        Some("polly") => cfg.selected_polly_voice = voice,
+        Some("system") => cfg.selected_system_voice = voice,
+        Some("mobile") => cfg.selected_mobile_voice = voice,
+        Some("custom") => cfg.custom_tts_speaker = voice,
+        _ => cfg.selected_microsoft_voice = voice,
+    }
+    debug!(language = %code, voice = %value, "Using the voice mapped to the read's language");
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_voice_map_values_name_their_provider() {
+        assert_eq!(
+            voice_target("pt_BR-cadu-medium"),
+            (Some("piper"), "pt_BR-cadu-medium")
+        );
+        assert_eq!(
+            voice_target("en-US-AriaNeural"),
+            (Some("microsoft"), "en-US-AriaNeural")
+        );
+        assert_eq!(voice_target("polly:Camila"), (Some("polly"), "Camila"));
+        assert_eq!(voice_target("Camila"), (None, "Camila"));
+
+        let map = HashMap::from([
+            ("pt".to_string(), "pt_BR-cadu-medium".to_string()),
+            ("deu".to_string(), "de-DE-KatjaNeural".to_string()),
+        ]);
+        assert_eq!(mapped_voice(&map, "por"), Some("pt_BR-cadu-medium"));
+        assert_eq!(mapped_voice(&map, "deu"), Some("de-DE-KatjaNeural"));
+        assert_eq!(mapped_voice(&map, "eng"), None);
+    }
+
+    #[test]
+    fn test_tags_spans_and_finds_quotes() {
+        assert_eq!(
+            quotes("She said \"je ne sais pas\" and «¿qué?» twice, \"unclosed"),
+            vec!["je ne sais pas", "¿qué?"]
+        );
+
+        let text = "Say  hola\n amigo twice.";
+        let spans = [
+            LanguageSpan {
+                start: 4,
+                end: 16,
+                language: "es".to_string(),
+            },
+            LanguageSpan {
+                start: 4,
+                end: 99,
+                language: "es".to_string(),
+            },
+        ];
+        assert_eq!(
+            tags_from_spans(text, &spans),
+            LanguageTags::new(vec![("hola amigo".to_string(), "es-ES".to_string())])
+        );
+        assert_eq!(language_tag("pt_BR").as_deref(), Some("pt-BR"));
+        assert_eq!(language_tag("deu").as_deref(), Some("de-DE"));
+    }
+}
@@ -0,0 +1,434 @@
+//! Pronunciation lexicon: user-edited substitutions applied to text before it is spoken.
+//!
+//! Entries live in `lexicon.json` in the config dir (with a profile active, the profile's own
+//! file). A `word` entry replaces a whole word, matched case-sensitively; a `regex` entry replaces
+//! every match and may use `$1`-style group references. Entries with a `language` ("en", "pt-BR")
+//! only apply when the current voice speaks that language.
+//!
+//! A word entry may also carry an IPA `phoneme`. Polly and Microsoft speak such words through an
+//! SSML `<phoneme>` override; Piper has no SSML and speaks the replacement instead. Plain
+//! substitutions run in the preprocessing pipeline, phoneme entries only at synthesis, so the
+//! displayed and highlighted text keeps the original word.
+
+use std::borrow::Cow;
+use std::fs;
+use std::path::PathBuf;
+
+use nanoid::nanoid;
+use regex::{NoExpand, Regex};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::ssml;
+use crate::config::FullConfig;
+use crate::paths;
+
+const LEXICON_FILE_NAME: &str = "lexicon.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    Word,
+    Regex,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LexiconEntry {
+    pub id: String,
+    pub kind: EntryKind,
+    pub pattern: String,
+    #[serde(default)]
+    pub replacement: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// IPA pronunciation, for word entries only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phoneme: Option<String>,
+}
+
+fn lexicon_path() -> Result<PathBuf, String> {
+    Ok(paths::get_config_dir()?.join(LEXICON_FILE_NAME))
+}
+
+/// Reads the lexicon; a missing or unreadable file is an empty lexicon.
+pub fn load_entries() -> Vec<LexiconEntry> {
+    let Ok(path) = lexicon_path() else {
+        return Vec::new();
+    };
+    let Ok(data) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    serde_json::from_str(&data).unwrap_or_else(|e| {
+        warn!(error = %e, path = %path.display(), "Failed to parse lexicon, ignoring it");
+        Vec::new()
+    })
+}
+
+fn save_entries(entries: &[LexiconEntry]) -> Result<(), String> {
+    let path = lexicon_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {e}"))?;
+    }
+    let data = serde_json::to_string_pretty(entries)
+        .map_err(|e| format!("Failed to serialize lexicon: {e}"))?;
+    fs::write(&path, data).map_err(|e| format!("Failed to write lexicon: {e}"))
+}
+
+/// Regex matching `word` as a whole word. Half boundaries also work for words that start or end
+/// with punctuation ("C++", ".NET").
+fn word_regex(words: &[&str]) -> Result<Regex, regex::Error> {
+    let alternatives: Vec<String> = words.iter().map(|w| regex::escape(w)).collect();
+    Regex::new(&format!(
+        r"\b{{start-half}}(?:{})\b{{end-half}}",
+        alternatives.join("|")
+    ))
+}
+
+fn normalize_language(tag: &str) -> String {
+    tag.trim().replace('_', "-").to_ascii_lowercase()
+}
+
+/// True when an entry for `entry` applies to a voice speaking `voice` ("en" covers "en-US").
+/// Entries without a language always apply.
+fn language_matches(entry: Option<&str>, voice: Option<&str>) -> bool {
+    let Some(entry) = entry.map(normalize_language).filter(|l| !l.is_empty()) else {
+        return true;
+    };
+    let Some(voice) = voice.map(normalize_language) else {
+        return false;
+    };
+    voice == entry || voice.starts_with(&format!("{entry}-"))
+}
+
+/// Language of the configured voice, from its name ("pt_BR-cadu-medium", "en-US-AriaNeural").
+/// Polly, system and mobile voice names carry no language, so the UI language stands in.
+pub fn voice_language(cfg: &FullConfig) -> Option<String> {
+    match cfg.voice_provider.as_deref() {
+        Some("piper") => cfg
+            .selected_voice
+            .as_deref()
+            .and_then(|voice| voice.split('-').next())
+            .map(str::to_string),
+        Some("polly") | Some("system") | Some("mobile") => cfg.ui_language.clone(),
+        Some("custom") => cfg
+            .custom_tts_language
+            .clone()
+            .or_else(|| cfg.ui_language.clone()),
+        _ => {
+            let voice = cfg
+                .selected_microsoft_voice
+                .as_deref()
+                .unwrap_or("en-US-AriaNeural");
+            let mut parts = voice.splitn(3, '-');
+            match (parts.next(), parts.next()) {
+                (Some(lang), Some(region)) => Some(format!("{lang}-{region}")),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Validates and appends an entry; returns it with its new id.
+pub fn add_entry(
+    kind: EntryKind,
+    pattern: String,
+    replacement: String,
+    language: Option<String>,
+    phoneme: Option<String>,
+) -> Result<LexiconEntry, String> {
+    let pattern = pattern.trim().to_string();
+    if pattern.is_empty() {
+        return Err("Lexicon pattern cannot be empty".to_string());
+    }
+    let phoneme = phoneme
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty());
+    match kind {
+        EntryKind::Word => {
+            word_regex(&[&pattern]).map_err(|e| format!("Invalid word: {e}"))?;
+        }
+        EntryKind::Regex => {
+            if phoneme.is_some() {
+                return Err("Phoneme overrides are only supported for word entries".to_string());
+            }
+            Regex::new(&pattern).map_err(|e| format!("Invalid regex: {e}"))?;
+        }
+    }
+    let entry = LexiconEntry {
+        id: nanoid!(8),
+        kind,
+        pattern,
+        replacement,
+        language: language
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty()),
+        phoneme,
+    };
+    let mut entries = load_entries();
+    entries.push(entry.clone());
+    save_entries(&entries)?;
+    Ok(entry)
+}
+
+/// Removes the entry with `id`. Returns whether it existed.
+pub fn remove_entry(id: &str) -> Result<bool, String> {
+    let mut entries = load_entries();
+    let before = entries.len();
+    entries.retain(|e| e.id != id);
+    if entries.len() == before {
+        return Ok(false);
+    }
+    save_entries(&entries)?;
+    Ok(true)
+}
+
+/// Plain (non-phoneme) substitutions for one voice language, run as a pipeline step.
+#[derive(Debug, Clone)]
+pub struct Substitutions {
+    rules: Vec<(Regex, String, EntryKind)>,
+    fingerprint: String,
+}
+
+impl PartialEq for Substitutions {
+    fn eq(&self, other: &Self) -> bool {
+        self.fingerprint == other.fingerprint
+    }
+}
+
+impl Eq for Substitutions {}
+
+impl Substitutions {
+    /// Compiles the matching entries; `None` when there are none.
+    pub fn new(entries: &[LexiconEntry], language: Option<&str>) -> Option<Self> {
+        let entries: Vec<&LexiconEntry> = entries
+            .iter()
+            .filter(|e| e.phoneme.is_none() && language_matches(e.language.as_deref(), language))
+            .collect();
+        let rules: Vec<(Regex, String, EntryKind)> = entries
+            .iter()
+            .filter_map(|e| {
+                let regex = match e.kind {
+                    EntryKind::Word => word_regex(&[&e.pattern]),
+                    EntryKind::Regex => Regex::new(&e.pattern),
+                };
+                match regex {
+                    Ok(regex) => Some((regex, e.replacement.clone(), e.kind)),
+                    Err(err) => {
+                        warn!(id = %e.id, error = %err, "Skipping invalid lexicon entry");
+                        None
+                    }
+                }
+            })
+            .collect();
+        if rules.is_empty() {
+            return None;
+        }
+        let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+        Some(Self {
+            rules,
+            fingerprint: format!("lexicon:{}", ids.join("+")),
+        })
+    }
+
+    /// Builds the substitutions for the configured voice.
+    pub fn for_config(cfg: &FullConfig) -> Option<Self> {
+        Self::new(&load_entries(), voice_language(cfg).as_deref())
+    }
+
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        self.rules
+            .iter()
+            .fold(text.to_string(), |text, (regex, replacement, kind)| {
+                match kind {
+                    EntryKind::Word => regex.replace_all(&text, NoExpand(replacement)),
+                    EntryKind::Regex => regex.replace_all(&text, replacement.as_str()),
+                }
+                .into_owned()
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pronunciation {
+    word: String,
+    ipa: String,
+    replacement: String,
+}
+
+/// Phoneme entries for one voice language, applied by the providers at synthesis.
+#[derive(Debug, Clone, Default)]
+pub struct Pronunciations {
+    entries: Vec<Pronunciation>,
+    regex: Option<Regex>,
+}
+
+impl PartialEq for Pronunciations {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl Eq for Pronunciations {}
+
+impl Pronunciations {
+    pub fn new(entries: &[LexiconEntry], language: Option<&str>) -> Self {
+        let mut entries: Vec<Pronunciation> = entries
+            .iter()
+            .filter(|e| e.kind == EntryKind::Word)
+            .filter(|e| language_matches(e.language.as_deref(), language))
+            .filter_map(|e| {
+                Some(Pronunciation {
+                    word: e.pattern.clone(),
+                    ipa: e.phoneme.clone()?,
+                    replacement: e.replacement.clone(),
+                })
+            })
+            .collect();
+        // Longest first, so "New York" wins over "New".
+        entries.sort_by_key(|e| std::cmp::Reverse(e.word.len()));
+        let words: Vec<&str> = entries.iter().map(|e| e.word.as_str()).collect();
+        let regex = (!words.is_empty())
+            .then(|| word_regex(&words))
+            .and_then(Result::ok);
+        Self { entries, regex }
+    }
+
+    /// Builds the phoneme overrides for the configured voice.
+    pub fn for_config(cfg: &FullConfig) -> Self {
+        Self::new(&load_entries(), voice_language(cfg).as_deref())
+    }
+
+    fn lookup(&self, word: &str) -> Option<&Pronunciation> {
+        self.entries.iter().find(|e| e.word == word)
+    }
+
+    /// `text` as an SSML fragment (escaped, without `<speak>`) with `<phoneme>` overrides, or
+    /// `None` when no override applies.
+    pub fn to_ssml(&self, text: &str) -> Option<String> {
+        let (ssml, overridden) = self.render_ssml(text, ssml::escape);
+        overridden.then_some(ssml)
+    }
+
+    /// `text` as SSML with overridden words as `<phoneme>` and everything between them through
+    /// `plain` (which must escape it). Also returns whether any word was overridden.
+    pub fn render_ssml(&self, text: &str, plain: impl Fn(&str) -> String) -> (String, bool) {
+        let mut out = String::with_capacity(text.len() + 64);
+        let mut last = 0;
+        let mut overridden = false;
+        for m in self.regex.iter().flat_map(|regex| regex.find_iter(text)) {
+            let Some(entry) = self.lookup(m.as_str()) else {
+                continue;
+            };
+            out.push_str(&plain(&text[last..m.start()]));
+            out.push_str(&format!(
+                "<phoneme alphabet=\"ipa\" ph=\"{}\">{}</phoneme>",
+                ssml::escape(&entry.ipa),
+                ssml::escape(m.as_str())
+            ));
+            last = m.end();
+            overridden = true;
+        }
+        out.push_str(&plain(&text[last..]));
+        (out, overridden)
+    }
+
+    /// `text` with overridden words replaced by their replacement text, for voices without SSML.
+    pub fn respell<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let Some(regex) = &self.regex else {
+            return Cow::Borrowed(text);
+        };
+        regex.replace_all(text, |caps: &regex::Captures| {
+            let word = &caps[0];
+            match self.lookup(word) {
+                Some(entry) if !entry.replacement.is_empty() => entry.replacement.clone(),
+                _ => word.to_string(),
+            }
+        })
+    }
+}
+
+#[tauri::command]
+pub fn lexicon_list() -> Vec<LexiconEntry> {
+    load_entries()
+}
+
+/// Adds a lexicon entry. `kind` is "word" or "regex"; `phoneme` (IPA) is for word entries.
+#[tauri::command]
+pub fn lexicon_add(
+    kind: EntryKind,
+    pattern: String,
+    replacement: Option<String>,
+    language: Option<String>,
+    phoneme: Option<String>,
+) -> Result<LexiconEntry, String> {
+    add_entry(
+        kind,
+        pattern,
+        replacement.unwrap_or_default(),
+        language,
+        phoneme,
+    )
+}
+
+#[tauri::command]
+pub fn lexicon_remove(id: String) -> Result<bool, String> {
+    remove_entry(&id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(kind: EntryKind, pattern: &str, replacement: &str) -> LexiconEntry {
+        LexiconEntry {
+            id: pattern.to_string(),
+            kind,
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            language: None,
+            phoneme: None,
+        }
+    }
+
+    fn entries() -> [LexiconEntry; 4] {
+        let mut french = entry(EntryKind::Word, "chat", "shah");
+        french.language = Some("fr".to_string());
+        let mut nginx = entry(EntryKind::Word, "nginx", "engine x");
+        nginx.phoneme = Some("ˈɛndʒɪn ɛks".to_string());
+        [
+            entry(EntryKind::Word, "C++", "C plus plus"),
+            entry(EntryKind::Regex, r"v(\d+)\.(\d+)", "version $1 point $2"),
+            french,
+            nginx,
+        ]
+    }
+
+    #[test]
+    fn test_substitutions_apply_whole_words_regexes_and_language_entries() {
+        let entries = entries();
+        let subs = Substitutions::new(&entries, Some("en_US")).unwrap();
+        assert_eq!(
+            subs.apply("chat about C++ and C++11 v2.5 nginx"),
+            "chat about C plus plus and C++11 version 2 point 5 nginx"
+        );
+        let subs = Substitutions::new(&entries, Some("fr-FR")).unwrap();
+        assert_eq!(subs.apply("chat"), "shah");
+    }
+
+    #[test]
+    fn test_pronunciations_become_ssml_phonemes_or_respellings() {
+        let entries = entries();
+        let prons = Pronunciations::new(&entries, Some("en-US"));
+        assert_eq!(
+            prons.to_ssml("Tom & nginx").as_deref(),
+            Some("Tom &amp; <phoneme alphabet=\"ipa\" ph=\"ˈɛndʒɪn ɛks\">nginx</phoneme>")
+        );
+        assert_eq!(prons.to_ssml("no override"), None);
+        assert_eq!(prons.respell("run nginx now"), "run engine x now");
+    }
+}
@@ -0,0 +1,188 @@
+//! Text preparation for speech: the preprocessing pipeline (with the pronunciation lexicon and the
+//! optional profanity filter), then whitespace normalization and sentence segmentation, with a
+//! session-wide cache.
+//!
+//! Replays, speed changes, and re-reads of the same document send identical text again; the
+//! processed segment list is cached by text hash + pipeline fingerprint so only the first pass
+//! pays for it. The cache is in-memory only and bounded by entry count and total size.
+
+pub mod align;
+pub mod cleanup;
+pub mod language;
+pub mod lexicon;
+pub mod pipeline;
+pub mod profanity;
+pub mod readability;
+pub mod reading_mode;
+pub mod segment;
+pub mod ssml;
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use tauri::State;
+use tracing::debug;
+
+use crate::commands_config::ConfigState;
+use pipeline::{Pipeline, StageOutput};
+use segment::Segment;
+
+/// Maximum number of cached texts.
+const CACHE_MAX_ENTRIES: usize = 64;
+/// Maximum total cached source text, in bytes.
+const CACHE_MAX_BYTES: usize = 8 * 1024 * 1024;
+
+/// Segmented, normalized text ready to be spoken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedText {
+    pub segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct CacheKey {
+    text_hash: u64,
+    text_len: usize,
+    pipeline_hash: u64,
+}
+
+struct CacheEntry {
+    prepared: Arc<PreparedText>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Cache {
+    entries: HashMap<CacheKey, CacheEntry>,
+    bytes: usize,
+    clock: u64,
+}
+
+impl Cache {
+    fn get(&mut self, key: &CacheKey) -> Option<Arc<PreparedText>> {
+        self.clock += 1;
+        let clock = self.clock;
+        self.entries.get_mut(key).map(|entry| {
+            entry.last_used = clock;
+            Arc::clone(&entry.prepared)
+        })
+    }
+
+    fn insert(&mut self, key: CacheKey, prepared: Arc<PreparedText>) {
+        if key.text_len > CACHE_MAX_BYTES {
+            return;
+        }
+        if self.entries.remove(&key).is_some() {
+            self.bytes = self.bytes.saturating_sub(key.text_len);
+        }
+        while !self.entries.is_empty()
+            && (self.entries.len() >= CACHE_MAX_ENTRIES
+                || self.bytes + key.text_len > CACHE_MAX_BYTES)
+        {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key);
+            let Some(oldest) = oldest else {
+                break;
+            };
+            self.entries.remove(&oldest);
+            self.bytes = self.bytes.saturating_sub(oldest.text_len);
+        }
+        self.clock += 1;
+        self.bytes += key.text_len;
+        self.entries.insert(
+            key,
+            CacheEntry {
+                prepared,
+                last_used: self.clock,
+            },
+        );
+    }
+}
+
+static CACHE: Mutex<Option<Cache>> = Mutex::new(None);
+
+fn hash_str(value: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Runs `pipeline` on `text` and segments the result, reusing the cached result for the same text
+/// and pipeline.
+pub fn prepare(text: &str, pipeline: &Pipeline) -> Arc<PreparedText> {
+    let key = CacheKey {
+        text_hash: hash_str(text),
+        text_len: text.len(),
+        pipeline_hash: hash_str(&pipeline.fingerprint()),
+    };
+    if let Ok(mut cache) = CACHE.lock() {
+        if let Some(prepared) = cache.get_or_insert_with(Cache::default).get(&key) {
+            debug!(len = text.len(), "Prepared text cache hit");
+            return prepared;
+        }
+    }
+
+    let prepared = Arc::new(PreparedText {
+        segments: segment::segment(&pipeline.run(text)),
+    });
+    if let Ok(mut cache) = CACHE.lock() {
+        cache
+            .get_or_insert_with(Cache::default)
+            .insert(key, Arc::clone(&prepared));
+    }
+    prepared
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PreprocessingPreview {
+    /// Text after each configured stage, in order.
+    pub stages: Vec<StageOutput>,
+    /// Final sentence segments, as sent to the voice.
+    pub segments: Vec<String>,
+}
+
+/// Runs the configured preprocessing pipeline on `text` and returns the text after each stage
+/// plus the resulting segments (for debugging cleanup rules).
+#[tauri::command]
+pub fn preview_preprocessing(
+    state: State<'_, ConfigState>,
+    text: String,
+) -> Result<PreprocessingPreview, String> {
+    let pipeline = {
+        let cfg = state
+            .lock()
+            .map_err(|_| "Config lock poisoned".to_string())?;
+        Pipeline::for_config(&cfg)
+    };
+    let stages = pipeline.run_with_trace(&text);
+    let processed = stages.last().map_or(text.as_str(), |s| s.text.as_str());
+    let segments = segment::segment(processed)
+        .into_iter()
+        .map(|s| s.text)
+        .collect();
+    Ok(PreprocessingPreview { stages, segments })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prepare_reuses_cached_result_per_pipeline() {
+        let text = "First  sentence. Second\nsentence.\n\nNew paragraph.";
+        let pipeline = Pipeline::default();
+        let first = prepare(text, &pipeline);
+        assert!(Arc::ptr_eq(&first, &prepare(text, &pipeline)));
+        let other = Pipeline::from_config(Some(&["cleanup".to_string()]));
+        assert!(!Arc::ptr_eq(&first, &prepare(text, &other)));
+        let texts: Vec<&str> = first.segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            ["First sentence.", "Second sentence.", "New paragraph."]
+        );
+    }
+}
@@ -0,0 +1,369 @@
+//! Preprocessing pipeline: an ordered list of named text stages run before segmentation.
+//!
+//! The order comes from the `preprocessing_stages` config value (stage names, e.g.
+//! `["cleanup", "markdown", "normalize"]`); unknown names are skipped with a warning and an unset
+//! value uses `DEFAULT_STAGES`. New stages are added to `Stage` and its name table. The
+//! `declutter` stage (URLs, citations, boilerplate; see `cleanup`) is opt-in, and so is
+//! `markdown`: it strips `*`, `_`, backticks and list dashes, which plain text uses as is. Markdown
+//! input is read with `reading_mode` "markdown" on `tts_speak` (see `reading_mode`) instead.
+//!
+//! The pronunciation lexicon's substitutions and the profanity filter (`profanity_filter` config)
+//! are not configurable stages: they always run after the stages, lexicon first, so the filter
+//! sees the final spoken words.
+
+use tracing::warn;
+
+use super::cleanup;
+use super::lexicon::Substitutions;
+use super::profanity::{FilterMode, ProfanityFilter};
+use crate::config::FullConfig;
+
+/// Stages run when `preprocessing_stages` is not configured.
+pub const DEFAULT_STAGES: [Stage; 2] = [Stage::Cleanup, Stage::Normalize];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Line endings, control and zero-width characters, words hyphenated across line breaks.
+    Cleanup,
+    /// Markdown syntax (headings, emphasis, links, code fences, list bullets, tables).
+    Markdown,
+    /// Typographic quotes, non-breaking spaces, repeated punctuation.
+    Normalize,
+    /// Code fences, URLs, citation markers, boilerplate and repeated header/footer lines.
+    Declutter,
+}
+
+impl Stage {
+    const ALL: [Stage; 4] = [
+        Stage::Cleanup,
+        Stage::Markdown,
+        Stage::Normalize,
+        Stage::Declutter,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Cleanup => "cleanup",
+            Self::Markdown => "markdown",
+            Self::Normalize => "normalize",
+            Self::Declutter => "declutter",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        Self::ALL.into_iter().find(|stage| stage.name() == name)
+    }
+
+    fn apply(self, text: &str) -> String {
+        match self {
+            Self::Cleanup => cleanup(text),
+            Self::Markdown => strip_markdown(text),
+            Self::Normalize => normalize(text),
+            Self::Declutter => cleanup::declutter(text),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+    lexicon: Option<Substitutions>,
+    profanity: Option<ProfanityFilter>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self {
+            stages: DEFAULT_STAGES.to_vec(),
+            lexicon: None,
+            profanity: None,
+        }
+    }
+}
+
+/// Text after one stage, returned by `preview_preprocessing`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StageOutput {
+    pub stage: &'static str,
+    pub text: String,
+}
+
+impl Pipeline {
+    /// Builds the pipeline from the `preprocessing_stages` config value.
+    pub fn from_config(stages: Option<&[String]>) -> Self {
+        let Some(names) = stages else {
+            return Self::default();
+        };
+        let stages = names
+            .iter()
+            .filter_map(|name| {
+                let stage = Stage::from_name(name);
+                if stage.is_none() {
+                    warn!(stage = %name, "Unknown preprocessing stage, skipping");
+                }
+                stage
+            })
+            .collect();
+        Self {
+            stages,
+            lexicon: None,
+            profanity: None,
+        }
+    }
+
+    /// Builds the pipeline from `preprocessing_stages`, the lexicon for the configured voice, and
+    /// `profanity_filter`.
+    pub fn for_config(cfg: &FullConfig) -> Self {
+        let mut pipeline = Self::from_config(cfg.preprocessing_stages.as_deref());
+        pipeline.lexicon = Substitutions::for_config(cfg);
+        pipeline.profanity =
+            FilterMode::from_config(cfg.profanity_filter.as_deref()).map(ProfanityFilter::load);
+        pipeline
+    }
+
+    /// Identifies the stage list; used as the prepared-text cache profile.
+    pub fn fingerprint(&self) -> String {
+        let mut parts: Vec<String> = self.stages.iter().map(|s| s.name().to_string()).collect();
+        parts.extend(self.lexicon.as_ref().map(|l| l.fingerprint().to_string()));
+        parts.extend(self.profanity.as_ref().map(ProfanityFilter::fingerprint));
+        parts.join(",")
+    }
+
+    pub fn run(&self, text: &str) -> String {
+        let text = self
+            .stages
+            .iter()
+            .fold(text.to_string(), |text, stage| stage.apply(&text));
+        let text = match &self.lexicon {
+            Some(lexicon) => lexicon.apply(&text),
+            None => text,
+        };
+        match &self.profanity {
+            Some(filter) => filter.apply(&text),
+            None => text,
+        }
+    }
+
+    /// Runs the pipeline and keeps the text after every stage.
+    pub fn run_with_trace(&self, text: &str) -> Vec<StageOutput> {
+        let mut current = text.to_string();
+        let mut trace: Vec<StageOutput> = self
+            .stages
+            .iter()
+            .map(|stage| {
+                current = stage.apply(&current);
+                StageOutput {
+                    stage: stage.name(),
+                    text: current.clone(),
+                }
+            })
+            .collect();
+        if let Some(lexicon) = &self.lexicon {
+            current = lexicon.apply(&current);
+            trace.push(StageOutput {
+                stage: "lexicon",
+                text: current.clone(),
+            });
+        }
+        if let Some(filter) = &self.profanity {
+            trace.push(StageOutput {
+                stage: "profanity",
+                text: filter.apply(&current),
+            });
+        }
+        trace
+    }
+}
+
+// --- Stages ---
+
+pub(super) fn cleanup(text: &str) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let text: String = text
+        .chars()
+        .filter(|c| {
+            !matches!(c, '\u{ad}' | '\u{200b}'..='\u{200d}' | '\u{feff}')
+                && (!c.is_control() || matches!(c, '\n' | '\t'))
+        })
+        .collect();
+
+    // "exam-\nple" -> "example" (only between lowercase letters, to keep "well-\nKnown" dashes).
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '-'
+            && i > 0
+            && chars[i - 1].is_alphabetic()
+            && chars.get(i + 1) == Some(&'\n')
+            && chars.get(i + 2).is_some_and(|c| c.is_lowercase())
+        {
+            i += 2;
+            continue;
+        }
+        out.push(chars[i]);
+        i += 1;
+    }
+    out
+}
+
+fn strip_markdown(text: &str) -> String {
+    let mut out = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            out.push(line.to_string());
+            continue;
+        }
+        if is_rule_or_table_separator(trimmed) {
+            out.push(String::new());
+            continue;
+        }
+        let mut line = trimmed;
+        line = line.trim_start_matches('>').trim_start();
+        let heading = line.trim_start_matches('#');
+        if heading.len() < line.len() && (heading.is_empty() || heading.starts_with(' ')) {
+            line = heading.trim_start();
+        }
+        for bullet in ["- [ ] ", "- [x] ", "- ", "* ", "+ "] {
+            if let Some(rest) = line.strip_prefix(bullet) {
+                line = rest;
+                break;
+            }
+        }
+        let line = if line.starts_with('|') && line.ends_with('|') {
+            line.trim_matches('|')
+                .split('|')
+                .map(str::trim)
+                .filter(|cell| !cell.is_empty())
+                .collect::<Vec<_>>()
+                .join(", ")
+        } else {
+            line.to_string()
+        };
+        out.push(strip_inline_markdown(&line));
+    }
+    out.join("\n")
+}
+
+/// `---`, `***`, `___`, and table separator rows like `|---|:--:|`.
+fn is_rule_or_table_separator(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && (compact.chars().all(|c| c == '-')
+            || compact.chars().all(|c| c == '*')
+            || compact.chars().all(|c| c == '_')
+            || (compact.contains('-') && compact.chars().all(|c| matches!(c, '|' | '-' | ':'))))
+}
+
+/// Links and images keep their text, emphasis markers and backticks are dropped.
+fn strip_inline_markdown(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let image = c == '!' && chars.get(i + 1) == Some(&'[');
+        if c == '[' || image {
+            let open = if image { i + 1 } else { i };
+            if let Some((label, next)) = parse_link(&chars, open) {
+                out.push_str(&label);
+                i = next;
+                continue;
+            }
+        }
+        let doubled = chars.get(i + 1) == Some(&c);
+        let is_marker = match c {
+            '`' | '*' | '~' => true,
+            // `_` only when it wraps a word, so snake_case survives.
+            '_' => {
+                let prev = i.checked_sub(1).and_then(|p| chars.get(p));
+                let next = chars.get(if doubled { i + 2 } else { i + 1 });
+                !prev.is_some_and(|p| p.is_alphanumeric())
+                    || !next.is_some_and(|n| n.is_alphanumeric())
+            }
+            _ => false,
+        };
+        if is_marker {
+            i += if doubled { 2 } else { 1 };
+            continue;
+        }
+        out.push(c);
+        i += 1;
+    }
+    out
+}
+
+/// Parses `[label](target)` starting at `open` (the `[`). Returns the label and the index after
+/// the closing `)`.
+fn parse_link(chars: &[char], open: usize) -> Option<(String, usize)> {
+    let close = open + chars[open..].iter().position(|c| *c == ']')?;
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+    let end = close + 1 + chars[close + 1..].iter().position(|c| *c == ')')?;
+    Some((chars[open + 1..close].iter().collect(), end + 1))
+}
+
+fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last: Option<char> = None;
+    for c in text.chars() {
+        let c = match c {
+            '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{2032}' => '\'',
+            '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{2033}' => '"',
+            '\u{a0}' | '\u{2007}' | '\u{202f}' | '\t' => ' ',
+            other => other,
+        };
+        // "!!!" -> "!", "??" -> "?", but "..." and "?!" are kept.
+        if matches!(c, '!' | '?' | ',' | ';' | ':') && last == Some(c) {
+            continue;
+        }
+        out.push(c);
+        last = Some(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_stage_keeps_readable_text() {
+        let md = "# Title\n\n> Some **bold** and _italic_ with `code` and a [link](http://x.y).\n\
+                  - item one\n---\n| a | b |\n|---|---|\n```\nlet x = 1;\n```\nsnake_case stays";
+        assert_eq!(
+            strip_markdown(md),
+            "Title\n\nSome bold and italic with code and a link.\nitem one\n\na, b\n\nlet x = 1;\nsnake_case stays"
+        );
+    }
+
+    #[test]
+    fn test_pipeline_runs_configured_stages_in_order() {
+        let stages = vec![
+            "normalize".to_string(),
+            "bogus".to_string(),
+            "cleanup".to_string(),
+        ];
+        let pipeline = Pipeline::from_config(Some(&stages));
+        assert_eq!(pipeline.fingerprint(), "normalize,cleanup");
+
+        let trace = pipeline.run_with_trace("\u{201c}Hyphen-\nated\u{201d}!!!\r\n");
+        assert_eq!(trace[0].text, "\"Hyphen-\nated\"!\r\n");
+        assert_eq!(trace[1].text, "\"Hyphenated\"!\n");
+        assert_eq!(pipeline.run("x\u{200b}y"), "xy");
+
+        let default = Pipeline::default();
+        assert_eq!(
+            default.run("- 5 apples, snake_case"),
+            "- 5 apples, snake_case"
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Optional profanity filter for shared/family use, run after the preprocessing stages.
+//!
+//! Words are matched case-insensitively against a built-in English list plus an optional per-user
+//! list (`profanity-words.txt` in the config dir, one word per line, `#` for comments; with a
+//! profile active this is the profile's own list). Common suffixes ("-s", "-ing", "-ed", "-er",
+//! "-y") are stripped before matching, but words are never matched by substring, so "class" and
+//! "Scunthorpe" are left alone. Matches are either replaced with "bleep" or dropped.
+
+use std::collections::BTreeSet;
+use std::fs;
+
+use tracing::warn;
+
+use crate::paths;
+
+const CUSTOM_WORDS_FILE_NAME: &str = "profanity-words.txt";
+/// Spoken in place of a masked word.
+const MASK_WORD: &str = "bleep";
+
+const BUILTIN_WORDS: &[&str] = &[
+    "arse",
+    "arsehole",
+    "ass",
+    "asshole",
+    "bastard",
+    "bitch",
+    "bollocks",
+    "bullshit",
+    "crap",
+    "cunt",
+    "damn",
+    "dickhead",
+    "fuck",
+    "goddamn",
+    "motherfuck",
+    "piss",
+    "prick",
+    "shit",
+    "slut",
+    "twat",
+    "wank",
+    "whore",
+];
+
+const SUFFIXES: [&str; 7] = ["ers", "ing", "er", "ed", "es", "s", "y"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterMode {
+    /// Replace each match with "bleep".
+    Mask,
+    /// Leave matches out of the spoken text.
+    Skip,
+}
+
+impl FilterMode {
+    /// Parses the `profanity_filter` config value; unset or "off" disables the filter.
+    pub fn from_config(value: Option<&str>) -> Option<Self> {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Some("mask") => Some(Self::Mask),
+            Some("skip") => Some(Self::Skip),
+            Some("off") | Some("") | None => None,
+            Some(other) => {
+                warn!(value = %other, "Unknown profanity_filter value, filter disabled");
+                None
+            }
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Mask => "mask",
+            Self::Skip => "skip",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfanityFilter {
+    mode: FilterMode,
+    words: BTreeSet<String>,
+}
+
+impl ProfanityFilter {
+    /// Filter with the built-in list plus the user's custom words, if any.
+    pub fn load(mode: FilterMode) -> Self {
+        let mut filter = Self::new(mode, BUILTIN_WORDS.iter().copied());
+        filter.words.extend(load_custom_words());
+        filter
+    }
+
+    fn new<'a>(mode: FilterMode, words: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            mode,
+            words: words.into_iter().map(str::to_lowercase).collect(),
+        }
+    }
+
+    /// Identifies the mode and word list, for the prepared-text cache key.
+    pub fn fingerprint(&self) -> String {
+        let words: Vec<&str> = self.words.iter().map(String::as_str).collect();
+        format!("profanity-{}:{}", self.mode.name(), words.join("|"))
+    }
+
+    fn is_profane(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        if self.words.contains(&word) {
+            return true;
+        }
+        SUFFIXES.iter().any(|suffix| {
+            let Some(stem) = word.strip_suffix(suffix).filter(|s| s.chars().count() > 2) else {
+                return false;
+            };
+            if self.words.contains(stem) {
+                return true;
+            }
+            // "shitting" -> "shitt" -> "shit"
+            let mut chars = stem.chars().rev();
+            let (Some(last), Some(before)) = (chars.next(), chars.next()) else {
+                return false;
+            };
+            last == before && self.words.contains(&stem[..stem.len() - last.len_utf8()])
+        })
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find(char::is_alphabetic) {
+            out.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let end = rest
+                .find(|c: char| !c.is_alphabetic())
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            rest = &rest[end..];
+            if !self.is_profane(word) {
+                out.push_str(word);
+                continue;
+            }
+            match self.mode {
+                FilterMode::Mask => out.push_str(MASK_WORD),
+                FilterMode::Skip => {
+                    // Drop one space so "a word here" becomes "a here", not "a  here".
+                    if out.is_empty() || out.ends_with(' ') {
+                        rest = rest.strip_prefix(' ').unwrap_or(rest);
+                    }
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+fn load_custom_words() -> Vec<String> {
+    let Ok(path) = paths::get_config_dir().map(|dir| dir.join(CUSTOM_WORDS_FILE_NAME)) else {
+        return Vec::new();
+    };
+    let Ok(data) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    data.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masks_or_skips_whole_words_only() {
+        let text = "Well, shit. That FUCKING class in Scunthorpe was shitty, damned assessors.";
+        let mask = ProfanityFilter::new(FilterMode::Mask, BUILTIN_WORDS.iter().copied());
+        assert_eq!(
+            mask.apply(text),
+            "Well, bleep. That bleep class in Scunthorpe was bleep, bleep assessors."
+        );
+        let skip = ProfanityFilter::new(FilterMode::Skip, ["damn", "shit", "fuck"]);
+        assert_eq!(
+            skip.apply("shit happens, damn it. A fucking test"),
+            "happens, it. A test"
+        );
+        assert!(skip.is_profane("shitting"));
+        assert!(!skip.is_profane("shift"));
+    }
+}
@@ -0,0 +1,162 @@
+//! Readability metrics for proofreading by ear: per-sentence word count and Flesch-Kincaid grade.
+//!
+//! Syllables are counted with the usual vowel-group heuristic, so the grade is only computed for
+//! sentences written mostly in Latin letters; other scripts are checked for length only.
+
+use serde::Serialize;
+
+use super::align::Aligner;
+use super::segment::Segment;
+use crate::config::FullConfig;
+
+/// Sentences with more words than this are flagged when `proofread_max_words` is not set.
+pub const DEFAULT_MAX_WORDS: u32 = 25;
+/// Sentences above this grade are flagged when `proofread_max_grade` is not set.
+pub const DEFAULT_MAX_GRADE: f32 = 12.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Thresholds {
+    pub max_words: u32,
+    pub max_grade: f32,
+}
+
+impl Thresholds {
+    pub fn from_config(cfg: &FullConfig) -> Self {
+        Self {
+            max_words: cfg.proofread_max_words.unwrap_or(DEFAULT_MAX_WORDS),
+            max_grade: cfg.proofread_max_grade.unwrap_or(DEFAULT_MAX_GRADE),
+        }
+    }
+}
+
+/// A sentence over one of the thresholds. Offsets are UTF-16 code units into the reviewed text.
+#[derive(Debug, Clone, Serialize)]
+pub struct SentenceFlag {
+    /// Index of the sentence among all sentences of the text.
+    pub index: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+    pub words: usize,
+    /// Flesch-Kincaid grade; `None` for text the syllable heuristic does not apply to.
+    pub grade: Option<f32>,
+    pub too_long: bool,
+    pub too_hard: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProofreadReport {
+    pub sentences: usize,
+    pub flagged: Vec<SentenceFlag>,
+}
+
+/// Checks each segment of `source` against `thresholds`.
+pub fn review(source: &str, segments: &[Segment], thresholds: Thresholds) -> ProofreadReport {
+    let mut aligner = Aligner::new(source);
+    let flagged = segments
+        .iter()
+        .enumerate()
+        .filter_map(|(index, segment)| {
+            let span = aligner.find_span(&segment.text);
+            let words = words(&segment.text).count();
+            let grade = grade(&segment.text);
+            let too_long = words > thresholds.max_words as usize;
+            let too_hard = grade.is_some_and(|g| g > thresholds.max_grade);
+            let (char_start, char_end) = span?;
+            (too_long || too_hard).then_some(SentenceFlag {
+                index,
+                char_start,
+                char_end,
+                words,
+                grade,
+                too_long,
+                too_hard,
+            })
+        })
+        .collect();
+    ProofreadReport {
+        sentences: segments.len(),
+        flagged,
+    }
+}
+
+fn words(sentence: &str) -> impl Iterator<Item = &str> {
+    sentence
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|w| !w.is_empty())
+}
+
+/// Flesch-Kincaid grade level of a single sentence.
+fn grade(sentence: &str) -> Option<f32> {
+    let letters = sentence.chars().filter(|c| c.is_alphabetic()).count();
+    let latin = sentence
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic() || ('\u{c0}'..='\u{24f}').contains(c))
+        .count();
+    if letters == 0 || latin * 10 < letters * 9 {
+        return None;
+    }
+    let words: Vec<&str> = words(sentence).collect();
+    if words.is_empty() {
+        return None;
+    }
+    let syllables: usize = words.iter().map(|w| syllables(w)).sum();
+    let words_per_sentence = words.len() as f32;
+    let syllables_per_word = syllables as f32 / words.len() as f32;
+    Some(0.39 * words_per_sentence + 11.8 * syllables_per_word - 15.59)
+}
+
+/// Vowel groups, minus a silent final "e" ("make", but not "table"); at least one per word.
+fn syllables(word: &str) -> usize {
+    let word = word.to_lowercase();
+    let is_vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for c in word.chars() {
+        let vowel = is_vowel(c) || ('\u{e0}'..='\u{fc}').contains(&c);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+    if word.ends_with('e') && !word.ends_with("le") && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::segment::segment;
+
+    #[test]
+    fn test_flags_long_and_hard_sentences() {
+        let text = "The cat sat. Notwithstanding considerable organizational \
+                    complexity, institutional modernization necessitates comprehensive \
+                    reevaluation. \u{201c}This one is long\u{201d} and it just keeps going and going \
+                    with many small words that are easy but there are far too many of them here.";
+        let thresholds = Thresholds {
+            max_words: 20,
+            max_grade: DEFAULT_MAX_GRADE,
+        };
+        let report = review(text, &segment(text), thresholds);
+        assert_eq!(report.sentences, 3);
+        assert_eq!(report.flagged.len(), 2);
+
+        let hard = &report.flagged[0];
+        assert_eq!((hard.index, hard.too_hard, hard.too_long), (1, true, false));
+        let long = &report.flagged[1];
+        assert_eq!((long.index, long.too_hard, long.too_long), (2, false, true));
+        let flagged: String = text.encode_utf16().collect::<Vec<_>>()
+            [long.char_start..long.char_end]
+            .iter()
+            .map(|&u| char::from_u32(u32::from(u)).unwrap_or('?'))
+            .collect();
+        assert!(flagged.starts_with("This one") && flagged.ends_with("here"));
+
+        assert_eq!(syllables("make"), 1);
+        assert_eq!(syllables("table"), 2);
+        assert_eq!(grade("今日は晴れ。"), None);
+    }
+}
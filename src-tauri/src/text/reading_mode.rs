@@ -0,0 +1,363 @@
+//! Reading modes for Markdown and source code, chosen per read (`reading_mode` on `tts_speak`).
+//!
+//! The `markdown` preprocessing stage only strips syntax. The Markdown mode turns structure into
+//! prose instead: headings are announced ("Heading: …"), list items, table rows and quotes become
+//! sentences, link targets and bare URLs are skipped, and fenced code is read in code mode. Code
+//! mode reads one sentence per line: identifiers split into words (`parse_http_url`,
+//! `parseHttpUrl` -> "parse http url"), string and comment text kept, and operators, brackets and
+//! other punctuation left out. `auto` picks a mode from the text itself.
+
+use serde::Deserialize;
+
+/// How a single read treats its text before the preprocessing pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadingMode {
+    /// The text as is.
+    #[default]
+    Plain,
+    Markdown,
+    Code,
+    /// Markdown or code when the text looks like it, plain otherwise.
+    Auto,
+}
+
+impl ReadingMode {
+    /// Converts `text` for reading. `None` when the mode leaves it unchanged.
+    pub fn apply(self, text: &str) -> Option<String> {
+        match self {
+            Self::Plain => None,
+            Self::Markdown => Some(markdown_to_prose(text)),
+            Self::Code => Some(code_to_prose(text)),
+            Self::Auto => match detect(text) {
+                Self::Plain | Self::Auto => None,
+                mode => mode.apply(text),
+            },
+        }
+    }
+}
+
+/// Lines (of the non-blank ones) that must look like code for `auto` to pick code mode, in percent.
+const CODE_LINE_SHARE_PERCENT: usize = 50;
+
+fn detect(text: &str) -> ReadingMode {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    if lines.is_empty() {
+        return ReadingMode::Plain;
+    }
+    let code_lines = lines.iter().filter(|l| looks_like_code(l)).count();
+    if code_lines * 100 >= lines.len() * CODE_LINE_SHARE_PERCENT {
+        return ReadingMode::Code;
+    }
+    let markdown = lines.iter().any(|l| {
+        l.starts_with("```")
+            || l.starts_with("# ")
+            || l.starts_with("## ")
+            || l.starts_with("### ")
+            || (l.starts_with('|') && l.ends_with('|'))
+    }) || text.contains("](");
+    if markdown {
+        ReadingMode::Markdown
+    } else {
+        ReadingMode::Plain
+    }
+}
+
+fn looks_like_code(line: &str) -> bool {
+    const KEYWORDS: [&str; 14] = [
+        "fn ",
+        "def ",
+        "let ",
+        "const ",
+        "var ",
+        "import ",
+        "return ",
+        "class ",
+        "pub ",
+        "use ",
+        "function ",
+        "#include",
+        "public ",
+        "private ",
+    ];
+    line.ends_with(';')
+        || line.ends_with('{')
+        || line == "}"
+        || line.starts_with("//")
+        || line.contains("=>")
+        || line.contains("->")
+        || KEYWORDS.iter().any(|k| line.starts_with(k))
+}
+
+// --- Markdown ---
+
+fn markdown_to_prose(text: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut fence: Option<Vec<&str>> = None;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            match fence.take() {
+                Some(code) => {
+                    if out.last().is_some_and(|l| !l.is_empty()) {
+                        out.push(String::new());
+                    }
+                    out.push("Code block.".to_string());
+                    out.push(code_to_prose(&code.join("\n")));
+                    out.push(String::new());
+                }
+                None => fence = Some(Vec::new()),
+            }
+            continue;
+        }
+        if let Some(code) = &mut fence {
+            code.push(line);
+            continue;
+        }
+        if trimmed.is_empty() || is_rule(trimmed) {
+            out.push(String::new());
+            continue;
+        }
+        out.push(markdown_line(trimmed));
+    }
+    // An unclosed fence is still code.
+    if let Some(code) = fence {
+        out.push("Code block.".to_string());
+        out.push(code_to_prose(&code.join("\n")));
+    }
+    out.join("\n").trim().to_string()
+}
+
+fn markdown_line(line: &str) -> String {
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&hashes) && line[hashes..].starts_with(' ') {
+        let label = if hashes == 1 { "Heading" } else { "Subheading" };
+        return format!("\n{label}: {}\n", sentence(&inline(&line[hashes..])));
+    }
+    if let Some(quote) = line.strip_prefix('>') {
+        return format!("Quote: {}", sentence(&inline(quote)));
+    }
+    if line.starts_with('|') && line.ends_with('|') {
+        if line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' ')) {
+            return String::new();
+        }
+        let cells: Vec<String> = line
+            .trim_matches('|')
+            .split('|')
+            .map(inline)
+            .filter(|cell| !cell.is_empty())
+            .collect();
+        return sentence(&cells.join(", "));
+    }
+    for bullet in ["- [ ] ", "- [x] ", "- ", "* ", "+ "] {
+        if let Some(item) = line.strip_prefix(bullet) {
+            return sentence(&inline(item));
+        }
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 && line[digits..].starts_with(". ") {
+        return sentence(&inline(&line[digits + 2..]));
+    }
+    inline(line)
+}
+
+fn is_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|m| compact.chars().all(|c| c == *m))
+}
+
+/// Inline Markdown: link and image targets and bare URLs are dropped, inline code is read as
+/// code, emphasis markers go.
+fn inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.trim();
+    while let Some(c) = rest.chars().next() {
+        let image = rest.starts_with("![");
+        if c == '[' || image {
+            let label_start = if image { 2 } else { 1 };
+            if let Some((label, after)) = parse_link(&rest[label_start..]) {
+                if image {
+                    out.push_str("Image: ");
+                }
+                out.push_str(&inline(label));
+                rest = after;
+                continue;
+            }
+        }
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                out.push_str(&identifier_words(&rest[1..=end]).join(" "));
+                rest = &rest[end + 2..];
+                continue;
+            }
+        }
+        if out.is_empty() || out.ends_with(' ') {
+            let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let word = &rest[..word_end];
+            if word.starts_with("http://")
+                || word.starts_with("https://")
+                || word.starts_with("www.")
+            {
+                // Keep the punctuation after the URL ("see https://x.y." -> "see.").
+                let url_len = word
+                    .trim_end_matches(['.', ',', ';', ':', '!', '?', ')'])
+                    .len();
+                rest = &rest[url_len..];
+                if out.ends_with(' ') && !rest.starts_with(char::is_whitespace) {
+                    out.pop();
+                }
+                continue;
+            }
+        }
+        if !matches!(c, '*' | '_' | '~') || is_inner_underscore(&out, c, rest) {
+            out.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `snake_case` keeps its underscore; `_emphasis_` loses it.
+fn is_inner_underscore(before: &str, c: char, rest: &str) -> bool {
+    c == '_'
+        && before
+            .chars()
+            .next_back()
+            .is_some_and(char::is_alphanumeric)
+        && rest[1..].chars().next().is_some_and(char::is_alphanumeric)
+}
+
+/// Parses `label](target)` (after the `[`). Returns the label and the text after the `)`.
+fn parse_link(text: &str) -> Option<(&str, &str)> {
+    let close = text.find(']')?;
+    let after = text[close + 1..].strip_prefix('(')?;
+    let end = after.find(')')?;
+    Some((&text[..close], &after[end + 1..]))
+}
+
+/// Ends `text` with a period unless it already ends a sentence, so items are read as sentences.
+fn sentence(text: &str) -> String {
+    let text = text.trim();
+    if text.is_empty() || text.ends_with(['.', '!', '?', ':', '…']) {
+        text.to_string()
+    } else {
+        format!("{text}.")
+    }
+}
+
+// --- Code ---
+
+fn code_to_prose(text: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let words = code_line_words(line);
+        if words.is_empty() {
+            if out.last().is_some_and(|l| !l.is_empty()) {
+                out.push(String::new());
+            }
+            continue;
+        }
+        out.push(sentence(&words.join(" ")));
+    }
+    out.join("\n").trim().to_string()
+}
+
+/// Words of a line of code: identifiers split into words, numbers, and the text of strings.
+fn code_line_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut token = String::new();
+    let mut quote: Option<char> = None;
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => {
+                words.extend(token.split_whitespace().map(str::to_string));
+                token.clear();
+                quote = None;
+            }
+            Some(_) => token.push(c),
+            None if c.is_alphanumeric() || c == '_' => token.push(c),
+            None => {
+                words.extend(identifier_words(&token));
+                token.clear();
+                if matches!(c, '"' | '\'' | '`') {
+                    quote = Some(c);
+                }
+            }
+        }
+    }
+    if quote.is_some() {
+        words.extend(token.split_whitespace().map(str::to_string));
+    } else {
+        words.extend(identifier_words(&token));
+    }
+    words
+}
+
+/// `parse_http_url`, `parseHttpUrl`, `ParseHTTPUrl` -> "parse", "http", "url" (acronyms keep
+/// their case so they are spelled out).
+fn identifier_words(identifier: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in identifier.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (prev, c) = (chars[i - 1], chars[i]);
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if (prev.is_lowercase() && c.is_uppercase())
+                || (prev.is_uppercase() && c.is_uppercase() && next_lower)
+            {
+                words.push(chars[start..i].iter().collect::<String>());
+                start = i;
+            }
+        }
+        if start < chars.len() {
+            words.push(chars[start..].iter().collect::<String>());
+        }
+    }
+    words
+        .into_iter()
+        .map(|w| {
+            let acronym = w.chars().count() > 1 && w.chars().all(|c| !c.is_lowercase());
+            if acronym {
+                w
+            } else {
+                w.to_lowercase()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_and_code_read_as_prose() {
+        let md = "# Setup guide\nInstall the [CLI](https://example.com/cli) first, see \
+                  https://example.com.\n\n- Run `cargo build`\n- Done!\n\n```\nlet user_name = \
+                  getUserName(\"Ada\"); // greet\n```";
+        assert_eq!(
+            ReadingMode::Markdown.apply(md).as_deref(),
+            Some(
+                "Heading: Setup guide.\n\nInstall the CLI first, see.\n\nRun cargo build.\nDone!\n\n\
+                 Code block.\nlet user name get user name Ada greet."
+            )
+        );
+        assert_eq!(
+            ReadingMode::Code
+                .apply("fn parseHTTPResponse(raw_body: &[u8]) -> Result<Vec<u8>> {\n}")
+                .as_deref(),
+            Some("fn parse HTTP response raw body u8 result vec u8.")
+        );
+        assert_eq!(ReadingMode::Auto.apply("Just a sentence."), None);
+        assert_eq!(detect("let x = 1;\nreturn x;"), ReadingMode::Code);
+        assert_eq!(detect("## Notes\nSome text."), ReadingMode::Markdown);
+    }
+}
@@ -0,0 +1,197 @@
+//! Paragraph and sentence segmentation.
+
+/// Sentence terminators. CJK full-width terminators end a sentence even without a following space.
+const TERMINATORS: [char; 7] = ['.', '!', '?', '…', '。', '！', '？'];
+const CJK_TERMINATORS: [char; 3] = ['。', '！', '？'];
+
+/// Characters that may follow a terminator and still belong to the sentence (`"Stop!" he said`).
+const CLOSING: [char; 8] = ['"', '\'', '”', '’', ')', ']', '»', '」'];
+
+/// Lowercase words that end with a period without ending the sentence.
+const ABBREVIATIONS: [&str; 18] = [
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "etc", "e.g", "i.e", "no", "fig",
+    "approx", "inc", "ltd", "cf",
+];
+
+/// Segments longer than this (in chars) are split at a comma or space.
+const MAX_SEGMENT_CHARS: usize = 400;
+
+/// One sentence (or a piece of a very long one).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub text: String,
+    /// Index of the paragraph the segment belongs to.
+    pub paragraph: usize,
+}
+
+/// Splits `text` into paragraphs (blank-line separated) and whitespace-normalized sentences.
+pub fn segment(text: &str) -> Vec<Segment> {
+    paragraphs(text)
+        .iter()
+        .enumerate()
+        .flat_map(|(paragraph, para)| {
+            split_sentences(para)
+                .into_iter()
+                .flat_map(|sentence| split_long(&sentence))
+                .map(move |text| Segment { text, paragraph })
+        })
+        .collect()
+}
+
+/// Paragraphs separated by blank lines; hard-wrapped lines inside a paragraph are joined.
+fn paragraphs(text: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                out.push(collapse_whitespace(&current.join(" ")));
+                current.clear();
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        out.push(collapse_whitespace(&current.join(" ")));
+    }
+    out
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Splits a whitespace-normalized paragraph into sentences.
+fn split_sentences(paragraph: &str) -> Vec<String> {
+    let chars: Vec<char> = paragraph.chars().collect();
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if !TERMINATORS.contains(&c) {
+            i += 1;
+            continue;
+        }
+        let mut end = i + 1;
+        while end < chars.len()
+            && (TERMINATORS.contains(&chars[end]) || CLOSING.contains(&chars[end]))
+        {
+            end += 1;
+        }
+        let at_end = end >= chars.len();
+        let splits = CJK_TERMINATORS.contains(&c)
+            || at_end
+            || (chars[end] == ' ' && !continues_sentence(&chars, start, i, end));
+        if splits {
+            let sentence: String = chars[start..end].iter().collect();
+            let sentence = sentence.trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence.to_string());
+            }
+            start = end;
+        }
+        i = end;
+    }
+    let rest: String = chars[start..].iter().collect();
+    if !rest.trim().is_empty() {
+        sentences.push(rest.trim().to_string());
+    }
+    sentences
+}
+
+/// True when the period at `dot` belongs to an abbreviation or initial, or the next word starts
+/// lowercase ("approx. ten", "J. R. R. Tolkien").
+fn continues_sentence(chars: &[char], start: usize, dot: usize, end: usize) -> bool {
+    if chars[dot] != '.' {
+        return false;
+    }
+    let word_start = chars[start..dot]
+        .iter()
+        .rposition(|c| c.is_whitespace() || *c == '(')
+        .map_or(start, |p| start + p + 1);
+    let word: String = chars[word_start..dot]
+        .iter()
+        .collect::<String>()
+        .to_lowercase();
+    if ABBREVIATIONS.contains(&word.as_str()) {
+        return true;
+    }
+    if word.chars().count() == 1 && word.chars().all(char::is_alphabetic) {
+        return true;
+    }
+    chars[end..]
+        .iter()
+        .find(|c| !c.is_whitespace())
+        .is_some_and(|c| c.is_lowercase())
+}
+
+/// Splits a sentence longer than `MAX_SEGMENT_CHARS` at the last comma/semicolon (or space) before
+/// the limit.
+fn split_long(sentence: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut rest = sentence;
+    while rest.chars().count() > MAX_SEGMENT_CHARS {
+        let limit = rest
+            .char_indices()
+            .nth(MAX_SEGMENT_CHARS)
+            .map_or(rest.len(), |(i, _)| i);
+        let head = &rest[..limit];
+        let cut = head
+            .rfind([',', ';', ':'])
+            .map(|i| i + 1)
+            .or_else(|| head.rfind(' '))
+            .filter(|i| *i > 0)
+            .unwrap_or(limit);
+        out.push(rest[..cut].trim().to_string());
+        rest = rest[cut..].trim_start();
+    }
+    if !rest.is_empty() {
+        out.push(rest.to_string());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(segments: &[Segment]) -> Vec<(&str, usize)> {
+        segments
+            .iter()
+            .map(|s| (s.text.as_str(), s.paragraph))
+            .collect()
+    }
+
+    #[test]
+    fn test_splits_sentences_and_paragraphs() {
+        let text = "Dr. Smith arrived at 3.30 p.m. today.  He said \"Stop!\" Then\nleft.\n\n\
+                    J. R. R. Tolkien wrote it… It took approx. ten years?";
+        assert_eq!(
+            texts(&segment(text)),
+            [
+                ("Dr. Smith arrived at 3.30 p.m. today.", 0),
+                ("He said \"Stop!\"", 0),
+                ("Then left.", 0),
+                ("J. R. R. Tolkien wrote it…", 1),
+                ("It took approx. ten years?", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_splits_long_sentences_and_cjk() {
+        let long = format!("{}, {}", "a".repeat(300), "b ".repeat(200));
+        let segments = segment(&long);
+        assert!(segments
+            .iter()
+            .all(|s| s.text.chars().count() <= MAX_SEGMENT_CHARS));
+        assert!(segments[0].text.ends_with(','));
+
+        assert_eq!(
+            texts(&segment("今日は晴れ。明日は雨？")),
+            [("今日は晴れ。", 0), ("明日は雨？", 0)]
+        );
+    }
+}
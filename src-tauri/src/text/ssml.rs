@@ -0,0 +1,421 @@
+//! SSML for the cloud voices (Polly, Microsoft): passed through when the caller sends SSML, or
+//! generated from plain text when `ssml_generation` is on.
+//!
+//! SSML input (`InputKind::Ssml`) is spoken as one chunk: the body of `<speak>` goes to the voice
+//! as-is, and its plain text drives the word timeline and Piper (which has no SSML). The body must
+//! not contain `<voice>` elements; Microsoft wraps it in its own voice element.
+//!
+//! Generation escapes each chunk and adds, per config: a `<prosody>` rate/pitch (`ssml_rate`,
+//! `ssml_pitch`, in percent; Polly's neural voices have no pitch, so it is Microsoft only),
+//! a `<break>` at paragraph breaks inside the chunk (`ssml_paragraph_break_ms`), and `<say-as>`
+//! for ISO dates and long or grouped numbers (`ssml_say_as`). Short numbers are left to the voice,
+//! which already reads years and times well. Lexicon phoneme overrides apply either way.
+//!
+//! Stretches of a read in another language (see `LanguageTags`) are wrapped in `<lang>` for the
+//! voices that support it, so they get that language's pronunciation rules; other voices get
+//! the chunk without them.
+
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use super::lexicon::Pronunciations;
+use crate::config::FullConfig;
+
+pub const DEFAULT_PARAGRAPH_BREAK_MS: u32 = 600;
+
+/// What the text of a speak request is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputKind {
+    #[default]
+    Text,
+    Ssml,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SsmlOptions {
+    generate: bool,
+    rate: i32,
+    pitch: i32,
+    paragraph_break_ms: u32,
+    say_as: bool,
+    language_tags: bool,
+    /// The user's pitch (`playback_pitch`, percent), sent as prosody even without generation.
+    playback_pitch: i32,
+}
+
+impl SsmlOptions {
+    pub fn from_config(cfg: &FullConfig) -> Self {
+        Self {
+            generate: cfg.ssml_generation.unwrap_or(false),
+            rate: cfg.ssml_rate.unwrap_or(0).clamp(-50, 100),
+            pitch: cfg.ssml_pitch.unwrap_or(0).clamp(-50, 50),
+            paragraph_break_ms: cfg
+                .ssml_paragraph_break_ms
+                .unwrap_or(DEFAULT_PARAGRAPH_BREAK_MS)
+                .min(5_000),
+            say_as: cfg.ssml_say_as.unwrap_or(true),
+            language_tags: cfg.ssml_language_tags.unwrap_or(false),
+            playback_pitch: cfg.playback_pitch.unwrap_or(0).clamp(-50, 50),
+        }
+    }
+
+    /// Whether stretches in another language are detected and tagged (`ssml_language_tags`).
+    pub fn language_tags(&self) -> bool {
+        self.language_tags
+    }
+}
+
+/// What a voice accepts in the SSML it is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SsmlSupport {
+    /// Prosody pitch.
+    pub pitch: bool,
+    /// `<lang xml:lang="...">`.
+    pub lang: bool,
+}
+
+/// Stretches of a read in another language than the voice's, each with its BCP-47 tag ("pt-BR").
+/// They are matched by text in each chunk; a stretch no chunk contains verbatim (e.g. changed by
+/// preprocessing) is spoken untagged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LanguageTags(Vec<(String, String)>);
+
+impl LanguageTags {
+    pub fn new(tags: Vec<(String, String)>) -> Self {
+        Self(
+            tags.into_iter()
+                .filter(|(text, tag)| !text.is_empty() && !tag.is_empty())
+                .collect(),
+        )
+    }
+
+    /// `chunk` cut into untagged and tagged pieces, in order. Where stretches overlap, the one
+    /// starting first wins.
+    fn split<'a>(&'a self, chunk: &'a str) -> Vec<(Option<&'a str>, &'a str)> {
+        let mut found: Vec<(usize, usize, &str)> = self
+            .0
+            .iter()
+            .flat_map(|(text, tag)| {
+                chunk
+                    .match_indices(text.as_str())
+                    .map(move |(start, m)| (start, start + m.len(), tag.as_str()))
+            })
+            .collect();
+        found.sort_by_key(|&(start, end, _)| (start, std::cmp::Reverse(end)));
+        let mut pieces = Vec::new();
+        let mut last = 0;
+        for (start, end, tag) in found {
+            if start < last {
+                continue;
+            }
+            if start > last {
+                pieces.push((None, &chunk[last..start]));
+            }
+            pieces.push((Some(tag), &chunk[start..end]));
+            last = end;
+        }
+        if last < chunk.len() {
+            pieces.push((None, &chunk[last..]));
+        }
+        pieces
+    }
+}
+
+/// SSML sent by the caller: its `<speak>` body and plain text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Passthrough {
+    pub body: String,
+    pub plain: String,
+}
+
+impl Passthrough {
+    pub fn new(ssml: &str) -> Self {
+        let body = speak_body(ssml).to_string();
+        let plain = plain_text(&body);
+        Self { body, plain }
+    }
+}
+
+/// How chunks are marked up for a voice: lexicon overrides, generation options, SSML input, and
+/// stretches in another language.
+#[derive(Debug, Clone, Default)]
+pub struct Markup {
+    pronunciations: Pronunciations,
+    options: SsmlOptions,
+    passthrough: Option<Passthrough>,
+    languages: LanguageTags,
+}
+
+impl Markup {
+    pub fn new(
+        pronunciations: Pronunciations,
+        options: SsmlOptions,
+        passthrough: Option<Passthrough>,
+    ) -> Self {
+        Self {
+            pronunciations,
+            options,
+            passthrough,
+            languages: LanguageTags::default(),
+        }
+    }
+
+    pub fn with_languages(mut self, languages: LanguageTags) -> Self {
+        self.languages = languages;
+        self
+    }
+
+    /// SSML for one chunk (a fragment, without `<speak>`), or `None` to send the plain text.
+    pub fn to_ssml(&self, text: &str, voice: SsmlSupport) -> Option<String> {
+        if let Some(passthrough) = self.passthrough.as_ref().filter(|p| p.plain == text) {
+            return Some(passthrough.body.clone());
+        }
+        let pieces = if voice.lang {
+            self.languages.split(text)
+        } else {
+            Vec::new()
+        };
+        let tagged = pieces.iter().any(|(tag, _)| tag.is_some());
+        let pitch = match (voice.pitch, self.options.generate) {
+            (false, _) => 0,
+            (true, true) => (self.options.pitch + self.options.playback_pitch).clamp(-50, 50),
+            (true, false) => self.options.playback_pitch,
+        };
+        if !self.options.generate && !tagged && pitch == 0 {
+            return self.pronunciations.to_ssml(text);
+        }
+        let body = if tagged {
+            pieces
+                .iter()
+                .map(|(tag, piece)| match tag {
+                    Some(tag) => format!(
+                        "<lang xml:lang=\"{}\">{}</lang>",
+                        escape(tag),
+                        self.render(piece)
+                    ),
+                    None => self.render(piece),
+                })
+                .collect()
+        } else {
+            self.render(text)
+        };
+        let rate = if self.options.generate {
+            self.options.rate
+        } else {
+            0
+        };
+        if rate == 0 && pitch == 0 {
+            return Some(body);
+        }
+        Some(format!(
+            "<prosody rate=\"{rate:+}%\" pitch=\"{pitch:+}%\">{body}</prosody>"
+        ))
+    }
+
+    /// `text` for voices without SSML (see `Pronunciations::respell`).
+    pub fn respell<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        self.pronunciations.respell(text)
+    }
+
+    /// `text` as SSML: phoneme overrides, and say-as and breaks when generating.
+    fn render(&self, text: &str) -> String {
+        if self.options.generate {
+            self.pronunciations
+                .render_ssml(text, |span| self.generate_span(span))
+                .0
+        } else {
+            self.pronunciations.render_ssml(text, escape).0
+        }
+    }
+
+    /// Escaped `span` with say-as and paragraph breaks.
+    fn generate_span(&self, span: &str) -> String {
+        let brk = format!("<break time=\"{}ms\"/>", self.options.paragraph_break_ms);
+        let with_breaks = |s: &str| {
+            let escaped = escape(s);
+            if self.options.paragraph_break_ms == 0 {
+                escaped
+            } else {
+                escaped.replace('\n', &brk)
+            }
+        };
+        let Some(regex) = say_as_regex().filter(|_| self.options.say_as) else {
+            return with_breaks(span);
+        };
+        let mut out = String::with_capacity(span.len() + 32);
+        let mut last = 0;
+        for caps in regex.captures_iter(span) {
+            let Some(m) = caps.get(0) else {
+                continue;
+            };
+            out.push_str(&with_breaks(&span[last..m.start()]));
+            let value = escape(m.as_str());
+            if caps.name("date").is_some() {
+                out.push_str(&format!(
+                    "<say-as interpret-as=\"date\" format=\"ymd\">{value}</say-as>"
+                ));
+            } else {
+                out.push_str(&format!(
+                    "<say-as interpret-as=\"cardinal\">{value}</say-as>"
+                ));
+            }
+            last = m.end();
+        }
+        out.push_str(&with_breaks(&span[last..]));
+        out
+    }
+}
+
+/// ISO dates, and numbers with thousands separators or five or more digits.
+fn say_as_regex() -> Option<&'static Regex> {
+    static REGEX: OnceLock<Option<Regex>> = OnceLock::new();
+    REGEX
+        .get_or_init(|| {
+            Regex::new(r"(?P<date>\b\d{4}-\d{2}-\d{2}\b)|\b\d{1,3}(?:,\d{3})+\b|\b\d{5,}\b").ok()
+        })
+        .as_ref()
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Content of the `<speak>` element (the whole input when there is none).
+fn speak_body(ssml: &str) -> &str {
+    let Some(start) = ssml.find("<speak") else {
+        return ssml.trim();
+    };
+    let Some(open_end) = ssml[start..].find('>').map(|i| start + i + 1) else {
+        return ssml.trim();
+    };
+    let end = ssml.rfind("</speak>").filter(|&end| end >= open_end);
+    ssml[open_end..end.unwrap_or(ssml.len())].trim()
+}
+
+/// Text of an SSML fragment: tags removed, entities decoded, paragraphs on their own lines.
+pub fn plain_text(ssml: &str) -> String {
+    let mut out = String::with_capacity(ssml.len());
+    let mut rest = ssml;
+    while let Some(open) = rest.find('<') {
+        out.push_str(&unescape(&rest[..open]));
+        let Some(close) = rest[open..].find('>').map(|i| open + i) else {
+            rest = "";
+            break;
+        };
+        let tag = rest[open + 1..close].trim_start_matches('/');
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("");
+        match name {
+            "p" | "paragraph" => out.push('\n'),
+            "s" | "sentence" | "break" => out.push(' '),
+            _ => {}
+        }
+        rest = &rest[close + 1..];
+    }
+    out.push_str(&unescape(rest));
+    out.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passes_through_and_generates_ssml() {
+        let input = "<?xml version=\"1.0\"?><speak version=\"1.1\"><p>Tom &amp; Jerry</p>\
+                     <p><s>Wait<break time=\"1s\"/>now.</s></p></speak>";
+        let passthrough = Passthrough::new(input);
+        assert_eq!(passthrough.plain, "Tom & Jerry\nWait now.");
+        let markup = Markup::new(Pronunciations::default(), SsmlOptions::default(), None);
+        let cloud = SsmlSupport {
+            pitch: true,
+            lang: true,
+        };
+        assert_eq!(markup.to_ssml("a < b", cloud), None);
+        let pitched = SsmlOptions {
+            playback_pitch: 10,
+            ..SsmlOptions::default()
+        };
+        let markup = Markup::new(Pronunciations::default(), pitched, None);
+        assert_eq!(
+            markup.to_ssml("a < b", cloud).as_deref(),
+            Some("<prosody rate=\"+0%\" pitch=\"+10%\">a &lt; b</prosody>")
+        );
+
+        let markup = Markup::new(
+            Pronunciations::default(),
+            SsmlOptions {
+                generate: true,
+                rate: 10,
+                pitch: -5,
+                paragraph_break_ms: 500,
+                say_as: true,
+                language_tags: false,
+                playback_pitch: 0,
+            },
+            Some(passthrough.clone()),
+        );
+        assert_eq!(
+            markup.to_ssml(&passthrough.plain, cloud).as_deref(),
+            Some(passthrough.body.as_str())
+        );
+        assert_eq!(
+            markup
+                .to_ssml(
+                    "On 2024-05-01, 12,500 <fans>\ncame.",
+                    SsmlSupport {
+                        pitch: false,
+                        lang: true,
+                    }
+                )
+                .as_deref(),
+            Some(
+                "<prosody rate=\"+10%\" pitch=\"+0%\">On <say-as interpret-as=\"date\" \
+                 format=\"ymd\">2024-05-01</say-as>, <say-as interpret-as=\"cardinal\">12,500\
+                 </say-as> &lt;fans&gt;<break time=\"500ms\"/>came.</prosody>"
+            )
+        );
+    }
+
+    #[test]
+    fn test_tags_stretches_in_another_language() {
+        let markup = Markup::default().with_languages(LanguageTags::new(vec![(
+            "bom dia".to_string(),
+            "pt-BR".to_string(),
+        )]));
+        let voice = |lang| SsmlSupport { pitch: true, lang };
+        assert_eq!(
+            markup
+                .to_ssml("He said bom dia & bom dia again.", voice(true))
+                .as_deref(),
+            Some(
+                "He said <lang xml:lang=\"pt-BR\">bom dia</lang> &amp; \
+                 <lang xml:lang=\"pt-BR\">bom dia</lang> again."
+            )
+        );
+        assert_eq!(markup.to_ssml("He said bom dia.", voice(false)), None);
+        assert_eq!(markup.to_ssml("Nothing here.", voice(true)), None);
+    }
+}
@@ -1,7 +1,9 @@
 //! Timeout-wrapped selection and clipboard text capture.
 //!
 //! System selection and clipboard reads can block (e.g. on X11). This module runs them in a
-//! short-lived thread with a timeout so the UI and TTS pipeline stay responsive. Used by the
+//! short-lived thread with a timeout so the UI and TTS pipeline stay responsive. A timed-out read
+//! keeps its thread until the system answers, so reads in flight are capped
+//! (`dispatch::CAPTURES`); past the cap a capture returns nothing instead of starting another. Used by the
 //! frontend (get_selected_text, get_text_or_clipboard, get_clipboard_text commands) and by the
 //! actions layer when executing "Read Selected" or "Summarize Selected".
 
@@ -10,6 +12,7 @@ use std::time::Duration;
 
 use tracing::{debug, info, warn};
 
+use crate::dispatch;
 use crate::system;
 
 // --- Constants ---
@@ -23,8 +26,16 @@ fn read_text_with_timeout<F>(source: &'static str, reader: F) -> Option<String>
 where
     F: FnOnce() -> Option<String> + Send + 'static,
 {
+    let Some(permit) = dispatch::CAPTURES.try_acquire() else {
+        warn!(
+            source,
+            "Text capture skipped: earlier reads are still blocked"
+        );
+        return None;
+    };
     let (tx, rx) = mpsc::channel();
     std::thread::spawn(move || {
+        let _permit = permit;
         let _ = tx.send(reader());
     });
 
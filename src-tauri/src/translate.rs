@@ -0,0 +1,146 @@
+//! Translate-and-read: the selection (or given text) is translated by the backend `TRANSLATE`
+//! task (see backend-api.md; the target language goes in `instruction`) and the translation is
+//! read aloud with the voice `voice_map` has for that language (see `text::language`).
+//!
+//! `translate_language` is the default target (else the `ui_language`, else English);
+//! `translate_url` sends the task to a different server than `backend_url`. Used by the
+//! `translate_and_read` command and the "Translate Selected" action.
+
+use std::sync::mpsc;
+
+use tauri::State;
+use tracing::{debug, info};
+
+use crate::backend::{self, BackendError};
+use crate::commands_config::ConfigState;
+use crate::config::FullConfig;
+use crate::error::AppError;
+use crate::history;
+use crate::text::language::{self, LanguageSpan};
+use crate::tts;
+
+/// Backend task that translates `content` into the language named in `instruction`.
+pub const TASK: &str = "TRANSLATE";
+
+/// Target language when none is requested or configured.
+const DEFAULT_LANGUAGE: &str = "en-US";
+
+/// Target language tag: `requested`, else `translate_language`, else the UI language.
+pub fn target_language(requested: Option<&str>, cfg: &FullConfig) -> String {
+    [
+        requested,
+        cfg.translate_language.as_deref(),
+        cfg.ui_language.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .find_map(language::language_tag)
+    .unwrap_or_else(|| DEFAULT_LANGUAGE.to_string())
+}
+
+/// Translates `text` into `target` (a language tag), on `url` when set.
+pub async fn translate(
+    text: String,
+    target: &str,
+    url: Option<&str>,
+) -> Result<String, BackendError> {
+    let translation = backend::send_prompt_to(
+        url,
+        TASK.to_string(),
+        text,
+        None,
+        None,
+        Some(target.to_string()),
+    )
+    .await?;
+    info!(target, len = translation.len(), "Translated text");
+    Ok(translation)
+}
+
+/// Reads `translation` aloud as `target` text, so the voice mapped to that language speaks it.
+/// Blocks until the TTS worker has started it.
+pub fn speak_translation(
+    tts_tx: &tts::TtsState,
+    translation: String,
+    target: &str,
+) -> Result<(), AppError> {
+    let span = LanguageSpan {
+        start: 0,
+        end: translation.len(),
+        language: target.to_string(),
+    };
+    history::begin("translation", &translation);
+    let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+    let result = tts_tx
+        .send(tts::TtsRequest::Speak(
+            translation,
+            tts::InputKind::Text,
+            Some(vec![span]),
+            resp_tx,
+        ))
+        .map_err(|e| AppError::from(format!("TTS channel: {e}")))
+        .and_then(|()| {
+            resp_rx
+                .recv()
+                .map_err(|_| AppError::from("TTS worker disconnected"))?
+                .map_err(AppError::from)
+        });
+    if result.is_err() {
+        history::abandon();
+    }
+    result
+}
+
+/// Translates `text` into `target_lang` (default: `translate_language`) and reads the translation
+/// aloud. Returns the translation.
+#[tauri::command]
+pub async fn translate_and_read(
+    app: tauri::AppHandle,
+    config: State<'_, ConfigState>,
+    tts_state: State<'_, tts::TtsState>,
+    text: String,
+    target_lang: Option<String>,
+) -> Result<String, AppError> {
+    if text.trim().is_empty() {
+        return Err(AppError::NoText);
+    }
+    let (target, url) = {
+        let cfg = config
+            .lock()
+            .map_err(|_| AppError::from("Config lock poisoned"))?;
+        (
+            target_language(target_lang.as_deref(), &cfg),
+            cfg.translate_url.clone(),
+        )
+    };
+    debug!(target = %target, len = text.len(), "Translate and read");
+    let result = translate(text, &target, url.as_deref()).await;
+    if let Err(e) = &result {
+        backend::notify_rate_limited(&app, TASK, e);
+    }
+    let translation = result?;
+    let tts_tx = tts_state.inner().clone();
+    let spoken = translation.clone();
+    tokio::task::spawn_blocking(move || speak_translation(&tts_tx, spoken, &target))
+        .await
+        .map_err(|e| format!("spawn_blocking: {e}"))??;
+    Ok(translation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_language_falls_back_to_the_config() {
+        let mut cfg = FullConfig {
+            ui_language: Some("pt_BR".to_string()),
+            ..FullConfig::default()
+        };
+        assert_eq!(target_language(None, &cfg), "pt-BR");
+        cfg.translate_language = Some("de".to_string());
+        assert_eq!(target_language(None, &cfg), "de-DE");
+        assert_eq!(target_language(Some("fr"), &cfg), "fr-FR");
+        assert_eq!(target_language(None, &FullConfig::default()), "en-US");
+    }
+}
@@ -1,59 +1,152 @@
 //! System tray icon and menu.
 //!
-//! Builds the tray menu (Read Selected, Summarize Selected, Insight Editor, Hide Window,
-//! Show Window, Quit) and provides the app logo for the tray icon. Menu event handling
-//! lives in `tray_actions`; hide/show control the main window; quit is handled there too.
+//! Builds the tray menu (Read Selected, Read Screenshot, Summarize Selected, Explain Selected,
+//! Translate Selected, Insight Editor, Read Copied Text, Hide Window, Show Window, Quit) and
+//! provides the app logo for the tray icon. Menu event handling lives in `tray_actions`;
+//! hide/show control the main window; quit is handled there too.
+//! A Profile submenu is added once any named profile exists (see `profiles`).
 
-use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::Manager;
+
+use crate::actions::AppAction;
+use crate::clipboard_watch;
+use crate::ocr;
+use crate::paths;
+use crate::profiles;
+
+/// Menu id prefix of the Profile submenu entries; the rest is the profile name (empty = Default).
+pub const PROFILE_ID_PREFIX: &str = "profile:";
 
 /// Tray icon: app logo at 32x32 (icons/logo.png).
 pub const TRAY_ICON_PNG: &[u8] = include_bytes!("../icons/logo.png");
 
-/// Builds the tray menu with Read Selected, Summarize Selected, Insight Editor, Hide Window,
-/// Show Window, and Quit. Hide is enabled when the main window is visible; Show when hidden.
+/// Menu entries that run an app action (see `actions`), by menu id.
+pub const ACTION_ITEMS: [(&str, AppAction); 6] = [
+    ("read_selected", AppAction::ReadSelected),
+    ("read_screenshot", AppAction::ReadScreenshot),
+    ("summarize_selected", AppAction::Summarize),
+    ("explain_selected", AppAction::ExplainSelected),
+    ("translate_selected", AppAction::TranslateSelected),
+    ("insight_editor", AppAction::OpenEditor),
+];
+
+/// Check entry toggling the clipboard watch (see `clipboard_watch`), by menu id and label.
+pub const CLIPBOARD_WATCH_ITEM: (&str, &str) = ("clipboard_watch", "Read Copied Text");
+
+/// The other fixed entries, by menu id and label.
+pub const WINDOW_ITEMS: [(&str, &str); 3] = [
+    ("hide_window", "Hide Window"),
+    ("show_window", "Show Window"),
+    ("quit", "Quit"),
+];
+
+/// Action run by a menu entry, if it runs one.
+pub fn action_for_item(id: &str) -> Option<AppAction> {
+    ACTION_ITEMS
+        .iter()
+        .find(|(item, _)| *item == id)
+        .map(|(_, action)| *action)
+}
+
+/// Builds the tray menu with Read Selected, Read Screenshot, Summarize Selected, Explain Selected,
+/// Translate Selected, Insight Editor, Read Copied Text (checked while the clipboard watch is
+/// on), Hide Window, Show Window, and Quit. Hide is enabled when the main window is visible;
+/// Show when hidden. Read Screenshot is left out when screenshots cannot be read here (see
+/// `ocr::check_available`).
 pub fn build_tray_menu<R: tauri::Runtime>(
     app: &impl tauri::Manager<R>,
     is_main_visible: bool,
 ) -> Result<Menu<R>, tauri::Error> {
-    let read_selected =
-        MenuItem::with_id(app, "read_selected", "Read Selected", true, None::<&str>)?;
-    let summarize_selected = MenuItem::with_id(
+    let ocr_available = ocr::is_available();
+    let action_items = ACTION_ITEMS
+        .iter()
+        .filter(|(_, action)| *action != AppAction::ReadScreenshot || ocr_available)
+        .map(|(id, action)| MenuItem::with_id(app, *id, action.label(), true, None::<&str>))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (watch_id, watch_label) = CLIPBOARD_WATCH_ITEM;
+    let clipboard_watch = CheckMenuItem::with_id(
         app,
-        "summarize_selected",
-        "Summarize Selected",
+        watch_id,
+        watch_label,
         true,
+        clipboard_watch::is_enabled(),
         None::<&str>,
     )?;
-    let insight_editor =
-        MenuItem::with_id(app, "insight_editor", "Insight Editor", true, None::<&str>)?;
+    let [(hide_id, hide_label), (show_id, show_label), (quit_id, quit_label)] = WINDOW_ITEMS;
     let sep1 = PredefinedMenuItem::separator(app)?;
-    let hide_window = MenuItem::with_id(
-        app,
-        "hide_window",
-        "Hide Window",
-        is_main_visible,
-        None::<&str>,
-    )?;
+    let hide_window = MenuItem::with_id(app, hide_id, hide_label, is_main_visible, None::<&str>)?;
     let show_window = MenuItem::with_id(
         app,
-        "show_window",
-        "Show Window",
+        show_id,
+        show_label,
         true, // Always enabled so user can restore/resize if window is too small
         None::<&str>,
     )?;
     let sep2 = PredefinedMenuItem::separator(app)?;
-    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-    Menu::with_items(
+    let quit = MenuItem::with_id(app, quit_id, quit_label, true, None::<&str>)?;
+    let profile_menu = build_profile_menu(app)?;
+    let mut items: Vec<&dyn IsMenuItem<R>> = action_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<R>)
+        .collect();
+    items.extend([
+        &clipboard_watch as &dyn IsMenuItem<R>,
+        &sep1,
+        &hide_window,
+        &show_window,
+        &sep2,
+    ]);
+    if let Some(profile_menu) = &profile_menu {
+        items.push(profile_menu);
+    }
+    items.push(&quit);
+    Menu::with_items(app, &items)
+}
+
+/// Rebuilds the tray menu after something it shows changed (profiles, check marks).
+pub fn refresh_tray_menu<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(tray_icon) = app.tray_by_id("main") else {
+        return;
+    };
+    let is_visible = app
+        .get_webview_window("main")
+        .and_then(|win| win.is_visible().ok())
+        .unwrap_or(true);
+    let _ = build_tray_menu(app, is_visible).and_then(|m| tray_icon.set_menu(Some(m)));
+}
+
+/// Profile submenu with Default plus each named profile, the active one checked. `None` when
+/// there are no named profiles.
+fn build_profile_menu<R: tauri::Runtime>(
+    app: &impl tauri::Manager<R>,
+) -> Result<Option<Submenu<R>>, tauri::Error> {
+    let names = profiles::list();
+    if names.is_empty() {
+        return Ok(None);
+    }
+    let active = paths::active_profile();
+    let mut entries = vec![CheckMenuItem::with_id(
         app,
-        &[
-            &read_selected,
-            &summarize_selected,
-            &insight_editor,
-            &sep1,
-            &hide_window,
-            &show_window,
-            &sep2,
-            &quit,
-        ],
-    )
+        PROFILE_ID_PREFIX,
+        "Default",
+        true,
+        active.is_none(),
+        None::<&str>,
+    )?];
+    for name in &names {
+        entries.push(CheckMenuItem::with_id(
+            app,
+            format!("{PROFILE_ID_PREFIX}{name}"),
+            name,
+            true,
+            active.as_deref() == Some(name.as_str()),
+            None::<&str>,
+        )?);
+    }
+    let items: Vec<&dyn IsMenuItem<R>> = entries
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<R>)
+        .collect();
+    Submenu::with_items(app, "Profile", true, &items).map(Some)
 }
@@ -1,48 +1,32 @@
 //! Tray menu action handling.
 //!
-//! Dispatches tray menu events (Read Selected, Summarize Selected, Insight Editor,
-//! Hide/Show Window, Quit). Summarize runs in a background thread with a dedicated
-//! tokio runtime; runtime creation failures are surfaced to the user instead of panicking.
+//! Dispatches tray menu events (Read Selected, Read Screenshot, Summarize Selected, Explain
+//! Selected, Translate Selected, Insight Editor, Read Copied Text, Hide/Show Window, Profile,
+//! Quit). The reading, summary, explanation, translation and editor entries run through
+//! `actions::execute_action`, like the hotkeys.
 
 use tauri::menu::MenuEvent;
-use tauri::Manager;
-use tracing::{error, warn};
+use tracing::warn;
 
 use crate::actions;
-use crate::backend;
+use crate::clipboard_watch;
 use crate::commands_windows;
-use crate::config;
-use crate::hotkeys;
-use crate::text_capture;
-use crate::tts;
-use crate::windows;
+use crate::profiles;
+use crate::shutdown;
+use crate::tray;
 
 /// Handles a tray menu click. Call from `tray.on_menu_event` in setup.
 pub fn handle_tray_menu_event<R: tauri::Runtime>(app: &tauri::AppHandle<R>, event: MenuEvent) {
     let id = event.id().0.as_str();
+    if let Some(action) = tray::action_for_item(id) {
+        actions::execute_action(app, action, "tray");
+        return;
+    }
     match id {
-        "read_selected" => {
-            actions::execute_action(app, hotkeys::AppAction::ReadSelected, "tray");
-        }
-        "summarize_selected" => {
-            let app = app.clone();
-            std::thread::spawn(move || {
-                handle_summarize_selected(&app);
-            });
-        }
-        "insight_editor" => {
-            let text = text_capture::get_text_or_clipboard_impl();
-            match app.try_state::<crate::EditorInitialText>() {
-                Some(state) => {
-                    if let Err(e) =
-                        windows::open_or_focus_editor_with_text(app, &state, text, false)
-                    {
-                        warn!(error = %e, "Insight Editor: open_editor_window failed");
-                    }
-                }
-                None => {
-                    warn!("Insight Editor: EditorInitialText state not found");
-                }
+        "clipboard_watch" => {
+            let enabled = !clipboard_watch::is_enabled();
+            if let Err(e) = clipboard_watch::set_enabled_impl(app, enabled) {
+                warn!(error = %e, "Tray: toggle clipboard watch failed");
             }
         }
         "hide_window" => {
@@ -52,75 +36,14 @@ pub fn handle_tray_menu_event<R: tauri::Runtime>(app: &tauri::AppHandle<R>, even
             commands_windows::show_main_window_impl(app);
         }
         "quit" => {
-            if let Some(state) = app.try_state::<tts::TtsState>() {
-                let _ = state.inner().send(tts::TtsRequest::Shutdown);
-            }
-            app.exit(0);
-        }
-        _ => {}
-    }
-}
-
-fn handle_summarize_selected<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
-    let text = text_capture::get_text_or_clipboard_impl();
-    if text.trim().is_empty() {
-        warn!("Summarize Selected: no text available");
-        return;
-    }
-
-    let summary_muted = config::load_full_config()
-        .ok()
-        .and_then(|c| c.summary_muted)
-        .unwrap_or(false);
-    let task = if summary_muted {
-        "SUMMARIZE_PROMPT"
-    } else {
-        "SUMMARIZE_AND_READ_PROMPT"
-    };
-
-    let rt = match tokio::runtime::Runtime::new() {
-        Ok(r) => r,
-        Err(e) => {
-            error!(error = %e, "Failed to create tokio runtime for tray summarize");
-            if let Some(state) = app.try_state::<crate::EditorInitialText>() {
-                let msg = "Summary failed: could not start background task.";
-                let _ =
-                    windows::open_or_focus_editor_with_text(app, &state, msg.to_string(), false);
-            }
-            return;
+            shutdown::request_shutdown(app);
         }
-    };
-
-    let result = rt.block_on(backend::backend_prompt(
-        task.to_string(),
-        text,
-        None,
-        None,
-        None,
-    ));
-
-    match result {
-        Ok(summary) => {
-            if let Some(state) = app.try_state::<crate::EditorInitialText>() {
-                if let Err(e) =
-                    windows::open_or_focus_editor_with_text(app, &state, summary, !summary_muted)
-                {
-                    warn!(error = %e, "Summarize Selected: open_editor_window failed");
+        _ => {
+            if let Some(name) = id.strip_prefix(tray::PROFILE_ID_PREFIX) {
+                let name = (!name.is_empty()).then(|| name.to_string());
+                if let Err(e) = profiles::switch_profile_impl(app, name) {
+                    warn!(error = %e, "Tray: switch profile failed");
                 }
-            } else {
-                warn!("Summarize Selected: EditorInitialText state not found");
-            }
-        }
-        Err(e) => {
-            if let Some(state) = app.try_state::<crate::EditorInitialText>() {
-                let _ = windows::open_or_focus_editor_with_text(
-                    app,
-                    &state,
-                    format!("Summary failed: {}", e),
-                    false,
-                );
-            } else {
-                warn!(error = %e, "Summarize Selected: backend_prompt failed");
             }
         }
     }
@@ -0,0 +1,60 @@
+//! Start-of-read announcement: a short phrase ("Reading selection, 3 minutes.") spoken before the
+//! text when `announce_reads` is on.
+//!
+//! The source comes from the reading history: `history::begin` notes it right before the Speak
+//! that reads the text, and that Speak takes it. Speech that is not a new read (previews and
+//! calibration, which `detach`, and history resumes) is not announced. The worker speaks the
+//! announcement in place of the text and holds the text back as the next queue item (see
+//! `Queue::defer`), so the timeline and the history keep following the text itself.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// Speaking rate for the time estimate, in words per minute at normal speed.
+const WORDS_PER_MINUTE: usize = 160;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PENDING_SOURCE: Mutex<Option<String>> = Mutex::new(None);
+
+pub(super) fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub(super) fn note_source(source: Option<&str>) {
+    if let Ok(mut pending) = PENDING_SOURCE.lock() {
+        *pending = source.map(str::to_string);
+    }
+}
+
+/// Takes the noted source for the Speak being handled; `None` when it is not to be announced.
+pub(super) fn take_source() -> Option<String> {
+    let source = PENDING_SOURCE.lock().ok()?.take();
+    source.filter(|_| ENABLED.load(Ordering::Relaxed))
+}
+
+/// Estimated listening time of `text` in whole minutes (0 under half a minute).
+fn estimated_minutes(text: &str) -> u32 {
+    let words = text.split_whitespace().count();
+    ((words + WORDS_PER_MINUTE / 2) / WORDS_PER_MINUTE) as u32
+}
+
+/// The phrase to speak before `text`, in the UI language.
+pub(super) fn announcement(source: &str, text: &str) -> String {
+    crate::i18n::read_announcement(
+        source,
+        estimated_minutes(text),
+        crate::i18n::configured_language(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_rounds_to_whole_minutes() {
+        assert_eq!(estimated_minutes("A few words."), 0);
+        assert_eq!(estimated_minutes(&"word ".repeat(100)), 1);
+        assert_eq!(estimated_minutes(&"word ".repeat(500)), 3);
+    }
+}
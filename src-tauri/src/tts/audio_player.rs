@@ -1,17 +1,39 @@
-//! Minimal audio playback for TTS: rodio sink, pitch-preserving speed via SoundTouch.
+//! Minimal audio playback for TTS: rodio sink with a segment queue, pitch-preserving speed via
+//! SoundTouch.
 
 use std::io::Cursor;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
 use soundtouch::{Setting, SoundTouch};
-use tracing::{debug, error, trace, warn};
+use tracing::{debug, error, info, trace, warn};
 
+use super::output;
+use super::priority;
+use super::trace::{self, PlaybackTraceEvent};
 use super::TTSError;
 
-/// Audio playback for TTS. Plays f32 samples via rodio; supports play and stop.
-/// Speed changes use SoundTouch time-stretching (pitch-preserving). Original PCM is kept
-/// so speed can be changed while playing (re-stretch + seek).
+/// Fade at each end of a segment (see `declick`).
+const DECLICK_MS: usize = 3;
+/// Volume steps of `AudioPlayer::fade_out`.
+const FADE_STEP: Duration = Duration::from_millis(50);
+
+/// Outcome of `AudioPlayer::seek`: where playback landed, in content time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct SeekResult {
+    pub success: bool,
+    pub at_start: bool,
+    pub at_end: bool,
+    pub position_ms: u64,
+    /// Content duration of the segments queued so far.
+    pub duration_ms: u64,
+}
+
+/// Audio playback for TTS. Plays a queue of f32 mono segments (one per synthesized chunk) via
+/// rodio; later segments can be appended while earlier ones play. Speed changes use SoundTouch
+/// time-stretching (pitch-preserving), and pitch changes its pitch shift (speed-preserving).
+/// Original PCM is kept per segment so speed and pitch can be changed and seeks can cross segment
+/// boundaries (re-stretch + rebuild the sink).
 pub struct AudioPlayer {
     sample_rate: u32,
     _stream: Option<OutputStream>,
@@ -20,21 +42,37 @@ pub struct AudioPlayer {
     volume: f32,
     /// Playback speed factor (1.0 = normal). Applied via time-stretch; content position = get_pos() * speed.
     speed: f32,
-    /// Original PCM (mono f32) for the current utterance. Kept so we can re-stretch on speed change.
-    original_pcm: Vec<f32>,
-    /// Content duration in ms from original_pcm length and sample_rate.
-    total_duration_ms: u64,
+    /// Pitch ratio (1.0 = unchanged), applied with the time-stretch.
+    pitch: f32,
+    /// Original PCM (mono f32) per queued segment, in playback order.
+    segments: Vec<Vec<f32>>,
+    /// Content duration of each segment in ms.
+    segment_ms: Vec<u64>,
+    /// Index of the first segment appended to the current sink.
+    sink_start: usize,
+    /// Number of segments appended to the current sink.
+    sink_appended: usize,
+    /// Content position a seek moved to while paused. The sink catches up on its next tick; this
+    /// keeps `get_position` exact in the meantime. Cleared on resume and when playback restarts.
+    paused_position_ms: Option<u64>,
+    /// Device the stream was opened on (see `output::open`).
+    output_name: Option<String>,
+    output_checked: Instant,
 }
 
 impl AudioPlayer {
     /// Create a new audio player with the given sample rate.
     pub fn new(sample_rate: u32) -> Result<Self, TTSError> {
         trace!(sample_rate, "AudioPlayer::new");
-        let (stream, stream_handle) = OutputStream::try_default().map_err(|e| {
-            error!("Failed to open audio output: {e}");
-            TTSError::AudioError(format!("Failed to open audio output: {e}"))
+        let (stream, stream_handle, output_name) = output::open().map_err(|e| {
+            error!("{e}");
+            trace::record(|| PlaybackTraceEvent::DeviceError {
+                message: e.to_string(),
+            });
+            e
         })?;
-        debug!(sample_rate, "Audio output stream initialized");
+        debug!(sample_rate, output = ?output_name, "Audio output stream initialized");
+        trace::record(|| PlaybackTraceEvent::DeviceOpened { sample_rate });
         Ok(Self {
             sample_rate,
             _stream: Some(stream),
@@ -42,34 +80,121 @@ impl AudioPlayer {
             sink: None,
             volume: 1.0,
             speed: 1.0,
-            original_pcm: Vec::new(),
-            total_duration_ms: 0,
+            pitch: 1.0,
+            segments: Vec::new(),
+            segment_ms: Vec::new(),
+            sink_start: 0,
+            sink_appended: 0,
+            paused_position_ms: None,
+            output_name,
+            output_checked: Instant::now(),
         })
     }
 
-    /// Load audio data and start playback. Audio should be normalized f32, -1.0 to 1.0.
-    pub fn play_audio(&mut self, audio_data: Vec<f32>) -> Result<(), TTSError> {
+    /// Appends a segment to the playback queue (normalized f32, -1.0 to 1.0). Starts playback if
+    /// nothing is queued; otherwise the segment plays after the previous one.
+    pub fn append_audio(
+        &mut self,
+        mut audio_data: Vec<f32>,
+        sample_rate: u32,
+    ) -> Result<(), TTSError> {
+        if audio_data.is_empty() {
+            return Err(TTSError::AudioError("No audio data to play".into()));
+        }
+        declick(&mut audio_data, sample_rate);
+        self.sample_rate = sample_rate;
+        let index = self.segments.len();
+        let content_ms = self.content_duration_ms_from_len(audio_data.len());
         debug!(
+            index,
             samples = audio_data.len(),
-            sample_rate = self.sample_rate,
-            "AudioPlayer::play_audio"
+            sample_rate,
+            content_ms,
+            "AudioPlayer::append_audio"
         );
-        self.original_pcm = audio_data;
-        self.total_duration_ms = self.content_duration_ms_from_len(self.original_pcm.len());
-        debug!(
-            total_duration_ms = self.total_duration_ms,
-            "Calculated duration"
+        trace::record(|| PlaybackTraceEvent::SegmentQueued {
+            index,
+            samples: audio_data.len(),
+            content_ms,
+        });
+        self.segments.push(audio_data);
+        self.segment_ms.push(content_ms);
+
+        // Appending to a drained sink (synthesis fell behind) resumes playback.
+        if let Some(sink) = &self.sink {
+            sink.append(self.stretched_source(&self.segments[index])?);
+            self.sink_appended += 1;
+            Ok(())
+        } else {
+            // A stream left idle may be on a device that is gone by now.
+            self.check_output(true);
+            self.start_playback_from(index, 0, false)
+        }
+    }
+
+    /// Reopens the output when the device it should use changed (see `output`) and resumes at
+    /// the same position. Checks at most every `output::CHECK_INTERVAL` unless `force`. Returns
+    /// whether it reconnected.
+    pub fn check_output(&mut self, force: bool) -> bool {
+        if !force && self.output_checked.elapsed() < output::CHECK_INTERVAL {
+            return false;
+        }
+        self.output_checked = Instant::now();
+        if output::target_name() == self.output_name {
+            return false;
+        }
+        self.reopen_output()
+    }
+
+    /// Reopens the output even when the device is the same, and resumes at the same position
+    /// (paused if it was). After the system wakes from sleep the old stream may be dead.
+    pub fn reopen_output(&mut self) -> bool {
+        self.output_checked = Instant::now();
+        let content_ms = self.content_position_ms();
+        let (stream, stream_handle, output_name) = match output::open() {
+            Ok(opened) => opened,
+            Err(e) => {
+                warn!(error = %e, "Reopening the audio output failed");
+                trace::record(|| PlaybackTraceEvent::DeviceError {
+                    message: e.to_string(),
+                });
+                return false;
+            }
+        };
+        info!(
+            from = ?self.output_name,
+            to = ?output_name,
+            content_ms,
+            "Reconnecting the audio output"
         );
-        self.start_playback()
+        trace::record(|| PlaybackTraceEvent::DeviceOpened {
+            sample_rate: self.sample_rate,
+        });
+        self.stream_handle = Some(stream_handle);
+        self._stream = Some(stream);
+        self.output_name = output_name;
+        // The sink plays into the old stream: rebuild it, or drop it when it has drained.
+        self.restart_at(content_ms, "reopen_output");
+        if self.sink.as_ref().is_some_and(Sink::empty) {
+            self.sink = None;
+        }
+        true
     }
 
-    /// Play raw encoded audio (MP3/Opus). Decodes to PCM once, stores as original, then uses common play path.
-    pub fn play_audio_raw(
-        &mut self,
-        audio_data: Vec<u8>,
-        _sample_rate: u32,
-    ) -> Result<(), TTSError> {
-        debug!(bytes = audio_data.len(), "AudioPlayer::play_audio_raw");
+    /// Peak amplitude (0.0-1.0) of the audio queued so far in `buckets` equal slices of content
+    /// time, from the original (unstretched) PCM, so it lines up with `get_position`.
+    pub fn waveform(&self, buckets: usize) -> Vec<f32> {
+        let segments: Vec<(&[f32], u64)> = self
+            .segments
+            .iter()
+            .map(Vec::as_slice)
+            .zip(self.segment_ms.iter().copied())
+            .collect();
+        envelope(&segments, buckets)
+    }
+
+    /// Decodes encoded audio (MP3/Opus/WAV) to mono f32 PCM. Returns the samples and sample rate.
+    pub fn decode_audio(audio_data: Vec<u8>) -> Result<(Vec<f32>, u32), TTSError> {
         if audio_data.is_empty() {
             return Err(TTSError::AudioError("No audio data to play".into()));
         }
@@ -77,12 +202,14 @@ impl AudioPlayer {
         let cursor = Cursor::new(audio_data);
         let decoder = Decoder::new(cursor).map_err(|e| {
             error!("Failed to decode audio: {}", e);
+            trace::record(|| PlaybackTraceEvent::DecodeError {
+                message: e.to_string(),
+            });
             TTSError::AudioError(format!("Failed to decode audio: {}", e))
         })?;
 
         let sample_rate = decoder.sample_rate();
         let channels = decoder.channels();
-        self.sample_rate = sample_rate;
 
         let samples_i16: Vec<i16> = decoder.collect();
         let pcm_f32: Vec<f32> = if channels == 2 {
@@ -96,50 +223,59 @@ impl AudioPlayer {
                 .map(|s| s as f32 / 32768.0)
                 .collect()
         };
+        Ok((pcm_f32, sample_rate))
+    }
 
-        self.original_pcm = pcm_f32;
-        self.total_duration_ms = self.content_duration_ms_from_len(self.original_pcm.len());
-        self.start_playback()
+    /// Index of the segment currently playing (the last one once the queue has drained).
+    pub fn current_segment(&self) -> usize {
+        let last = self.segments.len().saturating_sub(1);
+        match &self.sink {
+            Some(sink) => {
+                let finished = self.sink_appended.saturating_sub(sink.len());
+                (self.sink_start + finished).min(last)
+            }
+            None => 0,
+        }
     }
 
     /// Set playback speed (1.0 = normal). Pitch-preserving. If playing, re-stretches and seeks to same content position.
     pub fn set_speed(&mut self, value: f32) {
-        let (was_playing, was_paused, content_ms) = self
-            .sink
-            .as_ref()
-            .map(|s| {
-                (
-                    !s.empty(),
-                    s.is_paused(),
-                    (s.get_pos().as_secs_f64() * self.speed as f64 * 1000.0) as u64,
-                )
-            })
-            .unwrap_or((false, false, 0));
-
+        let content_ms = self.content_position_ms();
         self.speed = value;
+        trace::record(|| PlaybackTraceEvent::SpeedChanged { speed: value });
+        self.restart_at(content_ms, "set_speed");
+    }
 
-        if was_playing && !self.original_pcm.is_empty() {
-            if let Some(sink) = self.sink.take() {
-                sink.stop();
-            }
-            if let Err(e) = self.start_playback() {
-                warn!(error = %e, "set_speed: start_playback failed");
+    /// Set the pitch as a frequency ratio (1.0 = unchanged) without changing the speed. If
+    /// playing, re-renders from the same content position.
+    pub fn set_pitch(&mut self, ratio: f32) {
+        if (self.pitch - ratio).abs() < 1e-6 {
+            return;
+        }
+        let content_ms = self.content_position_ms();
+        self.pitch = ratio;
+        self.restart_at(content_ms, "set_pitch");
+    }
+
+    /// Rebuilds playback at `content_ms` after a speed or pitch change, keeping pause state.
+    fn restart_at(&mut self, content_ms: u64, caller: &str) {
+        let (was_playing, was_paused) = self
+            .sink
+            .as_ref()
+            .map(|s| (!s.empty(), s.is_paused()))
+            .unwrap_or((false, false));
+        if was_playing && !self.segments.is_empty() {
+            let (index, offset_ms) = self.locate(content_ms);
+            if let Err(e) = self.start_playback_from(index, offset_ms, was_paused) {
+                warn!(error = %e, "{caller}: start_playback failed");
                 return;
             }
-            if let Some(sink) = &self.sink {
-                let seek_output_secs = content_ms as f64 / 1000.0 / self.speed as f64;
-                let seek_duration = Duration::from_secs_f64(seek_output_secs);
-                if let Err(e) = sink.try_seek(seek_duration) {
-                    warn!(error = %e, "set_speed: seek failed");
-                }
-                if was_paused {
-                    sink.pause();
-                }
+            if was_paused {
+                self.paused_position_ms = Some(content_ms);
             }
         }
     }
 
-    /// Convert raw PCM bytes (16-bit signed LE mono) to normalized f32 samples.
     pub fn pcm_to_f32(pcm_bytes: &[u8]) -> Vec<f32> {
         pcm_bytes
             .chunks_exact(2)
@@ -155,12 +291,29 @@ impl AudioPlayer {
         trace!("AudioPlayer::stop");
         if let Some(sink) = self.sink.take() {
             sink.stop();
+            trace::record(|| PlaybackTraceEvent::Stopped);
         }
-        self.original_pcm.clear();
-        self.total_duration_ms = 0;
+        self.segments.clear();
+        self.segment_ms.clear();
+        self.sink_start = 0;
+        self.sink_appended = 0;
+        self.paused_position_ms = None;
         Ok(())
     }
 
+    /// Lowers the volume to silence over `duration`, then stops like `stop`. Blocks while fading;
+    /// paused or drained playback stops right away. Later playback uses the volume set before.
+    pub fn fade_out(&mut self, duration: Duration) -> Result<(), TTSError> {
+        if let Some(sink) = self.sink.as_ref().filter(|s| !s.empty() && !s.is_paused()) {
+            let steps = (duration.as_millis() / FADE_STEP.as_millis()).max(1) as u32;
+            for step in 1..=steps {
+                sink.set_volume(fade_volume(self.volume, step, steps));
+                std::thread::sleep(FADE_STEP);
+            }
+        }
+        self.stop()
+    }
+
     /// Toggle pause state. Returns the new paused status (true if paused, false if playing).
     pub fn toggle_pause(&mut self) -> Result<bool, TTSError> {
         trace!("AudioPlayer::toggle_pause");
@@ -168,9 +321,12 @@ impl AudioPlayer {
             let was_paused = sink.is_paused();
             if was_paused {
                 sink.play();
+                self.paused_position_ms = None;
+                trace::record(|| PlaybackTraceEvent::Resumed);
                 Ok(false)
             } else {
                 sink.pause();
+                trace::record(|| PlaybackTraceEvent::Paused);
                 Ok(true)
             }
         } else {
@@ -182,6 +338,7 @@ impl AudioPlayer {
     pub fn set_volume_percent(&mut self, volume_percent: u8) {
         let normalized = (volume_percent as f32 / 100.0).clamp(0.0, 1.0);
         self.volume = normalized;
+        trace::record(|| PlaybackTraceEvent::VolumeChanged { volume: normalized });
         if let Some(sink) = &self.sink {
             sink.set_volume(normalized);
         }
@@ -198,26 +355,43 @@ impl AudioPlayer {
         }
     }
 
-    /// Get current playback position and total duration in milliseconds (content time).
+    /// Get current playback position and total duration in milliseconds (content time). The
+    /// total covers the segments queued so far.
     pub fn get_position(&self) -> (u64, u64) {
-        if self.total_duration_ms == 0 {
+        let total_ms = self.total_duration_ms();
+        if total_ms == 0 {
             return (0, 0);
         }
         if let Some(sink) = &self.sink {
-            let output_secs = sink.get_pos().as_secs_f64();
-            let current_content_ms = (output_secs * self.speed as f64 * 1000.0) as u64;
-            (
-                current_content_ms.min(self.total_duration_ms),
-                self.total_duration_ms,
-            )
+            let current_content_ms = self.content_position_ms();
+            trace::record(|| PlaybackTraceEvent::Position {
+                content_ms: current_content_ms,
+                sink_pos_ms: sink.get_pos().as_millis() as u64,
+                queued_sources: sink.len(),
+                paused: sink.is_paused(),
+            });
+            (current_content_ms.min(total_ms), total_ms)
         } else {
-            (0, self.total_duration_ms)
+            (0, total_ms)
         }
     }
 
-    /// Seek by the given offset in milliseconds. Returns (success, at_start, at_end).
-    pub fn seek(&mut self, offset_ms: i64) -> Result<(bool, bool, bool), TTSError> {
-        if self.original_pcm.is_empty() || self.total_duration_ms == 0 {
+    /// Seek by the given offset in milliseconds across all queued segments. Works while paused:
+    /// playback stays paused at the new position.
+    pub fn seek(&mut self, offset_ms: i64) -> Result<SeekResult, TTSError> {
+        let target_ms = seek_target(
+            self.content_position_ms(),
+            offset_ms,
+            self.total_duration_ms(),
+        );
+        self.seek_to(target_ms)
+    }
+
+    /// Seek to a content position in milliseconds, clamped to the queued segments. Like `seek`,
+    /// works while paused.
+    pub fn seek_to(&mut self, position_ms: u64) -> Result<SeekResult, TTSError> {
+        let total_ms = self.total_duration_ms();
+        if self.segments.is_empty() || total_ms == 0 {
             return Err(TTSError::AudioError("No audio data loaded".into()));
         }
 
@@ -226,41 +400,91 @@ impl AudioPlayer {
             .as_ref()
             .ok_or_else(|| TTSError::AudioError("No active playback".into()))?;
 
-        if sink.is_paused() {
-            return Err(TTSError::AudioError("Cannot seek while paused".into()));
-        }
+        let paused = sink.is_paused();
         if sink.empty() {
             return Err(TTSError::AudioError("Playback has finished".into()));
         }
 
-        let output_secs = sink.get_pos().as_secs_f64();
-        let current_content_ms = (output_secs * self.speed as f64 * 1000.0) as u64;
+        let current_content_ms = self.content_position_ms();
 
-        let offset_abs = offset_ms.unsigned_abs();
-        let new_content_ms = if offset_ms < 0 {
-            current_content_ms.saturating_sub(offset_abs)
+        let clamped_ms = position_ms.min(total_ms);
+        let at_start = clamped_ms == 0;
+        let at_end = clamped_ms >= total_ms;
+
+        let (index, within_ms) = self.locate(clamped_ms);
+        let seek_result = if index == self.current_segment() {
+            let seek_duration =
+                Duration::from_secs_f64(within_ms as f64 / 1000.0 / self.speed as f64);
+            sink.try_seek(seek_duration)
+                .map_err(|e| TTSError::AudioError(format!("Seek failed: {e}")))
         } else {
-            current_content_ms.saturating_add(offset_abs)
+            self.start_playback_from(index, within_ms, paused)
         };
-
-        let clamped_ms = new_content_ms.min(self.total_duration_ms);
-        let at_start = clamped_ms == 0;
-        let at_end = clamped_ms >= self.total_duration_ms;
-
-        let seek_duration = Duration::from_secs_f64(clamped_ms as f64 / 1000.0 / self.speed as f64);
-
-        match sink.try_seek(seek_duration) {
+        trace::record(|| PlaybackTraceEvent::Seek {
+            from_ms: current_content_ms,
+            to_ms: clamped_ms,
+            ok: seek_result.is_ok(),
+        });
+        match seek_result {
             Ok(()) => {
-                trace!(current_content_ms, clamped_ms, offset_ms, "Seek successful");
-                Ok((true, at_start, at_end))
+                trace!(
+                    current_content_ms,
+                    clamped_ms,
+                    index,
+                    paused,
+                    "Seek successful"
+                );
+                if paused {
+                    self.paused_position_ms = Some(clamped_ms);
+                }
+                Ok(SeekResult {
+                    success: true,
+                    at_start,
+                    at_end,
+                    position_ms: clamped_ms,
+                    duration_ms: total_ms,
+                })
             }
             Err(e) => {
                 warn!(error = %e, "Seek failed");
-                Err(TTSError::AudioError(format!("Seek failed: {e}")))
+                Err(e)
             }
         }
     }
 
+    fn total_duration_ms(&self) -> u64 {
+        self.segment_ms.iter().sum()
+    }
+
+    /// Content position: durations of finished segments plus the position in the current one.
+    fn content_position_ms(&self) -> u64 {
+        let Some(sink) = &self.sink else {
+            return 0;
+        };
+        if let Some(position_ms) = self.paused_position_ms.filter(|_| sink.is_paused()) {
+            return position_ms;
+        }
+        if sink.empty() {
+            return self.total_duration_ms();
+        }
+        let index = self.current_segment();
+        let before: u64 = self.segment_ms[..index].iter().sum();
+        let within = (sink.get_pos().as_secs_f64() * self.speed as f64 * 1000.0) as u64;
+        before + within.min(self.segment_ms[index])
+    }
+
+    /// Maps a content position to (segment index, offset within the segment in ms).
+    fn locate(&self, content_ms: u64) -> (usize, u64) {
+        let mut start = 0;
+        for (index, ms) in self.segment_ms.iter().enumerate() {
+            if content_ms < start + ms || index + 1 == self.segment_ms.len() {
+                return (index, content_ms.saturating_sub(start).min(*ms));
+            }
+            start += ms;
+        }
+        (0, 0)
+    }
+
     fn content_duration_ms_from_len(&self, num_samples: usize) -> u64 {
         if num_samples == 0 || self.sample_rate == 0 {
             return 0;
@@ -268,14 +492,20 @@ impl AudioPlayer {
         (num_samples as f64 / self.sample_rate as f64 * 1000.0) as u64
     }
 
-    /// Build playback buffer (time-stretch if speed != 1.0), then create sink and play at 1.0x.
-    fn start_playback(&mut self) -> Result<(), TTSError> {
-        trace!("AudioPlayer::start_playback");
+    /// Rebuilds the sink with segments from `index` onward (time-stretched if speed != 1.0) and
+    /// starts playing `offset_ms` into the first one.
+    fn start_playback_from(
+        &mut self,
+        index: usize,
+        offset_ms: u64,
+        paused: bool,
+    ) -> Result<(), TTSError> {
+        trace!(index, offset_ms, paused, "AudioPlayer::start_playback_from");
         if let Some(sink) = self.sink.take() {
             sink.stop();
         }
-
-        if self.original_pcm.is_empty() {
+        self.paused_position_ms = None;
+        if index >= self.segments.len() {
             return Err(TTSError::AudioError("No audio data to play".into()));
         }
 
@@ -283,17 +513,56 @@ impl AudioPlayer {
             .stream_handle
             .as_ref()
             .ok_or_else(|| TTSError::AudioError("No audio output available".into()))?;
+        let sink = Sink::try_new(stream_handle).map_err(|e| {
+            error!("Failed to create audio sink: {e}");
+            TTSError::AudioError(format!("Failed to create audio sink: {e}"))
+        })?;
+        sink.set_volume(self.volume);
+        if paused {
+            sink.pause();
+        }
 
-        let to_play: Vec<f32> = if (self.speed - 1.0).abs() < 1e-6 {
-            self.original_pcm.clone()
-        } else {
-            let mut st = SoundTouch::new();
-            st.set_channels(1)
-                .set_sample_rate(self.sample_rate)
-                .set_tempo(self.speed as f64)
-                .set_setting(Setting::UseQuickseek, 1);
-            st.generate_audio(&self.original_pcm)
-        };
+        let mut buffer_samples = 0;
+        for pcm in &self.segments[index..] {
+            buffer_samples += pcm.len();
+            sink.append(self.stretched_source(pcm)?);
+        }
+        if offset_ms > 0 {
+            let seek = Duration::from_secs_f64(offset_ms as f64 / 1000.0 / self.speed as f64);
+            if let Err(e) = sink.try_seek(seek) {
+                warn!(error = %e, "start_playback_from: seek failed");
+            }
+        }
+        trace::record(|| PlaybackTraceEvent::PlaybackStarted {
+            buffer_samples,
+            sample_rate: self.sample_rate,
+            speed: self.speed,
+            content_ms: self.total_duration_ms(),
+        });
+        self.sink_start = index;
+        self.sink_appended = self.segments.len() - index;
+        self.sink = Some(sink);
+        Ok(())
+    }
+
+    /// Time-stretches one segment for the current speed and pitch and wraps it as a seekable WAV
+    /// source.
+    fn stretched_source(&self, pcm: &[f32]) -> Result<Decoder<Cursor<Vec<u8>>>, TTSError> {
+        let to_play: Vec<f32> =
+            if (self.speed - 1.0).abs() < 1e-6 && (self.pitch - 1.0).abs() < 1e-6 {
+                pcm.to_vec()
+            } else {
+                let (sample_rate, speed, pitch) = (self.sample_rate, self.speed, self.pitch);
+                priority::run_at_synthesis_priority(|| {
+                    let mut st = SoundTouch::new();
+                    st.set_channels(1)
+                        .set_sample_rate(sample_rate)
+                        .set_tempo(speed as f64)
+                        .set_pitch(pitch as f64)
+                        .set_setting(Setting::UseQuickseek, 1);
+                    st.generate_audio(pcm)
+                })
+            };
 
         if to_play.is_empty() {
             return Err(TTSError::AudioError(
@@ -307,24 +576,17 @@ impl AudioPlayer {
             .collect();
 
         let wav_data = Self::create_wav(&samples_i16, self.sample_rate);
-        let cursor = Cursor::new(wav_data);
-        let source = Decoder::new(cursor).map_err(|e| {
+        Decoder::new(Cursor::new(wav_data)).map_err(|e| {
             error!("Failed to decode WAV: {e}");
+            trace::record(|| PlaybackTraceEvent::DecodeError {
+                message: e.to_string(),
+            });
             TTSError::AudioError(format!("Failed to decode WAV: {e}"))
-        })?;
-
-        let sink = Sink::try_new(stream_handle).map_err(|e| {
-            error!("Failed to create audio sink: {e}");
-            TTSError::AudioError(format!("Failed to create audio sink: {e}"))
-        })?;
-
-        sink.set_volume(self.volume);
-        sink.append(source);
-        self.sink = Some(sink);
-        Ok(())
+        })
     }
 
-    fn create_wav(samples: &[i16], sample_rate: u32) -> Vec<u8> {
+    /// Wraps mono 16-bit PCM in a WAV header.
+    pub(super) fn create_wav(samples: &[i16], sample_rate: u32) -> Vec<u8> {
         let num_samples = samples.len();
         let data_size = num_samples * 2;
         let file_size = 36 + data_size;
@@ -349,3 +611,90 @@ impl AudioPlayer {
         wav
     }
 }
+
+/// Peak envelope over (samples, duration in ms) segments played back to back.
+fn envelope(segments: &[(&[f32], u64)], buckets: usize) -> Vec<f32> {
+    let mut peaks = vec![0.0f32; buckets];
+    let total_ms: u64 = segments.iter().map(|(_, ms)| ms).sum();
+    if buckets == 0 || total_ms == 0 {
+        return peaks;
+    }
+    let mut offset_ms = 0;
+    for (pcm, ms) in segments {
+        let len = pcm.len().max(1) as u64;
+        for (i, sample) in pcm.iter().enumerate() {
+            let at_ms = offset_ms + i as u64 * ms / len;
+            let bucket = (at_ms * buckets as u64 / total_ms).min(buckets as u64 - 1) as usize;
+            peaks[bucket] = peaks[bucket].max(sample.abs().min(1.0));
+        }
+        offset_ms += ms;
+    }
+    peaks
+}
+
+/// Fades the first and last `DECLICK_MS` of a segment in and out, so a segment that starts or
+/// ends mid-waveform does not click against its neighbour.
+fn declick(pcm: &mut [f32], sample_rate: u32) {
+    let ramp = (sample_rate as usize * DECLICK_MS / 1000).min(pcm.len() / 2);
+    let len = pcm.len();
+    for i in 0..ramp {
+        let gain = i as f32 / ramp as f32;
+        pcm[i] *= gain;
+        pcm[len - 1 - i] *= gain;
+    }
+}
+
+/// Volume after `step` of `steps` fading out from `volume`.
+fn fade_volume(volume: f32, step: u32, steps: u32) -> f32 {
+    volume * (1.0 - step as f32 / steps as f32)
+}
+
+/// Content position `offset_ms` away from `current_ms`, clamped to `0..=total_ms`.
+fn seek_target(current_ms: u64, offset_ms: i64, total_ms: u64) -> u64 {
+    let offset_abs = offset_ms.unsigned_abs();
+    let target_ms = if offset_ms < 0 {
+        current_ms.saturating_sub(offset_abs)
+    } else {
+        current_ms.saturating_add(offset_abs)
+    };
+    target_ms.min(total_ms)
+}
+
+/// Content position at `percent` (0-100) of `total_ms`.
+pub(super) fn percent_position(total_ms: u64, percent: f64) -> u64 {
+    (total_ms as f64 * percent.clamp(0.0, 100.0) / 100.0).round() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_spans_segments_by_duration() {
+        let loud = [0.5, -0.9, 0.2, 0.1];
+        let quiet = [0.1, -0.1];
+        // The quiet segment is as long as the loud one despite fewer samples.
+        let peaks = envelope(&[(&loud, 100), (&quiet, 100)], 4);
+        assert_eq!(peaks, [0.9, 0.2, 0.1, 0.1]);
+        assert!(envelope(&[], 3).iter().all(|p| *p == 0.0));
+    }
+
+    #[test]
+    fn test_seek_targets_clamp_to_the_queued_audio() {
+        assert_eq!(seek_target(3_000, -5_000, 10_000), 0);
+        assert_eq!(seek_target(3_000, 5_000, 10_000), 8_000);
+        assert_eq!(seek_target(8_000, 5_000, 10_000), 10_000);
+        assert_eq!(percent_position(10_000, 25.0), 2_500);
+        assert_eq!(percent_position(10_000, 150.0), 10_000);
+    }
+
+    #[test]
+    fn test_declick_fades_segment_edges() {
+        let mut pcm = vec![1.0; 1000];
+        declick(&mut pcm, 1000);
+        assert_eq!(pcm[0], 0.0);
+        assert_eq!(pcm[999], 0.0);
+        assert!(pcm[1] > 0.0 && pcm[1] < 1.0);
+        assert_eq!(pcm[500], 1.0);
+    }
+}
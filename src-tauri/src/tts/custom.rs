@@ -0,0 +1,176 @@
+//! Custom server provider: a locally hosted voice-cloning TTS server (XTTS, OpenVoice) speaking
+//! with the user's own voice.
+//!
+//! Targets the xtts-api-server protocol, which the OpenVoice wrappers mirror: `POST
+//! /tts_to_audio/` with the text, the speaker (a reference WAV or speaker embedding file on the
+//! server's machine, or a speaker name the server knows) and the language returns WAV audio.
+//! Speakers are listed with `GET /speakers_list` (see `voices::fetch_custom_server_voices`).
+
+use std::time::Duration;
+
+use serde::Serialize;
+use tracing::{debug, info};
+
+use super::audio_player::{AudioPlayer, SeekResult};
+use super::stream::{ChunkAudio, SynthesizeFn};
+use super::TTSError;
+use crate::text::ssml::Markup;
+
+pub const DEFAULT_SERVER_URL: &str = "http://localhost:8020";
+/// Cloning models are slow on CPU; a long sentence can take many seconds.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+/// Player rate until the first chunk reports its own (XTTS outputs 24 kHz).
+const SAMPLE_RATE: u32 = 24000;
+
+/// Where and how to synthesize: the server, speaker and language from config.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CustomServer {
+    pub url: String,
+    pub speaker: String,
+    pub language: String,
+}
+
+#[derive(Serialize)]
+struct TtsRequest<'a> {
+    text: &'a str,
+    speaker_wav: &'a str,
+    language: &'a str,
+}
+
+pub struct CustomTTSProvider {
+    player: AudioPlayer,
+    server: CustomServer,
+}
+
+impl CustomTTSProvider {
+    pub fn new(server: Option<CustomServer>) -> Result<Self, TTSError> {
+        let server = server.ok_or_else(|| {
+            TTSError::NotConfigured("No speaker set for the custom voice server".into())
+        })?;
+        info!(
+            url = %server.url,
+            speaker = %server.speaker,
+            "Initializing custom server TTS provider"
+        );
+        let player = AudioPlayer::new(SAMPLE_RATE)?;
+        Ok(Self { player, server })
+    }
+
+    /// Returns a function that synthesizes text with the configured speaker (runs on the
+    /// synthesis thread). The servers take plain text, so phoneme overrides are respelled.
+    pub fn synthesizer(&self, markup: Markup) -> SynthesizeFn {
+        let server = self.server.clone();
+        let client = reqwest::blocking::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build();
+        Box::new(move |text: &str| {
+            let client = client
+                .as_ref()
+                .map_err(|e| TTSError::ProcessError(format!("HTTP client: {e}")))?;
+            debug!(
+                chars = text.len(),
+                text_preview = %text.chars().take(50).collect::<String>(),
+                "Custom server: synthesizing chunk"
+            );
+            let spoken = markup.respell(text);
+            let wav = synthesize_bytes(client, &server, &spoken)?;
+            let (pcm, sample_rate) = AudioPlayer::decode_audio(wav)?;
+            info!(samples = pcm.len(), "Custom server: audio generated");
+            Ok(ChunkAudio::estimated(text, pcm, sample_rate))
+        })
+    }
+
+    pub fn append_audio(&mut self, audio_data: Vec<f32>, sample_rate: u32) -> Result<(), TTSError> {
+        self.player.append_audio(audio_data, sample_rate)
+    }
+
+    pub fn current_segment(&self) -> usize {
+        self.player.current_segment()
+    }
+
+    pub fn stop(&mut self) -> Result<(), TTSError> {
+        self.player.stop()
+    }
+
+    pub fn fade_out(&mut self, duration: Duration) -> Result<(), TTSError> {
+        self.player.fade_out(duration)
+    }
+
+    pub fn toggle_pause(&mut self) -> Result<bool, TTSError> {
+        self.player.toggle_pause()
+    }
+
+    pub fn get_status(&self) -> (bool, bool) {
+        self.player.get_status()
+    }
+
+    pub fn seek(&mut self, offset_ms: i64) -> Result<SeekResult, TTSError> {
+        self.player.seek(offset_ms)
+    }
+
+    pub fn seek_to(&mut self, position_ms: u64) -> Result<SeekResult, TTSError> {
+        self.player.seek_to(position_ms)
+    }
+
+    pub fn check_output(&mut self, force: bool) -> bool {
+        self.player.check_output(force)
+    }
+
+    pub fn reopen_output(&mut self) -> bool {
+        self.player.reopen_output()
+    }
+
+    pub fn get_position(&self) -> (u64, u64) {
+        self.player.get_position()
+    }
+
+    pub fn waveform(&self, buckets: usize) -> Vec<f32> {
+        self.player.waveform(buckets)
+    }
+
+    pub fn set_volume(&mut self, volume_percent: u8) {
+        self.player.set_volume_percent(volume_percent);
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.player.set_speed(speed);
+    }
+
+    pub fn set_pitch(&mut self, ratio: f32) {
+        self.player.set_pitch(ratio);
+    }
+}
+
+fn synthesize_bytes(
+    client: &reqwest::blocking::Client,
+    server: &CustomServer,
+    text: &str,
+) -> Result<Vec<u8>, TTSError> {
+    let url = format!("{}/tts_to_audio/", server.url.trim_end_matches('/'));
+    let response = client
+        .post(&url)
+        .json(&TtsRequest {
+            text,
+            speaker_wav: &server.speaker,
+            language: &server.language,
+        })
+        .send()
+        .map_err(|e| TTSError::ProcessError(format!("Custom voice server unreachable: {e}")))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().unwrap_or_default();
+        return Err(TTSError::ProcessError(format!(
+            "Custom voice server returned {status}: {}",
+            body.trim()
+        )));
+    }
+    let bytes = response
+        .bytes()
+        .map_err(|e| TTSError::ProcessError(format!("Custom voice server: {e}")))?;
+    if bytes.is_empty() {
+        return Err(TTSError::ProcessError(
+            "No audio data returned from the custom voice server".into(),
+        ));
+    }
+    Ok(bytes.to_vec())
+}
@@ -0,0 +1,207 @@
+//! Audio export: synthesizes text with the current provider and writes it to a WAV or MP3 file
+//! instead of playing it.
+//!
+//! The worker hands out a synthesizer for its provider (`TtsRequest::Synthesizer`), so export runs
+//! on the caller's thread and playback is not blocked. Audio is written at the voice's natural
+//! speed; the playback speed setting only applies to the player.
+
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc;
+
+use mp3lame_encoder::{Bitrate, FlushNoGap, MonoPcm, Quality};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::audio_player::AudioPlayer;
+use super::{stream, TtsRequest, TtsState};
+use crate::tasks::TaskHandle;
+use crate::text::pipeline::Pipeline;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Wav,
+    Mp3,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Mp3 => "mp3",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportResult {
+    pub path: String,
+    pub duration_ms: u64,
+    pub bytes: u64,
+}
+
+/// Synthesizes `text` (after `pipeline`) and writes it to `path`, reporting progress and checking
+/// for cancellation through `task` after every chunk. The file only appears once it is complete.
+pub fn export_to_file(
+    tts_tx: &TtsState,
+    pipeline: &Pipeline,
+    text: &str,
+    path: &Path,
+    format: ExportFormat,
+    task: &mut TaskHandle,
+) -> Result<ExportResult, String> {
+    let prepared = crate::text::prepare(text, pipeline);
+    let chunks = stream::plan_chunks(&prepared.segments);
+    if chunks.is_empty() {
+        return Err("Cannot export empty text".to_string());
+    }
+
+    let (resp_tx, resp_rx) = mpsc::sync_channel(0);
+    tts_tx
+        .send(TtsRequest::Synthesizer(resp_tx))
+        .map_err(|e| format!("TTS channel: {e}"))?;
+    let mut synthesize = resp_rx
+        .recv()
+        .map_err(|_| "TTS worker disconnected".to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let mut pcm: Vec<f32> = Vec::new();
+    let mut sample_rate = None;
+    for (index, chunk) in chunks.iter().enumerate() {
+        if task.is_cancelled() {
+            return Err("Export cancelled".to_string());
+        }
+        let audio = synthesize(chunk).map_err(|e| e.to_string())?;
+        if *sample_rate.get_or_insert(audio.sample_rate) != audio.sample_rate {
+            return Err("Voice changed sample rate during export".to_string());
+        }
+        pcm.extend(audio.pcm);
+        task.set_progress(
+            (index + 1) as f32 / chunks.len() as f32,
+            Some(format!("{} of {} parts", index + 1, chunks.len())),
+        );
+    }
+    let sample_rate = sample_rate.unwrap_or(22_050);
+
+    let samples: Vec<i16> = pcm
+        .iter()
+        .map(|&s| (s * 32767.0).clamp(-32768.0, 32767.0) as i16)
+        .collect();
+    let data = encode(format, &samples, sample_rate)?;
+    write_complete(path, format, &data)?;
+
+    let duration_ms = samples.len() as u64 * 1000 / u64::from(sample_rate.max(1));
+    info!(path = %path.display(), duration_ms, bytes = data.len(), ?format, "Audio exported");
+    Ok(ExportResult {
+        path: path.to_string_lossy().to_string(),
+        duration_ms,
+        bytes: data.len() as u64,
+    })
+}
+
+fn encode(format: ExportFormat, samples: &[i16], sample_rate: u32) -> Result<Vec<u8>, String> {
+    match format {
+        ExportFormat::Wav => Ok(AudioPlayer::create_wav(samples, sample_rate)),
+        ExportFormat::Mp3 => encode_mp3(samples, sample_rate),
+    }
+}
+
+/// Writes `data` to `<path>.<ext>.part` and renames it to `path`, removing the partial file when
+/// that fails.
+fn write_complete(path: &Path, format: ExportFormat, data: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create export directory: {e}"))?;
+    }
+    let partial = path.with_extension(format!("{}.part", format.extension()));
+    fs::write(&partial, data)
+        .and_then(|()| fs::rename(&partial, path))
+        .map_err(|e| {
+            let _ = fs::remove_file(&partial);
+            format!("Failed to write audio file: {e}")
+        })
+}
+
+/// Encodes mono 16-bit PCM as a 64 kbps MP3 (plenty for speech).
+fn encode_mp3(samples: &[i16], sample_rate: u32) -> Result<Vec<u8>, String> {
+    let mut builder = mp3lame_encoder::Builder::new()
+        .ok_or_else(|| "Failed to create MP3 encoder".to_string())?;
+    builder
+        .set_num_channels(1)
+        .map_err(|e| format!("MP3 encoder: {e}"))?;
+    builder
+        .set_sample_rate(sample_rate)
+        .map_err(|e| format!("MP3 encoder: {e}"))?;
+    builder
+        .set_brate(Bitrate::Kbps64)
+        .map_err(|e| format!("MP3 encoder: {e}"))?;
+    builder
+        .set_quality(Quality::Good)
+        .map_err(|e| format!("MP3 encoder: {e}"))?;
+    let mut encoder = builder.build().map_err(|e| format!("MP3 encoder: {e}"))?;
+
+    let mut out = Vec::with_capacity(mp3lame_encoder::max_required_buffer_size(samples.len()));
+    encoder
+        .encode_to_vec(MonoPcm(samples), &mut out)
+        .map_err(|e| format!("MP3 encoder: {e}"))?;
+    encoder
+        .flush_to_vec::<FlushNoGap>(&mut out)
+        .map_err(|e| format!("MP3 encoder: {e}"))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One second of a 440 Hz tone.
+    fn tone(sample_rate: u32) -> Vec<i16> {
+        (0..sample_rate)
+            .map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                ((t * 440.0 * std::f32::consts::TAU).sin() * 8000.0) as i16
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_writes_the_chosen_format() {
+        let samples = tone(22_050);
+        let wav = encode(ExportFormat::Wav, &samples, 22_050).unwrap();
+        assert!(wav.starts_with(b"RIFF"));
+        assert_eq!(wav.len(), 44 + samples.len() * 2);
+
+        let mp3 = encode(ExportFormat::Mp3, &samples, 22_050).unwrap();
+        assert!(!mp3.is_empty());
+        assert!(!mp3.starts_with(b"RIFF"));
+        // 64 kbps is well under the 352 kbps of 22.05 kHz 16-bit PCM.
+        assert!(mp3.len() < wav.len() / 2, "{} bytes", mp3.len());
+    }
+
+    #[test]
+    fn test_write_complete_leaves_only_the_finished_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out").join("article.mp3");
+        write_complete(&path, ExportFormat::Mp3, b"audio").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"audio");
+        let names: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["article.mp3"]);
+    }
+
+    #[test]
+    fn test_write_complete_removes_the_partial_file_on_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        // A non-empty directory where the file should go makes the rename fail.
+        let path = dir.path().join("article.wav");
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("keep"), b"").unwrap();
+
+        assert!(write_complete(&path, ExportFormat::Wav, b"audio").is_err());
+        assert!(!dir.path().join("article.wav.part").exists());
+        assert!(path.join("keep").exists());
+    }
+}
@@ -0,0 +1,115 @@
+//! Provider fallback chain: other providers to try, in order, when the active one fails.
+//!
+//! The chain comes from `provider_fallbacks` in config. A provider that cannot be loaded (missing
+//! credentials, no voice installed) is skipped at load time; one whose first chunk fails (network
+//! down, server gone) is replaced by the next one and the read starts over. Each switch is
+//! reported to the `FallbackNotifier` (the app emits `tts-provider-fallback`). The bundled
+//! onboarding voice stays the last resort once the chain is used up; switching to it is reported
+//! too, with `to` set to `ONBOARDING`. The next read starts again from the configured provider.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use super::{TTSError, TtsProvider};
+
+/// `ProviderFallback::to` when the bundled onboarding voice stands in.
+pub const ONBOARDING: &str = "onboarding";
+
+/// Payload of the `tts-provider-fallback` event: `from` failed with `reason`, `to` is used instead.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderFallback {
+    pub from: &'static str,
+    pub to: &'static str,
+    pub reason: String,
+}
+
+/// Receives provider switches (set once the app is running; see `commands_tts`).
+pub type FallbackNotifier = Box<dyn Fn(&ProviderFallback) + Send + Sync>;
+
+/// Providers named in `provider_fallbacks`, in order; unknown names and repeats are dropped.
+pub(super) fn parse_chain(names: &[String]) -> Vec<TtsProvider> {
+    let mut chain = Vec::new();
+    for name in names {
+        match TtsProvider::from_name(name.trim()) {
+            Some(provider) if !chain.contains(&provider) => chain.push(provider),
+            Some(_) => {}
+            None => tracing::warn!(name = %name, "Unknown provider in provider_fallbacks"),
+        }
+    }
+    chain
+}
+
+/// The worker's position in the chain for the current read.
+#[derive(Default)]
+pub(super) struct Fallbacks {
+    remaining: Vec<TtsProvider>,
+    notifier: Option<Arc<FallbackNotifier>>,
+}
+
+impl Fallbacks {
+    pub fn set_notifier(&mut self, notifier: FallbackNotifier) {
+        self.notifier = Some(Arc::new(notifier));
+    }
+
+    /// The notifier, for the synthesis thread (see `with_onboarding_fallback`).
+    pub fn notifier(&self) -> Option<Arc<FallbackNotifier>> {
+        self.notifier.clone()
+    }
+
+    /// Starts a read with `active`: the rest of `chain` (without `active`) is left to try.
+    pub fn reset(&mut self, active: TtsProvider, chain: &[TtsProvider]) {
+        self.remaining = chain.iter().copied().filter(|p| *p != active).collect();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining.is_empty()
+    }
+
+    /// Takes the next provider to try.
+    pub fn next(&mut self) -> Option<TtsProvider> {
+        (!self.remaining.is_empty()).then(|| self.remaining.remove(0))
+    }
+
+    pub fn report(&self, from: TtsProvider, to: TtsProvider, reason: &TTSError) {
+        tracing::warn!(
+            from = from.name(),
+            to = to.name(),
+            error = %reason,
+            "Provider failed, switching to the next fallback"
+        );
+        if let Some(notifier) = &self.notifier {
+            notifier(&ProviderFallback {
+                from: from.name(),
+                to: to.name(),
+                reason: reason.to_string(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_keeps_order_and_skips_active_provider() {
+        let names = ["microsoft", "bogus", "piper", "microsoft", " system "].map(String::from);
+        let chain = parse_chain(&names);
+        assert_eq!(
+            chain,
+            [
+                TtsProvider::Microsoft,
+                TtsProvider::Piper,
+                TtsProvider::System
+            ]
+        );
+
+        let mut fallbacks = Fallbacks::default();
+        fallbacks.reset(TtsProvider::Microsoft, &chain);
+        assert_eq!(fallbacks.next(), Some(TtsProvider::Piper));
+        assert_eq!(fallbacks.next(), Some(TtsProvider::System));
+        assert!(fallbacks.is_empty());
+        assert_eq!(fallbacks.next(), None);
+    }
+}
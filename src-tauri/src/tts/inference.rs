@@ -0,0 +1,216 @@
+//! Accelerator selection for local (Piper/ONNX) inference.
+//!
+//! Available execution providers are discovered by asking the onnxruntime installed next to the
+//! Piper binary (`python -c "import onnxruntime; ..."`), once per process on a background thread
+//! started at launch (`probe_in_background`); synthesis never waits for it and uses CPU until the
+//! probe is done. The preferred backend comes from the `inference_backend` config value ("auto",
+//! "cpu", "cuda", "directml", "coreml"). When the preferred backend is unavailable, or the Piper
+//! CLI cannot use it, synthesis falls back to CPU. In-process inference (`onnx`) can use every
+//! backend; the Piper CLI only CPU and CUDA.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use tracing::{debug, info};
+
+use super::piper::PiperTTSProvider;
+
+/// onnxruntime provider names and the backend id they map to.
+const PROVIDERS: [(&str, &str, &str); 4] = [
+    ("cpu", "CPU", "CPUExecutionProvider"),
+    ("cuda", "NVIDIA CUDA", "CUDAExecutionProvider"),
+    ("directml", "DirectML", "DmlExecutionProvider"),
+    ("coreml", "Core ML", "CoreMLExecutionProvider"),
+];
+
+/// Backends the Piper CLI can be told to use (`--cuda`); others need in-process inference.
+const PIPER_SUPPORTED: [&str; 2] = ["cpu", "cuda"];
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InferenceBackendInfo {
+    pub id: &'static str,
+    pub label: &'static str,
+    /// onnxruntime on this machine reports the provider.
+    pub available: bool,
+    /// The Piper CLI can use it.
+    pub supported_by_piper: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InferenceBackends {
+    pub backends: Vec<InferenceBackendInfo>,
+    /// Configured preference ("auto" when unset).
+    pub preferred: String,
+    /// Backend actually used for synthesis after fallback.
+    pub effective: &'static str,
+}
+
+static PREFERRED: Mutex<Option<String>> = Mutex::new(None);
+static AVAILABLE_PROVIDERS: OnceLock<Vec<String>> = OnceLock::new();
+/// Set when Piper failed with an accelerator; CPU is used for the rest of the session.
+static ACCELERATOR_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Sets the preferred backend from the `inference_backend` config value.
+pub fn set_preferred(value: Option<&str>) {
+    let normalized = value
+        .map(|v| v.trim().to_ascii_lowercase())
+        .filter(|v| !v.is_empty() && v != "auto");
+    if let Ok(mut preferred) = PREFERRED.lock() {
+        *preferred = normalized;
+    }
+}
+
+/// Disables accelerators for the rest of the session after a failed accelerated run.
+pub fn mark_accelerator_failed() {
+    ACCELERATOR_FAILED.store(true, Ordering::Relaxed);
+}
+
+fn preferred() -> Option<String> {
+    PREFERRED.lock().ok().and_then(|p| p.clone())
+}
+
+/// Starts the provider probe on a background thread, so the first synthesis does not wait for
+/// Python. Called once at startup.
+pub fn probe_in_background() {
+    let spawned = std::thread::Builder::new()
+        .name("inference-probe".into())
+        .spawn(|| {
+            available_providers();
+        });
+    if let Err(e) = spawned {
+        debug!(error = %e, "Failed to start the inference provider probe");
+    }
+}
+
+/// Execution providers reported by onnxruntime in the Piper environment (cached). Blocks until
+/// the probe is done.
+fn available_providers() -> &'static [String] {
+    AVAILABLE_PROVIDERS.get_or_init(|| {
+        let providers = query_onnxruntime_providers(&PiperTTSProvider::find_piper_binary())
+            .unwrap_or_else(|e| {
+                debug!(error = %e, "Could not query onnxruntime providers, assuming CPU only");
+                vec![PROVIDERS[0].2.to_string()]
+            });
+        info!(?providers, "Detected inference providers");
+        providers
+    })
+}
+
+/// Runs the Python next to the Piper binary (venv) to list onnxruntime providers.
+fn query_onnxruntime_providers(piper_bin: &Path) -> Result<Vec<String>, String> {
+    let bin_dir = piper_bin
+        .parent()
+        .ok_or_else(|| "Piper binary has no parent directory".to_string())?;
+    let python = ["python3", "python", "python.exe"]
+        .iter()
+        .map(|name| bin_dir.join(name))
+        .find(|p| p.is_file())
+        .unwrap_or_else(|| PathBuf::from("python3"));
+
+    let mut cmd = Command::new(&python);
+    cmd.args([
+        "-c",
+        "import onnxruntime; print(','.join(onnxruntime.get_available_providers()))",
+    ])
+    .stdin(Stdio::null())
+    .stderr(Stdio::null());
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run {}: {e}", python.display()))?;
+    if !output.status.success() {
+        return Err("onnxruntime is not importable".to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Providers found so far, without waiting for the probe (empty while it runs).
+fn probed_providers() -> &'static [String] {
+    AVAILABLE_PROVIDERS.get().map_or(&[], Vec::as_slice)
+}
+
+fn is_available(id: &str, providers: &[String]) -> bool {
+    PROVIDERS
+        .iter()
+        .find(|(pid, _, _)| *pid == id)
+        .is_some_and(|(_, _, ort_name)| providers.iter().any(|p| p == ort_name))
+}
+
+/// Backend to use among `supported`: the preference if available, the first available
+/// accelerator for "auto", else CPU (also after an accelerated run failed this session).
+fn choose_backend(supported: &[&'static str], providers: &[String]) -> &'static str {
+    if ACCELERATOR_FAILED.load(Ordering::Relaxed) {
+        return "cpu";
+    }
+    let available = |id: &str| id == "cpu" || is_available(id, providers);
+    let mut supported = supported.iter().copied();
+    match preferred() {
+        Some(id) => match supported.find(|b| *b == id).filter(|b| available(b)) {
+            Some(backend) => backend,
+            None => {
+                debug!(preferred = %id, "Inference backend unavailable, using CPU");
+                "cpu"
+            }
+        },
+        None => supported
+            .filter(|b| *b != "cpu")
+            .find(|b| available(b))
+            .unwrap_or("cpu"),
+    }
+}
+
+/// Backend the Piper CLI will use. Does not wait for the provider probe.
+pub fn effective_backend() -> &'static str {
+    choose_backend(&PIPER_SUPPORTED, probed_providers())
+}
+
+/// onnxruntime execution provider for in-process inference (e.g. "CUDAExecutionProvider").
+/// Does not wait for the provider probe.
+pub(super) fn onnx_execution_provider() -> &'static str {
+    let ids = PROVIDERS.map(|(id, _, _)| id);
+    let backend = choose_backend(&ids, probed_providers());
+    PROVIDERS
+        .iter()
+        .find(|(id, _, _)| *id == backend)
+        .map_or(PROVIDERS[0].2, |(_, _, ort_name)| ort_name)
+}
+
+/// Extra Piper CLI arguments for the effective backend.
+pub fn piper_args() -> &'static [&'static str] {
+    if effective_backend() == "cuda" {
+        &["--cuda"]
+    } else {
+        &[]
+    }
+}
+
+/// Report for `get_inference_backends`. Blocking (waits for the provider probe).
+pub fn inference_backends() -> InferenceBackends {
+    let providers = available_providers();
+    let backends = PROVIDERS
+        .iter()
+        .map(|(id, label, _)| InferenceBackendInfo {
+            id,
+            label,
+            available: is_available(id, providers),
+            supported_by_piper: PIPER_SUPPORTED.contains(id),
+        })
+        .collect();
+    InferenceBackends {
+        backends,
+        preferred: preferred().unwrap_or_else(|| "auto".to_string()),
+        effective: choose_backend(&PIPER_SUPPORTED, providers),
+    }
+}
@@ -1,9 +1,14 @@
 //! Microsoft Edge TTS provider: uses msedge-tts Rust crate for direct API calls.
 
-use tracing::{debug, info, warn};
+use std::time::Duration;
 
-use super::audio_player::AudioPlayer;
+use tracing::{debug, info};
+
+use super::audio_player::{AudioPlayer, SeekResult};
+use super::stream::{ChunkAudio, SynthesizeFn};
+use super::timeline;
 use super::TTSError;
+use crate::text::ssml::{Markup, SsmlSupport};
 
 pub struct MicrosoftTTSProvider {
     player: AudioPlayer,
@@ -12,44 +17,58 @@ pub struct MicrosoftTTSProvider {
 
 impl MicrosoftTTSProvider {
     const WAV_HEADER_LEN: usize = 44;
+    const SAMPLE_RATE: u32 = 24000;
 
     pub fn new(voice: Option<String>) -> Result<Self, TTSError> {
         info!("Initializing Microsoft Edge TTS provider");
 
-        let player = AudioPlayer::new(24000)?;
+        let player = AudioPlayer::new(Self::SAMPLE_RATE)?;
         let voice = voice.unwrap_or_else(|| "en-US-AriaNeural".to_string());
         info!(voice = %voice, "Using Microsoft Edge TTS voice");
         Ok(Self { player, voice })
     }
 
-    pub fn speak(&mut self, text: &str) -> Result<(), TTSError> {
-        let text = text.trim();
-        if text.is_empty() {
-            warn!("Empty text provided to edge-tts, skipping synthesis");
-            return Err(TTSError::ProcessError(
-                "Cannot synthesize empty text".into(),
-            ));
-        }
-
-        debug!(
-            chars = text.len(),
-            text_preview = %text.chars().take(50).collect::<String>(),
-            voice = %self.voice,
-            "Microsoft Edge: synthesizing speech"
-        );
-
-        self.player.stop()?;
-
-        self.synthesize(text)?;
-
-        info!("Microsoft Edge: audio generated and playing");
-
-        Ok(())
+    /// Returns a function that synthesizes text with this voice (runs on the synthesis thread).
+    /// Chunks with markup (see `ssml::Markup`) are sent as SSML; msedge-tts places the text inside
+    /// its own `<speak>`/`<voice>`/`<prosody>` envelope, so the fragment needs no wrapper. Only
+    /// the multilingual voices take `<lang>`.
+    pub fn synthesizer(&self, markup: Markup) -> SynthesizeFn {
+        let voice = self.voice.clone();
+        let support = SsmlSupport {
+            pitch: true,
+            lang: voice.contains("Multilingual"),
+        };
+        Box::new(move |text: &str| {
+            debug!(
+                chars = text.len(),
+                text_preview = %text.chars().take(50).collect::<String>(),
+                voice = %voice,
+                "Microsoft Edge: synthesizing chunk"
+            );
+            let ssml = markup.to_ssml(text, support);
+            let response = Self::synthesize_bytes(ssml.as_deref().unwrap_or(text), &voice)?;
+            let (pcm, sample_rate) = Self::decode(response.audio_bytes, &response.audio_format)?;
+            info!("Microsoft Edge: audio generated");
+            let boundaries = response.audio_metadata.iter().filter_map(|m| {
+                let word = m.text.as_deref()?;
+                let is_word = m.metadata_type.as_deref() == Some("WordBoundary");
+                // Offsets are in 100 ns ticks.
+                is_word.then_some((m.offset / 10_000, word))
+            });
+            let words = timeline::marks_from_boundaries(text, boundaries);
+            if words.is_empty() {
+                return Ok(ChunkAudio::estimated(text, pcm, sample_rate));
+            }
+            Ok(ChunkAudio {
+                pcm,
+                sample_rate,
+                words,
+            })
+        })
     }
 
-    fn synthesize(&mut self, text: &str) -> Result<(), TTSError> {
-        let (audio_bytes, audio_format) = Self::synthesize_bytes(text, &self.voice)?;
-
+    /// Converts an Edge TTS response to mono f32 PCM and its sample rate.
+    fn decode(audio_bytes: Vec<u8>, audio_format: &str) -> Result<(Vec<f32>, u32), TTSError> {
         // Handle different audio formats
         if audio_format.starts_with("riff-") {
             // WAV format - skip header
@@ -59,10 +78,10 @@ impl MicrosoftTTSProvider {
                 ));
             }
             let pcm_data = AudioPlayer::pcm_to_f32(&audio_bytes[Self::WAV_HEADER_LEN..]);
-            self.player.play_audio(pcm_data)
-        } else if Self::is_streaming_audio_format(&audio_format) {
+            Ok((pcm_data, Self::SAMPLE_RATE))
+        } else if Self::is_streaming_audio_format(audio_format) {
             // MP3/Opus format - rodio will decode automatically
-            self.player.play_audio_raw(audio_bytes, 24000)
+            AudioPlayer::decode_audio(audio_bytes)
         } else {
             Err(TTSError::ProcessError(format!(
                 "Unsupported audio format: {}",
@@ -75,7 +94,10 @@ impl MicrosoftTTSProvider {
         audio_format.contains("mp3") || audio_format.contains("opus")
     }
 
-    fn synthesize_bytes(text: &str, voice: &str) -> Result<(Vec<u8>, String), TTSError> {
+    fn synthesize_bytes(
+        text: &str,
+        voice: &str,
+    ) -> Result<msedge_tts::tts::client::SynthesizedAudio, TTSError> {
         use msedge_tts::tts::client::connect;
         use msedge_tts::tts::SpeechConfig;
 
@@ -102,22 +124,31 @@ impl MicrosoftTTSProvider {
             response.audio_format
         );
 
-        let audio_bytes = response.audio_bytes;
-        let audio_format = response.audio_format;
-
-        if audio_bytes.is_empty() {
+        if response.audio_bytes.is_empty() {
             return Err(TTSError::ProcessError(
                 "No audio data returned from Edge TTS".into(),
             ));
         }
 
-        Ok((audio_bytes, audio_format))
+        Ok(response)
+    }
+
+    pub fn append_audio(&mut self, audio_data: Vec<f32>, sample_rate: u32) -> Result<(), TTSError> {
+        self.player.append_audio(audio_data, sample_rate)
+    }
+
+    pub fn current_segment(&self) -> usize {
+        self.player.current_segment()
     }
 
     pub fn stop(&mut self) -> Result<(), TTSError> {
         self.player.stop()
     }
 
+    pub fn fade_out(&mut self, duration: Duration) -> Result<(), TTSError> {
+        self.player.fade_out(duration)
+    }
+
     pub fn toggle_pause(&mut self) -> Result<bool, TTSError> {
         self.player.toggle_pause()
     }
@@ -126,14 +157,30 @@ impl MicrosoftTTSProvider {
         self.player.get_status()
     }
 
-    pub fn seek(&mut self, offset_ms: i64) -> Result<(bool, bool, bool), TTSError> {
+    pub fn seek(&mut self, offset_ms: i64) -> Result<SeekResult, TTSError> {
         self.player.seek(offset_ms)
     }
 
+    pub fn seek_to(&mut self, position_ms: u64) -> Result<SeekResult, TTSError> {
+        self.player.seek_to(position_ms)
+    }
+
+    pub fn check_output(&mut self, force: bool) -> bool {
+        self.player.check_output(force)
+    }
+
+    pub fn reopen_output(&mut self) -> bool {
+        self.player.reopen_output()
+    }
+
     pub fn get_position(&self) -> (u64, u64) {
         self.player.get_position()
     }
 
+    pub fn waveform(&self, buckets: usize) -> Vec<f32> {
+        self.player.waveform(buckets)
+    }
+
     pub fn set_volume(&mut self, volume_percent: u8) {
         self.player.set_volume_percent(volume_percent);
     }
@@ -150,9 +197,9 @@ mod tests {
     #[test]
     fn test_edge_tts_synthesizes_audio_bytes() {
         let test_text = "Hello world, this is a test.";
-        let (audio_bytes, audio_format) =
-            MicrosoftTTSProvider::synthesize_bytes(test_text, "en-US-AriaNeural")
-                .expect("Failed to synthesize speech");
+        let response = MicrosoftTTSProvider::synthesize_bytes(test_text, "en-US-AriaNeural")
+            .expect("Failed to synthesize speech");
+        let (audio_bytes, audio_format) = (response.audio_bytes, response.audio_format);
 
         assert!(!audio_bytes.is_empty(), "Audio bytes should not be empty");
         assert!(
@@ -0,0 +1,211 @@
+//! Mobile TTS provider: the platform speech engine on Android (`TextToSpeech`) and iOS
+//! (`AVSpeechSynthesizer`), reached through the app's `speech` mobile plugin.
+//!
+//! The native halves of the plugin are in `mobile/` (`SpeechPlugin.kt`, `SpeechPlugin.swift`) and
+//! are added to the generated Android and Xcode projects. As with the system provider, each chunk
+//! is rendered to a WAV file (by the native side, in the app's cache) and played through our own
+//! player, so seek, speed and the word timeline work as for the other providers (timing is
+//! estimated). On desktop the provider is never available.
+
+use std::time::Duration;
+
+use tracing::{debug, info};
+
+use super::audio_player::{AudioPlayer, SeekResult};
+use super::stream::{ChunkAudio, SynthesizeFn};
+use super::TTSError;
+use crate::text::ssml::Markup;
+
+/// Player rate until the first chunk reports its own.
+const SAMPLE_RATE: u32 = 22050;
+
+#[cfg(mobile)]
+pub use bridge::init;
+
+pub struct MobileTTSProvider {
+    player: AudioPlayer,
+    voice: Option<String>,
+}
+
+impl MobileTTSProvider {
+    pub fn new(voice: Option<String>) -> Result<Self, TTSError> {
+        info!("Initializing mobile TTS provider");
+        if !Self::is_available() {
+            return Err(TTSError::ProcessError(
+                "The platform speech engine is only available on Android and iOS".into(),
+            ));
+        }
+        let player = AudioPlayer::new(SAMPLE_RATE)?;
+        info!(voice = ?voice, "Using platform TTS voice");
+        Ok(Self { player, voice })
+    }
+
+    /// Whether the `speech` plugin has been registered.
+    pub fn is_available() -> bool {
+        #[cfg(mobile)]
+        {
+            bridge::is_registered()
+        }
+        #[cfg(desktop)]
+        {
+            false
+        }
+    }
+
+    /// Returns a function that synthesizes text with this voice (runs on the synthesis thread).
+    /// The platform engines take plain text: words with a phoneme override are spoken as their
+    /// replacement.
+    pub fn synthesizer(&self, markup: Markup) -> SynthesizeFn {
+        let voice = self.voice.clone();
+        Box::new(move |text: &str| {
+            debug!(
+                chars = text.len(),
+                text_preview = %text.chars().take(50).collect::<String>(),
+                voice = ?voice,
+                "Mobile TTS: synthesizing chunk"
+            );
+            let spoken = markup.respell(text);
+            let wav = render_wav(&spoken, voice.as_deref())?;
+            let (pcm, sample_rate) = AudioPlayer::decode_audio(wav)?;
+            info!(samples = pcm.len(), "Mobile TTS: audio generated");
+            Ok(ChunkAudio::estimated(text, pcm, sample_rate))
+        })
+    }
+
+    pub fn append_audio(&mut self, audio_data: Vec<f32>, sample_rate: u32) -> Result<(), TTSError> {
+        self.player.append_audio(audio_data, sample_rate)
+    }
+
+    pub fn current_segment(&self) -> usize {
+        self.player.current_segment()
+    }
+
+    pub fn stop(&mut self) -> Result<(), TTSError> {
+        self.player.stop()
+    }
+
+    pub fn fade_out(&mut self, duration: Duration) -> Result<(), TTSError> {
+        self.player.fade_out(duration)
+    }
+
+    pub fn toggle_pause(&mut self) -> Result<bool, TTSError> {
+        self.player.toggle_pause()
+    }
+
+    pub fn get_status(&self) -> (bool, bool) {
+        self.player.get_status()
+    }
+
+    pub fn seek(&mut self, offset_ms: i64) -> Result<SeekResult, TTSError> {
+        self.player.seek(offset_ms)
+    }
+
+    pub fn seek_to(&mut self, position_ms: u64) -> Result<SeekResult, TTSError> {
+        self.player.seek_to(position_ms)
+    }
+
+    pub fn check_output(&mut self, force: bool) -> bool {
+        self.player.check_output(force)
+    }
+
+    pub fn reopen_output(&mut self) -> bool {
+        self.player.reopen_output()
+    }
+
+    pub fn get_position(&self) -> (u64, u64) {
+        self.player.get_position()
+    }
+
+    pub fn waveform(&self, buckets: usize) -> Vec<f32> {
+        self.player.waveform(buckets)
+    }
+
+    pub fn set_volume(&mut self, volume_percent: u8) {
+        self.player.set_volume_percent(volume_percent);
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.player.set_speed(speed);
+    }
+
+    pub fn set_pitch(&mut self, ratio: f32) {
+        self.player.set_pitch(ratio);
+    }
+}
+
+#[cfg(mobile)]
+fn render_wav(text: &str, voice: Option<&str>) -> Result<Vec<u8>, TTSError> {
+    let path = bridge::synthesize(text, voice)?;
+    let wav = std::fs::read(&path)
+        .map_err(|e| TTSError::ProcessError(format!("Failed to read platform speech: {e}")));
+    let _ = std::fs::remove_file(&path);
+    wav
+}
+
+#[cfg(desktop)]
+fn render_wav(_text: &str, _voice: Option<&str>) -> Result<Vec<u8>, TTSError> {
+    Err(TTSError::ProcessError(
+        "The platform speech engine is only available on Android and iOS".into(),
+    ))
+}
+
+/// The `speech` mobile plugin: registered at startup, called from the synthesis thread.
+#[cfg(mobile)]
+mod bridge {
+    use std::sync::OnceLock;
+
+    use serde::{Deserialize, Serialize};
+    use tauri::plugin::{Builder, PluginHandle, TauriPlugin};
+    use tauri::Wry;
+
+    use super::TTSError;
+
+    #[cfg(target_os = "android")]
+    const ANDROID_PACKAGE: &str = "com.gabriel.insight_reader_2";
+
+    #[cfg(target_os = "ios")]
+    tauri::ios_plugin_binding!(init_plugin_speech);
+
+    static SPEECH: OnceLock<PluginHandle<Wry>> = OnceLock::new();
+
+    #[derive(Serialize)]
+    struct SynthesizeArgs<'a> {
+        text: &'a str,
+        voice: Option<&'a str>,
+    }
+
+    /// Where the native side wrote the WAV; the caller removes it.
+    #[derive(Deserialize)]
+    struct SynthesizeResponse {
+        path: String,
+    }
+
+    /// The plugin to register on the app builder.
+    pub fn init() -> TauriPlugin<Wry> {
+        Builder::new("speech")
+            .setup(|_app, api| {
+                #[cfg(target_os = "android")]
+                let handle = api.register_android_plugin(ANDROID_PACKAGE, "SpeechPlugin")?;
+                #[cfg(target_os = "ios")]
+                let handle = api.register_ios_plugin(init_plugin_speech)?;
+                let _ = SPEECH.set(handle);
+                Ok(())
+            })
+            .build()
+    }
+
+    pub fn is_registered() -> bool {
+        SPEECH.get().is_some()
+    }
+
+    /// Renders `text` to a WAV file and returns its path.
+    pub fn synthesize(text: &str, voice: Option<&str>) -> Result<String, TTSError> {
+        let handle = SPEECH
+            .get()
+            .ok_or_else(|| TTSError::ProcessError("The speech plugin is not registered".into()))?;
+        handle
+            .run_mobile_plugin::<SynthesizeResponse>("synthesize", SynthesizeArgs { text, voice })
+            .map(|response| response.path)
+            .map_err(|e| TTSError::ProcessError(format!("Platform speech failed: {e}")))
+    }
+}
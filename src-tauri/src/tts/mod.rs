@@ -2,24 +2,88 @@
 //!
 //! Piper/rodio are !Send on some platforms, so we run a dedicated worker thread
 //! that owns the provider and receive commands via a channel. TtsState is the
-//! Sender, which is Send.
+//! Sender, which is Send. Synthesis runs sentence by sentence on a separate thread
+//! (see `stream`) and feeds the player queue through the same channel. While audio
+//! plays, the worker reports the spoken word (see `timeline`). Texts enqueued while something
+//! plays are read one after another (see `queue`). Export to WAV/MP3 reuses the provider's
+//! synthesizer outside playback (see `export`). Reads can be announced before they start (see
+//! `announce`). On Android and iOS the platform speech engine is a provider too (see `mobile`).
+//! The worker records the provider it loaded and the last synthesis error (see `status`). A sleep
+//! timer can fade out and stop playback after a set time (see `sleep_timer`).
 
+mod announce;
 mod audio_player;
+mod custom;
+mod export;
+mod fallback;
+mod inference;
 mod microsoft;
+mod mobile;
+mod onnx;
+mod output;
 mod piper;
 mod polly;
+mod priority;
+mod proofread;
+mod queue;
+mod sleep_timer;
+mod status;
+mod stream;
+mod system;
+mod timeline;
+mod trace;
 
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc, OnceLock};
+use std::time::{Duration, Instant};
 
+use queue::Queue;
+use sleep_timer::SleepTimer;
+use stream::{ChunkAudio, ChunkReady, Stream};
+use timeline::Timeline;
+
+pub use audio_player::SeekResult;
+pub use custom::DEFAULT_SERVER_URL as DEFAULT_CUSTOM_SERVER_URL;
+use custom::{CustomServer, CustomTTSProvider};
+pub use export::{export_to_file, ExportFormat, ExportResult};
+use fallback::Fallbacks;
+pub use fallback::{FallbackNotifier, ProviderFallback};
+pub use inference::InferenceBackends;
 use microsoft::MicrosoftTTSProvider;
+#[cfg(mobile)]
+pub use mobile::init as mobile_speech_plugin;
+use mobile::MobileTTSProvider;
+pub use output::AudioOutput;
 use piper::PiperTTSProvider;
 use polly::PollyTTSProvider;
+pub use polly::{aws_config_loader, PollyDiagnostics, PollySettings};
+pub use queue::{QueueAdvance, QueueItem, QueueNotifier};
+pub use sleep_timer::{SleepTimerStatus, MAX_SLEEP_MINUTES};
+pub use status::{provider_status, TtsProviderStatus};
+use system::SystemTTSProvider;
+pub use timeline::{ProgressNotifier, SegmentUnit, TimelineWord, TtsProgress};
+pub use trace::PlaybackTraceEntry;
+
+use crate::features;
+use crate::latency;
+use crate::text::language::{self, LanguageSpan};
+use crate::text::lexicon::Pronunciations;
+pub use crate::text::ssml::InputKind;
+use crate::text::ssml::{LanguageTags, Markup, Passthrough, SsmlOptions};
+use crate::usage;
 
 /// Errors that can occur during TTS operations.
 #[derive(Debug)]
 pub enum TTSError {
     ProcessError(String),
     AudioError(String),
+    /// Nothing left to synthesize after preprocessing.
+    EmptyText,
+    /// The provider lacks credentials or settings.
+    NotConfigured(String),
+    /// A cloud provider was requested in offline mode (see `offline`).
+    Offline,
+    /// The read would go over a blocking usage budget (see `usage`).
+    BudgetExceeded(String),
 }
 
 impl std::fmt::Display for TTSError {
@@ -27,6 +91,9 @@ impl std::fmt::Display for TTSError {
         match self {
             TTSError::ProcessError(s) => write!(f, "TTS process error: {s}"),
             TTSError::AudioError(s) => write!(f, "Audio error: {s}"),
+            TTSError::EmptyText => write!(f, "Cannot synthesize empty text"),
+            TTSError::NotConfigured(s) | TTSError::BudgetExceeded(s) => write!(f, "{s}"),
+            TTSError::Offline => write!(f, "{}", crate::offline::OFFLINE_ERROR),
         }
     }
 }
@@ -35,16 +102,93 @@ impl std::error::Error for TTSError {}
 
 /// Request to the TTS worker thread.
 pub enum TtsRequest {
-    Speak(String, mpsc::SyncSender<Result<(), TTSError>>),
+    /// Text, its kind, and the stretches in another language, if known (see
+    /// `text::language::LanguageSpan`; without them they may be detected).
+    Speak(
+        String,
+        InputKind,
+        Option<Vec<LanguageSpan>>,
+        mpsc::SyncSender<Result<(), TTSError>>,
+    ),
+    /// Like Speak, but clause by clause with pauses (see `proofread`).
+    Proofread(String, mpsc::SyncSender<Result<(), TTSError>>),
     Stop,
     TogglePause(mpsc::SyncSender<Result<bool, TTSError>>),
     GetStatus(mpsc::SyncSender<(bool, bool)>),
-    Seek(i64, mpsc::SyncSender<Result<(bool, bool, bool), TTSError>>),
+    Seek(i64, mpsc::SyncSender<Result<SeekResult, TTSError>>),
+    /// Seeks to a content position in milliseconds; answers like Seek.
+    SeekTo(u64, mpsc::SyncSender<Result<SeekResult, TTSError>>),
+    /// Seeks to a percentage (0-100) of the audio queued so far; answers like Seek.
+    SeekPercent(f64, mpsc::SyncSender<Result<SeekResult, TTSError>>),
+    /// Seeks to the next sentence or paragraph of the current read; answers like Seek.
+    NextSegment(SegmentUnit, mpsc::SyncSender<Result<SeekResult, TTSError>>),
+    /// Seeks to the start of the current sentence or paragraph, or to the previous one when
+    /// playback is near its start; answers like Seek.
+    PrevSegment(SegmentUnit, mpsc::SyncSender<Result<SeekResult, TTSError>>),
     GetPosition(mpsc::SyncSender<(u64, u64)>),
     SetVolume(u8, mpsc::SyncSender<Result<(), TTSError>>),
+    /// Sets the normal speed, leaving skim mode.
     SetSpeed(f32, mpsc::SyncSender<Result<(), TTSError>>),
+    /// The speed set with SetSpeed; `None` while the voice's calibrated speed applies.
+    GetSpeed(mpsc::SyncSender<Option<f32>>),
+    /// Puts back a speed read with GetSpeed (after a calibration sample).
+    RestoreSpeed(Option<f32>),
+    /// Switches between the normal speed and the skim speed; answers whether skimming now.
+    ToggleSkim(mpsc::SyncSender<Result<bool, TTSError>>),
+    /// Pitch in percent (see `TtsProviderImpl::set_pitch`).
+    SetPitch(i32, mpsc::SyncSender<Result<(), TTSError>>),
     SwitchProvider(TtsProvider, mpsc::SyncSender<Result<(), TTSError>>),
-    Shutdown,
+    /// Reopens the audio output (after the system woke up, when the old stream may be dead).
+    ReopenOutput,
+    /// Re-reads the config after it changed: playback, markup and fallback settings apply right
+    /// away, a new provider or voice is loaded once playback is idle (else at the next read).
+    /// When the provider could not be initialized, initializing it is tried again.
+    ReloadConfig,
+    /// Word timeline of the current Speak (words synthesized so far).
+    GetTimeline(mpsc::SyncSender<Vec<TimelineWord>>),
+    /// Peak envelope of the current read in the given number of buckets.
+    GetWaveform(usize, mpsc::SyncSender<TtsWaveform>),
+    /// Sets the receiver of word progress during playback.
+    SetProgressNotifier(ProgressNotifier),
+    /// Reads the text after the queued ones (right away when idle); returns its queue id.
+    /// The string after the text is the source, as recorded in the reading history.
+    Enqueue(
+        String,
+        InputKind,
+        String,
+        mpsc::SyncSender<Result<u64, TTSError>>,
+    ),
+    /// Texts waiting in the queue, next first.
+    QueueList(mpsc::SyncSender<Vec<QueueItem>>),
+    /// Stops the current text and starts the next queued one; false when the queue is empty.
+    QueueSkip(mpsc::SyncSender<bool>),
+    /// Drops the waiting texts (the current one keeps playing); returns how many were dropped.
+    QueueClear(mpsc::SyncSender<usize>),
+    /// Sets the receiver of queue advances.
+    SetQueueNotifier(QueueNotifier),
+    /// Sets the receiver of provider switches (see `fallback`).
+    SetFallbackNotifier(FallbackNotifier),
+    /// Fades out and stops playback after the given minutes, replacing a running sleep timer.
+    SetSleepTimer(u32, mpsc::SyncSender<Result<SleepTimerStatus, TTSError>>),
+    /// Cancels the sleep timer; answers false when none was running.
+    CancelSleepTimer(mpsc::SyncSender<bool>),
+    GetSleepTimer(mpsc::SyncSender<Option<SleepTimerStatus>>),
+    /// A synthesizer for the current provider, for synthesis outside playback (see `export`).
+    Synthesizer(mpsc::SyncSender<Result<stream::SynthesizeFn, TTSError>>),
+    /// Stops playback, records the current read and ends the worker; answers once done (see
+    /// `shutdown_worker`).
+    Shutdown(mpsc::SyncSender<()>),
+    /// Internal: a chunk finished synthesizing on the streaming thread.
+    ChunkReady(ChunkReady),
+}
+
+/// Amplitude envelope of the current read, for a waveform progress bar.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct TtsWaveform {
+    /// Peak amplitude (0.0-1.0) per bucket; buckets split `duration_ms` evenly.
+    pub peaks: Vec<f32>,
+    /// Content duration of the audio synthesized so far (grows while synthesis runs).
+    pub duration_ms: u64,
 }
 
 /// Sender to the TTS worker. The worker owns PiperTTSProvider (and rodio) on its thread.
@@ -53,9 +197,48 @@ pub type TtsState = mpsc::Sender<TtsRequest>;
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum TtsProvider {
     Piper,
-    #[default]
+    #[cfg_attr(desktop, default)]
     Microsoft,
     Polly,
+    /// The OS speech engine (see `system`).
+    System,
+    /// The platform speech engine on Android and iOS (see `mobile`); the default there.
+    #[cfg_attr(mobile, default)]
+    Mobile,
+    /// A local voice-cloning server (see `custom`).
+    Custom,
+}
+
+impl TtsProvider {
+    /// The provider's name in config (`voice_provider`, `provider_fallbacks`).
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Piper => "piper",
+            Self::Microsoft => "microsoft",
+            Self::Polly => "polly",
+            Self::System => "system",
+            Self::Mobile => "mobile",
+            Self::Custom => "custom",
+        }
+    }
+
+    /// Whether the provider is an internet service (off in offline mode, see `offline`). The
+    /// custom voice server runs locally.
+    pub fn is_cloud(self) -> bool {
+        matches!(self, Self::Microsoft | Self::Polly)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "piper" => Some(Self::Piper),
+            "microsoft" => Some(Self::Microsoft),
+            "polly" => Some(Self::Polly),
+            "system" => Some(Self::System),
+            "mobile" => Some(Self::Mobile),
+            "custom" => Some(Self::Custom),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -64,8 +247,154 @@ struct TtsConfigSnapshot {
     selected_voice: Option<String>,
     selected_polly_voice: Option<String>,
     selected_microsoft_voice: Option<String>,
+    selected_system_voice: Option<String>,
+    selected_mobile_voice: Option<String>,
+    /// Region, profile and engine for Polly.
+    polly: PollySettings,
+    /// `None` when no speaker is configured.
+    custom_server: Option<CustomServer>,
+    /// Calibrated speed for the selected voice (see `calibration`), applied on provider load.
+    calibrated_speed: Option<f32>,
+    /// Saved volume, speed and pitch, applied when the worker starts.
+    playback: PlaybackSettings,
+    /// Preprocessing applied to text before it is spoken.
+    pipeline: crate::text::pipeline::Pipeline,
+    /// Lexicon phoneme overrides for the selected voice, applied at synthesis.
+    pronunciations: Pronunciations,
+    /// SSML generation for the cloud voices.
+    ssml: SsmlOptions,
+    /// Language of the selected voice (see `lexicon::voice_language`).
+    voice_language: Option<String>,
+    /// Providers to try, in order, when the active one fails (see `fallback`).
+    fallbacks: Vec<TtsProvider>,
+    /// `selected_audio_output`; `None` for the system default (see `output`).
+    audio_output: Option<String>,
 }
 
+impl TtsConfigSnapshot {
+    /// The voice configured for `provider` (the speaker for the custom server).
+    fn voice_for(&self, provider: TtsProvider) -> Option<String> {
+        match provider {
+            TtsProvider::Piper => self.selected_voice.clone(),
+            TtsProvider::Microsoft => self.selected_microsoft_voice.clone(),
+            TtsProvider::Polly => self.selected_polly_voice.clone(),
+            TtsProvider::System => self.selected_system_voice.clone(),
+            TtsProvider::Mobile => self.selected_mobile_voice.clone(),
+            TtsProvider::Custom => self.custom_server.as_ref().map(|s| s.speaker.clone()),
+        }
+    }
+
+    fn markup(&self, passthrough: Option<Passthrough>) -> Markup {
+        Markup::new(self.pronunciations.clone(), self.ssml.clone(), passthrough)
+    }
+
+    /// Whether this selects another voice than `old` for this snapshot's provider.
+    fn voice_differs(&self, old: &Self) -> bool {
+        match self.provider {
+            TtsProvider::Piper => self.selected_voice != old.selected_voice,
+            TtsProvider::Polly => {
+                self.selected_polly_voice != old.selected_polly_voice || self.polly != old.polly
+            }
+            TtsProvider::Microsoft => self.selected_microsoft_voice != old.selected_microsoft_voice,
+            TtsProvider::System => self.selected_system_voice != old.selected_system_voice,
+            TtsProvider::Mobile => self.selected_mobile_voice != old.selected_mobile_voice,
+            TtsProvider::Custom => self.custom_server != old.custom_server,
+        }
+    }
+}
+
+/// Volume, speed and pitch the user set (`playback_volume`, `playback_speed`, `playback_pitch`),
+/// the pause between sentences (`sentence_pause_ms`) and the skim speed (`skim_speed`). Kept by
+/// the worker across provider loads; the commands that change them also save them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PlaybackSettings {
+    volume_percent: u8,
+    /// `None` until set, so the voice's calibrated speed applies.
+    speed: Option<f32>,
+    /// Percent, -50..=50.
+    pitch: i32,
+    /// Silence between chunks, 0..=2000; with a pause, every sentence is a chunk.
+    sentence_pause_ms: u32,
+    /// Speed while skimming (see `TtsRequest::ToggleSkim`).
+    skim_speed: f32,
+    /// Skim mode is on: `skim_speed` plays instead of `speed`. Not saved.
+    skimming: bool,
+}
+
+impl Default for PlaybackSettings {
+    fn default() -> Self {
+        Self {
+            volume_percent: 100,
+            speed: None,
+            pitch: 0,
+            sentence_pause_ms: 0,
+            skim_speed: DEFAULT_SKIM_SPEED,
+            skimming: false,
+        }
+    }
+}
+
+impl PlaybackSettings {
+    fn from_config(cfg: &crate::config::FullConfig) -> Self {
+        Self {
+            volume_percent: cfg.playback_volume.unwrap_or(100).min(100),
+            speed: cfg
+                .playback_speed
+                .filter(|s| s.is_finite())
+                .map(|s| s.clamp(MIN_SPEED, MAX_SPEED)),
+            pitch: cfg.playback_pitch.unwrap_or(0).clamp(MIN_PITCH, MAX_PITCH),
+            sentence_pause_ms: cfg
+                .sentence_pause_ms
+                .unwrap_or(0)
+                .min(MAX_SENTENCE_PAUSE_MS),
+            skim_speed: cfg
+                .skim_speed
+                .filter(|s| s.is_finite())
+                .map_or(DEFAULT_SKIM_SPEED, |s| s.clamp(MIN_SPEED, MAX_SPEED)),
+            skimming: false,
+        }
+    }
+
+    /// Speed to play at: the skim speed while skimming, else the saved speed or, without one,
+    /// the voice's calibrated speed.
+    fn active_speed(&self, calibrated_speed: Option<f32>) -> Option<f32> {
+        if self.skimming {
+            Some(self.skim_speed)
+        } else {
+            self.speed.or(calibrated_speed)
+        }
+    }
+
+    /// Silence before each chunk after the first; none when proofreading, which has its own.
+    fn pause_ms(&self, proofreading: bool) -> u64 {
+        if proofreading {
+            0
+        } else {
+            u64::from(self.sentence_pause_ms)
+        }
+    }
+
+    /// Applies the settings to a newly loaded provider (see `active_speed`).
+    fn apply(&self, provider: &mut TtsProviderImpl, calibrated_speed: Option<f32>) {
+        provider.set_volume(self.volume_percent);
+        if let Some(speed) = self.active_speed(calibrated_speed) {
+            provider.set_speed(speed);
+        }
+        provider.set_pitch(self.pitch);
+    }
+}
+
+/// Playback speed range (1.0 = normal).
+pub const MIN_SPEED: f32 = 0.25;
+pub const MAX_SPEED: f32 = 4.0;
+/// Pitch range, in percent.
+pub const MIN_PITCH: i32 = -50;
+pub const MAX_PITCH: i32 = 50;
+/// Longest pause between sentences.
+const MAX_SENTENCE_PAUSE_MS: u32 = 2000;
+/// Skim speed when `skim_speed` is not set.
+const DEFAULT_SKIM_SPEED: f32 = 2.0;
+
 fn normalize_voice(value: Option<String>) -> Option<String> {
     value
         .as_deref()
@@ -73,20 +402,94 @@ fn normalize_voice(value: Option<String>) -> Option<String> {
         .map(|s| s.to_string())
 }
 
-fn load_tts_config() -> TtsConfigSnapshot {
+/// Provider and voice used in place of the configured ones for this process (the CLI's
+/// `--provider` and `--voice`). The CLI sets it before `create_tts_state`.
+#[derive(Debug, Clone, Default)]
+pub struct VoiceOverride {
+    pub provider: Option<TtsProvider>,
+    /// Voice of the effective provider: a Piper key, Polly ID, Microsoft short name, system voice
+    /// or custom server speaker.
+    pub voice: Option<String>,
+}
+
+impl VoiceOverride {
+    fn apply(&self, cfg: &mut crate::config::FullConfig) {
+        if let Some(provider) = self.provider {
+            cfg.voice_provider = Some(provider.name().to_string());
+        }
+        let Some(voice) = self.voice.clone() else {
+            return;
+        };
+        let provider = cfg
+            .voice_provider
+            .as_deref()
+            .and_then(TtsProvider::from_name)
+            .unwrap_or_default();
+        let field = match provider {
+            TtsProvider::Piper => &mut cfg.selected_voice,
+            TtsProvider::Polly => &mut cfg.selected_polly_voice,
+            TtsProvider::Microsoft => &mut cfg.selected_microsoft_voice,
+            TtsProvider::System => &mut cfg.selected_system_voice,
+            TtsProvider::Mobile => &mut cfg.selected_mobile_voice,
+            TtsProvider::Custom => &mut cfg.custom_tts_speaker,
+        };
+        *field = Some(voice);
+    }
+}
+
+static VOICE_OVERRIDE: OnceLock<VoiceOverride> = OnceLock::new();
+
+/// Uses `value` instead of the configured provider and voice for the rest of the process.
+pub fn set_voice_override(value: VoiceOverride) {
+    let _ = VOICE_OVERRIDE.set(value);
+}
+
+/// TTS settings from config. With `text`, the voice mapped to its language (`language` when
+/// known, else detected) replaces the selected one (see `text::language`).
+fn load_tts_config(text: Option<&str>, language: Option<&str>) -> TtsConfigSnapshot {
     match crate::config::load_full_config() {
-        Ok(cfg) => {
-            let provider = match cfg.voice_provider.as_deref() {
-                Some("piper") => TtsProvider::Piper,
-                Some("polly") => TtsProvider::Polly,
-                Some("microsoft") => TtsProvider::Microsoft,
-                _ => TtsProvider::default(),
-            };
+        Ok(mut cfg) => {
+            if let Some(text) = text {
+                crate::text::language::apply_voice_map(&mut cfg, text, language);
+            }
+            if let Some(value) = VOICE_OVERRIDE.get() {
+                value.apply(&mut cfg);
+            }
+            let provider = offline_provider(
+                cfg.voice_provider
+                    .as_deref()
+                    .and_then(TtsProvider::from_name)
+                    .unwrap_or_default(),
+            );
+            let calibrated_speed = crate::calibration::calibrated_speed(&cfg);
+            let playback = PlaybackSettings::from_config(&cfg);
+            let pipeline = crate::text::pipeline::Pipeline::for_config(&cfg);
+            let pronunciations = Pronunciations::for_config(&cfg);
+            let ssml = SsmlOptions::from_config(&cfg);
+            let voice_language = crate::text::lexicon::voice_language(&cfg);
+            let custom_server = custom_server(&cfg);
+            let mut fallbacks =
+                fallback::parse_chain(cfg.provider_fallbacks.as_deref().unwrap_or(&[]));
+            if crate::offline::is_offline() {
+                fallbacks.retain(|p| !p.is_cloud());
+            }
             TtsConfigSnapshot {
                 provider,
+                calibrated_speed,
+                playback,
+                pipeline,
+                pronunciations,
+                ssml,
+                voice_language,
                 selected_voice: normalize_voice(cfg.selected_voice),
                 selected_polly_voice: normalize_voice(cfg.selected_polly_voice),
                 selected_microsoft_voice: normalize_voice(cfg.selected_microsoft_voice),
+                selected_system_voice: normalize_voice(cfg.selected_system_voice),
+                selected_mobile_voice: normalize_voice(cfg.selected_mobile_voice),
+                polly: PollySettings::from_config(&cfg),
+                custom_server,
+                fallbacks,
+                audio_output: normalize_voice(cfg.selected_audio_output),
             }
         }
         Err(err) => {
@@ -96,18 +499,173 @@ fn load_tts_config() -> TtsConfigSnapshot {
     }
 }
 
-pub fn check_polly_credentials() -> Result<(), String> {
-    PollyTTSProvider::check_credentials()
+/// `provider`, or a local one in its place when it is a cloud provider and offline mode is on:
+/// Piper when it can run, else the platform speech engine.
+fn offline_provider(provider: TtsProvider) -> TtsProvider {
+    if !provider.is_cloud() || !crate::offline::is_offline() {
+        return provider;
+    }
+    let local = if PiperTTSProvider::is_installed() {
+        TtsProvider::Piper
+    } else if cfg!(mobile) {
+        TtsProvider::Mobile
+    } else {
+        TtsProvider::System
+    };
+    tracing::debug!(configured = ?provider, using = ?local, "Offline mode, using a local provider");
+    local
+}
+
+/// Custom voice server settings; the language falls back to the UI language, then English.
+fn custom_server(cfg: &crate::config::FullConfig) -> Option<CustomServer> {
+    let speaker = normalize_voice(cfg.custom_tts_speaker.clone())?;
+    let url = normalize_voice(cfg.custom_tts_url.clone())
+        .unwrap_or_else(|| custom::DEFAULT_SERVER_URL.to_string());
+    let language = normalize_voice(cfg.custom_tts_language.clone())
+        .or_else(|| normalize_voice(cfg.ui_language.clone()))
+        .map(|lang| lang.split(['-', '_']).next().unwrap_or("en").to_string())
+        .unwrap_or_else(|| "en".to_string());
+    Some(CustomServer {
+        url,
+        speaker,
+        language,
+    })
+}
+
+/// Audio output devices, for picking `selected_audio_output`.
+pub fn audio_outputs() -> Vec<AudioOutput> {
+    output::list()
+}
+
+/// Credentials, profile, region and engine Polly would use (see `polly::diagnostics`).
+pub fn polly_diagnostics() -> PollyDiagnostics {
+    polly::diagnostics()
+}
+
+/// Whether local (Piper) voices can run, in process or with the Piper binary.
+pub fn is_piper_installed() -> bool {
+    PiperTTSProvider::is_installed()
+}
+
+/// Synthesizes a short phrase with the given Piper model (path without `.onnx`), discarding the audio.
+pub fn smoke_test_piper_model(model_path: &std::path::Path) -> Result<(), TTSError> {
+    PiperTTSProvider::smoke_test_model(model_path)
+}
+
+/// Sets the CPU priority for Piper and time-stretching from the `synthesis_priority` config value
+/// ("normal", "low", "idle").
+pub fn set_synthesis_priority(value: Option<&str>) {
+    priority::set(priority::SynthesisPriority::from_config(value));
+}
+
+/// Sets the preferred local inference backend from the `inference_backend` config value.
+pub fn set_inference_backend(value: Option<&str>) {
+    inference::set_preferred(value);
+}
+
+/// Detects the available inference backends on a background thread. Called once at startup.
+pub fn probe_inference_backends() {
+    inference::probe_in_background();
+}
+
+/// Available inference backends and the one Piper will use. Blocks until they are detected.
+pub fn inference_backends() -> InferenceBackends {
+    inference::inference_backends()
+}
+
+/// Enables or disables the start-of-read announcement (see `announce`).
+pub fn set_read_announcement(enabled: bool) {
+    announce::set_enabled(enabled);
+}
+
+/// Notes what started the read about to be spoken, for its announcement (`None` for speech that
+/// is not a read).
+pub fn note_read_source(source: Option<&str>) {
+    announce::note_source(source);
+}
+
+/// Enables or disables the in-memory playback trace (see `trace`).
+pub fn set_playback_trace_enabled(enabled: bool) {
+    trace::set_enabled(enabled);
+}
+
+/// Buffered playback trace entries, oldest first.
+pub fn playback_trace() -> Vec<PlaybackTraceEntry> {
+    trace::snapshot()
 }
 
 enum TtsProviderImpl {
     Piper(PiperTTSProvider),
     Microsoft(MicrosoftTTSProvider),
     Polly(PollyTTSProvider),
+    System(SystemTTSProvider),
+    Mobile(MobileTTSProvider),
+    Custom(CustomTTSProvider),
 }
 
 impl TtsProviderImpl {
-    fn new(provider: TtsProvider, config: &TtsConfigSnapshot) -> Result<Self, TTSError> {
+    /// Loads `provider`; when it cannot be loaded (no Piper voice installed, missing Polly
+    /// credentials, ...), the first fallback provider that loads, then the bundled onboarding
+    /// voice. Starts `fallbacks` over for the provider.
+    fn new(
+        provider: TtsProvider,
+        config: &TtsConfigSnapshot,
+        fallbacks: &mut Fallbacks,
+    ) -> Result<Self, TTSError> {
+        fallbacks.reset(provider, &config.fallbacks);
+        let e = match Self::load(provider, config) {
+            Ok(loaded) => return Ok(loaded),
+            Err(e) => e,
+        };
+        if let Some(loaded) = Self::load_fallback(fallbacks, config, provider, &e) {
+            return Ok(loaded);
+        }
+        match PiperTTSProvider::onboarding() {
+            Ok(onboarding) => {
+                tracing::warn!(?provider, error = %e, "Provider unavailable, using the onboarding voice");
+                Ok(Self::Piper(onboarding))
+            }
+            Err(_) => Err(e),
+        }
+    }
+
+    /// Loads the next provider of the fallback chain that can be loaded, reporting the switch
+    /// from `from` (which failed with `reason`).
+    fn load_fallback(
+        fallbacks: &mut Fallbacks,
+        config: &TtsConfigSnapshot,
+        from: TtsProvider,
+        reason: &TTSError,
+    ) -> Option<Self> {
+        while let Some(next) = fallbacks.next() {
+            match Self::load(next, config) {
+                Ok(loaded) => {
+                    fallbacks.report(from, next, reason);
+                    return Some(loaded);
+                }
+                Err(e) => {
+                    tracing::warn!(provider = next.name(), error = %e, "Fallback provider unavailable");
+                }
+            }
+        }
+        None
+    }
+
+    fn variant(&self) -> TtsProvider {
+        match self {
+            Self::Piper(_) => TtsProvider::Piper,
+            Self::Microsoft(_) => TtsProvider::Microsoft,
+            Self::Polly(_) => TtsProvider::Polly,
+            Self::System(_) => TtsProvider::System,
+            Self::Mobile(_) => TtsProvider::Mobile,
+            Self::Custom(_) => TtsProvider::Custom,
+        }
+    }
+
+    fn load(provider: TtsProvider, config: &TtsConfigSnapshot) -> Result<Self, TTSError> {
+        if provider.is_cloud() && crate::offline::is_offline() {
+            return Err(TTSError::Offline);
+        }
         match provider {
             TtsProvider::Piper => Ok(Self::Piper(PiperTTSProvider::new(
                 config.selected_voice.clone(),
@@ -116,21 +674,74 @@ impl TtsProviderImpl {
                 config.selected_microsoft_voice.clone(),
             )?)),
             TtsProvider::Polly => {
-                if let Err(e) = PollyTTSProvider::check_credentials() {
-                    return Err(TTSError::ProcessError(e));
+                if let Err(e) = PollyTTSProvider::check_credentials(&config.polly) {
+                    return Err(TTSError::NotConfigured(e));
                 }
                 Ok(Self::Polly(PollyTTSProvider::new(
                     config.selected_polly_voice.clone(),
+                    &config.polly,
                 )?))
             }
+            TtsProvider::System => Ok(Self::System(SystemTTSProvider::new(
+                config.selected_system_voice.clone(),
+            )?)),
+            TtsProvider::Mobile => Ok(Self::Mobile(MobileTTSProvider::new(
+                config.selected_mobile_voice.clone(),
+            )?)),
+            TtsProvider::Custom => Ok(Self::Custom(CustomTTSProvider::new(
+                config.custom_server.clone(),
+            )?)),
         }
     }
 
-    fn speak(&mut self, text: &str) -> Result<(), TTSError> {
+    /// Synthesizer for this provider. With `onboarding_fallback`, cloud and server voices fall
+    /// back to the onboarding voice instead of failing, reported to its notifier (see
+    /// `with_onboarding_fallback`).
+    fn synthesizer(
+        &self,
+        markup: Markup,
+        onboarding_fallback: Option<OnboardingFallback>,
+    ) -> stream::SynthesizeFn {
+        let (synthesizer, remote) = match self {
+            Self::Piper(p) => (p.synthesizer(markup.clone()), false),
+            Self::Microsoft(p) => (p.synthesizer(markup.clone()), true),
+            Self::Polly(p) => (p.synthesizer(markup.clone()), true),
+            Self::System(p) => (p.synthesizer(markup.clone()), false),
+            Self::Mobile(p) => (p.synthesizer(markup.clone()), false),
+            Self::Custom(p) => (p.synthesizer(markup.clone()), true),
+        };
+        let engine = match self {
+            Self::Polly(p) => Some(p.engine_name()),
+            _ => None,
+        };
+        let synthesizer = metered(synthesizer, usage::meter(self.variant(), engine));
+        match onboarding_fallback {
+            Some(notifier) if remote => {
+                with_onboarding_fallback(synthesizer, markup, self.variant(), notifier)
+            }
+            _ => synthesizer,
+        }
+    }
+
+    fn append_audio(&mut self, audio_data: Vec<f32>, sample_rate: u32) -> Result<(), TTSError> {
+        match self {
+            Self::Piper(p) => p.append_audio(audio_data, sample_rate),
+            Self::Microsoft(p) => p.append_audio(audio_data, sample_rate),
+            Self::Polly(p) => p.append_audio(audio_data, sample_rate),
+            Self::System(p) => p.append_audio(audio_data, sample_rate),
+            Self::Mobile(p) => p.append_audio(audio_data, sample_rate),
+            Self::Custom(p) => p.append_audio(audio_data, sample_rate),
+        }
+    }
+
+    fn current_segment(&self) -> usize {
         match self {
-            Self::Piper(p) => p.speak(text),
-            Self::Microsoft(p) => p.speak(text),
-            Self::Polly(p) => p.speak(text),
+            Self::Piper(p) => p.current_segment(),
+            Self::Microsoft(p) => p.current_segment(),
+            Self::Polly(p) => p.current_segment(),
+            Self::System(p) => p.current_segment(),
+            Self::Mobile(p) => p.current_segment(),
+            Self::Custom(p) => p.current_segment(),
         }
     }
 
@@ -139,6 +750,20 @@ impl TtsProviderImpl {
             Self::Piper(p) => p.stop(),
             Self::Microsoft(p) => p.stop(),
             Self::Polly(p) => p.stop(),
+            Self::System(p) => p.stop(),
+            Self::Mobile(p) => p.stop(),
+            Self::Custom(p) => p.stop(),
+        }
+    }
+
+    fn fade_out(&mut self, duration: Duration) -> Result<(), TTSError> {
+        match self {
+            Self::Piper(p) => p.fade_out(duration),
+            Self::Microsoft(p) => p.fade_out(duration),
+            Self::Polly(p) => p.fade_out(duration),
+            Self::System(p) => p.fade_out(duration),
+            Self::Mobile(p) => p.fade_out(duration),
+            Self::Custom(p) => p.fade_out(duration),
         }
     }
 
@@ -147,6 +772,9 @@ impl TtsProviderImpl {
             Self::Piper(p) => p.toggle_pause(),
             Self::Microsoft(p) => p.toggle_pause(),
             Self::Polly(p) => p.toggle_pause(),
+            Self::System(p) => p.toggle_pause(),
+            Self::Mobile(p) => p.toggle_pause(),
+            Self::Custom(p) => p.toggle_pause(),
         }
     }
 
@@ -155,14 +783,65 @@ impl TtsProviderImpl {
             Self::Piper(p) => p.get_status(),
             Self::Microsoft(p) => p.get_status(),
             Self::Polly(p) => p.get_status(),
+            Self::System(p) => p.get_status(),
+            Self::Mobile(p) => p.get_status(),
+            Self::Custom(p) => p.get_status(),
         }
     }
 
-    fn seek(&mut self, offset_ms: i64) -> Result<(bool, bool, bool), TTSError> {
+    fn seek(&mut self, offset_ms: i64) -> Result<SeekResult, TTSError> {
         match self {
             Self::Piper(p) => p.seek(offset_ms),
             Self::Microsoft(p) => p.seek(offset_ms),
             Self::Polly(p) => p.seek(offset_ms),
+            Self::System(p) => p.seek(offset_ms),
+            Self::Mobile(p) => p.seek(offset_ms),
+            Self::Custom(p) => p.seek(offset_ms),
+        }
+    }
+
+    fn seek_to(&mut self, position_ms: u64) -> Result<SeekResult, TTSError> {
+        match self {
+            Self::Piper(p) => p.seek_to(position_ms),
+            Self::Microsoft(p) => p.seek_to(position_ms),
+            Self::Polly(p) => p.seek_to(position_ms),
+            Self::System(p) => p.seek_to(position_ms),
+            Self::Mobile(p) => p.seek_to(position_ms),
+            Self::Custom(p) => p.seek_to(position_ms),
+        }
+    }
+
+    /// See `AudioPlayer::check_output`.
+    fn check_output(&mut self, force: bool) -> bool {
+        match self {
+            Self::Piper(p) => p.check_output(force),
+            Self::Microsoft(p) => p.check_output(force),
+            Self::Polly(p) => p.check_output(force),
+            Self::System(p) => p.check_output(force),
+            Self::Mobile(p) => p.check_output(force),
+            Self::Custom(p) => p.check_output(force),
+        }
+    }
+
+    fn reopen_output(&mut self) -> bool {
+        match self {
+            Self::Piper(p) => p.reopen_output(),
+            Self::Microsoft(p) => p.reopen_output(),
+            Self::Polly(p) => p.reopen_output(),
+            Self::System(p) => p.reopen_output(),
+            Self::Mobile(p) => p.reopen_output(),
+            Self::Custom(p) => p.reopen_output(),
+        }
+    }
+
+    fn waveform(&self, buckets: usize) -> Vec<f32> {
+        match self {
+            Self::Piper(p) => p.waveform(buckets),
+            Self::Microsoft(p) => p.waveform(buckets),
+            Self::Polly(p) => p.waveform(buckets),
+            Self::System(p) => p.waveform(buckets),
+            Self::Mobile(p) => p.waveform(buckets),
+            Self::Custom(p) => p.waveform(buckets),
         }
     }
 
@@ -171,6 +850,9 @@ impl TtsProviderImpl {
             Self::Piper(p) => p.get_position(),
             Self::Microsoft(p) => p.get_position(),
             Self::Polly(p) => p.get_position(),
+            Self::System(p) => p.get_position(),
+            Self::Mobile(p) => p.get_position(),
+            Self::Custom(p) => p.get_position(),
         }
     }
 
@@ -179,6 +861,9 @@ impl TtsProviderImpl {
             Self::Piper(p) => p.set_volume(volume_percent),
             Self::Microsoft(p) => p.set_volume(volume_percent),
             Self::Polly(p) => p.set_volume(volume_percent),
+            Self::System(p) => p.set_volume(volume_percent),
+            Self::Mobile(p) => p.set_volume(volume_percent),
+            Self::Custom(p) => p.set_volume(volume_percent),
         }
     }
 
@@ -187,35 +872,210 @@ impl TtsProviderImpl {
             Self::Piper(p) => p.set_speed(speed),
             Self::Microsoft(p) => p.set_speed(speed),
             Self::Polly(p) => p.set_speed(speed),
+            Self::System(p) => p.set_speed(speed),
+            Self::Mobile(p) => p.set_speed(speed),
+            Self::Custom(p) => p.set_speed(speed),
         }
     }
+
+    /// Pitch in percent. Local voices are pitch-shifted at playback; the cloud voices get it as
+    /// SSML prosody from `SsmlOptions` at synthesis instead (Polly's neural voices ignore it).
+    fn set_pitch(&mut self, percent: i32) {
+        let ratio = 1.0 + percent.clamp(MIN_PITCH, MAX_PITCH) as f32 / 100.0;
+        match self {
+            Self::Piper(p) => p.set_pitch(ratio),
+            Self::System(p) => p.set_pitch(ratio),
+            Self::Mobile(p) => p.set_pitch(ratio),
+            Self::Custom(p) => p.set_pitch(ratio),
+            Self::Microsoft(_) | Self::Polly(_) => {}
+        }
+    }
+}
+
+/// Counts the characters of each chunk `synthesize` completes (see `usage`).
+fn metered(mut synthesize: stream::SynthesizeFn, meter: String) -> stream::SynthesizeFn {
+    Box::new(move |text: &str| {
+        let audio = synthesize(text)?;
+        usage::record(&meter, text.chars().count());
+        Ok(audio)
+    })
+}
+
+/// Where a switch to the onboarding voice is reported (`None`: only logged).
+type OnboardingFallback = Option<Arc<FallbackNotifier>>;
+
+/// Whether the onboarding voice (US English) may stand in for a voice in `language`. An unknown
+/// language counts as English, the UI default.
+fn onboarding_speaks(language: Option<&str>) -> bool {
+    language.is_none_or(|language| language.to_ascii_lowercase().starts_with("en"))
+}
+
+/// The onboarding fallback for a voice in `language`, or `None` when the onboarding voice does
+/// not speak it.
+fn onboarding_fallback(
+    language: Option<&str>,
+    notifier: Option<Arc<FallbackNotifier>>,
+) -> Option<OnboardingFallback> {
+    onboarding_speaks(language).then_some(notifier)
+}
+
+/// Speaks with the bundled onboarding voice when a cloud or server voice fails (offline, service
+/// down), so a read still produces audio. After the first failure the rest of the read uses the
+/// fallback; the switch is reported like a provider fallback.
+fn with_onboarding_fallback(
+    mut primary: stream::SynthesizeFn,
+    markup: Markup,
+    from: TtsProvider,
+    notifier: OnboardingFallback,
+) -> stream::SynthesizeFn {
+    let Some(mut fallback) = PiperTTSProvider::onboarding_synthesizer(markup) else {
+        return primary;
+    };
+    let mut failed = false;
+    Box::new(move |text: &str| {
+        if !failed {
+            match primary(text) {
+                Ok(audio) => return Ok(audio),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Cloud voice failed, using the onboarding voice");
+                    if let Some(notifier) = &notifier {
+                        notifier(&ProviderFallback {
+                            from: from.name(),
+                            to: fallback::ONBOARDING,
+                            reason: e.to_string(),
+                        });
+                    }
+                    failed = true;
+                }
+            }
+        }
+        fallback(text)
+    })
+}
+
+/// Synthesizer for a read. The onboarding voice only backs up a cloud voice once no fallback
+/// provider is left to try, and only when it speaks the voice's language.
+fn read_synthesizer(
+    provider: &TtsProviderImpl,
+    markup: Markup,
+    proofread: bool,
+    fallbacks: &Fallbacks,
+    voice_language: Option<&str>,
+) -> stream::SynthesizeFn {
+    let onboarding = if fallbacks.is_empty() {
+        onboarding_fallback(voice_language, fallbacks.notifier())
+    } else {
+        None
+    };
+    let synthesizer = provider.synthesizer(markup, onboarding);
+    if proofread {
+        proofread::with_pauses(synthesizer)
+    } else {
+        synthesizer
+    }
+}
+
+/// Queues a chunk's audio at the current end of the queue, after `pause_ms` of silence unless it
+/// is the first one, and adds its words to the timeline.
+fn queue_chunk(
+    provider: &mut TtsProviderImpl,
+    timeline: &mut Timeline,
+    chunk_text: &str,
+    audio: ChunkAudio,
+    pause_ms: u64,
+) -> Result<(), TTSError> {
+    let (_, queued_ms) = provider.get_position();
+    let ChunkAudio {
+        mut pcm,
+        sample_rate,
+        words,
+    } = audio;
+    let pause_ms = if queued_ms > 0 { pause_ms } else { 0 };
+    let silence = (u64::from(sample_rate) * pause_ms / 1000) as usize;
+    pcm.splice(0..0, std::iter::repeat(0.0).take(silence));
+    provider.append_audio(pcm, sample_rate)?;
+    timeline.push_chunk(chunk_text, &words, queued_ms + pause_ms);
+    Ok(())
+}
+
+/// Seeks to the segment start `target` (see `TtsRequest::NextSegment`).
+fn seek_to_segment(
+    provider: &mut TtsProviderImpl,
+    target: Option<u64>,
+    unit: SegmentUnit,
+) -> Result<SeekResult, TTSError> {
+    let target =
+        target.ok_or_else(|| TTSError::AudioError(format!("No {} to jump to", unit.name())))?;
+    provider.seek_to(target)
+}
+
+/// Records the loaded provider and its voice for `tts_get_provider_status`.
+fn set_active_status(provider: &TtsProviderImpl, config: &TtsConfigSnapshot) {
+    let variant = provider.variant();
+    status::set_active(variant, config.voice_for(variant));
+}
+
+/// Starts the next queued text by sending Speak to the worker itself. Returns false when the queue
+/// ran out.
+fn advance_queue(queue: &mut Queue, worker_tx: &TtsState) -> bool {
+    let Some(item) = queue.advance() else {
+        return false;
+    };
+    tracing::debug!(id = item.id, source = %item.source, "Starting queued text");
+    // Nobody waits for the queued item's response.
+    let (resp_tx, _) = mpsc::sync_channel(1);
+    let _ = worker_tx.send(TtsRequest::Speak(item.text, item.input_kind, None, resp_tx));
+    true
+}
+
+/// (playing, paused), or `None` when the worker is gone.
+pub fn playback_status(tx: &TtsState) -> Option<(bool, bool)> {
+    let (resp_tx, resp_rx) = mpsc::sync_channel(1);
+    tx.send(TtsRequest::GetStatus(resp_tx)).ok()?;
+    resp_rx.recv().ok()
+}
+
+/// Stops the worker and waits, at most `timeout`, until it has stopped playback and recorded the
+/// current read (`reading_stats`), so a flush that follows includes it. False on timeout.
+pub fn shutdown_worker(tx: &TtsState, timeout: Duration) -> bool {
+    let (done_tx, done_rx) = mpsc::sync_channel(1);
+    if tx.send(TtsRequest::Shutdown(done_tx)).is_err() {
+        // The worker is already gone.
+        return true;
+    }
+    done_rx.recv_timeout(timeout).is_ok()
 }
 
 /// Spawn the TTS worker and return the channel sender to manage.
 pub fn create_tts_state() -> TtsState {
     let (tx, rx) = mpsc::channel();
-    let mut config_snapshot = load_tts_config();
+    let worker_tx: TtsState = tx.clone();
+    let mut config_snapshot = load_tts_config(None, None);
     let default_provider = config_snapshot.provider;
 
     std::thread::spawn(move || {
         tracing::info!(provider = ?default_provider, "Initializing TTS worker");
-        let mut current_volume_percent: u8 = 100;
-        let mut provider = match TtsProviderImpl::new(default_provider, &config_snapshot) {
-            Ok(p) => {
-                tracing::info!("TTS worker initialized successfully");
-                p
-            }
+        output::select(config_snapshot.audio_output.clone());
+        let mut playback = config_snapshot.playback;
+        let mut fallbacks = Fallbacks::default();
+        let mut timeline = Timeline::default();
+        let mut queue = Queue::default();
+        let initial = TtsProviderImpl::new(default_provider, &config_snapshot, &mut fallbacks);
+        let mut provider = match initial {
+            Ok(p) => p,
             Err(e) => {
                 tracing::warn!(error = %e, "TTS not available: provider init failed");
+                status::record_error(None, &e);
                 loop {
                     match rx.recv() {
-                        Ok(TtsRequest::Speak(_, resp)) => {
+                        Ok(TtsRequest::Speak(_, _, _, resp))
+                        | Ok(TtsRequest::Proofread(_, resp)) => {
                             let _ = resp.send(Err(TTSError::ProcessError(
                                 "TTS not available: provider could not be initialized.".into(),
                             )));
                         }
                         Ok(TtsRequest::Stop) => {}
-                        Ok(TtsRequest::TogglePause(resp)) => {
+                        Ok(TtsRequest::TogglePause(resp)) | Ok(TtsRequest::ToggleSkim(resp)) => {
                             let _ = resp.send(Err(TTSError::ProcessError(
                                 "TTS not available: provider could not be initialized.".into(),
                             )));
@@ -223,7 +1083,11 @@ pub fn create_tts_state() -> TtsState {
                         Ok(TtsRequest::GetStatus(resp)) => {
                             let _ = resp.send((false, false));
                         }
-                        Ok(TtsRequest::Seek(_, resp)) => {
+                        Ok(TtsRequest::Seek(_, resp))
+                        | Ok(TtsRequest::SeekTo(_, resp))
+                        | Ok(TtsRequest::SeekPercent(_, resp))
+                        | Ok(TtsRequest::NextSegment(_, resp))
+                        | Ok(TtsRequest::PrevSegment(_, resp)) => {
                             let _ = resp.send(Err(TTSError::ProcessError(
                                 "TTS not available: provider could not be initialized.".into(),
                             )));
@@ -236,7 +1100,10 @@ pub fn create_tts_state() -> TtsState {
                                 "TTS not available: provider could not be initialized.".into(),
                             )));
                         }
-                        Ok(TtsRequest::SetSpeed(_, resp)) => {
+                        Ok(TtsRequest::GetSpeed(resp)) => {
+                            let _ = resp.send(None);
+                        }
+                        Ok(TtsRequest::SetSpeed(_, resp)) | Ok(TtsRequest::SetPitch(_, resp)) => {
                             let _ = resp.send(Err(TTSError::ProcessError(
                                 "TTS not available: provider could not be initialized.".into(),
                             )));
@@ -246,37 +1113,225 @@ pub fn create_tts_state() -> TtsState {
                                 "TTS not available: provider could not be initialized.".into(),
                             )));
                         }
-                        Ok(TtsRequest::Shutdown) => break,
-                        Err(_) => break,
+                        Ok(TtsRequest::GetTimeline(resp)) => {
+                            let _ = resp.send(Vec::new());
+                        }
+                        Ok(TtsRequest::GetWaveform(_, resp)) => {
+                            let _ = resp.send(TtsWaveform::default());
+                        }
+                        Ok(TtsRequest::Synthesizer(resp)) => {
+                            let _ = resp.send(Err(TTSError::ProcessError(
+                                "TTS not available: provider could not be initialized.".into(),
+                            )));
+                        }
+                        Ok(TtsRequest::Enqueue(_, _, _, resp)) => {
+                            let _ = resp.send(Err(TTSError::ProcessError(
+                                "TTS not available: provider could not be initialized.".into(),
+                            )));
+                        }
+                        Ok(TtsRequest::QueueList(resp)) => {
+                            let _ = resp.send(Vec::new());
+                        }
+                        Ok(TtsRequest::QueueSkip(resp)) => {
+                            let _ = resp.send(false);
+                        }
+                        Ok(TtsRequest::QueueClear(resp)) => {
+                            let _ = resp.send(0);
+                        }
+                        Ok(TtsRequest::SetSleepTimer(_, resp)) => {
+                            let _ = resp.send(Err(TTSError::ProcessError(
+                                "TTS not available: provider could not be initialized.".into(),
+                            )));
+                        }
+                        Ok(TtsRequest::CancelSleepTimer(resp)) => {
+                            let _ = resp.send(false);
+                        }
+                        Ok(TtsRequest::GetSleepTimer(resp)) => {
+                            let _ = resp.send(None);
+                        }
+                        Ok(TtsRequest::ReloadConfig) => {
+                            // Settings may have fixed what failed (credentials, voice): try again.
+                            config_snapshot = load_tts_config(None, None);
+                            output::select(config_snapshot.audio_output.clone());
+                            playback = config_snapshot.playback;
+                            match TtsProviderImpl::new(
+                                config_snapshot.provider,
+                                &config_snapshot,
+                                &mut fallbacks,
+                            ) {
+                                Ok(p) => break p,
+                                Err(e) => {
+                                    tracing::warn!(error = %e, "TTS still not available after config reload");
+                                    status::record_error(None, &e);
+                                }
+                            }
+                        }
+                        // Kept for when a config reload brings TTS up.
+                        Ok(TtsRequest::SetProgressNotifier(notifier)) => {
+                            timeline.set_notifier(notifier);
+                        }
+                        Ok(TtsRequest::SetQueueNotifier(notifier)) => {
+                            queue.set_notifier(notifier);
+                        }
+                        Ok(TtsRequest::SetFallbackNotifier(notifier)) => {
+                            fallbacks.set_notifier(notifier);
+                        }
+                        Ok(TtsRequest::ReopenOutput)
+                        | Ok(TtsRequest::RestoreSpeed(_))
+                        | Ok(TtsRequest::ChunkReady(_)) => {}
+                        Ok(TtsRequest::Shutdown(done)) => {
+                            let _ = done.send(());
+                            return;
+                        }
+                        Err(_) => return,
                     }
                 }
-                return;
             }
         };
-        while let Ok(req) = rx.recv() {
+        tracing::info!("TTS worker initialized successfully");
+        playback.apply(&mut provider, config_snapshot.calibrated_speed);
+        set_active_status(&provider, &config_snapshot);
+        let mut synthesis = Stream::default();
+        let mut sleep_timer = SleepTimer::default();
+        let mut proofreading = false;
+        // Markup of the current read, to start it over with a fallback provider.
+        let mut read_markup = Markup::default();
+        // The cached audio came from SSML input (never reused).
+        let mut cached_ssml = false;
+        // Stretches in another language of the read the cached audio came from.
+        let mut cached_languages = LanguageTags::default();
+        // Latency of the current read, until its first audio is queued (see `latency`).
+        let mut read_id = None;
+        loop {
+            if sleep_timer.take_due(Instant::now()) {
+                tracing::info!("Sleep timer ended, stopping playback");
+                synthesis.cancel();
+                let _ = provider.fade_out(sleep_timer::FADE_OUT);
+                timeline.reset("");
+                queue.clear();
+            }
+            // While a stream is active, wake up regularly so synthesis can keep running ahead;
+            // while words are playing, wake up often enough to follow them.
+            let reporting = timeline.is_reporting() && provider.get_status() == (true, false);
+            let tick = if reporting {
+                Some(timeline::PROGRESS_TICK)
+            } else if synthesis.is_pending() {
+                Some(stream::STREAM_TICK)
+            } else if queue.is_active() {
+                Some(queue::QUEUE_TICK)
+            } else if provider.get_status().0 {
+                // Playing without word progress: still watch for a changed output device.
+                Some(output::CHECK_INTERVAL)
+            } else {
+                None
+            };
+            let tick = sleep_timer.limit(tick, Instant::now());
+            let mut req = match tick {
+                Some(tick) => match rx.recv_timeout(tick) {
+                    Ok(req) => req,
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        synthesis.set_playing(provider.current_segment());
+                        if provider.get_status().0 {
+                            provider.check_output(false);
+                        }
+                        if reporting {
+                            timeline.update(provider.get_position().0);
+                        }
+                        let finished = !synthesis.is_pending() && !provider.get_status().0;
+                        if finished {
+                            timeline.finish(true);
+                        }
+                        if finished && queue.can_advance() {
+                            advance_queue(&mut queue, &worker_tx);
+                        }
+                        continue;
+                    }
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                },
+                None => match rx.recv() {
+                    Ok(req) => req,
+                    Err(_) => break,
+                },
+            };
+            synthesis.set_playing(provider.current_segment());
+            let proofread = matches!(req, TtsRequest::Proofread(..));
+            let ssml_input = matches!(req, TtsRequest::Speak(_, InputKind::Ssml, ..));
+            let spans = match &mut req {
+                TtsRequest::Speak(_, _, spans, _) => spans.take(),
+                _ => None,
+            };
             match req {
-                TtsRequest::Speak(text, resp) => {
-                    let new_config = load_tts_config();
-                    let current_provider = new_config.provider;
-                    let provider_variant = match provider {
-                        TtsProviderImpl::Piper(_) => TtsProvider::Piper,
-                        TtsProviderImpl::Microsoft(_) => TtsProvider::Microsoft,
-                        TtsProviderImpl::Polly(_) => TtsProvider::Polly,
-                    };
-                    let provider_changed = current_provider != provider_variant;
-                    let voice_changed = match current_provider {
-                        TtsProvider::Piper => {
-                            new_config.selected_voice != config_snapshot.selected_voice
+                TtsRequest::Speak(text, _, _, resp) | TtsRequest::Proofread(text, resp) => {
+                    read_id = (!proofread).then(latency::speak_received);
+                    let _span = read_id.map(|id| latency::span(id).entered());
+                    queue.on_speak();
+                    // An announced read is spoken after its announcement, as the next queue item.
+                    let announced = announce::take_source().filter(|_| !proofread);
+                    let announcing = announced.is_some();
+                    let text = match announced {
+                        Some(source) if ssml_input => {
+                            let plain = Passthrough::new(&text).plain;
+                            queue.defer(text, InputKind::Ssml, source.clone());
+                            announce::announcement(&source, &plain)
                         }
-                        TtsProvider::Polly => {
-                            new_config.selected_polly_voice != config_snapshot.selected_polly_voice
+                        Some(source) => {
+                            let announcement = announce::announcement(&source, &text);
+                            queue.defer(text, InputKind::Text, source);
+                            announcement
                         }
-                        TtsProvider::Microsoft => {
-                            new_config.selected_microsoft_voice
-                                != config_snapshot.selected_microsoft_voice
+                        None => text,
+                    };
+                    let ssml_input = ssml_input && !announcing;
+                    // SSML input is one chunk; its plain text drives the timeline (and Piper).
+                    let passthrough = ssml_input.then(|| Passthrough::new(&text));
+                    let text = passthrough.as_ref().map_or(text, |p| p.plain.clone());
+                    let read_language = spans
+                        .as_deref()
+                        .filter(|_| !announcing)
+                        .and_then(|spans| language::whole_text_language(&text, spans));
+                    let new_config = load_tts_config(Some(&text), read_language);
+                    let preprocess_started = Instant::now();
+                    let prepared = crate::text::prepare(&text, &new_config.pipeline);
+                    if let Some(id) = read_id {
+                        latency::preprocessed(id, preprocess_started.elapsed());
+                    }
+                    // Spans sent with an announced read or SSML input do not fit what is spoken.
+                    let languages = match spans.filter(|_| !announcing && passthrough.is_none()) {
+                        Some(spans) => language::tags_from_spans(&text, &spans),
+                        None if new_config.ssml.language_tags()
+                            && passthrough.is_none()
+                            && matches!(
+                                new_config.provider,
+                                TtsProvider::Microsoft | TtsProvider::Polly
+                            ) =>
+                        {
+                            language::detect_tags(
+                                &prepared.segments,
+                                new_config.voice_language.as_deref(),
+                            )
                         }
+                        None => LanguageTags::default(),
                     };
+                    synthesis.cancel();
+                    let _ = provider.stop();
+                    // The announcement is not part of the text the app shows; nothing to follow.
+                    timeline.reset(if announcing { "" } else { &text });
+                    let current_provider = new_config.provider;
+                    let provider_variant = provider.variant();
+                    let provider_changed = current_provider != provider_variant;
+                    let voice_changed = new_config.voice_differs(&config_snapshot);
 
+                    // Chunk text is unchanged when only the markup changed.
+                    if std::mem::replace(&mut cached_ssml, passthrough.is_some())
+                        || passthrough.is_some()
+                        || std::mem::replace(&mut cached_languages, languages.clone()) != languages
+                        || new_config.pronunciations != config_snapshot.pronunciations
+                        || new_config.ssml != config_snapshot.ssml
+                    {
+                        synthesis.clear_cache();
+                        config_snapshot.pronunciations = new_config.pronunciations.clone();
+                        config_snapshot.ssml = new_config.ssml.clone();
+                    }
                     if provider_changed || voice_changed {
                         tracing::info!(
                             old = ?provider_variant,
@@ -285,65 +1340,370 @@ pub fn create_tts_state() -> TtsState {
                             voice_changed,
                             "TTS config changed, reloading provider"
                         );
-                        match TtsProviderImpl::new(current_provider, &new_config) {
+                        synthesis.clear_cache();
+                        match TtsProviderImpl::new(current_provider, &new_config, &mut fallbacks) {
                             Ok(mut new_provider) => {
-                                new_provider.set_volume(current_volume_percent);
+                                playback.apply(&mut new_provider, new_config.calibrated_speed);
                                 provider = new_provider;
                                 config_snapshot = new_config;
+                                set_active_status(&provider, &config_snapshot);
                             }
                             Err(e) => {
+                                status::record_error(Some(current_provider), &e);
                                 let _ = resp.send(Err(e));
                                 continue;
                             }
                         }
+                    } else {
+                        config_snapshot.fallbacks = new_config.fallbacks;
+                        fallbacks.reset(provider_variant, &config_snapshot.fallbacks);
                     }
-                    let result = provider.speak(&text);
-                    if let Err(ref e) = result {
-                        tracing::error!(error = %e, "TTS speak failed");
+                    let chunks = if passthrough.is_some() && !text.is_empty() {
+                        vec![text.clone()]
+                    } else if passthrough.is_some() {
+                        Vec::new()
+                    } else if proofread {
+                        proofread::plan_chunks(&prepared.segments)
+                    } else {
+                        let (chunks, layout) = stream::plan_chunk_layout(
+                            &prepared.segments,
+                            playback.sentence_pause_ms > 0,
+                        );
+                        timeline.set_layout(layout);
+                        chunks
+                    };
+                    if chunks.is_empty() {
+                        tracing::warn!("Empty text provided, skipping synthesis");
+                        let _ = resp.send(Err(TTSError::EmptyText));
+                        continue;
+                    }
+                    let characters = chunks.iter().map(|c| c.chars().count()).sum();
+                    if let Err(e) = usage::check_budget(provider.variant(), characters) {
+                        let _ = resp.send(Err(TTSError::BudgetExceeded(e)));
+                        continue;
+                    }
+                    tracing::debug!(
+                        chars = text.len(),
+                        segments = prepared.segments.len(),
+                        chunks = chunks.len(),
+                        proofread,
+                        "Speaking"
+                    );
+                    timeline.set_chunks(chunks.len());
+                    timeline.set_provider(provider.variant());
+                    if !features::is_enabled(features::Flag::AudioReuse) {
+                        synthesis.clear_cache();
+                    }
+                    // Cached audio of the other mode has different pauses.
+                    if proofread != proofreading {
+                        synthesis.clear_cache();
+                        proofreading = proofread;
+                    }
+                    read_markup = config_snapshot
+                        .markup(passthrough)
+                        .with_languages(languages);
+                    let synthesizer = read_synthesizer(
+                        &provider,
+                        read_markup.clone(),
+                        proofread,
+                        &fallbacks,
+                        config_snapshot.voice_language.as_deref(),
+                    );
+                    if let Some(id) = read_id {
+                        latency::synthesis_started(id);
+                    }
+                    let cached = synthesis.start(chunks, synthesizer, worker_tx.clone(), resp);
+                    if !cached.is_empty() {
+                        tracing::debug!(chunks = cached.len(), "Reusing audio of unchanged chunks");
+                        let queued =
+                            cached
+                                .into_iter()
+                                .enumerate()
+                                .try_for_each(|(index, audio)| {
+                                    let chunk_text = synthesis.chunk_text(index);
+                                    queue_chunk(
+                                        &mut provider,
+                                        &mut timeline,
+                                        chunk_text,
+                                        audio,
+                                        playback.pause_ms(proofreading),
+                                    )
+                                });
+                        match queued {
+                            Ok(()) => {
+                                if let Some(id) = read_id.take() {
+                                    latency::first_audio(id);
+                                }
+                                synthesis.respond(Ok(()))
+                            }
+                            Err(e) => {
+                                tracing::error!(error = %e, "TTS speak failed");
+                                status::record_error(Some(provider.variant()), &e);
+                                synthesis.respond(Err(e));
+                                synthesis.cancel();
+                            }
+                        }
+                    }
+                }
+                TtsRequest::ChunkReady(ready) => {
+                    let Some((index, result)) = synthesis.accept(ready) else {
+                        continue;
+                    };
+                    let _span = read_id.map(|id| latency::span(id).entered());
+                    let result = result.and_then(|audio| {
+                        let chunk_text = synthesis.chunk_text(index);
+                        let pause_ms = playback.pause_ms(proofreading);
+                        queue_chunk(&mut provider, &mut timeline, chunk_text, audio, pause_ms)
+                    });
+                    match result {
+                        Ok(()) => {
+                            if index == 0 {
+                                if let Some(id) = read_id.take() {
+                                    latency::first_audio(id);
+                                }
+                            }
+                            synthesis.respond(Ok(()))
+                        }
+                        Err(e) if index == 0 => {
+                            let from = provider.variant();
+                            status::record_error(Some(from), &e);
+                            match TtsProviderImpl::load_fallback(
+                                &mut fallbacks,
+                                &config_snapshot,
+                                from,
+                                &e,
+                            ) {
+                                Some(mut next) => {
+                                    playback.apply(&mut next, config_snapshot.calibrated_speed);
+                                    let _ = provider.stop();
+                                    provider = next;
+                                    set_active_status(&provider, &config_snapshot);
+                                    let synthesizer = read_synthesizer(
+                                        &provider,
+                                        read_markup.clone(),
+                                        proofreading,
+                                        &fallbacks,
+                                        config_snapshot.voice_language.as_deref(),
+                                    );
+                                    synthesis.retry(synthesizer, worker_tx.clone());
+                                }
+                                None => {
+                                    tracing::error!(error = %e, "TTS speak failed");
+                                    synthesis.respond(Err(e));
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            tracing::error!(index, error = %e, "TTS chunk failed, ending playback early");
+                            status::record_error(Some(provider.variant()), &e);
+                            synthesis.cancel();
+                        }
                     }
-                    let _ = resp.send(result);
                 }
                 TtsRequest::Stop => {
+                    synthesis.cancel();
                     let _ = provider.stop();
+                    timeline.reset("");
+                    queue.clear();
                 }
                 TtsRequest::TogglePause(resp) => {
                     let _ = resp.send(provider.toggle_pause());
                 }
                 TtsRequest::GetStatus(resp) => {
-                    let _ = resp.send(provider.get_status());
+                    // Between chunks the sink may be drained while synthesis is still running.
+                    let (playing, paused) = provider.get_status();
+                    let _ = resp.send((playing || synthesis.is_pending(), paused));
                 }
                 TtsRequest::Seek(offset_ms, resp) => {
                     let _ = resp.send(provider.seek(offset_ms));
                 }
+                TtsRequest::SeekTo(position_ms, resp) => {
+                    let _ = resp.send(provider.seek_to(position_ms));
+                }
+                TtsRequest::SeekPercent(percent, resp) => {
+                    let (_, total_ms) = provider.get_position();
+                    let _ = resp
+                        .send(provider.seek_to(audio_player::percent_position(total_ms, percent)));
+                }
+                TtsRequest::NextSegment(unit, resp) => {
+                    let (position_ms, _) = provider.get_position();
+                    let target = timeline.next_segment(position_ms, unit);
+                    let _ = resp.send(seek_to_segment(&mut provider, target, unit));
+                }
+                TtsRequest::PrevSegment(unit, resp) => {
+                    let (position_ms, _) = provider.get_position();
+                    let target = timeline.prev_segment(position_ms, unit);
+                    let _ = resp.send(seek_to_segment(&mut provider, target, unit));
+                }
                 TtsRequest::GetPosition(resp) => {
                     let _ = resp.send(provider.get_position());
                 }
                 TtsRequest::SetVolume(volume_percent, resp) => {
-                    current_volume_percent = volume_percent;
+                    playback.volume_percent = volume_percent;
                     provider.set_volume(volume_percent);
                     let _ = resp.send(Ok(()));
                 }
                 TtsRequest::SetSpeed(speed, resp) => {
+                    playback.speed = Some(speed);
+                    playback.skimming = false;
                     provider.set_speed(speed);
                     let _ = resp.send(Ok(()));
                 }
+                TtsRequest::GetSpeed(resp) => {
+                    let _ = resp.send(playback.speed);
+                }
+                TtsRequest::RestoreSpeed(speed) => {
+                    playback.speed = speed;
+                    playback.skimming = false;
+                    let speed = playback
+                        .active_speed(config_snapshot.calibrated_speed)
+                        .unwrap_or(1.0);
+                    provider.set_speed(speed);
+                }
+                TtsRequest::ToggleSkim(resp) => {
+                    playback.skimming = !playback.skimming;
+                    let speed = playback
+                        .active_speed(config_snapshot.calibrated_speed)
+                        .unwrap_or(1.0);
+                    tracing::debug!(skimming = playback.skimming, speed, "Toggled skim speed");
+                    provider.set_speed(speed);
+                    let _ = resp.send(Ok(playback.skimming));
+                }
+                TtsRequest::SetPitch(pitch, resp) => {
+                    playback.pitch = pitch;
+                    provider.set_pitch(pitch);
+                    let _ = resp.send(Ok(()));
+                }
                 TtsRequest::SwitchProvider(new_provider, resp) => {
+                    synthesis.cancel();
+                    synthesis.clear_cache();
                     let _ = provider.stop();
-                    let new_config = load_tts_config();
-                    match TtsProviderImpl::new(new_provider, &new_config) {
+                    timeline.reset("");
+                    let new_config = load_tts_config(None, None);
+                    match TtsProviderImpl::new(new_provider, &new_config, &mut fallbacks) {
                         Ok(mut new_provider) => {
-                            new_provider.set_volume(current_volume_percent);
+                            playback.apply(&mut new_provider, new_config.calibrated_speed);
                             provider = new_provider;
                             config_snapshot = new_config;
+                            set_active_status(&provider, &config_snapshot);
                             let _ = resp.send(Ok(()));
                         }
                         Err(e) => {
+                            status::record_error(Some(new_provider), &e);
                             let _ = resp.send(Err(e));
                         }
                     }
                 }
-                TtsRequest::Shutdown => {
+                TtsRequest::ReopenOutput => {
+                    provider.reopen_output();
+                }
+                TtsRequest::ReloadConfig => {
+                    let new_config = load_tts_config(None, None);
+                    if output::select(new_config.audio_output.clone()) {
+                        provider.check_output(true);
+                    }
+                    if new_config.pronunciations != config_snapshot.pronunciations
+                        || new_config.ssml != config_snapshot.ssml
+                    {
+                        synthesis.clear_cache();
+                    }
+                    let reloaded = PlaybackSettings {
+                        skimming: playback.skimming,
+                        ..new_config.playback
+                    };
+                    if reloaded != playback {
+                        playback = reloaded;
+                        playback.apply(&mut provider, new_config.calibrated_speed);
+                    }
+                    let reload = new_config.provider != provider.variant()
+                        || new_config.voice_differs(&config_snapshot);
+                    let idle = !synthesis.is_pending() && !provider.get_status().0;
+                    if reload && idle {
+                        tracing::info!(provider = ?new_config.provider, "Config changed, reloading provider");
+                        synthesis.clear_cache();
+                        match TtsProviderImpl::new(new_config.provider, &new_config, &mut fallbacks)
+                        {
+                            Ok(mut new_provider) => {
+                                playback.apply(&mut new_provider, new_config.calibrated_speed);
+                                provider = new_provider;
+                                config_snapshot = new_config;
+                                set_active_status(&provider, &config_snapshot);
+                            }
+                            Err(e) => {
+                                tracing::warn!(error = %e, "Reloading provider failed, keeping the current one");
+                                status::record_error(Some(new_config.provider), &e);
+                            }
+                        }
+                    } else {
+                        // The voice fields stay, so the next read still sees a pending change.
+                        config_snapshot.pronunciations = new_config.pronunciations;
+                        config_snapshot.ssml = new_config.ssml;
+                        config_snapshot.pipeline = new_config.pipeline;
+                        config_snapshot.playback = new_config.playback;
+                        config_snapshot.fallbacks = new_config.fallbacks;
+                        fallbacks.reset(provider.variant(), &config_snapshot.fallbacks);
+                    }
+                }
+                TtsRequest::GetTimeline(resp) => {
+                    let _ = resp.send(timeline.words());
+                }
+                TtsRequest::GetWaveform(buckets, resp) => {
+                    let (_, duration_ms) = provider.get_position();
+                    let _ = resp.send(TtsWaveform {
+                        peaks: provider.waveform(buckets),
+                        duration_ms,
+                    });
+                }
+                TtsRequest::SetProgressNotifier(notifier) => {
+                    timeline.set_notifier(notifier);
+                }
+                TtsRequest::Enqueue(text, input_kind, source, resp) => {
+                    let id = queue.push(text, input_kind, source);
+                    let idle = !synthesis.is_pending() && !provider.get_status().0;
+                    if idle && queue.can_advance() {
+                        advance_queue(&mut queue, &worker_tx);
+                    }
+                    let _ = resp.send(Ok(id));
+                }
+                TtsRequest::QueueList(resp) => {
+                    let _ = resp.send(queue.items());
+                }
+                TtsRequest::QueueSkip(resp) => {
+                    synthesis.cancel();
+                    let _ = provider.stop();
+                    timeline.reset("");
+                    let started = queue.can_advance() && advance_queue(&mut queue, &worker_tx);
+                    let _ = resp.send(started);
+                }
+                TtsRequest::QueueClear(resp) => {
+                    let _ = resp.send(queue.clear_waiting());
+                }
+                TtsRequest::SetQueueNotifier(notifier) => {
+                    queue.set_notifier(notifier);
+                }
+                TtsRequest::SetFallbackNotifier(notifier) => {
+                    fallbacks.set_notifier(notifier);
+                }
+                TtsRequest::SetSleepTimer(minutes, resp) => {
+                    let _ = resp.send(Ok(sleep_timer.set(minutes, Instant::now())));
+                }
+                TtsRequest::CancelSleepTimer(resp) => {
+                    let _ = resp.send(sleep_timer.cancel());
+                }
+                TtsRequest::GetSleepTimer(resp) => {
+                    let _ = resp.send(sleep_timer.status(Instant::now()));
+                }
+                TtsRequest::Synthesizer(resp) => {
+                    let config = load_tts_config(None, None);
+                    let onboarding =
+                        onboarding_fallback(config.voice_language.as_deref(), fallbacks.notifier());
+                    let _ = resp.send(Ok(provider.synthesizer(config.markup(None), onboarding)));
+                }
+                TtsRequest::Shutdown(done) => {
+                    synthesis.cancel();
                     let _ = provider.stop();
+                    timeline.finish(false);
+                    let _ = done.send(());
                     break;
                 }
             }
@@ -0,0 +1,474 @@
+//! In-process Piper inference: runs the voice's ONNX model with onnxruntime (`ort`) instead of
+//! spawning the Piper CLI per chunk, so local voices work without the Python venv.
+//!
+//! Piper models take eSpeak NG phonemes. libespeak-ng and onnxruntime are loaded at runtime
+//! (libespeak-ng ships with most Linux distributions and with Piper itself; `ort` is built with
+//! `load-dynamic`, so nothing is downloaded at build time); when either or the model config is
+//! missing, callers fall back to the CLI. The last loaded model stays in memory, so only the first chunk after a
+//! voice change pays the load time. The session uses the execution provider chosen by `inference`
+//! (from `inference_backend`); onnxruntime falls back to CPU when it cannot register it.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use ort::execution_providers::{
+    CPUExecutionProvider, CUDAExecutionProvider, CoreMLExecutionProvider,
+    DirectMLExecutionProvider, ExecutionProviderDispatch,
+};
+use ort::session::Session;
+use ort::value::Tensor;
+use serde::Deserialize;
+use tracing::{debug, info};
+
+use super::{inference, TTSError};
+
+const PAD: &str = "_";
+const BOS: &str = "^";
+const EOS: &str = "$";
+
+/// The parts of a model's `.onnx.json` used for inference.
+#[derive(Debug, Deserialize)]
+struct VoiceConfig {
+    audio: AudioConfig,
+    espeak: EspeakConfig,
+    #[serde(default)]
+    inference: InferenceConfig,
+    phoneme_id_map: HashMap<String, Vec<i64>>,
+    #[serde(default = "one")]
+    num_speakers: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct AudioConfig {
+    sample_rate: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct EspeakConfig {
+    voice: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct InferenceConfig {
+    noise_scale: f32,
+    length_scale: f32,
+    noise_w: f32,
+}
+
+impl Default for InferenceConfig {
+    fn default() -> Self {
+        Self {
+            noise_scale: 0.667,
+            length_scale: 1.0,
+            noise_w: 0.8,
+        }
+    }
+}
+
+fn one() -> u32 {
+    1
+}
+
+/// A loaded Piper model.
+pub(super) struct OnnxVoice {
+    session: Session,
+    config: VoiceConfig,
+}
+
+/// The most recently loaded model (path without `.onnx`) and its execution provider.
+type LoadedVoice = (PathBuf, &'static str, Arc<Mutex<OnnxVoice>>);
+static LOADED: Mutex<Option<LoadedVoice>> = Mutex::new(None);
+
+fn tts_error(context: &str) -> impl Fn(ort::Error) -> TTSError + '_ {
+    move |e| TTSError::ProcessError(format!("{context}: {e}"))
+}
+
+/// Whether in-process inference can run (libespeak-ng and onnxruntime found).
+pub(super) fn is_available() -> bool {
+    espeak::library().is_ok() && runtime::init().is_ok()
+}
+
+/// Loads the model at `model_path` (without `.onnx`), reusing it when it is already loaded with
+/// the current execution provider.
+pub(super) fn voice(model_path: &Path) -> Result<Arc<Mutex<OnnxVoice>>, TTSError> {
+    let provider = inference::onnx_execution_provider();
+    let mut loaded = LOADED
+        .lock()
+        .map_err(|_| TTSError::ProcessError("ONNX model cache lock poisoned".into()))?;
+    if let Some((path, loaded_provider, voice)) = loaded.as_ref() {
+        if path == model_path && *loaded_provider == provider {
+            return Ok(Arc::clone(voice));
+        }
+    }
+    // Free the previous model before loading the next one.
+    *loaded = None;
+    let voice = Arc::new(Mutex::new(OnnxVoice::load(model_path, provider)?));
+    *loaded = Some((model_path.to_path_buf(), provider, Arc::clone(&voice)));
+    Ok(voice)
+}
+
+/// The ort execution provider for an onnxruntime provider name; CPU for unknown names.
+fn execution_provider(provider: &str) -> ExecutionProviderDispatch {
+    match provider {
+        "CUDAExecutionProvider" => CUDAExecutionProvider::default().build(),
+        "DmlExecutionProvider" => DirectMLExecutionProvider::default().build(),
+        "CoreMLExecutionProvider" => CoreMLExecutionProvider::default().build(),
+        _ => CPUExecutionProvider::default().build(),
+    }
+}
+
+impl OnnxVoice {
+    fn load(model_path: &Path, provider: &str) -> Result<Self, TTSError> {
+        espeak::library().map_err(|e| TTSError::ProcessError(e.clone()))?;
+        runtime::init().map_err(|e| TTSError::ProcessError(e.clone()))?;
+        let onnx = model_path.with_extension("onnx");
+        let config_path = model_path.with_extension("onnx.json");
+        let config: VoiceConfig = std::fs::read_to_string(&config_path)
+            .map_err(|e| format!("Failed to read {}: {e}", config_path.display()))
+            .and_then(|data| {
+                serde_json::from_str(&data)
+                    .map_err(|e| format!("Invalid voice config {}: {e}", config_path.display()))
+            })
+            .map_err(TTSError::ProcessError)?;
+        let session = Session::builder()
+            .and_then(|builder| builder.with_execution_providers([execution_provider(provider)]))
+            .and_then(|builder| builder.commit_from_file(&onnx))
+            .map_err(tts_error("Failed to load ONNX model"))?;
+        info!(
+            model = %onnx.display(),
+            voice = %config.espeak.voice,
+            provider,
+            "Loaded Piper model in process"
+        );
+        Ok(Self { session, config })
+    }
+
+    /// Synthesizes `text`; returns mono samples and their sample rate.
+    pub(super) fn synthesize(&mut self, text: &str) -> Result<(Vec<f32>, u32), TTSError> {
+        let phonemes = espeak::phonemize(text, &self.config.espeak.voice)?;
+        let ids = phoneme_ids(&phonemes, &self.config.phoneme_id_map);
+        debug!(phonemes = %phonemes, ids = ids.len(), "Piper (in process): phonemized chunk");
+        let length = ids.len() as i64;
+        let inference = &self.config.inference;
+        let scales = vec![
+            inference.noise_scale,
+            inference.length_scale,
+            inference.noise_w,
+        ];
+        let input_error = tts_error("Invalid ONNX input");
+        let input = Tensor::from_array(([1usize, ids.len()], ids)).map_err(&input_error)?;
+        let lengths = Tensor::from_array(([1usize], vec![length])).map_err(&input_error)?;
+        let scales = Tensor::from_array(([3usize], scales)).map_err(&input_error)?;
+        let mut inputs = ort::inputs![
+            "input" => input,
+            "input_lengths" => lengths,
+            "scales" => scales,
+        ];
+        // Multi-speaker models need a speaker id; the first speaker is used.
+        if self.config.num_speakers > 1 {
+            let sid = Tensor::from_array(([1usize], vec![0i64])).map_err(&input_error)?;
+            inputs.push(("sid".into(), sid.into()));
+        }
+        let outputs = self
+            .session
+            .run(inputs)
+            .map_err(tts_error("ONNX inference failed"))?;
+        let (_, samples) = outputs["output"]
+            .try_extract_tensor::<f32>()
+            .map_err(tts_error("Unexpected ONNX output"))?;
+        Ok((normalize(samples), self.config.audio.sample_rate))
+    }
+}
+
+/// Maps phonemes to model ids the way Piper does: BOS, then each known phoneme followed by PAD,
+/// then EOS. Phonemes the model does not know are skipped.
+fn phoneme_ids(phonemes: &str, map: &HashMap<String, Vec<i64>>) -> Vec<i64> {
+    let id = |symbol: &str| map.get(symbol).cloned().unwrap_or_default();
+    let pad = id(PAD);
+    let mut ids = id(BOS);
+    ids.extend(&pad);
+    let mut buf = [0u8; 4];
+    for phoneme in phonemes.chars() {
+        if let Some(phoneme_ids) = map.get(phoneme.encode_utf8(&mut buf) as &str) {
+            ids.extend(phoneme_ids);
+            ids.extend(&pad);
+        }
+    }
+    ids.extend(id(EOS));
+    ids
+}
+
+/// Scales the output to full range, as the Piper CLI does before writing 16-bit samples.
+fn normalize(samples: &[f32]) -> Vec<f32> {
+    let peak = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+    let scale = 1.0 / peak.max(0.01);
+    samples
+        .iter()
+        .map(|s| (s * scale).clamp(-1.0, 1.0))
+        .collect()
+}
+
+/// Finds an onnxruntime library new enough for `ort` and hands it to `ort`, which would panic on a
+/// missing or too old one.
+mod runtime {
+    use std::ffi::CStr;
+    use std::path::{Path, PathBuf};
+    use std::sync::OnceLock;
+
+    use libloading::{Library, Symbol};
+    use tracing::debug;
+
+    #[cfg(target_os = "linux")]
+    const LIBRARY_NAMES: &[&str] = &["libonnxruntime.so.1", "libonnxruntime.so"];
+    #[cfg(target_os = "macos")]
+    const LIBRARY_NAMES: &[&str] = &["libonnxruntime.dylib"];
+    #[cfg(target_os = "windows")]
+    const LIBRARY_NAMES: &[&str] = &["onnxruntime.dll"];
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    const LIBRARY_NAMES: &[&str] = &[];
+
+    type GetApiBase = unsafe extern "system" fn() -> *const ort::sys::OrtApiBase;
+
+    static RUNTIME: OnceLock<Result<(), String>> = OnceLock::new();
+
+    /// Loads onnxruntime once per process.
+    pub(super) fn init() -> Result<(), &'static String> {
+        RUNTIME.get_or_init(load).as_ref().map(|_| ())
+    }
+
+    /// `ORT_DYLIB_PATH`, then the library next to the executable, then the system search path.
+    fn candidates() -> Vec<PathBuf> {
+        let exe_dir = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(PathBuf::from));
+        std::env::var_os("ORT_DYLIB_PATH")
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .into_iter()
+            .chain(LIBRARY_NAMES.iter().filter_map(|name| {
+                exe_dir
+                    .as_ref()
+                    .map(|dir| dir.join(name))
+                    .filter(|path| path.is_file())
+            }))
+            .chain(LIBRARY_NAMES.iter().map(PathBuf::from))
+            .collect()
+    }
+
+    fn load() -> Result<(), String> {
+        for path in candidates() {
+            match minor_version(&path) {
+                Ok(minor) if minor >= ort::MINOR_VERSION => {
+                    ort::init_from(path.display().to_string())
+                        .commit()
+                        .map_err(|e| format!("onnxruntime: {e}"))?;
+                    debug!(path = %path.display(), "Loaded onnxruntime");
+                    return Ok(());
+                }
+                Ok(minor) => debug!(path = %path.display(), minor, "onnxruntime is too old"),
+                Err(e) => debug!(path = %path.display(), error = %e, "onnxruntime not loaded"),
+            }
+        }
+        Err(format!(
+            "onnxruntime 1.{} or later not found",
+            ort::MINOR_VERSION
+        ))
+    }
+
+    /// Minor version of the onnxruntime library at `path` ("1.22.0" is 22).
+    fn minor_version(path: &Path) -> Result<u32, String> {
+        // SAFETY: onnxruntime has no library constructors with preconditions.
+        let library = unsafe { Library::new(path) }.map_err(|e| e.to_string())?;
+        // SAFETY: the signature matches OrtGetApiBase, which returns a pointer to a static struct;
+        // its version string is static too and copied before the library is closed.
+        let version = unsafe {
+            let get_api_base: Symbol<GetApiBase> =
+                library.get(b"OrtGetApiBase\0").map_err(|e| e.to_string())?;
+            let base = get_api_base();
+            if base.is_null() {
+                return Err("OrtGetApiBase returned null".to_string());
+            }
+            CStr::from_ptr(((*base).GetVersionString)())
+                .to_string_lossy()
+                .into_owned()
+        };
+        version
+            .split('.')
+            .nth(1)
+            .and_then(|minor| minor.parse().ok())
+            .ok_or_else(|| format!("unexpected version {version:?}"))
+    }
+}
+
+/// Minimal runtime binding to libespeak-ng for phonemization.
+mod espeak {
+    use std::ffi::{c_char, c_int, c_void, CStr, CString};
+    use std::sync::{Mutex, OnceLock};
+
+    use libloading::{Library, Symbol};
+
+    use super::{inference, TTSError};
+
+    #[cfg(target_os = "linux")]
+    const LIBRARY_NAMES: &[&str] = &["libespeak-ng.so.1", "libespeak-ng.so"];
+    #[cfg(target_os = "macos")]
+    const LIBRARY_NAMES: &[&str] = &["libespeak-ng.1.dylib", "libespeak-ng.dylib"];
+    #[cfg(target_os = "windows")]
+    const LIBRARY_NAMES: &[&str] = &["espeak-ng.dll", "libespeak-ng.dll"];
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    const LIBRARY_NAMES: &[&str] = &[];
+
+    /// `AUDIO_OUTPUT_SYNCHRONOUS`: no audio device is opened.
+    const AUDIO_OUTPUT_SYNCHRONOUS: c_int = 2;
+    const CHARS_UTF8: c_int = 1;
+    /// `espeakPHONEMES_IPA`.
+    const PHONEMES_IPA: c_int = 0x02;
+    const TO_PHONEMES: &[u8] = b"espeak_TextToPhonemesWithTerminator\0";
+
+    type Initialize = unsafe extern "C" fn(c_int, c_int, *const c_char, c_int) -> c_int;
+    type SetVoiceByName = unsafe extern "C" fn(*const c_char) -> c_int;
+    type TextToPhonemesWithTerminator =
+        unsafe extern "C" fn(*mut *const c_void, c_int, c_int, *mut c_int) -> *const c_char;
+
+    /// Clause terminators reported by `espeak_TextToPhonemesWithTerminator` (the pause and
+    /// intonation bits of espeak-ng's `CLAUSE_*` values, as in piper-phonemize).
+    const TERMINATOR_MASK: c_int = 0x000F_FFFF;
+    const CLAUSE_TYPE_CLAUSE: c_int = 0x0004_0000;
+    const CLAUSE_TYPE_SENTENCE: c_int = 0x0008_0000;
+    const INTONATION_COMMA: c_int = 0x0000_1000;
+    const INTONATION_QUESTION: c_int = 0x0000_2000;
+    const INTONATION_EXCLAMATION: c_int = 0x0000_3000;
+    const CLAUSE_PERIOD: c_int = 40 | CLAUSE_TYPE_SENTENCE;
+    const CLAUSE_QUESTION: c_int = 40 | INTONATION_QUESTION | CLAUSE_TYPE_SENTENCE;
+    const CLAUSE_EXCLAMATION: c_int = 45 | INTONATION_EXCLAMATION | CLAUSE_TYPE_SENTENCE;
+    const CLAUSE_COMMA: c_int = 20 | INTONATION_COMMA | CLAUSE_TYPE_CLAUSE;
+    const CLAUSE_COLON: c_int = 30 | CLAUSE_TYPE_CLAUSE;
+    const CLAUSE_SEMICOLON: c_int = 30 | INTONATION_COMMA | CLAUSE_TYPE_CLAUSE;
+
+    /// Punctuation that ended a clause, re-appended to its phonemes so the model keeps the pause
+    /// and intonation.
+    pub(super) fn terminator_punctuation(terminator: c_int) -> Option<char> {
+        match terminator & TERMINATOR_MASK {
+            CLAUSE_PERIOD => Some('.'),
+            CLAUSE_QUESTION => Some('?'),
+            CLAUSE_EXCLAMATION => Some('!'),
+            CLAUSE_COMMA => Some(','),
+            CLAUSE_COLON => Some(':'),
+            CLAUSE_SEMICOLON => Some(';'),
+            _ => None,
+        }
+    }
+
+    pub(super) struct Espeak {
+        library: Library,
+        /// The library keeps global state (current voice, output buffer).
+        lock: Mutex<()>,
+    }
+
+    static ESPEAK: OnceLock<Result<Espeak, String>> = OnceLock::new();
+
+    /// The loaded and initialized library (loaded once per process).
+    pub(super) fn library() -> Result<&'static Espeak, &'static String> {
+        ESPEAK.get_or_init(load).as_ref()
+    }
+
+    fn load() -> Result<Espeak, String> {
+        let library = LIBRARY_NAMES
+            .iter()
+            // SAFETY: libespeak-ng has no library constructors with preconditions.
+            .find_map(|name| unsafe { Library::new(name) }.ok())
+            .ok_or_else(|| "libespeak-ng not found".to_string())?;
+        // SAFETY: the signature matches espeak_Initialize; a null path uses the default data.
+        let sample_rate = unsafe {
+            let initialize: Symbol<Initialize> = library
+                .get(b"espeak_Initialize\0")
+                .map_err(|e| format!("libespeak-ng: {e}"))?;
+            initialize(AUDIO_OUTPUT_SYNCHRONOUS, 0, std::ptr::null(), 0)
+        };
+        if sample_rate <= 0 {
+            return Err("libespeak-ng failed to initialize (data not found?)".to_string());
+        }
+        // SAFETY: only checks that the symbol exists (espeak-ng 1.52 and later).
+        if unsafe { library.get::<TextToPhonemesWithTerminator>(TO_PHONEMES) }.is_err() {
+            return Err("libespeak-ng is too old (1.52 or later is needed)".to_string());
+        }
+        Ok(Espeak {
+            library,
+            lock: Mutex::new(()),
+        })
+    }
+
+    /// IPA phonemes of `text` for the eSpeak voice, one clause after another. The library drops
+    /// clause punctuation, so each clause gets its terminator back, as Piper does.
+    pub(super) fn phonemize(text: &str, voice: &str) -> Result<String, TTSError> {
+        let espeak = library().map_err(|e| TTSError::ProcessError(e.clone()))?;
+        let error = |e: &dyn std::fmt::Display| TTSError::ProcessError(format!("eSpeak: {e}"));
+        let text = CString::new(text.replace('\0', " ")).map_err(|e| error(&e))?;
+        let voice = CString::new(voice).map_err(|e| error(&e))?;
+        let _guard = espeak
+            .lock
+            .lock()
+            .map_err(|_| error(&"library lock poisoned"))?;
+        let mut clauses = Vec::new();
+        // SAFETY: the signatures match the espeak-ng API; `text` outlives the loop and the library
+        // advances `cursor` within it, setting it to null at the end. The returned phoneme buffer
+        // is copied before the next call.
+        unsafe {
+            let set_voice: Symbol<SetVoiceByName> = espeak
+                .library
+                .get(b"espeak_SetVoiceByName\0")
+                .map_err(|e| error(&e))?;
+            let to_phonemes: Symbol<TextToPhonemesWithTerminator> =
+                espeak.library.get(TO_PHONEMES).map_err(|e| error(&e))?;
+            if set_voice(voice.as_ptr()) != 0 {
+                return Err(error(&format!("unknown voice {voice:?}")));
+            }
+            let mut cursor = text.as_ptr().cast::<c_void>();
+            while !cursor.is_null() {
+                let mut terminator: c_int = 0;
+                let phonemes = to_phonemes(&mut cursor, CHARS_UTF8, PHONEMES_IPA, &mut terminator);
+                if phonemes.is_null() {
+                    break;
+                }
+                let mut clause = CStr::from_ptr(phonemes)
+                    .to_string_lossy()
+                    .trim()
+                    .to_string();
+                if clause.is_empty() {
+                    continue;
+                }
+                clause.extend(terminator_punctuation(terminator));
+                clauses.push(clause);
+            }
+        }
+        Ok(clauses.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maps_phonemes_with_padding_and_skips_unknown() {
+        let map = HashMap::from([
+            ("_".to_string(), vec![0]),
+            ("^".to_string(), vec![1]),
+            ("$".to_string(), vec![2]),
+            ("h".to_string(), vec![20]),
+            ("ə".to_string(), vec![59]),
+        ]);
+        assert_eq!(phoneme_ids("hə~", &map), [1, 0, 20, 0, 59, 0, 2]);
+    }
+
+    #[test]
+    fn test_clause_terminators_keep_their_punctuation() {
+        // Values espeak-ng reports for "?", "," and "." (with the clause pause bits).
+        assert_eq!(espeak::terminator_punctuation(0x0008_2028), Some('?'));
+        assert_eq!(espeak::terminator_punctuation(0x0004_1014), Some(','));
+        assert_eq!(espeak::terminator_punctuation(0x1008_0028), Some('.'));
+        assert_eq!(espeak::terminator_punctuation(0), None);
+    }
+}
@@ -0,0 +1,125 @@
+//! Audio output devices: the list for the settings UI (`list_audio_outputs`) and the device the
+//! player opens, `selected_audio_output` or the system default when unset or unplugged.
+//!
+//! rodio's stream does not report a device that went away, so `AudioPlayer::check_output`
+//! compares the device it opened with the one it would open now, at most every `CHECK_INTERVAL`,
+//! and rebuilds the stream when they differ: headphones unplugged (the default changed) or the
+//! selected device plugged back in.
+
+use std::sync::RwLock;
+use std::time::Duration;
+
+use rodio::cpal::traits::HostTrait;
+use rodio::{Device, DeviceTrait, OutputStream, OutputStreamHandle};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use super::TTSError;
+
+/// How often the player looks for a changed output device while it plays.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioOutput {
+    pub name: String,
+    pub is_default: bool,
+    /// Whether this is `selected_audio_output`.
+    pub selected: bool,
+}
+
+static SELECTED: RwLock<Option<String>> = RwLock::new(None);
+
+/// Sets the device the player should use (`None` = system default). Returns whether it changed.
+pub fn select(name: Option<String>) -> bool {
+    let name = name.filter(|n| !n.trim().is_empty());
+    match SELECTED.write() {
+        Ok(mut selected) if *selected != name => {
+            info!(output = ?name, "Audio output selected");
+            *selected = name;
+            true
+        }
+        _ => false,
+    }
+}
+
+fn selected() -> Option<String> {
+    SELECTED.read().ok().and_then(|s| s.clone())
+}
+
+fn default_device() -> Option<(Device, String)> {
+    let device = rodio::cpal::default_host().default_output_device()?;
+    let name = device.name().ok()?;
+    Some((device, name))
+}
+
+fn named_device(name: &str) -> Option<Device> {
+    rodio::cpal::default_host()
+        .output_devices()
+        .ok()?
+        .find(|d| d.name().is_ok_and(|n| n == name))
+}
+
+/// Output devices, as named by the OS.
+pub fn list() -> Vec<AudioOutput> {
+    let default_name = default_device().map(|(_, name)| name);
+    let selected = selected();
+    let devices = match rodio::cpal::default_host().output_devices() {
+        Ok(devices) => devices,
+        Err(e) => {
+            warn!(error = %e, "Failed to list audio outputs");
+            return Vec::new();
+        }
+    };
+    devices
+        .filter_map(|d| d.name().ok())
+        .map(|name| AudioOutput {
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            selected: selected.as_deref() == Some(name.as_str()),
+            name,
+        })
+        .collect()
+}
+
+/// The device to open and its name: the selected one while it is present, else the default.
+fn target() -> Option<(Device, String)> {
+    if let Some(name) = selected() {
+        if let Some(device) = named_device(&name) {
+            return Some((device, name));
+        }
+    }
+    default_device()
+}
+
+/// Name of the device `open` would use now.
+pub(super) fn target_name() -> Option<String> {
+    target().map(|(_, name)| name)
+}
+
+/// Opens the target device, falling back to whatever rodio can open. Returns the stream, its
+/// handle and the target's name, also after a fallback, so a device that fails to open is not
+/// retried until the target changes.
+pub(super) fn open() -> Result<(OutputStream, OutputStreamHandle, Option<String>), TTSError> {
+    let target = target();
+    if let Some((device, name)) = &target {
+        match OutputStream::try_from_device(device) {
+            Ok((stream, handle)) => return Ok((stream, handle, Some(name.clone()))),
+            Err(e) => warn!(output = %name, error = %e, "Failed to open audio output, trying any"),
+        }
+    }
+    let (stream, handle) = OutputStream::try_default()
+        .map_err(|e| TTSError::AudioError(format!("Failed to open audio output: {e}")))?;
+    Ok((stream, handle, target.map(|(_, name)| name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_reports_changes_and_treats_blank_as_default() {
+        assert!(select(Some("USB Headset".to_string())));
+        assert!(!select(Some("USB Headset".to_string())));
+        assert!(select(Some("  ".to_string())));
+        assert_eq!(selected(), None);
+    }
+}
@@ -1,27 +1,40 @@
-//! Piper TTS provider: runs the Piper binary and plays audio via rodio.
+//! Piper TTS provider: runs Piper models in process (see `onnx`) or through the Piper binary, and
+//! plays audio via rodio. The binary is the fallback when in-process inference is off
+//! (`in_process_inference` flag) or cannot load the model.
 
+use crate::features::{self, Flag};
+use crate::janitor;
 use crate::paths;
+use crate::text::ssml::Markup;
 use std::env;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::time::Duration;
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
 use tracing::{debug, error, info, warn};
 
-use super::audio_player::AudioPlayer;
+use super::audio_player::{AudioPlayer, SeekResult};
+use super::inference;
+use super::onnx;
+use super::priority;
+use super::stream::{ChunkAudio, SynthesizeFn};
 use super::TTSError;
 
+/// Text synthesized when validating a freshly downloaded model.
+const SMOKE_TEST_TEXT: &str = "test";
+
+/// Sample rate of Piper's raw output (medium/low quality voices).
+const PIPER_SAMPLE_RATE: u32 = 22050;
+
+/// Small voice bundled with the app (see `paths::get_onboarding_voice_dir`), so the first read
+/// works before any voice is downloaded and when cloud voices are unreachable.
+pub const ONBOARDING_VOICE: &str = "en_US-danny-low";
+
 fn get_voices_base_dir() -> PathBuf {
-    if let Some(home) = dirs::home_dir() {
-        home.join(".local")
-            .join("share")
-            .join("insight-reader")
-            .join("voices")
-    } else {
-        PathBuf::from("/tmp")
-    }
+    paths::get_voices_dir().unwrap_or_else(|_| PathBuf::from("/tmp"))
 }
 
 /// Piper TTS provider using the local Piper binary and ONNX models.
@@ -38,7 +51,7 @@ impl PiperTTSProvider {
         let piper_bin = Self::find_piper_binary();
         let model_path = Self::find_any_model(selected_voice)?;
 
-        if !piper_bin.is_file() {
+        if !piper_bin.is_file() && !in_process() {
             error!(?piper_bin, "Piper binary not found");
             return Err(TTSError::ProcessError(format!(
                 "Piper binary not found at {}",
@@ -56,7 +69,7 @@ impl PiperTTSProvider {
         info!("Initializing Piper TTS provider");
         debug!(?piper_bin, ?model_path, "Piper configuration");
 
-        let player = AudioPlayer::new(22050)?;
+        let player = AudioPlayer::new(PIPER_SAMPLE_RATE)?;
         Ok(Self {
             piper_bin,
             model_path,
@@ -64,44 +77,83 @@ impl PiperTTSProvider {
         })
     }
 
-    /// Speak the given text. Stops any current playback first.
-    pub fn speak(&mut self, text: &str) -> Result<(), TTSError> {
-        let text = text.trim();
-        if text.is_empty() {
-            warn!("Empty text provided to piper, skipping synthesis");
-            return Err(TTSError::ProcessError(
-                "Cannot synthesize empty text".into(),
-            ));
+    /// Provider speaking with the bundled onboarding voice.
+    pub fn onboarding() -> Result<Self, TTSError> {
+        let model_path = onboarding_model().ok_or_else(|| {
+            TTSError::ProcessError("Onboarding voice is not bundled with this build".into())
+        })?;
+        let piper_bin = Self::find_piper_binary();
+        if !piper_bin.is_file() && !in_process() {
+            return Err(TTSError::ProcessError(format!(
+                "Piper binary not found at {}",
+                piper_bin.display()
+            )));
         }
+        info!("Initializing Piper TTS provider with the onboarding voice");
+        let player = AudioPlayer::new(PIPER_SAMPLE_RATE)?;
+        Ok(Self {
+            piper_bin,
+            model_path,
+            player,
+        })
+    }
 
-        debug!(
-            chars = text.len(),
-            text_preview = %text.chars().take(50).collect::<String>(),
-            "Piper: synthesizing speech"
-        );
-
-        self.player.stop()?;
-
-        let model_arg = self.model_path.to_str().unwrap_or("");
-        debug!(
-            piper_bin = %self.piper_bin.display(),
-            model_path = %model_arg,
-            "Executing piper command"
-        );
+    /// Synthesizer for the bundled onboarding voice, or `None` when it (or Piper) is missing.
+    pub fn onboarding_synthesizer(markup: Markup) -> Option<SynthesizeFn> {
+        let model_path = onboarding_model()?;
+        let piper_bin = Self::find_piper_binary();
+        (piper_bin.is_file() || in_process())
+            .then(|| Self::model_synthesizer(piper_bin, model_path, markup))
+    }
 
-        #[cfg(target_os = "windows")]
-        let audio_data = self.run_piper_windows(text, model_arg)?;
+    /// Returns a function that synthesizes text with this voice (runs on the synthesis thread).
+    /// Piper does not report phoneme durations, so word timing is estimated.
+    pub fn synthesizer(&self, markup: Markup) -> SynthesizeFn {
+        Self::model_synthesizer(self.piper_bin.clone(), self.model_path.clone(), markup)
+    }
 
-        #[cfg(not(target_os = "windows"))]
-        let audio_data = self.run_piper_unix(text, model_arg)?;
+    fn model_synthesizer(piper_bin: PathBuf, model_path: PathBuf, markup: Markup) -> SynthesizeFn {
+        Box::new(move |text: &str| {
+            let model_arg = model_path.to_str().unwrap_or("");
+            debug!(
+                chars = text.len(),
+                text_preview = %text.chars().take(50).collect::<String>(),
+                "Piper: synthesizing chunk"
+            );
+            // No SSML: words with a phoneme override are spoken as their replacement text.
+            let spoken = markup.respell(text);
+            let (audio_data, sample_rate) = match Self::run_in_process(&model_path, &spoken) {
+                Some(Ok(audio)) => audio,
+                Some(Err(e)) if piper_bin.is_file() => {
+                    warn!(error = %e, "In-process Piper failed, using the Piper binary");
+                    (
+                        Self::run_piper(&piper_bin, &spoken, model_arg)?,
+                        PIPER_SAMPLE_RATE,
+                    )
+                }
+                Some(Err(e)) => return Err(e),
+                None => (
+                    Self::run_piper(&piper_bin, &spoken, model_arg)?,
+                    PIPER_SAMPLE_RATE,
+                ),
+            };
+            info!(
+                samples = audio_data.len(),
+                duration_sec = format!("{:.1}", audio_data.len() as f32 / sample_rate as f32),
+                "Piper: audio generated"
+            );
+            Ok(ChunkAudio::estimated(text, audio_data, sample_rate))
+        })
+    }
 
-        info!(
-            samples = audio_data.len(),
-            duration_sec = format!("{:.1}", audio_data.len() as f32 / 22050.0),
-            "Piper: audio generated"
-        );
+    /// Queue synthesized audio for playback.
+    pub fn append_audio(&mut self, audio_data: Vec<f32>, sample_rate: u32) -> Result<(), TTSError> {
+        self.player.append_audio(audio_data, sample_rate)
+    }
 
-        self.player.play_audio(audio_data)
+    /// Index of the queued chunk currently playing.
+    pub fn current_segment(&self) -> usize {
+        self.player.current_segment()
     }
 
     /// Stop current playback.
@@ -109,6 +161,11 @@ impl PiperTTSProvider {
         self.player.stop()
     }
 
+    /// Fade out and stop current playback.
+    pub fn fade_out(&mut self, duration: Duration) -> Result<(), TTSError> {
+        self.player.fade_out(duration)
+    }
+
     /// Toggle pause state. Returns the new paused status (true if paused, false if playing).
     pub fn toggle_pause(&mut self) -> Result<bool, TTSError> {
         self.player.toggle_pause()
@@ -119,17 +176,34 @@ impl PiperTTSProvider {
         self.player.get_status()
     }
 
-    /// Seek by the given offset in milliseconds. Returns (success, at_start, at_end).
-    pub fn seek(&mut self, offset_ms: i64) -> Result<(bool, bool, bool), TTSError> {
+    /// Seek by the given offset in milliseconds. Works while paused.
+    pub fn seek(&mut self, offset_ms: i64) -> Result<SeekResult, TTSError> {
         self.player.seek(offset_ms)
     }
 
+    pub fn seek_to(&mut self, position_ms: u64) -> Result<SeekResult, TTSError> {
+        self.player.seek_to(position_ms)
+    }
+
+    pub fn check_output(&mut self, force: bool) -> bool {
+        self.player.check_output(force)
+    }
+
+    pub fn reopen_output(&mut self) -> bool {
+        self.player.reopen_output()
+    }
+
     /// Get current playback position and total duration in milliseconds.
     /// Returns (current_ms, total_ms).
     pub fn get_position(&self) -> (u64, u64) {
         self.player.get_position()
     }
 
+    /// Peak envelope of the queued audio (see `AudioPlayer::waveform`).
+    pub fn waveform(&self, buckets: usize) -> Vec<f32> {
+        self.player.waveform(buckets)
+    }
+
     pub fn set_volume(&mut self, volume_percent: u8) {
         self.player.set_volume_percent(volume_percent);
     }
@@ -138,24 +212,123 @@ impl PiperTTSProvider {
         self.player.set_speed(speed);
     }
 
+    pub fn set_pitch(&mut self, ratio: f32) {
+        self.player.set_pitch(ratio);
+    }
+
+    /// Whether local voices can run: in process, or with a Piper binary (dev venv, app venv, PATH,
+    /// or the one bundled with the onboarding voice).
+    pub fn is_installed() -> bool {
+        in_process() || Self::find_piper_binary().is_file()
+    }
+
+    /// Synthesizes in process; `None` when in-process inference is off or unavailable.
+    fn run_in_process(model_path: &Path, text: &str) -> Option<Result<(Vec<f32>, u32), TTSError>> {
+        if !in_process() {
+            return None;
+        }
+        Some(onnx::voice(model_path).and_then(|voice| {
+            voice
+                .lock()
+                .map_err(|_| TTSError::ProcessError("ONNX model lock poisoned".into()))?
+                .synthesize(text)
+        }))
+    }
+
+    /// Runs a tiny synthesis with the given model (path without `.onnx`) to confirm Piper can load it.
+    /// Does not open an audio output; the generated samples are discarded.
+    pub fn smoke_test_model(model_path: &Path) -> Result<(), TTSError> {
+        let piper_bin = Self::find_piper_binary();
+        match Self::run_in_process(model_path, SMOKE_TEST_TEXT) {
+            Some(Ok((audio_data, _))) if audio_data.is_empty() => {
+                return Err(TTSError::ProcessError(
+                    "No audio data generated by piper".into(),
+                ));
+            }
+            Some(Ok(_)) => return Ok(()),
+            Some(Err(e)) if !piper_bin.is_file() => return Err(e),
+            Some(Err(e)) => {
+                warn!(error = %e, "In-process smoke test failed, trying the Piper binary")
+            }
+            None => {}
+        }
+        if !piper_bin.is_file() {
+            return Err(TTSError::ProcessError(format!(
+                "Piper binary not found at {}",
+                piper_bin.display()
+            )));
+        }
+        let model_arg = model_path.to_str().ok_or_else(|| {
+            TTSError::ProcessError(format!(
+                "Model path is not valid UTF-8: {}",
+                model_path.display()
+            ))
+        })?;
+
+        debug!(model_path = %model_arg, "Piper: running smoke test");
+
+        let audio_data = Self::run_piper(&piper_bin, SMOKE_TEST_TEXT, model_arg)?;
+
+        if audio_data.is_empty() {
+            return Err(TTSError::ProcessError(
+                "No audio data generated by piper".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Runs Piper with the configured accelerator. If Piper fails with an accelerator, the
+    /// accelerator is disabled for the session and the run is retried on CPU.
+    fn run_piper(piper_bin: &Path, text: &str, model_arg: &str) -> Result<Vec<f32>, TTSError> {
+        let accel_args = inference::piper_args();
+
+        #[cfg(target_os = "windows")]
+        let result = Self::run_piper_windows(piper_bin, text, model_arg, accel_args);
+        #[cfg(not(target_os = "windows"))]
+        let result = Self::run_piper_unix(piper_bin, text, model_arg, accel_args);
+
+        match result {
+            Err(e) if !accel_args.is_empty() => {
+                warn!(error = %e, "Piper failed with accelerator, falling back to CPU");
+                inference::mark_accelerator_failed();
+
+                #[cfg(target_os = "windows")]
+                return Self::run_piper_windows(piper_bin, text, model_arg, &[]);
+                #[cfg(not(target_os = "windows"))]
+                return Self::run_piper_unix(piper_bin, text, model_arg, &[]);
+            }
+            other => other,
+        }
+    }
+
     #[cfg(target_os = "windows")]
-    fn run_piper_windows(&self, text: &str, model_arg: &str) -> Result<Vec<f32>, TTSError> {
+    fn run_piper_windows(
+        piper_bin: &Path,
+        text: &str,
+        model_arg: &str,
+        accel_args: &[&str],
+    ) -> Result<Vec<f32>, TTSError> {
         use std::fs;
         use std::io::Write;
 
-        let temp_file = env::temp_dir().join("insight-reader-2-piper-output.wav");
+        // Piper writes over the empty file; it is removed when `temp_file` is dropped.
+        let temp_file = janitor::temp_file("piper-", ".wav")
+            .map_err(TTSError::ProcessError)?
+            .into_temp_path();
         let temp_file_str = temp_file.to_string_lossy().to_string();
 
         const CREATE_NO_WINDOW: u32 = 0x08000000;
-        let mut child = Command::new(&self.piper_bin)
+        let mut child = priority::command(piper_bin)
             .args(["--model", model_arg, "--output_file", &temp_file_str])
+            .args(accel_args)
             .env("PYTHONIOENCODING", "utf-8")
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .creation_flags(CREATE_NO_WINDOW)
+            .creation_flags(CREATE_NO_WINDOW | priority::windows_priority_class())
             .spawn()
             .map_err(|e| TTSError::ProcessError(format!("Failed to start piper: {e}")))?;
+        let _tracked = janitor::track_child(child.id(), "piper");
 
         if let Some(ref mut stdin) = child.stdin {
             stdin
@@ -168,7 +341,6 @@ impl PiperTTSProvider {
             .map_err(|e| TTSError::ProcessError(format!("Piper process failed: {e}")))?;
 
         if !output.status.success() {
-            let _ = fs::remove_file(&temp_file);
             return Err(TTSError::ProcessError(format!(
                 "Piper failed: {}",
                 String::from_utf8_lossy(&output.stderr).trim()
@@ -177,7 +349,6 @@ impl PiperTTSProvider {
 
         let wav_data = fs::read(&temp_file)
             .map_err(|e| TTSError::ProcessError(format!("Failed to read piper output: {e}")))?;
-        let _ = fs::remove_file(&temp_file);
 
         if wav_data.len() < 44 || &wav_data[0..4] != b"RIFF" {
             return Err(TTSError::ProcessError(
@@ -189,17 +360,24 @@ impl PiperTTSProvider {
     }
 
     #[cfg(not(target_os = "windows"))]
-    fn run_piper_unix(&self, text: &str, model_arg: &str) -> Result<Vec<f32>, TTSError> {
+    fn run_piper_unix(
+        piper_bin: &Path,
+        text: &str,
+        model_arg: &str,
+        accel_args: &[&str],
+    ) -> Result<Vec<f32>, TTSError> {
         use std::io::Write;
 
-        let mut child = Command::new(&self.piper_bin)
+        let mut child = priority::command(piper_bin)
             .args(["--model", model_arg, "--output_file", "-"])
+            .args(accel_args)
             .env("PYTHONIOENCODING", "utf-8")
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
             .map_err(|e| TTSError::ProcessError(format!("Failed to start piper: {e}")))?;
+        let _tracked = janitor::track_child(child.id(), "piper");
 
         if let Some(ref mut stdin) = child.stdin {
             stdin
@@ -227,7 +405,7 @@ impl PiperTTSProvider {
         Ok(AudioPlayer::pcm_to_f32(&output.stdout))
     }
 
-    fn find_piper_binary() -> PathBuf {
+    pub(super) fn find_piper_binary() -> PathBuf {
         #[cfg(target_os = "windows")]
         const VENV_BIN_DIR: &str = "Scripts";
         #[cfg(target_os = "windows")]
@@ -246,7 +424,7 @@ impl PiperTTSProvider {
             }
         }
 
-        // 2. Check production venv in ${HOME}/.insight-reader-2/venv
+        // 2. Check production venv (see paths::get_venv_dir)
         if let Ok(venv_dir) = paths::get_venv_dir() {
             let p = venv_dir.join(VENV_BIN_DIR).join(PIPER_BIN_NAME);
             if p.exists() {
@@ -288,14 +466,34 @@ impl PiperTTSProvider {
             }
         }
 
-        // 4. Default to production venv path (even if it doesn't exist yet)
+        // 4. Piper bundled with the onboarding voice (see scripts/fetch-onboarding-voice.sh)
+        if let Ok(dir) = paths::get_onboarding_voice_dir() {
+            let p = dir.join("piper").join(PIPER_BIN_NAME);
+            if p.exists() {
+                return p;
+            }
+        }
+
+        // 5. Default to production venv path (even if it doesn't exist yet)
         paths::get_venv_dir()
-            .unwrap_or_else(|_| PathBuf::from("/tmp/.insight-reader-2"))
+            .unwrap_or_else(|_| PathBuf::from("/tmp/insight-reader"))
             .join(VENV_BIN_DIR)
             .join(PIPER_BIN_NAME)
     }
 
     /// Find any installed Piper model. Prefers selected voice from config, else finds any available.
+    /// The model `new` would load (without `.onnx`), when one is installed.
+    pub(super) fn model_path(selected_voice: Option<String>) -> Option<PathBuf> {
+        Self::find_any_model(selected_voice)
+            .ok()
+            .filter(|path| model_with_extension(path).is_file())
+    }
+
+    /// Whether Piper runs in process (ONNX) instead of through the binary.
+    pub(super) fn runs_in_process() -> bool {
+        in_process()
+    }
+
     fn find_any_model(selected_voice: Option<String>) -> Result<PathBuf, TTSError> {
         let model_name = selected_voice
             .as_deref()
@@ -352,12 +550,30 @@ impl PiperTTSProvider {
             }
         }
 
+        if let Some(stem) = onboarding_model() {
+            info!("No Piper voice installed, using the onboarding voice");
+            return Ok(stem);
+        }
+
         Err(TTSError::ProcessError(
             "No Piper model (.onnx) found. Download a voice in Settings.".into(),
         ))
     }
 }
 
+/// Whether Piper models run in process (flag on and libespeak-ng available).
+fn in_process() -> bool {
+    features::is_enabled(Flag::InProcessInference) && onnx::is_available()
+}
+
+/// The bundled onboarding model (path without .onnx), when this build ships it.
+fn onboarding_model() -> Option<PathBuf> {
+    let stem = paths::get_onboarding_voice_dir()
+        .ok()?
+        .join(ONBOARDING_VOICE);
+    model_with_extension(&stem).is_file().then_some(stem)
+}
+
 fn model_with_extension(path: &Path) -> PathBuf {
     path.with_extension("onnx")
 }
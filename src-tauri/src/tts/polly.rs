@@ -1,24 +1,230 @@
 //! AWS Polly TTS provider using the official AWS SDK.
 
+use std::sync::Arc;
+use std::time::Duration;
+
 use aws_config::BehaviorVersion;
-use aws_sdk_polly::types::{Engine, OutputFormat, VoiceId};
+use aws_sdk_polly::types::{Engine, OutputFormat, SpeechMarkType, TextType, VoiceId};
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
-use super::audio_player::AudioPlayer;
+use super::audio_player::{AudioPlayer, SeekResult};
+use super::stream::{ChunkAudio, SynthesizeFn};
+use super::timeline::WordMark;
 use super::TTSError;
+use crate::config::FullConfig;
+use crate::text::ssml::{Markup, SsmlSupport};
+
+/// PCM sample rate requested from Polly.
+const SAMPLE_RATE: u32 = 16000;
+
+/// One line of Polly's speech-mark output (`{"time":6,"type":"word","start":0,"end":5,...}`).
+/// `start`/`end` are byte offsets into the request text.
+#[derive(Deserialize)]
+struct SpeechMark {
+    time: u64,
+    #[serde(rename = "type")]
+    kind: String,
+    start: usize,
+    end: usize,
+}
+
+fn parse_speech_marks(json_lines: &str) -> Vec<WordMark> {
+    json_lines
+        .lines()
+        .filter_map(|line| serde_json::from_str::<SpeechMark>(line).ok())
+        .filter(|mark| mark.kind == "word")
+        .map(|mark| WordMark {
+            start_ms: mark.time,
+            start: mark.start,
+            end: mark.end,
+        })
+        .collect()
+}
+
+const CREDENTIALS_ERROR_MSG: &str = "AWS credentials not found. Please configure credentials via:\n  - Settings (stored in the system keychain)\n  - Environment variables: AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY\n  - Or credentials file: ~/.aws/credentials";
+
+/// Engine used when `polly_engine` is unset or unknown.
+const DEFAULT_ENGINE: Engine = Engine::Neural;
+
+/// Polly settings from config: `polly_region`, `polly_profile` and `polly_engine`. Unset values
+/// fall back to the AWS environment and `~/.aws` files, as the AWS CLI does.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PollySettings {
+    pub region: Option<String>,
+    pub profile: Option<String>,
+    pub engine: Option<String>,
+}
+
+impl PollySettings {
+    pub fn from_config(cfg: &FullConfig) -> Self {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        Self {
+            region: non_empty(&cfg.polly_region),
+            profile: non_empty(&cfg.polly_profile),
+            engine: non_empty(&cfg.polly_engine),
+        }
+    }
+
+    /// The settings from config.json; defaults when it cannot be read.
+    pub fn load() -> Self {
+        crate::config::load_full_config()
+            .map(|cfg| Self::from_config(&cfg))
+            .unwrap_or_default()
+    }
 
-const CREDENTIALS_ERROR_MSG: &str = "AWS credentials not found. Please configure credentials via:\n  - Environment variables: AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY\n  - Or credentials file: ~/.aws/credentials";
+    /// `polly_profile`, else `AWS_PROFILE`, else `default`.
+    pub fn profile(&self) -> String {
+        self.profile
+            .clone()
+            .or_else(|| std::env::var("AWS_PROFILE").ok().filter(|p| !p.is_empty()))
+            .unwrap_or_else(|| "default".to_string())
+    }
+
+    /// The region and where it came from: `polly_region`, else `AWS_REGION` /
+    /// `AWS_DEFAULT_REGION`, else the profile's region in `~/.aws/config`, else `us-east-1`.
+    pub fn region(&self) -> (String, &'static str) {
+        if let Some(region) = &self.region {
+            return (region.clone(), "config");
+        }
+        for var in ["AWS_REGION", "AWS_DEFAULT_REGION"] {
+            if let Ok(region) = std::env::var(var) {
+                if !region.is_empty() {
+                    return (region, "environment");
+                }
+            }
+        }
+        if let Some(home) = dirs::home_dir() {
+            let config_path = home.join(".aws").join("config");
+            if let Ok(content) = std::fs::read_to_string(&config_path) {
+                if let Some(region) = profile_value(&content, &self.profile(), "region") {
+                    return (region, "aws config file");
+                }
+            }
+        }
+        ("us-east-1".to_string(), "default")
+    }
+
+    /// The preferred engine: `polly_engine` when it names one, else neural.
+    pub fn engine(&self) -> Engine {
+        match self.engine.as_deref() {
+            None => DEFAULT_ENGINE,
+            Some(name) => parse_engine(name).unwrap_or_else(|| {
+                warn!(engine = %name, "Unknown polly_engine, using neural");
+                DEFAULT_ENGINE
+            }),
+        }
+    }
+}
+
+fn parse_engine(name: &str) -> Option<Engine> {
+    match name.to_ascii_lowercase().as_str() {
+        "standard" => Some(Engine::Standard),
+        "neural" => Some(Engine::Neural),
+        "long-form" => Some(Engine::LongForm),
+        "generative" => Some(Engine::Generative),
+        _ => None,
+    }
+}
+
+/// `key` from the section of `profile` in an `~/.aws` file. Profile sections are `[name]` in
+/// `credentials` and `[profile name]` in `config`; both are accepted.
+fn profile_value(content: &str, profile: &str, key: &str) -> Option<String> {
+    let headers = [format!("[{profile}]"), format!("[profile {profile}]")];
+    let mut in_section = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_section = headers.iter().any(|h| line.eq_ignore_ascii_case(h));
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        if let Some((name, value)) = line.split_once('=') {
+            if name.trim() == key && !value.trim().is_empty() {
+                return Some(value.trim().to_string());
+            }
+        }
+    }
+    None
+}
+
+/// `preferred` when the voice supports it (or its engines are unknown); otherwise standard, or the
+/// first engine the voice has.
+fn pick_engine(preferred: Engine, supported: &[Engine]) -> Engine {
+    if supported.is_empty() || supported.contains(&preferred) {
+        return preferred;
+    }
+    if supported.contains(&Engine::Standard) {
+        return Engine::Standard;
+    }
+    supported.first().cloned().unwrap_or(preferred)
+}
+
+/// What the settings UI shows about the Polly setup (`check_polly_credentials`).
+#[derive(Debug, Clone, Serialize)]
+pub struct PollyDiagnostics {
+    pub credentials_configured: bool,
+    /// Why credentials were not found, when they were not.
+    pub credentials_error: Option<String>,
+    pub profile: String,
+    pub region: String,
+    /// `config`, `environment`, `aws config file` or `default`.
+    pub region_source: &'static str,
+    /// The preferred engine; a voice without it falls back to standard.
+    pub engine: String,
+}
+
+/// Diagnostics for the Polly settings in config.json.
+pub fn diagnostics() -> PollyDiagnostics {
+    let settings = PollySettings::load();
+    let credentials = PollyTTSProvider::check_credentials(&settings);
+    let (region, region_source) = settings.region();
+    PollyDiagnostics {
+        credentials_configured: credentials.is_ok(),
+        credentials_error: credentials.err(),
+        profile: settings.profile(),
+        region,
+        region_source,
+        engine: settings.engine().as_str().to_string(),
+    }
+}
+
+/// AWS config for Polly with `settings`. Credentials stored in the keychain (`secrets`) take
+/// precedence over the SDK's default chain (environment, `~/.aws/credentials`, ...).
+pub fn aws_config_loader(settings: &PollySettings) -> aws_config::ConfigLoader {
+    let (region, _) = settings.region();
+    let mut loader =
+        aws_config::defaults(BehaviorVersion::latest()).region(aws_config::Region::new(region));
+    if let Some(profile) = &settings.profile {
+        loader = loader.profile_name(profile);
+    }
+    #[cfg(desktop)]
+    if let Some(credentials) = crate::secrets::aws_credentials() {
+        debug!("Using AWS credentials from the keychain");
+        return loader.credentials_provider(credentials);
+    }
+    loader
+}
 
 pub struct PollyTTSProvider {
     client: aws_sdk_polly::Client,
     player: AudioPlayer,
-    runtime: tokio::runtime::Runtime,
+    /// Shared with synthesis threads, which block on Polly requests.
+    runtime: Arc<tokio::runtime::Runtime>,
     voice_id: String,
     engine: Engine,
 }
 
 impl PollyTTSProvider {
-    pub fn new(selected_voice: Option<String>) -> Result<Self, TTSError> {
+    pub fn new(selected_voice: Option<String>, settings: &PollySettings) -> Result<Self, TTSError> {
         info!("Initializing AWS Polly TTS provider");
 
         let runtime = tokio::runtime::Builder::new_multi_thread()
@@ -26,20 +232,15 @@ impl PollyTTSProvider {
             .build()
             .map_err(|e| TTSError::ProcessError(format!("Failed to create tokio runtime: {e}")))?;
 
-        let region = Self::detect_aws_region();
-        debug!(region = %region, "Using AWS region");
+        let (region, region_source) = settings.region();
+        debug!(region = %region, source = region_source, "Using AWS region");
 
-        let config = runtime.block_on(async {
-            aws_config::defaults(BehaviorVersion::latest())
-                .region(aws_config::Region::new(region))
-                .load()
-                .await
-        });
+        let config = runtime.block_on(aws_config_loader(settings).load());
 
         let client = aws_sdk_polly::Client::new(&config);
         debug!("AWS Polly client created");
 
-        let player = AudioPlayer::new(16000)?;
+        let player = AudioPlayer::new(SAMPLE_RATE)?;
 
         let voice_id = selected_voice
             .as_deref()
@@ -47,46 +248,71 @@ impl PollyTTSProvider {
             .map(|s| s.to_string())
             .unwrap_or_else(|| "Matthew".to_string());
 
+        let preferred = settings.engine();
+        let engine = runtime.block_on(Self::supported_engine(
+            &client,
+            &voice_id,
+            preferred.clone(),
+        ));
+        if engine != preferred {
+            warn!(
+                voice = %voice_id,
+                preferred = preferred.as_str(),
+                engine = engine.as_str(),
+                "Polly voice does not support the configured engine, falling back"
+            );
+        }
+
         Ok(Self {
             client,
             player,
-            runtime,
+            runtime: Arc::new(runtime),
             voice_id,
-            engine: Engine::Neural,
+            engine,
         })
     }
 
-    fn detect_aws_region() -> String {
-        if let Ok(region) = std::env::var("AWS_REGION") {
-            if !region.is_empty() {
-                return region;
-            }
-        }
-        if let Ok(region) = std::env::var("AWS_DEFAULT_REGION") {
-            if !region.is_empty() {
-                return region;
-            }
-        }
-        if let Some(home) = dirs::home_dir() {
-            let config_path = home.join(".aws").join("config");
-            if let Ok(content) = std::fs::read_to_string(&config_path) {
-                for line in content.lines() {
-                    let line = line.trim();
-                    if line.starts_with("region") {
-                        if let Some(region) = line.split('=').nth(1) {
-                            let region = region.trim().to_string();
-                            if !region.is_empty() {
-                                return region;
-                            }
-                        }
-                    }
-                }
+    /// The engine to use for `voice_id` (see `pick_engine`). Keeps `preferred` when the voice
+    /// list cannot be fetched; synthesis then reports the error.
+    async fn supported_engine(
+        client: &aws_sdk_polly::Client,
+        voice_id: &str,
+        preferred: Engine,
+    ) -> Engine {
+        let response = match client.describe_voices().send().await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!(error = %e, "Polly: could not check the voice's engines");
+                return preferred;
             }
+        };
+        match response
+            .voices()
+            .iter()
+            .find(|v| v.id().map(|id| id.as_str()) == Some(voice_id))
+        {
+            Some(voice) => pick_engine(preferred, voice.supported_engines()),
+            None => preferred,
         }
-        "us-east-1".to_string()
     }
 
-    pub fn check_credentials() -> Result<(), String> {
+    /// Checks that AWS accepts the credentials, with a `DescribeVoices` call.
+    pub async fn validate_credentials(settings: &PollySettings) -> Result<(), String> {
+        let config = aws_config_loader(settings).load().await;
+        aws_sdk_polly::Client::new(&config)
+            .describe_voices()
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Polly rejected the credentials: {e}"))
+    }
+
+    pub fn check_credentials(settings: &PollySettings) -> Result<(), String> {
+        #[cfg(desktop)]
+        if crate::secrets::aws_credentials().is_some() {
+            return Ok(());
+        }
+
         if std::env::var("AWS_ACCESS_KEY_ID").is_ok()
             && std::env::var("AWS_SECRET_ACCESS_KEY").is_ok()
         {
@@ -97,15 +323,10 @@ impl PollyTTSProvider {
             let credentials_path = home.join(".aws").join("credentials");
             if credentials_path.exists() {
                 if let Ok(content) = std::fs::read_to_string(&credentials_path) {
-                    let profile =
-                        std::env::var("AWS_PROFILE").unwrap_or_else(|_| "default".to_string());
-                    let section_header = if profile == "default" {
-                        "[default]".to_string()
-                    } else {
-                        format!("[profile {}]", profile)
-                    };
-
-                    if Self::parse_credentials_from_section(&content, &section_header) {
+                    let profile = settings.profile();
+                    if profile_value(&content, &profile, "aws_access_key_id").is_some()
+                        && profile_value(&content, &profile, "aws_secret_access_key").is_some()
+                    {
                         return Ok(());
                     }
                 }
@@ -115,97 +336,124 @@ impl PollyTTSProvider {
         Err(CREDENTIALS_ERROR_MSG.to_string())
     }
 
-    fn parse_credentials_from_section(content: &str, section_header: &str) -> bool {
-        let mut in_section = false;
-        let mut has_access_key = false;
-        let mut has_secret_key = false;
-
-        for line in content.lines() {
-            let line = line.trim();
-            if line.starts_with('[') {
-                in_section = line.eq_ignore_ascii_case(section_header);
-                continue;
+    /// Returns a function that synthesizes text with this voice (runs on the synthesis thread).
+    /// Word timing comes from a second request for speech marks. Chunks with markup (see
+    /// `ssml::Markup`) are synthesized from SSML; the marks still come from the plain text so their
+    /// offsets point into `text`. Neural voices have no prosody pitch; all voices take `<lang>`.
+    pub fn synthesizer(&self, markup: Markup) -> SynthesizeFn {
+        let client = self.client.clone();
+        let runtime = Arc::clone(&self.runtime);
+        let voice_id = self.voice_id.clone();
+        let engine = self.engine.clone();
+        Box::new(move |text: &str| {
+            debug!(
+                chars = text.len(),
+                text_preview = %text.chars().take(50).collect::<String>(),
+                "Polly: synthesizing chunk"
+            );
+
+            let (audio_bytes, marks) = runtime.block_on(async {
+                let marks = async {
+                    let response = client
+                        .synthesize_speech()
+                        .text(text)
+                        .output_format(OutputFormat::Json)
+                        .speech_mark_types(SpeechMarkType::Word)
+                        .voice_id(VoiceId::from(voice_id.as_str()))
+                        .engine(engine.clone())
+                        .send()
+                        .await
+                        .map_err(|e| format!("speech marks request failed: {e}"))?;
+                    let bytes = response
+                        .audio_stream
+                        .collect()
+                        .await
+                        .map_err(|e| format!("failed to read speech marks: {e}"))?;
+                    Ok::<_, String>(parse_speech_marks(&String::from_utf8_lossy(
+                        &bytes.into_bytes(),
+                    )))
+                };
+                let audio = async {
+                    let support = SsmlSupport {
+                        pitch: false,
+                        lang: true,
+                    };
+                    let request = match markup.to_ssml(text, support) {
+                        Some(ssml) => client
+                            .synthesize_speech()
+                            .text(format!("<speak>{ssml}</speak>"))
+                            .text_type(TextType::Ssml),
+                        None => client.synthesize_speech().text(text),
+                    };
+                    let response = request
+                        .output_format(OutputFormat::Pcm)
+                        .voice_id(VoiceId::from(voice_id.as_str()))
+                        .engine(engine.clone())
+                        .sample_rate(SAMPLE_RATE.to_string())
+                        .send()
+                        .await
+                        .map_err(|_| TTSError::ProcessError("AWS Polly API error".to_string()))?;
+
+                    let audio_stream = response.audio_stream;
+                    let bytes = audio_stream.collect().await.map_err(|e| {
+                        TTSError::ProcessError(format!("Failed to read audio stream: {e}"))
+                    })?;
+
+                    Ok::<_, TTSError>(bytes.into_bytes().to_vec())
+                };
+                let (audio, marks) = tokio::join!(audio, marks);
+                Ok::<_, TTSError>((audio?, marks))
+            })?;
+
+            if audio_bytes.is_empty() {
+                return Err(TTSError::ProcessError(
+                    "No audio data generated by AWS Polly".into(),
+                ));
             }
-            if in_section {
-                if line.starts_with("aws_access_key_id") {
-                    if let Some(value) = line.split('=').nth(1) {
-                        if !value.trim().is_empty() {
-                            has_access_key = true;
-                        }
-                    }
-                } else if line.starts_with("aws_secret_access_key") {
-                    if let Some(value) = line.split('=').nth(1) {
-                        if !value.trim().is_empty() {
-                            has_secret_key = true;
-                        }
-                    }
+
+            let audio_data = AudioPlayer::pcm_to_f32(&audio_bytes);
+            let duration_sec = audio_data.len() as f32 / SAMPLE_RATE as f32;
+            info!(
+                samples = audio_data.len(),
+                duration_sec = format!("{:.1}", duration_sec),
+                "Polly: audio generated"
+            );
+            match marks {
+                Ok(words) if !words.is_empty() => Ok(ChunkAudio {
+                    pcm: audio_data,
+                    sample_rate: SAMPLE_RATE,
+                    words,
+                }),
+                Ok(_) => Ok(ChunkAudio::estimated(text, audio_data, SAMPLE_RATE)),
+                Err(e) => {
+                    warn!(error = %e, "Polly: no speech marks, estimating word timing");
+                    Ok(ChunkAudio::estimated(text, audio_data, SAMPLE_RATE))
                 }
             }
-        }
-
-        has_access_key && has_secret_key
+        })
     }
 
-    pub fn speak(&mut self, text: &str) -> Result<(), TTSError> {
-        let text = text.trim();
-        if text.is_empty() {
-            warn!("Empty text provided to Polly, skipping synthesis");
-            return Err(TTSError::ProcessError(
-                "Cannot synthesize empty text".into(),
-            ));
-        }
-
-        debug!(
-            chars = text.len(),
-            text_preview = %text.chars().take(50).collect::<String>(),
-            "Polly: synthesizing speech"
-        );
-
-        self.player.stop()?;
-
-        let audio_bytes = self.runtime.block_on(async {
-            let response = self
-                .client
-                .synthesize_speech()
-                .text(text)
-                .output_format(OutputFormat::Pcm)
-                .voice_id(VoiceId::from(self.voice_id.as_str()))
-                .engine(self.engine.clone())
-                .sample_rate("16000")
-                .send()
-                .await
-                .map_err(|_| TTSError::ProcessError("AWS Polly API error".to_string()))?;
-
-            let audio_stream = response.audio_stream;
-            let bytes = audio_stream
-                .collect()
-                .await
-                .map_err(|e| TTSError::ProcessError(format!("Failed to read audio stream: {e}")))?;
-
-            Ok::<_, TTSError>(bytes.into_bytes().to_vec())
-        })?;
-
-        if audio_bytes.is_empty() {
-            return Err(TTSError::ProcessError(
-                "No audio data generated by AWS Polly".into(),
-            ));
-        }
+    /// The engine in use (`neural`, `standard`, ...), after any fallback.
+    pub fn engine_name(&self) -> &str {
+        self.engine.as_str()
+    }
 
-        let audio_data = AudioPlayer::pcm_to_f32(&audio_bytes);
-        let duration_sec = audio_data.len() as f32 / 16000.0;
-        info!(
-            samples = audio_data.len(),
-            duration_sec = format!("{:.1}", duration_sec),
-            "Polly: audio generated and playing"
-        );
+    pub fn append_audio(&mut self, audio_data: Vec<f32>, sample_rate: u32) -> Result<(), TTSError> {
+        self.player.append_audio(audio_data, sample_rate)
+    }
 
-        self.player.play_audio(audio_data)
+    pub fn current_segment(&self) -> usize {
+        self.player.current_segment()
     }
 
     pub fn stop(&mut self) -> Result<(), TTSError> {
         self.player.stop()
     }
 
+    pub fn fade_out(&mut self, duration: Duration) -> Result<(), TTSError> {
+        self.player.fade_out(duration)
+    }
+
     pub fn toggle_pause(&mut self) -> Result<bool, TTSError> {
         self.player.toggle_pause()
     }
@@ -214,14 +462,30 @@ impl PollyTTSProvider {
         self.player.get_status()
     }
 
-    pub fn seek(&mut self, offset_ms: i64) -> Result<(bool, bool, bool), TTSError> {
+    pub fn seek(&mut self, offset_ms: i64) -> Result<SeekResult, TTSError> {
         self.player.seek(offset_ms)
     }
 
+    pub fn seek_to(&mut self, position_ms: u64) -> Result<SeekResult, TTSError> {
+        self.player.seek_to(position_ms)
+    }
+
+    pub fn check_output(&mut self, force: bool) -> bool {
+        self.player.check_output(force)
+    }
+
+    pub fn reopen_output(&mut self) -> bool {
+        self.player.reopen_output()
+    }
+
     pub fn get_position(&self) -> (u64, u64) {
         self.player.get_position()
     }
 
+    pub fn waveform(&self, buckets: usize) -> Vec<f32> {
+        self.player.waveform(buckets)
+    }
+
     pub fn set_volume(&mut self, volume_percent: u8) {
         self.player.set_volume_percent(volume_percent);
     }
@@ -230,3 +494,43 @@ impl PollyTTSProvider {
         self.player.set_speed(speed);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_value_reads_credentials_and_config_sections() {
+        let credentials = "[default]\naws_access_key_id = A\n\n[work]\nregion=eu-west-1\n";
+        let config = "[profile work]\nregion = eu-central-1\n";
+        assert_eq!(
+            profile_value(credentials, "default", "aws_access_key_id").as_deref(),
+            Some("A")
+        );
+        assert_eq!(
+            profile_value(credentials, "work", "region").as_deref(),
+            Some("eu-west-1")
+        );
+        assert_eq!(
+            profile_value(config, "work", "region").as_deref(),
+            Some("eu-central-1")
+        );
+        assert_eq!(profile_value(config, "default", "region"), None);
+    }
+
+    #[test]
+    fn test_engine_falls_back_to_one_the_voice_supports() {
+        assert_eq!(parse_engine("Long-Form"), Some(Engine::LongForm));
+        assert_eq!(parse_engine("turbo"), None);
+        let standard_only = [Engine::Standard];
+        assert_eq!(
+            pick_engine(Engine::Neural, &standard_only),
+            Engine::Standard
+        );
+        assert_eq!(pick_engine(Engine::Neural, &[]), Engine::Neural);
+        assert_eq!(
+            pick_engine(Engine::Standard, &[Engine::Neural, Engine::Generative]),
+            Engine::Neural
+        );
+    }
+}
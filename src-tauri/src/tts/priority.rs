@@ -0,0 +1,133 @@
+//! CPU priority for synthesis work.
+//!
+//! Long Piper runs peg a core and make the UI choppy on slow machines. With `synthesis_priority`
+//! set to "low" or "idle" the Piper process is started with a higher niceness (Unix, set with
+//! `setpriority` in the child before it execs) or a lower priority class (Windows), and SoundTouch time-stretching runs on a separate thread
+//! with lowered priority (Linux; other platforms only move it off the worker thread).
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SynthesisPriority {
+    Normal,
+    Low,
+    Idle,
+}
+
+impl SynthesisPriority {
+    /// Parses the `synthesis_priority` config value; unknown or missing values mean normal.
+    pub fn from_config(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Some("low") => Self::Low,
+            Some("idle") => Self::Idle,
+            _ => Self::Normal,
+        }
+    }
+
+    #[cfg(unix)]
+    fn niceness(self) -> i32 {
+        match self {
+            Self::Normal => 0,
+            Self::Low => 10,
+            Self::Idle => 19,
+        }
+    }
+}
+
+static PRIORITY: AtomicU8 = AtomicU8::new(0);
+
+pub fn set(priority: SynthesisPriority) {
+    let value = match priority {
+        SynthesisPriority::Normal => 0,
+        SynthesisPriority::Low => 1,
+        SynthesisPriority::Idle => 2,
+    };
+    PRIORITY.store(value, Ordering::Relaxed);
+}
+
+pub fn current() -> SynthesisPriority {
+    match PRIORITY.load(Ordering::Relaxed) {
+        1 => SynthesisPriority::Low,
+        2 => SynthesisPriority::Idle,
+        _ => SynthesisPriority::Normal,
+    }
+}
+
+/// Builds the command that runs `program` at the current synthesis priority.
+#[cfg(unix)]
+pub fn command(program: &Path) -> Command {
+    use std::os::unix::process::CommandExt;
+
+    let mut cmd = Command::new(program);
+    let niceness = current().niceness();
+    if niceness != 0 {
+        // SAFETY: the closure runs in the forked child before exec; it only calls setpriority,
+        // which is async-signal-safe, and touches no memory shared with the parent. A failure
+        // leaves the child at normal priority rather than aborting the spawn.
+        unsafe {
+            cmd.pre_exec(move || {
+                libc::setpriority(libc::PRIO_PROCESS, 0, niceness);
+                Ok(())
+            });
+        }
+    }
+    cmd
+}
+
+/// Builds the command that runs `program`; the priority class is applied via `creation_flags`.
+#[cfg(target_os = "windows")]
+pub fn command(program: &Path) -> Command {
+    Command::new(program)
+}
+
+/// Windows process creation flag for the current synthesis priority (0 for normal).
+#[cfg(target_os = "windows")]
+pub fn windows_priority_class() -> u32 {
+    const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+    const IDLE_PRIORITY_CLASS: u32 = 0x0000_0040;
+    match current() {
+        SynthesisPriority::Normal => 0,
+        SynthesisPriority::Low => BELOW_NORMAL_PRIORITY_CLASS,
+        SynthesisPriority::Idle => IDLE_PRIORITY_CLASS,
+    }
+}
+
+/// Runs CPU-heavy `work` on the calling thread (normal priority) or on a scoped background
+/// thread with lowered priority. Returns `T::default()` if the background thread panics.
+pub fn run_at_synthesis_priority<T, F>(work: F) -> T
+where
+    T: Send + Default,
+    F: FnOnce() -> T + Send,
+{
+    let priority = current();
+    if priority == SynthesisPriority::Normal {
+        return work();
+    }
+    std::thread::scope(|scope| {
+        scope
+            .spawn(move || {
+                lower_current_thread_priority(priority);
+                work()
+            })
+            .join()
+            .unwrap_or_else(|_| {
+                tracing::error!("Background synthesis thread panicked");
+                T::default()
+            })
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn lower_current_thread_priority(priority: SynthesisPriority) {
+    // SAFETY: setpriority with PRIO_PROCESS and who = 0 only changes the calling thread's
+    // niceness on Linux (threads are scheduled individually); no memory is touched.
+    let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, priority.niceness()) };
+    if result != 0 {
+        tracing::debug!("Failed to lower synthesis thread priority");
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn lower_current_thread_priority(_priority: SynthesisPriority) {}
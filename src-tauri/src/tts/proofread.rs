@@ -0,0 +1,68 @@
+//! Proofread-by-ear reading: every clause is synthesized on its own and followed by a short
+//! pause (longer after a sentence), so run-ons and comma splices are easy to hear.
+
+use super::stream::SynthesizeFn;
+use crate::text::segment::Segment;
+
+const CLAUSE_PAUSE_MS: u64 = 300;
+const SENTENCE_PAUSE_MS: u64 = 700;
+
+/// One chunk per clause: segments are split after `,`, `;` and `:`.
+pub(super) fn plan_chunks(segments: &[Segment]) -> Vec<String> {
+    let mut chunks = Vec::new();
+    for segment in segments {
+        let mut clause = String::new();
+        for word in segment.text.split_whitespace() {
+            if !clause.is_empty() {
+                clause.push(' ');
+            }
+            clause.push_str(word);
+            if word.ends_with([',', ';', ':']) {
+                chunks.push(std::mem::take(&mut clause));
+            }
+        }
+        if !clause.is_empty() {
+            chunks.push(clause);
+        }
+    }
+    chunks
+}
+
+/// Wraps `synthesize` so each chunk ends with silence: a clause pause after `,;:`, a sentence
+/// pause otherwise.
+pub(super) fn with_pauses(mut synthesize: SynthesizeFn) -> SynthesizeFn {
+    Box::new(move |text: &str| {
+        let mut audio = synthesize(text)?;
+        let pause_ms = if text.ends_with([',', ';', ':']) {
+            CLAUSE_PAUSE_MS
+        } else {
+            SENTENCE_PAUSE_MS
+        };
+        let silence = (u64::from(audio.sample_rate) * pause_ms / 1000) as usize;
+        audio.pcm.resize(audio.pcm.len() + silence, 0.0);
+        Ok(audio)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_splits_sentences_into_clauses() {
+        let segments = [
+            Segment {
+                text: "First, we read; then: we listen.".to_string(),
+                paragraph: 0,
+            },
+            Segment {
+                text: "Short one.".to_string(),
+                paragraph: 1,
+            },
+        ];
+        assert_eq!(
+            plan_chunks(&segments),
+            ["First,", "we read;", "then:", "we listen.", "Short one."]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Playback queue: texts waiting to be read after the current one.
+//!
+//! `Enqueue` starts the text right away when nothing is playing, otherwise appends it. While the
+//! queue has a current item or items waiting, the worker checks every `QUEUE_TICK` whether
+//! playback has finished and then starts the next item, reporting each step to the
+//! `QueueNotifier` (the app emits `tts-queue-advanced` and records the read in the history). An
+//! item is started by sending Speak to the worker itself, so it goes through the same path as any
+//! other read. Stop clears the queue; a Speak from outside the queue replaces the current item but
+//! leaves the waiting ones. An announced read waits as the next item while its announcement plays.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use super::InputKind;
+
+/// How often the worker checks for the end of the current item while the queue is active.
+pub(super) const QUEUE_TICK: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueItem {
+    pub id: u64,
+    /// What queued the text ("selection", "app", ...), as in the reading history.
+    pub source: String,
+    pub text: String,
+    #[serde(skip)]
+    pub input_kind: InputKind,
+    /// Already in the reading history (a read held back behind its announcement).
+    #[serde(skip)]
+    pub recorded: bool,
+}
+
+/// Payload of the `tts-queue-advanced` event: `previous` finished (or was skipped) and `current`
+/// started; `current` is `None` when the queue ran out.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueAdvance {
+    pub previous: Option<u64>,
+    pub current: Option<QueueItem>,
+    pub remaining: usize,
+}
+
+/// Receives queue advances (set once the app is running; see `commands_tts`).
+pub type QueueNotifier = Box<dyn Fn(&QueueAdvance) + Send>;
+
+#[derive(Default)]
+pub(super) struct Queue {
+    items: VecDeque<QueueItem>,
+    current: Option<u64>,
+    /// An item was sent to Speak and has not been picked up yet.
+    starting: bool,
+    next_id: u64,
+    notifier: Option<QueueNotifier>,
+}
+
+impl Queue {
+    pub fn set_notifier(&mut self, notifier: QueueNotifier) {
+        self.notifier = Some(notifier);
+    }
+
+    pub fn push(&mut self, text: String, input_kind: InputKind, source: String) -> u64 {
+        self.next_id += 1;
+        self.items.push_back(QueueItem {
+            id: self.next_id,
+            source,
+            text,
+            input_kind,
+            recorded: false,
+        });
+        self.next_id
+    }
+
+    /// Holds back the text of a read that is being announced (see `announce`): it becomes the next
+    /// item, ahead of the waiting ones.
+    pub fn defer(&mut self, text: String, input_kind: InputKind, source: String) -> u64 {
+        self.next_id += 1;
+        self.items.push_front(QueueItem {
+            id: self.next_id,
+            source,
+            text,
+            input_kind,
+            recorded: true,
+        });
+        self.next_id
+    }
+
+    pub fn items(&self) -> Vec<QueueItem> {
+        self.items.iter().cloned().collect()
+    }
+
+    /// True while the worker needs to watch for the end of playback.
+    pub fn is_active(&self) -> bool {
+        self.current.is_some() || !self.items.is_empty()
+    }
+
+    /// Whether an item may be started now (none is on its way to Speak).
+    pub fn can_advance(&self) -> bool {
+        !self.starting && self.is_active()
+    }
+
+    /// Called for each Speak: the first one after `advance` is the queued item, any other one
+    /// replaces it.
+    pub fn on_speak(&mut self) {
+        if !std::mem::take(&mut self.starting) {
+            self.current = None;
+        }
+    }
+
+    /// Drops the waiting items, keeping the one playing. Returns how many were dropped.
+    pub fn clear_waiting(&mut self) -> usize {
+        let dropped = self.items.len();
+        self.items.clear();
+        dropped
+    }
+
+    /// Forgets everything (Stop).
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.current = None;
+        self.starting = false;
+    }
+
+    /// Ends the current item and takes the next one to speak, notifying the change.
+    pub fn advance(&mut self) -> Option<QueueItem> {
+        let previous = self.current.take();
+        let next = self.items.pop_front();
+        self.current = next.as_ref().map(|item| item.id);
+        self.starting = next.is_some();
+        if let Some(notifier) = &self.notifier {
+            notifier(&QueueAdvance {
+                previous,
+                current: next.clone(),
+                remaining: self.items.len(),
+            });
+        }
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advances_through_items_and_ends() {
+        let mut queue = Queue::default();
+        assert!(!queue.is_active());
+        let first = queue.push("one".into(), InputKind::Text, "app".into());
+        queue.push("two".into(), InputKind::Text, "app".into());
+
+        assert_eq!(queue.advance().map(|item| item.id), Some(first));
+        assert!(!queue.can_advance());
+        queue.on_speak();
+        assert!(queue.can_advance());
+
+        // A Speak from outside replaces the current item; the waiting one stays.
+        queue.on_speak();
+        assert_eq!(queue.items().len(), 1);
+        assert_eq!(
+            queue.advance().map(|item| item.text),
+            Some("two".to_string())
+        );
+        queue.on_speak();
+        assert!(queue.advance().is_none());
+        assert!(!queue.is_active());
+    }
+}
@@ -0,0 +1,90 @@
+//! Sleep timer: stops playback on its own after a set time, for listening to long documents in
+//! bed.
+//!
+//! The worker owns the timer and wakes up when it is due, then fades the audio out over
+//! `FADE_OUT` and stops like Stop (the queue is cleared; the reading history keeps the position).
+//! A new timer replaces the running one.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Fade before the sleep timer stops playback.
+pub(super) const FADE_OUT: Duration = Duration::from_secs(3);
+pub const MAX_SLEEP_MINUTES: u32 = 480;
+
+/// The running sleep timer, as reported by `tts_get_sleep_timer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SleepTimerStatus {
+    pub minutes: u32,
+    pub remaining_secs: u64,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct SleepTimer {
+    /// Minutes set and when they are up.
+    running: Option<(u32, Instant)>,
+}
+
+impl SleepTimer {
+    pub fn set(&mut self, minutes: u32, now: Instant) -> SleepTimerStatus {
+        self.running = Some((minutes, now + Duration::from_secs(u64::from(minutes) * 60)));
+        SleepTimerStatus {
+            minutes,
+            remaining_secs: u64::from(minutes) * 60,
+        }
+    }
+
+    /// Cancels the timer; false when none was running.
+    pub fn cancel(&mut self) -> bool {
+        self.running.take().is_some()
+    }
+
+    pub fn status(&self, now: Instant) -> Option<SleepTimerStatus> {
+        self.running.map(|(minutes, due)| SleepTimerStatus {
+            minutes,
+            remaining_secs: due.saturating_duration_since(now).as_secs(),
+        })
+    }
+
+    /// Ends the timer when it is due; true when playback should stop.
+    pub fn take_due(&mut self, now: Instant) -> bool {
+        let due = self.running.is_some_and(|(_, due)| due <= now);
+        if due {
+            self.running = None;
+        }
+        due
+    }
+
+    /// Shortens the worker's wait (`None`: until the next request) so it wakes up when due.
+    pub fn limit(&self, tick: Option<Duration>, now: Instant) -> Option<Duration> {
+        let Some((_, due)) = self.running else {
+            return tick;
+        };
+        let left = due.saturating_duration_since(now);
+        Some(tick.map_or(left, |tick| tick.min(left)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timer_wakes_the_worker_and_fires_once() {
+        let now = Instant::now();
+        let mut timer = SleepTimer::default();
+        assert_eq!(timer.limit(None, now), None);
+        timer.set(30, now);
+        assert_eq!(timer.limit(None, now), Some(Duration::from_secs(1800)));
+        let tick = Duration::from_millis(50);
+        assert_eq!(timer.limit(Some(tick), now), Some(tick));
+
+        let later = now + Duration::from_secs(600);
+        assert_eq!(timer.status(later).map(|s| s.remaining_secs), Some(1200));
+        assert!(!timer.take_due(later));
+        assert!(timer.take_due(now + Duration::from_secs(1800)));
+        assert!(!timer.take_due(now + Duration::from_secs(1800)));
+        assert_eq!(timer.status(now), None);
+    }
+}
@@ -0,0 +1,202 @@
+//! Provider health for the settings UI and bug reports (`tts_get_provider_status`).
+//!
+//! The worker records the provider and voice it has loaded and the last synthesis error, so a
+//! failed read can be explained after the fact. Each provider's availability is checked on
+//! request: Piper's binary and model, Polly's credentials (with a `DescribeVoices` call), and
+//! whether the Edge TTS service and the custom voice server answer. Cloud checks are skipped in
+//! offline mode.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use super::mobile::MobileTTSProvider;
+use super::piper::PiperTTSProvider;
+use super::polly::PollyTTSProvider;
+use super::system::SystemTTSProvider;
+use super::{TTSError, TtsConfigSnapshot, TtsProvider};
+use crate::offline;
+use crate::util::unix_millis_now;
+
+/// Host the Edge TTS voices are served from; any HTTP response counts as reachable.
+const MICROSOFT_PROBE_URL: &str = "https://speech.platform.bing.com/";
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SynthesisError {
+    pub provider: Option<&'static str>,
+    pub message: String,
+    /// Unix time in milliseconds.
+    pub at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderHealth {
+    pub provider: &'static str,
+    pub available: bool,
+    /// Why the provider is unavailable, or what it would use.
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TtsProviderStatus {
+    /// The provider the worker has loaded (after fallbacks); `None` before it started.
+    pub current_provider: Option<&'static str>,
+    pub active_voice: Option<String>,
+    pub last_error: Option<SynthesisError>,
+    pub providers: Vec<ProviderHealth>,
+    pub offline_mode: bool,
+}
+
+struct WorkerStatus {
+    provider: Option<TtsProvider>,
+    voice: Option<String>,
+    last_error: Option<SynthesisError>,
+}
+
+static WORKER: Mutex<WorkerStatus> = Mutex::new(WorkerStatus {
+    provider: None,
+    voice: None,
+    last_error: None,
+});
+
+/// Records the provider and voice the worker loaded.
+pub(super) fn set_active(provider: TtsProvider, voice: Option<String>) {
+    if let Ok(mut status) = WORKER.lock() {
+        status.provider = Some(provider);
+        status.voice = voice;
+    }
+}
+
+/// Records a failed load or read; `provider` is `None` when no provider could be loaded.
+pub(super) fn record_error(provider: Option<TtsProvider>, error: &TTSError) {
+    if let Ok(mut status) = WORKER.lock() {
+        status.last_error = Some(SynthesisError {
+            provider: provider.map(TtsProvider::name),
+            message: error.to_string(),
+            at: unix_millis_now(),
+        });
+    }
+}
+
+fn health(provider: TtsProvider, result: Result<Option<String>, String>) -> ProviderHealth {
+    let (available, detail) = match result {
+        Ok(detail) => (true, detail),
+        Err(reason) => (false, Some(reason)),
+    };
+    ProviderHealth {
+        provider: provider.name(),
+        available,
+        detail,
+    }
+}
+
+fn piper(selected_voice: Option<String>) -> Result<Option<String>, String> {
+    if !PiperTTSProvider::find_piper_binary().is_file() && !PiperTTSProvider::runs_in_process() {
+        return Err("Piper binary not found".to_string());
+    }
+    match PiperTTSProvider::model_path(selected_voice) {
+        Some(model) => Ok(Some(model.display().to_string())),
+        None => Err("No Piper voice is downloaded".to_string()),
+    }
+}
+
+async fn polly(config: &TtsConfigSnapshot) -> Result<Option<String>, String> {
+    offline::ensure_online()?;
+    PollyTTSProvider::check_credentials(&config.polly)?;
+    PollyTTSProvider::validate_credentials(&config.polly).await?;
+    Ok(Some(format!("Region {}", config.polly.region().0)))
+}
+
+async fn reachable(url: &str) -> Result<reqwest::StatusCode, String> {
+    let client = reqwest::Client::builder()
+        .timeout(PROBE_TIMEOUT)
+        .build()
+        .map_err(|e| format!("HTTP client: {e}"))?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("{url} is unreachable: {e}"))?;
+    Ok(response.status())
+}
+
+async fn microsoft() -> Result<Option<String>, String> {
+    offline::ensure_online()?;
+    reachable(MICROSOFT_PROBE_URL).await?;
+    Ok(None)
+}
+
+async fn custom(config: &TtsConfigSnapshot) -> Result<Option<String>, String> {
+    let server = config
+        .custom_server
+        .as_ref()
+        .ok_or_else(|| "No speaker set for the custom voice server".to_string())?;
+    let url = format!("{}/speakers_list", server.url.trim_end_matches('/'));
+    let status = reachable(&url).await?;
+    if !status.is_success() {
+        return Err(format!("The custom voice server answered {status}"));
+    }
+    Ok(Some(server.url.clone()))
+}
+
+/// The worker's provider, voice and last error, and each provider's availability.
+pub async fn provider_status() -> TtsProviderStatus {
+    let config = super::load_tts_config(None);
+    let (polly, microsoft, custom) = tokio::join!(polly(&config), microsoft(), custom(&config));
+
+    let system = if SystemTTSProvider::is_available() {
+        Ok(None)
+    } else {
+        Err("No OS speech engine found".to_string())
+    };
+    let mut providers = vec![
+        health(TtsProvider::Piper, piper(config.selected_voice.clone())),
+        health(TtsProvider::Microsoft, microsoft),
+        health(TtsProvider::Polly, polly),
+        health(TtsProvider::System, system),
+        health(TtsProvider::Custom, custom),
+    ];
+    if cfg!(mobile) {
+        let mobile = if MobileTTSProvider::is_available() {
+            Ok(None)
+        } else {
+            Err("The platform speech engine is not registered".to_string())
+        };
+        providers.push(health(TtsProvider::Mobile, mobile));
+    }
+
+    let (current_provider, active_voice, last_error) = match WORKER.lock() {
+        Ok(status) => (
+            status.provider.map(TtsProvider::name),
+            status.voice.clone(),
+            status.last_error.clone(),
+        ),
+        Err(_) => (None, None, None),
+    };
+    TtsProviderStatus {
+        current_provider,
+        active_voice,
+        last_error,
+        providers,
+        offline_mode: offline::is_offline(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_the_last_error() {
+        record_error(
+            Some(TtsProvider::Polly),
+            &TTSError::NotConfigured("no credentials".into()),
+        );
+        let status = WORKER.lock().unwrap();
+        let error = status.last_error.as_ref().unwrap();
+        assert_eq!(error.provider, Some("polly"));
+        assert!(error.message.contains("no credentials"));
+    }
+}
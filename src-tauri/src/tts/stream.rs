@@ -0,0 +1,376 @@
+//! Streaming synthesis: text is split into sentence chunks that are synthesized one after another
+//! on a background thread and appended to the player queue as they arrive, so the first sentence
+//! plays while later ones are still being synthesized.
+//!
+//! Every `Speak` starts a new generation; Stop, a new Speak, or a provider switch bump the
+//! generation, which ends the synthesis thread and makes late results stale. Results come back to
+//! the worker as `TtsRequest::ChunkReady` on the worker's own channel.
+//!
+//! The audio of the last stream is kept per chunk. When the next Speak starts with the same chunks
+//! (e.g. re-reading after fixing a typo in the editor), those leading chunks are queued from the
+//! cache and synthesis starts at the first chunk whose text changed.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use tracing::{debug, warn};
+
+use super::timeline::{self, SegmentStart, WordMark};
+use super::{TTSError, TtsRequest, TtsState};
+use crate::text::segment::Segment;
+
+/// Synthesized audio of one chunk: mono f32 PCM, its sample rate, and word timing.
+#[derive(Clone)]
+pub struct ChunkAudio {
+    pub pcm: Vec<f32>,
+    pub sample_rate: u32,
+    pub words: Vec<WordMark>,
+}
+
+impl ChunkAudio {
+    /// Chunk audio with word timing estimated from `text` (no timing from the provider).
+    pub fn estimated(text: &str, pcm: Vec<f32>, sample_rate: u32) -> Self {
+        let duration_ms = pcm.len() as u64 * 1000 / u64::from(sample_rate.max(1));
+        Self {
+            words: timeline::estimate_marks(text, duration_ms),
+            pcm,
+            sample_rate,
+        }
+    }
+}
+
+/// Synthesizes one chunk of text.
+pub type SynthesizeFn = Box<dyn FnMut(&str) -> Result<ChunkAudio, TTSError> + Send>;
+
+/// Chunks after the first are grown up to this many chars (fewer Piper launches per article).
+const CHUNK_MAX_CHARS: usize = 600;
+/// How many chunks synthesis may run ahead of playback.
+const LOOKAHEAD_CHUNKS: usize = 2;
+const LOOKAHEAD_POLL: Duration = Duration::from_millis(50);
+/// How often the worker refreshes the playing chunk while a stream is active.
+pub(super) const STREAM_TICK: Duration = Duration::from_millis(200);
+/// Upper bound on cached audio (about six minutes of Piper audio).
+const CACHE_MAX_SAMPLES: usize = 8_000_000;
+
+/// A synthesized chunk (or the synthesis error), sent from the synthesis thread to the worker.
+pub struct ChunkReady {
+    generation: u64,
+    index: usize,
+    result: Result<ChunkAudio, TTSError>,
+}
+
+/// Groups segments into synthesis chunks: the first sentence alone (fast start), then sentences
+/// joined up to `CHUNK_MAX_CHARS`. Paragraph breaks inside a chunk become newlines.
+pub(super) fn plan_chunks(segments: &[Segment]) -> Vec<String> {
+    plan_chunk_layout(segments, false).0
+}
+
+/// Like `plan_chunks`, with where each segment starts in its chunk (for segment navigation). With
+/// `per_sentence`, every sentence is a chunk of its own, so the pause between chunks
+/// (`sentence_pause_ms`) falls between all sentences.
+pub(super) fn plan_chunk_layout(
+    segments: &[Segment],
+    per_sentence: bool,
+) -> (Vec<String>, Vec<Vec<SegmentStart>>) {
+    let mut chunks: Vec<String> = Vec::new();
+    let mut layout = Vec::new();
+    let mut current = String::new();
+    let mut starts = Vec::new();
+    let mut last_paragraph = None;
+    for (i, segment) in segments.iter().enumerate() {
+        let fits = !per_sentence && current.len() + segment.text.len() < CHUNK_MAX_CHARS;
+        if !current.is_empty() && (i == 1 || !fits) {
+            chunks.push(std::mem::take(&mut current));
+            layout.push(std::mem::take(&mut starts));
+        }
+        let paragraph = last_paragraph != Some(segment.paragraph);
+        if !current.is_empty() {
+            current.push(if paragraph { '\n' } else { ' ' });
+        }
+        starts.push(SegmentStart {
+            offset: current.len(),
+            paragraph,
+        });
+        current.push_str(&segment.text);
+        last_paragraph = Some(segment.paragraph);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+        layout.push(starts);
+    }
+    (chunks, layout)
+}
+
+/// Synthesized audio of one chunk of the last stream.
+struct CachedChunk {
+    text: String,
+    audio: ChunkAudio,
+}
+
+/// Number of leading `chunks` whose audio is in `cache`.
+fn reusable_prefix(cache: &[CachedChunk], chunks: &[String]) -> usize {
+    cache
+        .iter()
+        .zip(chunks)
+        .take_while(|(cached, chunk)| cached.text == **chunk)
+        .count()
+}
+
+/// Worker-side state of the current stream.
+#[derive(Default)]
+pub(super) struct Stream {
+    generation: Arc<AtomicU64>,
+    /// Chunk currently playing; the synthesis thread stays at most `LOOKAHEAD_CHUNKS` ahead.
+    playing: Arc<AtomicUsize>,
+    chunks: Vec<String>,
+    received: usize,
+    /// Response for the Speak request, sent once the first chunk plays (or fails).
+    first_response: Option<mpsc::SyncSender<Result<(), TTSError>>>,
+    /// Audio of the leading chunks of the last stream, in order.
+    cache: Vec<CachedChunk>,
+    cache_samples: usize,
+}
+
+impl Stream {
+    /// Starts a stream for `chunks`. Leading chunks that match the previous stream are not
+    /// synthesized again: their cached audio is returned for the caller to queue, and the rest is
+    /// synthesized on a background thread. `response` is answered when the first chunk is queued.
+    pub fn start(
+        &mut self,
+        chunks: Vec<String>,
+        synthesize: SynthesizeFn,
+        worker_tx: TtsState,
+        response: mpsc::SyncSender<Result<(), TTSError>>,
+    ) -> Vec<ChunkAudio> {
+        self.cancel();
+        let generation = self.generation.load(Ordering::SeqCst);
+        let reused = reusable_prefix(&self.cache, &chunks);
+        for dropped in self.cache.drain(reused..) {
+            self.cache_samples -= dropped.audio.pcm.len();
+        }
+        self.playing.store(0, Ordering::Relaxed);
+        self.chunks = chunks.clone();
+        self.received = reused;
+        self.first_response = Some(response);
+        debug!(
+            chunks = chunks.len(),
+            reused, generation, "Starting synthesis stream"
+        );
+
+        if reused < chunks.len() {
+            let current_generation = Arc::clone(&self.generation);
+            let playing = Arc::clone(&self.playing);
+            // Synthesis logs belong to the read that started it.
+            let span = tracing::Span::current();
+            let spawned = std::thread::Builder::new()
+                .name("tts-synthesis".into())
+                .spawn(move || {
+                    let _span = span.entered();
+                    synthesis_loop(
+                        chunks,
+                        reused,
+                        synthesize,
+                        generation,
+                        &current_generation,
+                        &playing,
+                        &worker_tx,
+                    )
+                });
+            if let Err(e) = spawned {
+                self.respond(Err(TTSError::ProcessError(format!(
+                    "Failed to start synthesis thread: {e}"
+                ))));
+                self.chunks.clear();
+                self.received = 0;
+                return Vec::new();
+            }
+        }
+        self.cache
+            .iter()
+            .map(|cached| cached.audio.clone())
+            .collect()
+    }
+
+    /// Drops cached audio (the voice or provider changed).
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+        self.cache_samples = 0;
+    }
+
+    /// Ends the current stream (stale chunks are ignored). A pending Speak is answered with Ok,
+    /// since the caller's request was superseded rather than failed.
+    pub fn cancel(&mut self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.chunks.clear();
+        self.received = 0;
+        if let Some(response) = self.first_response.take() {
+            let _ = response.send(Ok(()));
+        }
+    }
+
+    /// True while chunks are still being synthesized.
+    pub fn is_pending(&self) -> bool {
+        self.received < self.chunks.len()
+    }
+
+    /// Text of chunk `index` of the current stream.
+    pub fn chunk_text(&self, index: usize) -> &str {
+        self.chunks.get(index).map_or("", String::as_str)
+    }
+
+    /// Records the chunk the player is on (lets the synthesis thread continue).
+    pub fn set_playing(&self, index: usize) {
+        self.playing.store(index, Ordering::Relaxed);
+    }
+
+    /// Accepts a chunk for the current stream, or returns `None` for a stale one.
+    pub fn accept(&mut self, ready: ChunkReady) -> Option<(usize, Result<ChunkAudio, TTSError>)> {
+        if ready.generation != self.generation.load(Ordering::SeqCst) || !self.is_pending() {
+            return None;
+        }
+        self.received += 1;
+        match &ready.result {
+            Ok(audio) => {
+                let text = self.chunks.get(ready.index);
+                let fits = self.cache_samples + audio.pcm.len() <= CACHE_MAX_SAMPLES;
+                if let Some(text) = text.filter(|_| ready.index == self.cache.len() && fits) {
+                    self.cache_samples += audio.pcm.len();
+                    self.cache.push(CachedChunk {
+                        text: text.clone(),
+                        audio: audio.clone(),
+                    });
+                }
+            }
+            // The synthesis thread stops after an error; nothing more will arrive. The chunks are
+            // kept for `retry`.
+            Err(_) => self.received = self.chunks.len(),
+        }
+        Some((ready.index, ready.result))
+    }
+
+    /// Starts the stream over with another synthesizer after its first chunk failed (see
+    /// `fallback`); the pending Speak request is answered by the new stream.
+    pub fn retry(&mut self, synthesize: SynthesizeFn, worker_tx: TtsState) {
+        let Some(response) = self.first_response.take() else {
+            return;
+        };
+        let chunks = std::mem::take(&mut self.chunks);
+        // Audio of another voice must not be mixed into the read.
+        self.clear_cache();
+        self.start(chunks, synthesize, worker_tx, response);
+    }
+
+    /// Answers the pending Speak request, if any.
+    pub fn respond(&mut self, result: Result<(), TTSError>) {
+        if let Some(response) = self.first_response.take() {
+            let _ = response.send(result);
+        }
+    }
+}
+
+fn synthesis_loop(
+    chunks: Vec<String>,
+    first: usize,
+    mut synthesize: SynthesizeFn,
+    generation: u64,
+    current_generation: &AtomicU64,
+    playing: &AtomicUsize,
+    worker_tx: &TtsState,
+) {
+    let is_current = || current_generation.load(Ordering::SeqCst) == generation;
+    for (index, chunk) in chunks.iter().enumerate().skip(first) {
+        while index > playing.load(Ordering::Relaxed) + LOOKAHEAD_CHUNKS {
+            if !is_current() {
+                return;
+            }
+            std::thread::sleep(LOOKAHEAD_POLL);
+        }
+        if !is_current() {
+            return;
+        }
+        let result = synthesize(chunk);
+        if let Err(e) = &result {
+            warn!(index, error = %e, "Chunk synthesis failed");
+        }
+        let failed = result.is_err();
+        let ready = ChunkReady {
+            generation,
+            index,
+            result,
+        };
+        if worker_tx.send(TtsRequest::ChunkReady(ready)).is_err() || failed {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg(text: &str, paragraph: usize) -> Segment {
+        Segment {
+            text: text.to_string(),
+            paragraph,
+        }
+    }
+
+    #[test]
+    fn test_first_sentence_is_its_own_chunk() {
+        let long = "x".repeat(590);
+        let segments = [
+            seg("One.", 0),
+            seg("Two.", 0),
+            seg("Three.", 1),
+            seg(&long, 1),
+            seg("Four.", 2),
+        ];
+        let chunks = plan_chunks(&segments);
+        assert_eq!(chunks[0], "One.");
+        assert_eq!(chunks[1], "Two.\nThree.");
+        assert_eq!(chunks[2], format!("{long}\nFour."));
+        assert!(plan_chunks(&[]).is_empty());
+
+        let (_, layout) = plan_chunk_layout(&segments, false);
+        let starts = |chunk: usize| -> Vec<(usize, bool)> {
+            layout[chunk]
+                .iter()
+                .map(|s| (s.offset, s.paragraph))
+                .collect()
+        };
+        assert_eq!(starts(0), [(0, true)]);
+        assert_eq!(starts(1), [(0, false), (5, true)]);
+        assert_eq!(starts(2), [(0, false), (long.len() + 1, true)]);
+    }
+
+    #[test]
+    fn test_edit_reuses_chunks_before_the_change() {
+        let sentences: Vec<String> = (0..12)
+            .map(|i| format!("Sentence number {i} is here and it is of moderate length, really."))
+            .collect();
+        let original: Vec<Segment> = sentences.iter().map(|s| seg(s, 0)).collect();
+        let mut edited = original.clone();
+        edited[11].text = edited[11].text.replace("moderate", "modrate");
+
+        let cache: Vec<CachedChunk> = plan_chunks(&original)
+            .into_iter()
+            .map(|text| CachedChunk {
+                text,
+                audio: ChunkAudio {
+                    pcm: Vec::new(),
+                    sample_rate: 22050,
+                    words: Vec::new(),
+                },
+            })
+            .collect();
+        let chunks = plan_chunks(&edited);
+        let reused = reusable_prefix(&cache, &chunks);
+        assert_eq!(reused, 2);
+        assert!(chunks[reused].contains("modrate"));
+        assert_eq!(
+            reusable_prefix(&cache, &plan_chunks(&original)),
+            cache.len()
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! System TTS provider: the speech engine that ships with the OS, for users without Piper voices
+//! or network access.
+//!
+//! Each chunk is rendered to WAV and played through our own player, so seek, speed and the word
+//! timeline work as for the other providers (timing is estimated). On Linux speech-dispatcher can
+//! only speak to the sound card, so we run eSpeak NG (its default module) directly; on macOS we
+//! use `say` (AVSpeechSynthesizer voices) and on Windows SAPI through PowerShell's System.Speech.
+
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use tracing::{debug, info};
+
+use super::audio_player::{AudioPlayer, SeekResult};
+use super::stream::{ChunkAudio, SynthesizeFn};
+use super::TTSError;
+use crate::text::ssml::Markup;
+
+/// Player rate until the first chunk reports its own.
+const SAMPLE_RATE: u32 = 22050;
+
+pub struct SystemTTSProvider {
+    player: AudioPlayer,
+    voice: Option<String>,
+}
+
+impl SystemTTSProvider {
+    pub fn new(voice: Option<String>) -> Result<Self, TTSError> {
+        info!("Initializing system TTS provider");
+        if !Self::is_available() {
+            return Err(TTSError::ProcessError(
+                "No system speech engine found (install espeak-ng)".into(),
+            ));
+        }
+        let player = AudioPlayer::new(SAMPLE_RATE)?;
+        info!(voice = ?voice, "Using system TTS voice");
+        Ok(Self { player, voice })
+    }
+
+    /// Whether the OS speech engine can be run.
+    pub fn is_available() -> bool {
+        #[cfg(target_os = "linux")]
+        {
+            espeak_bin().is_some()
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            cfg!(any(target_os = "macos", target_os = "windows"))
+        }
+    }
+
+    /// Returns a function that synthesizes text with this voice (runs on the synthesis thread).
+    /// The engines take plain text: words with a phoneme override are spoken as their replacement.
+    pub fn synthesizer(&self, markup: Markup) -> SynthesizeFn {
+        let voice = self.voice.clone();
+        Box::new(move |text: &str| {
+            debug!(
+                chars = text.len(),
+                text_preview = %text.chars().take(50).collect::<String>(),
+                voice = ?voice,
+                "System TTS: synthesizing chunk"
+            );
+            let spoken = markup.respell(text);
+            let wav = render_wav(&spoken, voice.as_deref())?;
+            let (pcm, sample_rate) = AudioPlayer::decode_audio(wav)?;
+            info!(samples = pcm.len(), "System TTS: audio generated");
+            Ok(ChunkAudio::estimated(text, pcm, sample_rate))
+        })
+    }
+
+    pub fn append_audio(&mut self, audio_data: Vec<f32>, sample_rate: u32) -> Result<(), TTSError> {
+        self.player.append_audio(audio_data, sample_rate)
+    }
+
+    pub fn current_segment(&self) -> usize {
+        self.player.current_segment()
+    }
+
+    pub fn stop(&mut self) -> Result<(), TTSError> {
+        self.player.stop()
+    }
+
+    pub fn fade_out(&mut self, duration: Duration) -> Result<(), TTSError> {
+        self.player.fade_out(duration)
+    }
+
+    pub fn toggle_pause(&mut self) -> Result<bool, TTSError> {
+        self.player.toggle_pause()
+    }
+
+    pub fn get_status(&self) -> (bool, bool) {
+        self.player.get_status()
+    }
+
+    pub fn seek(&mut self, offset_ms: i64) -> Result<SeekResult, TTSError> {
+        self.player.seek(offset_ms)
+    }
+
+    pub fn seek_to(&mut self, position_ms: u64) -> Result<SeekResult, TTSError> {
+        self.player.seek_to(position_ms)
+    }
+
+    pub fn check_output(&mut self, force: bool) -> bool {
+        self.player.check_output(force)
+    }
+
+    pub fn reopen_output(&mut self) -> bool {
+        self.player.reopen_output()
+    }
+
+    pub fn get_position(&self) -> (u64, u64) {
+        self.player.get_position()
+    }
+
+    pub fn waveform(&self, buckets: usize) -> Vec<f32> {
+        self.player.waveform(buckets)
+    }
+
+    pub fn set_volume(&mut self, volume_percent: u8) {
+        self.player.set_volume_percent(volume_percent);
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.player.set_speed(speed);
+    }
+
+    pub fn set_pitch(&mut self, ratio: f32) {
+        self.player.set_pitch(ratio);
+    }
+}
+
+/// Runs `command` with `text` on stdin and waits for it to finish.
+fn run_engine(mut command: Command, text: &str, name: &str) -> Result<(), TTSError> {
+    use std::io::Write;
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| TTSError::ProcessError(format!("Failed to start {name}: {e}")))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| TTSError::ProcessError(format!("Failed to write to {name}: {e}")))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| TTSError::ProcessError(format!("{name} process failed: {e}")))?;
+    if !output.status.success() {
+        return Err(TTSError::ProcessError(format!(
+            "{name} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn espeak_bin() -> Option<std::path::PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path).find_map(|dir| {
+        ["espeak-ng", "espeak"]
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|bin| bin.is_file())
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn render_wav(text: &str, voice: Option<&str>) -> Result<Vec<u8>, TTSError> {
+    let bin =
+        espeak_bin().ok_or_else(|| TTSError::ProcessError("espeak-ng is not installed".into()))?;
+    // With --stdout the WAV header has no sizes, so write a file; it is removed on drop.
+    let temp_file = crate::janitor::temp_file("espeak-", ".wav")
+        .map_err(TTSError::ProcessError)?
+        .into_temp_path();
+    let mut command = Command::new(bin);
+    command.arg("--stdin").arg("-w").arg(&temp_file);
+    if let Some(voice) = voice {
+        command.args(["-v", voice]);
+    }
+    run_engine(command, text, "espeak-ng")?;
+    std::fs::read(&temp_file)
+        .map_err(|e| TTSError::ProcessError(format!("Failed to read espeak-ng output: {e}")))
+}
+
+#[cfg(target_os = "macos")]
+fn render_wav(text: &str, voice: Option<&str>) -> Result<Vec<u8>, TTSError> {
+    // `say` cannot write WAV to stdout; it writes over the empty file, which is removed on drop.
+    let temp_file = crate::janitor::temp_file("say-", ".wav")
+        .map_err(TTSError::ProcessError)?
+        .into_temp_path();
+    let mut command = Command::new("/usr/bin/say");
+    command
+        .args([
+            "-f",
+            "-",
+            "--file-format=WAVE",
+            "--data-format=LEI16@22050",
+            "-o",
+        ])
+        .arg(&temp_file);
+    if let Some(voice) = voice {
+        command.args(["-v", voice]);
+    }
+    run_engine(command, text, "say")?;
+    std::fs::read(&temp_file)
+        .map_err(|e| TTSError::ProcessError(format!("Failed to read say output: {e}")))
+}
+
+#[cfg(target_os = "windows")]
+fn render_wav(text: &str, voice: Option<&str>) -> Result<Vec<u8>, TTSError> {
+    use std::os::windows::process::CommandExt;
+
+    // SAPI writes over the empty file; it is removed when `temp_file` is dropped.
+    let temp_file = crate::janitor::temp_file("sapi-", ".wav")
+        .map_err(TTSError::ProcessError)?
+        .into_temp_path();
+    let select_voice = voice
+        .map(|v| format!("$s.SelectVoice({});", ps_quote(v)))
+        .unwrap_or_default();
+    let script = format!(
+        "[Console]::InputEncoding = [Text.Encoding]::UTF8; \
+         Add-Type -AssemblyName System.Speech; \
+         $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; {select_voice} \
+         $s.SetOutputToWaveFile({}); $s.Speak([Console]::In.ReadToEnd()); $s.Dispose()",
+        ps_quote(&temp_file.to_string_lossy())
+    );
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+    let mut command = Command::new("powershell");
+    command
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW);
+    run_engine(command, text, "SAPI")?;
+    std::fs::read(&temp_file)
+        .map_err(|e| TTSError::ProcessError(format!("Failed to read SAPI output: {e}")))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn render_wav(_text: &str, _voice: Option<&str>) -> Result<Vec<u8>, TTSError> {
+    Err(TTSError::ProcessError(
+        "System TTS is not supported on this platform".into(),
+    ))
+}
+
+/// A PowerShell single-quoted string literal.
+#[cfg(any(target_os = "windows", test))]
+fn ps_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quotes_powershell_literals() {
+        assert_eq!(ps_quote("Microsoft Zira"), "'Microsoft Zira'");
+        assert_eq!(
+            ps_quote(r"C:\Users\O'Brien\a.wav"),
+            r"'C:\Users\O''Brien\a.wav'"
+        );
+    }
+}
@@ -0,0 +1,441 @@
+//! Word timing for playback highlighting.
+//!
+//! Each synthesized chunk carries word marks (start time within the chunk audio + byte range in
+//! the chunk text). Polly returns speech marks and Edge TTS returns word boundaries; Piper's CLI
+//! does not expose phoneme durations, so its words are spread over the chunk duration by length.
+//! The worker maps the marks onto the text passed to Speak (which may differ after preprocessing)
+//! and emits `tts-progress` whenever the spoken word changes, with the chunk it belongs to so long
+//! texts can show how far the read has come. It also keeps where each sentence and paragraph
+//! starts in the audio, for jumping to the next or previous one. When a read ends, the words
+//! spoken and the time listened go to the reading stats (see `reading_stats`).
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use super::TtsProvider;
+use crate::reading_stats;
+use crate::text::align::Aligner;
+
+/// How often the worker checks the playback position while reporting progress.
+pub(super) const PROGRESS_TICK: Duration = Duration::from_millis(50);
+/// Jumping back this far into a segment restarts it; closer to its start goes to the one before.
+const RESTART_WINDOW_MS: u64 = 1500;
+
+/// A word in a synthesized chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WordMark {
+    /// Start of the word, in ms from the start of the chunk audio.
+    pub start_ms: u64,
+    /// Byte range of the word in the chunk text.
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A word of the Speak text on the playback timeline. Offsets are UTF-16 code units (JS string
+/// indices) into the text passed to `tts_speak`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TimelineWord {
+    pub start_ms: u64,
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+/// Payload of the `tts-progress` event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TtsProgress {
+    pub position_ms: u64,
+    pub char_start: usize,
+    pub char_end: usize,
+    /// Synthesis chunk of the word (0-based) and the number of chunks in the read.
+    pub chunk: usize,
+    pub chunks: usize,
+}
+
+/// Where a sentence starts in its synthesis chunk: byte offset in the chunk text, and whether it
+/// starts a paragraph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct SegmentStart {
+    pub offset: usize,
+    pub paragraph: bool,
+}
+
+/// What `tts_next_segment` and `tts_prev_segment` jump by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SegmentUnit {
+    #[default]
+    Sentence,
+    Paragraph,
+}
+
+impl SegmentUnit {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sentence => "sentence",
+            Self::Paragraph => "paragraph",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sentence" => Some(Self::Sentence),
+            "paragraph" => Some(Self::Paragraph),
+            _ => None,
+        }
+    }
+}
+
+/// A sentence on the playback timeline.
+#[derive(Debug, Clone, Copy)]
+struct TimelineSegment {
+    start_ms: u64,
+    paragraph: bool,
+}
+
+/// Receives progress updates (set once the app is running; see `commands_tts`).
+pub type ProgressNotifier = Box<dyn Fn(&TtsProgress) + Send>;
+
+/// Words of `text` with their byte ranges.
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_whitespace()
+        .map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+/// Spreads `duration_ms` over the words of `text` by length, with extra weight for the pause after
+/// punctuation.
+pub fn estimate_marks(text: &str, duration_ms: u64) -> Vec<WordMark> {
+    let weight = |word: &str| {
+        let pause = match word.chars().last() {
+            Some('.' | '!' | '?' | '…' | '。') => 4,
+            Some(',' | ';' | ':') => 2,
+            _ => 0,
+        };
+        (word.chars().count() + 1 + pause) as u64
+    };
+    let total: u64 = words(text).map(|(_, word)| weight(word)).sum();
+    if total == 0 {
+        return Vec::new();
+    }
+    let mut elapsed = 0;
+    words(text)
+        .map(|(start, word)| {
+            let mark = WordMark {
+                start_ms: duration_ms * elapsed / total,
+                start,
+                end: start + word.len(),
+            };
+            elapsed += weight(word);
+            mark
+        })
+        .collect()
+}
+
+/// Builds marks from provider word boundaries (`(start_ms, word)` in spoken order) by finding each
+/// word in `text`. Boundaries that cannot be found are dropped.
+pub fn marks_from_boundaries<'a>(
+    text: &str,
+    boundaries: impl IntoIterator<Item = (u64, &'a str)>,
+) -> Vec<WordMark> {
+    let mut cursor = 0;
+    let mut marks = Vec::new();
+    for (start_ms, word) in boundaries {
+        let Some(found) = text[cursor..].find(word).filter(|_| !word.is_empty()) else {
+            continue;
+        };
+        let start = cursor + found;
+        cursor = start + word.len();
+        marks.push(WordMark {
+            start_ms,
+            start,
+            end: cursor,
+        });
+    }
+    marks
+}
+
+/// Word timeline of the current Speak; emits progress as playback moves through it.
+#[derive(Default)]
+pub(super) struct Timeline {
+    /// Locates spoken words in the text passed to Speak.
+    aligner: Aligner,
+    words: Vec<TimelineWord>,
+    /// Index in `words` of the first word of each chunk pushed so far.
+    chunk_starts: Vec<usize>,
+    chunks: usize,
+    /// Sentence starts of every chunk of the read (see `set_layout`).
+    layout: Vec<Vec<SegmentStart>>,
+    /// Sentences of the chunks pushed so far, in order.
+    segments: Vec<TimelineSegment>,
+    current: Option<usize>,
+    notifier: Option<ProgressNotifier>,
+    /// Provider of the read, until it is recorded in the reading stats.
+    provider: Option<TtsProvider>,
+    /// Words reached and furthest position played, for the reading stats.
+    spoken_words: usize,
+    listened_ms: u64,
+}
+
+impl Timeline {
+    pub fn set_notifier(&mut self, notifier: ProgressNotifier) {
+        self.notifier = Some(notifier);
+    }
+
+    /// Starts a new timeline for `source`.
+    pub fn reset(&mut self, source: &str) {
+        self.finish(false);
+        self.aligner = Aligner::new(source);
+        self.words.clear();
+        self.chunk_starts.clear();
+        self.chunks = 0;
+        self.layout.clear();
+        self.segments.clear();
+        self.current = None;
+    }
+
+    /// Sets the number of chunks the read is synthesized in.
+    pub fn set_chunks(&mut self, chunks: usize) {
+        self.chunks = chunks;
+    }
+
+    /// Sets the provider speaking the read, recorded in the reading stats when it ends.
+    pub fn set_provider(&mut self, provider: TtsProvider) {
+        self.provider = Some(provider);
+    }
+
+    /// Records the read in the reading stats once: every word when it `completed`, otherwise the
+    /// words reached before it was stopped.
+    pub fn finish(&mut self, completed: bool) {
+        let Some(provider) = self.provider.take() else {
+            return;
+        };
+        let words = if completed {
+            self.words.len()
+        } else {
+            self.spoken_words
+        };
+        reading_stats::record(provider.name(), words, self.listened_ms);
+        self.spoken_words = 0;
+        self.listened_ms = 0;
+    }
+
+    /// Sets where the sentences of each chunk start. Chunks without a layout count as one sentence,
+    /// and the first chunk starts a paragraph.
+    pub fn set_layout(&mut self, layout: Vec<Vec<SegmentStart>>) {
+        self.layout = layout;
+    }
+
+    /// Adds the words of a chunk whose audio starts at `offset_ms` on the playback timeline.
+    pub fn push_chunk(&mut self, chunk_text: &str, marks: &[WordMark], offset_ms: u64) {
+        let index = self.chunk_starts.len();
+        let single = [SegmentStart {
+            offset: 0,
+            paragraph: index == 0,
+        }];
+        let starts = self.layout.get(index).map_or(&single[..], Vec::as_slice);
+        for start in starts {
+            // The first sentence starts with the chunk audio, before any leading silence.
+            let start_ms = if start.offset == 0 {
+                Some(offset_ms)
+            } else {
+                marks
+                    .iter()
+                    .find(|mark| mark.start >= start.offset)
+                    .map(|mark| offset_ms + mark.start_ms)
+            };
+            if let Some(start_ms) = start_ms {
+                self.segments.push(TimelineSegment {
+                    start_ms,
+                    paragraph: start.paragraph,
+                });
+            }
+        }
+        self.chunk_starts.push(self.words.len());
+        for mark in marks {
+            let Some(word) = chunk_text.get(mark.start..mark.end) else {
+                continue;
+            };
+            if let Some((char_start, char_end)) = self.aligner.find(word) {
+                self.words.push(TimelineWord {
+                    start_ms: offset_ms + mark.start_ms,
+                    char_start,
+                    char_end,
+                });
+            }
+        }
+    }
+
+    pub fn words(&self) -> Vec<TimelineWord> {
+        self.words.clone()
+    }
+
+    /// Starts of the sentences (or paragraphs) synthesized so far.
+    fn segment_starts(&self, unit: SegmentUnit) -> impl Iterator<Item = u64> + '_ {
+        self.segments
+            .iter()
+            .filter(move |segment| unit == SegmentUnit::Sentence || segment.paragraph)
+            .map(|segment| segment.start_ms)
+    }
+
+    /// Start of the segment after the one playing at `position_ms`; `None` past the last one
+    /// synthesized so far.
+    pub fn next_segment(&self, position_ms: u64, unit: SegmentUnit) -> Option<u64> {
+        self.segment_starts(unit).find(|start| *start > position_ms)
+    }
+
+    /// Start of the segment playing at `position_ms`, or of the one before it when playback is
+    /// still near its start (as media players do for "previous").
+    pub fn prev_segment(&self, position_ms: u64, unit: SegmentUnit) -> Option<u64> {
+        let starts: Vec<u64> = self
+            .segment_starts(unit)
+            .take_while(|start| *start <= position_ms)
+            .collect();
+        match starts.as_slice() {
+            [.., before, current] if position_ms - current < RESTART_WINDOW_MS => Some(*before),
+            [.., current] => Some(*current),
+            [] => None,
+        }
+    }
+
+    /// True when there is someone to notify and something to report.
+    pub fn is_reporting(&self) -> bool {
+        self.notifier.is_some() && !self.words.is_empty()
+    }
+
+    /// Notifies when the word at `position_ms` differs from the last one reported.
+    pub fn update(&mut self, position_ms: u64) {
+        self.listened_ms = self.listened_ms.max(position_ms);
+        let index = self
+            .words
+            .partition_point(|word| word.start_ms <= position_ms)
+            .checked_sub(1);
+        if let Some(index) = index {
+            self.spoken_words = self.spoken_words.max(index + 1);
+        }
+        if index == self.current {
+            return;
+        }
+        self.current = index;
+        let (Some(index), Some(notifier)) = (index, &self.notifier) else {
+            return;
+        };
+        let word = self.words[index];
+        // Chunks without words start where the next one does; the word is in the last of them.
+        let chunk = self
+            .chunk_starts
+            .partition_point(|start| *start <= index)
+            .saturating_sub(1);
+        notifier(&TtsProgress {
+            position_ms,
+            char_start: word.char_start,
+            char_end: word.char_end,
+            chunk,
+            chunks: self.chunks.max(self.chunk_starts.len()),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_spreads_duration_by_word_length() {
+        let marks = estimate_marks("Hi there. Go", 1000);
+        assert_eq!(marks.len(), 3);
+        assert_eq!((marks[0].start, marks[0].end), (0, 2));
+        assert_eq!(marks[0].start_ms, 0);
+        assert!(marks[1].start_ms < marks[2].start_ms && marks[2].start_ms < 1000);
+        assert_eq!(&"Hi there. Go"[marks[2].start..marks[2].end], "Go");
+    }
+
+    #[test]
+    fn test_timeline_maps_spoken_words_onto_source_text() {
+        // Markdown stripped before speaking; the emoji is two UTF-16 units.
+        let source = "# Title\n\n😀 Read **this** [link](http://example.com) now.";
+        let spoken = "Title\n😀 Read this link now.";
+        let marks = estimate_marks(spoken, 600);
+
+        let mut timeline = Timeline::default();
+        timeline.reset(source);
+        timeline.push_chunk(spoken, &marks, 1000);
+        let words = timeline.words();
+        let texts: Vec<String> = words
+            .iter()
+            .map(|w| {
+                String::from_utf16_lossy(
+                    &source.encode_utf16().collect::<Vec<_>>()[w.char_start..w.char_end],
+                )
+            })
+            .collect();
+        assert_eq!(texts, ["Title", "Read", "this", "link", "now"]);
+        assert!(words[0].start_ms >= 1000);
+    }
+
+    #[test]
+    fn test_segment_jumps_follow_sentences_and_paragraphs() {
+        let first = "One two. Three four.";
+        let second = "Five six.";
+        let mut timeline = Timeline::default();
+        timeline.reset("One two. Three four.\n\nFive six.");
+        timeline.set_layout(vec![
+            vec![
+                SegmentStart {
+                    offset: 0,
+                    paragraph: true,
+                },
+                SegmentStart {
+                    offset: 9,
+                    paragraph: false,
+                },
+            ],
+            vec![SegmentStart {
+                offset: 0,
+                paragraph: true,
+            }],
+        ]);
+        let marks = estimate_marks(first, 4000);
+        timeline.push_chunk(first, &marks, 0);
+        timeline.push_chunk(second, &estimate_marks(second, 2000), 4000);
+        let three = marks[2].start_ms;
+
+        assert_eq!(
+            timeline.next_segment(100, SegmentUnit::Sentence),
+            Some(three)
+        );
+        assert_eq!(
+            timeline.next_segment(100, SegmentUnit::Paragraph),
+            Some(4000)
+        );
+        assert_eq!(timeline.next_segment(4500, SegmentUnit::Sentence), None);
+        // Near the start of "Five six." goes back a sentence; further in restarts it.
+        assert_eq!(
+            timeline.prev_segment(4200, SegmentUnit::Sentence),
+            Some(three)
+        );
+        assert_eq!(
+            timeline.prev_segment(5800, SegmentUnit::Sentence),
+            Some(4000)
+        );
+        assert_eq!(timeline.prev_segment(4200, SegmentUnit::Paragraph), Some(0));
+    }
+
+    #[test]
+    fn test_progress_reports_the_chunk_of_the_word() {
+        let reported = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = std::sync::Arc::clone(&reported);
+        let mut timeline = Timeline::default();
+        timeline.set_notifier(Box::new(move |progress| {
+            if let Ok(mut reported) = sink.lock() {
+                reported.push((progress.chunk, progress.chunks));
+            }
+        }));
+        timeline.reset("One two. Three four.");
+        timeline.set_chunks(3);
+        timeline.push_chunk("One two.", &estimate_marks("One two.", 1000), 0);
+        timeline.push_chunk("Three four.", &estimate_marks("Three four.", 1000), 1000);
+
+        timeline.update(100);
+        timeline.update(1500);
+        assert_eq!(*reported.lock().unwrap(), [(0, 3), (1, 3)]);
+    }
+}
@@ -0,0 +1,111 @@
+//! Opt-in fine-grained playback trace for diagnosing audio stutters.
+//!
+//! When enabled (`playback_trace_enabled` in config), the audio player records sink position
+//! snapshots, buffer sizes, and device events into a fixed-size in-memory ring buffer. The buffer
+//! is returned by `dump_playback_trace` so a glitch report can include exactly what the sink did.
+//! Recording is a no-op (one atomic load) while disabled.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::util::unix_millis_now;
+
+/// Maximum number of entries kept; the oldest are dropped first.
+const TRACE_CAPACITY: usize = 2000;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static TRACE: Mutex<VecDeque<PlaybackTraceEntry>> = Mutex::new(VecDeque::new());
+
+/// One recorded playback event.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlaybackTraceEvent {
+    DeviceOpened {
+        sample_rate: u32,
+    },
+    DeviceError {
+        message: String,
+    },
+    PlaybackStarted {
+        /// Samples handed to the sink (after time-stretch).
+        buffer_samples: usize,
+        sample_rate: u32,
+        speed: f32,
+        content_ms: u64,
+    },
+    /// A synthesized segment was added to the playback queue.
+    SegmentQueued {
+        index: usize,
+        samples: usize,
+        content_ms: u64,
+    },
+    /// Sink position snapshot: content position vs raw sink output position.
+    Position {
+        content_ms: u64,
+        sink_pos_ms: u64,
+        queued_sources: usize,
+        paused: bool,
+    },
+    Paused,
+    Resumed,
+    Seek {
+        from_ms: u64,
+        to_ms: u64,
+        ok: bool,
+    },
+    SpeedChanged {
+        speed: f32,
+    },
+    VolumeChanged {
+        volume: f32,
+    },
+    Stopped,
+    DecodeError {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PlaybackTraceEntry {
+    /// Unix timestamp in milliseconds.
+    pub at_ms: u64,
+    #[serde(flatten)]
+    pub event: PlaybackTraceEvent,
+}
+
+pub fn set_enabled(enabled: bool) {
+    let was = ENABLED.swap(enabled, Ordering::Relaxed);
+    if was != enabled {
+        tracing::info!(enabled, "Playback trace toggled");
+    }
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Records an event. The closure only runs when tracing is enabled.
+pub fn record(event: impl FnOnce() -> PlaybackTraceEvent) {
+    if !is_enabled() {
+        return;
+    }
+    let entry = PlaybackTraceEntry {
+        at_ms: unix_millis_now(),
+        event: event(),
+    };
+    if let Ok(mut trace) = TRACE.lock() {
+        if trace.len() >= TRACE_CAPACITY {
+            trace.pop_front();
+        }
+        trace.push_back(entry);
+    }
+}
+
+/// Copy of the buffered entries, oldest first.
+pub fn snapshot() -> Vec<PlaybackTraceEntry> {
+    TRACE
+        .lock()
+        .map(|trace| trace.iter().cloned().collect())
+        .unwrap_or_default()
+}
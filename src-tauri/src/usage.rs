@@ -0,0 +1,321 @@
+//! TTS usage metering: characters synthesized per provider per month, a Polly cost estimate and
+//! an optional monthly character budget.
+//!
+//! The TTS worker counts every chunk a provider synthesizes (`record`; audio reused from the cache
+//! is free) and calls `check_budget` before a read. Counts live in `tts_usage.json` in the app data
+//! dir, shared by profiles since the bill is per account, keyed by UTC month. They are kept in a
+//! `store::JsonStore` and written at most every `SAVE_INTERVAL`; shutdown calls `flush`.
+//!
+//! Only Polly is billed; Microsoft voices come from the free Edge read-aloud service and are
+//! counted but cost nothing. With `usage_monthly_char_budget` set, a Polly read that would go over
+//! it is logged (`usage_budget_action` "warn", the default) or refused ("block") until
+//! `allow_usage_over_budget` lifts the block for the rest of the month.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::config::FullConfig;
+use crate::paths;
+use crate::store::JsonStore;
+use crate::tts::TtsProvider;
+use crate::util::{civil_date, unix_millis_now};
+
+const USAGE_FILE_NAME: &str = "tts_usage.json";
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Polly list prices (us-east-1) in USD per million characters, by engine.
+fn polly_price_per_million(engine: &str) -> Option<f64> {
+    match engine {
+        "standard" => Some(4.0),
+        "neural" => Some(16.0),
+        "long-form" => Some(100.0),
+        "generative" => Some(30.0),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct UsageFile {
+    /// Characters by month (`YYYY-MM`, UTC), then by meter (see `meter`).
+    months: BTreeMap<String, BTreeMap<String, u64>>,
+    /// Month in which Polly reads over budget are allowed (`allow_usage_over_budget`).
+    over_budget_month: Option<String>,
+}
+
+static USAGE: JsonStore<UsageFile> = JsonStore::new("TTS usage", usage_path, SAVE_INTERVAL);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BudgetAction {
+    Warn,
+    Block,
+}
+
+impl BudgetAction {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Warn => "warn",
+            Self::Block => "block",
+        }
+    }
+}
+
+static BUDGET: Mutex<(Option<u64>, BudgetAction)> = Mutex::new((None, BudgetAction::Warn));
+
+/// Applies `usage_monthly_char_budget` and `usage_budget_action` from the config.
+pub fn configure(cfg: &FullConfig) {
+    let action = match cfg.usage_budget_action.as_deref() {
+        Some("block") => BudgetAction::Block,
+        _ => BudgetAction::Warn,
+    };
+    if let Ok(mut budget) = BUDGET.lock() {
+        *budget = (cfg.usage_monthly_char_budget.filter(|b| *b > 0), action);
+    }
+}
+
+fn budget() -> (Option<u64>, BudgetAction) {
+    BUDGET
+        .lock()
+        .map(|b| *b)
+        .unwrap_or((None, BudgetAction::Warn))
+}
+
+fn usage_path() -> Result<PathBuf, String> {
+    Ok(paths::get_app_data_dir()?.join(USAGE_FILE_NAME))
+}
+
+/// Writes counts not saved yet.
+pub fn flush() {
+    USAGE.flush();
+}
+
+/// The meter usage is counted under: the provider name, with the engine for Polly
+/// (`polly/neural`), whose price depends on it.
+pub fn meter(provider: TtsProvider, engine: Option<&str>) -> String {
+    match engine {
+        Some(engine) => format!("{}/{engine}", provider.name()),
+        None => provider.name().to_string(),
+    }
+}
+
+fn split_meter(meter: &str) -> (&str, Option<&str>) {
+    match meter.split_once('/') {
+        Some((provider, engine)) => (provider, Some(engine)),
+        None => (meter, None),
+    }
+}
+
+fn estimated_cost(meter: &str, characters: u64) -> Option<f64> {
+    match split_meter(meter) {
+        ("polly", Some(engine)) => {
+            polly_price_per_million(engine).map(|price| characters as f64 * price / 1_000_000.0)
+        }
+        _ => None,
+    }
+}
+
+fn billed_characters(meters: Option<&BTreeMap<String, u64>>) -> u64 {
+    meters
+        .into_iter()
+        .flatten()
+        .filter(|(meter, _)| split_meter(meter).0 == TtsProvider::Polly.name())
+        .map(|(_, characters)| characters)
+        .sum()
+}
+
+/// Counts `characters` synthesized under `meter` this month.
+pub fn record(meter: &str, characters: usize) {
+    if characters == 0 {
+        return;
+    }
+    let month = current_month();
+    let result = USAGE.update(|usage| {
+        *usage
+            .months
+            .entry(month)
+            .or_default()
+            .entry(meter.to_string())
+            .or_default() += characters as u64;
+    });
+    if let Err(e) = result {
+        warn!(error = %e, "Failed to record TTS usage");
+    }
+}
+
+/// Checks the monthly budget before a read of `characters` with `provider`. Errs when the read
+/// would go over a blocking budget.
+pub fn check_budget(provider: TtsProvider, characters: usize) -> Result<(), String> {
+    if provider != TtsProvider::Polly {
+        return Ok(());
+    }
+    let (Some(limit), action) = budget() else {
+        return Ok(());
+    };
+    let month = current_month();
+    let (used, allowed) = USAGE
+        .read(|usage| {
+            (
+                billed_characters(usage.months.get(&month)),
+                usage.over_budget_month.as_deref() == Some(month.as_str()),
+            )
+        })
+        .unwrap_or((0, false));
+    let after = used + characters as u64;
+    if after <= limit {
+        return Ok(());
+    }
+    if action == BudgetAction::Block && !allowed {
+        return Err(format!(
+            "Monthly Polly budget of {limit} characters reached ({used} used, this read needs \
+             {characters}). Allow reads over budget in Settings to continue."
+        ));
+    }
+    warn!(
+        limit,
+        used, characters, "Read goes over the monthly Polly character budget"
+    );
+    Ok(())
+}
+
+/// Characters counted under one meter in a month.
+#[derive(Debug, Clone, Serialize)]
+pub struct MeterUsage {
+    pub provider: String,
+    /// Polly engine (`neural`, `standard`, ...); `None` for other providers.
+    pub engine: Option<String>,
+    pub characters: u64,
+    /// `None` when the provider is not billed.
+    pub estimated_cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonthUsage {
+    /// `YYYY-MM`, UTC.
+    pub month: String,
+    pub meters: Vec<MeterUsage>,
+    /// Characters that count against the budget (Polly).
+    pub billed_characters: u64,
+    pub estimated_cost_usd: f64,
+}
+
+/// Returned by `get_tts_usage`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TtsUsage {
+    pub current_month: String,
+    /// Newest first.
+    pub months: Vec<MonthUsage>,
+    pub budget_characters: Option<u64>,
+    /// `warn` or `block`.
+    pub budget_action: &'static str,
+    /// Whether reads over a blocking budget are allowed this month.
+    pub over_budget_allowed: bool,
+}
+
+fn month_usage(month: &str, meters: &BTreeMap<String, u64>) -> MonthUsage {
+    let meters: Vec<MeterUsage> = meters
+        .iter()
+        .map(|(meter, &characters)| {
+            let (provider, engine) = split_meter(meter);
+            MeterUsage {
+                provider: provider.to_string(),
+                engine: engine.map(str::to_string),
+                characters,
+                estimated_cost_usd: estimated_cost(meter, characters),
+            }
+        })
+        .collect();
+    MonthUsage {
+        month: month.to_string(),
+        billed_characters: meters
+            .iter()
+            .filter(|m| m.provider == TtsProvider::Polly.name())
+            .map(|m| m.characters)
+            .sum(),
+        estimated_cost_usd: meters.iter().filter_map(|m| m.estimated_cost_usd).sum(),
+        meters,
+    }
+}
+
+/// `YYYY-MM` (UTC) of a Unix time.
+fn month_of(unix_secs: u64) -> String {
+    let (year, month, _) = civil_date(unix_secs);
+    format!("{year:04}-{month:02}")
+}
+
+fn current_month() -> String {
+    month_of(unix_millis_now() / 1000)
+}
+
+// --- Commands ---
+
+/// Characters synthesized per provider and month, with the Polly cost estimate and the budget.
+#[tauri::command]
+pub fn get_tts_usage() -> TtsUsage {
+    let current_month = current_month();
+    let (budget_characters, action) = budget();
+    let (months, over_budget_allowed) = USAGE
+        .read(|usage| {
+            let months = usage
+                .months
+                .iter()
+                .rev()
+                .map(|(month, meters)| month_usage(month, meters))
+                .collect();
+            let allowed = usage.over_budget_month.as_deref() == Some(current_month.as_str());
+            (months, allowed)
+        })
+        .unwrap_or_default();
+    TtsUsage {
+        current_month,
+        months,
+        budget_characters,
+        budget_action: action.name(),
+        over_budget_allowed,
+    }
+}
+
+/// Allows (or again blocks) Polly reads over a blocking budget for the rest of this month.
+#[tauri::command]
+pub fn allow_usage_over_budget(allowed: bool) -> Result<(), String> {
+    let month = current_month();
+    USAGE.update_now(|usage| usage.over_budget_month = allowed.then(|| month.clone()))?;
+    if allowed {
+        info!(month = %month, "Polly reads over budget allowed");
+    } else {
+        debug!(month = %month, "Polly budget enforced again");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_month_of_formats_the_utc_month() {
+        assert_eq!(month_of(0), "1970-01");
+        // 2024-02-29 23:59:59 and the second after, UTC.
+        assert_eq!(month_of(1_709_251_199), "2024-02");
+        assert_eq!(month_of(1_709_251_200), "2024-03");
+    }
+
+    #[test]
+    fn test_only_polly_characters_are_billed_and_priced() {
+        let meters = BTreeMap::from([
+            (meter(TtsProvider::Polly, Some("neural")), 500_000),
+            (meter(TtsProvider::Polly, Some("standard")), 250_000),
+            (meter(TtsProvider::Microsoft, None), 1_000_000),
+        ]);
+        let usage = month_usage("2024-03", &meters);
+        assert_eq!(usage.billed_characters, 750_000);
+        assert!((usage.estimated_cost_usd - 9.0).abs() < 1e-9);
+        assert_eq!(billed_characters(Some(&meters)), 750_000);
+        let microsoft = usage.meters.iter().find(|m| m.provider == "microsoft");
+        assert_eq!(microsoft.and_then(|m| m.estimated_cost_usd), None);
+    }
+}
@@ -0,0 +1,88 @@
+//! Small helpers shared across modules.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tempfile::NamedTempFile;
+
+/// Milliseconds since the Unix epoch (0 if the system clock is set before it).
+pub fn unix_millis_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Year, month and day (UTC) of a Unix time.
+pub fn civil_date(unix_secs: u64) -> (i64, i64, i64) {
+    // Days to civil date (Howard Hinnant's algorithm).
+    let z = (unix_secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Whether `a` and `b` are equal, compared in time that depends only on their lengths (for
+/// secrets such as API tokens).
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Writes `data` to `path` through a temp file in the same directory that is then renamed over
+/// it, so readers (and a crash mid-write) never see a partly written file. Creates the directory
+/// if needed.
+pub fn write_atomic(path: &Path, data: impl AsRef<[u8]>) -> Result<(), String> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| format!("{} has no parent directory", path.display()))?;
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create directory {}: {e}", dir.display()))?;
+    let mut file = NamedTempFile::new_in(dir)
+        .map_err(|e| format!("Failed to create temp file in {}: {e}", dir.display()))?;
+    file.write_all(data.as_ref())
+        .and_then(|()| file.as_file().sync_all())
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    file.persist(path)
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_date_handles_leap_days() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        // 2024-02-29 23:59:59 and the second after, UTC.
+        assert_eq!(civil_date(1_709_251_199), (2024, 2, 29));
+        assert_eq!(civil_date(1_709_251_200), (2024, 3, 1));
+    }
+
+    #[test]
+    fn test_constant_time_eq_compares_length_and_bytes() {
+        assert!(constant_time_eq(b"abc123", b"abc123"));
+        assert!(!constant_time_eq(b"abc124", b"abc123"));
+        assert!(!constant_time_eq(b"abc", b"abc123"));
+        assert!(constant_time_eq(b"", b""));
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_the_file_and_leaves_no_temp_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("data.json");
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        let files = fs::read_dir(path.parent().unwrap()).unwrap().count();
+        assert_eq!(files, 1);
+    }
+}
@@ -0,0 +1,143 @@
+//! On-disk cache shared by the voice lists (Piper catalog, Polly, Microsoft).
+//!
+//! A list is served from the cache while it is younger than `CACHE_TTL`, fetched otherwise (or
+//! when a refresh is forced), and the fresh copy is written back. When the fetch fails, e.g.
+//! offline, the cached list is returned however old it is, so the voice pickers keep working.
+
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::{debug, error, warn};
+
+use crate::offline;
+use crate::paths;
+
+/// How long a cached voice list is used before it is fetched again.
+const CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Cache files, in the app cache dir.
+pub const PIPER_CACHE_FILE: &str = "voices.json";
+pub const POLLY_CACHE_FILE: &str = "polly_voices.json";
+pub const MICROSOFT_CACHE_FILE: &str = "microsoft_voices.json";
+const CACHE_FILES: [&str; 3] = [PIPER_CACHE_FILE, POLLY_CACHE_FILE, MICROSOFT_CACHE_FILE];
+
+fn cache_path(file_name: &str) -> Result<PathBuf, String> {
+    Ok(paths::get_cache_dir()?.join(file_name))
+}
+
+/// Returns the list cached in `file_name`, or the result of `fetch` when the cache is missing,
+/// older than `CACHE_TTL` or `force_refresh` is set. A failed fetch falls back to the cached list.
+pub async fn cached_or_fetch<T, F, Fut>(
+    file_name: &str,
+    force_refresh: bool,
+    fetch: F,
+) -> Result<T, String>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    let path = cache_path(file_name)?;
+    if offline::is_offline() {
+        // No refresh; any cached list will do.
+        return match read_cache(&path, None) {
+            Some(list) if !force_refresh => Ok(list),
+            _ => Err(offline::OFFLINE_ERROR.to_string()),
+        };
+    }
+    if !force_refresh {
+        if let Some(list) = read_cache(&path, Some(CACHE_TTL)) {
+            debug!(file = %file_name, "Using cached voice list");
+            return Ok(list);
+        }
+    }
+    match fetch().await {
+        Ok(list) => {
+            if let Err(e) = write_cache(&path, &list) {
+                error!(file = %file_name, error = %e, "Failed to cache voice list");
+            }
+            Ok(list)
+        }
+        Err(e) => match read_cache(&path, None) {
+            Some(list) => {
+                warn!(file = %file_name, error = %e, "Voice list fetch failed, using stale cache");
+                Ok(list)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// Reads a cached list, if there is one no older than `max_age` (any age when `None`).
+fn read_cache<T: DeserializeOwned>(path: &Path, max_age: Option<Duration>) -> Option<T> {
+    if let Some(max_age) = max_age {
+        let age = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())?;
+        if age >= max_age {
+            return None;
+        }
+    }
+    let content = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(list) => Some(list),
+        Err(e) => {
+            warn!(path = %path.display(), error = %e, "Ignoring unreadable voice list cache");
+            None
+        }
+    }
+}
+
+fn write_cache<T: Serialize>(path: &Path, list: &T) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create cache directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(list)
+        .map_err(|e| format!("Failed to serialize voices for cache: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write cache file: {}", e))?;
+    debug!(path = %path.display(), "Cached voice list");
+    Ok(())
+}
+
+/// Removes all cached voice lists. Returns the bytes freed.
+pub fn clear() -> Result<u64, String> {
+    let mut freed = 0;
+    for file_name in CACHE_FILES {
+        let path = cache_path(file_name)?;
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        if size > 0 {
+            fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove {}: {e}", path.display()))?;
+            freed += size;
+        }
+    }
+    Ok(freed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_cache_is_only_used_without_max_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache").join(POLLY_CACHE_FILE);
+        assert_eq!(read_cache::<Vec<String>>(&path, None), None);
+
+        write_cache(&path, &vec!["Joanna".to_string()]).unwrap();
+        assert_eq!(
+            read_cache::<Vec<String>>(&path, Some(CACHE_TTL)),
+            Some(vec!["Joanna".to_string()])
+        );
+        assert_eq!(read_cache::<Vec<String>>(&path, Some(Duration::ZERO)), None);
+        assert_eq!(
+            read_cache::<Vec<String>>(&path, None),
+            Some(vec!["Joanna".to_string()])
+        );
+    }
+}
@@ -1,49 +1,151 @@
 //! Voice download functionality for Piper TTS.
 //!
-//! Downloads voice model files (.onnx and .onnx.json) from HuggingFace.
+//! Downloads voice model files (.onnx and .onnx.json) from HuggingFace, failing over to the next
+//! mirror (`voices::mirrors`) when one is unreachable or serves a file that doesn't match the
+//! catalog checksum. Both files are fetched concurrently into `.part` files that are renamed once
+//! verified; a failed transfer is retried and, like a later download of the same voice, resumes
+//! from the bytes already on disk with an HTTP range request. Each running download has a
+//! progress entry (`active_downloads`) and can be cancelled by voice key (`cancel_download`).
+//!
+//! The checksum is MD5 because that is the only digest the Piper catalog (`voices.json`)
+//! publishes (`md5_digest`). It catches corrupt and truncated transfers and mirrors serving
+//! different files; it is not a defense against a mirror forging a matching file.
 
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::time::Duration;
 
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-use crate::voices::VoiceInfo;
+use crate::offline;
+use crate::paths;
+use crate::storage;
+use crate::tasks::{CancelToken, TaskHandle};
+use crate::voices::{self, validate, FileInfo, VoiceInfo};
 
-const HUGGINGFACE_BASE_URL: &str = "https://huggingface.co/rhasspy/piper-voices/resolve/main";
+/// Attempts per file before a download fails; later attempts resume the partial file.
+const MAX_ATTEMPTS: u32 = 3;
+/// Wait before the second attempt, doubled for each further one.
+const RETRY_DELAY: Duration = Duration::from_secs(2);
 
-static DOWNLOAD_PROGRESS: Mutex<Option<DownloadProgress>> = Mutex::new(None);
+/// Running downloads by voice key.
+static DOWNLOADS: Mutex<BTreeMap<String, DownloadProgress>> = Mutex::new(BTreeMap::new());
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct DownloadProgress {
     pub voice_key: String,
+    /// Background task running the download (see `tasks`).
+    pub task_id: String,
+    pub downloaded_bytes: u64,
+    pub total_bytes: u64,
+    pub files: Vec<FileProgress>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FileProgress {
+    pub name: String,
     pub downloaded_bytes: u64,
     pub total_bytes: u64,
-    pub current_file: String,
+}
+
+/// Registers a voice's progress entry for the lifetime of the guard.
+struct ProgressEntry {
+    voice_key: String,
+}
+
+impl ProgressEntry {
+    fn register(voice_key: &str, task_id: &str, files: Vec<FileProgress>) -> Result<Self, String> {
+        let mut downloads = DOWNLOADS
+            .lock()
+            .map_err(|_| "Download registry lock poisoned".to_string())?;
+        if downloads.contains_key(voice_key) {
+            return Err(format!("Voice {voice_key} is already downloading"));
+        }
+        let mut progress = DownloadProgress {
+            voice_key: voice_key.to_string(),
+            task_id: task_id.to_string(),
+            downloaded_bytes: 0,
+            total_bytes: 0,
+            files,
+        };
+        progress.sum_files();
+        downloads.insert(voice_key.to_string(), progress);
+        Ok(Self {
+            voice_key: voice_key.to_string(),
+        })
+    }
+
+    /// Records a file's progress. Returns the voice's downloaded and total bytes.
+    fn update(&self, file_index: usize, downloaded: u64, total: u64) -> (u64, u64) {
+        let Ok(mut downloads) = DOWNLOADS.lock() else {
+            return (0, 0);
+        };
+        let Some(progress) = downloads.get_mut(&self.voice_key) else {
+            return (0, 0);
+        };
+        if let Some(file) = progress.files.get_mut(file_index) {
+            file.downloaded_bytes = downloaded;
+            if total > 0 {
+                file.total_bytes = total;
+            }
+        }
+        progress.sum_files();
+        (progress.downloaded_bytes, progress.total_bytes)
+    }
+}
+
+impl Drop for ProgressEntry {
+    fn drop(&mut self) {
+        if let Ok(mut downloads) = DOWNLOADS.lock() {
+            downloads.remove(&self.voice_key);
+        }
+    }
+}
+
+impl DownloadProgress {
+    fn sum_files(&mut self) {
+        self.downloaded_bytes = self.files.iter().map(|f| f.downloaded_bytes).sum();
+        self.total_bytes = self.files.iter().map(|f| f.total_bytes).sum();
+    }
 }
 
 fn get_voices_base_dir() -> Result<PathBuf, String> {
-    let home = dirs::home_dir().ok_or("Could not find home directory")?;
-    Ok(home
-        .join(".local")
-        .join("share")
-        .join("insight-reader")
-        .join("voices"))
+    paths::get_voices_dir()
 }
 
 fn get_voice_directory(language: &str, voice_name: &str) -> Result<PathBuf, String> {
     Ok(get_voices_base_dir()?.join(language).join(voice_name))
 }
 
-pub fn get_current_progress() -> Option<DownloadProgress> {
-    DOWNLOAD_PROGRESS
+/// Progress of the running downloads, by voice key.
+pub fn active_downloads() -> Vec<DownloadProgress> {
+    DOWNLOADS
+        .lock()
+        .map(|downloads| downloads.values().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Task ID of the running download of `voice_key`, for cancellation.
+pub fn download_task_id(voice_key: &str) -> Option<String> {
+    DOWNLOADS
         .lock()
-        .ok()
-        .and_then(|guard| guard.clone())
+        .ok()?
+        .get(voice_key)
+        .map(|p| p.task_id.clone())
 }
 
-pub async fn download_voice(voice_key: &str, voice_info: &VoiceInfo) -> Result<PathBuf, String> {
+/// Downloads the voice files into the voices dir, reporting progress on `task` and stopping
+/// (and removing partial files) when the task is cancelled. Partial files of a failed download
+/// are kept so the next attempt resumes them.
+pub async fn download_voice(
+    voice_key: &str,
+    voice_info: &VoiceInfo,
+    task: &mut TaskHandle,
+) -> Result<PathBuf, String> {
+    offline::ensure_online()?;
     info!(voice_key = %voice_key, "Starting voice download");
 
     let voice_dir = get_voice_directory(&voice_info.language.code, voice_key)?;
@@ -63,84 +165,351 @@ pub async fn download_voice(voice_key: &str, voice_info: &VoiceInfo) -> Result<P
         .find(|(path, _)| path.ends_with(".onnx.json"))
         .ok_or_else(|| format!("No .onnx.json file found for voice {voice_key}"))?;
 
-    download_file(
-        &format!("{}/{}", HUGGINGFACE_BASE_URL, onnx_file.0),
-        &voice_dir.join(format!("{}.onnx", voice_key)),
-    )
-    .await?;
-
-    download_file(
-        &format!("{}/{}", HUGGINGFACE_BASE_URL, json_file.0),
-        &voice_dir.join(format!("{}.onnx.json", voice_key)),
-    )
-    .await?;
+    let downloads = [
+        (
+            onnx_file.0,
+            onnx_file.1,
+            voice_dir.join(format!("{}.onnx", voice_key)),
+        ),
+        (
+            json_file.0,
+            json_file.1,
+            voice_dir.join(format!("{}.onnx.json", voice_key)),
+        ),
+    ];
+    let files = downloads
+        .iter()
+        .map(|(_, info, path)| FileProgress {
+            name: file_name(path),
+            downloaded_bytes: 0,
+            total_bytes: info.size_bytes,
+        })
+        .collect();
+    let entry = ProgressEntry::register(voice_key, task.id(), files)?;
+
+    let cancel = task.token().clone();
+    let task = Mutex::new(task);
+    let client = reqwest::Client::new();
+    let mirrors = voices::mirrors();
+    let fetches =
+        downloads
+            .iter()
+            .enumerate()
+            .map(|(index, (remote_path, expected, local_path))| {
+                let (entry, task) = (&entry, &task);
+                let message = format!("Downloading {}", file_name(local_path));
+                download_file(
+                    &client,
+                    &mirrors,
+                    remote_path,
+                    local_path,
+                    expected,
+                    &cancel,
+                    move |downloaded, file_total| {
+                        let (done, total) = entry.update(index, downloaded, file_total);
+                        if total > 0 {
+                            if let Ok(mut task) = task.lock() {
+                                task.set_progress(
+                                    done as f32 / total as f32,
+                                    Some(message.clone()),
+                                );
+                            }
+                        }
+                    },
+                )
+            });
+    let result = futures_util::future::try_join_all(fetches).await;
+    drop(entry);
+
+    if let Err(e) = result {
+        if cancel.is_cancelled() {
+            info!(voice_key = %voice_key, "Voice download cancelled, removing partial files");
+            let paths = downloads.iter().map(|(_, _, path)| path.as_path());
+            remove_partial_files(&voice_dir, paths).await;
+        } else {
+            warn!(voice_key = %voice_key, error = %e, "Voice download failed, keeping partial files");
+        }
+        return Err(e);
+    }
 
     info!(
         voice_key = %voice_key,
         path = %voice_dir.display(),
-        "Voice download completed"
+        "Voice download completed, validating"
     );
+
+    let validation = {
+        let voice_key = voice_key.to_string();
+        let voice_dir = voice_dir.clone();
+        let voice_info = voice_info.clone();
+        tokio::task::spawn_blocking(move || {
+            validate::validate_downloaded_voice(&voice_key, &voice_dir, &voice_info)
+        })
+        .await
+        .map_err(|e| format!("Voice validation task failed: {}", e))?
+    };
+
+    if let Err(e) = validation {
+        warn!(voice_key = %voice_key, error = %e, "Downloaded voice failed validation, removing it");
+        if let Err(remove_err) = fs::remove_dir_all(&voice_dir).await {
+            warn!(error = %remove_err, "Failed to remove invalid voice directory");
+        }
+        return Err(e);
+    }
+
     Ok(voice_dir)
 }
 
-async fn download_file(url: &str, path: &Path) -> Result<(), String> {
-    debug!(url = %url, path = %path.display(), "Starting file download");
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
 
-    let client = reqwest::Client::new();
-    let response = client
-        .get(url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+/// `<path>.part`, where a file is downloaded before it is renamed into place.
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// Removes the `.part` files of `paths`, then `voice_dir` if that left it empty. Anything else in
+/// the directory (files already in place, other files) is kept.
+async fn remove_partial_files(voice_dir: &Path, paths: impl Iterator<Item = &Path>) {
+    for path in paths {
+        let part = part_path(path);
+        if let Err(e) = fs::remove_file(&part).await {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!(path = %part.display(), error = %e, "Failed to remove partial file");
+            }
+        }
+    }
+    // Fails, as intended, unless the directory is empty.
+    let _ = fs::remove_dir(voice_dir).await;
+}
+
+/// Why a download attempt failed.
+enum AttemptError {
+    /// Worth another attempt (network error, server error, truncated body).
+    Retry(String),
+    /// Cancelled, or an error another attempt won't fix.
+    Fatal(String),
+}
+
+/// Downloads `remote_path` to `path` through `<path>.part`, trying each mirror in turn. On each
+/// mirror a failed transfer is retried up to `MAX_ATTEMPTS` times, resuming the partial file. The
+/// finished file is checked against the catalog size and MD5 digest; a mismatch discards it and
+/// moves on to the next mirror. A file already in place that passes the check is kept. Calls
+/// `on_progress(downloaded, total)` per chunk. Returns the file size.
+async fn download_file<F>(
+    client: &reqwest::Client,
+    mirrors: &[String],
+    remote_path: &str,
+    path: &Path,
+    expected: &FileInfo,
+    cancel: &CancelToken,
+    on_progress: F,
+) -> Result<u64, String>
+where
+    F: Fn(u64, u64),
+{
+    let expected_size = expected.size_bytes;
+    if expected_size > 0 && verify_file(path, expected).await.is_ok() {
+        debug!(path = %path.display(), "File already downloaded");
+        on_progress(expected_size, expected_size);
+        return Ok(expected_size);
+    }
 
-    if !response.status().is_success() {
+    let part = part_path(path);
+    let mut last_error = "No voice mirrors configured".to_string();
+    for mirror in mirrors {
+        let url = format!("{mirror}/{remote_path}");
+        let size =
+            match download_with_retries(client, &url, &part, expected_size, cancel, &on_progress)
+                .await
+            {
+                Ok(size) => size,
+                Err(AttemptError::Fatal(e)) if cancel.is_cancelled() => return Err(e),
+                Err(AttemptError::Retry(e) | AttemptError::Fatal(e)) => {
+                    warn!(mirror = %mirror, error = %e, "Voice mirror failed, trying the next one");
+                    last_error = e;
+                    continue;
+                }
+            };
+        if let Err(e) = verify_file(&part, expected).await {
+            warn!(mirror = %mirror, error = %e, "Downloaded file failed verification, discarding it");
+            let _ = fs::remove_file(&part).await;
+            last_error = e;
+            continue;
+        }
+        fs::rename(&part, path)
+            .await
+            .map_err(|e| format!("Failed to move {} into place: {e}", part.display()))?;
+        debug!(path = %path.display(), bytes = size, "File downloaded successfully");
+        return Ok(size);
+    }
+    Err(last_error)
+}
+
+/// Downloads `url` into `part`, retrying up to `MAX_ATTEMPTS` times and resuming each time.
+async fn download_with_retries<F>(
+    client: &reqwest::Client,
+    url: &str,
+    part: &Path,
+    expected_size: u64,
+    cancel: &CancelToken,
+    on_progress: &F,
+) -> Result<u64, AttemptError>
+where
+    F: Fn(u64, u64),
+{
+    let mut attempt = 1;
+    loop {
+        match download_attempt(client, url, part, expected_size, cancel, on_progress).await {
+            Err(AttemptError::Retry(e)) if attempt < MAX_ATTEMPTS => {
+                let delay = RETRY_DELAY * 2u32.pow(attempt - 1);
+                warn!(url = %url, attempt, error = %e, "Download failed, retrying");
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = cancel.cancelled() => {
+                        return Err(AttemptError::Fatal("Download cancelled".to_string()));
+                    }
+                }
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Checks `path` against the catalog size and MD5 digest (each skipped when the catalog has none).
+async fn verify_file(path: &Path, expected: &FileInfo) -> Result<(), String> {
+    let size = fs::metadata(path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?
+        .len();
+    if expected.size_bytes > 0 && size != expected.size_bytes {
+        return Err(format!(
+            "{} is {size} bytes, expected {}",
+            path.display(),
+            expected.size_bytes
+        ));
+    }
+    let digest = expected.md5_digest.trim();
+    if digest.is_empty() {
+        return Ok(());
+    }
+    let hash_path = path.to_path_buf();
+    let actual = tokio::task::spawn_blocking(move || validate::md5_hex(&hash_path))
+        .await
+        .map_err(|e| format!("Checksum task failed: {e}"))?
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    if !actual.eq_ignore_ascii_case(digest) {
         return Err(format!(
-            "Failed to fetch {}: HTTP {}",
-            url,
-            response.status()
+            "{} has checksum {actual}, expected {digest}",
+            path.display()
         ));
     }
+    Ok(())
+}
 
-    let total_size = response.content_length().unwrap_or(0);
+/// One request for `url`, appending to `part` from its current length when the server supports
+/// ranges. `expected_size` (0 if unknown) stands in for a missing Content-Length. Returns the
+/// complete size.
+async fn download_attempt<F>(
+    client: &reqwest::Client,
+    url: &str,
+    part: &Path,
+    expected_size: u64,
+    cancel: &CancelToken,
+    on_progress: &F,
+) -> Result<u64, AttemptError>
+where
+    F: Fn(u64, u64),
+{
+    use futures_util::stream::StreamExt;
 
-    let mut file = fs::File::create(path)
-        .await
-        .map_err(|e| format!("Failed to create file {}: {}", path.display(), e))?;
+    let offset = fs::metadata(part).await.map(|m| m.len()).unwrap_or(0);
+    debug!(url = %url, path = %part.display(), offset, "Starting file download");
 
-    let mut downloaded: u64 = 0;
+    let mut request = client.get(url);
+    if offset > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={offset}-"));
+    }
+    let response = tokio::select! {
+        response = request.send() => response,
+        _ = cancel.cancelled() => {
+            return Err(AttemptError::Fatal("Download cancelled".to_string()));
+        }
+    }
+    .map_err(|e| AttemptError::Retry(format!("Failed to fetch {}: {}", url, e)))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file is stale (or longer than the remote file); start over.
+        let _ = fs::remove_file(part).await;
+        return Err(AttemptError::Retry(format!(
+            "Failed to resume {}: HTTP {}",
+            url, status
+        )));
+    }
+    if !status.is_success() {
+        let message = format!("Failed to fetch {}: HTTP {}", url, status);
+        return Err(if status.is_server_error() {
+            AttemptError::Retry(message)
+        } else {
+            AttemptError::Fatal(message)
+        });
+    }
 
-    let mut stream = response.bytes_stream();
+    let resumed = status == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut downloaded = if resumed { offset } else { 0 };
+    let total_size = response
+        .content_length()
+        .map(|len| len + downloaded)
+        .unwrap_or(expected_size);
+    if resumed {
+        debug!(url = %url, offset, "Resuming download");
+    }
 
-    use futures_util::stream::StreamExt;
+    let mut file = if resumed {
+        fs::OpenOptions::new().append(true).open(part).await
+    } else {
+        fs::File::create(part).await
+    }
+    .map_err(|e| AttemptError::Fatal(format!("Failed to create file {}: {}", part.display(), e)))?;
 
-    while let Some(chunk_result) = stream.next().await {
-        let chunk = chunk_result.map_err(|e| format!("Download error: {}", e))?;
+    let mut stream = response.bytes_stream();
+    loop {
+        let next = tokio::select! {
+            chunk = stream.next() => chunk,
+            _ = cancel.cancelled() => {
+                return Err(AttemptError::Fatal("Download cancelled".to_string()));
+            }
+        };
+        let Some(chunk_result) = next else {
+            break;
+        };
+        let chunk =
+            chunk_result.map_err(|e| AttemptError::Retry(format!("Download error: {}", e)))?;
         file.write_all(&chunk)
             .await
-            .map_err(|e| format!("Failed to write to file: {}", e))?;
+            .map_err(|e| AttemptError::Fatal(format!("Failed to write to file: {}", e)))?;
         downloaded += chunk.len() as u64;
-
-        if total_size > 0 {
-            if let Ok(mut guard) = DOWNLOAD_PROGRESS.lock() {
-                if let Some(progress) = guard.as_mut() {
-                    progress.downloaded_bytes = downloaded;
-                    progress.total_bytes = total_size;
-                }
-            }
-        }
+        on_progress(downloaded, total_size);
     }
 
     file.flush()
         .await
-        .map_err(|e| format!("Failed to flush file: {}", e))?;
-
-    debug!(
-        path = %path.display(),
-        bytes = downloaded,
-        "File downloaded successfully"
-    );
+        .map_err(|e| AttemptError::Fatal(format!("Failed to flush file: {}", e)))?;
 
-    Ok(())
+    if total_size > 0 && downloaded < total_size {
+        return Err(AttemptError::Retry(format!(
+            "Download of {} ended early ({downloaded} of {total_size} bytes)",
+            url
+        )));
+    }
+    Ok(downloaded)
 }
 
 pub fn list_downloaded_voices() -> Result<Vec<DownloadedVoice>, String> {
@@ -198,6 +567,7 @@ pub fn list_downloaded_voices() -> Result<Vec<DownloadedVoice>, String> {
                 voices.push(DownloadedVoice {
                     key: voice_name.to_string(),
                     language: language.to_string(),
+                    size_bytes: storage::dir_size(&voice_path),
                     path: voice_path,
                 });
             }
@@ -212,6 +582,30 @@ pub struct DownloadedVoice {
     pub key: String,
     pub language: String,
     pub path: PathBuf,
+    /// Disk space used by the voice directory.
+    pub size_bytes: u64,
+}
+
+/// Finds a downloaded voice by key.
+pub fn find_downloaded_voice(voice_key: &str) -> Result<Option<DownloadedVoice>, String> {
+    Ok(list_downloaded_voices()?
+        .into_iter()
+        .find(|v| v.key == voice_key))
+}
+
+/// Removes a downloaded voice's directory (and its language directory once empty). Returns the
+/// bytes freed.
+pub fn delete_downloaded_voice(voice_key: &str) -> Result<u64, String> {
+    let voice = find_downloaded_voice(voice_key)?
+        .ok_or_else(|| format!("Voice {voice_key} is not downloaded"))?;
+    std::fs::remove_dir_all(&voice.path)
+        .map_err(|e| format!("Failed to delete voice {voice_key}: {e}"))?;
+    if let Some(lang_dir) = voice.path.parent() {
+        // Only succeeds when no other voice of the language is left.
+        let _ = std::fs::remove_dir(lang_dir);
+    }
+    info!(voice_key = %voice_key, freed_bytes = voice.size_bytes, "Deleted voice");
+    Ok(voice.size_bytes)
 }
 
 #[cfg(test)]
@@ -224,6 +618,63 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_cancel_removes_only_partial_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let voice_dir = dir.path().join("en_US-test-low");
+        std::fs::create_dir(&voice_dir).unwrap();
+        let onnx = voice_dir.join("en_US-test-low.onnx");
+        let json = voice_dir.join("en_US-test-low.onnx.json");
+        std::fs::write(part_path(&onnx), b"partial").unwrap();
+        std::fs::write(&json, b"{}").unwrap();
+
+        let paths = [onnx.as_path(), json.as_path()];
+        tauri::async_runtime::block_on(remove_partial_files(&voice_dir, paths.into_iter()));
+        assert!(!part_path(&onnx).exists());
+        assert!(json.exists(), "a finished file is kept");
+
+        std::fs::remove_file(&json).unwrap();
+        std::fs::write(part_path(&json), b"partial").unwrap();
+        tauri::async_runtime::block_on(remove_partial_files(&voice_dir, paths.into_iter()));
+        assert!(!voice_dir.exists(), "an emptied directory is removed");
+    }
+
+    #[test]
+    fn test_progress_entry_per_voice() {
+        let file = |name: &str, total_bytes| FileProgress {
+            name: name.to_string(),
+            downloaded_bytes: 0,
+            total_bytes,
+        };
+        let entry = ProgressEntry::register(
+            "xx_XX-progress-test",
+            "task1",
+            vec![file("a.onnx", 100), file("a.onnx.json", 10)],
+        )
+        .unwrap();
+        assert!(ProgressEntry::register("xx_XX-progress-test", "task2", Vec::new()).is_err());
+        assert_eq!(entry.update(0, 40, 0), (40, 110));
+        assert_eq!(entry.update(1, 10, 10), (50, 110));
+        assert_eq!(
+            download_task_id("xx_XX-progress-test").as_deref(),
+            Some("task1")
+        );
+        drop(entry);
+        assert_eq!(download_task_id("xx_XX-progress-test"), None);
+    }
+
+    #[test]
+    fn test_part_path_appends_the_part_extension() {
+        assert_eq!(
+            part_path(Path::new("/v/a.onnx")),
+            PathBuf::from("/v/a.onnx.part")
+        );
+        assert_eq!(
+            part_path(Path::new("/v/a.onnx.json")),
+            PathBuf::from("/v/a.onnx.json.part")
+        );
+    }
+
     #[test]
     fn test_get_voice_directory() {
         let result = get_voice_directory("en", "en_US-lessac-medium");
@@ -1,20 +1,61 @@
 //! Voice listing functionality for Piper and Polly TTS providers.
 //!
 //! This module handles fetching and managing available voices from:
-//! - Piper: Fetches from piper-voices.com API with local caching
+//! - Piper: Fetches the voices.json catalog from the first mirror that answers (see `mirrors`)
 //! - Polly: Uses AWS SDK to list available voices
+//! - Microsoft: Lists the Edge TTS voices
+//! - Custom server: Asks the local voice-cloning server for its speakers
+//!
+//! The Piper, Polly and Microsoft lists go through the shared on-disk cache (`cache`), which also
+//! keeps them available offline.
 
+pub mod cache;
 pub mod download;
+pub mod validate;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
-use tracing::{debug, error, trace};
+use std::sync::Mutex;
+use tracing::{debug, trace, warn};
+
+use crate::config::FullConfig;
+
+/// Base URLs serving the `rhasspy/piper-voices` tree, in failover order. Mirrors from the
+/// `voice_mirrors` config are tried before these.
+const DEFAULT_MIRRORS: [&str; 2] = [
+    "https://huggingface.co/rhasspy/piper-voices/resolve/main",
+    "https://hf-mirror.com/rhasspy/piper-voices/resolve/main",
+];
+const CATALOG_FILE: &str = "voices.json";
+
+static CONFIGURED_MIRRORS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Applies `voice_mirrors` from the config.
+pub fn configure(cfg: &FullConfig) {
+    if let Ok(mut mirrors) = CONFIGURED_MIRRORS.lock() {
+        *mirrors = cfg
+            .voice_mirrors
+            .iter()
+            .flatten()
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty())
+            .collect();
+    }
+}
 
-const PIPER_VOICES_API_URL: &str =
-    "https://huggingface.co/rhasspy/piper-voices/resolve/main/voices.json";
-const CACHE_FILE_NAME: &str = "voices.json";
+/// Mirror base URLs to try for voice files and the catalog: configured ones, then the defaults.
+pub fn mirrors() -> Vec<String> {
+    let mut mirrors = CONFIGURED_MIRRORS
+        .lock()
+        .map(|m| m.clone())
+        .unwrap_or_default();
+    for url in DEFAULT_MIRRORS {
+        if !mirrors.iter().any(|m| m == url) {
+            mirrors.push(url.to_string());
+        }
+    }
+    mirrors
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoiceInfo {
@@ -61,37 +102,40 @@ pub struct MicrosoftVoiceInfo {
     pub voice_type: String,
 }
 
-fn get_cache_dir() -> Result<PathBuf, String> {
-    let home = dirs::home_dir().ok_or("Could not find home directory")?;
-    Ok(home.join(".cache").join("insight-reader"))
-}
-
-fn get_cache_path() -> Result<PathBuf, String> {
-    Ok(get_cache_dir()?.join(CACHE_FILE_NAME))
+/// A speaker known to the custom voice server (cloned voice or reference sample).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomServerVoice {
+    /// Value for `custom_tts_speaker`.
+    pub name: String,
 }
 
-fn ensure_cache_dir() -> Result<(), String> {
-    let cache_dir = get_cache_dir()?;
-    if !cache_dir.exists() {
-        fs::create_dir_all(&cache_dir)
-            .map_err(|e| format!("Failed to create cache directory: {}", e))?;
-    }
-    Ok(())
+/// The Piper voice catalog, cached (see `cache`); `force_refresh` skips a fresh cache.
+pub async fn fetch_piper_voices(force_refresh: bool) -> Result<HashMap<String, VoiceInfo>, String> {
+    cache::cached_or_fetch(cache::PIPER_CACHE_FILE, force_refresh, fetch_piper_catalog).await
 }
 
-pub async fn fetch_piper_voices(force_refresh: bool) -> Result<HashMap<String, VoiceInfo>, String> {
-    debug!("Fetching Piper voices from HuggingFace");
+async fn fetch_piper_catalog() -> Result<HashMap<String, VoiceInfo>, String> {
+    debug!("Fetching Piper voices catalog");
 
-    // Check cache first (unless force refresh)
-    if !force_refresh {
-        if let Ok(Some(v)) = get_cached_voices() {
-            debug!("Using cached Piper voices");
-            return Ok(v);
+    // Fetch from the first mirror that answers
+    let mut last_error = String::from("No voice mirrors configured");
+    for mirror in mirrors() {
+        match fetch_catalog(&format!("{mirror}/{CATALOG_FILE}")).await {
+            Ok(voices) => {
+                debug!(count = voices.len(), "Parsed Piper voices");
+                return Ok(voices);
+            }
+            Err(e) => {
+                warn!(mirror = %mirror, error = %e, "Voice mirror failed, trying the next one");
+                last_error = e;
+            }
         }
     }
+    Err(last_error)
+}
 
-    // Fetch from API
-    let response = reqwest::get(PIPER_VOICES_API_URL)
+async fn fetch_catalog(url: &str) -> Result<HashMap<String, VoiceInfo>, String> {
+    let response = reqwest::get(url)
         .await
         .map_err(|e| format!("Failed to fetch Piper voices: {}", e))?;
 
@@ -107,56 +151,26 @@ pub async fn fetch_piper_voices(force_refresh: bool) -> Result<HashMap<String, V
         .await
         .map_err(|e| format!("Failed to read response body: {}", e))?;
 
-    debug!(bytes = json_text.len(), "Received Piper voices response");
-
-    let voices: HashMap<String, VoiceInfo> = serde_json::from_str(&json_text)
-        .map_err(|e| format!("Failed to parse voices JSON: {}", e))?;
+    debug!(url = %url, bytes = json_text.len(), "Received Piper voices response");
 
-    // Cache the response
-    if let Err(e) = cache_voices(&voices) {
-        error!("Failed to cache Piper voices: {}", e);
-    }
-
-    debug!(count = voices.len(), "Parsed Piper voices");
-    Ok(voices)
+    serde_json::from_str(&json_text).map_err(|e| format!("Failed to parse voices JSON: {}", e))
 }
 
-fn get_cached_voices() -> Result<Option<HashMap<String, VoiceInfo>>, String> {
-    let cache_path = get_cache_path()?;
-
-    if !cache_path.exists() {
-        return Ok(None);
-    }
-
-    let content =
-        fs::read_to_string(&cache_path).map_err(|e| format!("Failed to read cache file: {}", e))?;
-
-    let cached: HashMap<String, VoiceInfo> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse cached voices: {}", e))?;
-
-    Ok(Some(cached))
+/// The Polly voices, cached (see `cache`); `force_refresh` skips a fresh cache.
+pub async fn fetch_polly_voices(force_refresh: bool) -> Result<Vec<PollyVoiceInfo>, String> {
+    cache::cached_or_fetch(
+        cache::POLLY_CACHE_FILE,
+        force_refresh,
+        fetch_polly_voice_list,
+    )
+    .await
 }
 
-fn cache_voices(voices: &HashMap<String, VoiceInfo>) -> Result<(), String> {
-    ensure_cache_dir()?;
-
-    let cache_path = get_cache_path()?;
-    let json = serde_json::to_string_pretty(voices)
-        .map_err(|e| format!("Failed to serialize voices for cache: {}", e))?;
-
-    fs::write(&cache_path, json).map_err(|e| format!("Failed to write cache file: {}", e))?;
-
-    debug!("Cached Piper voices to {:?}", cache_path);
-    Ok(())
-}
-
-pub async fn fetch_polly_voices() -> Result<Vec<PollyVoiceInfo>, String> {
+async fn fetch_polly_voice_list() -> Result<Vec<PollyVoiceInfo>, String> {
     debug!("Fetching Polly voices from AWS");
 
-    let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
-        .region(aws_config::Region::new(detect_aws_region()))
-        .load()
-        .await;
+    let settings = crate::tts::PollySettings::load();
+    let config = crate::tts::aws_config_loader(&settings).load().await;
 
     let client = aws_sdk_polly::Client::new(&config);
 
@@ -204,7 +218,19 @@ pub async fn fetch_polly_voices() -> Result<Vec<PollyVoiceInfo>, String> {
     Ok(voices)
 }
 
-pub async fn fetch_microsoft_voices() -> Result<Vec<MicrosoftVoiceInfo>, String> {
+/// The Microsoft Edge TTS voices, cached (see `cache`); `force_refresh` skips a fresh cache.
+pub async fn fetch_microsoft_voices(
+    force_refresh: bool,
+) -> Result<Vec<MicrosoftVoiceInfo>, String> {
+    cache::cached_or_fetch(
+        cache::MICROSOFT_CACHE_FILE,
+        force_refresh,
+        fetch_microsoft_voice_list,
+    )
+    .await
+}
+
+async fn fetch_microsoft_voice_list() -> Result<Vec<MicrosoftVoiceInfo>, String> {
     debug!("Fetching Microsoft Edge TTS voices");
 
     let voices = msedge_tts::voice::get_voices_list()
@@ -242,53 +268,51 @@ pub async fn fetch_microsoft_voices() -> Result<Vec<MicrosoftVoiceInfo>, String>
     Ok(result)
 }
 
-fn detect_aws_region() -> String {
-    if let Ok(region) = std::env::var("AWS_REGION") {
-        if !region.is_empty() {
-            return region;
-        }
-    }
-    if let Ok(region) = std::env::var("AWS_DEFAULT_REGION") {
-        if !region.is_empty() {
-            return region;
-        }
-    }
-
-    if let Some(home) = dirs::home_dir() {
-        let config_path = home.join(".aws").join("config");
-        if let Ok(content) = fs::read_to_string(&config_path) {
-            if let Some(region) = parse_aws_config_region(&content) {
-                return region;
-            }
-        }
+/// Lists the speakers of the custom voice server at `url` (`GET /speakers_list`).
+pub async fn fetch_custom_server_voices(url: &str) -> Result<Vec<CustomServerVoice>, String> {
+    let url = format!("{}/speakers_list", url.trim_end_matches('/'));
+    debug!(url = %url, "Fetching custom server voices");
+    let response = reqwest::get(&url)
+        .await
+        .map_err(|e| format!("Custom voice server unreachable: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Custom voice server returned {}",
+            response.status()
+        ));
     }
-
-    "us-east-1".to_string()
+    let names: Vec<String> = response
+        .json()
+        .await
+        .map_err(|e| format!("Unexpected speaker list from custom voice server: {e}"))?;
+    debug!(count = names.len(), "Fetched custom server voices");
+    Ok(names
+        .into_iter()
+        .map(|name| CustomServerVoice { name })
+        .collect())
 }
 
-fn parse_aws_config_region(content: &str) -> Option<String> {
-    let profile = std::env::var("AWS_PROFILE").unwrap_or_else(|_| "default".to_string());
-    let section_header = if profile == "default" {
-        "[default]".to_string()
-    } else {
-        format!("[profile {}]", profile)
-    };
-
-    let mut in_section = false;
-    for line in content.lines() {
-        let line = line.trim();
-        if line.starts_with('[') {
-            in_section = line.eq_ignore_ascii_case(&section_header);
-            continue;
-        }
-        if in_section && line.starts_with("region") {
-            if let Some(value) = line.split('=').nth(1) {
-                let region = value.trim();
-                if !region.is_empty() {
-                    return Some(region.to_string());
-                }
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_mirrors_come_first() {
+        configure(&FullConfig {
+            voice_mirrors: Some(vec![
+                " https://voices.example.com/piper/ ".to_string(),
+                String::new(),
+                DEFAULT_MIRRORS[1].to_string(),
+            ]),
+            ..Default::default()
+        });
+        assert_eq!(
+            mirrors(),
+            vec![
+                "https://voices.example.com/piper",
+                DEFAULT_MIRRORS[1],
+                DEFAULT_MIRRORS[0],
+            ]
+        );
     }
-    None
 }
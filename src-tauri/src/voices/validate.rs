@@ -0,0 +1,266 @@
+//! Post-download validation for Piper voices.
+//!
+//! Checks the downloaded files against the catalog (sizes, parseable config) and runs a tiny
+//! synthesis through Piper so a corrupt model or an incompatible Piper install is reported right
+//! after the download instead of at the first real read. `check_integrity` re-checks an installed
+//! voice later (sizes, optionally MD5 checksums) without running Piper.
+
+use std::io::Read;
+use std::path::Path;
+
+use md5::{Digest, Md5};
+
+use tracing::{info, warn};
+
+use crate::tts;
+use crate::voices::VoiceInfo;
+
+/// Validates a downloaded voice in `voice_dir`. Returns a user-actionable error on failure.
+/// Blocking (runs the Piper binary); call from `spawn_blocking` in async code.
+pub fn validate_downloaded_voice(
+    voice_key: &str,
+    voice_dir: &Path,
+    voice_info: &VoiceInfo,
+) -> Result<(), String> {
+    let onnx_path = voice_dir.join(format!("{voice_key}.onnx"));
+    let json_path = voice_dir.join(format!("{voice_key}.onnx.json"));
+
+    check_file_size(voice_key, &onnx_path, expected_size(voice_info, ".onnx"))?;
+    check_file_size(
+        voice_key,
+        &json_path,
+        expected_size(voice_info, ".onnx.json"),
+    )?;
+    check_model_config(voice_key, &json_path)?;
+
+    if !tts::is_piper_installed() {
+        warn!(voice_key = %voice_key, "Piper not installed, skipping voice smoke test");
+        return Ok(());
+    }
+
+    tts::smoke_test_piper_model(&voice_dir.join(voice_key))
+        .map_err(|e| describe_smoke_test_failure(voice_key, &e.to_string()))?;
+
+    info!(voice_key = %voice_key, "Voice smoke test passed");
+    Ok(())
+}
+
+/// Checks an installed voice against the catalog: both files present, non-empty and the expected
+/// size, the model config parseable and, when `checksum` is set, the catalog MD5 digests. Returns
+/// the problems found (empty when the voice is intact). Blocking; hashing a model takes a while.
+pub fn check_integrity(
+    voice_key: &str,
+    voice_dir: &Path,
+    voice_info: &VoiceInfo,
+    checksum: bool,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    for suffix in [".onnx", ".onnx.json"] {
+        let path = voice_dir.join(format!("{voice_key}{suffix}"));
+        if let Err(e) = check_file_size(voice_key, &path, expected_size(voice_info, suffix)) {
+            problems.push(e);
+            continue;
+        }
+        if suffix == ".onnx.json" {
+            if let Err(e) = check_model_config(voice_key, &path) {
+                problems.push(e);
+            }
+        }
+        let Some(expected) = checksum.then(|| expected_md5(voice_info, suffix)).flatten() else {
+            continue;
+        };
+        match md5_hex(&path) {
+            Ok(actual) if actual.eq_ignore_ascii_case(expected) => {}
+            Ok(actual) => problems.push(format!(
+                "Voice {voice_key} is corrupt: {} has checksum {actual}, expected {expected}. \
+                 Download it again.",
+                path.display()
+            )),
+            Err(e) => problems.push(format!(
+                "Voice {voice_key}: failed to read {} ({e})",
+                path.display()
+            )),
+        }
+    }
+    problems
+}
+
+/// Catalog MD5 digest for the file whose path ends with `suffix`.
+fn expected_md5<'a>(voice_info: &'a VoiceInfo, suffix: &str) -> Option<&'a str> {
+    voice_info
+        .files
+        .iter()
+        .find(|(path, _)| is_file_for_suffix(path, suffix))
+        .map(|(_, info)| info.md5_digest.as_str())
+        .filter(|digest| !digest.is_empty())
+}
+
+pub(super) fn md5_hex(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Md5::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn is_file_for_suffix(path: &str, suffix: &str) -> bool {
+    path.ends_with(suffix) && (suffix != ".onnx" || !path.ends_with(".onnx.json"))
+}
+
+/// Catalog size for the file whose path ends with `suffix` (".onnx" excludes ".onnx.json").
+fn expected_size(voice_info: &VoiceInfo, suffix: &str) -> Option<u64> {
+    voice_info
+        .files
+        .iter()
+        .find(|(path, _)| is_file_for_suffix(path, suffix))
+        .map(|(_, info)| info.size_bytes)
+        .filter(|size| *size > 0)
+}
+
+fn check_file_size(voice_key: &str, path: &Path, expected: Option<u64>) -> Result<(), String> {
+    let actual = std::fs::metadata(path)
+        .map_err(|e| format!("Voice {voice_key} is incomplete: {} ({e})", path.display()))?
+        .len();
+    if actual == 0 {
+        return Err(format!(
+            "Voice {voice_key} is corrupt: {} is empty. Download it again.",
+            path.display()
+        ));
+    }
+    if let Some(expected) = expected {
+        if actual != expected {
+            return Err(format!(
+                "Voice {voice_key} is corrupt: {} is {actual} bytes, expected {expected}. \
+                 Download it again.",
+                path.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_model_config(voice_key: &str, json_path: &Path) -> Result<(), String> {
+    let data = std::fs::read_to_string(json_path)
+        .map_err(|e| format!("Voice {voice_key}: failed to read model config: {e}"))?;
+    let value: serde_json::Value = serde_json::from_str(&data).map_err(|e| {
+        format!(
+            "Voice {voice_key} is corrupt: model config is not valid JSON ({e}). \
+             Download it again."
+        )
+    })?;
+    if value.get("phoneme_id_map").is_none() {
+        return Err(format!(
+            "Voice {voice_key} has an unsupported model config (no phoneme_id_map). \
+             Choose another voice."
+        ));
+    }
+    Ok(())
+}
+
+/// Maps raw Piper stderr into an actionable message (corrupt model vs incompatible Piper).
+fn describe_smoke_test_failure(voice_key: &str, error: &str) -> String {
+    let lower = error.to_lowercase();
+    let corrupt_markers = ["protobuf", "invalid_graph", "load model"];
+    let incompatible_markers = [
+        "unrecognized arguments",
+        "keyerror",
+        "unsupported",
+        "opset",
+        "ir version",
+    ];
+
+    if corrupt_markers.iter().any(|m| lower.contains(m)) {
+        format!(
+            "Voice {voice_key} appears to be corrupt (Piper could not load the model). \
+             Download it again."
+        )
+    } else if incompatible_markers.iter().any(|m| lower.contains(m)) {
+        format!(
+            "Voice {voice_key} is not compatible with the installed Piper version. \
+             Update Piper or choose another voice. ({error})"
+        )
+    } else {
+        format!("Voice {voice_key} failed a test synthesis: {error}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_smoke_test_failure_corrupt_model() {
+        let msg = describe_smoke_test_failure(
+            "en_US-lessac-medium",
+            "TTS process error: Piper failed: [ONNXRuntimeError] : 7 : INVALID_PROTOBUF",
+        );
+        assert!(msg.contains("corrupt"));
+    }
+
+    #[test]
+    fn test_describe_smoke_test_failure_incompatible_piper() {
+        let msg = describe_smoke_test_failure(
+            "en_US-lessac-medium",
+            "Piper failed: KeyError: 'phoneme_type'",
+        );
+        assert!(msg.contains("not compatible"));
+    }
+
+    #[test]
+    fn test_check_integrity_sizes_and_checksums() {
+        use crate::voices::{FileInfo, LanguageInfo};
+
+        let dir = tempfile::tempdir().unwrap();
+        let key = "en_US-test-low";
+        std::fs::write(dir.path().join(format!("{key}.onnx")), "abc").unwrap();
+        let config = r#"{"phoneme_id_map":{}}"#;
+        std::fs::write(dir.path().join(format!("{key}.onnx.json")), config).unwrap();
+        let file = |size_bytes, md5_digest: &str| FileInfo {
+            size_bytes,
+            md5_digest: md5_digest.to_string(),
+        };
+        let mut info = VoiceInfo {
+            key: key.to_string(),
+            name: "test".to_string(),
+            language: LanguageInfo {
+                code: "en_US".to_string(),
+                family: "en".to_string(),
+                region: "US".to_string(),
+                name_english: "English".to_string(),
+            },
+            quality: "low".to_string(),
+            num_speakers: 1,
+            files: [
+                (
+                    format!("en/en_US/test/low/{key}.onnx"),
+                    file(3, "900150983cd24fb0d6963f7d28e17f72"),
+                ),
+                (
+                    format!("en/en_US/test/low/{key}.onnx.json"),
+                    file(config.len() as u64, ""),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        assert!(check_integrity(key, dir.path(), &info, true).is_empty());
+
+        if let Some(onnx) = info.files.values_mut().find(|f| f.size_bytes == 3) {
+            onnx.md5_digest = "00000000000000000000000000000000".to_string();
+        }
+        assert!(check_integrity(key, dir.path(), &info, false).is_empty());
+        let problems = check_integrity(key, dir.path(), &info, true);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("checksum"));
+
+        std::fs::write(dir.path().join(format!("{key}.onnx")), "ab").unwrap();
+        let problems = check_integrity(key, dir.path(), &info, false);
+        assert!(problems[0].contains("expected 3"));
+    }
+}
@@ -0,0 +1,654 @@
+//! Fetches a web page and extracts its readable article text, for reading a copied link.
+//!
+//! Extraction is readability-style and works on any HTML, well-formed or not: scripts, styles,
+//! navigation, headers, footers, forms and elements whose class or id names comments, sharing,
+//! ads and the like are dropped; each paragraph adds a score (longer text and more commas score
+//! higher) to its container and half to the container's parent; the best-scoring container's
+//! headings, paragraphs, list items and quotes are the article. The title comes from `<title>`,
+//! or the first `<h1>`.
+//!
+//! Used by `read_url` and, with `expand_urls` on, by Read Selected when the selection is a lone
+//! URL (see `actions`). Those automatic fetches only go to public hosts (`fetch_public_article`):
+//! a copied link to the router or a local service is read out as text, not requested.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, ToSocketAddrs};
+use std::time::Duration;
+
+use futures_util::stream::StreamExt;
+use reqwest::redirect::Policy;
+use reqwest::Url;
+
+use tauri::State;
+use tracing::{debug, info};
+
+use crate::error::AppError;
+use crate::history;
+use crate::offline;
+use crate::tts;
+
+/// Timeout for fetching a page.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+/// Pages larger than this are not read.
+const MAX_PAGE_BYTES: usize = 5 * 1024 * 1024;
+/// Redirects followed for one page (as reqwest's default policy).
+const MAX_REDIRECTS: usize = 10;
+/// Paragraphs shorter than this (in chars) do not score their container.
+const MIN_SCORED_PARAGRAPH_CHARS: usize = 25;
+
+/// Elements whose content is never article text.
+const SKIPPED_ELEMENTS: [&str; 14] = [
+    "script", "style", "noscript", "template", "svg", "iframe", "nav", "header", "footer", "aside",
+    "form", "button", "select", "head",
+];
+
+/// Class or id fragments of page furniture.
+const SKIPPED_CLASS_HINTS: [&str; 10] = [
+    "comment",
+    "sidebar",
+    "share",
+    "social",
+    "related",
+    "advert",
+    "cookie",
+    "newsletter",
+    "promo",
+    "breadcrumb",
+];
+
+/// Elements that hold blocks of text and can be the article.
+const CONTAINER_ELEMENTS: [&str; 6] = ["article", "main", "section", "div", "td", "body"];
+
+/// Elements that start a new paragraph.
+const BLOCK_ELEMENTS: [&str; 15] = [
+    "p",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "li",
+    "blockquote",
+    "pre",
+    "dt",
+    "dd",
+    "figcaption",
+    "tr",
+    "br",
+];
+
+/// Elements without content or end tag.
+const VOID_ELEMENTS: [&str; 12] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "wbr",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Article {
+    pub url: String,
+    pub title: Option<String>,
+    /// Paragraphs separated by blank lines.
+    pub text: String,
+}
+
+impl Article {
+    /// The title followed by the text, as read aloud.
+    pub fn spoken_text(&self) -> String {
+        match &self.title {
+            Some(title) if !self.text.starts_with(title.as_str()) => {
+                format!("{title}\n\n{}", self.text)
+            }
+            _ => self.text.clone(),
+        }
+    }
+}
+
+/// The URL when `text` is nothing but one (`www.` links get `https://`).
+pub fn as_url(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.is_empty() || text.contains(char::is_whitespace) {
+        return None;
+    }
+    let lower = text.to_ascii_lowercase();
+    if lower.starts_with("https://") || lower.starts_with("http://") {
+        (text.len() > "https://".len()).then(|| text.to_string())
+    } else if lower.starts_with("www.") && text.len() > "www.".len() {
+        Some(format!("https://{text}"))
+    } else {
+        None
+    }
+}
+
+/// Downloads `url` and extracts its article.
+pub async fn fetch_article(url: &str) -> Result<Article, String> {
+    fetch(url, false).await
+}
+
+/// Like `fetch_article`, but refuses (also on redirect) hosts that resolve to loopback, private
+/// or link-local addresses. For links the user did not explicitly ask to open.
+pub async fn fetch_public_article(url: &str) -> Result<Article, String> {
+    fetch(url, true).await
+}
+
+async fn fetch(url: &str, public_only: bool) -> Result<Article, String> {
+    offline::ensure_online()?;
+    // Public-only fetches follow redirects here, to check each target first.
+    let redirects = if public_only {
+        Policy::none()
+    } else {
+        Policy::limited(MAX_REDIRECTS)
+    };
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("InsightReader/", env!("CARGO_PKG_VERSION")))
+        .redirect(redirects)
+        .build()
+        .map_err(|e| format!("HTTP client: {e}"))?;
+    let mut target = Url::parse(url).map_err(|e| format!("Not a web address: {url} ({e})"))?;
+    let mut redirected = 0;
+    let response = loop {
+        if public_only {
+            ensure_public_host(&target).await?;
+        }
+        let response = client
+            .get(target.clone())
+            .send()
+            .await
+            .map_err(|e| format!("Could not fetch {url}: {e}"))?;
+        if !public_only || !response.status().is_redirection() {
+            break response;
+        }
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| format!("Could not fetch {url}: redirect without a location"))?;
+        redirected += 1;
+        if redirected > MAX_REDIRECTS {
+            return Err(format!("Could not fetch {url}: too many redirects"));
+        }
+        target = target
+            .join(location)
+            .map_err(|e| format!("Could not fetch {url}: bad redirect ({e})"))?;
+    };
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("Could not fetch {url}: HTTP {status}"));
+    }
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_none_or(|v| v.contains("html"));
+    if !is_html {
+        return Err(format!("{url} is not a web page"));
+    }
+    if response
+        .content_length()
+        .is_some_and(|len| len as usize > MAX_PAGE_BYTES)
+    {
+        return Err(format!("{url} is too large to read"));
+    }
+    // The length header is optional (and may lie), so the body is also counted as it arrives.
+    let mut bytes = Vec::new();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Could not fetch {url}: {e}"))?;
+        if bytes.len() + chunk.len() > MAX_PAGE_BYTES {
+            return Err(format!("{url} is too large to read"));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    let html = String::from_utf8_lossy(&bytes);
+    let (title, text) = extract(&html);
+    if text.is_empty() {
+        return Err(format!("No article text found at {url}"));
+    }
+    debug!(url, len = text.len(), "Extracted article");
+    Ok(Article {
+        url: url.to_string(),
+        title,
+        text,
+    })
+}
+
+/// Fails unless every address `url`'s host resolves to is public.
+async fn ensure_public_host(url: &Url) -> Result<(), String> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("Not a web address: {url}"))?;
+    let host = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let addrs: Vec<IpAddr> = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![ip],
+        Err(_) => {
+            let port = url.port_or_known_default().unwrap_or(443);
+            let lookup = host.clone();
+            tokio::task::spawn_blocking(move || (lookup.as_str(), port).to_socket_addrs())
+                .await
+                .map_err(|e| format!("spawn_blocking: {e}"))?
+                .map_err(|e| format!("Could not resolve {host}: {e}"))?
+                .map(|addr| addr.ip())
+                .collect()
+        }
+    };
+    if addrs.is_empty() || !addrs.into_iter().all(is_public) {
+        return Err(format!(
+            "{host} is on this computer or the local network; not fetching it automatically"
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `ip` is reachable on the internet rather than loopback, a private or link-local
+/// network, carrier-grade NAT or a reserved range.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(a == 0
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || (a == 100 && (b & 0xc0) == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public(v4.into()),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+// --- Extraction ---
+
+enum Token<'a> {
+    Open { name: String, attrs: &'a str },
+    Close(String),
+    Text(&'a str),
+}
+
+/// Splits HTML into tags and text. Comments, doctypes, and `script` and `style` elements (where a
+/// `<` does not open a tag) are dropped.
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            tokens.push(Token::Text(rest));
+            break;
+        };
+        if lt > 0 {
+            tokens.push(Token::Text(&rest[..lt]));
+        }
+        rest = &rest[lt..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(gt) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+        if let Some(name) = tag.strip_prefix('/') {
+            tokens.push(Token::Close(name.trim().to_ascii_lowercase()));
+            continue;
+        }
+        let name_end = tag
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .unwrap_or(tag.len());
+        let name = tag[..name_end].to_ascii_lowercase();
+        if name.is_empty() || !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            continue;
+        }
+        if matches!(name.as_str(), "script" | "style") {
+            let end = rest
+                .match_indices("</")
+                .find(|(i, _)| {
+                    rest[i + 2..]
+                        .get(..name.len())
+                        .is_some_and(|n| n.eq_ignore_ascii_case(&name))
+                })
+                .map_or(rest.len(), |(i, _)| i);
+            rest = &rest[end..];
+            rest = rest.find('>').map_or("", |gt| &rest[gt + 1..]);
+            continue;
+        }
+        let self_closing = tag.ends_with('/') || VOID_ELEMENTS.contains(&name.as_str());
+        tokens.push(Token::Open {
+            name: name.clone(),
+            attrs: &tag[name_end..],
+        });
+        if self_closing {
+            tokens.push(Token::Close(name));
+        }
+    }
+    tokens
+}
+
+struct Element {
+    name: String,
+    skipped: bool,
+    /// Container number (in document order), when this is a container.
+    container: Option<usize>,
+}
+
+struct Paragraph {
+    text: String,
+    /// Enclosing containers, outermost first.
+    containers: Vec<usize>,
+    scores: bool,
+}
+
+/// Returns the page title and the article text.
+fn extract(html: &str) -> (Option<String>, String) {
+    let mut stack: Vec<Element> = Vec::new();
+    let mut container_count = 0usize;
+    let mut paragraphs: Vec<Paragraph> = Vec::new();
+    let mut current = String::new();
+    let mut current_scores = false;
+    let mut title: Option<String> = None;
+    let mut in_title = false;
+    let mut first_h1: Option<String> = None;
+    let mut h1_buf: Option<String> = None;
+
+    let flush =
+        |current: &mut String, scores: bool, stack: &[Element], paragraphs: &mut Vec<Paragraph>| {
+            let text = collapse_whitespace(current);
+            current.clear();
+            if text.is_empty() {
+                return;
+            }
+            paragraphs.push(Paragraph {
+                scores: scores && text.chars().count() >= MIN_SCORED_PARAGRAPH_CHARS,
+                text,
+                containers: stack.iter().filter_map(|e| e.container).collect(),
+            });
+        };
+
+    for token in tokenize(html) {
+        let skipping = stack.last().is_some_and(|e| e.skipped);
+        match token {
+            Token::Open { name, attrs } => {
+                if name == "title" {
+                    in_title = true;
+                }
+                let is_block = BLOCK_ELEMENTS.contains(&name.as_str());
+                if (is_block || CONTAINER_ELEMENTS.contains(&name.as_str())) && !skipping {
+                    flush(&mut current, current_scores, &stack, &mut paragraphs);
+                    current_scores = name == "p" || name == "pre";
+                }
+                if name == "h1" && first_h1.is_none() && !skipping {
+                    h1_buf = Some(String::new());
+                }
+                if VOID_ELEMENTS.contains(&name.as_str()) {
+                    continue;
+                }
+                let skipped = skipping
+                    || SKIPPED_ELEMENTS.contains(&name.as_str())
+                    || has_skipped_class(attrs);
+                let container =
+                    (!skipped && CONTAINER_ELEMENTS.contains(&name.as_str())).then(|| {
+                        container_count += 1;
+                        container_count - 1
+                    });
+                stack.push(Element {
+                    name,
+                    skipped,
+                    container,
+                });
+            }
+            Token::Close(name) => {
+                if name == "title" {
+                    in_title = false;
+                }
+                if BLOCK_ELEMENTS.contains(&name.as_str())
+                    || CONTAINER_ELEMENTS.contains(&name.as_str())
+                {
+                    if !skipping {
+                        flush(&mut current, current_scores, &stack, &mut paragraphs);
+                    }
+                    current_scores = false;
+                }
+                if name == "h1" {
+                    if let Some(buf) = h1_buf.take() {
+                        first_h1 = Some(collapse_whitespace(&buf)).filter(|t| !t.is_empty());
+                    }
+                }
+                // Unclosed elements inside are closed with it; stray end tags are ignored.
+                if let Some(pos) = stack.iter().rposition(|e| e.name == name) {
+                    stack.truncate(pos);
+                }
+            }
+            Token::Text(text) => {
+                let text = decode_entities(text);
+                if in_title {
+                    let text = collapse_whitespace(&text);
+                    if !text.is_empty() {
+                        title.get_or_insert(text);
+                    }
+                    continue;
+                }
+                if skipping {
+                    continue;
+                }
+                current.push_str(&text);
+                if let Some(buf) = h1_buf.as_mut() {
+                    buf.push_str(&text);
+                }
+            }
+        }
+    }
+    flush(&mut current, current_scores, &stack, &mut paragraphs);
+
+    let mut scores: HashMap<usize, f64> = HashMap::new();
+    for paragraph in paragraphs.iter().filter(|p| p.scores) {
+        let chars = paragraph.text.chars().count();
+        let score =
+            1.0 + paragraph.text.matches(',').count() as f64 + (chars as f64 / 100.0).min(3.0);
+        let mut ancestors = paragraph.containers.iter().rev();
+        if let Some(parent) = ancestors.next() {
+            *scores.entry(*parent).or_default() += score;
+        }
+        if let Some(grandparent) = ancestors.next() {
+            *scores.entry(*grandparent).or_default() += score / 2.0;
+        }
+    }
+    let best = scores
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(container, _)| container);
+    let text = paragraphs
+        .iter()
+        .filter(|p| best.is_none_or(|best| p.containers.contains(&best)))
+        .map(|p| p.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    (title.or(first_h1), text)
+}
+
+fn has_skipped_class(attrs: &str) -> bool {
+    let attrs = attrs.to_ascii_lowercase();
+    ["class=", "id="].iter().any(|attr| {
+        attrs.match_indices(attr).any(|(i, _)| {
+            let value = &attrs[i + attr.len()..];
+            let value = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => value[1..].split(quote).next(),
+                _ => value.split(char::is_whitespace).next(),
+            };
+            value.is_some_and(|v| SKIPPED_CLASS_HINTS.iter().any(|hint| v.contains(hint)))
+        })
+    })
+}
+
+/// Decodes character references and the common named entities.
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| {
+                let entity = &rest[1..=end];
+                let c = match entity.strip_prefix('#') {
+                    Some(num) => match num.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => num.parse().ok(),
+                    }
+                    .and_then(char::from_u32),
+                    None => named_entity(entity),
+                };
+                c.map(|c| (c, end + 2))
+            });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn named_entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        "mdash" => Some('—'),
+        "ndash" => Some('–'),
+        "hellip" => Some('…'),
+        "lsquo" => Some('‘'),
+        "rsquo" => Some('’'),
+        "ldquo" => Some('“'),
+        "rdquo" => Some('”'),
+        _ => None,
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Fetches `url`, extracts the article and reads it (title first). Returns the article.
+#[tauri::command]
+pub async fn read_url(state: State<'_, tts::TtsState>, url: String) -> Result<Article, AppError> {
+    let url =
+        as_url(&url).ok_or_else(|| AppError::InvalidInput(format!("Not a web address: {url}")))?;
+    let article = fetch_article(&url).await?;
+    info!(url = %article.url, len = article.text.len(), "Reading web article");
+    let text = article.spoken_text();
+    let tx = state.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        history::begin("web", &text);
+        let (resp_tx, resp_rx) = std::sync::mpsc::sync_channel(0);
+        let result = tx
+            .send(tts::TtsRequest::Speak(
+                text,
+                tts::InputKind::Text,
+                None,
+                resp_tx,
+            ))
+            .map_err(|e| AppError::from(format!("TTS channel: {e}")))
+            .and_then(|()| {
+                resp_rx
+                    .recv()
+                    .map_err(|_| AppError::from("TTS worker disconnected"))?
+                    .map_err(AppError::from)
+            });
+        if result.is_err() {
+            history::abandon();
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking: {e}"))??;
+    Ok(article)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_finds_the_article_and_title() {
+        let html = r#"<!DOCTYPE html><html><head><title>Elephants &amp; Friends</title>
+            <script>if (a < b) { document.write("<p>no</p>"); }</script></head>
+            <body><nav><ul><li>Home</li><li>About</li></ul></nav>
+            <div class="layout"><div id="content"><h1>Elephants</h1>
+            <p>Elephants are the largest land animals, and they live in herds led by a matriarch.
+            <p>They eat grass, leaves, bark and fruit&#8212;up to 150&nbsp;kg a day.<br>
+            Truly.</p><div class="share-buttons"><p>Share this on every network, please, now.</p></div>
+            </div><div class="sidebar-widget"><p>Buy our newsletter, it is great, really great.</p></div>
+            </div><footer><p>Copyright notice that is long enough to score, sadly.</p></footer>
+            <!-- <p>commented out</p> --></body></html>"#;
+        let (title, text) = extract(html);
+        assert_eq!(title.as_deref(), Some("Elephants & Friends"));
+        assert_eq!(
+            text,
+            "Elephants\n\nElephants are the largest land animals, and they live in herds led by a \
+             matriarch.\n\nThey eat grass, leaves, bark and fruit—up to 150 kg a day.\n\nTruly."
+        );
+    }
+
+    #[test]
+    fn test_as_url_accepts_only_a_lone_link() {
+        assert_eq!(
+            as_url("https://example.com/a"),
+            Some("https://example.com/a".into())
+        );
+        assert_eq!(
+            as_url(" www.example.com/a "),
+            Some("https://www.example.com/a".into())
+        );
+        assert_eq!(as_url("see https://example.com"), None);
+        assert_eq!(as_url("https://"), None);
+        assert_eq!(as_url("example.com"), None);
+    }
+
+    #[test]
+    fn test_is_public_refuses_local_and_private_addresses() {
+        for ip in ["93.184.215.14", "2606:2800:21f:cb07:6820:80da:af6b:8b2c"] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:192.168.1.1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+}
@@ -3,12 +3,18 @@
 //! Builds the correct URL for loading HTML (dev server vs packed app path) and provides
 //! open_or_focus_editor_with_text: store initial text in state, then focus the editor window
 //! (emitting `editor-set-text` if it already exists) or create it. Used by the open_editor_window
-//! command and by the tray "Insight Editor" and "Summarize Selected" flows.
+//! command and by the tray "Insight Editor" and "Summarize Selected" flows. Very large texts are
+//! paged: the editor gets the first page (see `editor_pages`). On mobile the app has a single
+//! webview, which shows the editor page itself.
 
 #[cfg(target_os = "macos")]
 use tauri::window::{Effect, EffectsBuilder};
-use tauri::{Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder};
+use tauri::{Emitter, Manager, State};
+#[cfg(desktop)]
+use tauri::{WebviewUrl, WebviewWindowBuilder};
 
+use crate::editor_pages::{self, EditorPagesState, PagedText};
+use crate::error::AppError;
 use crate::{EditorInitialStateInner, EditorInitialText};
 
 /// Window corner radius in logical pixels. Mac-only for now.
@@ -20,6 +26,7 @@ const WINDOW_RADIUS_MACOS: f64 = 10.0;
 /// Builds a WebviewUrl for the given HTML file path.
 /// In dev mode, uses the configured dev_url or defaults to localhost:1420.
 /// In production, uses the app path.
+#[cfg(desktop)]
 pub fn build_webview_url<R: tauri::Runtime>(
     app: &tauri::AppHandle<R>,
     html_path: &str,
@@ -51,6 +58,18 @@ pub fn open_or_focus_editor_with_text<R: tauri::Runtime>(
     initial_text: String,
     trigger_read: bool,
 ) -> Result<(), String> {
+    let paged = (initial_text.len() > editor_pages::PAGING_THRESHOLD_BYTES)
+        .then(|| PagedText::new(initial_text));
+    let page_count = paged.as_ref().map_or(0, PagedText::page_count);
+    let initial_text = match &paged {
+        Some(paged) => paged.page(0).unwrap_or_default().to_string(),
+        None => initial_text,
+    };
+    if let Some(pages) = app.try_state::<EditorPagesState>() {
+        *pages
+            .lock()
+            .map_err(|e| format!("editor pages lock: {}", e))? = paged;
+    }
     {
         let mut guard = state
             .inner()
@@ -59,11 +78,23 @@ pub fn open_or_focus_editor_with_text<R: tauri::Runtime>(
         *guard = EditorInitialStateInner {
             text: Some(initial_text.clone()),
             trigger_read,
+            page_count,
         };
     }
 
+    show_editor_window(app, &initial_text, trigger_read)
+}
+
+/// Focuses the editor window (emitting `editor-set-text` and optionally `editor-trigger-read`) or
+/// creates it.
+#[cfg(desktop)]
+fn show_editor_window<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    initial_text: &str,
+    trigger_read: bool,
+) -> Result<(), String> {
     if let Some(win) = app.get_webview_window("editor") {
-        win.emit("editor-set-text", &initial_text)
+        win.emit("editor-set-text", initial_text)
             .map_err(|e: tauri::Error| e.to_string())?;
         let _ = win.show(); // restore if it was hidden (user had "closed" it)
         win.set_focus().map_err(|e| e.to_string())?;
@@ -106,6 +137,23 @@ pub fn open_or_focus_editor_with_text<R: tauri::Runtime>(
     Ok(())
 }
 
+/// Mobile has a single webview, so there is no editor window: the app shows its editor page,
+/// which picks up the stored text with `get_editor_initial_text` (or takes `editor-set-text` when
+/// it is already showing).
+#[cfg(mobile)]
+fn show_editor_window<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    initial_text: &str,
+    trigger_read: bool,
+) -> Result<(), String> {
+    app.emit("editor-set-text", initial_text)
+        .map_err(|e: tauri::Error| e.to_string())?;
+    if trigger_read {
+        let _ = app.emit("editor-trigger-read", ());
+    }
+    Ok(())
+}
+
 // --- Commands ---
 
 /// Opens the grammar editor window, creating it if it does not exist.
@@ -117,15 +165,16 @@ pub fn open_editor_window(
     state: State<EditorInitialText>,
     initial_text: String,
     trigger_read: Option<bool>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     open_or_focus_editor_with_text(&app, &state, initial_text, trigger_read.unwrap_or(false))
+        .map_err(AppError::from)
 }
 
 /// Returns the stored initial text and trigger_read flag, and clears trigger_read after read.
 #[tauri::command]
 pub fn get_editor_initial_text(
     state: State<EditorInitialText>,
-) -> Result<EditorInitialStateInner, String> {
+) -> Result<EditorInitialStateInner, AppError> {
     let mut guard = state
         .inner()
         .lock()
@@ -24,7 +24,13 @@ import { EditorAssistantPanel } from "./components/editor/EditorAssistantPanel";
 import { EditorLegend } from "./components/editor/EditorLegend";
 import { FORMAT_OPTIONS, type AssistantTabId } from "./components/editor/editorData";
 import { applySuggestion } from "./utils/applySuggestion";
-import { callBackendPrompt, type BackendPromptTask } from "./backendPrompt";
+import { errorMessage } from "./utils/appError";
+import {
+  BackendPromptCancelled,
+  callBackendPrompt,
+  cleanTextForReading,
+  type BackendPromptTask,
+} from "./backendPrompt";
 import { parseThemeMode } from "./player/utils";
 import { useWindowSize } from "./player/hooks/useWindowSize";
 import { usePlatform } from "./player/hooks/usePlatform";
@@ -271,7 +277,7 @@ export default function EditorPage() {
     } catch (e) {
       console.warn("[EditorPage] tts_speak failed:", e);
       alert(
-        typeof e === "string" ? e : "Could not read aloud. Is Piper installed?",
+        errorMessage(e, "Could not read aloud. Is Piper installed?"),
       );
     } finally {
       setReadPreparing(false);
@@ -291,20 +297,25 @@ export default function EditorPage() {
     if (!content) return null;
     setTransformTask(task);
     try {
-      const response = await callBackendPrompt(task, content, {
-        tone: options?.tone,
-        format: options?.format,
-        instruction: options?.instruction,
-      });
+      const response =
+        task === "TTS"
+          ? await cleanTextForReading(content)
+          : await callBackendPrompt(task, content, {
+              tone: options?.tone,
+              format: options?.format,
+              instruction: options?.instruction,
+            });
       setText(response);
       return response;
     } catch (e) {
+      if (e instanceof BackendPromptCancelled) return null;
       console.warn(`[EditorPage] backend_prompt ${task} failed:`, e);
       if (!options?.silent) {
         alert(
-          typeof e === "string"
-            ? e
-            : `Backend ${task} failed. Is the ReadingService running on port 8080?`,
+          errorMessage(
+            e,
+            `Backend ${task} failed. Is the ReadingService running on port 8080?`,
+          ),
         );
       }
       return null;
@@ -324,7 +335,7 @@ export default function EditorPage() {
       } catch (e) {
         console.warn("[EditorPage] tts_speak after summarize failed:", e);
         alert(
-          typeof e === "string" ? e : "Could not read aloud. Is Piper installed?",
+          errorMessage(e, "Could not read aloud. Is Piper installed?"),
         );
       }
     }
@@ -349,9 +360,7 @@ export default function EditorPage() {
               invoke("tts_speak", { text: t }).catch((e) => {
                 console.warn("[EditorPage] tts_speak failed:", e);
                 alert(
-                  typeof e === "string"
-                    ? e
-                    : "Could not read aloud. Is Piper installed?",
+                  errorMessage(e, "Could not read aloud. Is Piper installed?"),
                 );
               });
             }
@@ -396,9 +405,7 @@ export default function EditorPage() {
           invoke("tts_speak", { text: t }).catch((e) => {
             console.warn("[EditorPage] tts_speak failed:", e);
             alert(
-              typeof e === "string"
-                ? e
-                : "Could not read aloud. Is Piper installed?",
+              errorMessage(e, "Could not read aloud. Is Piper installed?"),
             );
           });
         }
@@ -4,6 +4,7 @@
  */
 
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 
 /** Task values accepted by POST /api/prompt (case-sensitive). */
 export const BACKEND_PROMPT_TASKS = [
@@ -16,18 +17,105 @@ export const BACKEND_PROMPT_TASKS = [
   "EXPLAIN2",
   "REWRITE",
   "QUICK_EDIT",
+  "TRANSLATE",
 ] as const;
 
 export type BackendPromptTask = (typeof BACKEND_PROMPT_TASKS)[number];
 
+/** Payload of the `backend-prompt-done` event. */
+interface BackendPromptDone {
+  request_id: string;
+  status: "completed" | "failed" | "cancelled";
+  response: string | null;
+  error: unknown;
+}
+
+/** Thrown by callBackendPrompt when the request was cancelled. */
+export class BackendPromptCancelled extends Error {
+  constructor() {
+    super("Backend request cancelled");
+    this.name = "BackendPromptCancelled";
+  }
+}
+
 /**
  * Calls the ReadingService backend with the given task and content.
  * Returns the response string on success; throws on network or backend error.
+ * Aborting `signal` cancels the request (it then throws BackendPromptCancelled,
+ * as it does when the backend side cancels it, e.g. because the editor was closed).
  */
 export async function callBackendPrompt(
   task: BackendPromptTask,
   content: string,
-  options?: { tone?: string; format?: string; instruction?: string },
+  options?: { tone?: string; format?: string; instruction?: string; signal?: AbortSignal },
 ): Promise<string> {
-  return invoke<string>("backend_prompt", { task, content, ...options });
+  const { signal, ...rest } = options ?? {};
+  const requestId = crypto.randomUUID();
+  let resolveDone: (done: BackendPromptDone) => void = () => {};
+  const done = new Promise<BackendPromptDone>((resolve) => {
+    resolveDone = resolve;
+  });
+  const unlisten = await listen<BackendPromptDone>("backend-prompt-done", (event) => {
+    if (event.payload.request_id === requestId) resolveDone(event.payload);
+  });
+  const cancel = () => {
+    invoke("backend_cancel", { requestId }).catch((e) =>
+      console.warn("[backendPrompt] backend_cancel failed:", e),
+    );
+  };
+  try {
+    await invoke<string>("backend_prompt", { task, content, ...rest, requestId });
+    if (signal?.aborted) cancel();
+    signal?.addEventListener("abort", cancel);
+    const result = await done;
+    if (result.status === "cancelled") throw new BackendPromptCancelled();
+    if (result.status === "failed") throw result.error;
+    return result.response ?? "";
+  } finally {
+    signal?.removeEventListener("abort", cancel);
+    unlisten();
+  }
+}
+
+/** Payload of the `backend-stream-chunk` event. */
+interface BackendStreamChunk {
+  stream_id: string;
+  delta: string;
+}
+
+/**
+ * Like callBackendPrompt, but the response streams in: `onDelta` gets each piece as it arrives.
+ * With `readAloud`, each paragraph is read as soon as it is complete.
+ * Resolves with the full response; throws on network or backend error.
+ */
+export async function streamBackendPrompt(
+  task: BackendPromptTask,
+  content: string,
+  onDelta: (delta: string) => void,
+  options?: { tone?: string; format?: string; instruction?: string; readAloud?: boolean },
+): Promise<string> {
+  const streamId = crypto.randomUUID();
+  const unlisten = await listen<BackendStreamChunk>("backend-stream-chunk", (event) => {
+    if (event.payload.stream_id === streamId) onDelta(event.payload.delta);
+  });
+  try {
+    const { readAloud, ...rest } = options ?? {};
+    return await invoke<string>("backend_prompt_stream", {
+      task,
+      content,
+      ...rest,
+      streamId,
+      readAloud,
+    });
+  } finally {
+    unlisten();
+  }
+}
+
+/**
+ * Cleans text for reading (the editor's Clear action) with the configured
+ * `text_cleanup_mode`: local rules, the backend TTS task, or both.
+ */
+export async function cleanTextForReading(content: string): Promise<string> {
+  return invoke<string>("clean_text", { text: content });
 }
@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from '../../utils/appError';
 import './AboutTab.css';
 
 export function AboutTab() {
@@ -18,6 +19,24 @@ export function AboutTab() {
         </ul>
       </div>
 
+      <div className="about-section">
+        <h4>Bug Reports</h4>
+        <p>Copies the environment, voice providers, recent errors and the last crash as JSON.</p>
+        <button
+          type="button"
+          onClick={() => {
+            invoke('get_diagnostics_report')
+              .then((report) => navigator.clipboard.writeText(JSON.stringify(report, null, 2)))
+              .catch((e) => {
+                console.warn('get_diagnostics_report failed:', e);
+                alert(errorMessage(e, 'Could not copy the diagnostics report.'));
+              });
+          }}
+        >
+          Copy Diagnostics
+        </button>
+      </div>
+
       <div className="about-section">
         <h4>Features</h4>
         <ul>
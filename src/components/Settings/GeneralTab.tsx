@@ -1,6 +1,9 @@
-import type { Config, HotkeyStatus } from './Settings.types';
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import type { AudioOutput, Config, HotkeyStatus, OllamaStatus } from './Settings.types';
 import { VolumeRow } from '../../player/VolumeRow';
 import { clampVolume, DEFAULT_VOLUME } from '../../player/utils';
+import { errorMessage } from '../../utils/appError';
 
 const defaultBackendPlaceholder = 'https://api.insightreader.xyz';
 
@@ -18,8 +21,51 @@ export function GeneralTab({
 
   const effectiveVolume = config.ui_muted ? 0 : (config.ui_volume ?? DEFAULT_VOLUME);
 
+  const [audioOutputs, setAudioOutputs] = useState<AudioOutput[]>([]);
+  useEffect(() => {
+    invoke<AudioOutput[]>('list_audio_outputs')
+      .then(setAudioOutputs)
+      .catch((e) => console.warn('list_audio_outputs failed:', e));
+  }, []);
+  const selectedOutput = config.selected_audio_output ?? '';
+  const selectedMissing =
+    selectedOutput !== '' && !audioOutputs.some((o) => o.name === selectedOutput);
+
+  const llmProvider = config.llm_provider || 'backend';
+  const llmKeyName =
+    llmProvider === 'openai' ? 'openai_api_key' : llmProvider === 'anthropic' ? 'anthropic_api_key' : null;
+  const [llmKey, setLlmKey] = useState('');
+  const [llmKeyStored, setLlmKeyStored] = useState(false);
+  useEffect(() => {
+    setLlmKey('');
+    if (!llmKeyName) return;
+    invoke<boolean>('has_secret', { name: llmKeyName })
+      .then(setLlmKeyStored)
+      .catch((e) => console.warn('has_secret failed:', e));
+  }, [llmKeyName]);
+  const [ollamaStatus, setOllamaStatus] = useState<OllamaStatus | null>(null);
+  useEffect(() => {
+    if (llmProvider !== 'ollama') return;
+    invoke<OllamaStatus>('ollama_status')
+      .then(setOllamaStatus)
+      .catch((e) => console.warn('ollama_status failed:', e));
+  }, [llmProvider, config.llm_url]);
+  const saveLlmKey = () => {
+    if (!llmKeyName || !llmKey.trim()) return;
+    invoke('set_secret', { name: llmKeyName, value: llmKey })
+      .then(() => {
+        setLlmKey('');
+        setLlmKeyStored(true);
+      })
+      .catch((e) => console.warn('set_secret failed:', e));
+  };
+
   const modeHelp = hotkeyStatus?.mode === 'wayland-compositor'
     ? 'Wayland session detected: app-owned global hotkeys are not available. Configure your compositor shortcut to run `insight-reader action read-selected` instead.'
+    : hotkeyStatus?.mode === 'wayland-portal'
+      ? hotkeyStatus.native_active
+        ? `Global shortcuts are bound through the desktop portal: ${readShortcut} (read), ${pauseShortcut} (pause/resume). Change them in your desktop's shortcut settings.`
+        : 'Waiting for the desktop to confirm the global shortcuts.'
     : hotkeyStatus?.native_active
       ? `Global shortcuts are active in-app: ${readShortcut} (read), ${pauseShortcut} (pause/resume).`
       : 'Global shortcuts are currently not active in-app.';
@@ -40,6 +86,109 @@ export function GeneralTab({
         </p>
       </div>
 
+      <div className="setting-group">
+        <label>LLM Provider</label>
+        <select
+          value={llmProvider}
+          onChange={(e) => onChange({ llm_provider: e.target.value === 'backend' ? null : e.target.value })}
+        >
+          <option value="backend">ReadingService backend</option>
+          <option value="openai">OpenAI</option>
+          <option value="anthropic">Anthropic</option>
+          <option value="ollama">Ollama (local)</option>
+        </select>
+        {llmProvider === 'ollama' && ollamaStatus?.running && ollamaStatus.models.length > 0 && (
+          <select
+            value={config.llm_model ?? ''}
+            onChange={(e) => onChange({ llm_model: e.target.value || null })}
+          >
+            <option value="">Automatic</option>
+            {ollamaStatus.models.map((model) => (
+              <option key={model.name} value={model.name}>
+                {model.parameter_size ? `${model.name} (${model.parameter_size})` : model.name}
+              </option>
+            ))}
+          </select>
+        )}
+        {llmProvider === 'ollama' && ollamaStatus && (
+          <p className="setting-help">
+            {!ollamaStatus.running
+              ? `Ollama is not running at ${ollamaStatus.url}.`
+              : ollamaStatus.models.length === 0
+                ? `Ollama is running at ${ollamaStatus.url} but has no models. Run \`ollama pull llama3.2\`.`
+                : `Ollama is running at ${ollamaStatus.url}. Summaries and explanations stay on this machine.`}
+          </p>
+        )}
+        {llmProvider !== 'backend' && (
+          <>
+            {llmProvider !== 'ollama' && (
+              <input
+                type="text"
+                placeholder="Model (provider default)"
+                value={config.llm_model ?? ''}
+                onChange={(e) => onChange({ llm_model: e.target.value.trim() || null })}
+                className="setting-input"
+              />
+            )}
+            <input
+              type="url"
+              placeholder={llmProvider === 'ollama' ? 'http://localhost:11434' : 'Endpoint URL (provider default)'}
+              value={config.llm_url ?? ''}
+              onChange={(e) => onChange({ llm_url: e.target.value.trim() || null })}
+              className="setting-input"
+            />
+          </>
+        )}
+        {llmKeyName && (
+          <div className="setting-actions">
+            <input
+              type="password"
+              placeholder={llmKeyStored ? 'API key stored in the keychain' : 'API key'}
+              value={llmKey}
+              onChange={(e) => setLlmKey(e.target.value)}
+              className="setting-input"
+            />
+            <button type="button" onClick={saveLlmKey} disabled={!llmKey.trim()}>
+              Save Key
+            </button>
+          </div>
+        )}
+        <p className="setting-help">
+          Runs Summary, Explain and the editor tools on the backend above, or directly on OpenAI,
+          Anthropic or a local Ollama with your own key or endpoint.
+        </p>
+      </div>
+
+      <div className="setting-group">
+        <label>
+          <input
+            type="checkbox"
+            checked={config.backend_cache_enabled ?? true}
+            onChange={(e) => onChange({ backend_cache_enabled: e.target.checked })}
+          />
+          Cache Backend Responses
+        </label>
+        <p className="setting-help">
+          Reuses the answer when the same text is summarized or explained again, even offline.
+          Answers are kept for a week.
+        </p>
+      </div>
+
+      <div className="setting-group">
+        <label>Translate To</label>
+        <input
+          type="text"
+          placeholder="en-US"
+          value={config.translate_language ?? ''}
+          onChange={(e) => onChange({ translate_language: e.target.value.trim() || null })}
+          className="setting-input"
+        />
+        <p className="setting-help">
+          Language for "Translate Selected" (e.g. pt-BR, de). Leave empty for the app language.
+          The voice mapped to that language reads the translation.
+        </p>
+      </div>
+
       <div className="setting-group">
         <label>
           <input 
@@ -75,6 +224,81 @@ export function GeneralTab({
         />
       </div>
 
+      <div className="setting-group">
+        <label>Pause Between Sentences: {config.sentence_pause_ms ?? 0} ms</label>
+        <input
+          type="range"
+          min={0}
+          max={2000}
+          step={50}
+          value={config.sentence_pause_ms ?? 0}
+          onChange={(e) => onChange({ sentence_pause_ms: parseInt(e.target.value, 10) })}
+        />
+        <p className="setting-help">
+          Silence added after each sentence. Applies from the next text read.
+        </p>
+      </div>
+
+      <div className="setting-group">
+        <label>Skim Speed: {(config.skim_speed ?? 2).toFixed(2)}x</label>
+        <input
+          type="range"
+          min={0.25}
+          max={4}
+          step={0.25}
+          value={config.skim_speed ?? 2}
+          onChange={(e) => onChange({ skim_speed: parseFloat(e.target.value) })}
+        />
+        <p className="setting-help">
+          Speed used by the skim toggle (`insight-reader action skim`, or a hotkey for "skim").
+        </p>
+      </div>
+
+      <div className="setting-group">
+        <label>Audio Output</label>
+        <select
+          value={selectedOutput}
+          onChange={(e) => onChange({ selected_audio_output: e.target.value || null })}
+        >
+          <option value="">System default</option>
+          {audioOutputs.map((output) => (
+            <option key={output.name} value={output.name}>
+              {output.is_default ? `${output.name} (default)` : output.name}
+            </option>
+          ))}
+          {selectedMissing && (
+            <option value={selectedOutput}>{selectedOutput} (not connected)</option>
+          )}
+        </select>
+        <p className="setting-help">
+          When this device is unplugged, playback moves to the system default and comes back when it is reconnected.
+        </p>
+      </div>
+
+      <div className="setting-group">
+        <label>
+          <input
+            type="checkbox"
+            checked={config.duck_background_audio ?? false}
+            onChange={(e) => onChange({ duck_background_audio: e.target.checked })}
+          />
+          Lower Other Audio While Reading
+        </label>
+        {config.duck_background_audio && (
+          <input
+            type="range"
+            min={0}
+            max={100}
+            step={5}
+            value={config.duck_level ?? 30}
+            onChange={(e) => onChange({ duck_level: parseInt(e.target.value, 10) })}
+          />
+        )}
+        <p className="setting-help">
+          Other apps play at {config.duck_level ?? 30}% of their volume while a text is read, and go back when playback pauses or stops. On macOS this applies to Music and Spotify.
+        </p>
+      </div>
+
       <div className="setting-group">
         <label>Log Level</label>
         <select 
@@ -86,6 +310,32 @@ export function GeneralTab({
           <option value="info">Info</option>
           <option value="debug">Debug</option>
         </select>
+        <div className="setting-actions">
+          <button
+            type="button"
+            onClick={() => {
+              invoke('open_log_folder').catch((e) => {
+                console.warn('open_log_folder failed:', e);
+                alert(errorMessage(e, 'Could not open the log folder.'));
+              });
+            }}
+          >
+            Open Log Folder
+          </button>
+          <button
+            type="button"
+            onClick={() => {
+              invoke<string[]>('get_recent_logs', { lines: 500 })
+                .then((lines) => navigator.clipboard.writeText(lines.join('\n')))
+                .catch((e) => {
+                  console.warn('get_recent_logs failed:', e);
+                  alert(errorMessage(e, 'Could not copy the recent logs.'));
+                });
+            }}
+          >
+            Copy Recent Logs
+          </button>
+        </div>
       </div>
 
     </div>
@@ -14,6 +14,26 @@ export interface Config {
   editor_dark_mode?: boolean | null;
   summary_muted?: boolean | null;
   explain_mode?: "EXPLAIN1" | "EXPLAIN2" | null;
+  selected_audio_output?: string | null;
+  duck_background_audio?: boolean | null;
+  duck_level?: number | null;
+  sentence_pause_ms?: number | null;
+  skim_speed?: number | null;
+  translate_language?: string | null;
+  translate_url?: string | null;
+  backend_retry_attempts?: number | null;
+  backend_retry_delay_ms?: number | null;
+  backend_cache_enabled?: boolean | null;
+  backend_cache_ttl_hours?: number | null;
+  llm_provider?: string | null;
+  llm_model?: string | null;
+  llm_url?: string | null;
+}
+
+export interface AudioOutput {
+  name: string;
+  is_default: boolean;
+  selected: boolean;
 }
 
 export interface HotkeyStatus {
@@ -23,7 +43,20 @@ export interface HotkeyStatus {
   native_active: boolean;
   read_shortcut: string;
   pause_shortcut: string;
+  screenshot_shortcut: string;
   last_error: string | null;
 }
 
 export type Tab = 'general' | 'voices' | 'about';
+
+export interface OllamaModel {
+  name: string;
+  size: number;
+  parameter_size: string | null;
+}
+
+export interface OllamaStatus {
+  url: string;
+  running: boolean;
+  models: OllamaModel[];
+}
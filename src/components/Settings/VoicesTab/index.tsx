@@ -8,6 +8,13 @@ import { PollySection } from './PollySection';
 import { MicrosoftSection } from './MicrosoftSection';
 import '../VoicesTab.css';
 
+interface ProviderStatus {
+  current_provider: string | null;
+  active_voice: string | null;
+  last_error: { provider: string | null; message: string; at: number } | null;
+  providers: { provider: string; available: boolean; detail: string | null }[];
+}
+
 const MOST_USED_LOCALES = new Set([
   'en-US',
   'es-ES',
@@ -66,15 +73,25 @@ export function VoicesTab({ config, onChange }: { config: Config; onChange: (upd
   const [downloading, setDownloading] = useState<string | null>(null);
   const [downloadedVoices, setDownloadedVoices] = useState<string[]>([]);
   const [awsCredentialsConfigured, setAwsCredentialsConfigured] = useState<boolean | null>(null);
+  const [providerStatus, setProviderStatus] = useState<ProviderStatus | null>(null);
 
   const currentProvider = config?.voice_provider || 'microsoft';
+  const currentHealth = providerStatus?.providers.find((p) => p.provider === currentProvider);
+
+  useEffect(() => {
+    invoke<ProviderStatus>('tts_get_provider_status')
+      .then(setProviderStatus)
+      .catch((e) => console.error('Failed to check voice providers:', e));
+  }, [currentProvider]);
 
   useEffect(() => {
     const loadVoices = async () => {
       setLoadingPiper(true);
       setLoadingPolly(true);
       setLoadingMicrosoft(true);
-      const awsCheckPromise = invoke<boolean>('check_polly_credentials').catch(() => false);
+      const awsCheckPromise = invoke<{ credentials_configured: boolean }>('check_polly_credentials')
+        .then((diagnostics) => diagnostics.credentials_configured)
+        .catch(() => false);
 
       try {
         const piper = await invoke<any[]>('list_piper_voices');
@@ -256,6 +273,12 @@ export function VoicesTab({ config, onChange }: { config: Config; onChange: (upd
         currentProvider={currentProvider}
         onChange={(value) => onChange({ voice_provider: value })}
       />
+      {currentHealth && !currentHealth.available && (
+        <p className="setting-help">Unavailable: {currentHealth.detail}</p>
+      )}
+      {providerStatus?.last_error && (
+        <p className="setting-help">Last error: {providerStatus.last_error.message}</p>
+      )}
 
       {currentProvider === 'piper' && (
         <PiperSection
@@ -1,6 +1,7 @@
 import { invoke } from "@tauri-apps/api/core";
 import { PencilIcon, QuickReplayIcon } from "../components/icons";
 import { callBackendPrompt } from "../backendPrompt";
+import { errorMessage } from "../utils/appError";
 import { SummaryWithSpeaker } from "../components/SummaryWithSpeaker";
 
 interface ActionRowProps {
@@ -44,13 +45,7 @@ export function ActionRow({
         triggerRead: !summaryMuted,
       });
     } catch (err) {
-      const msg =
-        err instanceof Error
-          ? err.message
-          : typeof err === "string"
-            ? err
-            : "Summary request failed.";
-      onErrorsAdd(msg);
+      onErrorsAdd(errorMessage(err, "Summary request failed."));
     } finally {
       onSummarizingChange(false);
     }
@@ -4,7 +4,6 @@ import { PLAYBACK_SPEEDS } from "./utils";
 
 interface ControlsRowProps {
   isPlaying: boolean;
-  isPaused: boolean;
   /** True while TTS synthesis is starting (request in progress). */
   isPreparing: boolean;
   currentTimeMs: number;
@@ -20,7 +19,6 @@ interface ControlsRowProps {
 
 export function ControlsRow({
   isPlaying,
-  isPaused,
   isPreparing,
   currentTimeMs,
   atEnd,
@@ -52,14 +50,14 @@ export function ControlsRow({
       <button
         className="control-btn"
         onClick={onBackward}
-        disabled={isPaused || currentTimeMs < 5000}
+        disabled={currentTimeMs < 5000}
       >
         -5s
       </button>
       <button
         className="control-btn"
         onClick={onForward}
-        disabled={isPaused || atEnd}
+        disabled={atEnd}
       >
         +5s
       </button>
@@ -102,7 +102,6 @@ export function PlayerCard() {
         />
         <ControlsRow
           isPlaying={ttsState.isPlaying}
-          isPaused={ttsState.isPaused}
           isPreparing={ttsState.isPreparing}
           currentTimeMs={ttsState.currentTimeMs}
           atEnd={ttsState.atEnd}
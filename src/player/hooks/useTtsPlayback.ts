@@ -1,6 +1,15 @@
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
 
+/** Result of `tts_seek`: where playback landed, in content time. */
+interface SeekResult {
+  success: boolean;
+  at_start: boolean;
+  at_end: boolean;
+  position_ms: number;
+  duration_ms: number;
+}
+
 /** True while TTS synthesis is starting (any provider); UI can show "Preparing…". */
 export function useTtsPlayback(platform: string | null) {
   const [isPlaying, setIsPlaying] = useState(false);
@@ -82,17 +91,16 @@ export function useTtsPlayback(platform: string | null) {
   const handleSeek = async (offsetMs: number, disabled: boolean) => {
     if (disabled) return;
     try {
-      await invoke<[boolean, boolean, boolean]>("tts_seek", { offsetMs });
-      const [currentMs, totalMs] = await invoke<[number, number]>("tts_get_position");
-      setAtEnd(currentMs >= totalMs);
-      setCurrentTimeMs(currentMs);
+      const result = await invoke<SeekResult>("tts_seek", { offsetMs });
+      setAtEnd(result.at_end);
+      setCurrentTimeMs(result.position_ms);
     } catch (e) {
       console.warn("tts_seek failed:", e);
     }
   };
 
-  const handleBackward = () => handleSeek(-5000, isPaused || currentTimeMs < 5000);
-  const handleForward = () => handleSeek(5000, isPaused || atEnd);
+  const handleBackward = () => handleSeek(-5000, currentTimeMs < 5000);
+  const handleForward = () => handleSeek(5000, atEnd);
 
   return {
     isPlaying,
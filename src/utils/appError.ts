@@ -0,0 +1,48 @@
+/**
+ * Structured errors returned by the TTS, backend, voice and window commands
+ * (`AppError` in src-tauri/src/error.rs).
+ */
+
+export type AppErrorCode =
+  | "no_text"
+  | "provider_not_configured"
+  | "network"
+  | "offline"
+  | "rate_limited"
+  | "budget_exceeded"
+  | "not_found"
+  | "invalid_input"
+  | "internal";
+
+export interface AppError {
+  code: AppErrorCode;
+  message: string;
+  /** What the user can do about it, when there is something. */
+  hint: string | null;
+  /** Whether trying again later can succeed. */
+  retryable: boolean;
+  /** Set for `rate_limited` when the service said how long to wait. */
+  retry_after_secs?: number;
+}
+
+export function isAppError(err: unknown): err is AppError {
+  return (
+    typeof err === "object" &&
+    err !== null &&
+    typeof (err as AppError).code === "string" &&
+    typeof (err as AppError).message === "string"
+  );
+}
+
+/**
+ * Text to show for an error thrown by `invoke`: the message and hint of an
+ * `AppError`, a plain string error as is, or `fallback`.
+ */
+export function errorMessage(err: unknown, fallback: string): string {
+  if (isAppError(err)) {
+    return err.hint ? `${err.message} ${err.hint}` : err.message;
+  }
+  if (err instanceof Error) return err.message;
+  if (typeof err === "string") return err;
+  return fallback;
+}